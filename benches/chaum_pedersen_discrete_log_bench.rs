@@ -1,9 +1,11 @@
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
 use num_bigint::{BigUint, RandBigInt};
 use rand::rngs::OsRng;
+// The 1024-bit group is still benchmarked for comparison with the recommended groups.
+#[allow(deprecated)]
 use zk_pass::chaum_pedersen::constants::{
-    RFC5114_MODP_1024_160_BIT_PARAMS, RFC5114_MODP_2048_224_BIT_PARAMS,
-    RFC5114_MODP_2048_256_BIT_PARAMS,
+    RFC3526_MODP_2048_BIT_PARAMS, RFC5114_MODP_1024_160_BIT_PARAMS,
+    RFC5114_MODP_2048_224_BIT_PARAMS, RFC5114_MODP_2048_256_BIT_PARAMS,
 };
 use zk_pass::chaum_pedersen::discretelog::DiscreteLogChaumPedersen;
 use zk_pass::chaum_pedersen::test::test_execute_protocol;
@@ -61,10 +63,10 @@ fn discrete_log_verification_benchmark(c: &mut Criterion) {
 
 fn bench_protocol(params: &GroupParams<BigUint>, c: &mut Criterion, label: &str) {
     let mut rng = OsRng;
-    let x = rng.gen_biguint_below(&params.p);
+    let x = rng.gen_biguint_below(&params.q);
 
     c.bench_function(&format!("discrete_log_protocol_{}", label), |b| {
-        b.iter(|| test_execute_protocol::<DiscreteLogChaumPedersen>(&params, &x))
+        b.iter(|| test_execute_protocol::<DiscreteLogChaumPedersen>(params, &x))
     });
 }
 
@@ -83,12 +85,56 @@ fn bench_2048_256_bits(c: &mut Criterion) {
     bench_protocol(&params, c, "2048_256");
 }
 
+fn bench_rfc3526_2048_bits(c: &mut Criterion) {
+    let params = RFC3526_MODP_2048_BIT_PARAMS.to_owned();
+    bench_protocol(&params, c, "rfc3526_2048");
+}
+
+/// Benchmarks verifying an honest proof in each of the given groups. Secrets are drawn below
+/// `q` in every group, so the timings differ only by the cost of the group arithmetic.
+fn bench_verify(c: &mut Criterion, group_name: &str, groups: &[(&str, &GroupParams<BigUint>)]) {
+    let mut group = c.benchmark_group(group_name);
+    for (label, params) in groups {
+        let x = OsRng.gen_biguint_below(&params.q);
+        let (cp, k) = DiscreteLogChaumPedersen::commitment(params, &x);
+        let challenge = DiscreteLogChaumPedersen::challenge(params);
+        let s = DiscreteLogChaumPedersen::challenge_response(params, &k, &challenge, &x);
+        group.bench_function(*label, |b| {
+            b.iter(|| {
+                DiscreteLogChaumPedersen::verify(params, black_box(&s), black_box(&challenge), &cp)
+            })
+        });
+    }
+    group.finish();
+}
+
+// RFC 5114 groups have a 224 or 256-bit prime-order subgroup, while the RFC 3526 group is a
+// safe-prime group with a 2047-bit `q`, so responses and challenges are as large as `p`.
+// Comparing them at the same 2048-bit modulus quantifies the cost of the full-order subgroup.
+fn bench_verify_rfc5114_vs_rfc3526(c: &mut Criterion) {
+    bench_verify(
+        c,
+        "discrete_log_verify_rfc5114_small_q",
+        &[
+            ("2048_224", &RFC5114_MODP_2048_224_BIT_PARAMS),
+            ("2048_256", &RFC5114_MODP_2048_256_BIT_PARAMS),
+        ],
+    );
+    bench_verify(
+        c,
+        "discrete_log_verify_rfc3526_full_order_q",
+        &[("2048", &RFC3526_MODP_2048_BIT_PARAMS)],
+    );
+}
+
 criterion_group!(
     benches,
     discrete_log_commitment_benchmark,
     discrete_log_verification_benchmark,
     bench_1024_160_bits,
     bench_2048_224_bits,
-    bench_2048_256_bits
+    bench_2048_256_bits,
+    bench_rfc3526_2048_bits,
+    bench_verify_rfc5114_vs_rfc3526
 );
 criterion_main!(benches);
@@ -13,8 +13,8 @@ pub fn elliptic_curve_commitment_benchmark(c: &mut Criterion) {
         let g = RISTRETTO_BASEPOINT_POINT * Scalar::random(&mut rng);
         let h = RISTRETTO_BASEPOINT_POINT * Scalar::random(&mut rng);
         let params = GroupParams::<RistrettoPoint> {
-            g: g.clone(),
-            h: h.clone(),
+            g,
+            h,
             p: RISTRETTO_BASEPOINT_POINT,
             q: RISTRETTO_BASEPOINT_POINT,
         };
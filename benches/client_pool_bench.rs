@@ -0,0 +1,106 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use curve25519_dalek::{RistrettoPoint, Scalar};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::runtime::Runtime;
+use zk_pass::chaum_pedersen::constants::EC25519_GROUP_PARAMS;
+use zk_pass::chaum_pedersen::curve25519::Curve25519ChaumPedersen;
+use zk_pass::chaum_pedersen::transcript::derive_nonce_challenge;
+use zk_pass::chaum_pedersen::ChaumPedersen;
+use zk_pass::client::AuthClientPool;
+use zk_pass::conversion::ByteConvertible;
+use zk_pass::rand::RandomGenerator;
+use zk_pass::service::ZkAuth;
+
+#[path = "../tests/common/mod.rs"]
+mod common;
+
+const FLOWS: usize = 1000;
+
+/// Starts an in-process Curve25519 server on a free local port.
+fn start_server(rt: &Runtime) -> SocketAddr {
+    let auth = ZkAuth::<Curve25519ChaumPedersen, RistrettoPoint, Scalar>::new(
+        EC25519_GROUP_PARAMS.to_owned(),
+    );
+    rt.block_on(common::start_server(auth))
+}
+
+/// Runs a full challenge/response authentication flow for an already registered user.
+async fn authenticate(pool: &AuthClientPool, user: String, x: Scalar) {
+    let params = EC25519_GROUP_PARAMS.to_owned();
+    let ((_, _, r1, r2), k) = Curve25519ChaumPedersen::commitment(&params, &x);
+    let (c, auth_id, nonce, _) = pool
+        .create_authentication_challenge(
+            user,
+            RistrettoPoint::convert_to(&r1),
+            RistrettoPoint::convert_to(&r2),
+        )
+        .await
+        .unwrap();
+    let c = derive_nonce_challenge(&params, &nonce, &Scalar::convert_from(&c).unwrap());
+    let s = Curve25519ChaumPedersen::challenge_response(&params, &k, &c, &x);
+    pool.verify_authentication(auth_id, Scalar::convert_to(&s), nonce)
+        .await
+        .unwrap();
+}
+
+pub fn client_pool_benchmark(c: &mut Criterion) {
+    let rt = Runtime::new().unwrap();
+    let addr = start_server(&rt);
+    let dst = format!("http://{}", addr);
+    let params = EC25519_GROUP_PARAMS.to_owned();
+
+    // Registers the users once up front so each iteration only measures authentication.
+    let secrets: Vec<Scalar> = (0..FLOWS)
+        .map(|_| Scalar::generate_random().unwrap())
+        .collect();
+    rt.block_on(async {
+        // The listener is bound before the server starts, so the first connection succeeds.
+        let pool = AuthClientPool::connect_pooled(dst.clone(), 1)
+            .await
+            .unwrap();
+        for (i, x) in secrets.iter().enumerate() {
+            let (y1, y2) = (params.g * x, params.h * x);
+            pool.register(
+                format!("user{}", i),
+                RistrettoPoint::convert_to(&y1),
+                RistrettoPoint::convert_to(&y2),
+            )
+            .await
+            .unwrap();
+        }
+    });
+
+    let mut group = c.benchmark_group("client_pool_1000_concurrent_flows");
+    group.sample_size(10);
+    for size in [1, 4, 8, 16] {
+        let pool = Arc::new(
+            rt.block_on(AuthClientPool::connect_pooled(dst.clone(), size))
+                .unwrap(),
+        );
+        group.bench_with_input(BenchmarkId::from_parameter(size), &size, |b, _| {
+            b.iter(|| {
+                rt.block_on(async {
+                    let flows: Vec<_> = secrets
+                        .iter()
+                        .enumerate()
+                        .map(|(i, x)| {
+                            let pool = pool.clone();
+                            let x = *x;
+                            tokio::spawn(async move {
+                                authenticate(&pool, format!("user{}", i), x).await
+                            })
+                        })
+                        .collect();
+                    for flow in flows {
+                        flow.await.unwrap();
+                    }
+                })
+            });
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, client_pool_benchmark);
+criterion_main!(benches);
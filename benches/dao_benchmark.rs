@@ -0,0 +1,125 @@
+use criterion::measurement::WallTime;
+use criterion::{
+    criterion_group, criterion_main, BenchmarkGroup, BenchmarkId, Criterion, Throughput,
+};
+use curve25519_dalek::{RistrettoPoint, Scalar};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::thread;
+use zk_pass::rand::RandomGenerator;
+use zk_pass::repository::dao::UserDao;
+use zk_pass::repository::daoimpl::InMemoryUserDao;
+use zk_pass::repository::models::User;
+use zk_pass::repository::sqlite::SqliteUserDao;
+
+const CYCLES: usize = 1000;
+const THREAD_COUNTS: [usize; 2] = [1, 8];
+const NONCE: [u8; 16] = [7; 16];
+
+/// The values every benchmarked user and challenge is created with, generated once so that
+/// random generation is not measured.
+struct Fixture {
+    y1: RistrettoPoint,
+    y2: RistrettoPoint,
+    c: Scalar,
+}
+
+impl Fixture {
+    fn new() -> Self {
+        Self {
+            y1: RistrettoPoint::generate_random().unwrap(),
+            y2: RistrettoPoint::generate_random().unwrap(),
+            c: Scalar::generate_random().unwrap(),
+        }
+    }
+}
+
+/// A single benchmarked cycle against a shared store, given the index of the cycle.
+type Cycle<D> = fn(&Mutex<D>, &Fixture, usize);
+
+/// Creates, reads and deletes a user, as a registration followed by a login and the removal of
+/// the account.
+fn user_cycle<D: UserDao<RistrettoPoint, Scalar>>(dao: &Mutex<D>, fixture: &Fixture, i: usize) {
+    let username = format!("user{}", i);
+    let user = User {
+        username: username.clone(),
+        y1: fixture.y1,
+        y2: fixture.y2,
+        metadata: HashMap::new(),
+        namespace: String::new(),
+        params_version: 0,
+    };
+    dao.lock().unwrap().create(user).unwrap();
+    dao.lock().unwrap().read(&username).unwrap();
+    dao.lock().unwrap().delete(&username).unwrap();
+}
+
+/// Creates, reads and deletes an authentication challenge, as a challenge request followed by
+/// its answer.
+fn challenge_cycle<D: UserDao<RistrettoPoint, Scalar>>(
+    dao: &Mutex<D>, fixture: &Fixture, i: usize,
+) {
+    let username = format!("user{}", i);
+    let id = dao.lock().unwrap().create_auth_challenge(
+        &username,
+        &fixture.y1,
+        &fixture.y2,
+        &fixture.c,
+        &NONCE,
+        None,
+    );
+    dao.lock()
+        .unwrap()
+        .get_authentication_challenge(&id)
+        .unwrap();
+    dao.lock().unwrap().delete_auth_challenge(&id);
+}
+
+/// Runs [`CYCLES`] cycles split evenly across `threads` threads sharing the store.
+fn run_cycles<D: Send>(dao: &Mutex<D>, fixture: &Fixture, threads: usize, cycle: Cycle<D>) {
+    thread::scope(|scope| {
+        for first in 0..threads {
+            scope.spawn(move || {
+                for i in (first..CYCLES).step_by(threads) {
+                    cycle(dao, fixture, i);
+                }
+            });
+        }
+    });
+}
+
+/// Benchmarks a cycle against a store for every thread count.
+fn bench_backend<D: Send>(
+    group: &mut BenchmarkGroup<WallTime>, backend: &str, dao: D, cycle: Cycle<D>,
+) {
+    let dao = Mutex::new(dao);
+    let fixture = Fixture::new();
+    for threads in THREAD_COUNTS {
+        group.bench_with_input(
+            BenchmarkId::new(backend, format!("{}_threads", threads)),
+            &threads,
+            |b, &threads| b.iter(|| run_cycles(&dao, &fixture, threads, cycle)),
+        );
+    }
+}
+
+/// Benchmarks a cycle against the in-memory and the SQLite stores. Throughput is reported in
+/// cycles per second.
+fn bench_cycle(
+    c: &mut Criterion, name: &str, in_memory: Cycle<InMemoryUserDao<RistrettoPoint, Scalar>>,
+    sqlite: Cycle<SqliteUserDao<RistrettoPoint, Scalar>>,
+) {
+    let mut group = c.benchmark_group(name);
+    group.throughput(Throughput::Elements(CYCLES as u64));
+    bench_backend(&mut group, "in_memory", InMemoryUserDao::new(), in_memory);
+    bench_backend(&mut group, "sqlite", SqliteUserDao::open(":memory:").unwrap(), sqlite);
+    group.finish();
+}
+
+pub fn dao_benchmark(c: &mut Criterion) {
+    bench_cycle(c, "dao_user_cycles", user_cycle, user_cycle);
+    bench_cycle(c, "dao_challenge_cycles", challenge_cycle, challenge_cycle);
+}
+
+criterion_group!(benches, dao_benchmark);
+criterion_main!(benches);
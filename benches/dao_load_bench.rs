@@ -0,0 +1,81 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use curve25519_dalek::{RistrettoPoint, Scalar};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::runtime::Runtime;
+use tokio::sync::Mutex;
+use zk_pass::rand::RandomGenerator;
+use zk_pass::repository::dao::AsyncUserDao;
+use zk_pass::repository::daoimpl::{AsyncInMemoryUserDao, InMemoryUserDao};
+use zk_pass::repository::models::User;
+
+const USERS: usize = 1000;
+const REQUESTS: usize = 10_000;
+
+/// Populates a store with users, each with a pending authentication challenge.
+async fn populate<D: AsyncUserDao<RistrettoPoint, Scalar>>(dao: &D) -> Vec<String> {
+    let mut auth_ids = Vec::with_capacity(USERS);
+    for i in 0..USERS {
+        let username = format!("user{}", i);
+        dao.create(User {
+            username: username.clone(),
+            y1: RistrettoPoint::generate_random().unwrap(),
+            y2: RistrettoPoint::generate_random().unwrap(),
+            metadata: HashMap::new(),
+            namespace: String::new(),
+            params_version: 0,
+        })
+        .await;
+        let r = RistrettoPoint::generate_random().unwrap();
+        let c = Scalar::generate_random().unwrap();
+        auth_ids.push(
+            dao.create_auth_challenge(&username, &r, &r, &c, &[], None)
+                .await,
+        );
+    }
+    auth_ids
+}
+
+/// Issues concurrent lookups mirroring the read path of `verify_authentication`.
+async fn load<D: AsyncUserDao<RistrettoPoint, Scalar> + 'static>(
+    dao: Arc<D>, auth_ids: Arc<Vec<String>>,
+) {
+    let tasks: Vec<_> = (0..REQUESTS)
+        .map(|i| {
+            let dao = dao.clone();
+            let auth_ids = auth_ids.clone();
+            tokio::spawn(async move {
+                let challenge = dao
+                    .get_authentication_challenge(&auth_ids[i % USERS])
+                    .await
+                    .unwrap();
+                dao.read(&challenge.user).await.unwrap();
+            })
+        })
+        .collect();
+    for task in tasks {
+        task.await.unwrap();
+    }
+}
+
+pub fn dao_load_benchmark(c: &mut Criterion) {
+    let rt = Runtime::new().unwrap();
+    let mut group = c.benchmark_group("dao_10000_concurrent_lookups");
+
+    let rw_dao = Arc::new(AsyncInMemoryUserDao::<RistrettoPoint, Scalar>::new());
+    let rw_ids = Arc::new(rt.block_on(populate(rw_dao.as_ref())));
+    group.bench_function(BenchmarkId::from_parameter("async_rwlock"), |b| {
+        b.iter(|| rt.block_on(load(rw_dao.clone(), rw_ids.clone())));
+    });
+
+    let mutex_dao = Arc::new(Mutex::new(InMemoryUserDao::<RistrettoPoint, Scalar>::new()));
+    let mutex_ids = Arc::new(rt.block_on(populate(mutex_dao.as_ref())));
+    group.bench_function(BenchmarkId::from_parameter("sync_mutex"), |b| {
+        b.iter(|| rt.block_on(load(mutex_dao.clone(), mutex_ids.clone())));
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, dao_load_benchmark);
+criterion_main!(benches);
@@ -0,0 +1,151 @@
+use criterion::measurement::WallTime;
+use criterion::{
+    criterion_group, criterion_main, BenchmarkGroup, BenchmarkId, Criterion, Throughput,
+};
+use curve25519_dalek::{RistrettoPoint, Scalar};
+use num_bigint::BigUint;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::runtime::Runtime;
+use zk_pass::chaum_pedersen::constants::{
+    EC25519_GROUP_PARAMS, PALLAS_GROUP_PARAMS, RFC5114_MODP_2048_256_BIT_PARAMS,
+};
+use zk_pass::chaum_pedersen::curve25519::Curve25519ChaumPedersen;
+use zk_pass::chaum_pedersen::discretelog::DiscreteLogChaumPedersen;
+use zk_pass::chaum_pedersen::pallas::PallasCurveChaumPedersen;
+use zk_pass::chaum_pedersen::transcript::TranscriptChallenge;
+use zk_pass::chaum_pedersen::{ChaumPedersen, GroupParams, ValidateGroupParams};
+use zk_pass::client::execute_protocol;
+use zk_pass::conversion::ByteConvertible;
+use zk_pass::rand::RandomGenerator;
+use zk_pass::repository::dao::AsyncUserDao;
+use zk_pass::repository::daoimpl::AsyncInMemoryUserDao;
+use zk_pass::service::ZkAuth;
+
+#[path = "../tests/common/mod.rs"]
+mod common;
+
+const USER: &str = "bench";
+
+/// Benchmarks the full register, challenge and verify flow against an in-process server.
+///
+/// The server's store is shared with the benchmark so the registered user can be deleted after
+/// each flow, outside the timed region; otherwise the next registration would be rejected.
+fn bench_protocol<C, T, S>(
+    group: &mut BenchmarkGroup<WallTime>, rt: &Runtime, name: &str, params: GroupParams<T>,
+) where
+    C: ChaumPedersen<
+            Response = S,
+            CommitmentRandom = S,
+            Challenge = S,
+            Secret = S,
+            GroupParameters = GroupParams<T>,
+            CommitParameters = (T, T, T, T),
+        > + Send
+        + Sync
+        + 'static,
+    T: ByteConvertible<T>
+        + RandomGenerator<T>
+        + ValidateGroupParams
+        + Clone
+        + Send
+        + Sync
+        + 'static,
+    S: ByteConvertible<S>
+        + RandomGenerator<S>
+        + TranscriptChallenge<GroupParams<T>>
+        + Clone
+        + PartialEq
+        + Send
+        + Sync
+        + 'static,
+{
+    let dao = Arc::new(AsyncInMemoryUserDao::<T, S>::new());
+    let addr = start_server::<C, T, S>(rt, params.clone(), dao.clone());
+    let client = rt.block_on(common::connect(addr));
+    let x = S::generate_random().unwrap();
+
+    group.bench_function(BenchmarkId::from_parameter(name), |b| {
+        b.to_async(rt).iter_custom(|iters| {
+            let (params, x, dao) = (&params, &x, &dao);
+            let mut client = client.clone();
+            async move {
+                let mut elapsed = Duration::ZERO;
+                for _ in 0..iters {
+                    let start = Instant::now();
+                    execute_protocol::<C, T, S>(params, x, USER, &mut client)
+                        .await
+                        .unwrap();
+                    elapsed += start.elapsed();
+                    dao.delete(USER).await.unwrap();
+                }
+                elapsed
+            }
+        });
+    });
+}
+
+/// Starts an in-process server backed by `dao` on a free local port.
+fn start_server<C, T, S>(
+    rt: &Runtime, params: GroupParams<T>, dao: Arc<AsyncInMemoryUserDao<T, S>>,
+) -> SocketAddr
+where
+    C: ChaumPedersen<
+            Response = S,
+            CommitmentRandom = S,
+            Challenge = S,
+            Secret = S,
+            GroupParameters = GroupParams<T>,
+            CommitParameters = (T, T, T, T),
+        > + Send
+        + Sync
+        + 'static,
+    T: ByteConvertible<T> + ValidateGroupParams + Clone + Send + Sync + 'static,
+    S: ByteConvertible<S>
+        + TranscriptChallenge<GroupParams<T>>
+        + Clone
+        + PartialEq
+        + Send
+        + Sync
+        + 'static,
+{
+    let auth = ZkAuth::<C, T, S, _>::with_dao(params, dao);
+    rt.block_on(common::start_server(auth))
+}
+
+#[allow(deprecated)]
+pub fn grpc_roundtrip_benchmark(c: &mut Criterion) {
+    let rt = Runtime::new().unwrap();
+    let mut group = c.benchmark_group("grpc_auth_roundtrip");
+    group.throughput(Throughput::Elements(1));
+
+    bench_protocol::<DiscreteLogChaumPedersen, BigUint, BigUint>(
+        &mut group,
+        &rt,
+        "rfc5114_modp_1024_160",
+        zk_pass::chaum_pedersen::constants::RFC5114_MODP_1024_160_BIT_PARAMS.to_owned(),
+    );
+    bench_protocol::<DiscreteLogChaumPedersen, BigUint, BigUint>(
+        &mut group,
+        &rt,
+        "rfc5114_modp_2048_256",
+        RFC5114_MODP_2048_256_BIT_PARAMS.to_owned(),
+    );
+    bench_protocol::<Curve25519ChaumPedersen, RistrettoPoint, Scalar>(
+        &mut group,
+        &rt,
+        "ec25519",
+        EC25519_GROUP_PARAMS.to_owned(),
+    );
+    bench_protocol::<PallasCurveChaumPedersen, _, _>(
+        &mut group,
+        &rt,
+        "pallas",
+        PALLAS_GROUP_PARAMS.to_owned(),
+    );
+    group.finish();
+}
+
+criterion_group!(benches, grpc_roundtrip_benchmark);
+criterion_main!(benches);
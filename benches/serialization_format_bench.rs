@@ -0,0 +1,63 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use num_bigint::BigUint;
+use std::time::{Duration, Instant};
+use zk_pass::chaum_pedersen::constants::RFC5114_MODP_2048_256_BIT_PARAMS;
+use zk_pass::chaum_pedersen::GroupParams;
+
+const ROUNDS: u32 = 10_000;
+
+/// Measures the time taken by `ROUNDS` runs of `f`.
+fn time(mut f: impl FnMut()) -> Duration {
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        f();
+    }
+    start.elapsed()
+}
+
+/// Compares the size and speed of the JSON and MessagePack encodings of the RFC 5114 2048-bit
+/// group parameters with a 256-bit subgroup.
+pub fn serialization_format_benchmark(c: &mut Criterion) {
+    let params = RFC5114_MODP_2048_256_BIT_PARAMS.to_owned();
+    let json = serde_json::to_vec(&params).unwrap();
+    let msgpack = params.to_msgpack().unwrap();
+
+    let json_time = time(|| {
+        let bytes = serde_json::to_vec(black_box(&params)).unwrap();
+        black_box(serde_json::from_slice::<GroupParams<BigUint>>(&bytes).unwrap());
+    });
+    let msgpack_time = time(|| {
+        let bytes = black_box(&params).to_msgpack().unwrap();
+        black_box(GroupParams::<BigUint>::from_msgpack(&bytes).unwrap());
+    });
+    println!(
+        "GroupParams<BigUint> size: JSON {} bytes, MessagePack {} bytes, ratio {:.2}",
+        json.len(),
+        msgpack.len(),
+        msgpack.len() as f64 / json.len() as f64
+    );
+    println!(
+        "GroupParams<BigUint> encode + decode: JSON {:?}, MessagePack {:?}, ratio {:.2}",
+        json_time / ROUNDS,
+        msgpack_time / ROUNDS,
+        msgpack_time.as_secs_f64() / json_time.as_secs_f64()
+    );
+
+    let mut group = c.benchmark_group("group_params_2048_256");
+    group.bench_function(BenchmarkId::new("encode", "json"), |b| {
+        b.iter(|| serde_json::to_vec(black_box(&params)).unwrap())
+    });
+    group.bench_function(BenchmarkId::new("encode", "msgpack"), |b| {
+        b.iter(|| black_box(&params).to_msgpack().unwrap())
+    });
+    group.bench_function(BenchmarkId::new("decode", "json"), |b| {
+        b.iter(|| serde_json::from_slice::<GroupParams<BigUint>>(black_box(&json)).unwrap())
+    });
+    group.bench_function(BenchmarkId::new("decode", "msgpack"), |b| {
+        b.iter(|| GroupParams::<BigUint>::from_msgpack(black_box(&msgpack)).unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, serialization_format_benchmark);
+criterion_main!(benches);
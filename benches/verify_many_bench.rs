@@ -0,0 +1,41 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use num_bigint::RandBigInt;
+use rand::rngs::OsRng;
+use zk_pass::chaum_pedersen::constants::RFC5114_MODP_2048_256_BIT_PARAMS;
+use zk_pass::chaum_pedersen::discretelog::DiscreteLogChaumPedersen;
+use zk_pass::chaum_pedersen::ProofBundle;
+
+/// The number of proofs verified per iteration, as in a bulk import.
+const PROOFS: usize = 1000;
+
+fn verify_many_benchmark(c: &mut Criterion) {
+    let params = RFC5114_MODP_2048_256_BIT_PARAMS.to_owned();
+    let mut rng = OsRng;
+    let proofs: Vec<_> = (0..PROOFS)
+        .map(|i| {
+            let x = rng.gen_biguint_below(&params.q);
+            ProofBundle::<DiscreteLogChaumPedersen>::prove(&params, format!("user{}", i), 0, &x)
+        })
+        .collect();
+
+    let mut group = c.benchmark_group("verify_1000_proofs");
+    group.sample_size(10);
+    group.bench_function("serial", |b| {
+        b.iter(|| {
+            proofs
+                .iter()
+                .map(|proof| {
+                    let (y1, y2, _, _) = &proof.commitment;
+                    proof.verify(&params, y1, y2)
+                })
+                .collect::<Vec<bool>>()
+        })
+    });
+    group.bench_function("parallel", |b| {
+        b.iter(|| DiscreteLogChaumPedersen::verify_many(&params, &proofs))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, verify_many_benchmark);
+criterion_main!(benches);
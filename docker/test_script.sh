@@ -40,5 +40,6 @@ run_test "discrete_log" "ec25519" "rfc5114_modp_1024_160"
 run_test "elliptic_curve" "ec25519" "rfc5114_modp_1024_160"
 run_test "elliptic_curve" "pallas" "rfc5114_modp_1024_160"
 run_test "elliptic_curve" "vesta" "rfc5114_modp_1024_160"
+run_test "elliptic_curve" "jubjub" "rfc5114_modp_1024_160"
 
 echo "All tests passed successfully!"
@@ -0,0 +1,52 @@
+//! Derives a secret from a password with `hash_or_randomize_secret` and proves knowledge of it,
+//! showing that the same password always yields the same registration.
+//!
+//! Run with `cargo run --example hash_commitment`.
+
+use curve25519_dalek::{RistrettoPoint, Scalar};
+use zk_pass::chaum_pedersen::constants::EC25519_GROUP_PARAMS;
+use zk_pass::chaum_pedersen::curve25519::Curve25519ChaumPedersen;
+use zk_pass::chaum_pedersen::keygen::DeterministicKeygen;
+use zk_pass::chaum_pedersen::ChaumPedersen;
+use zk_pass::cmdutil::{hash_or_randomize_secret, HashAlgorithm};
+use zk_pass::conversion::ByteConvertible;
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let params = EC25519_GROUP_PARAMS.to_owned();
+    let password = String::from("correct horse battery staple");
+    let derive = |password: &String, domain| {
+        hash_or_randomize_secret::<Curve25519ChaumPedersen>(
+            &params,
+            Some(password),
+            Some(domain),
+            HashAlgorithm::Sha512,
+        )
+    };
+
+    // The password is hashed within a domain and reduced into a scalar.
+    let x = derive(&password, "ec25519");
+    println!("🔑 secret: {}", hex::encode(Scalar::convert_to(&x)));
+
+    // The registration holds only the public values derived from the secret.
+    let (y1, y2) = Curve25519ChaumPedersen::public_values(&params, &x);
+    println!("📝 y1: {}", hex::encode(RistrettoPoint::convert_to(&y1)));
+    println!("📝 y2: {}", hex::encode(RistrettoPoint::convert_to(&y2)));
+
+    // Hashing the same password again yields the same secret, so it proves the registration.
+    let again = derive(&password, "ec25519");
+    let ((_, _, r1, r2), k) = Curve25519ChaumPedersen::commitment(&params, &again);
+    let c = Curve25519ChaumPedersen::challenge(&params);
+    let s = Curve25519ChaumPedersen::challenge_response(&params, &k, &c, &again);
+    let verified = Curve25519ChaumPedersen::verify(&params, &s, &c, &(y1, y2, r1, r2));
+    println!("✅ same password verified: {}", verified);
+
+    // The same password in another domain yields an unrelated secret.
+    let other = derive(&password, "other");
+    let separated = other != x;
+    println!("🧱 domains separated: {}", separated);
+
+    if !verified || !separated {
+        return Err("the secret derivation did not behave as expected".into());
+    }
+    Ok(())
+}
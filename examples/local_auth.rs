@@ -0,0 +1,35 @@
+//! Authenticates a user against an in-process verifier, without any network I/O.
+//!
+//! Run with `cargo run --example local_auth`.
+
+use curve25519_dalek::{RistrettoPoint, Scalar};
+use zk_pass::chaum_pedersen::constants::EC25519_GROUP_PARAMS;
+use zk_pass::chaum_pedersen::curve25519::Curve25519ChaumPedersen;
+use zk_pass::chaum_pedersen::local::LocalVerifier;
+use zk_pass::rand::RandomGenerator;
+use zk_pass::repository::daoimpl::AsyncInMemoryUserDao;
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let params = EC25519_GROUP_PARAMS.to_owned();
+
+    // Starts the verifier; it runs until every prover has been dropped.
+    let (verifier, prover) = LocalVerifier::<Curve25519ChaumPedersen>::new(16);
+    let handle = verifier.run(params, AsyncInMemoryUserDao::<RistrettoPoint, Scalar>::new());
+
+    // The first proof registers the user; later proofs authenticate against it.
+    let x = Scalar::generate_random()?;
+    for _ in 0..2 {
+        let session_id = prover.prove(&params, "alice", &x).await?;
+        println!("✅ authenticated alice, session ID: {}", session_id);
+    }
+
+    let wrong = Scalar::generate_random()?;
+    if let Err(e) = prover.prove(&params, "alice", &wrong).await {
+        println!("❌ wrong secret rejected: {}", e);
+    }
+
+    drop(prover);
+    handle.await?;
+    Ok(())
+}
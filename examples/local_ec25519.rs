@@ -0,0 +1,40 @@
+//! Runs the Chaum-Pedersen protocol over Ristretto25519 end to end with the library API alone,
+//! without any network I/O.
+//!
+//! Run with `cargo run --example local_ec25519`.
+
+use curve25519_dalek::{RistrettoPoint, Scalar};
+use zk_pass::chaum_pedersen::constants::EC25519_GROUP_PARAMS;
+use zk_pass::chaum_pedersen::curve25519::Curve25519ChaumPedersen;
+use zk_pass::chaum_pedersen::ChaumPedersen;
+use zk_pass::conversion::ByteConvertible;
+use zk_pass::rand::RandomGenerator;
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let params = EC25519_GROUP_PARAMS.to_owned();
+    params.validate()?;
+    println!("🟢 g: {}", hex::encode(RistrettoPoint::convert_to(&params.g)));
+    println!("🟢 h: {}", hex::encode(RistrettoPoint::convert_to(&params.h)));
+
+    // The prover commits to its secret `x`, publishing (y1, y2) and the random commitment (r1, r2).
+    let x = Scalar::generate_random()?;
+    let (cp, k) = Curve25519ChaumPedersen::commitment(&params, &x);
+
+    // The verifier challenges the commitment, and the prover answers with `s = k + c * x`.
+    let c = Curve25519ChaumPedersen::challenge(&params);
+    let s = Curve25519ChaumPedersen::challenge_response(&params, &k, &c, &x);
+
+    let verified = Curve25519ChaumPedersen::verify(&params, &s, &c, &cp);
+    println!("✅ proof verified: {}", verified);
+
+    // A response computed from the wrong secret does not verify.
+    let wrong = Scalar::generate_random()?;
+    let forged = Curve25519ChaumPedersen::challenge_response(&params, &k, &c, &wrong);
+    let rejected = !Curve25519ChaumPedersen::verify(&params, &forged, &c, &cp);
+    println!("❌ wrong secret rejected: {}", rejected);
+
+    if !verified || !rejected {
+        return Err("the protocol did not behave as expected".into());
+    }
+    Ok(())
+}
@@ -1,22 +1,24 @@
-use curve25519_dalek::RistrettoPoint;
-use num_bigint::BigUint;
-use sha2::{Digest, Sha512};
-use std::str::FromStr;
+use serde::{Deserialize, Serialize};
 use structopt::StructOpt;
 use strum::VariantNames;
-use zk_pass::conversion::ByteConvertible;
+use zk_pass::conversion::{decode_proof, ByteConvertible};
 
-use pasta_curves::pallas::Point as PallasPoint;
-use pasta_curves::vesta::Point as VestaPoint;
 use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
 use zk_pass::chaum_pedersen::{
-    curve25519::Curve25519ChaumPedersen, discretelog::DiscreteLogChaumPedersen,
-    pallas::PallasCurveChaumPedersen, vesta::VestaCurveChaumPedersen, GroupParams,
+    brainpool::BrainpoolChaumPedersen, curve25519::Curve25519ChaumPedersen,
+    discretelog::DiscreteLogChaumPedersen, jubjub::JubjubChaumPedersen,
+    pallas::PallasCurveChaumPedersen, vesta::VestaCurveChaumPedersen, ChaumPedersen, GroupParams,
+    ValidateGroupParams,
 };
-use zk_pass::client::execute_protocol;
+use zk_pass::client::zkp_auth::GetGroupParamsResponse;
 use zk_pass::client::AuthClientLib;
-use zk_pass::cmdutil::{ChaumPedersenType, EllipticCurveType, RfcModpType};
-use zk_pass::rand::RandomGenerator;
+use zk_pass::client::{decode_group_params, decode_hex_group_params, execute_protocol, AuthResult};
+use zk_pass::cmdutil::{
+    hash_or_randomize_secret, parse_enum_or_help, ChaumPedersenType, CurveParams,
+    EllipticCurveType, HashAlgorithm, RfcModpType,
+};
 
 /// Command-line options structure for the ZKPass client.
 #[derive(Debug, StructOpt)]
@@ -39,66 +41,70 @@ struct Opt {
     user: String,
 
     /// Type of RFC log group to use for the Discrete Log implementation of Chaum-Pedersen.
-    #[structopt(short, long, possible_values = RfcModpType::VARIANTS, default_value = "rfc5114_modp_1024_160", required_if("stereotype", "discrete_log"))]
+    #[structopt(short, long, possible_values = RfcModpType::VARIANTS, parse(try_from_str = parse_enum_or_help), default_value = "rfc5114_modp_1024_160", required_if("stereotype", "discrete_log"))]
     modp: RfcModpType,
 
     /// Underlying type of the Chaum-Pedersen protocol to use.
-    #[structopt(short, long, possible_values = ChaumPedersenType::VARIANTS, default_value = "discrete_log")]
+    #[structopt(short, long, possible_values = ChaumPedersenType::VARIANTS, parse(try_from_str = parse_enum_or_help), default_value = "discrete_log")]
     r#type: ChaumPedersenType,
 
     /// Elliptic curve type for the Elliptic Curve implementation of Chaum-Pedersen.
-    #[structopt(short, long, possible_values = EllipticCurveType::VARIANTS, default_value = "ec25519", required_if("stereotype", "elliptic_curve"))]
+    #[structopt(short, long, possible_values = EllipticCurveType::VARIANTS, parse(try_from_str = parse_enum_or_help), default_value = "ec25519", required_if("stereotype", "elliptic_curve"))]
     curve: EllipticCurveType,
+
+    /// Hex-encoded `y1` of the server's secret. The server must prove its identity before registering.
+    #[structopt(long, requires = "server-y2")]
+    server_y1: Option<String>,
+
+    /// Hex-encoded `y2` of the server's secret. The server must prove its identity before registering.
+    #[structopt(long, requires = "server-y1")]
+    server_y2: Option<String>,
+
+    /// Negotiates the protocol type with the server, overriding `--type`, `--modp` and `--curve`.
+    #[structopt(long)]
+    auto_negotiate: bool,
+
+    /// Fetches the protocol type and group parameters from the server and uses them instead of
+    /// `--type`, `--modp` and `--curve`.
+    #[structopt(long, conflicts_with = "auto-negotiate")]
+    fetch_params: bool,
+
+    /// Deployment identifier, e.g. the server's hostname, scoping the secret derived from the passcode.
+    /// The secret is always scoped to the protocol type.
+    #[structopt(long)]
+    secret_domain: Option<String>,
+
+    /// Derives the secret from the passcode alone, without domain separation, as older clients did.
+    /// Only needed to authenticate users registered by such clients.
+    #[structopt(long, conflicts_with = "secret-domain")]
+    legacy_secret_hash: bool,
+
+    /// Hash function deriving the secret from the passcode. Must match the one used at registration.
+    #[structopt(long, possible_values = HashAlgorithm::VARIANTS, parse(try_from_str = parse_enum_or_help), default_value = "sha512")]
+    hash_algorithm: HashAlgorithm,
+
+    /// API key attached to the registration, for servers started with `--register-api-key`.
+    /// May also be set through the ZK_PASS_API_KEY environment variable.
+    #[structopt(long, env = "ZK_PASS_API_KEY", hide_env_values = true)]
+    api_key: Option<String>,
+
+    /// Writes the proof transcript `(y1, y2, r1, r2, c, s)` as hex-encoded JSON to this file
+    /// after authenticating, e.g. to compare a working and a failing run.
+    #[structopt(long, parse(from_os_str))]
+    dump_transcript: Option<PathBuf>,
 }
 
-/// Hashes the provided secret string or generates a random value.
-///
-/// This function takes an optional secret string and performs one of two actions:
-/// - If a secret string is provided, it hashes the string using SHA-512 and then
-///   converts the hash to the specified type `T`.
-/// - If no secret is provided (i.e., `None`), it generates a random value of type `T`.
-///
-/// # Type Parameters
-/// * `T`: The target type for the hashed or random value. The type must implement
-///   `ByteConvertible` to allow conversion from bytes to `T`, and `RandomGenerator`
-///   to allow generation of a random value of type `T`.
-///
-/// # Parameters
-/// * `secret`: An `Option<&String>` representing the secret string to hash.
-///   - `Some(&String)`: The string to hash.
-///   - `None`: Indicates that a random value should be generated instead of hashing.
-///
-/// # Returns
-/// Returns a value of type `T`. The value is either:
-/// - The hash of the provided secret string, converted to type `T`, or
-/// - A randomly generated value of type `T`, if no secret string was provided.
-///
-/// # Panics
-/// This function may panic in the following cases:
-/// - If conversion from the hash bytes to the target type `T` fails.
-/// - If random value generation for the target type `T` fails.
-///
-/// # Examples
-/// ```
-/// let secret = Some(String::from("my_secret"));
-/// let hashed_secret: [u8; 64] = hash_or_randomize_secret(secret.as_ref());
-/// // hashed_secret is now the SHA-512 hash of "my_secret", as an array of bytes.
-///
-/// let random_secret: [u8; 64] = hash_or_randomize_secret(None);
-/// // random_secret is now a randomly generated array of bytes.
-/// ```
-fn hash_or_randomize_secret<T: ByteConvertible<T> + RandomGenerator<T>>(
-    secret: Option<&String>,
-) -> T {
-    match secret {
-        Some(s) => {
-            let mut hasher = Sha512::new();
-            hasher.update(s);
-            let result = hasher.finalize();
-            T::convert_from(&result).expect("Failed to convert hash to target type")
-        }
-        None => T::generate_random().expect("Failed to generate random value"),
-    }
+/// The proof transcript written by `--dump-transcript`, with every value hex-encoded.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct TranscriptDump {
+    y1: String,
+    y2: String,
+    r1: String,
+    r2: String,
+    c: String,
+    s: String,
+    /// Whether the transcript verifies under the group parameters of the run.
+    verified: bool,
 }
 
 /// Main entry point for the ZKPass client.
@@ -141,6 +147,14 @@ fn hash_or_randomize_secret<T: ByteConvertible<T> + RandomGenerator<T>>(
 /// - `--modp` or `-m`: Sets the type of the RFC log group to use. Required if `--type` is "discrete_log".
 /// - `--type` or `-t`: Sets the type of the Chaum-Pedersen protocol to use. Possible values: "discrete_log", "elliptic_curve".
 /// - `--curve` or `-c`: Sets the elliptic curve type. Required if `--type` is "elliptic_curve".
+/// - `--server-y1` and `--server-y2`: The server's hex-encoded public values. When set, the server must prove its identity before the client registers.
+/// - `--auto-negotiate`: Agrees on the protocol type with the server before registering, preferring the configured one.
+/// - `--fetch-params`: Fetches the protocol type and group parameters from the server instead of configuring them locally.
+/// - `--secret-domain`: Scopes the secret derived from the passcode to a deployment, in addition to the protocol type.
+/// - `--legacy-secret-hash`: Derives the secret from the passcode without domain separation, as older clients did.
+/// - `--hash-algorithm`: Sets the hash function deriving the secret from the passcode. Possible values: "sha256", "sha512", "blake2b".
+/// - `--api-key`: Attaches an API key to the registration, for servers gating registration.
+/// - `--dump-transcript`: Writes the proof transcript as hex-encoded JSON to the given file after authenticating.
 ///
 /// ### Example Usage
 ///
@@ -166,81 +180,484 @@ async fn main() -> Result<(), Box<dyn Error>> {
     }
     println!("      🔑 user: {}", opt.user);
 
+    let result = authenticate(opt).await?;
+
+    // Displays the session ID.
+    println!("🔑 Authentication successful! 🔑");
+    println!("Session ID: {}", result.session_id);
+    Ok(())
+}
+
+/// Connects to the ZKPass server, registers the user and authenticates them with the configured
+/// or server-provided protocol.
+async fn authenticate(mut opt: Opt) -> Result<AuthResult, Box<dyn Error>> {
     // Establishes a connection to the ZKPass server.
     let mut client = AuthClientLib::connect(format!("http://{}:{}", opt.host, opt.port)).await?;
-    execute_selected_protocol(opt, &mut client).await?;
+    if let (Some(y1), Some(y2)) = (&opt.server_y1, &opt.server_y2) {
+        println!("      🤝 verifying server identity");
+        client = client.with_server_verification(hex::decode(y1)?, hex::decode(y2)?);
+    }
+    if let Some(api_key) = &opt.api_key {
+        client = client.with_api_key(api_key.as_str());
+    }
+
+    // Agrees on the protocol type with the server, or adopts the server's, if requested.
+    let mut server_params = None;
+    if opt.auto_negotiate {
+        let (selected, encoded) = client.negotiate(supported_types(&opt)).await?;
+        select_protocol_type(&mut opt, &selected)?;
+        println!("      🤝 negotiated protocol: {}", selected);
+        server_params = Some(ServerParams::Negotiated(encoded));
+    } else if opt.fetch_params {
+        let fetched = client.fetch_group_params().await?;
+        // Servers not reporting their protocol type are assumed to run the configured one.
+        if !fetched.protocol_type.is_empty() {
+            select_protocol_type(&mut opt, &fetched.protocol_type)?;
+        }
+        println!("      📥 fetched parameters for: {}", protocol_type(&opt));
+        server_params = Some(ServerParams::Fetched(fetched));
+    }
+    execute_selected_protocol(opt, &mut client, server_params.as_ref()).await
+}
+
+/// The group parameters the server sent before registration.
+enum ServerParams {
+    /// Sent during negotiation, JSON-encoded, to check the local parameters against.
+    Negotiated(Vec<u8>),
+    /// Fetched from the server, hex-encoded, to use instead of the local parameters.
+    Fetched(GetGroupParamsResponse),
+}
+
+/// Configures the protocol type selected by the server, such as `ec25519`, overriding `--type`,
+/// `--modp` and `--curve`.
+fn select_protocol_type(opt: &mut Opt, selected: &str) -> Result<(), Box<dyn Error>> {
+    if let Ok(curve) = EllipticCurveType::from_str(selected) {
+        opt.r#type = ChaumPedersenType::EllipticCurve;
+        opt.curve = curve;
+    } else {
+        opt.r#type = ChaumPedersenType::DiscreteLog;
+        opt.modp = RfcModpType::from_str(selected)
+            .map_err(|_| format!("Server selected an unknown protocol type: {}", selected))?;
+    }
     Ok(())
 }
 
+/// Returns the name of the configured protocol type, such as `ec25519`.
+fn protocol_type(opt: &Opt) -> String {
+    match opt.r#type {
+        ChaumPedersenType::EllipticCurve => opt.curve.to_string(),
+        ChaumPedersenType::DiscreteLog => opt.modp.to_string(),
+    }
+}
+
+/// Returns the domain the secret hash is scoped to: the protocol type, followed by the
+/// deployment identifier if one was given, or `None` if the legacy hash was requested.
+fn secret_domain(opt: &Opt) -> Option<String> {
+    if opt.legacy_secret_hash {
+        return None;
+    }
+    let group = protocol_type(opt);
+    Some(match &opt.secret_domain {
+        Some(deployment) => format!("{}/{}", group, deployment),
+        None => group,
+    })
+}
+
+/// Lists the protocol types the client supports, with the configured type first.
+fn supported_types(opt: &Opt) -> Vec<String> {
+    let mut supported = vec![protocol_type(opt)];
+    for t in EllipticCurveType::VARIANTS
+        .iter()
+        .chain(RfcModpType::VARIANTS)
+    {
+        if **t != supported[0] {
+            supported.push(t.to_string());
+        }
+    }
+    supported
+}
+
+/// Returns the group parameters to run the protocol with: the ones fetched from the server, if
+/// any, or else the local ones, after checking them against the ones sent during negotiation.
+fn resolve_params<P>(
+    local: GroupParams<P>, server_params: Option<&ServerParams>,
+) -> Result<GroupParams<P>, Box<dyn Error>>
+where
+    P: ByteConvertible<P> + ValidateGroupParams + PartialEq,
+{
+    match server_params {
+        Some(ServerParams::Negotiated(encoded)) => {
+            if decode_group_params::<P>(encoded)? != local {
+                return Err(
+                    "Server group parameters do not match the negotiated protocol type".into()
+                );
+            }
+            Ok(local)
+        }
+        Some(ServerParams::Fetched(fetched)) => decode_hex_group_params::<P>(fetched),
+        None => Ok(local),
+    }
+}
+
+/// Writes the proof transcript of `result` to `path` as hex-encoded JSON, if a path is given,
+/// noting whether the transcript verifies under `params`.
+///
+/// # Type Parameters
+/// * `C` - The Chaum-Pedersen protocol that produced the transcript.
+fn dump_transcript<C, P, S>(
+    path: Option<&Path>, params: &GroupParams<P>, result: AuthResult,
+) -> Result<AuthResult, Box<dyn Error>>
+where
+    C: ChaumPedersen<
+        GroupParameters = GroupParams<P>,
+        CommitParameters = (P, P, P, P),
+        Challenge = S,
+        Response = S,
+    >,
+    P: ByteConvertible<P>,
+    S: ByteConvertible<S>,
+{
+    if let Some(path) = path {
+        let proof = decode_proof::<P, S>(&result.proof)?;
+        let dump = TranscriptDump {
+            y1: P::to_hex(&proof.y1),
+            y2: P::to_hex(&proof.y2),
+            r1: P::to_hex(&proof.r1),
+            r2: P::to_hex(&proof.r2),
+            c: S::to_hex(&proof.c),
+            s: S::to_hex(&proof.s),
+            verified: C::verify(
+                params,
+                &proof.s,
+                &proof.c,
+                &(proof.y1, proof.y2, proof.r1, proof.r2),
+            ),
+        };
+        std::fs::write(path, serde_json::to_string_pretty(&dump)?)?;
+    }
+    Ok(result)
+}
+
 async fn execute_selected_protocol(
-    opt: Opt, client: &mut AuthClientLib,
-) -> Result<(), Box<dyn Error>> {
+    opt: Opt, client: &mut AuthClientLib, server_params: Option<&ServerParams>,
+) -> Result<AuthResult, Box<dyn Error>> {
     // Executes the selected Chaum-Pedersen protocol.
+    let domain = secret_domain(&opt);
+    let dump = opt.dump_transcript.as_deref();
     match opt.r#type {
         ChaumPedersenType::DiscreteLog => {
-            let dl_params =
-                GroupParams::<BigUint>::from_str(&opt.modp.to_string()).map_err(|_| {
-                    "Invalid discrete log group parameters provided in command-line arguments"
-                        .to_string()
-                })?;
+            let dl_params = resolve_params(GroupParams::for_modp(opt.modp), server_params)?;
             // Executes the discrete log version of the protocol
             execute_protocol::<DiscreteLogChaumPedersen, _, _>(
                 &dl_params,
-                &hash_or_randomize_secret(opt.secret.as_ref()),
+                &hash_or_randomize_secret::<DiscreteLogChaumPedersen>(
+                    &dl_params,
+                    opt.secret.as_ref(),
+                    domain.as_deref(),
+                    opt.hash_algorithm,
+                ),
                 &opt.user,
                 client,
             )
             .await
+            .and_then(|result| {
+                dump_transcript::<DiscreteLogChaumPedersen, _, _>(dump, &dl_params, result)
+            })
         }
         ChaumPedersenType::EllipticCurve => {
-            match opt.curve {
-                EllipticCurveType::Ec25519 => {
-                    let ec_params = GroupParams::<RistrettoPoint>::from_str(&opt.curve.to_string())
-                    .map_err(|_| {
-                        "Invalid elliptic curve group parameters provided in command-line arguments"
-                            .to_string()
-                    })?;
+            match CurveParams::for_curve(opt.curve) {
+                CurveParams::Ec25519(ec_params) => {
+                    let ec_params = resolve_params(ec_params, server_params)?;
                     // Executes the elliptic curve version of the protocol
                     execute_protocol::<Curve25519ChaumPedersen, _, _>(
                         &ec_params,
-                        &hash_or_randomize_secret(opt.secret.as_ref()),
+                        &hash_or_randomize_secret::<Curve25519ChaumPedersen>(
+                            &ec_params,
+                            opt.secret.as_ref(),
+                            domain.as_deref(),
+                            opt.hash_algorithm,
+                        ),
                         &opt.user,
                         client,
                     )
                     .await
+                    .and_then(|result| {
+                        dump_transcript::<Curve25519ChaumPedersen, _, _>(dump, &ec_params, result)
+                    })
                 }
-                EllipticCurveType::Pallas => {
-                    let ec_params = GroupParams::<PallasPoint>::from_str(&opt.curve.to_string())
-                    .map_err(|_| {
-                        "Invalid elliptic curve group parameters provided in command-line arguments"
-                            .to_string()
-                    })?;
+                CurveParams::Pallas(ec_params) => {
+                    let ec_params = resolve_params(ec_params, server_params)?;
                     // Executes the elliptic curve version of the protocol
                     execute_protocol::<PallasCurveChaumPedersen, _, _>(
                         &ec_params,
-                        &hash_or_randomize_secret(opt.secret.as_ref()),
+                        &hash_or_randomize_secret::<PallasCurveChaumPedersen>(
+                            &ec_params,
+                            opt.secret.as_ref(),
+                            domain.as_deref(),
+                            opt.hash_algorithm,
+                        ),
                         &opt.user,
                         client,
                     )
                     .await
+                    .and_then(|result| {
+                        dump_transcript::<PallasCurveChaumPedersen, _, _>(dump, &ec_params, result)
+                    })
                 }
 
-                EllipticCurveType::Vesta => {
-                    let ec_params = GroupParams::<VestaPoint>::from_str(&opt.curve.to_string())
-                    .map_err(|_| {
-                        "Invalid elliptic curve group parameters provided in command-line arguments"
-                            .to_string()
-                    })?;
+                CurveParams::Vesta(ec_params) => {
+                    let ec_params = resolve_params(ec_params, server_params)?;
                     // Executes the elliptic curve version of the protocol
                     execute_protocol::<VestaCurveChaumPedersen, _, _>(
                         &ec_params,
-                        &hash_or_randomize_secret(opt.secret.as_ref()),
+                        &hash_or_randomize_secret::<VestaCurveChaumPedersen>(
+                            &ec_params,
+                            opt.secret.as_ref(),
+                            domain.as_deref(),
+                            opt.hash_algorithm,
+                        ),
+                        &opt.user,
+                        client,
+                    )
+                    .await
+                    .and_then(|result| {
+                        dump_transcript::<VestaCurveChaumPedersen, _, _>(dump, &ec_params, result)
+                    })
+                }
+
+                CurveParams::Jubjub(ec_params) => {
+                    let ec_params = resolve_params(ec_params, server_params)?;
+                    // Executes the elliptic curve version of the protocol
+                    execute_protocol::<JubjubChaumPedersen, _, _>(
+                        &ec_params,
+                        &hash_or_randomize_secret::<JubjubChaumPedersen>(
+                            &ec_params,
+                            opt.secret.as_ref(),
+                            domain.as_deref(),
+                            opt.hash_algorithm,
+                        ),
                         &opt.user,
                         client,
                     )
                     .await
+                    .and_then(|result| {
+                        dump_transcript::<JubjubChaumPedersen, _, _>(dump, &ec_params, result)
+                    })
+                }
+
+                CurveParams::BrainpoolP256(ec_params) => {
+                    let ec_params = resolve_params(ec_params, server_params)?;
+                    // Executes the elliptic curve version of the protocol
+                    execute_protocol::<BrainpoolChaumPedersen, _, _>(
+                        &ec_params,
+                        &hash_or_randomize_secret::<BrainpoolChaumPedersen>(
+                            &ec_params,
+                            opt.secret.as_ref(),
+                            domain.as_deref(),
+                            opt.hash_algorithm,
+                        ),
+                        &opt.user,
+                        client,
+                    )
+                    .await
+                    .and_then(|result| {
+                        dump_transcript::<BrainpoolChaumPedersen, _, _>(dump, &ec_params, result)
+                    })
                 }
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use num_bigint::BigUint;
+    use pasta_curves::pallas::{Point, Scalar};
+    use sha2::{Digest, Sha512};
+    use zk_pass::chaum_pedersen::constants::{EC25519_GROUP_PARAMS, PALLAS_GROUP_PARAMS};
+    use zk_pass::conversion::{encode_proof, ProofData};
+    use zk_pass::service::zkp_auth::auth_server::AuthServer;
+    use zk_pass::service::ZkAuth;
+
+    const ALGORITHMS: [HashAlgorithm; 3] =
+        [HashAlgorithm::Sha256, HashAlgorithm::Sha512, HashAlgorithm::Blake2b];
+
+    /// Proves knowledge of `registered` with `x`, returning whether the proof verifies.
+    fn proves<C: ChaumPedersen<CommitParameters = (P, P, P, P)>, P: Clone>(
+        params: &C::GroupParameters, registered: &C::Secret, x: &C::Secret,
+    ) -> bool {
+        let ((y1, y2, _, _), _) = C::commitment(params, registered);
+        let ((_, _, r1, r2), k) = C::commitment(params, x);
+        let c = C::challenge(params);
+        let s = C::challenge_response(params, &k, &c, x);
+        C::verify(params, &s, &c, &(y1, y2, r1, r2))
+    }
+
+    #[test]
+    fn test_secret_domains_separate_secrets() {
+        let params = GroupParams::for_modp(RfcModpType::Rfc5114Modp_2048_256);
+        let passcode = String::from("correct horse battery staple");
+        let derive = |domain| {
+            hash_or_randomize_secret::<DiscreteLogChaumPedersen>(
+                &params,
+                Some(&passcode),
+                domain,
+                HashAlgorithm::Sha512,
+            )
+        };
+        let ec25519 = derive(Some("ec25519"));
+        assert_ne!(ec25519, derive(Some("pallas")));
+        assert_eq!(ec25519, derive(Some("ec25519")));
+
+        // The legacy hash is the plain SHA-512 of the passcode, reduced modulo `q`.
+        let legacy = derive(None);
+        assert_eq!(legacy, BigUint::from_bytes_be(&Sha512::digest(&passcode)) % &params.q);
+        assert_ne!(legacy, ec25519);
+    }
+
+    #[test]
+    fn test_hash_algorithms_yield_valid_secrets() {
+        let passcode = String::from("correct horse battery staple");
+        let dl_params = GroupParams::for_modp(RfcModpType::Rfc5114Modp_2048_256);
+        let ec_params = EC25519_GROUP_PARAMS.to_owned();
+        let pallas_params = PALLAS_GROUP_PARAMS.to_owned();
+
+        let mut dl_secrets = Vec::new();
+        for algorithm in ALGORITHMS {
+            let x = hash_or_randomize_secret::<DiscreteLogChaumPedersen>(
+                &dl_params,
+                Some(&passcode),
+                Some("rfc5114_modp_2048_256"),
+                algorithm,
+            );
+            assert!(x < dl_params.q);
+            assert!(proves::<DiscreteLogChaumPedersen, _>(&dl_params, &x, &x));
+            dl_secrets.push(x);
+
+            let x = hash_or_randomize_secret::<Curve25519ChaumPedersen>(
+                &ec_params,
+                Some(&passcode),
+                Some("ec25519"),
+                algorithm,
+            );
+            assert!(proves::<Curve25519ChaumPedersen, _>(&ec_params, &x, &x));
+
+            let x = hash_or_randomize_secret::<PallasCurveChaumPedersen>(
+                &pallas_params,
+                Some(&passcode),
+                Some("pallas"),
+                algorithm,
+            );
+            assert!(proves::<PallasCurveChaumPedersen, _>(&pallas_params, &x, &x));
+        }
+        dl_secrets.dedup();
+        assert_eq!(dl_secrets.len(), ALGORITHMS.len());
+    }
+
+    #[test]
+    fn test_mismatched_hash_algorithm_fails_verification() {
+        let passcode = String::from("correct horse battery staple");
+        let params = EC25519_GROUP_PARAMS.to_owned();
+        let derive = |algorithm| {
+            hash_or_randomize_secret::<Curve25519ChaumPedersen>(
+                &params,
+                Some(&passcode),
+                Some("ec25519"),
+                algorithm,
+            )
+        };
+
+        // Registered with SHA-256, but authenticating with SHA-512.
+        let registered = derive(HashAlgorithm::Sha256);
+        assert!(proves::<Curve25519ChaumPedersen, _>(&params, &registered, &registered));
+        let x = derive(HashAlgorithm::Sha512);
+        assert!(!proves::<Curve25519ChaumPedersen, _>(&params, &registered, &x));
+    }
+    #[test]
+    fn test_dumped_transcript_parses_back() {
+        let params = PALLAS_GROUP_PARAMS.to_owned();
+        let x = hash_or_randomize_secret::<PallasCurveChaumPedersen>(
+            &params,
+            None,
+            None,
+            HashAlgorithm::Sha512,
+        );
+        let ((y1, y2, r1, r2), k) = PallasCurveChaumPedersen::commitment(&params, &x);
+        let c = PallasCurveChaumPedersen::challenge(&params);
+        let s = PallasCurveChaumPedersen::challenge_response(&params, &k, &c, &x);
+        let result = AuthResult {
+            session_id: "session".to_string(),
+            session_token: None,
+            verified: true,
+            proof: encode_proof(&y1, &y2, &r1, &r2, &c, &s).unwrap(),
+        };
+
+        let path = std::env::temp_dir()
+            .join(format!("zk_pass_client_transcript_{}.json", std::process::id()));
+        let result =
+            dump_transcript::<PallasCurveChaumPedersen, _, _>(Some(&path), &params, result)
+                .unwrap();
+        assert_eq!(result.session_id, "session");
+        let dump: TranscriptDump =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert!(dump.verified);
+
+        let parsed = ProofData {
+            y1: Point::from_hex(&dump.y1).unwrap(),
+            y2: Point::from_hex(&dump.y2).unwrap(),
+            r1: Point::from_hex(&dump.r1).unwrap(),
+            r2: Point::from_hex(&dump.r2).unwrap(),
+            c: Scalar::from_hex(&dump.c).unwrap(),
+            s: Scalar::from_hex(&dump.s).unwrap(),
+        };
+        assert_eq!(
+            parsed,
+            ProofData {
+                y1,
+                y2,
+                r1,
+                r2,
+                c,
+                s
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fetched_params_authenticate_without_local_group() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let auth =
+            ZkAuth::<PallasCurveChaumPedersen, Point, Scalar>::new(PALLAS_GROUP_PARAMS.to_owned())
+                .with_protocol_type("pallas");
+        tokio::spawn(
+            tonic::transport::Server::builder()
+                .add_service(AuthServer::new(auth))
+                .serve_with_incoming(tokio_stream::wrappers::TcpListenerStream::new(listener)),
+        );
+
+        // Neither `--type` nor `--curve` is given, so the defaults would select a discrete log
+        // group the server does not run.
+        let port = port.to_string();
+        let opt = Opt::from_iter_safe([
+            "client",
+            "--host",
+            "127.0.0.1",
+            "--port",
+            &port,
+            "--user",
+            "alice",
+            "--secret",
+            "correct horse battery staple",
+            "--fetch-params",
+        ])
+        .unwrap();
+        assert_eq!(opt.r#type, ChaumPedersenType::DiscreteLog);
+
+        let result = authenticate(opt).await.unwrap();
+        assert!(result.verified);
+        assert!(!result.session_id.is_empty());
+    }
+}
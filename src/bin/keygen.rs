@@ -0,0 +1,239 @@
+use num_bigint::{BigUint, RandBigInt};
+use num_prime::nt_funcs::{is_prime, primes};
+use num_traits::One;
+use pasta_curves::arithmetic::CurveExt;
+use pasta_curves::group::Group;
+use pasta_curves::pallas::Point as PallasPoint;
+use sha2::{Digest, Sha512};
+use std::path::{Path, PathBuf};
+use structopt::StructOpt;
+use strum::VariantNames;
+use zk_pass::chaum_pedersen::{GroupParams, ValidateGroupParams};
+use zk_pass::cmdutil::{parse_enum_or_help, ChaumPedersenType};
+use zk_pass::conversion::ByteConvertible;
+
+/// The number of Miller-Rabin rounds `--verify` checks the primality of `p` and `q` with.
+const PRIMALITY_TEST_ROUNDS: usize = 32;
+
+/// The bound of the small primes safe prime candidates are sieved with.
+const SIEVE_LIMIT: u64 = 10_000;
+
+/// Command-line options structure for the parameter generator.
+#[derive(StructOpt, Debug)]
+#[structopt(
+    name = "keygen",
+    about = "Generates group parameters for the ZKPass Chaum-Pedersen protocol"
+)]
+struct Opt {
+    /// Command line option to set the underlying type of the Chaum-Pedersen protocol to
+    /// generate parameters for.
+    #[structopt(short, long, possible_values = ChaumPedersenType::VARIANTS, parse(try_from_str = parse_enum_or_help), default_value = "discrete_log")]
+    r#type: ChaumPedersenType,
+
+    /// Command line option to set the bits of security the parameters must offer.
+    #[structopt(long, possible_values = &["128", "192", "256"], default_value = "128")]
+    security_bits: u32,
+
+    /// Command line option to override the bit length of the "discrete_log" modulus, e.g. for
+    /// quick experiments. The parameters then offer the security of the given length instead.
+    #[structopt(long)]
+    modulus_bits: Option<usize>,
+
+    /// Command line option to set the domain separation string `h` is derived from, so that
+    /// nobody knows its discrete logarithm with respect to `g`.
+    #[structopt(long, default_value = "zk_pass.keygen")]
+    domain: String,
+
+    /// Command line option to write the parameters to a file instead of stdout.
+    #[structopt(short, long, parse(from_os_str))]
+    output: Option<PathBuf>,
+
+    /// Command line option to re-validate the generated parameters before writing them.
+    #[structopt(long)]
+    verify: bool,
+}
+
+/// Main entry point for the ZKPass parameter generator.
+///
+/// ## Usage
+/// This program generates group parameters at a requested security level and writes them as
+/// JSON, in the encoding `GroupParams` deserializes from.
+///
+/// ### Command Line Options
+/// - `--type`: "discrete_log" generates a safe prime `p = 2q + 1` and generators of the subgroup
+///   of order `q`; "elliptic_curve" selects the Pallas curve. Defaults to "discrete_log".
+/// - `--security-bits`: 128, 192 or 256. A safe prime takes 3072, 7680 or 15360 bits following
+///   NIST SP 800-57 Part 1, and generating one takes long at the larger sizes. No supported
+///   curve offers more than 128 bits.
+/// - `--modulus-bits`: Overrides the bit length of the safe prime.
+/// - `--domain`: The domain separation string `h` is derived from.
+/// - `--output`: Writes the parameters to a file instead of stdout.
+/// - `--verify`: Re-validates the parameters, including the primality of `p` and `q`.
+///
+/// ### Example Usage
+///
+/// ```bash
+/// cargo run --bin keygen -- --type elliptic_curve --security-bits 128 --verify
+/// ```
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let Opt {
+        r#type: stereotype,
+        security_bits,
+        modulus_bits,
+        domain,
+        output,
+        verify,
+    } = Opt::from_args();
+
+    match stereotype {
+        ChaumPedersenType::DiscreteLog => {
+            let bits = modulus_bits.unwrap_or_else(|| modulus_bits_for(security_bits));
+            eprintln!("🔢 generating a {}-bit safe prime", bits);
+            let params = generate_discrete_log_params(bits, domain.as_bytes());
+            if verify {
+                params.check_primality(PRIMALITY_TEST_ROUNDS)?;
+            }
+            write_params(&params, verify, output.as_deref())
+        }
+        ChaumPedersenType::EllipticCurve => {
+            if security_bits > 128 {
+                return Err(format!(
+                    "No supported curve offers {} bits of security; use --type discrete_log",
+                    security_bits
+                )
+                .into());
+            }
+            eprintln!("🌀 selecting the Pallas curve");
+            let params = generate_pallas_params(&domain);
+            write_params(&params, verify, output.as_deref())
+        }
+    }
+}
+
+/// Returns the bit length of a safe prime offering a security level, following
+/// NIST SP 800-57 Part 1.
+///
+/// # Arguments
+/// * `security_bits` - The bits of security, one of 128, 192 or 256.
+fn modulus_bits_for(security_bits: u32) -> usize {
+    match security_bits {
+        128 => 3072,
+        192 => 7680,
+        _ => 15360,
+    }
+}
+
+/// Generates discrete log parameters over a random safe prime `p = 2q + 1`.
+///
+/// The subgroup of order `q` is the group of quadratic residues modulo `p`, so `g` and `h` are
+/// the squares of values hashed from the domain. Neither has a discrete logarithm anyone knows
+/// with respect to the other.
+///
+/// # Arguments
+/// * `bits` - The bit length of `p`.
+/// * `domain` - The domain separation string the generators are derived from.
+fn generate_discrete_log_params(bits: usize, domain: &[u8]) -> GroupParams<BigUint> {
+    let q = generate_sophie_germain_prime(bits - 1);
+    let p = (&q << 1u8) + BigUint::one();
+    let g = hash_to_subgroup(&p, domain, b"g");
+    let h = hash_to_subgroup(&p, domain, b"h");
+    GroupParams { g, h, p, q }
+}
+
+/// Generates a random prime `q` of the given bit length such that `2q + 1` is prime too.
+///
+/// Candidates are sieved with small primes, for both `q` and `2q + 1`, before the costlier
+/// primality tests.
+///
+/// # Arguments
+/// * `bits` - The bit length of `q`.
+fn generate_sophie_germain_prime(bits: usize) -> BigUint {
+    let small_primes = primes(SIEVE_LIMIT);
+    let mut rng = rand::thread_rng();
+    loop {
+        let mut q = rng.gen_biguint(bits as u64);
+        q.set_bit(bits as u64 - 1, true);
+        q.set_bit(0, true);
+        // `q` and `2q + 1` are divisible by `r` exactly when `q` is 0 or `(r - 1) / 2` mod `r`.
+        let sieved = small_primes.iter().skip(1).all(|&r| {
+            let residue = (&q % r).iter_u64_digits().next().unwrap_or(0);
+            residue != 0 && residue != (r - 1) / 2
+        });
+        if !sieved || !is_prime(&q, None).probably() {
+            continue;
+        }
+        let p = (&q << 1u8) + BigUint::one();
+        if is_prime(&p, None).probably() {
+            return q;
+        }
+    }
+}
+
+/// Hashes a label into the subgroup of quadratic residues modulo a safe prime, other than 1.
+///
+/// # Arguments
+/// * `p` - The safe prime.
+/// * `domain` - The domain separation string.
+/// * `label` - The label telling apart the elements derived from the same domain.
+fn hash_to_subgroup(p: &BigUint, domain: &[u8], label: &[u8]) -> BigUint {
+    // 128 extra bits make the reduction modulo `p` close to uniform.
+    let len = (p.bits() as usize + 128).div_ceil(8);
+    for counter in 0u32.. {
+        let mut bytes = Vec::with_capacity(len + 64);
+        for block in 0u32.. {
+            if bytes.len() >= len {
+                break;
+            }
+            let mut hasher = Sha512::new();
+            for part in [domain, label, &counter.to_be_bytes(), &block.to_be_bytes()] {
+                hasher.update((part.len() as u64).to_be_bytes());
+                hasher.update(part);
+            }
+            bytes.extend_from_slice(&hasher.finalize());
+        }
+        let x = BigUint::from_bytes_be(&bytes[..len]) % p;
+        let element = x.modpow(&BigUint::from(2u8), p);
+        if element > BigUint::one() {
+            return element;
+        }
+    }
+    unreachable!("the counter never runs out before finding an element")
+}
+
+/// Selects the Pallas curve, with the standard generator as `g` and `h` hashed to the curve
+/// from the domain. `p` and `q` are unused by curve groups and left as the identity.
+///
+/// # Arguments
+/// * `domain` - The domain separation string `h` is derived from.
+fn generate_pallas_params(domain: &str) -> GroupParams<PallasPoint> {
+    GroupParams {
+        g: PallasPoint::generator(),
+        h: PallasPoint::hash_to_curve(domain)(b"h"),
+        p: PallasPoint::identity(),
+        q: PallasPoint::identity(),
+    }
+}
+
+/// Writes the parameters as JSON, after validating them if requested.
+///
+/// # Arguments
+/// * `params` - The generated parameters.
+/// * `verify` - Whether to validate the parameters first.
+/// * `output` - The file to write to, or `None` for stdout.
+///
+/// # Returns
+/// An error if the parameters do not validate or cannot be written.
+fn write_params<T: ByteConvertible<T> + ValidateGroupParams>(
+    params: &GroupParams<T>, verify: bool, output: Option<&Path>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if verify {
+        params.validate()?;
+        eprintln!("✅ parameters verified");
+    }
+    let json = serde_json::to_string_pretty(params)?;
+    match output {
+        Some(path) => std::fs::write(path, json + "\n")?,
+        None => println!("{}", json),
+    }
+    Ok(())
+}
@@ -1,19 +1,44 @@
-use curve25519_dalek::RistrettoPoint;
+use ed25519_dalek::SigningKey;
+use ipnet::IpNet;
 use num_bigint::BigUint;
-use pasta_curves::pallas::Point as PallasPoint;
-use pasta_curves::vesta::Point as VestaPoint;
-use std::str::FromStr;
-use structopt::StructOpt;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use structopt::{clap, StructOpt};
 use strum::VariantNames;
-use tonic::transport::Server;
+use tokio::signal::unix::{signal, SignalKind};
+use tokio::sync::oneshot;
+use tonic::transport::server::TcpIncoming;
+use tonic::transport::{Identity, Server, ServerTlsConfig};
+use tower::util::option_layer;
+use zk_pass::challenge::ExternalChallengeSource;
+use zk_pass::chaum_pedersen::brainpool::BrainpoolChaumPedersen;
 use zk_pass::chaum_pedersen::curve25519::Curve25519ChaumPedersen;
-use zk_pass::chaum_pedersen::discretelog::DiscreteLogChaumPedersen;
+use zk_pass::chaum_pedersen::discretelog::{
+    DiscreteLogChaumPedersen, DiscreteLogChaumPedersenConfig,
+};
+use zk_pass::chaum_pedersen::jubjub::JubjubChaumPedersen;
 use zk_pass::chaum_pedersen::pallas::PallasCurveChaumPedersen;
+use zk_pass::chaum_pedersen::transcript::TranscriptChallenge;
 use zk_pass::chaum_pedersen::vesta::VestaCurveChaumPedersen;
-use zk_pass::chaum_pedersen::GroupParams;
-use zk_pass::cmdutil::{ChaumPedersenType, EllipticCurveType, RfcModpType};
-use zk_pass::service::zkp_auth::auth_server::AuthServer;
-use zk_pass::service::ZkAuth;
+use zk_pass::chaum_pedersen::{ChaumPedersen, GroupParams, SecurityLevel};
+use zk_pass::cmdutil::{
+    parse_enum_or_help, ChaumPedersenType, CurveParams, EllipticCurveType, LogFormat, LogLevel,
+    RfcModpType, SessionBackend, SessionTokenType,
+};
+use zk_pass::conversion::ByteConvertible;
+use zk_pass::repository::seed::load_seed_users;
+use zk_pass::repository::session::SessionStore;
+use zk_pass::repository::transcript::JsonlTranscriptLog;
+use zk_pass::resume::{ResumeTokenSealer, DEFAULT_RESUME_TOKEN_TTL};
+use zk_pass::service::zkp_auth::admin_server::{Admin, AdminServer};
+use zk_pass::service::zkp_auth::auth_server::{Auth, AuthServer};
+use zk_pass::service::{admin_token_interceptor, rpc_timeout_layer, RegisterApiKey, ZkAuth};
+use zk_pass::token::SessionTokenFormat;
 
 /// Struct representing command line options for the server.
 #[derive(StructOpt, Debug)]
@@ -22,6 +47,11 @@ use zk_pass::service::ZkAuth;
     about = "A server for the ZKPass Chaum-Pedersen protocol service"
 )]
 struct Opt {
+    /// Command line option to read options from a TOML file, keyed by the long option names
+    /// with underscores, e.g. `port = 50051`. Options given on the command line take precedence.
+    #[structopt(long, parse(from_os_str))]
+    config: Option<PathBuf>,
+
     /// Command line option to set the host address for the server.
     /// Defaults to "[::1]" if not specified.
     #[structopt(short, long, default_value = "[::1]")]
@@ -34,17 +64,154 @@ struct Opt {
 
     /// Command line option to set the type of the RFC log group to use.
     /// Required if the stereotype is set to "discrete_log".
-    #[structopt(short, long, possible_values = RfcModpType::VARIANTS, default_value = "rfc5114_modp_1024_160", required_if("stereotype", "discrete_log"))]
+    #[structopt(short, long, possible_values = RfcModpType::VARIANTS, parse(try_from_str = parse_enum_or_help), default_value = "rfc5114_modp_1024_160", required_if("stereotype", "discrete_log"))]
     modp: RfcModpType,
 
     /// Command line option to set the underlying type of the Chaum-Pedersen protocol to use.
-    #[structopt(short, long, possible_values = ChaumPedersenType::VARIANTS, default_value = "discrete_log")]
+    #[structopt(short, long, possible_values = ChaumPedersenType::VARIANTS, parse(try_from_str = parse_enum_or_help), default_value = "discrete_log")]
     r#type: ChaumPedersenType,
 
     /// Command line option to set the elliptic curve type.
     /// Required if the stereotype is set to "elliptic_curve".
-    #[structopt(short, long, possible_values = EllipticCurveType::VARIANTS, default_value = "ec25519", required_if("stereotype", "elliptic_curve"))]
+    #[structopt(short, long, possible_values = EllipticCurveType::VARIANTS, parse(try_from_str = parse_enum_or_help), default_value = "ec25519", required_if("stereotype", "elliptic_curve"))]
     curve: EllipticCurveType,
+
+    /// Command line option to set how long to wait for in-flight requests on shutdown.
+    /// Defaults to 30 seconds if not specified.
+    #[structopt(long, default_value = "30")]
+    shutdown_timeout_secs: u64,
+
+    /// Command line option to fail requests that do not complete within this many seconds with
+    /// a deadline exceeded error, cancelling their handling. Requests may take any time if not
+    /// specified.
+    #[structopt(long)]
+    rpc_timeout_secs: Option<u64>,
+
+    /// Command line option to reject requests with a resource exhausted error while this many
+    /// requests are already being handled. All requests are admitted if not specified.
+    #[structopt(long)]
+    max_concurrent_requests: Option<usize>,
+
+    /// Command line option to bind authentication challenges to the client's IP address.
+    /// A challenge answered from a different address is rejected.
+    #[structopt(long)]
+    bind_challenge_to_ip: bool,
+
+    /// Command line option to hand out a resumption token with every challenge, which a client
+    /// that lost the reply can exchange for the same challenge within 5 minutes.
+    #[structopt(long)]
+    enable_resume: bool,
+
+    /// Command line option to reject challenge requests that reuse a commitment the same user
+    /// sent within this many seconds, protecting clients that do not draw fresh commitments.
+    #[structopt(long)]
+    commitment_reuse_window_secs: Option<u64>,
+
+    /// Command line option to set the minimum bits of security the selected group must offer.
+    /// The server exits with an error if the group falls below this threshold.
+    #[structopt(long)]
+    min_security_bits: Option<u32>,
+
+    /// Command line option to set the bit length of the challenges answered with the
+    /// "discrete_log" type, between 128 and the bit length of the group order. Defaults to the
+    /// full group order.
+    #[structopt(long)]
+    challenge_bits: Option<u32>,
+
+    /// Command line option to only accept requests from clients within the given network.
+    /// May be repeated; all addresses are accepted if not specified.
+    #[structopt(name = "allow-cidr", long = "allow-cidr", number_of_values = 1)]
+    allow_cidrs: Vec<IpNet>,
+
+    /// Command line option to reject requests from clients within the given network.
+    /// May be repeated; takes precedence over `--allow-cidr`.
+    #[structopt(name = "deny-cidr", long = "deny-cidr", number_of_values = 1)]
+    deny_cidrs: Vec<IpNet>,
+
+    /// Command line option to set the server's hex-encoded secret, enabling mutual authentication.
+    /// May also be set through the ZK_PASS_SERVER_SECRET environment variable.
+    #[structopt(long, env = "ZK_PASS_SERVER_SECRET", hide_env_values = true)]
+    server_secret: Option<String>,
+
+    /// Command line option to sign sessions with the given hex-encoded Ed25519 signing key.
+    /// May also be set through the ZK_PASS_SIGNING_KEY environment variable.
+    #[structopt(long, env = "ZK_PASS_SIGNING_KEY", hide_env_values = true)]
+    signing_key: Option<String>,
+
+    /// Command line option to set the form of the session IDs issued to authenticated users.
+    /// "jwt" requires --signing-key and "paseto" requires --paseto-key.
+    #[structopt(long, possible_values = SessionTokenType::VARIANTS, parse(try_from_str = parse_enum_or_help), default_value = "uuid")]
+    session_token_type: SessionTokenType,
+
+    /// Command line option to encrypt "paseto" session tokens with the given hex-encoded
+    /// 32-byte key. May also be set through the ZK_PASS_PASETO_KEY environment variable.
+    #[structopt(long, env = "ZK_PASS_PASETO_KEY", hide_env_values = true)]
+    paseto_key: Option<String>,
+
+    /// Command line option to preload users from a JSON seed file before serving.
+    /// The file lists `{username, y1_hex, y2_hex}` entries; startup aborts on invalid data.
+    #[structopt(long, parse(from_os_str))]
+    seed_users: Option<PathBuf>,
+
+    /// Command line option to set where the sessions of authenticated users are kept.
+    /// Defaults to "memory" if not specified.
+    #[structopt(long, possible_values = SessionBackend::VARIANTS, parse(try_from_str = parse_enum_or_help), default_value = "memory")]
+    session_backend: SessionBackend,
+
+    /// Command line option to set the Redis server used by the "redis" session backend.
+    /// May also be set through the ZK_PASS_REDIS_URL environment variable.
+    #[structopt(
+        long,
+        env = "ZK_PASS_REDIS_URL",
+        hide_env_values = true,
+        default_value = "redis://127.0.0.1/"
+    )]
+    redis_url: String,
+
+    /// Command line option to serve over TLS with the PEM-encoded certificate chain in this file.
+    /// Requires --tls-key.
+    #[structopt(long, parse(from_os_str), requires = "tls-key")]
+    tls_cert: Option<PathBuf>,
+
+    /// Command line option to serve over TLS with the PEM-encoded private key in this file.
+    /// Requires --tls-cert.
+    #[structopt(long, parse(from_os_str), requires = "tls-cert")]
+    tls_key: Option<PathBuf>,
+
+    /// Command line option to set the format of log lines.
+    /// "json" writes one JSON object per line, for log aggregators.
+    #[structopt(long, possible_values = LogFormat::VARIANTS, parse(try_from_str = parse_enum_or_help), default_value = "text")]
+    log_format: LogFormat,
+
+    /// Command line option to set the minimum severity of logged events.
+    /// Defaults to "info" if not specified.
+    #[structopt(long, possible_values = LogLevel::VARIANTS, parse(try_from_str = parse_enum_or_help), default_value = "info")]
+    log_level: LogLevel,
+
+    /// Command line option to serve the admin service, requiring this token in the
+    /// `x-admin-token` header. May also be set through the ZK_PASS_ADMIN_TOKEN environment variable.
+    #[structopt(long, env = "ZK_PASS_ADMIN_TOKEN", hide_env_values = true)]
+    admin_token: Option<String>,
+
+    /// Command line option to only accept registrations carrying this key in the `x-api-key`
+    /// header. May also be set through the ZK_PASS_REGISTER_API_KEY environment variable.
+    #[structopt(long, env = "ZK_PASS_REGISTER_API_KEY", hide_env_values = true)]
+    register_api_key: Option<String>,
+
+    /// Command line option to append the transcript of every authentication attempt to a file,
+    /// one JSON object per line, as an audit trail.
+    #[structopt(long, parse(from_os_str))]
+    transcript_log: Option<PathBuf>,
+
+    /// Command line option to mix the latest round of the drand randomness beacon at this URL,
+    /// e.g. https://api.drand.sh, into every challenge.
+    #[structopt(long)]
+    drand_url: Option<String>,
+
+    /// Command line option to take every challenge from the trusted endpoint at this URL, which
+    /// answers GET requests with a JSON object holding 32 hex-encoded random bytes.
+    #[structopt(long, conflicts_with = "drand-url")]
+    challenge_url: Option<String>,
 }
 
 /// Main entry point for the ZKPass Chaum-Pedersen protocol server.
@@ -85,6 +252,32 @@ struct Opt {
 /// - `--modp` or `-m`: Sets the type of the RFC log group to use. Required if `--type` is "discrete_log".
 /// - `--type` or `-t`: Sets the type of the Chaum-Pedersen protocol to use. Possible values: "discrete_log", "elliptic_curve".
 /// - `--curve` or `-c`: Sets the elliptic curve type. Required if `--type` is "elliptic_curve".
+/// - `--shutdown-timeout-secs`: Sets how long to wait for in-flight requests after SIGTERM. Defaults to 30.
+/// - `--rpc-timeout-secs`: Fails and cancels requests that do not complete within the given number of seconds.
+/// - `--max-concurrent-requests`: Sheds requests beyond the given number being handled at a time.
+/// - `--min-security-bits`: Exits with an error if the selected group offers fewer bits of security.
+/// - `--challenge-bits`: Sets the bit length of the challenges answered with the "discrete_log" type, from 128 bits up to the group order.
+/// - `--bind-challenge-to-ip`: Rejects challenge answers sent from a different IP address than the challenge request.
+/// - `--enable-resume`: Hands out HMAC-sealed tokens with challenges, for resuming interrupted authentications.
+/// - `--allow-cidr`: Only accepts requests from clients within the given network. May be repeated.
+/// - `--deny-cidr`: Rejects requests from clients within the given network. May be repeated.
+/// - `--server-secret`: Enables mutual authentication with the given hex-encoded server secret.
+/// - `--signing-key`: Signs the session tokens of authenticated users with the given hex-encoded Ed25519 key.
+/// - `--session-token-type`: Sets the form of issued session IDs. Possible values: "uuid", "jwt", "paseto".
+/// - `--paseto-key`: Encrypts "paseto" session tokens with the given hex-encoded 32-byte key.
+/// - `--seed-users`: Preloads the users listed in a JSON seed file before serving.
+/// - `--session-backend`: Sets where sessions are kept. Possible values: "memory", "redis".
+/// - `--redis-url`: Sets the Redis server used by the "redis" session backend.
+/// - `--log-format`: Sets the format of log lines. Possible values: "text", "json".
+/// - `--log-level`: Sets the minimum severity of logged events. Defaults to "info".
+/// - `--commitment-reuse-window-secs`: Rejects challenge requests reusing a commitment the same user sent within the window.
+/// - `--admin-token`: Serves the admin service, e.g. for listing users, to requests carrying this token.
+/// - `--register-api-key`: Only accepts registrations carrying this key, leaving authentication open.
+/// - `--transcript-log`: Appends the transcript of every authentication attempt, failed or not, to a JSON lines file.
+/// - `--drand-url`: Mixes randomness from the drand beacon at the given URL into every challenge.
+/// - `--challenge-url`: Takes every challenge from the trusted endpoint at the given URL, e.g. in public-coin setups. Cannot be combined with `--drand-url`.
+/// - `--tls-cert` and `--tls-key`: Serves over TLS with the given PEM-encoded certificate chain and private key.
+/// - `--config`: Reads options from a TOML file, e.g. `port = 50051` or `allow_cidr = ["10.0.0.0/8"]`. Options given on the command line take precedence.
 ///
 /// ### Example Usage
 ///
@@ -96,85 +289,784 @@ struct Opt {
 /// Remember to replace the values in the command with those suitable for your setup.
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    // Parse command line arguments using struct destructuring.
-    let Opt {
-        host,
-        port,
-        r#type: stereotype,
-        curve,
-        modp,
-        ..
-    } = Opt::from_args();
+    let opt = load_options(std::env::args_os()).unwrap_or_else(|e| e.exit());
+    let config = Config::from_opt(&opt)?;
+    init_logging(opt.log_format, opt.log_level)?;
 
     // Print server start information.
     println!("🔥 Starting ZK_PASS server 🔥");
-    println!("      🤖 host: {}", host);
-    println!("      🔌 port: {}", port);
-    println!("      💥 stereotype: {}", stereotype);
-    match stereotype {
-        ChaumPedersenType::EllipticCurve => println!("      📈 elliptic curve: {}", curve),
-        _ => println!("      🔢 modp group: {}", modp),
+    println!("      🤖 host: {}", opt.host);
+    println!("      🔌 port: {}", opt.port);
+    println!("      💥 stereotype: {}", opt.r#type);
+    match opt.r#type {
+        ChaumPedersenType::EllipticCurve => println!("      📈 elliptic curve: {}", opt.curve),
+        _ => println!("      🔢 modp group: {}", opt.modp),
+    }
+    println!("      💾 session backend: {}", opt.session_backend);
+    if opt.admin_token.is_some() {
+        println!("      🔒 admin service enabled");
+    }
+    if opt.register_api_key.is_some() {
+        println!("      🔑 registration requires an API key");
     }
-
-    // Parse the address and handle errors informatively.
-    let addr = format!("{}:{}", host, port)
-        .parse()
-        .map_err(|_| "Failed to parse server address")?;
 
     // Initialize and start the server based on stereotype.
-    match stereotype {
+    match opt.r#type {
         ChaumPedersenType::DiscreteLog => {
-            let params = GroupParams::<BigUint>::from_str(&modp.to_string()).map_err(|_| {
-                "Invalid discrete log group parameters provided in command-line arguments"
-                    .to_string()
-            })?;
+            let params = GroupParams::for_modp(opt.modp);
+            check_security_level(params.security_level(), opt.min_security_bits)?;
             let auth = ZkAuth::<DiscreteLogChaumPedersen, _, _>::new(params);
-            Server::builder()
-                .add_service(AuthServer::new(auth))
-                .serve(addr)
-                .await?;
+            run(with_challenge_bits(auth, opt.challenge_bits)?, config).await
         }
         ChaumPedersenType::EllipticCurve => {
-            match curve {
-                EllipticCurveType::Ec25519 => {
-                    let params =
-                    GroupParams::<RistrettoPoint>::from_str(&curve.to_string()).map_err(|_| {
-                        "Invalid elliptic curve group parameters provided in command-line arguments"
-                            .to_string()
-                    })?;
-                    let auth = ZkAuth::<Curve25519ChaumPedersen, _, _>::new(params);
-                    Server::builder()
-                        .add_service(AuthServer::new(auth))
-                        .serve(addr)
-                        .await?;
+            // All supported curves have ~255-bit group orders, offering 128 bits of security.
+            check_security_level(SecurityLevel::Bits128, opt.min_security_bits)?;
+            if opt.challenge_bits.is_some() {
+                return Err("--challenge-bits only applies to the discrete_log type".into());
+            }
+            match CurveParams::for_curve(opt.curve) {
+                CurveParams::Ec25519(params) => {
+                    run(ZkAuth::<Curve25519ChaumPedersen, _, _>::new(params), config).await
                 }
-
-                EllipticCurveType::Pallas => {
-                    let params = GroupParams::<PallasPoint>::from_str(&curve.to_string()).map_err(|_| {
-                    "Invalid elliptic curve group parameters provided in command-line arguments"
-                        .to_string()
-                })?;
-                    let auth = ZkAuth::<PallasCurveChaumPedersen, _, _>::new(params);
-                    Server::builder()
-                        .add_service(AuthServer::new(auth))
-                        .serve(addr)
-                        .await?;
+                CurveParams::Pallas(params) => {
+                    run(ZkAuth::<PallasCurveChaumPedersen, _, _>::new(params), config).await
                 }
-
-                EllipticCurveType::Vesta => {
-                    let params = GroupParams::<VestaPoint>::from_str(&curve.to_string()).map_err(|_| {
-                    "Invalid elliptic curve group parameters provided in command-line arguments"
-                        .to_string()
-                })?;
-                    let auth = ZkAuth::<VestaCurveChaumPedersen, _, _>::new(params);
-                    Server::builder()
-                        .add_service(AuthServer::new(auth))
-                        .serve(addr)
-                        .await?;
+                CurveParams::Vesta(params) => {
+                    run(ZkAuth::<VestaCurveChaumPedersen, _, _>::new(params), config).await
+                }
+                CurveParams::Jubjub(params) => {
+                    run(ZkAuth::<JubjubChaumPedersen, _, _>::new(params), config).await
+                }
+                CurveParams::BrainpoolP256(params) => {
+                    run(ZkAuth::<BrainpoolChaumPedersen, _, _>::new(params), config).await
                 }
             }
         }
     }
+}
+
+/// The options the server is run with, whichever protocol type it serves.
+struct Config {
+    /// The name clients negotiate the served protocol type with, e.g. `ec25519`.
+    protocol_type: String,
+    /// The socket address to bind the server to.
+    addr: SocketAddr,
+    /// How the server handles requests.
+    serve: ServeOptions,
+    /// Where the sessions of authenticated users are kept.
+    sessions: Box<dyn SessionStore + Send + Sync>,
+    bind_challenge_to_ip: bool,
+    allow_cidrs: Vec<IpNet>,
+    deny_cidrs: Vec<IpNet>,
+    server_secret: Option<String>,
+    signing_key: Option<String>,
+    session_token_type: SessionTokenType,
+    paseto_key: Option<String>,
+    transcript_log: Option<PathBuf>,
+    commitment_reuse_window_secs: Option<u64>,
+    max_concurrent_requests: Option<usize>,
+    enable_resume: bool,
+    drand_url: Option<String>,
+    challenge_url: Option<String>,
+    seed_users: Option<PathBuf>,
+}
+
+impl Config {
+    /// Builds the configuration from the command line options.
+    ///
+    /// # Returns
+    /// The configuration, or an error if the address, the session backend or the TLS files
+    /// are invalid.
+    fn from_opt(opt: &Opt) -> Result<Self, Box<dyn std::error::Error>> {
+        let addr: SocketAddr = format!("{}:{}", opt.host, opt.port)
+            .parse()
+            .map_err(|_| "Failed to parse server address")?;
+        let protocol_type = match opt.r#type {
+            ChaumPedersenType::EllipticCurve => opt.curve.to_string(),
+            ChaumPedersenType::DiscreteLog => opt.modp.to_string(),
+        };
+        Ok(Self {
+            protocol_type,
+            addr,
+            serve: ServeOptions {
+                shutdown_timeout: Duration::from_secs(opt.shutdown_timeout_secs),
+                rpc_timeout: opt.rpc_timeout_secs.map(Duration::from_secs),
+                tls: load_tls_config(opt.tls_cert.as_deref(), opt.tls_key.as_deref())?,
+                admin_token: opt.admin_token.clone(),
+                register_api_key: opt.register_api_key.clone(),
+            },
+            sessions: opt.session_backend.session_store(&opt.redis_url)?,
+            bind_challenge_to_ip: opt.bind_challenge_to_ip,
+            allow_cidrs: opt.allow_cidrs.clone(),
+            deny_cidrs: opt.deny_cidrs.clone(),
+            server_secret: opt.server_secret.clone(),
+            signing_key: opt.signing_key.clone(),
+            session_token_type: opt.session_token_type,
+            paseto_key: opt.paseto_key.clone(),
+            transcript_log: opt.transcript_log.clone(),
+            commitment_reuse_window_secs: opt.commitment_reuse_window_secs,
+            max_concurrent_requests: opt.max_concurrent_requests,
+            enable_resume: opt.enable_resume,
+            drand_url: opt.drand_url.clone(),
+            challenge_url: opt.challenge_url.clone(),
+            seed_users: opt.seed_users.clone(),
+        })
+    }
+}
+
+/// How the server handles requests.
+///
+/// # Fields
+/// - `shutdown_timeout`: The maximum time to wait for in-flight requests once draining starts.
+/// - `rpc_timeout`: The time every request is given to complete, if limited.
+/// - `tls`: The TLS configuration, if serving over TLS.
+/// - `admin_token`: The token guarding the admin service, which is only served if set.
+/// - `register_api_key`: The API key required to register users, if registration is gated.
+struct ServeOptions {
+    shutdown_timeout: Duration,
+    rpc_timeout: Option<Duration>,
+    tls: Option<ServerTlsConfig>,
+    admin_token: Option<String>,
+    register_api_key: Option<String>,
+}
+
+/// Configures the authentication service and serves it until shutdown.
+///
+/// # Arguments
+/// * `auth` - The service for the selected protocol type and group parameters.
+/// * `config` - The options the service is configured and served with.
+///
+/// # Returns
+/// `Ok(())` once the server shut down, or an error if an option is invalid or the server fails.
+async fn run<C, T, S>(
+    auth: ZkAuth<C, T, S>, config: Config,
+) -> Result<(), Box<dyn std::error::Error>>
+where
+    ZkAuth<C, T, S>: Auth + Admin,
+    C: ChaumPedersen<GroupParameters = GroupParams<T>, CommitParameters = (T, T, T, T), Secret = S>,
+    T: Send + Sync + Clone + ByteConvertible<T> + 'static,
+    S: Send + Sync + Clone + ByteConvertible<S> + TranscriptChallenge<GroupParams<T>> + 'static,
+{
+    let auth = auth
+        .with_protocol_type(config.protocol_type)
+        .with_challenge_ip_binding(config.bind_challenge_to_ip)
+        .with_allowed_cidrs(config.allow_cidrs)
+        .with_denied_cidrs(config.deny_cidrs)
+        .with_session_store(config.sessions);
+    let auth = with_server_secret(auth, config.server_secret.as_deref())?;
+    let auth = with_signing_key(auth, config.signing_key.as_deref())?;
+    let auth = with_session_token_type(
+        auth,
+        &config.session_token_type,
+        config.signing_key.as_deref(),
+        config.paseto_key.as_deref(),
+    )?;
+    let auth = with_transcript_log(auth, config.transcript_log.as_deref())?;
+    let auth = with_commitment_reuse_guard(auth, config.commitment_reuse_window_secs);
+    let auth = with_load_shedding(auth, config.max_concurrent_requests);
+    let auth = with_resumption(auth, config.enable_resume);
+    let auth =
+        with_challenge_source(auth, config.drand_url.as_deref(), config.challenge_url.as_deref())?;
+    let auth = with_seed_users(auth, config.seed_users.as_deref()).await?;
+    serve(auth, config.addr, config.serve).await
+}
+
+/// A value in a configuration file.
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+enum ConfigValue {
+    Flag(bool),
+    Number(i64),
+    Text(String),
+    List(Vec<String>),
+}
+
+/// Parses the command line options, taking any option not given on the command line from the
+/// TOML file passed with `--config`, if any.
+///
+/// The file's keys are the long option names with underscores instead of dashes. Flags are set
+/// with `true`, and options that may be repeated take an array of values. A value in the file
+/// takes precedence over the option's environment variable.
+///
+/// # Arguments
+/// * `args` - The command line arguments, starting with the program name.
+///
+/// # Returns
+/// The merged options, or an error if the arguments or the configuration file are invalid.
+fn load_options<I: IntoIterator<Item = OsString>>(args: I) -> clap::Result<Opt> {
+    let args: Vec<OsString> = args.into_iter().collect();
+    let matches = Opt::clap().get_matches_from_safe(&args)?;
+    let opt = Opt::from_clap(&matches);
+    let path = match opt.config {
+        Some(path) => path,
+        None => return Ok(opt),
+    };
+    let config_error =
+        |message: String| clap::Error::with_description(&message, clap::ErrorKind::InvalidValue);
+    let contents = std::fs::read_to_string(&path)
+        .map_err(|e| config_error(format!("Failed to read {}: {}", path.display(), e)))?;
+    let config: BTreeMap<String, ConfigValue> = toml::from_str(&contents)
+        .map_err(|e| config_error(format!("Failed to parse {}: {}", path.display(), e)))?;
+
+    let mut merged: Vec<OsString> = args.iter().take(1).cloned().collect();
+    for (key, value) in config {
+        let name = key.replace('_', "-");
+        if name == "config" {
+            return Err(config_error(format!("{} cannot set another config file", path.display())));
+        }
+        if matches.occurrences_of(&name) > 0 {
+            continue;
+        }
+        match value {
+            ConfigValue::Flag(true) => merged.push(format!("--{}", name).into()),
+            ConfigValue::Flag(false) => {}
+            ConfigValue::Number(value) => merged.push(format!("--{}={}", name, value).into()),
+            ConfigValue::Text(value) => merged.push(format!("--{}={}", name, value).into()),
+            ConfigValue::List(values) => merged.extend(
+                values
+                    .iter()
+                    .map(|value| format!("--{}={}", name, value).into()),
+            ),
+        }
+    }
+    merged.extend(args.into_iter().skip(1));
+    Opt::from_iter_safe(merged)
+}
+
+/// Loads the TLS identity the server presents to clients.
+///
+/// # Arguments
+/// * `cert` - The path of the PEM-encoded certificate chain, if serving over TLS.
+/// * `key` - The path of the PEM-encoded private key, if serving over TLS.
+///
+/// # Returns
+/// The TLS configuration if both paths are given, `None` if neither is, or an error message if
+/// a file cannot be read.
+fn load_tls_config(
+    cert: Option<&Path>, key: Option<&Path>,
+) -> Result<Option<ServerTlsConfig>, String> {
+    let (cert, key) = match (cert, key) {
+        (Some(cert), Some(key)) => (cert, key),
+        (None, None) => return Ok(None),
+        _ => return Err("--tls-cert and --tls-key must be given together".into()),
+    };
+    let read = |path: &Path| {
+        std::fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))
+    };
+    let identity = Identity::from_pem(read(cert)?, read(key)?);
+    Ok(Some(ServerTlsConfig::new().identity(identity)))
+}
+
+/// Installs the global logger, which writes log events to stdout.
+///
+/// Events from the `log` macros used throughout the library are forwarded to the logger. In the
+/// JSON format, each line is an object with `timestamp`, `level`, `target` and `message` fields,
+/// along with the fields of any enclosing spans.
+///
+/// # Arguments
+/// * `format` - The format of log lines.
+/// * `level` - The minimum severity of logged events.
+///
+/// # Returns
+/// `Ok(())` if the logger was installed, or an error message if a logger is already installed.
+fn init_logging(format: LogFormat, level: LogLevel) -> Result<(), String> {
+    let builder = tracing_subscriber::fmt().with_max_level(level);
+    match format {
+        LogFormat::Text => builder.try_init(),
+        LogFormat::Json => builder.json().flatten_event(true).try_init(),
+    }
+    .map_err(|e| format!("Failed to install logger: {}", e))
+}
+
+/// Checks that the selected group parameters meet the minimum required security level.
+///
+/// # Arguments
+/// * `level` - The security level offered by the selected group parameters.
+/// * `min_security_bits` - The minimum bits of security required, if any.
+///
+/// # Returns
+/// `Ok(())` if no minimum is set or the level meets it, or an error message otherwise.
+fn check_security_level(
+    level: SecurityLevel, min_security_bits: Option<u32>,
+) -> Result<(), String> {
+    match min_security_bits {
+        Some(min_bits) if level.bits() < min_bits => Err(format!(
+            "Selected group parameters offer {} bits of security, below the required minimum of {}",
+            level.bits(),
+            min_bits
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Shortens the challenges users answer on a discrete log service, if a bit length was provided.
+///
+/// # Arguments
+/// * `auth` - The discrete log service.
+/// * `challenge_bits` - The bit length of the challenges, if configured.
+///
+/// # Returns
+/// The service, or an error message if the bit length does not suit the group parameters.
+fn with_challenge_bits(
+    auth: ZkAuth<DiscreteLogChaumPedersen, BigUint, BigUint>, challenge_bits: Option<u32>,
+) -> Result<ZkAuth<DiscreteLogChaumPedersen, BigUint, BigUint>, String> {
+    let Some(challenge_bits) = challenge_bits else {
+        return Ok(auth);
+    };
+    let auth = auth
+        .with_challenge_config(DiscreteLogChaumPedersenConfig { challenge_bits })
+        .map_err(|e| format!("Invalid challenge bits: {}", e))?;
+    println!("      🎲 challenge bits: {}", challenge_bits);
+    Ok(auth)
+}
+
+/// Enables mutual authentication on the service if a server secret was provided.
+///
+/// The server's public values are printed so they can be handed to clients, which pin them to
+/// verify the server's proofs.
+///
+/// # Arguments
+/// * `auth` - The authentication service.
+/// * `secret` - The hex-encoded server secret, if any.
+///
+/// # Returns
+/// The service, with mutual authentication enabled if a secret was provided, or an error
+/// message if the secret cannot be decoded.
+fn with_server_secret<C, T, S>(
+    auth: ZkAuth<C, T, S>, secret: Option<&str>,
+) -> Result<ZkAuth<C, T, S>, String>
+where
+    C: ChaumPedersen<GroupParameters = GroupParams<T>, CommitParameters = (T, T, T, T), Secret = S>,
+    T: Send + Sync + Clone + ByteConvertible<T> + 'static,
+    S: Send + Sync + Clone + ByteConvertible<S> + 'static,
+{
+    let Some(secret) = secret else {
+        return Ok(auth);
+    };
+    let x = S::from_hex(secret).map_err(|e| format!("Invalid server secret: {}", e))?;
+    let ((y1, y2, _, _), _) = C::commitment(&auth.params(), &x);
+    println!("      🤝 mutual authentication enabled");
+    println!("      🔐 server y1: {}", T::to_hex(&y1));
+    println!("      🔐 server y2: {}", T::to_hex(&y2));
+    Ok(auth.with_server_secret(x))
+}
+
+/// Signs the sessions issued by the service if a signing key was provided.
+///
+/// The public key is printed so it can be handed to services verifying the session tokens.
+///
+/// # Arguments
+/// * `auth` - The authentication service.
+/// * `key` - The hex-encoded 32-byte Ed25519 signing key, if any.
+///
+/// # Returns
+/// The service, signing sessions if a key was provided, or an error message if the key cannot
+/// be decoded.
+fn with_signing_key<C, T, S>(
+    auth: ZkAuth<C, T, S>, key: Option<&str>,
+) -> Result<ZkAuth<C, T, S>, String>
+where
+    T: Send + Sync + Clone + ByteConvertible<T> + 'static,
+    S: Send + Sync + Clone + ByteConvertible<S> + 'static,
+{
+    let Some(key) = key else {
+        return Ok(auth);
+    };
+    let key = decode_signing_key(key)?;
+    println!("      ✍️ session signing key: {}", hex::encode(key.verifying_key().as_bytes()));
+    Ok(auth.with_signing_key(key))
+}
+
+/// Decodes a hex-encoded 32-byte Ed25519 signing key.
+///
+/// # Arguments
+/// * `key` - The hex-encoded key.
+///
+/// # Returns
+/// The signing key, or an error message if the key cannot be decoded.
+fn decode_signing_key(key: &str) -> Result<SigningKey, String> {
+    let seed: [u8; 32] = hex::decode(key)
+        .ok()
+        .and_then(|seed| seed.try_into().ok())
+        .ok_or("Invalid signing key: expected 32 hex-encoded bytes")?;
+    Ok(SigningKey::from_bytes(&seed))
+}
+
+/// Sets the form of the session IDs issued by the service.
+///
+/// # Arguments
+/// * `auth` - The authentication service.
+/// * `token_type` - The selected form of session IDs.
+/// * `signing_key` - The hex-encoded Ed25519 signing key, which "jwt" tokens are signed with.
+/// * `paseto_key` - The hex-encoded 32-byte key, which "paseto" tokens are encrypted with.
+///
+/// # Returns
+/// The service, issuing session IDs of the selected form, or an error message if the key the
+/// form requires is missing or cannot be decoded.
+fn with_session_token_type<C, T, S>(
+    auth: ZkAuth<C, T, S>, token_type: &SessionTokenType, signing_key: Option<&str>,
+    paseto_key: Option<&str>,
+) -> Result<ZkAuth<C, T, S>, String>
+where
+    T: Send + Sync + Clone + ByteConvertible<T> + 'static,
+    S: Send + Sync + Clone + ByteConvertible<S> + 'static,
+{
+    if paseto_key.is_some() && *token_type != SessionTokenType::Paseto {
+        return Err("--paseto-key only applies to the paseto session token type".into());
+    }
+    let format = match token_type {
+        SessionTokenType::Uuid => return Ok(auth),
+        SessionTokenType::Jwt => {
+            let key = signing_key.ok_or("The jwt session token type requires --signing-key")?;
+            SessionTokenFormat::Jwt(decode_signing_key(key)?)
+        }
+        SessionTokenType::Paseto => {
+            let key = paseto_key.ok_or("The paseto session token type requires --paseto-key")?;
+            hex::decode(key)
+                .ok()
+                .and_then(|key| SessionTokenFormat::paseto(&key))
+                .ok_or("Invalid PASETO key: expected 32 hex-encoded bytes")?
+        }
+    };
+    println!("      🎟️ session tokens: {}", token_type);
+    Ok(auth.with_session_token_format(format))
+}
+
+/// Records the transcript of every authentication attempt in a file, if one was provided.
+///
+/// # Arguments
+/// * `auth` - The authentication service.
+/// * `path` - The path to the JSON lines transcript log, if any.
+///
+/// # Returns
+/// The service, logging transcripts if a path was provided, or an error message if the file
+/// cannot be opened for appending.
+fn with_transcript_log<C, T, S>(
+    auth: ZkAuth<C, T, S>, path: Option<&Path>,
+) -> Result<ZkAuth<C, T, S>, String>
+where
+    T: Send + Sync + Clone + ByteConvertible<T> + 'static,
+    S: Send + Sync + Clone + ByteConvertible<S> + 'static,
+{
+    let Some(path) = path else {
+        return Ok(auth);
+    };
+    let log = JsonlTranscriptLog::open(path)
+        .map_err(|e| format!("Failed to open transcript log {}: {}", path.display(), e))?;
+    println!("      📜 transcript log: {}", path.display());
+    Ok(auth.with_transcript_log(Box::new(log)))
+}
+
+/// Sheds requests beyond a maximum number in flight, if one was provided.
+///
+/// # Arguments
+/// * `auth` - The authentication service.
+/// * `max_concurrent_requests` - The maximum number of requests handled at a time, if limited.
+fn with_load_shedding<C, T, S>(
+    auth: ZkAuth<C, T, S>, max_concurrent_requests: Option<usize>,
+) -> ZkAuth<C, T, S>
+where
+    T: Send + Sync + Clone + ByteConvertible<T> + 'static,
+    S: Send + Sync + Clone + ByteConvertible<S> + 'static,
+{
+    let Some(max_concurrent_requests) = max_concurrent_requests else {
+        return auth;
+    };
+    println!("      🚦 max concurrent requests: {}", max_concurrent_requests);
+    auth.with_load_shedding(max_concurrent_requests)
+}
+
+/// Hands out resumption tokens with challenges, if enabled.
+///
+/// The tokens are sealed with a key generated at startup, so they do not survive a restart.
+///
+/// # Arguments
+/// * `auth` - The authentication service.
+/// * `enabled` - Whether authentications may be resumed.
+fn with_resumption<C, T, S>(auth: ZkAuth<C, T, S>, enabled: bool) -> ZkAuth<C, T, S>
+where
+    T: Send + Sync + Clone + ByteConvertible<T> + 'static,
+    S: Send + Sync + Clone + ByteConvertible<S> + 'static,
+{
+    if !enabled {
+        return auth;
+    }
+    println!("      ⏯️ resumable authentications enabled");
+    auth.with_resumption(ResumeTokenSealer::generate(DEFAULT_RESUME_TOKEN_TTL))
+}
+
+/// Rejects reused commitments within a window, if one was provided.
+///
+/// # Arguments
+/// * `auth` - The authentication service.
+/// * `window_secs` - How long, in seconds, a user's commitments are remembered, if at all.
+fn with_commitment_reuse_guard<C, T, S>(
+    auth: ZkAuth<C, T, S>, window_secs: Option<u64>,
+) -> ZkAuth<C, T, S>
+where
+    T: Send + Sync + Clone + ByteConvertible<T> + 'static,
+    S: Send + Sync + Clone + ByteConvertible<S> + 'static,
+{
+    let Some(window_secs) = window_secs else {
+        return auth;
+    };
+    println!("      ♻️ commitment reuse window: {}s", window_secs);
+    auth.with_commitment_reuse_guard(Duration::from_secs(window_secs))
+}
+
+/// Takes the service's challenges from a drand beacon or a trusted endpoint, if a URL was
+/// provided.
+///
+/// # Arguments
+/// * `auth` - The authentication service.
+/// * `drand_url` - The base URL of the drand beacon to mix into challenges, if any.
+/// * `challenge_url` - The URL of the endpoint supplying challenges, if any.
+///
+/// # Returns
+/// The service, or an error message if a URL is invalid.
+fn with_challenge_source<C, T, S>(
+    auth: ZkAuth<C, T, S>, drand_url: Option<&str>, challenge_url: Option<&str>,
+) -> Result<ZkAuth<C, T, S>, String>
+where
+    T: Send + Sync + Clone + ByteConvertible<T> + 'static,
+    S: Send + Sync + Clone + ByteConvertible<S> + TranscriptChallenge<GroupParams<T>> + 'static,
+{
+    let parse = |url: &str| {
+        reqwest::Url::parse(url)
+            .map(|_| url.to_string())
+            .map_err(|e| format!("Invalid URL {}: {}", url, e))
+    };
+    if let Some(url) = drand_url {
+        println!("      📡 drand beacon: {}", url);
+        return Ok(auth.with_drand_url(parse(url)?));
+    }
+    if let Some(url) = challenge_url {
+        println!("      🪙 challenge source: {}", url);
+        return Ok(auth.with_challenge_source(ExternalChallengeSource::new(parse(url)?)));
+    }
+    Ok(auth)
+}
+
+/// Preloads the users listed in a seed file into the service, if one was provided.
+///
+/// # Arguments
+/// * `auth` - The authentication service.
+/// * `path` - The path to the JSON seed file, if any.
+///
+/// # Returns
+/// The service with the seeded users registered, or an error message if the file cannot be
+/// read, holds invalid data or lists a user twice.
+async fn with_seed_users<C, T, S>(
+    auth: ZkAuth<C, T, S>, path: Option<&Path>,
+) -> Result<ZkAuth<C, T, S>, String>
+where
+    T: Send + Sync + Clone + ByteConvertible<T> + 'static,
+    S: Send + Sync + Clone + ByteConvertible<S> + 'static,
+{
+    let Some(path) = path else {
+        return Ok(auth);
+    };
+    let users = load_seed_users::<T>(path)?;
+    let count = users.len();
+    auth.seed_users(users).await?;
+    println!("      🌱 seeded users: {}", count);
+    Ok(auth)
+}
+
+/// Serves the authentication service until a SIGTERM is received, then drains in-flight requests.
+///
+/// # Arguments
+/// * `auth` - The authentication service implementation to serve.
+/// * `addr` - The socket address to bind the server to.
+/// * `options` - How the server handles requests.
+async fn serve<A: Auth + Admin + Clone>(
+    auth: A, addr: SocketAddr, options: ServeOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut sigterm = signal(SignalKind::terminate())?;
+    let incoming =
+        TcpIncoming::new(addr, true, None).map_err(|e| e as Box<dyn std::error::Error>)?;
+    let sigterm = async move {
+        sigterm.recv().await;
+        println!("🛑 SIGTERM received, draining in-flight requests");
+    };
+    Ok(serve_until(auth, options, incoming, sigterm).await?)
+}
+
+/// Serves the authentication service until `shutdown` completes, then drains in-flight requests.
+///
+/// Once `shutdown` completes the health status of the authentication service is set to
+/// `NOT_SERVING`, the server stops accepting new connections, and in-flight RPCs are given up to
+/// `shutdown_timeout` to complete before the server exits.
+///
+/// # Arguments
+/// * `auth` - The authentication service implementation to serve.
+/// * `options` - How the server handles requests.
+/// * `incoming` - The connections to serve.
+/// * `shutdown` - Completes when the server should shut down.
+async fn serve_until<A: Auth + Admin + Clone>(
+    auth: A, options: ServeOptions, incoming: TcpIncoming, shutdown: impl Future<Output = ()>,
+) -> Result<(), tonic::transport::Error> {
+    let ServeOptions {
+        shutdown_timeout,
+        rpc_timeout,
+        tls,
+        admin_token,
+        register_api_key,
+    } = options;
+    let (mut health_reporter, health_service) = tonic_health::server::health_reporter();
+    health_reporter.set_serving::<AuthServer<A>>().await;
+
+    let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+    let mut builder = Server::builder();
+    if let Some(tls) = tls {
+        builder = builder.tls_config(tls)?;
+    }
+    let server = builder
+        .layer(option_layer(rpc_timeout.map(rpc_timeout_layer)))
+        .add_service(health_service)
+        .add_optional_service(
+            register_api_key
+                .is_none()
+                .then(|| AuthServer::new(auth.clone())),
+        )
+        .add_optional_service(
+            register_api_key.map(|key| RegisterApiKey::new(AuthServer::new(auth.clone()), key)),
+        )
+        .add_optional_service(
+            admin_token
+                .map(|token| AdminServer::with_interceptor(auth, admin_token_interceptor(token))),
+        )
+        .serve_with_incoming_shutdown(incoming, async {
+            shutdown_rx.await.ok();
+        });
+    tokio::pin!(server);
 
+    tokio::select! {
+        result = &mut server => return result,
+        _ = shutdown => {}
+    }
+
+    health_reporter.set_not_serving::<AuthServer<A>>().await;
+    shutdown_tx.send(()).ok();
+
+    match tokio::time::timeout(shutdown_timeout, server).await {
+        Ok(result) => result?,
+        Err(_) => println!("⏰ Shutdown timeout elapsed, exiting with requests still in flight"),
+    }
     Ok(())
 }
+
+#[cfg(test)]
+#[path = "../../tests/common/mod.rs"]
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::{Gate, GatedDao};
+    use curve25519_dalek::Scalar;
+    use zk_pass::chaum_pedersen::constants::EC25519_GROUP_PARAMS;
+    use zk_pass::client::execute_protocol;
+    use zk_pass::rand::RandomGenerator;
+
+    /// Loads the options from the arguments with a configuration file holding `contents`.
+    fn load_with_config(name: &str, contents: &str, args: &[&str]) -> clap::Result<Opt> {
+        let path = std::env::temp_dir().join(format!(
+            "zk_pass_server_{}_{}.toml",
+            name,
+            std::process::id()
+        ));
+        std::fs::write(&path, contents).unwrap();
+        let config = [OsString::from("server"), "--config".into(), path.clone().into()];
+        let result = load_options(config.into_iter().chain(args.iter().map(OsString::from)));
+        std::fs::remove_file(&path).unwrap();
+        result
+    }
+
+    #[test]
+    fn test_config_file_with_command_line_override() {
+        let config = r#"
+            host = "0.0.0.0"
+            port = 6000
+            type = "elliptic_curve"
+            curve = "pallas"
+            enable_resume = true
+            bind_challenge_to_ip = false
+            deny_cidr = ["10.0.0.0/8", "192.168.0.0/16"]
+            session_backend = "redis"
+            rpc_timeout_secs = 5
+        "#;
+        let opt = load_with_config("override", config, &["-p", "7000", "--curve=vesta"]).unwrap();
+
+        // Options given on the command line take precedence over the file.
+        assert_eq!(opt.port, 7000);
+        assert_eq!(opt.curve, EllipticCurveType::Vesta);
+        assert_eq!(opt.host, "0.0.0.0");
+        assert_eq!(opt.r#type, ChaumPedersenType::EllipticCurve);
+        assert!(opt.enable_resume);
+        assert!(!opt.bind_challenge_to_ip);
+        assert_eq!(
+            opt.deny_cidrs,
+            ["10.0.0.0/8".parse().unwrap(), "192.168.0.0/16".parse().unwrap()]
+        );
+        assert_eq!(opt.session_backend, SessionBackend::Redis);
+        assert_eq!(opt.rpc_timeout_secs, Some(5));
+        // Options set nowhere keep their defaults.
+        assert_eq!(opt.modp, RfcModpType::Rfc5114Modp_1024_160);
+        assert_eq!(opt.shutdown_timeout_secs, 30);
+
+        // A repeated option on the command line replaces the file's values.
+        let opt = load_with_config("repeated", config, &["--deny-cidr", "127.0.0.0/8"]).unwrap();
+        assert_eq!(opt.deny_cidrs, ["127.0.0.0/8".parse::<IpNet>().unwrap()]);
+    }
+
+    #[test]
+    fn test_config_file_rejects_invalid_options() {
+        assert!(load_with_config("unknown", "no_such_option = 1", &[]).is_err());
+        assert!(load_with_config("invalid", "port = \"not a port\"", &[]).is_err());
+        assert!(load_with_config("syntax", "port = ", &[]).is_err());
+        assert!(load_with_config("nested", "config = \"other.toml\"", &[]).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_drains_in_flight_authentication() {
+        let gate = Gate::default();
+        let auth = ZkAuth::<Curve25519ChaumPedersen, _, _, _>::with_dao(
+            EC25519_GROUP_PARAMS.to_owned(),
+            GatedDao::new(gate.clone()),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let incoming = TcpIncoming::from_listener(listener, true, None).unwrap();
+        let options = ServeOptions {
+            shutdown_timeout: Duration::from_secs(60),
+            rpc_timeout: None,
+            tls: None,
+            admin_token: None,
+            register_api_key: None,
+        };
+        let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+        let server = tokio::spawn(serve_until(auth, options, incoming, async {
+            shutdown_rx.await.ok();
+        }));
+
+        // The authentication is held at the store while the server is told to shut down.
+        let mut client = common::connect(addr).await;
+        let params = EC25519_GROUP_PARAMS.to_owned();
+        let x = Scalar::generate_random().unwrap();
+        let authentication = tokio::spawn(async move {
+            execute_protocol::<Curve25519ChaumPedersen, _, _>(&params, &x, "alice", &mut client)
+                .await
+                .map(|result| result.verified)
+                .map_err(|e| e.to_string())
+        });
+        gate.entered().await;
+        shutdown_tx.send(()).unwrap();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(!server.is_finished(), "Server exited with a request in flight");
+
+        gate.open();
+        assert_eq!(authentication.await.unwrap(), Ok(true));
+        server.await.unwrap().unwrap();
+    }
+}
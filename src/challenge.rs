@@ -0,0 +1,266 @@
+//! # Challenge Source Module
+//!
+//! This module abstracts where the challenges a service issues come from. By default the
+//! verifier draws them itself, but in some threat models it should not: a [`ChallengeSource`]
+//! can instead mix in a public randomness beacon or take the challenge from a trusted third
+//! party, as in public-coin setups.
+
+use crate::chaum_pedersen::transcript::{derive_beacon_challenge, TranscriptChallenge};
+use crate::chaum_pedersen::{ChaumPedersen, GroupParams};
+use crate::client::ZkPassError;
+use crate::rand::{fetch_drand_randomness, ProtocolRng};
+use async_trait::async_trait;
+use log::warn;
+use merlin::Transcript;
+use rand_core::RngCore;
+use serde::Deserialize;
+use std::marker::PhantomData;
+
+/// Supplies the random challenges a service issues to users.
+///
+/// # Type Parameters
+/// * `T` - The type of the group elements.
+/// * `S` - The type of the challenges.
+#[async_trait]
+pub trait ChallengeSource<T, S>: Send + Sync {
+    /// Supplies a fresh challenge.
+    ///
+    /// # Arguments
+    /// * `params` - The group parameters of the user the challenge is issued to.
+    ///
+    /// # Returns
+    /// The challenge, or an error such as `ZkPassError::ChallengeSourceUnavailable` if the
+    /// source cannot be reached.
+    async fn challenge(&self, params: &GroupParams<T>) -> Result<S, ZkPassError>;
+
+    /// Whether the challenges are public coins the verifier must have no say in.
+    ///
+    /// A service issues public coins without a nonce of its own, so that the effective
+    /// challenge a user answers is a function of the source's output alone.
+    fn is_public_coin(&self) -> bool {
+        false
+    }
+}
+
+/// A [`ChallengeSource`] drawing challenges locally with [`ChaumPedersen::challenge`], as a
+/// service does unless configured otherwise.
+///
+/// # Type Parameters
+/// * `C` - The Chaum-Pedersen protocol the challenges are drawn for.
+pub struct RandomChallengeSource<C> {
+    _protocol: PhantomData<fn() -> C>,
+}
+
+impl<C> RandomChallengeSource<C> {
+    /// Creates the source.
+    pub fn new() -> Self {
+        Self {
+            _protocol: PhantomData,
+        }
+    }
+}
+
+impl<C> Default for RandomChallengeSource<C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl<C, T, S> ChallengeSource<T, S> for RandomChallengeSource<C>
+where
+    C: ChaumPedersen<GroupParameters = GroupParams<T>, Challenge = S>,
+    T: Sync,
+{
+    async fn challenge(&self, params: &GroupParams<T>) -> Result<S, ZkPassError> {
+        Ok(C::challenge(params))
+    }
+}
+
+/// A [`ChallengeSource`] generating challenges with a function, e.g. to issue shorter
+/// challenges.
+pub(crate) struct FnChallengeSource<F>(pub(crate) F);
+
+#[async_trait]
+impl<F, T, S> ChallengeSource<T, S> for FnChallengeSource<F>
+where
+    F: Fn(&GroupParams<T>) -> S + Send + Sync,
+    T: Sync,
+{
+    async fn challenge(&self, params: &GroupParams<T>) -> Result<S, ZkPassError> {
+        Ok((self.0)(params))
+    }
+}
+
+/// A [`ChallengeSource`] mixing the latest round of a drand randomness beacon with local
+/// randomness, as defence in depth against a weak local random number generator.
+///
+/// The challenges are not public coins: the latest round is known to anyone before a user
+/// commits, so a challenge determined by the beacon alone could be computed in advance and
+/// answered without the secret. The local randomness keeps challenges unpredictable, and the
+/// verifier still chooses them, so the service issues them with a nonce of its own.
+pub struct DrandChallengeSource {
+    url: String,
+}
+
+impl DrandChallengeSource {
+    /// Creates a source fetching from the given beacon.
+    ///
+    /// # Arguments
+    /// * `url` - The base URL of the beacon's HTTP API, e.g. `https://api.drand.sh`.
+    pub fn new(url: impl Into<String>) -> Self {
+        Self { url: url.into() }
+    }
+}
+
+#[async_trait]
+impl<T, S> ChallengeSource<T, S> for DrandChallengeSource
+where
+    T: Sync,
+    S: TranscriptChallenge<GroupParams<T>>,
+{
+    async fn challenge(&self, params: &GroupParams<T>) -> Result<S, ZkPassError> {
+        let beacon = fetch_drand_randomness(&self.url).await?;
+        let mut local = [0u8; 32];
+        ProtocolRng.fill_bytes(&mut local);
+        Ok(derive_beacon_challenge(params, &local, &beacon))
+    }
+}
+
+/// A random coin supplied by an [`ExternalChallengeSource`].
+#[derive(Deserialize)]
+struct ExternalCoin {
+    randomness: String,
+}
+
+/// A [`ChallengeSource`] taking challenges from a trusted third party, so the verifier has no
+/// say in them.
+///
+/// Each challenge is derived from a fresh coin fetched with a GET request to the endpoint,
+/// which answers with a JSON object whose `randomness` field holds 32 hex-encoded bytes. The
+/// challenges are public coins, so a service issues them without a nonce of its own.
+pub struct ExternalChallengeSource {
+    url: String,
+}
+
+impl ExternalChallengeSource {
+    /// Creates a source fetching from the given endpoint.
+    ///
+    /// # Arguments
+    /// * `url` - The URL of the endpoint supplying the coins.
+    pub fn new(url: impl Into<String>) -> Self {
+        Self { url: url.into() }
+    }
+
+    /// Fetches a fresh coin from the endpoint.
+    async fn fetch_coin(&self) -> Result<[u8; 32], ZkPassError> {
+        let coin: ExternalCoin = reqwest::get(&self.url)
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| {
+                warn!("Failed to fetch a challenge from {}: {}", self.url, e);
+                ZkPassError::ChallengeSourceUnavailable
+            })?
+            .json()
+            .await
+            .map_err(|e| {
+                warn!("Malformed challenge from {}: {}", self.url, e);
+                ZkPassError::ChallengeSourceUnavailable
+            })?;
+        hex::decode(&coin.randomness)
+            .ok()
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or(ZkPassError::ChallengeSourceUnavailable)
+    }
+}
+
+#[async_trait]
+impl<T, S> ChallengeSource<T, S> for ExternalChallengeSource
+where
+    T: Sync,
+    S: TranscriptChallenge<GroupParams<T>>,
+{
+    async fn challenge(&self, params: &GroupParams<T>) -> Result<S, ZkPassError> {
+        let coin = self.fetch_coin().await?;
+        let mut transcript = Transcript::new(b"zk_pass.chaum_pedersen");
+        transcript.append_message(b"dom-sep", b"zk_pass.external_challenge");
+        transcript.append_message(b"coin", &coin);
+        Ok(S::from_transcript(&mut transcript, params))
+    }
+
+    fn is_public_coin(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chaum_pedersen::constants::{
+        EC25519_GROUP_PARAMS, RFC5114_MODP_2048_256_BIT_PARAMS,
+    };
+    use crate::chaum_pedersen::curve25519::Curve25519ChaumPedersen;
+    use crate::rand::tests::mock_endpoint;
+    use curve25519_dalek::{RistrettoPoint, Scalar};
+    use num_bigint::BigUint;
+
+    #[tokio::test]
+    async fn test_random_challenge_source() {
+        let source = RandomChallengeSource::<Curve25519ChaumPedersen>::new();
+        let params = EC25519_GROUP_PARAMS.to_owned();
+        let c1: Scalar = source.challenge(&params).await.unwrap();
+        let c2: Scalar = source.challenge(&params).await.unwrap();
+        assert_ne!(c1, c2);
+    }
+
+    #[tokio::test]
+    async fn test_external_challenge_source() {
+        let coin = |byte: u8| format!(r#"{{"randomness":"{}"}}"#, hex::encode([byte; 32]));
+        let params = RFC5114_MODP_2048_256_BIT_PARAMS.to_owned();
+
+        // The same coin yields the same challenge, within the group order.
+        let source = ExternalChallengeSource::new(mock_endpoint(200, coin(1)).await);
+        let c: BigUint = source.challenge(&params).await.unwrap();
+        assert!(c < params.q);
+        assert_eq!(c, source.challenge(&params).await.unwrap());
+        let other = ExternalChallengeSource::new(mock_endpoint(200, coin(2)).await);
+        assert_ne!(c, other.challenge(&params).await.unwrap());
+
+        for (status, body) in [(200, r#"{"randomness":"00"}"#.to_string()), (500, coin(1))] {
+            let source = ExternalChallengeSource::new(mock_endpoint(status, body).await);
+            let result: Result<BigUint, _> = source.challenge(&params).await;
+            assert_eq!(result, Err(ZkPassError::ChallengeSourceUnavailable));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fn_challenge_source() {
+        let source = FnChallengeSource(|_: &GroupParams<RistrettoPoint>| Scalar::from(7u64));
+        let params = EC25519_GROUP_PARAMS.to_owned();
+        assert_eq!(source.challenge(&params).await, Ok(Scalar::from(7u64)));
+    }
+
+    #[cfg(feature = "deterministic-tests")]
+    #[tokio::test]
+    async fn test_drand_challenge_source() {
+        use crate::rand::set_test_seed;
+        use crate::rand::tests::drand_round;
+
+        async fn challenge(source: &DrandChallengeSource, seed: u64) -> BigUint {
+            set_test_seed(seed);
+            let params = RFC5114_MODP_2048_256_BIT_PARAMS.to_owned();
+            source.challenge(&params).await.unwrap()
+        }
+        let source = DrandChallengeSource::new(mock_endpoint(200, drand_round(&[7; 96])).await);
+        let other = DrandChallengeSource::new(mock_endpoint(200, drand_round(&[8; 96])).await);
+
+        // With the local randomness fixed, the challenge is determined by the beacon round.
+        let c = challenge(&source, 1).await;
+        assert!(c < RFC5114_MODP_2048_256_BIT_PARAMS.q);
+        assert_eq!(c, challenge(&source, 1).await);
+        assert_ne!(c, challenge(&other, 1).await);
+        // Within one round, the local randomness keeps challenges apart.
+        assert_ne!(c, challenge(&source, 2).await);
+        assert!(!ChallengeSource::<BigUint, BigUint>::is_public_coin(&source));
+    }
+}
@@ -1,28 +1,46 @@
 use crate::{chaum_pedersen::GroupParams, conversion::ByteConvertible};
+use alloc::borrow::ToOwned;
+use bp256::r1::ProjectivePoint as BrainpoolPoint;
+use core::str::FromStr;
 use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
 use curve25519_dalek::RistrettoPoint;
+use jubjub::SubgroupPoint as JubjubPoint;
 use lazy_static::lazy_static;
 use num_bigint::BigUint;
 use pasta_curves::pallas::Point as PallasPoint;
 use pasta_curves::vesta::Point as VestaPoint;
-use std::str::FromStr;
+
+#[allow(deprecated)]
+pub use legacy::RFC5114_MODP_1024_160_BIT_PARAMS;
+
+// The 1024-bit group lives in its own module so the deprecation only warns at use sites.
+#[allow(deprecated)]
+mod legacy {
+    use super::*;
+
+    // RFC5114_GROUP_PARAMETERS are constant Prime Order Subgroups as defined in RFC5114
+    // Reference: https://www.rfc-editor.org/rfc/rfc5114.html#section-2
+    lazy_static! {
+        // Group parameters for 1024-bit MODP group with 160-bit Prime Order Subgroup
+        #[deprecated(
+            note = "offers only ~80 bits of security; use RFC5114_MODP_2048_224_BIT_PARAMS or RFC5114_MODP_2048_256_BIT_PARAMS"
+        )]
+        pub static ref RFC5114_MODP_1024_160_BIT_PARAMS: GroupParams<BigUint> = {
+            GroupParams {
+                p: BigUint::from_bytes_be(&hex::decode("B10B8F96A080E01DDE92DE5EAE5D54EC52C99FBCFB06A3C69A6A9DCA52D23B616073E28675A23D189838EF1E2EE652C013ECB4AEA906112324975C3CD49B83BFACCBDD7D90C4BD7098488E9C219A73724EFFD6FAE5644738FAA31A4FF55BCCC0A151AF5F0DC8B4BD45BF37DF365C1A65E68CFDA76D4DA708DF1FB2BC2E4A4371").unwrap()),
+                q: BigUint::from_bytes_be(
+                    &hex::decode("F518AA8781A8DF278ABA4E7D64B7CB9D49462353").unwrap(),
+                ),
+                g: BigUint::from_bytes_be(&hex::decode("A4D1CBD5C3FD34126765A442EFB99905F8104DD258AC507FD6406CFF14266D31266FEA1E5C41564B777E690F5504F213160217B4B01B886A5E91547F9E2749F4D7FBD7D3B9A92EE1909D0D2263F80A76A6A24C087A091F531DBF0A0169B6A28AD662A4D18E73AFA32D779D5918D08BC8858F4DCEF97C2A24855E6EEB22B3B2E5").unwrap()),
+                h: BigUint::from_bytes_be(&hex::decode("4BFE69CCAB1878A8B2DD9B4F83FFAC8D659EFA94698852F75A47EA4F7545230AD20FFB306DE1C24B5856E0D2C4798B3CC65A0307538B6E431CB94EB62892B0296B281D31EA58A9CC9D5917BF4BAD70AE5B1363F63A9164A1442DA843FCFC3752B366BC3DE27819C41C44426C80203AB8BB511D93AEA55AD70CC31A5A989FC413").unwrap()),
+            }
+        };
+    }
+}
 
 // RFC5114_GROUP_PARAMETERS are constant Prime Order Subgroups as defined in RFC5114
-// Reference: https://www.rfc-editor.org/rfc/rfc5114.html#section-2
 lazy_static! {
 
-    // Group parameters for 1024-bit MODP group with 160-bit Prime Order Subgroup
-    pub static ref RFC5114_MODP_1024_160_BIT_PARAMS: GroupParams<BigUint> = {
-        GroupParams {
-            p: BigUint::from_bytes_be(&hex::decode("B10B8F96A080E01DDE92DE5EAE5D54EC52C99FBCFB06A3C69A6A9DCA52D23B616073E28675A23D189838EF1E2EE652C013ECB4AEA906112324975C3CD49B83BFACCBDD7D90C4BD7098488E9C219A73724EFFD6FAE5644738FAA31A4FF55BCCC0A151AF5F0DC8B4BD45BF37DF365C1A65E68CFDA76D4DA708DF1FB2BC2E4A4371").unwrap()),
-            q: BigUint::from_bytes_be(
-                &hex::decode("F518AA8781A8DF278ABA4E7D64B7CB9D49462353").unwrap(),
-            ),
-            g: BigUint::from_bytes_be(&hex::decode("A4D1CBD5C3FD34126765A442EFB99905F8104DD258AC507FD6406CFF14266D31266FEA1E5C41564B777E690F5504F213160217B4B01B886A5E91547F9E2749F4D7FBD7D3B9A92EE1909D0D2263F80A76A6A24C087A091F531DBF0A0169B6A28AD662A4D18E73AFA32D779D5918D08BC8858F4DCEF97C2A24855E6EEB22B3B2E5").unwrap()),
-            h: BigUint::from_bytes_be(&hex::decode("4BFE69CCAB1878A8B2DD9B4F83FFAC8D659EFA94698852F75A47EA4F7545230AD20FFB306DE1C24B5856E0D2C4798B3CC65A0307538B6E431CB94EB62892B0296B281D31EA58A9CC9D5917BF4BAD70AE5B1363F63A9164A1442DA843FCFC3752B366BC3DE27819C41C44426C80203AB8BB511D93AEA55AD70CC31A5A989FC413").unwrap()),
-        }
-    };
-
     // Similarly, defining RFC5114_MODP_2048_224_BIT_PARAMS as a lazy static variable, which contains group parameters for a 2048-bit MODP group with 224-bit Prime Order Subgroup.
     // Reference: https://www.rfc-editor.org/rfc/rfc5114.html#section-3
     pub static ref RFC5114_MODP_2048_224_BIT_PARAMS: GroupParams<BigUint> = {
@@ -49,6 +67,18 @@ lazy_static! {
         }
     };
 
+    // Defining RFC3526_MODP_2048_BIT_PARAMS as a lazy static variable, which contains group parameters for the 2048-bit safe-prime MODP group 14, whose subgroup of quadratic residues has the 2047-bit order q = (p - 1) / 2.
+    // Reference: https://www.rfc-editor.org/rfc/rfc3526.html#section-3
+    // RFC 3526 only fixes the generator g = 2, so h is the square of the SHA-512 digests of "zk_pass.rfc3526_modp_2048.h" followed by the bytes 0 to 4, concatenated and reduced modulo p.
+    pub static ref RFC3526_MODP_2048_BIT_PARAMS: GroupParams<BigUint> = {
+        GroupParams {
+            p: BigUint::from_bytes_be(&hex::decode("FFFFFFFFFFFFFFFFC90FDAA22168C234C4C6628B80DC1CD129024E088A67CC74020BBEA63B139B22514A08798E3404DDEF9519B3CD3A431B302B0A6DF25F14374FE1356D6D51C245E485B576625E7EC6F44C42E9A637ED6B0BFF5CB6F406B7EDEE386BFB5A899FA5AE9F24117C4B1FE649286651ECE45B3DC2007CB8A163BF0598DA48361C55D39A69163FA8FD24CF5F83655D23DCA3AD961C62F356208552BB9ED529077096966D670C354E4ABC9804F1746C08CA18217C32905E462E36CE3BE39E772C180E86039B2783A2EC07A28FB5C55DF06F4C52C9DE2BCBF6955817183995497CEA956AE515D2261898FA051015728E5A8AACAA68FFFFFFFFFFFFFFFF").unwrap()),
+            q: BigUint::from_bytes_be(&hex::decode("7FFFFFFFFFFFFFFFE487ED5110B4611A62633145C06E0E68948127044533E63A0105DF531D89CD9128A5043CC71A026EF7CA8CD9E69D218D98158536F92F8A1BA7F09AB6B6A8E122F242DABB312F3F637A262174D31BF6B585FFAE5B7A035BF6F71C35FDAD44CFD2D74F9208BE258FF324943328F6722D9EE1003E5C50B1DF82CC6D241B0E2AE9CD348B1FD47E9267AFC1B2AE91EE51D6CB0E3179AB1042A95DCF6A9483B84B4B36B3861AA7255E4C0278BA3604650C10BE19482F23171B671DF1CF3B960C074301CD93C1D17603D147DAE2AEF837A62964EF15E5FB4AAC0B8C1CCAA4BE754AB5728AE9130C4C7D02880AB9472D455655347FFFFFFFFFFFFFFF").unwrap()),
+            g: BigUint::from(2u32),
+            h: BigUint::from_bytes_be(&hex::decode("897D420438DC8F6905381A75A7878D9F935D285161CB336C6DF4C05A1A40FBD24D97AD78BF62028A9105D973AFE8C2F68338FF11532B065DAB81F866E82E6E9097BA143D180504304ABAEEDB99C375543D40752B9D39B8C6D4D7844ACC4CCBE02969BE66C3B4F6DBB2CEB5260B6A5F103416FD9DAB6CA5F223B9007D40D60C8C64CD41E6174E2920B664DE2B029F24B7BAF5541D807F9C9E0B2A3792EADD606A7FCC84DF3FEA91489B86934484BB095C3D10CB4B336167CBD200465CE928D8B6DF9268C7DBCAE66A2454F7B6E1941085C36285EEF33969E75CB98DEDB751C4F72BCC56B08E4C16B49D3A4A351594BD06B1CE37F493F1C2FC9CBEB0FBA4AEFE8C").unwrap()),
+        }
+    };
+
     // Defining `EC25519_GROUP_PARAMS` as a lazy static variable. This variable represents the group parameters for the elliptic curve Curve25519, specifically for the Ristretto group.
     pub static ref EC25519_GROUP_PARAMS: GroupParams<RistrettoPoint> = {
         GroupParams::<RistrettoPoint> {
@@ -66,54 +96,49 @@ lazy_static! {
     };
 
     pub static ref PALLAS_GROUP_PARAMS: GroupParams<PallasPoint> = {
-        //use pasta_curves::group::GroupEncoding;
         GroupParams::<PallasPoint> {
-            g: PallasPoint::convert_from(
-                convert(&hex::decode("f9abd1b1a37af310baa363ed031ef5613fb474f1780dc8fc767c2b1480da582b").unwrap()).unwrap()
-            ).unwrap(),
-            h: PallasPoint::convert_from(
-                convert(&hex::decode("8f1339a6e025db7854f67838a42764b870e85e991e7b2e6570c5e5fee6e5c30c").unwrap()).unwrap()
-            ).unwrap(),
-            p: PallasPoint::convert_from(
-                convert(&hex::decode("0000000000000000000000000000000000000000000000000000000000000000").unwrap()).unwrap()
-            ).unwrap(),
-            q: PallasPoint::convert_from(
-                convert(&hex::decode("0000000000000000000000000000000000000000000000000000000000000000").unwrap()).unwrap()
-            ).unwrap(),
+            g: PallasPoint::from_hex("f9abd1b1a37af310baa363ed031ef5613fb474f1780dc8fc767c2b1480da582b").unwrap(),
+            h: PallasPoint::from_hex("8f1339a6e025db7854f67838a42764b870e85e991e7b2e6570c5e5fee6e5c30c").unwrap(),
+            p: PallasPoint::from_hex("0000000000000000000000000000000000000000000000000000000000000000").unwrap(),
+            q: PallasPoint::from_hex("0000000000000000000000000000000000000000000000000000000000000000").unwrap(),
         }
     };
 
     pub static ref VESTA_GROUP_PARAMS: GroupParams<VestaPoint> = {
-        //use pasta_curves::group::GroupEncoding;
         GroupParams::<VestaPoint> {
-            g: VestaPoint::convert_from(
-                convert(&hex::decode("227b13b3f09fbc6312ea3a7d150e9879fc5debc5f19e0433a0d774e7485e7ea3").unwrap()).unwrap()
-            ).unwrap(),
-            h: VestaPoint::convert_from(
-                convert(&hex::decode("33fc580619f0b5fa23a88cb6be070033cfdb0ed10aef7491d2400ea6dd45f5a6").unwrap()).unwrap()
-            ).unwrap(),
-            p: VestaPoint::convert_from(
-                convert(&hex::decode("0000000000000000000000000000000000000000000000000000000000000000").unwrap()).unwrap()
-            ).unwrap(),
-            q: VestaPoint::convert_from(
-                convert(&hex::decode("0000000000000000000000000000000000000000000000000000000000000000").unwrap()).unwrap()
-            ).unwrap(),
+            g: VestaPoint::from_hex("227b13b3f09fbc6312ea3a7d150e9879fc5debc5f19e0433a0d774e7485e7ea3").unwrap(),
+            h: VestaPoint::from_hex("33fc580619f0b5fa23a88cb6be070033cfdb0ed10aef7491d2400ea6dd45f5a6").unwrap(),
+            p: VestaPoint::from_hex("0000000000000000000000000000000000000000000000000000000000000000").unwrap(),
+            q: VestaPoint::from_hex("0000000000000000000000000000000000000000000000000000000000000000").unwrap(),
         }
     };
 
-}
+    // `g` is the standard Jubjub subgroup generator. `h` is the first point obtained by hashing
+    // SHA-256("zk_pass.jubjub.h" || be32(i)) onto the curve and clearing the cofactor, so its
+    // discrete log with respect to `g` is unknown.
+    pub static ref JUBJUB_GROUP_PARAMS: GroupParams<JubjubPoint> = {
+        GroupParams::<JubjubPoint> {
+            g: JubjubPoint::from_hex("cb550cd538ea0cc1138480408e6eaab9b36c613f0dd3f7784fdb6eea837b13d7").unwrap(),
+            h: JubjubPoint::from_hex("58e6962e8eda47a3a79bb685081f663c3cb3afa194a8dfdebbef545de5a2ec96").unwrap(),
+            p: JubjubPoint::from_hex("0100000000000000000000000000000000000000000000000000000000000000").unwrap(),
+            q: JubjubPoint::from_hex("0100000000000000000000000000000000000000000000000000000000000000").unwrap(),
+        }
+    };
+
+    // `g` is the brainpoolP256r1 base point from RFC 5639. `h` is the first point obtained by
+    // hashing SHA-256("zk_pass.brainpool_p256.h" || be32(i)) to an x-coordinate with even y, so
+    // its discrete log with respect to `g` is unknown. The curve has cofactor 1.
+    pub static ref BRAINPOOL_P256_GROUP_PARAMS: GroupParams<BrainpoolPoint> = {
+        GroupParams::<BrainpoolPoint> {
+            g: BrainpoolPoint::from_hex("038bd2aeb9cb7e57cb2c4b482ffc81b7afb9de27e1e3bd23c23a4453bd9ace3262").unwrap(),
+            h: BrainpoolPoint::from_hex("0299b010a334c9b0913e9f3508d85b28b2568628b17877cf277c4cd48d64bbedf1").unwrap(),
+            p: BrainpoolPoint::from_hex("000000000000000000000000000000000000000000000000000000000000000000").unwrap(),
+            q: BrainpoolPoint::from_hex("000000000000000000000000000000000000000000000000000000000000000000").unwrap(),
+        }
+    };
 
-fn convert(vec: &Vec<u8>) -> Result<&[u8; 32], &'static str> {
-    if vec.len() == 32 {
-        let slice: &[u8; 32] = vec
-            .as_slice()
-            .try_into()
-            .expect("Slice with incorrect length");
-        Ok(slice)
-    } else {
-        Err("Vector does not have exactly 32 elements")
-    }
 }
+
 // Implementing the FromStr trait for GroupParams<BigUint>. This allows for creating GroupParams<BigUint> instances from string slices.
 impl FromStr for GroupParams<BigUint> {
     type Err = (); // Defining the error type as a unit type.
@@ -170,3 +195,29 @@ impl FromStr for GroupParams<VestaPoint> {
         }
     }
 }
+
+impl FromStr for GroupParams<JubjubPoint> {
+    type Err = (); // Defining the error type as a unit type.
+
+    // Implementing the from_str method which takes a string slice and returns a Result.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            // Matching the string "jubjub" and returning the corresponding group parameters.
+            "jubjub" => Ok(JUBJUB_GROUP_PARAMS.to_owned()),
+            _ => Err(()), // Returning an error for unrecognized strings.
+        }
+    }
+}
+
+impl FromStr for GroupParams<BrainpoolPoint> {
+    type Err = (); // Defining the error type as a unit type.
+
+    // Implementing the from_str method which takes a string slice and returns a Result.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            // Matching the string "brainpool_p256" and returning the corresponding group parameters.
+            "brainpool_p256" => Ok(BRAINPOOL_P256_GROUP_PARAMS.to_owned()),
+            _ => Err(()), // Returning an error for unrecognized strings.
+        }
+    }
+}
@@ -1,15 +1,24 @@
-use crate::chaum_pedersen::{ChaumPedersen, GroupParams};
-use crate::conversion::ByteConvertible;
-use crate::rand::RandomGenerator;
+use crate::chaum_pedersen::transcript::TranscriptChallenge;
+use crate::chaum_pedersen::{
+    ChaumPedersen, GroupParams, InvalidGroupParams, ValidateGroupParams, VerifyOutcome,
+};
+use crate::conversion::{ByteConvertible, ConversionError};
+use crate::rand::{ProtocolRng, RandomGenerator};
+use alloc::vec::Vec;
 use curve25519_dalek::ristretto::CompressedRistretto;
 use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::traits::Identity;
 use curve25519_dalek::RistrettoPoint;
-use rand::rngs::OsRng;
-use std::error::Error;
+use merlin::Transcript;
 
 /// A struct representing the Chaum-Pedersen protocol specialized for elliptic curve groups.
 /// This protocol is used for demonstrating knowledge of a secret in a zero-knowledge manner.
 /// The elliptic curve used in this implementation is based on Ristretto points.
+///
+/// Ristretto points are opaque by design and do not expose affine coordinates, so unlike the
+/// Pasta backends they do not implement
+/// [`AffineCoordinates`](crate::chaum_pedersen::AffineCoordinates); compare their compressed
+/// encodings instead.
 pub struct Curve25519ChaumPedersen {}
 
 /// Implementing the ChaumPedersen trait for EllipticCurveChaumPedersen.
@@ -56,7 +65,7 @@ impl ChaumPedersen for Curve25519ChaumPedersen {
     {
         let y1 = params.g * x; // Calculate y1 = g * x.
         let y2 = params.h * x; // Calculate y2 = h * x.
-        let mut rng = OsRng; // Create a random number generator.
+        let mut rng = ProtocolRng; // Create a random number generator.
         let k = Scalar::random(&mut rng); // Generate a random scalar k.
         let r1 = params.g * k; // Calculate r1 = g * k.
         let r2 = params.h * k; // Calculate r2 = h * k.
@@ -73,7 +82,7 @@ impl ChaumPedersen for Curve25519ChaumPedersen {
     /// # Returns
     /// A random scalar value to be used as a challenge.
     fn challenge(_: &GroupParams<RistrettoPoint>) -> Self::Challenge {
-        let mut rng = OsRng; // Create a random number generator.
+        let mut rng = ProtocolRng; // Create a random number generator.
         Scalar::random(&mut rng) // Generate and return a random scalar as the challenge.
     }
 
@@ -112,15 +121,24 @@ impl ChaumPedersen for Curve25519ChaumPedersen {
     /// * `cp` - Reference to the commitment parameters.
     ///
     /// # Returns
-    /// `true` if the verification is successful, `false` otherwise.
-    fn verify(
+    /// `VerifyOutcome::Ok` if both equations hold, or the first equation that fails.
+    fn verify_detailed(
         params: &Self::GroupParameters, s: &Self::Response, c: &Self::Challenge,
         cp: &Self::CommitParameters,
-    ) -> bool {
+    ) -> VerifyOutcome {
         // Deconstructing the commitment parameters tuple.
         let (y1, y2, r1, r2) = cp;
         // Verifying the proof by checking two equations.
-        (params.g * s == r1 + (y1 * c)) && (params.h * s == r2 + (y2 * c))
+        #[cfg(feature = "constant_time")]
+        {
+            use subtle::ConstantTimeEq;
+            VerifyOutcome::from_equations(
+                (params.g * s).ct_eq(&(r1 + (y1 * c))).into(),
+                (params.h * s).ct_eq(&(r2 + (y2 * c))).into(),
+            )
+        }
+        #[cfg(not(feature = "constant_time"))]
+        VerifyOutcome::from_equations(params.g * s == r1 + (y1 * c), params.h * s == r2 + (y2 * c))
     }
 }
 
@@ -134,13 +152,10 @@ impl ByteConvertible<Scalar> for Scalar {
         t.to_bytes().to_vec()
     }
 
-    fn convert_from(bytes: &[u8]) -> Result<Scalar, Box<dyn Error>> {
-        let array: [u8; 32] = bytes.try_into().map_err(|_| {
-            Box::new(std::io::Error::new(
-                std::io::ErrorKind::InvalidInput,
-                "Invalid bytes length for Scalar",
-            )) as Box<dyn Error>
-        })?;
+    fn convert_from(bytes: &[u8]) -> Result<Scalar, ConversionError> {
+        let array: [u8; 32] = bytes
+            .try_into()
+            .map_err(|_| ConversionError::InvalidLength)?;
         Ok(Scalar::from_bytes_mod_order(array))
     }
 }
@@ -150,19 +165,39 @@ impl ByteConvertible<Scalar> for Scalar {
 /// This implementation provides methods to convert `RistrettoPoint` objects to and from
 /// byte arrays. It uses the compression and decompression features of the Ristretto group
 /// to achieve this.
+impl ValidateGroupParams for RistrettoPoint {
+    /// Checks that `g` and `h` are distinct and not the identity. Every Ristretto point lies in
+    /// the prime-order group, so no further checks are needed.
+    fn validate(params: &GroupParams<RistrettoPoint>) -> Result<(), InvalidGroupParams> {
+        if params.g == RistrettoPoint::identity() || params.h == RistrettoPoint::identity() {
+            return Err(InvalidGroupParams::TrivialGenerator);
+        }
+        if params.g == params.h {
+            return Err(InvalidGroupParams::DependentGenerators);
+        }
+        Ok(())
+    }
+
+    /// Checks that the element is not the identity. Decoding already rejects every encoding
+    /// that is not a valid Ristretto point.
+    fn is_valid_group_element(
+        _params: &GroupParams<RistrettoPoint>, element: &RistrettoPoint,
+    ) -> bool {
+        *element != RistrettoPoint::identity()
+    }
+}
+
 impl ByteConvertible<RistrettoPoint> for RistrettoPoint {
     fn convert_to(t: &RistrettoPoint) -> Vec<u8> {
         t.compress().to_bytes().to_vec()
     }
 
-    fn convert_from(bytes: &[u8]) -> Result<RistrettoPoint, Box<dyn Error>> {
-        let compressed = CompressedRistretto::from_slice(bytes);
-        compressed?.decompress().ok_or_else(|| {
-            Box::new(std::io::Error::new(
-                std::io::ErrorKind::InvalidInput,
-                "Failed to decompress RistrettoPoint",
-            )) as Box<dyn Error>
-        })
+    fn convert_from(bytes: &[u8]) -> Result<RistrettoPoint, ConversionError> {
+        let compressed =
+            CompressedRistretto::from_slice(bytes).map_err(|_| ConversionError::InvalidLength)?;
+        compressed
+            .decompress()
+            .ok_or(ConversionError::InvalidEncoding)
     }
 }
 
@@ -174,9 +209,9 @@ impl RandomGenerator<Scalar> for Scalar {
     /// A `Result` containing the random `Scalar`, or an error if the generation fails.
     ///
     /// # Errors
-    /// Returns an error if the conversion from bytes to `Scalar` fails.
-    fn generate_random() -> Result<Scalar, Box<dyn Error>> {
-        Ok(Scalar::random(&mut OsRng))
+    /// Returns an error if the random number generator fails.
+    fn generate_random() -> Result<Scalar, rand_core::Error> {
+        Ok(Scalar::random(&mut ProtocolRng))
     }
 }
 
@@ -188,9 +223,19 @@ impl RandomGenerator<RistrettoPoint> for RistrettoPoint {
     /// A `Result` containing the random `RistrettoPoint`, or an error if the generation fails.
     ///
     /// # Errors
-    /// Returns an error if the conversion from bytes to `RistrettoPoint` fails.
-    fn generate_random() -> Result<RistrettoPoint, Box<dyn std::error::Error>> {
-        Ok(RistrettoPoint::random(&mut OsRng))
+    /// Returns an error if the random number generator fails.
+    fn generate_random() -> Result<RistrettoPoint, rand_core::Error> {
+        Ok(RistrettoPoint::random(&mut ProtocolRng))
+    }
+}
+
+// Implementation of `TranscriptChallenge` trait for `Scalar`.
+impl TranscriptChallenge<GroupParams<RistrettoPoint>> for Scalar {
+    /// Squeezes 64 bytes out of the transcript and reduces them to a `Scalar`.
+    fn from_transcript(transcript: &mut Transcript, _: &GroupParams<RistrettoPoint>) -> Scalar {
+        let mut bytes = [0u8; 64];
+        transcript.challenge_bytes(b"c", &mut bytes);
+        Scalar::from_bytes_mod_order_wide(&bytes)
     }
 }
 
@@ -202,6 +247,38 @@ mod test {
     use crate::rand::RandomGenerator;
     use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
     use curve25519_dalek::ristretto::CompressedRistretto;
+    use proptest::collection::vec;
+    use proptest::prelude::*;
+    use rand_core::OsRng;
+
+    #[test]
+    fn test_verify_detailed_reports_failing_equation() {
+        let params = EC25519_GROUP_PARAMS.to_owned();
+        let x = Scalar::generate_random().unwrap();
+        let ((y1, y2, r1, r2), k) = Curve25519ChaumPedersen::commitment(&params, &x);
+        let c = Curve25519ChaumPedersen::challenge(&params);
+        let s = Curve25519ChaumPedersen::challenge_response(&params, &k, &c, &x);
+
+        let outcome = Curve25519ChaumPedersen::verify_detailed(&params, &s, &c, &(y1, y2, r1, r2));
+        assert_eq!(outcome, VerifyOutcome::Ok);
+
+        let bad_r1 = (y1, y2, r1 + params.g, r2);
+        let outcome = Curve25519ChaumPedersen::verify_detailed(&params, &s, &c, &bad_r1);
+        assert_eq!(outcome, VerifyOutcome::FirstEquationFailed);
+        assert!(!Curve25519ChaumPedersen::verify(&params, &s, &c, &bad_r1));
+
+        let bad_r2 = (y1, y2, r1, r2 + params.h);
+        let outcome = Curve25519ChaumPedersen::verify_detailed(&params, &s, &c, &bad_r2);
+        assert_eq!(outcome, VerifyOutcome::SecondEquationFailed);
+    }
+
+    #[test]
+    fn ec25519_params_validate() {
+        let mut params = EC25519_GROUP_PARAMS.to_owned();
+        assert_eq!(params.validate(), Ok(()));
+        params.g = RistrettoPoint::identity();
+        assert_eq!(params.validate(), Err(InvalidGroupParams::TrivialGenerator));
+    }
 
     // Test case to ensure round-trip conversion for `RistrettoPoint`.
     #[test]
@@ -212,6 +289,30 @@ mod test {
         assert_eq!(original, recovered);
     }
 
+    // Test case to ensure hex round-trip conversion for `Scalar`.
+    #[test]
+    fn scalar_hex_round_trip() {
+        let original = Scalar::generate_random().unwrap();
+        let hex = Scalar::to_hex(&original);
+        assert_eq!(hex.len(), 64);
+        assert_eq!(Scalar::from_hex(&hex).unwrap(), original);
+    }
+
+    // Test case to ensure hex round-trip conversion for `RistrettoPoint`.
+    #[test]
+    fn ristretto_point_hex_round_trip() {
+        let original = RISTRETTO_BASEPOINT_POINT * Scalar::generate_random().unwrap();
+        let hex = RistrettoPoint::to_hex(&original);
+        assert_eq!(hex, serialize_ristretto_point(&original));
+        assert_eq!(RistrettoPoint::from_hex(&hex).unwrap(), original);
+    }
+
+    #[test]
+    fn invalid_hex_rejected() {
+        assert_eq!(Scalar::from_hex("not hex"), Err(ConversionError::InvalidEncoding));
+        assert_eq!(Scalar::from_hex("abcd"), Err(ConversionError::InvalidLength));
+    }
+
     fn serialize_ristretto_point(point: &RistrettoPoint) -> String {
         // Compress the RistrettoPoint
         let compressed_point = point.compress();
@@ -242,8 +343,8 @@ mod test {
 
         // Setting up the group parameters.
         let params = GroupParams::<RistrettoPoint> {
-            g: g.clone(),
-            h: h.clone(),
+            g,
+            h,
             p: RISTRETTO_BASEPOINT_POINT,
             q: RISTRETTO_BASEPOINT_POINT,
         };
@@ -270,8 +371,8 @@ mod test {
 
         // Setting up the group parameters.
         let params = GroupParams::<RistrettoPoint> {
-            g: g.clone(),
-            h: h.clone(),
+            g,
+            h,
             p: RISTRETTO_BASEPOINT_POINT,
             q: RISTRETTO_BASEPOINT_POINT,
         };
@@ -365,4 +466,36 @@ mod test {
         let result = Scalar::convert_from(&bytes);
         assert!(result.is_err());
     }
+
+    proptest! {
+        #[test]
+        fn prop_conversions_round_trip(seed in vec(any::<u8>(), 64)) {
+            let seed: [u8; 64] = seed.try_into().unwrap();
+            let x = Scalar::from_bytes_mod_order_wide(&seed);
+            prop_assert_eq!(Scalar::convert_from(&Scalar::convert_to(&x)), Ok(x));
+            let point = RistrettoPoint::from_uniform_bytes(&seed);
+            prop_assert_eq!(
+                RistrettoPoint::convert_from(&RistrettoPoint::convert_to(&point)),
+                Ok(point)
+            );
+        }
+
+        #[test]
+        fn prop_wrong_length_rejected(
+            bytes in vec(any::<u8>(), 0..=96).prop_filter("not 32 bytes", |b| b.len() != 32),
+        ) {
+            prop_assert_eq!(Scalar::convert_from(&bytes), Err(ConversionError::InvalidLength));
+            prop_assert_eq!(
+                RistrettoPoint::convert_from(&bytes),
+                Err(ConversionError::InvalidLength)
+            );
+        }
+
+        #[test]
+        fn prop_arbitrary_point_bytes_do_not_panic(bytes in vec(any::<u8>(), 32)) {
+            if let Ok(point) = RistrettoPoint::convert_from(&bytes) {
+                prop_assert_eq!(RistrettoPoint::convert_to(&point), bytes);
+            }
+        }
+    }
 }
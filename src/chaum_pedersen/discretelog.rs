@@ -1,10 +1,17 @@
-use crate::chaum_pedersen::{ChaumPedersen, GroupParams};
-use crate::conversion::ByteConvertible;
-use crate::rand::RandomGenerator;
-use num_bigint::{BigUint, RandBigInt};
-use num_traits::One;
-use rand::rngs::OsRng;
-use std::error::Error;
+use crate::chaum_pedersen::transcript::TranscriptChallenge;
+#[cfg(feature = "parallel")]
+use crate::chaum_pedersen::ProofBundle;
+use crate::chaum_pedersen::{
+    ChaumPedersen, GroupParams, InvalidGroupParams, SecurityLevel, ValidateGroupParams,
+    VerifyOutcome,
+};
+use crate::conversion::{ByteConvertible, ConversionError};
+use crate::rand::{ProtocolRng, RandomGenerator};
+use alloc::vec::Vec;
+use core::fmt;
+use merlin::Transcript;
+use num_bigint::{BigInt, BigUint, RandBigInt};
+use num_traits::{Euclid, One, Zero};
 
 /// A struct representing the Chaum-Pedersen protocol specialized for discrete logarithm-based groups.
 /// This protocol is used for demonstrating knowledge of a secret in a zero-knowledge manner.
@@ -50,8 +57,7 @@ impl ChaumPedersen for DiscreteLogChaumPedersen {
     {
         let y1 = params.g.modpow(x, &params.p);
         let y2 = params.h.modpow(x, &params.p);
-        let mut rng = OsRng;
-        let k = rng.gen_biguint_below(&params.p);
+        let k = generate_random_below(&params.q).expect("the OS random number generator failed");
         let r1 = params.g.modpow(&k, &params.p);
         let r2 = params.h.modpow(&k, &params.p);
         ((y1, y2, r1, r2), k)
@@ -60,24 +66,32 @@ impl ChaumPedersen for DiscreteLogChaumPedersen {
     /// Generates a random challenge for the protocol within the group's range.
     /// This challenge is used as part of the verification process.
     ///
+    /// The challenge spans the full bit length of `q`; see
+    /// [`DiscreteLogChaumPedersen::challenge_with_bits`] for shorter challenges.
+    ///
     /// # Arguments
     /// * `params`: Group parameters used to define the range within which the challenge is generated.
     ///
     /// # Returns
     /// A `BigUint` representing the challenge value.
     fn challenge(params: &GroupParams<BigUint>) -> BigUint {
-        let mut rng = OsRng;
-        rng.gen_biguint_below(&params.p)
+        let mut rng = ProtocolRng;
+        rng.gen_biguint_below(&params.q)
     }
 
-    /// Generates a random challenge for the protocol within the group's range.
-    /// This challenge is used as part of the verification process.
+    /// Computes the response `s = k - c * x mod q` to a challenge.
+    ///
+    /// `k - c * x` is negative whenever `c * x > k`, so the difference is taken over signed
+    /// integers and reduced with the Euclidean remainder, which always lies in `[0, q)`.
     ///
     /// # Arguments
-    /// * `params`: Group parameters used to define the range within which the challenge is generated.
+    /// * `params`: Group parameters whose order `q` reduces the response.
+    /// * `k`: The random value used in the commitment.
+    /// * `c`: The challenge.
+    /// * `x`: The secret.
     ///
     /// # Returns
-    /// A `BigUint` representing the challenge value.
+    /// A `BigUint` in `[0, q)` representing the response.
     fn challenge_response(
         params: &Self::GroupParameters, k: &Self::CommitmentRandom, c: &Self::Challenge,
         x: &Self::Secret,
@@ -85,11 +99,10 @@ impl ChaumPedersen for DiscreteLogChaumPedersen {
     where
         Self: Sized,
     {
-        if k >= &(c * x) {
-            (k - c * x).modpow(&BigUint::one(), &params.q)
-        } else {
-            &params.q - (c * x - k).modpow(&BigUint::one(), &params.q)
-        }
+        let q = BigInt::from(params.q.clone());
+        let s = (BigInt::from(k.clone()) - BigInt::from(c * x)).rem_euclid(&q);
+        // The remainder is never negative, so its magnitude is the response.
+        s.into_parts().1
     }
 
     /// Verifies the response against the given commitment, challenge, and group parameters.
@@ -102,11 +115,11 @@ impl ChaumPedersen for DiscreteLogChaumPedersen {
     /// * `cp`: The commitment parameters (`y1`, `y2`, `r1`, `r2`) against which the response is being verified.
     ///
     /// # Returns
-    /// `true` if the verification is successful and the response is valid; `false` otherwise.
-    fn verify(
+    /// `VerifyOutcome::Ok` if the response is valid, or the first verification equation that fails.
+    fn verify_detailed(
         params: &Self::GroupParameters, s: &Self::Response, c: &Self::Challenge,
         cp: &Self::CommitParameters,
-    ) -> bool {
+    ) -> VerifyOutcome {
         let (y1, y2, r1, r2) = cp;
 
         let lhs1 = params.g.modpow(s, &params.p);
@@ -114,10 +127,422 @@ impl ChaumPedersen for DiscreteLogChaumPedersen {
         let lhs2 = params.h.modpow(s, &params.p);
         let rhs2 = (r2 * y2.modpow(&(&params.p - c - BigUint::one()), &params.p)) % &params.p;
 
-        lhs1 == rhs1 && lhs2 == rhs2
+        #[cfg(feature = "constant_time")]
+        {
+            let len = params.p.to_bytes_be().len();
+            VerifyOutcome::from_equations(
+                ct_eq_padded(&lhs1, &rhs1, len),
+                ct_eq_padded(&lhs2, &rhs2, len),
+            )
+        }
+        #[cfg(not(feature = "constant_time"))]
+        VerifyOutcome::from_equations(lhs1 == rhs1, lhs2 == rhs2)
+    }
+}
+
+/// Compares two values in constant time, over their big-endian encodings left-padded to `len`
+/// bytes so that every byte is compared regardless of where the values differ.
+///
+/// # Arguments
+/// * `a`, `b` - The values to compare, both below `2^(8 * len)`.
+/// * `len` - The length of the encodings, e.g. the byte length of the modulus.
+///
+/// # Returns
+/// Whether the values are equal.
+#[cfg(feature = "constant_time")]
+fn ct_eq_padded(a: &BigUint, b: &BigUint, len: usize) -> bool {
+    use subtle::ConstantTimeEq;
+
+    let pad = |v: &BigUint| {
+        let bytes = v.to_bytes_be();
+        let mut padded = Vec::with_capacity(len);
+        padded.resize(len.saturating_sub(bytes.len()), 0);
+        padded.extend_from_slice(&bytes);
+        padded
+    };
+    pad(a).ct_eq(&pad(b)).into()
+}
+
+impl DiscreteLogChaumPedersen {
+    /// Generates a random challenge of at most `challenge_bits` bits, below the group order `q`.
+    ///
+    /// Shorter challenges make proving and verification cheaper at the cost of soundness: a
+    /// prover who does not know the secret can cheat by guessing the challenge in advance, which
+    /// succeeds with probability `2^-challenge_bits`. Challenges are therefore never shorter
+    /// than [`MIN_CHALLENGE_BITS`].
+    ///
+    /// A `ZkAuth` service hashes every challenge it issues with a nonce into the effective
+    /// challenge users answer, so it shortens that one instead when given a
+    /// [`DiscreteLogChaumPedersenConfig`].
+    ///
+    /// # Arguments
+    /// * `params`: Group parameters whose order `q` bounds the challenge.
+    /// * `challenge_bits`: The number of bits of the challenge.
+    ///
+    /// # Returns
+    /// A challenge below both `2^challenge_bits` and `q`, or the reason the size does not suit
+    /// the group, see [`check_challenge_bits`].
+    pub fn challenge_with_bits(
+        params: &GroupParams<BigUint>, challenge_bits: u32,
+    ) -> Result<BigUint, InvalidChallengeBits> {
+        check_challenge_bits(params, challenge_bits)?;
+        let bound = (BigUint::one() << challenge_bits).min(params.q.clone());
+        let mut rng = ProtocolRng;
+        Ok(rng.gen_biguint_below(&bound))
+    }
+}
+
+#[cfg(feature = "parallel")]
+impl DiscreteLogChaumPedersen {
+    /// Verifies many proof bundles in parallel, e.g. the proofs attached to users in a bulk
+    /// import.
+    ///
+    /// Every proof is checked against the `y1` and `y2` it carries, and the modular
+    /// exponentiations of different proofs are independent, so the proofs are spread over all
+    /// CPU cores.
+    ///
+    /// # Arguments
+    /// * `params`: The group parameters the proofs were created with.
+    /// * `proofs`: The proof bundles to verify.
+    ///
+    /// # Returns
+    /// Whether each proof verifies, in the order of `proofs`.
+    pub fn verify_many(params: &GroupParams<BigUint>, proofs: &[ProofBundle<Self>]) -> Vec<bool> {
+        use rayon::prelude::*;
+
+        proofs
+            .par_iter()
+            .map(|proof| {
+                let (y1, y2, _, _) = &proof.commitment;
+                proof.verify(params, y1, y2)
+            })
+            .collect()
+    }
+}
+
+/// The Schnorr identification scheme, proving knowledge of the discrete logarithm `x` of a
+/// public key `y = g^x mod p` with the single generator `g`.
+///
+/// Unlike [`DiscreteLogChaumPedersen`], which proves that `y1` and `y2` share a logarithm to the
+/// bases `g` and `h`, only `g` is used and `h` in the group parameters is ignored. The prover
+/// commits to `r = g^k`, answers a challenge `c` with `s = k + c * x mod q`, and the verifier
+/// checks that `g^s = r * y^c`.
+#[derive(Clone)]
+pub struct SchnorrIdentification {}
+
+impl ChaumPedersen for SchnorrIdentification {
+    /// Defines the type of secret values used in this protocol as `BigUint`.
+    type Secret = BigUint;
+
+    /// Defines the type of randomness used during the commitment phase as `BigUint`.
+    type CommitmentRandom = BigUint;
+
+    /// Defines the type of response generated in the protocol as `BigUint`.
+    type Response = BigUint;
+
+    /// Defines the type of challenge used in the protocol as `BigUint`.
+    type Challenge = BigUint;
+
+    /// Defines the group parameters as `GroupParams<BigUint>`, of which `h` is ignored.
+    type GroupParameters = GroupParams<BigUint>;
+
+    /// Defines the type of parameters returned during the commitment phase: the public key `y`
+    /// and the commitment `r`.
+    type CommitParameters = (BigUint, BigUint);
+
+    /// Calculates the public key and a fresh commitment for the given secret `x`.
+    ///
+    /// # Arguments
+    /// * `params`: Group parameters which include the base point `g`, and the moduli `p` and `q`.
+    /// * `x`: The secret value for which the commitment is being calculated.
+    ///
+    /// # Returns
+    /// A tuple containing:
+    /// * A tuple (`y`, `r`) of the public key `g^x` and the commitment `g^k`.
+    /// * The random value `k` used in the commitment.
+    fn commitment(
+        params: &Self::GroupParameters, x: &Self::Secret,
+    ) -> (Self::CommitParameters, Self::CommitmentRandom)
+    where
+        Self: Sized,
+    {
+        let y = params.g.modpow(x, &params.p);
+        let k = generate_random_below(&params.q).expect("the OS random number generator failed");
+        let r = params.g.modpow(&k, &params.p);
+        ((y, r), k)
+    }
+
+    /// Generates a random challenge below the group order `q`, as for
+    /// [`DiscreteLogChaumPedersen`].
+    ///
+    /// # Arguments
+    /// * `params`: Group parameters used to define the range within which the challenge is generated.
+    ///
+    /// # Returns
+    /// A `BigUint` representing the challenge value.
+    fn challenge(params: &GroupParams<BigUint>) -> BigUint {
+        DiscreteLogChaumPedersen::challenge(params)
+    }
+
+    /// Computes the response `s = k + c * x mod q` to a challenge.
+    ///
+    /// # Arguments
+    /// * `params`: Group parameters whose order `q` reduces the response.
+    /// * `k`: The random value used in the commitment.
+    /// * `c`: The challenge.
+    /// * `x`: The secret.
+    ///
+    /// # Returns
+    /// A `BigUint` in `[0, q)` representing the response.
+    fn challenge_response(
+        params: &Self::GroupParameters, k: &Self::CommitmentRandom, c: &Self::Challenge,
+        x: &Self::Secret,
+    ) -> Self::Response
+    where
+        Self: Sized,
+    {
+        (k + c * x) % &params.q
+    }
+
+    /// Verifies the response by checking that `g^s = r * y^c mod p`.
+    ///
+    /// # Arguments
+    /// * `params`: Group parameters used in the verification.
+    /// * `s`: The response to be verified.
+    /// * `c`: The challenge against which the response is being verified.
+    /// * `cp`: The public key and commitment (`y`, `r`) against which the response is being verified.
+    ///
+    /// # Returns
+    /// `VerifyOutcome::Ok` if the response is valid, or `VerifyOutcome::FirstEquationFailed`
+    /// otherwise. There is no second equation, as `h` is not used.
+    fn verify_detailed(
+        params: &Self::GroupParameters, s: &Self::Response, c: &Self::Challenge,
+        cp: &Self::CommitParameters,
+    ) -> VerifyOutcome {
+        let (y, r) = cp;
+
+        let lhs = params.g.modpow(s, &params.p);
+        let rhs = (r * y.modpow(c, &params.p)) % &params.p;
+
+        #[cfg(feature = "constant_time")]
+        {
+            let len = params.p.to_bytes_be().len();
+            VerifyOutcome::from_equations(ct_eq_padded(&lhs, &rhs, len), true)
+        }
+        #[cfg(not(feature = "constant_time"))]
+        VerifyOutcome::from_equations(lhs == rhs, true)
+    }
+}
+
+/// Generates a key pair for the Schnorr identification scheme.
+///
+/// # Arguments
+/// * `params`: Group parameters which include the base point `g`, and the moduli `p` and `q`.
+/// * `rng`: The random number generator drawing the private key.
+///
+/// # Returns
+/// A tuple of the private key, in `[1, q)`, and the public key `g^private mod p`.
+pub fn generate_keypair(
+    params: &GroupParams<BigUint>, rng: &mut impl RandBigInt,
+) -> (BigUint, BigUint) {
+    let private = rng.gen_biguint_range(&BigUint::one(), &params.q);
+    let public = params.g.modpow(&private, &params.p);
+    (private, public)
+}
+
+/// The shortest challenge [`DiscreteLogChaumPedersen::challenge_with_bits`] draws.
+pub const MIN_CHALLENGE_BITS: u32 = 128;
+
+/// Checks that a challenge size suits the given group parameters.
+///
+/// # Arguments
+/// * `params`: Group parameters whose order `q` bounds the challenge size.
+/// * `challenge_bits`: The number of bits of the challenge.
+///
+/// # Returns
+/// `Ok(())` if the challenge size is between [`MIN_CHALLENGE_BITS`] and the bit length of `q`,
+/// or the reason it is not.
+pub fn check_challenge_bits(
+    params: &GroupParams<BigUint>, challenge_bits: u32,
+) -> Result<(), InvalidChallengeBits> {
+    if challenge_bits < MIN_CHALLENGE_BITS {
+        Err(InvalidChallengeBits::TooShort)
+    } else if u64::from(challenge_bits) > params.q.bits() {
+        Err(InvalidChallengeBits::TooLong)
+    } else {
+        Ok(())
     }
 }
 
+/// Configuration of the discrete log backend, trading soundness for smaller challenges, e.g. to
+/// configure a `ZkAuth` service with `with_challenge_config`.
+///
+/// # Fields
+/// - `challenge_bits`: The maximum bit length of challenges, between [`MIN_CHALLENGE_BITS`] and
+///   the bit length of `q`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DiscreteLogChaumPedersenConfig {
+    pub challenge_bits: u32,
+}
+
+impl DiscreteLogChaumPedersenConfig {
+    /// Checks that the configured challenge size suits the given group parameters.
+    ///
+    /// # Arguments
+    /// * `params`: Group parameters whose order `q` bounds the challenge size.
+    ///
+    /// # Returns
+    /// `Ok(())` if the challenge size is between [`MIN_CHALLENGE_BITS`] and the bit length of
+    /// `q`, or the reason it is not.
+    pub fn validate(&self, params: &GroupParams<BigUint>) -> Result<(), InvalidChallengeBits> {
+        check_challenge_bits(params, self.challenge_bits)
+    }
+}
+
+/// An error raised when a challenge size does not suit a group.
+///
+/// # Variants
+/// - `TooShort`: The challenge size is below [`MIN_CHALLENGE_BITS`].
+/// - `TooLong`: The challenge size exceeds the bit length of the group order `q`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InvalidChallengeBits {
+    TooShort,
+    TooLong,
+}
+
+impl fmt::Display for InvalidChallengeBits {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InvalidChallengeBits::TooShort => {
+                write!(f, "Challenges must be at least {} bits long", MIN_CHALLENGE_BITS)
+            }
+            InvalidChallengeBits::TooLong => {
+                write!(f, "Challenges must not be longer than the group order")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for InvalidChallengeBits {}
+
+impl GroupParams<BigUint> {
+    /// Infers the security level offered by these parameters from the bit length of `p`.
+    ///
+    /// # Returns
+    /// The `SecurityLevel` of the group, following NIST SP 800-57 Part 1.
+    pub fn security_level(&self) -> SecurityLevel {
+        match self.p.bits() {
+            0..=2047 => SecurityLevel::Bits80,
+            2048..=3071 => SecurityLevel::Bits112,
+            3072..=7679 => SecurityLevel::Bits128,
+            7680..=15359 => SecurityLevel::Bits192,
+            _ => SecurityLevel::Bits256,
+        }
+    }
+}
+
+impl ValidateGroupParams for BigUint {
+    const HAS_MODULUS: bool = true;
+
+    /// Checks that `g` and `h` are distinct, non-trivial elements of the order-`q` subgroup
+    /// of the multiplicative group modulo `p`. The primality of `p` and `q` is checked by
+    /// `check_primality`.
+    fn validate(params: &GroupParams<BigUint>) -> Result<(), InvalidGroupParams> {
+        let one = BigUint::one();
+        if [&params.g, &params.h]
+            .iter()
+            .any(|generator| **generator <= one || **generator >= params.p)
+        {
+            return Err(InvalidGroupParams::TrivialGenerator);
+        }
+        if params.g == params.h {
+            return Err(InvalidGroupParams::DependentGenerators);
+        }
+        if params.q <= one
+            || params.p <= params.q
+            || (&params.p - &one) % &params.q != BigUint::default()
+            || params.g.modpow(&params.q, &params.p) != one
+            || params.h.modpow(&params.q, &params.p) != one
+        {
+            return Err(InvalidGroupParams::InvalidOrder);
+        }
+        Ok(())
+    }
+
+    /// Checks that `1 < element < p` and `element^q = 1 mod p`, so that the element lies in the
+    /// order-`q` subgroup rather than merely modulo `p`.
+    fn is_valid_group_element(params: &GroupParams<BigUint>, element: &BigUint) -> bool {
+        *element > BigUint::one()
+            && *element < params.p
+            && params.q > BigUint::zero()
+            && element.modpow(&params.q, &params.p).is_one()
+    }
+
+    /// Checks with the Miller-Rabin test that `p` and `q` are prime, then that `q` divides
+    /// `p - 1` and `g^q = 1 mod p`, so that `g` generates the subgroup of prime order `q`.
+    fn check_primality(
+        params: &GroupParams<BigUint>, rounds: usize,
+    ) -> Result<(), InvalidGroupParams> {
+        if !is_probable_prime(&params.p, rounds) {
+            return Err(InvalidGroupParams::CompositeModulus);
+        }
+        if !is_probable_prime(&params.q, rounds) {
+            return Err(InvalidGroupParams::CompositeOrder);
+        }
+        if !((&params.p - 1u32) % &params.q).is_zero() {
+            return Err(InvalidGroupParams::OrderDoesNotDivide);
+        }
+        if !params.g.modpow(&params.q, &params.p).is_one() {
+            return Err(InvalidGroupParams::InvalidOrder);
+        }
+        Ok(())
+    }
+}
+
+/// Tests whether `n` is prime with the Miller-Rabin test, using a random base in each round.
+///
+/// # Arguments
+/// * `n` - The number to test.
+/// * `rounds` - The number of rounds; a composite `n` passes all of them with probability at
+///   most `4^-rounds`.
+///
+/// # Returns
+/// `false` if `n` is certainly composite, or `true` if it is probably prime.
+pub fn is_probable_prime(n: &BigUint, rounds: usize) -> bool {
+    let two = BigUint::from(2u32);
+    if n < &two {
+        return false;
+    }
+    for small_prime in [2u32, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37] {
+        if (n % small_prime).is_zero() {
+            return *n == BigUint::from(small_prime);
+        }
+    }
+
+    // Write n - 1 = 2^s * d with d odd.
+    let n_minus_one = n - 1u32;
+    let s = n_minus_one
+        .trailing_zeros()
+        .expect("n - 1 is nonzero for n > 37");
+    let d = &n_minus_one >> s;
+    'rounds: for _ in 0..rounds {
+        let a = ProtocolRng.gen_biguint_range(&two, &n_minus_one);
+        let mut x = a.modpow(&d, n);
+        if x.is_one() || x == n_minus_one {
+            continue;
+        }
+        for _ in 1..s {
+            x = x.modpow(&two, n);
+            if x == n_minus_one {
+                continue 'rounds;
+            }
+        }
+        return false;
+    }
+    true
+}
+
 /// Implementation of `ByteConvertible` for `BigUint`.
 ///
 /// This implementation provides methods to convert `BigUint` objects to and from
@@ -127,39 +552,197 @@ impl ByteConvertible<BigUint> for BigUint {
         t.to_bytes_be()
     }
 
-    fn convert_from(bytes: &[u8]) -> Result<BigUint, Box<dyn Error>> {
+    fn convert_from(bytes: &[u8]) -> Result<BigUint, ConversionError> {
         Ok(BigUint::from_bytes_be(bytes))
     }
 }
 
 // Implementation of `RandomGenerator` trait for `BigUint`.
 impl RandomGenerator<BigUint> for BigUint {
-    /// Generates a random `BigUint`.
+    /// Generates a random 256-bit `BigUint`.
+    ///
+    /// The value is not reduced into any group, so it is not a valid secret on its own; use
+    /// [`generate_random_below`] with the group order `q` to generate secrets.
     ///
     /// # Returns
     /// A `Result` containing the random `BigUint`, or an error if the generation fails.
     ///
     /// # Errors
-    /// Returns an error if the conversion from bytes to `BigUint` fails.
-    fn generate_random() -> Result<BigUint, Box<dyn std::error::Error>> {
-        use rand::RngCore;
-        let mut rng = OsRng;
+    /// Returns an error if the operating system random number generator fails.
+    fn generate_random() -> Result<BigUint, rand_core::Error> {
+        use rand_core::RngCore;
+        let mut rng = ProtocolRng;
         let mut bytes = [0u8; 32];
-        rng.fill_bytes(&mut bytes);
+        rng.try_fill_bytes(&mut bytes)?;
         Ok(BigUint::from_bytes_be(&bytes))
     }
 }
 
+/// Generates a uniformly random, non-zero `BigUint` below a bound, e.g. a secret within the
+/// subgroup order `q`.
+///
+/// Random bytes are drawn for the bit length of the bound and rejected until they fall in
+/// `[1, bound)`, which keeps the distribution uniform.
+///
+/// # Arguments
+/// * `bound`: The exclusive upper bound, which must be at least 2.
+///
+/// # Returns
+/// A `Result` containing the random `BigUint` in `[1, bound)`, or an error if the generation
+/// fails.
+///
+/// # Errors
+/// Returns an error if the operating system random number generator fails.
+///
+/// # Panics
+/// Panics if `bound` is below 2, as there is no value to generate.
+pub fn generate_random_below(bound: &BigUint) -> Result<BigUint, rand_core::Error> {
+    use rand_core::RngCore;
+    assert!(*bound > BigUint::one(), "the bound must be at least 2");
+    let bits = bound.bits();
+    let mut bytes = alloc::vec![0u8; bits.div_ceil(8) as usize];
+    // Clears the excess bits of the leading byte, so that each draw succeeds with at least
+    // even odds.
+    let mask = 0xffu8 >> (bytes.len() as u64 * 8 - bits);
+    loop {
+        ProtocolRng.try_fill_bytes(&mut bytes)?;
+        bytes[0] &= mask;
+        let value = BigUint::from_bytes_be(&bytes);
+        if !value.is_zero() && value < *bound {
+            return Ok(value);
+        }
+    }
+}
+
+// Implementation of `TranscriptChallenge` trait for `BigUint`.
+impl TranscriptChallenge<GroupParams<BigUint>> for BigUint {
+    /// Squeezes 64 bytes out of the transcript and reduces them modulo the group order `q`.
+    fn from_transcript(transcript: &mut Transcript, params: &GroupParams<BigUint>) -> BigUint {
+        let mut bytes = [0u8; 64];
+        transcript.challenge_bytes(b"c", &mut bytes);
+        BigUint::from_bytes_be(&bytes) % &params.q
+    }
+
+    /// Keeps the lowest `challenge_bits` bits of the challenge.
+    fn shorten(&self, challenge_bits: u32) -> Option<BigUint> {
+        Some(self % (BigUint::one() << challenge_bits))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    #[allow(deprecated)]
     use crate::chaum_pedersen::constants::{
-        RFC5114_MODP_1024_160_BIT_PARAMS, RFC5114_MODP_2048_224_BIT_PARAMS,
-        RFC5114_MODP_2048_256_BIT_PARAMS,
+        RFC3526_MODP_2048_BIT_PARAMS, RFC5114_MODP_1024_160_BIT_PARAMS,
+        RFC5114_MODP_2048_224_BIT_PARAMS, RFC5114_MODP_2048_256_BIT_PARAMS,
     };
     use crate::chaum_pedersen::test::test_execute_protocol;
     use crate::rand::RandomGenerator;
     use num_bigint::ToBigUint;
+    use proptest::collection::vec;
+    use proptest::prelude::*;
+    use rand_core::OsRng;
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_rfc5114_params_validate() {
+        assert_eq!(RFC5114_MODP_1024_160_BIT_PARAMS.validate(), Ok(()));
+        assert_eq!(RFC5114_MODP_2048_224_BIT_PARAMS.validate(), Ok(()));
+        assert_eq!(RFC5114_MODP_2048_256_BIT_PARAMS.validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_invalid_params_rejected() {
+        let valid = RFC5114_MODP_2048_256_BIT_PARAMS.to_owned();
+
+        let mut params = valid.clone();
+        params.h = BigUint::one();
+        assert_eq!(params.validate(), Err(InvalidGroupParams::TrivialGenerator));
+
+        let mut params = valid.clone();
+        params.h = params.g.clone();
+        assert_eq!(params.validate(), Err(InvalidGroupParams::DependentGenerators));
+
+        // 2 does not lie in the order-q subgroup.
+        let mut params = valid;
+        params.h = 2u32.into();
+        assert_eq!(params.validate(), Err(InvalidGroupParams::InvalidOrder));
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_rfc5114_params_pass_primality_check() {
+        assert_eq!(RFC5114_MODP_1024_160_BIT_PARAMS.check_primality(16), Ok(()));
+        assert_eq!(RFC5114_MODP_2048_224_BIT_PARAMS.check_primality(16), Ok(()));
+        assert_eq!(RFC5114_MODP_2048_256_BIT_PARAMS.check_primality(16), Ok(()));
+    }
+
+    #[test]
+    fn test_rfc3526_params_form_safe_prime_group() {
+        let params = RFC3526_MODP_2048_BIT_PARAMS.to_owned();
+        assert_eq!(params.validate(), Ok(()));
+        assert_eq!(params.check_primality(16), Ok(()));
+        assert_eq!(params.q, (&params.p - 1u32) >> 1);
+
+        let x = OsRng.gen_biguint_below(&params.q);
+        assert!(test_execute_protocol::<DiscreteLogChaumPedersen>(&params, &x));
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_broken_params_fail_primality_check() {
+        let valid = RFC5114_MODP_2048_256_BIT_PARAMS.to_owned();
+
+        // The product of two large primes has no small factors, so only Miller-Rabin catches it.
+        let mut params = valid.clone();
+        params.p = &valid.p * &valid.q;
+        assert_eq!(params.check_primality(16), Err(InvalidGroupParams::CompositeModulus));
+
+        let mut params = valid.clone();
+        params.q = &valid.q * &valid.q;
+        assert_eq!(params.check_primality(16), Err(InvalidGroupParams::CompositeOrder));
+
+        let mut params = valid.clone();
+        params.q = RFC5114_MODP_1024_160_BIT_PARAMS.q.clone();
+        assert_eq!(params.check_primality(16), Err(InvalidGroupParams::OrderDoesNotDivide));
+
+        // 2 does not lie in the order-q subgroup.
+        let mut params = valid;
+        params.g = 2u32.into();
+        assert_eq!(params.check_primality(16), Err(InvalidGroupParams::InvalidOrder));
+    }
+
+    #[test]
+    fn test_is_valid_group_element() {
+        let params = RFC5114_MODP_2048_256_BIT_PARAMS.to_owned();
+        let x = DiscreteLogChaumPedersen::challenge(&params);
+        let y1 = params.g.modpow(&x, &params.p);
+        assert!(BigUint::is_valid_group_element(&params, &params.g));
+        assert!(BigUint::is_valid_group_element(&params, &y1));
+
+        // p - 1 has order 2, so it is a valid residue mod p outside the order-q subgroup.
+        let minus_one = &params.p - 1u32;
+        let beyond_p = &params.p + &y1;
+        for invalid in [BigUint::zero(), BigUint::one(), minus_one, params.p.clone(), beyond_p] {
+            assert!(!BigUint::is_valid_group_element(&params, &invalid));
+        }
+    }
+
+    #[test]
+    fn test_is_probable_prime() {
+        for prime in [2u64, 3, 37, 41, 104_729, 2_305_843_009_213_693_951] {
+            assert!(is_probable_prime(&BigUint::from(prime), 16), "{} is prime", prime);
+        }
+        // Includes Carmichael numbers, which fool the Fermat test.
+        for composite in [0u64, 1, 4, 1_369, 561, 41_041, 825_265, 2_305_843_009_213_693_953] {
+            assert!(
+                !is_probable_prime(&BigUint::from(composite), 16),
+                "{} is composite",
+                composite
+            );
+        }
+    }
 
     // Test case to ensure round-trip conversion for `BigUint`.
     #[test]
@@ -211,6 +794,66 @@ mod tests {
         assert!(test_execute_protocol::<DiscreteLogChaumPedersen>(&params, &x));
     }
 
+    /// The worked example of the Schnorr identification scheme in Stinson's "Cryptography:
+    /// Theory and Practice", with `g` of order `q = 1031` modulo `p = 88667`.
+    fn stinson_params() -> GroupParams<BigUint> {
+        GroupParams {
+            g: BigUint::from(70322u32),
+            h: BigUint::zero(),
+            p: BigUint::from(88667u32),
+            q: BigUint::from(1031u32),
+        }
+    }
+
+    #[test]
+    fn test_schnorr_identification_test_vector() {
+        let params = stinson_params();
+        let x = BigUint::from(755u32);
+        let y = params.g.modpow(&x, &params.p);
+        // The example publishes `v = g^-x` rather than `y = g^x`.
+        assert_eq!(y.modinv(&params.p), Some(BigUint::from(13136u32)));
+
+        let k = BigUint::from(543u32);
+        let r = params.g.modpow(&k, &params.p);
+        assert_eq!(r, BigUint::from(84109u32));
+        let c = BigUint::from(1000u32);
+        let s = SchnorrIdentification::challenge_response(&params, &k, &c, &x);
+        assert_eq!(s, BigUint::from(851u32));
+        assert!(SchnorrIdentification::verify(&params, &s, &c, &(y.clone(), r.clone())));
+
+        let wrong = BigUint::from(852u32);
+        assert_eq!(
+            SchnorrIdentification::verify_detailed(&params, &wrong, &c, &(y, r)),
+            VerifyOutcome::FirstEquationFailed
+        );
+    }
+
+    #[test]
+    fn test_schnorr_identification_round_trip() {
+        let params = RFC5114_MODP_2048_256_BIT_PARAMS.to_owned();
+        let (x, y) = generate_keypair(&params, &mut OsRng);
+        assert!(!x.is_zero() && x < params.q);
+        assert_eq!(y, params.g.modpow(&x, &params.p));
+
+        let ((public, r), k) = SchnorrIdentification::commitment(&params, &x);
+        assert_eq!(public, y);
+        let c = SchnorrIdentification::challenge(&params);
+        let s = SchnorrIdentification::challenge_response(&params, &k, &c, &x);
+        assert!(SchnorrIdentification::verify(&params, &s, &c, &(y.clone(), r.clone())));
+
+        // `h` plays no part in the proof.
+        let without_h = GroupParams {
+            h: BigUint::zero(),
+            ..params.clone()
+        };
+        assert!(SchnorrIdentification::verify(&without_h, &s, &c, &(y.clone(), r.clone())));
+
+        // A response computed with another secret is rejected.
+        let (other, _) = generate_keypair(&params, &mut OsRng);
+        let forged = SchnorrIdentification::challenge_response(&params, &k, &c, &other);
+        assert!(!SchnorrIdentification::verify(&params, &forged, &c, &(y, r)));
+    }
+
     #[test]
     fn test_rfc_1024_160_bits_params() {
         let params = RFC5114_MODP_1024_160_BIT_PARAMS.to_owned();
@@ -248,6 +891,27 @@ mod tests {
         assert!(!verified);
     }
 
+    #[test]
+    fn test_verify_detailed_reports_failing_equation() {
+        let params = RFC5114_MODP_2048_256_BIT_PARAMS.to_owned();
+        let x = OsRng.gen_biguint_below(&params.q);
+        let ((y1, y2, r1, r2), k) = DiscreteLogChaumPedersen::commitment(&params, &x);
+        let c = DiscreteLogChaumPedersen::challenge(&params);
+        let s = DiscreteLogChaumPedersen::challenge_response(&params, &k, &c, &x);
+
+        let cp = (y1.clone(), y2.clone(), r1.clone(), r2.clone());
+        let outcome = DiscreteLogChaumPedersen::verify_detailed(&params, &s, &c, &cp);
+        assert_eq!(outcome, VerifyOutcome::Ok);
+
+        let bad_r1 = (y1.clone(), y2.clone(), &r1 * &params.g % &params.p, r2.clone());
+        let outcome = DiscreteLogChaumPedersen::verify_detailed(&params, &s, &c, &bad_r1);
+        assert_eq!(outcome, VerifyOutcome::FirstEquationFailed);
+
+        let bad_r2 = (y1, y2, r1, &r2 * &params.h % &params.p);
+        let outcome = DiscreteLogChaumPedersen::verify_detailed(&params, &s, &c, &bad_r2);
+        assert_eq!(outcome, VerifyOutcome::SecondEquationFailed);
+    }
+
     #[test]
     fn test_fail_rfc_2048_224_bits_params() {
         let params = RFC5114_MODP_2048_224_BIT_PARAMS.to_owned();
@@ -274,6 +938,84 @@ mod tests {
         assert!(!verified);
     }
 
+    #[test]
+    fn test_generate_random_below_order() {
+        // Every value of a small order is drawn, and none outside `[1, q)`.
+        let q = BigUint::from(11u32);
+        let mut seen = [false; 11];
+        for _ in 0..1000 {
+            let x = generate_random_below(&q).unwrap();
+            assert!(!x.is_zero() && x < q);
+            seen[x.to_u32_digits().first().copied().unwrap_or(0) as usize] = true;
+        }
+        assert!(seen[1..].iter().all(|&seen| seen));
+
+        let params = RFC5114_MODP_2048_256_BIT_PARAMS.to_owned();
+        for _ in 0..100 {
+            let x = generate_random_below(&params.q).unwrap();
+            assert!(!x.is_zero() && x < params.q);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "the bound must be at least 2")]
+    fn test_generate_random_below_one() {
+        let _ = generate_random_below(&BigUint::one());
+    }
+
+    #[test]
+    fn test_short_challenges_verify() {
+        let params = RFC5114_MODP_2048_256_BIT_PARAMS.to_owned();
+        let x = generate_random_below(&params.q).unwrap();
+        for bits in [MIN_CHALLENGE_BITS, 200] {
+            let (cp, k) = DiscreteLogChaumPedersen::commitment(&params, &x);
+            let c = DiscreteLogChaumPedersen::challenge_with_bits(&params, bits).unwrap();
+            assert!(c.bits() <= u64::from(bits));
+            let s = DiscreteLogChaumPedersen::challenge_response(&params, &k, &c, &x);
+            assert!(DiscreteLogChaumPedersen::verify(&params, &s, &c, &cp));
+        }
+    }
+
+    #[test]
+    fn test_challenge_bits_out_of_range_rejected() {
+        let params = RFC5114_MODP_2048_256_BIT_PARAMS.to_owned();
+        let q_bits = params.q.bits() as u32;
+        assert!(DiscreteLogChaumPedersen::challenge_with_bits(&params, q_bits).is_ok());
+        assert_eq!(
+            DiscreteLogChaumPedersen::challenge_with_bits(&params, q_bits + 1),
+            Err(InvalidChallengeBits::TooLong)
+        );
+        assert_eq!(
+            DiscreteLogChaumPedersen::challenge_with_bits(&params, 80),
+            Err(InvalidChallengeBits::TooShort)
+        );
+    }
+
+    #[test]
+    fn test_challenge_config_validation() {
+        let params = RFC5114_MODP_2048_224_BIT_PARAMS.to_owned();
+        let config = |challenge_bits| DiscreteLogChaumPedersenConfig { challenge_bits };
+        assert_eq!(config(127).validate(&params), Err(InvalidChallengeBits::TooShort));
+        assert_eq!(config(MIN_CHALLENGE_BITS).validate(&params), Ok(()));
+        assert_eq!(config(224).validate(&params), Ok(()));
+        assert_eq!(config(225).validate(&params), Err(InvalidChallengeBits::TooLong));
+    }
+
+    #[test]
+    fn test_security_level() {
+        assert_eq!(RFC5114_MODP_1024_160_BIT_PARAMS.security_level(), SecurityLevel::Bits80);
+        assert_eq!(RFC5114_MODP_2048_224_BIT_PARAMS.security_level(), SecurityLevel::Bits112);
+        assert_eq!(RFC5114_MODP_2048_256_BIT_PARAMS.security_level(), SecurityLevel::Bits112);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_assert_security_level_rejects_weak_params() {
+        crate::chaum_pedersen::assert_security_level(
+            RFC5114_MODP_1024_160_BIT_PARAMS.security_level().bits(),
+        );
+    }
+
     #[test]
     fn test_verify() {
         let g = BigUint::from(4u32);
@@ -305,4 +1047,162 @@ mod tests {
         // server verifies
         assert!(DiscreteLogChaumPedersen::verify(&params, &s, &c, &cp));
     }
+
+    /// Returns the RFC 5114 groups the response is exercised in.
+    #[allow(deprecated)]
+    fn rfc_groups() -> [GroupParams<BigUint>; 3] {
+        [
+            RFC5114_MODP_1024_160_BIT_PARAMS.to_owned(),
+            RFC5114_MODP_2048_224_BIT_PARAMS.to_owned(),
+            RFC5114_MODP_2048_256_BIT_PARAMS.to_owned(),
+        ]
+    }
+
+    /// Computes the response for a chosen `k`, checking that it lies in `[0, q)` and verifies
+    /// against the commitment made with that `k`.
+    fn checked_response(
+        params: &GroupParams<BigUint>, k: &BigUint, c: &BigUint, x: &BigUint,
+    ) -> BigUint {
+        let cp = (
+            params.g.modpow(x, &params.p),
+            params.h.modpow(x, &params.p),
+            params.g.modpow(k, &params.p),
+            params.h.modpow(k, &params.p),
+        );
+        let s = DiscreteLogChaumPedersen::challenge_response(params, k, c, x);
+        assert!(s < params.q);
+        assert!(DiscreteLogChaumPedersen::verify(params, &s, c, &cp));
+        s
+    }
+
+    #[test]
+    fn test_response_when_k_below_cx() {
+        for params in rfc_groups() {
+            let x = OsRng.gen_biguint_range(&BigUint::from(2u32), &params.q);
+            let c = OsRng.gen_biguint_range(&BigUint::from(2u32), &params.q);
+            let k = OsRng.gen_biguint_below(&x);
+            let s = checked_response(&params, &k, &c, &x);
+            assert_eq!((s + &c * &x - &k) % &params.q, BigUint::from(0u32));
+
+            // c * x - k is a multiple of q, so the response is 0 rather than q.
+            let (c, x, k) = (BigUint::from(2u32), (&params.q + 1u32) / 2u32, BigUint::one());
+            assert_eq!(checked_response(&params, &k, &c, &x), BigUint::from(0u32));
+        }
+    }
+
+    #[test]
+    fn test_response_when_k_equals_cx() {
+        for params in rfc_groups() {
+            let x = OsRng.gen_biguint_below(&params.q);
+            let c = DiscreteLogChaumPedersen::challenge(&params);
+            let k = &c * &x;
+            assert_eq!(checked_response(&params, &k, &c, &x), BigUint::from(0u32));
+        }
+    }
+
+    #[test]
+    fn test_response_when_k_above_cx() {
+        for params in rfc_groups() {
+            let x = OsRng.gen_biguint_below(&params.q);
+            let c = DiscreteLogChaumPedersen::challenge(&params);
+            let k = &c * &x + 5u32;
+            assert_eq!(checked_response(&params, &k, &c, &x), BigUint::from(5u32));
+
+            // k exceeds c * x by more than q, as commitments draw k below p.
+            let k = &c * &x + &params.q * 3u32 + 7u32;
+            assert_eq!(checked_response(&params, &k, &c, &x), BigUint::from(7u32));
+        }
+    }
+
+    #[cfg(feature = "constant_time")]
+    #[test]
+    fn test_constant_time_verify_with_last_byte_difference() {
+        let params = RFC5114_MODP_2048_256_BIT_PARAMS.to_owned();
+        let len = params.p.to_bytes_be().len();
+        let a = &params.p - 2u32;
+        let b = &a ^ BigUint::one();
+        assert!(ct_eq_padded(&a, &a, len));
+        assert!(!ct_eq_padded(&a, &b, len));
+        // Values with encodings of different lengths are compared over padded encodings.
+        assert!(!ct_eq_padded(&BigUint::one(), &BigUint::from(256u32), len));
+
+        let x = OsRng.gen_biguint_below(&params.q);
+        let ((y1, y2, r1, r2), k) = DiscreteLogChaumPedersen::commitment(&params, &x);
+        let c = DiscreteLogChaumPedersen::challenge(&params);
+        let s = DiscreteLogChaumPedersen::challenge_response(&params, &k, &c, &x);
+        let tampered = |r: &BigUint| r ^ BigUint::one();
+
+        // Both equations are evaluated in full, whichever one differs in its last byte.
+        let cp = (y1.clone(), y2.clone(), tampered(&r1), r2.clone());
+        assert_eq!(
+            DiscreteLogChaumPedersen::verify_detailed(&params, &s, &c, &cp),
+            VerifyOutcome::FirstEquationFailed
+        );
+        let cp = (y1.clone(), y2.clone(), r1.clone(), tampered(&r2));
+        assert_eq!(
+            DiscreteLogChaumPedersen::verify_detailed(&params, &s, &c, &cp),
+            VerifyOutcome::SecondEquationFailed
+        );
+        assert!(DiscreteLogChaumPedersen::verify(&params, &s, &c, &(y1, y2, r1, r2)));
+    }
+
+    #[test]
+    #[cfg(feature = "parallel")]
+    #[allow(deprecated)]
+    fn test_verify_many_matches_serial() {
+        let params = RFC5114_MODP_1024_160_BIT_PARAMS.to_owned();
+        let proofs: Vec<_> = (0..12u64)
+            .map(|i| {
+                let x = generate_random_below(&params.q).unwrap();
+                let mut proof = ProofBundle::<DiscreteLogChaumPedersen>::prove(
+                    &params,
+                    format!("user{}", i),
+                    i,
+                    &x,
+                );
+                if i % 3 == 0 {
+                    proof.s += 1u8;
+                }
+                proof
+            })
+            .collect();
+
+        let serial: Vec<bool> = proofs
+            .iter()
+            .map(|proof| {
+                let (y1, y2, _, _) = &proof.commitment;
+                proof.verify(&params, y1, y2)
+            })
+            .collect();
+        let parallel = DiscreteLogChaumPedersen::verify_many(&params, &proofs);
+        assert_eq!(parallel, serial);
+        for (i, verified) in parallel.into_iter().enumerate() {
+            assert_eq!(verified, i % 3 != 0);
+        }
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(64))]
+
+        #[test]
+        fn prop_response_in_range_and_verifies(
+            group in 0..3usize,
+            k in vec(any::<u8>(), 0..=256),
+            c in vec(any::<u8>(), 0..=32),
+            x in vec(any::<u8>(), 0..=32),
+        ) {
+            let params = &rfc_groups()[group];
+            let k = BigUint::from_bytes_be(&k) % &params.p;
+            let c = BigUint::from_bytes_be(&c) % &params.q;
+            let x = BigUint::from_bytes_be(&x) % &params.q;
+            checked_response(params, &k, &c, &x);
+        }
+
+        #[test]
+        fn prop_conversion_round_trip(bytes in vec(any::<u8>(), 0..=512)) {
+            // Encodings of any length are valid, so only round trips are checked.
+            let x = BigUint::from_bytes_be(&bytes);
+            prop_assert_eq!(BigUint::convert_from(&BigUint::convert_to(&x)), Ok(x));
+        }
+    }
 }
@@ -0,0 +1,175 @@
+//! # HSM-Backed Commitments
+//!
+//! This module provides [`HsmBackedChaumPedersen`], a Curve25519 prover that keeps the
+//! generation of its commitment randomness `k` out of process, in a hardware security module
+//! reachable over HTTP. A weak or compromised local RNG then cannot leak the secret through a
+//! predictable `k`.
+//!
+//! Fetching `k` is I/O, so the prover implements [`AsyncChaumPedersen`] rather than
+//! [`ChaumPedersen`]; the challenge, response and verification are those of
+//! [`Curve25519ChaumPedersen`].
+
+use crate::chaum_pedersen::curve25519::Curve25519ChaumPedersen;
+use crate::chaum_pedersen::{AsyncChaumPedersen, ChaumPedersen, GroupParams};
+use async_trait::async_trait;
+use curve25519_dalek::{RistrettoPoint, Scalar};
+use log::warn;
+use serde::Deserialize;
+use std::fmt;
+
+/// An error raised when the HSM does not supply commitment randomness.
+#[derive(Debug, PartialEq)]
+pub enum HsmError {
+    /// The HSM cannot be reached or rejected the request.
+    Unavailable,
+    /// The HSM answered with something other than 64 hex-encoded bytes of randomness.
+    MalformedRandomness,
+}
+
+impl fmt::Display for HsmError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            HsmError::Unavailable => write!(f, "HSM unavailable"),
+            HsmError::MalformedRandomness => write!(f, "Malformed randomness from the HSM"),
+        }
+    }
+}
+
+impl std::error::Error for HsmError {}
+
+/// Randomness supplied by the HSM.
+#[derive(Deserialize)]
+struct HsmRandomness {
+    randomness: String,
+}
+
+/// A Curve25519 prover drawing its commitment randomness from an HSM.
+///
+/// Each commitment fetches fresh randomness with a GET request to the HSM's endpoint, which
+/// answers with a JSON object whose `randomness` field holds 64 hex-encoded bytes. The bytes
+/// are reduced to the scalar `k` modulo the group order.
+pub struct HsmBackedChaumPedersen {
+    url: String,
+}
+
+impl HsmBackedChaumPedersen {
+    /// Creates a prover fetching from the given endpoint.
+    ///
+    /// # Arguments
+    /// * `url` - The URL of the HSM endpoint supplying the randomness.
+    pub fn new(url: impl Into<String>) -> Self {
+        Self { url: url.into() }
+    }
+
+    /// Fetches the commitment randomness `k` from the HSM.
+    async fn fetch_randomness(&self) -> Result<Scalar, HsmError> {
+        let randomness: HsmRandomness = reqwest::get(&self.url)
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| {
+                warn!("Failed to fetch randomness from {}: {}", self.url, e);
+                HsmError::Unavailable
+            })?
+            .json()
+            .await
+            .map_err(|e| {
+                warn!("Malformed randomness from {}: {}", self.url, e);
+                HsmError::MalformedRandomness
+            })?;
+        let bytes: [u8; 64] = hex::decode(&randomness.randomness)
+            .ok()
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or(HsmError::MalformedRandomness)?;
+        Ok(Scalar::from_bytes_mod_order_wide(&bytes))
+    }
+}
+
+#[async_trait]
+impl AsyncChaumPedersen for HsmBackedChaumPedersen {
+    type Secret = Scalar;
+    type Response = Scalar;
+    type Challenge = Scalar;
+    type GroupParameters = GroupParams<RistrettoPoint>;
+    type CommitParameters = (RistrettoPoint, RistrettoPoint, RistrettoPoint, RistrettoPoint);
+    type CommitmentRandom = Scalar;
+    type Error = HsmError;
+
+    /// Computes `(y1, y2, r1, r2) = (g * x, h * x, g * k, h * k)` for the `k` fetched from the
+    /// HSM.
+    async fn commitment(
+        &self, params: &GroupParams<RistrettoPoint>, x: &Scalar,
+    ) -> Result<(Self::CommitParameters, Scalar), HsmError> {
+        let k = self.fetch_randomness().await?;
+        Ok(((params.g * x, params.h * x, params.g * k, params.h * k), k))
+    }
+
+    async fn challenge(&self, params: &GroupParams<RistrettoPoint>) -> Result<Scalar, HsmError> {
+        Ok(<Curve25519ChaumPedersen as ChaumPedersen>::challenge(params))
+    }
+
+    async fn challenge_response(
+        &self, params: &GroupParams<RistrettoPoint>, k: &Scalar, c: &Scalar, x: &Scalar,
+    ) -> Scalar {
+        <Curve25519ChaumPedersen as ChaumPedersen>::challenge_response(params, k, c, x)
+    }
+
+    async fn verify(
+        &self, params: &GroupParams<RistrettoPoint>, s: &Scalar, c: &Scalar,
+        cp: &Self::CommitParameters,
+    ) -> bool {
+        <Curve25519ChaumPedersen as ChaumPedersen>::verify(params, s, c, cp)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chaum_pedersen::constants::EC25519_GROUP_PARAMS;
+    use crate::rand::tests::mock_endpoint;
+    use crate::rand::RandomGenerator;
+
+    /// Runs the protocol with an asynchronous prover, returning whether the proof verifies.
+    async fn execute<C>(protocol: &C, params: &C::GroupParameters, x: &C::Secret) -> bool
+    where
+        C: AsyncChaumPedersen,
+        C::Error: fmt::Debug,
+    {
+        let (cp, k) = protocol.commitment(params, x).await.unwrap();
+        let c = protocol.challenge(params).await.unwrap();
+        let s = protocol.challenge_response(params, &k, &c, x).await;
+        protocol.verify(params, &s, &c, &cp).await
+    }
+
+    #[tokio::test]
+    async fn test_hsm_supplies_commitment_randomness() {
+        let body = format!(r#"{{"randomness":"{}"}}"#, hex::encode([7u8; 64]));
+        let hsm = HsmBackedChaumPedersen::new(mock_endpoint(200, body).await);
+        let params = EC25519_GROUP_PARAMS.to_owned();
+        let x = Scalar::generate_random().unwrap();
+
+        let ((_, _, r1, r2), k) = hsm.commitment(&params, &x).await.unwrap();
+        assert_eq!(k, Scalar::from_bytes_mod_order_wide(&[7u8; 64]));
+        assert_eq!((r1, r2), (params.g * k, params.h * k));
+        assert!(execute(&hsm, &params, &x).await);
+    }
+
+    #[tokio::test]
+    async fn test_hsm_failures_fail_the_commitment() {
+        let params = EC25519_GROUP_PARAMS.to_owned();
+        let x = Scalar::generate_random().unwrap();
+
+        let hsm = HsmBackedChaumPedersen::new(mock_endpoint(503, String::new()).await);
+        assert_eq!(hsm.commitment(&params, &x).await.unwrap_err(), HsmError::Unavailable);
+
+        let body = format!(r#"{{"randomness":"{}"}}"#, hex::encode([7u8; 32]));
+        let hsm = HsmBackedChaumPedersen::new(mock_endpoint(200, body).await);
+        assert_eq!(hsm.commitment(&params, &x).await.unwrap_err(), HsmError::MalformedRandomness);
+    }
+
+    #[tokio::test]
+    async fn test_synchronous_protocols_run_asynchronously() {
+        let params = EC25519_GROUP_PARAMS.to_owned();
+        let x = Scalar::generate_random().unwrap();
+        assert!(execute(&Curve25519ChaumPedersen {}, &params, &x).await);
+    }
+}
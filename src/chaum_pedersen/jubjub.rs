@@ -0,0 +1,332 @@
+//! # Jubjub Curve Chaum-Pedersen Protocol Module
+//!
+//! This module implements the Chaum-Pedersen protocol over the prime-order subgroup of the
+//! Jubjub elliptic curve, which is embedded in the BLS12-381 scalar field and therefore cheap to
+//! use inside zcash-style circuits.
+//! The protocol includes methods for generating commitments, creating challenges,
+//! responding to challenges, and verifying the correctness of the response.
+
+use crate::chaum_pedersen::transcript::TranscriptChallenge;
+use crate::chaum_pedersen::{
+    ChaumPedersen, GroupParams, InvalidGroupParams, ValidateGroupParams, VerifyOutcome,
+};
+use crate::conversion::{ByteConvertible, ConversionError};
+use crate::rand::{ProtocolRng, RandomGenerator};
+use alloc::vec::Vec;
+use group::ff::Field;
+use group::{Group, GroupEncoding};
+use jubjub::{Scalar, SubgroupPoint};
+use merlin::Transcript;
+
+/// The JubjubChaumPedersen struct defines the specific types used in the Chaum-Pedersen protocol for the Jubjub curve.
+pub struct JubjubChaumPedersen {}
+
+impl ChaumPedersen for JubjubChaumPedersen {
+    type Secret = Scalar;
+    type Response = Scalar;
+    type Challenge = Scalar;
+    type CommitmentRandom = Scalar;
+    type GroupParameters = GroupParams<SubgroupPoint>;
+    type CommitParameters = (SubgroupPoint, SubgroupPoint, SubgroupPoint, SubgroupPoint);
+
+    /// Generates a commitment to a secret on the Jubjub curve.
+    ///
+    /// # Parameters
+    ///
+    /// * `params` - Group parameters of the Jubjub curve.
+    /// * `x` - The secret scalar value to which the commitment is made.
+    ///
+    /// # Returns
+    ///
+    /// Returns a tuple containing the commitment parameters and a commitment random scalar.
+    fn commitment(
+        params: &Self::GroupParameters, x: &Self::Secret,
+    ) -> (Self::CommitParameters, Self::CommitmentRandom)
+    where
+        Self: Sized,
+    {
+        let y1 = params.g * x;
+        let y2 = params.h * x;
+        let mut rng = ProtocolRng;
+        let k = Scalar::random(&mut rng);
+        let r1 = params.g * k;
+        let r2 = params.h * k;
+        ((y1, y2, r1, r2), k)
+    }
+
+    /// Generates a random challenge scalar.
+    ///
+    /// # Parameters
+    ///
+    /// * `_params` - Ignored in this implementation. Group parameters can be used if needed.
+    ///
+    /// # Returns
+    ///
+    /// Returns a random scalar value to be used as a challenge.
+    fn challenge(_: &GroupParams<SubgroupPoint>) -> Self::Challenge {
+        let mut rng = ProtocolRng;
+        Scalar::random(&mut rng)
+    }
+
+    /// Generates a response to a challenge given a secret and a random scalar.
+    ///
+    /// # Parameters
+    ///
+    /// * `_params` - Ignored in this implementation. Group parameters can be used if needed.
+    /// * `k` - The random scalar used during commitment.
+    /// * `c` - The challenge scalar.
+    /// * `x` - The secret scalar.
+    ///
+    /// # Returns
+    ///
+    /// Returns the response scalar, which is calculated as `k + (c * x)`.
+    fn challenge_response(
+        _: &Self::GroupParameters, k: &Self::CommitmentRandom, c: &Self::Challenge,
+        x: &Self::Secret,
+    ) -> Self::Response
+    where
+        Self: Sized,
+    {
+        k + (c * x)
+    }
+
+    /// Verifies the correctness of the response to a challenge.
+    ///
+    /// # Parameters
+    ///
+    /// * `params` - Group parameters of the Jubjub curve.
+    /// * `s` - The response scalar.
+    /// * `c` - The challenge scalar.
+    /// * `cp` - The commitment parameters tuple.
+    ///
+    /// # Returns
+    ///
+    /// Returns `VerifyOutcome::Ok` if both equations hold, or the first equation that fails.
+    fn verify_detailed(
+        params: &Self::GroupParameters, s: &Self::Response, c: &Self::Challenge,
+        cp: &Self::CommitParameters,
+    ) -> VerifyOutcome {
+        let (y1, y2, r1, r2) = cp;
+        #[cfg(feature = "constant_time")]
+        {
+            // `SubgroupPoint` has no `ConstantTimeEq`, so compare the canonical encodings.
+            use subtle::ConstantTimeEq;
+            VerifyOutcome::from_equations(
+                (params.g * s)
+                    .to_bytes()
+                    .ct_eq(&(r1 + (y1 * c)).to_bytes())
+                    .into(),
+                (params.h * s)
+                    .to_bytes()
+                    .ct_eq(&(r2 + (y2 * c)).to_bytes())
+                    .into(),
+            )
+        }
+        #[cfg(not(feature = "constant_time"))]
+        VerifyOutcome::from_equations(params.g * s == r1 + (y1 * c), params.h * s == r2 + (y2 * c))
+    }
+}
+
+impl ValidateGroupParams for SubgroupPoint {
+    /// Checks that `g` and `h` are distinct and not the identity. `SubgroupPoint` can only hold
+    /// points of the prime-order subgroup, so every such point generates the whole subgroup.
+    fn validate(params: &GroupParams<SubgroupPoint>) -> Result<(), InvalidGroupParams> {
+        if bool::from(params.g.is_identity()) || bool::from(params.h.is_identity()) {
+            return Err(InvalidGroupParams::TrivialGenerator);
+        }
+        if params.g == params.h {
+            return Err(InvalidGroupParams::DependentGenerators);
+        }
+        Ok(())
+    }
+
+    /// Checks that the element is not the identity. Decoding already rejects points outside
+    /// the prime-order subgroup.
+    fn is_valid_group_element(
+        _params: &GroupParams<SubgroupPoint>, element: &SubgroupPoint,
+    ) -> bool {
+        !bool::from(element.is_identity())
+    }
+}
+
+impl ByteConvertible<SubgroupPoint> for SubgroupPoint {
+    fn convert_to(t: &SubgroupPoint) -> Vec<u8> {
+        t.to_bytes().to_vec()
+    }
+
+    fn convert_from(bytes: &[u8]) -> Result<SubgroupPoint, ConversionError> {
+        let array: [u8; 32] = bytes
+            .try_into()
+            .map_err(|_| ConversionError::InvalidLength)?;
+
+        // Rejects points that are not on the curve or lie outside the prime-order subgroup.
+        Option::from(SubgroupPoint::from_bytes(&array)).ok_or(ConversionError::InvalidEncoding)
+    }
+}
+
+impl ByteConvertible<Scalar> for Scalar {
+    fn convert_to(t: &Scalar) -> Vec<u8> {
+        t.to_bytes().to_vec()
+    }
+
+    fn convert_from(bytes: &[u8]) -> Result<Scalar, ConversionError> {
+        // pad the array with zeros
+        let array = |input: &[u8]| -> [u8; 64] {
+            let mut output = [0u8; 64];
+            let len = input.len().min(64);
+            output[..len].copy_from_slice(&input[..len]);
+            output // Return the new array
+        };
+        Ok(Scalar::from_bytes_wide(&array(bytes)))
+    }
+}
+
+impl RandomGenerator<Scalar> for Scalar {
+    /// Generates a random `Scalar`.
+    ///
+    /// # Returns
+    /// A `Result` containing the random `Scalar`, or an error if the generation fails.
+    ///
+    /// # Errors
+    /// Returns an error if the random number generator fails.
+    fn generate_random() -> Result<Scalar, rand_core::Error> {
+        Ok(Scalar::random(&mut ProtocolRng))
+    }
+}
+
+impl RandomGenerator<SubgroupPoint> for SubgroupPoint {
+    /// Generates a random `SubgroupPoint`.
+    ///
+    /// # Returns
+    /// A `Result` containing the random `SubgroupPoint`, or an error if the generation fails.
+    ///
+    /// # Errors
+    /// Returns an error if the random number generator fails.
+    fn generate_random() -> Result<SubgroupPoint, rand_core::Error> {
+        Ok(SubgroupPoint::random(&mut ProtocolRng))
+    }
+}
+
+impl TranscriptChallenge<GroupParams<SubgroupPoint>> for Scalar {
+    /// Squeezes 64 bytes out of the transcript and reduces them to a `Scalar`.
+    fn from_transcript(transcript: &mut Transcript, _: &GroupParams<SubgroupPoint>) -> Scalar {
+        let mut bytes = [0u8; 64];
+        transcript.challenge_bytes(b"c", &mut bytes);
+        Scalar::from_bytes_wide(&bytes)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    //! Test module for Jubjub Curve Chaum-Pedersen Protocol.
+    //!
+    //! Contains tests that verify the correct functioning of the commitment,
+    //! challenge, and verification steps of the protocol using the Jubjub elliptic curve.
+
+    use super::*;
+    use crate::chaum_pedersen::constants::JUBJUB_GROUP_PARAMS;
+    use crate::chaum_pedersen::test::test_execute_protocol;
+    use proptest::collection::vec;
+    use proptest::prelude::*;
+    use rand_core::OsRng;
+
+    #[test]
+    fn jubjub_point_conversion_round_trip() {
+        let original = SubgroupPoint::generate_random().unwrap();
+        let bytes = SubgroupPoint::convert_to(&original);
+        let recovered = SubgroupPoint::convert_from(&bytes).unwrap();
+        assert_eq!(original, recovered);
+    }
+
+    #[test]
+    fn jubjub_point_invalid_bytes() {
+        assert_eq!(
+            SubgroupPoint::convert_from(&[0u8; 31]).unwrap_err(),
+            ConversionError::InvalidLength
+        );
+        assert_eq!(
+            SubgroupPoint::convert_from(&[0xffu8; 32]).unwrap_err(),
+            ConversionError::InvalidEncoding
+        );
+    }
+
+    #[test]
+    fn jubjub_scalar_conversion_round_trip() {
+        let original = Scalar::generate_random().unwrap();
+        let bytes = Scalar::convert_to(&original);
+        let recovered = Scalar::convert_from(&bytes).unwrap();
+        assert_eq!(original, recovered);
+    }
+
+    #[test]
+    fn jubjub_params_validate() {
+        let mut params = JUBJUB_GROUP_PARAMS.to_owned();
+        assert_eq!(params.validate(), Ok(()));
+        params.h = SubgroupPoint::identity();
+        assert_eq!(params.validate(), Err(InvalidGroupParams::TrivialGenerator));
+    }
+
+    /// Test verification using standard protocol execution.
+    #[test]
+    fn test_elliptic_curve_standard_verification() {
+        let mut rng = OsRng;
+        let x = Scalar::random(&mut rng);
+        let params = JUBJUB_GROUP_PARAMS.to_owned();
+
+        // Testing the correctness of the serialization and deserialization of group parameters.
+        let gb = params.g.to_bytes();
+        let restored_g = SubgroupPoint::from_bytes(&gb).unwrap();
+        assert_eq!(params.g, restored_g);
+
+        let hb = params.h.to_bytes();
+        let restored_h = SubgroupPoint::from_bytes(&hb).unwrap();
+        assert_eq!(params.h, restored_h);
+
+        // Asserting the successful execution of the protocol.
+        assert!(test_execute_protocol::<JubjubChaumPedersen>(&params, &x));
+    }
+
+    /// Test verification fails with an incorrect response.
+    #[test]
+    fn test_fail_elliptic_curve_verification() {
+        let mut rng = OsRng;
+        let x = Scalar::random(&mut rng);
+        let params = JUBJUB_GROUP_PARAMS.to_owned();
+
+        // Generating commitment and a challenge to simulate an authentication attempt.
+        let (cp, _) = JubjubChaumPedersen::commitment(&params, &x);
+        let c = JubjubChaumPedersen::challenge(&params);
+
+        // Simulating a fake response to force a failed verification.
+        let fake_response = Scalar::random(&mut rng);
+
+        // Asserting that the verification should fail with the fake response.
+        let verified = JubjubChaumPedersen::verify(&params, &fake_response, &c, &cp);
+        assert!(!verified);
+    }
+
+    proptest! {
+        #[test]
+        fn prop_conversions_round_trip(seed in vec(any::<u8>(), 64)) {
+            let seed: [u8; 64] = seed.try_into().unwrap();
+            let x = Scalar::from_bytes_wide(&seed);
+            prop_assert_eq!(Scalar::convert_from(&Scalar::convert_to(&x)), Ok(x));
+            let point = SubgroupPoint::generator() * x;
+            prop_assert_eq!(SubgroupPoint::convert_from(&SubgroupPoint::convert_to(&point)), Ok(point));
+        }
+
+        #[test]
+        fn prop_wrong_length_rejected(
+            bytes in vec(any::<u8>(), 0..=96).prop_filter("not 32 bytes", |b| b.len() != 32),
+        ) {
+            prop_assert_eq!(SubgroupPoint::convert_from(&bytes), Err(ConversionError::InvalidLength));
+            // Scalars of any length are reduced into the field, so only panics are ruled out.
+            let _ = Scalar::convert_from(&bytes);
+        }
+
+        #[test]
+        fn prop_arbitrary_point_bytes_do_not_panic(bytes in vec(any::<u8>(), 32)) {
+            let _ = SubgroupPoint::convert_from(&bytes);
+        }
+    }
+}
@@ -0,0 +1,198 @@
+//! # Deterministic Key Derivation
+//!
+//! This module derives registration keypairs deterministically from a seed, so interop test
+//! suites written in other languages can fix a seed and compare the resulting encodings byte for
+//! byte. The seed is expanded with HKDF-SHA256 into 64 bytes of key material, which is reduced
+//! into the secret `x`; the public values are then `y1 = g^x` and `y2 = h^x`.
+//!
+//! Derived keys are only as strong as their seed, so this is intended for test vectors rather
+//! than production registrations.
+
+use crate::chaum_pedersen::brainpool::{self, BrainpoolChaumPedersen};
+use crate::chaum_pedersen::curve25519::Curve25519ChaumPedersen;
+use crate::chaum_pedersen::discretelog::DiscreteLogChaumPedersen;
+use crate::chaum_pedersen::jubjub::JubjubChaumPedersen;
+use crate::chaum_pedersen::pallas::PallasCurveChaumPedersen;
+use crate::chaum_pedersen::vesta::VestaCurveChaumPedersen;
+use crate::chaum_pedersen::{ChaumPedersen, GroupParams};
+use curve25519_dalek::{RistrettoPoint, Scalar};
+use hkdf::Hkdf;
+use num_bigint::BigUint;
+use pasta_curves::group::ff::FromUniformBytes;
+use pasta_curves::{pallas, vesta};
+use sha2::Sha256;
+
+/// The HKDF `info` string binding derived key material to this application.
+const KEYGEN_INFO: &[u8] = b"zk_pass.keygen";
+
+/// A trait for Chaum-Pedersen backends whose keypairs can be derived from a seed.
+pub trait DeterministicKeygen: ChaumPedersen {
+    /// The type of the public values `y1` and `y2`.
+    type Public;
+
+    /// Reduces 64 bytes of uniformly distributed key material into a secret.
+    ///
+    /// # Arguments
+    /// * `params` - Group parameters the secret is derived for.
+    /// * `okm` - The key material expanded from the seed.
+    ///
+    /// # Returns
+    /// The secret `x`.
+    fn secret_from_okm(params: &Self::GroupParameters, okm: &[u8; 64]) -> Self::Secret;
+
+    /// Computes the public values for a secret.
+    ///
+    /// # Arguments
+    /// * `params` - Group parameters used in the protocol.
+    /// * `x` - The secret value.
+    ///
+    /// # Returns
+    /// A tuple containing `y1` and `y2`.
+    fn public_values(
+        params: &Self::GroupParameters, x: &Self::Secret,
+    ) -> (Self::Public, Self::Public);
+}
+
+/// Derives a keypair deterministically from a seed using HKDF-SHA256.
+///
+/// # Arguments
+/// * `seed` - The input key material; the same seed always yields the same keypair.
+/// * `params` - Group parameters used in the protocol.
+///
+/// # Returns
+/// A tuple containing the secret `x` and the public values `y1` and `y2`.
+pub fn derive_keypair<C: DeterministicKeygen>(
+    seed: &[u8], params: &C::GroupParameters,
+) -> (C::Secret, C::Public, C::Public) {
+    let mut okm = [0u8; 64];
+    Hkdf::<Sha256>::new(None, seed)
+        .expand(KEYGEN_INFO, &mut okm)
+        .expect("64 bytes is a valid HKDF-SHA256 output length");
+    let x = C::secret_from_okm(params, &okm);
+    let (y1, y2) = C::public_values(params, &x);
+    (x, y1, y2)
+}
+
+impl DeterministicKeygen for DiscreteLogChaumPedersen {
+    type Public = BigUint;
+
+    fn secret_from_okm(params: &GroupParams<BigUint>, okm: &[u8; 64]) -> BigUint {
+        BigUint::from_bytes_be(okm) % &params.q
+    }
+
+    fn public_values(params: &GroupParams<BigUint>, x: &BigUint) -> (BigUint, BigUint) {
+        (params.g.modpow(x, &params.p), params.h.modpow(x, &params.p))
+    }
+}
+
+impl DeterministicKeygen for Curve25519ChaumPedersen {
+    type Public = RistrettoPoint;
+
+    fn secret_from_okm(_: &GroupParams<RistrettoPoint>, okm: &[u8; 64]) -> Scalar {
+        Scalar::from_bytes_mod_order_wide(okm)
+    }
+
+    fn public_values(
+        params: &GroupParams<RistrettoPoint>, x: &Scalar,
+    ) -> (RistrettoPoint, RistrettoPoint) {
+        (params.g * x, params.h * x)
+    }
+}
+
+impl DeterministicKeygen for PallasCurveChaumPedersen {
+    type Public = pallas::Point;
+
+    fn secret_from_okm(_: &GroupParams<pallas::Point>, okm: &[u8; 64]) -> pallas::Scalar {
+        pallas::Scalar::from_uniform_bytes(okm)
+    }
+
+    fn public_values(
+        params: &GroupParams<pallas::Point>, x: &pallas::Scalar,
+    ) -> (pallas::Point, pallas::Point) {
+        (params.g * x, params.h * x)
+    }
+}
+
+impl DeterministicKeygen for VestaCurveChaumPedersen {
+    type Public = vesta::Point;
+
+    fn secret_from_okm(_: &GroupParams<vesta::Point>, okm: &[u8; 64]) -> vesta::Scalar {
+        vesta::Scalar::from_uniform_bytes(okm)
+    }
+
+    fn public_values(
+        params: &GroupParams<vesta::Point>, x: &vesta::Scalar,
+    ) -> (vesta::Point, vesta::Point) {
+        (params.g * x, params.h * x)
+    }
+}
+
+impl DeterministicKeygen for JubjubChaumPedersen {
+    type Public = jubjub::SubgroupPoint;
+
+    fn secret_from_okm(_: &GroupParams<jubjub::SubgroupPoint>, okm: &[u8; 64]) -> jubjub::Scalar {
+        jubjub::Scalar::from_bytes_wide(okm)
+    }
+
+    fn public_values(
+        params: &GroupParams<jubjub::SubgroupPoint>, x: &jubjub::Scalar,
+    ) -> (jubjub::SubgroupPoint, jubjub::SubgroupPoint) {
+        (params.g * x, params.h * x)
+    }
+}
+
+impl DeterministicKeygen for BrainpoolChaumPedersen {
+    type Public = bp256::r1::ProjectivePoint;
+
+    fn secret_from_okm(
+        _: &GroupParams<bp256::r1::ProjectivePoint>, okm: &[u8; 64],
+    ) -> bp256::Scalar {
+        brainpool::scalar_from_bytes_wide(okm)
+    }
+
+    fn public_values(
+        params: &GroupParams<bp256::r1::ProjectivePoint>, x: &bp256::Scalar,
+    ) -> (bp256::r1::ProjectivePoint, bp256::r1::ProjectivePoint) {
+        (params.g * x, params.h * x)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chaum_pedersen::constants::{
+        EC25519_GROUP_PARAMS, RFC5114_MODP_2048_256_BIT_PARAMS,
+    };
+    use crate::conversion::ByteConvertible;
+
+    const SEED: &[u8] = b"zk_pass test vector seed";
+
+    #[test]
+    fn test_discrete_log_derive_keypair_is_deterministic() {
+        let params = RFC5114_MODP_2048_256_BIT_PARAMS.to_owned();
+        let (x, y1, y2) = derive_keypair::<DiscreteLogChaumPedersen>(SEED, &params);
+        let again = derive_keypair::<DiscreteLogChaumPedersen>(SEED, &params);
+        assert_eq!((x.clone(), y1.clone(), y2.clone()), again);
+        assert_eq!(y1, params.g.modpow(&x, &params.p));
+        assert_eq!(y2, params.h.modpow(&x, &params.p));
+    }
+
+    #[test]
+    fn test_curve25519_derive_keypair_is_deterministic() {
+        let params = EC25519_GROUP_PARAMS.to_owned();
+        let (x, y1, y2) = derive_keypair::<Curve25519ChaumPedersen>(SEED, &params);
+        assert_eq!((x, y1, y2), derive_keypair::<Curve25519ChaumPedersen>(SEED, &params));
+        assert_eq!(
+            hex::encode(RistrettoPoint::convert_to(&y1)),
+            "98b4c4dbc285be6993ea2b3569850201f5cfaf069af512a42bcc580bb3ffe07f"
+        );
+    }
+
+    #[test]
+    fn test_different_seeds_derive_different_keys() {
+        let params = EC25519_GROUP_PARAMS.to_owned();
+        let (x1, _, _) = derive_keypair::<Curve25519ChaumPedersen>(b"seed one", &params);
+        let (x2, _, _) = derive_keypair::<Curve25519ChaumPedersen>(b"seed two", &params);
+        assert_ne!(x1, x2);
+    }
+}
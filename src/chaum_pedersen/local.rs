@@ -0,0 +1,337 @@
+//! # Local Chaum-Pedersen Protocol Module
+//!
+//! This module runs the interactive Chaum-Pedersen protocol between a prover and a verifier in
+//! the same process, exchanging the commitment, challenge and response over `tokio` channels
+//! instead of gRPC. It suits tests and embedded servers that do not need network I/O.
+//!
+//! A [`LocalVerifier`] owns the receiving end of the channel and, once started with
+//! [`LocalVerifier::run`], handles each request in its own task against a user data store.
+//! Any number of [`LocalProver`] clones can then authenticate concurrently.
+
+use crate::chaum_pedersen::{ChaumPedersen, GroupParams};
+use crate::repository::dao::AsyncUserDao;
+use crate::repository::models::User;
+use crate::repository::session::{InMemorySessionStore, SessionStore, DEFAULT_SESSION_TTL};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+use tokio::sync::{mpsc, oneshot};
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+/// An error raised while authenticating through a local verifier.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LocalAuthError {
+    /// The verifier has stopped and no longer accepts requests.
+    VerifierClosed,
+    /// No user with the given name is registered.
+    UserNotFound,
+    /// The authentication challenge does not exist or has already been answered.
+    ChallengeNotFound,
+    /// The response does not verify against the challenge.
+    VerificationFailed,
+}
+
+impl fmt::Display for LocalAuthError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LocalAuthError::VerifierClosed => write!(f, "Local verifier is closed"),
+            LocalAuthError::UserNotFound => write!(f, "User not found"),
+            LocalAuthError::ChallengeNotFound => write!(f, "Challenge not found"),
+            LocalAuthError::VerificationFailed => write!(f, "Invalid authentication"),
+        }
+    }
+}
+
+impl std::error::Error for LocalAuthError {}
+
+/// A request sent from a prover to the verifier, carrying the channel for its reply.
+enum LocalRequest<C: ChaumPedersen> {
+    /// Commits to a secret, registering the user on first contact, and asks for a challenge.
+    Commit {
+        user: String,
+        commitment: C::CommitParameters,
+        reply: oneshot::Sender<Result<(String, C::Challenge), LocalAuthError>>,
+    },
+    /// Answers a challenge, asking for a session ID.
+    Answer {
+        auth_id: String,
+        s: C::Response,
+        reply: oneshot::Sender<Result<String, LocalAuthError>>,
+    },
+}
+
+/// The proving side of a local Chaum-Pedersen protocol run.
+///
+/// Provers are cheap to clone; every clone sends its requests to the same verifier.
+pub struct LocalProver<C: ChaumPedersen> {
+    sender: mpsc::Sender<LocalRequest<C>>,
+}
+
+impl<C: ChaumPedersen> Clone for LocalProver<C> {
+    fn clone(&self) -> Self {
+        Self {
+            sender: self.sender.clone(),
+        }
+    }
+}
+
+impl<C, T, S> LocalProver<C>
+where
+    C: ChaumPedersen<
+        GroupParameters = GroupParams<T>,
+        CommitParameters = (T, T, T, T),
+        Challenge = S,
+        Response = S,
+    >,
+{
+    /// Proves knowledge of `x` to the verifier, driving the full protocol.
+    ///
+    /// The user is registered with the commitment's `y1` and `y2` on first contact; later
+    /// proofs are verified against the values registered then.
+    ///
+    /// # Arguments
+    /// * `params` - The group parameters used in the protocol.
+    /// * `user` - The name of the user to authenticate as.
+    /// * `x` - The user's secret.
+    ///
+    /// # Returns
+    /// The session ID issued by the verifier, or a `LocalAuthError` if authentication fails.
+    pub async fn prove(
+        &self, params: &GroupParams<T>, user: &str, x: &C::Secret,
+    ) -> Result<String, LocalAuthError> {
+        let (commitment, k) = C::commitment(params, x);
+        let (auth_id, c) = self
+            .request(|reply| LocalRequest::Commit {
+                user: user.to_string(),
+                commitment,
+                reply,
+            })
+            .await?;
+        let s = C::challenge_response(params, &k, &c, x);
+        self.request(|reply| LocalRequest::Answer { auth_id, s, reply })
+            .await
+    }
+
+    /// Sends a request to the verifier and waits for its reply.
+    async fn request<R>(
+        &self, request: impl FnOnce(oneshot::Sender<Result<R, LocalAuthError>>) -> LocalRequest<C>,
+    ) -> Result<R, LocalAuthError> {
+        let (reply, response) = oneshot::channel();
+        self.sender
+            .send(request(reply))
+            .await
+            .map_err(|_| LocalAuthError::VerifierClosed)?;
+        response.await.map_err(|_| LocalAuthError::VerifierClosed)?
+    }
+}
+
+/// The verifying side of a local Chaum-Pedersen protocol run.
+pub struct LocalVerifier<C: ChaumPedersen> {
+    receiver: mpsc::Receiver<LocalRequest<C>>,
+    sessions: Arc<dyn SessionStore + Send + Sync>,
+}
+
+impl<C, T, S> LocalVerifier<C>
+where
+    C: ChaumPedersen<
+            GroupParameters = GroupParams<T>,
+            CommitParameters = (T, T, T, T),
+            Challenge = S,
+            Response = S,
+        > + 'static,
+    T: Clone + Send + Sync + 'static,
+    S: Clone + Send + Sync + 'static,
+{
+    /// Creates a verifier and a prover connected to it.
+    ///
+    /// # Arguments
+    /// * `buffer` - The number of requests that can be queued before provers wait.
+    ///
+    /// # Returns
+    /// The verifier and a prover that sends its requests to it.
+    pub fn new(buffer: usize) -> (Self, LocalProver<C>) {
+        let (sender, receiver) = mpsc::channel(buffer);
+        let sessions = Arc::new(InMemorySessionStore::new());
+        (Self { receiver, sessions }, LocalProver { sender })
+    }
+
+    /// Sets the store holding the sessions issued to provers. Defaults to an
+    /// [`InMemorySessionStore`].
+    ///
+    /// # Arguments
+    /// * `sessions` - The session store.
+    pub fn with_session_store(mut self, sessions: Arc<dyn SessionStore + Send + Sync>) -> Self {
+        self.sessions = sessions;
+        self
+    }
+
+    /// Starts processing requests from the channel, each in its own task.
+    ///
+    /// The verifier stops once every prover has been dropped.
+    ///
+    /// # Arguments
+    /// * `params` - The group parameters used in the protocol.
+    /// * `dao` - The store holding registered users and pending challenges.
+    ///
+    /// # Returns
+    /// A handle to the task processing requests.
+    pub fn run<D: AsyncUserDao<T, S> + 'static>(
+        mut self, params: GroupParams<T>, dao: D,
+    ) -> JoinHandle<()> {
+        let params = Arc::new(params);
+        let dao = Arc::new(dao);
+        tokio::spawn(async move {
+            while let Some(request) = self.receiver.recv().await {
+                let (params, dao, sessions) = (params.clone(), dao.clone(), self.sessions.clone());
+                tokio::spawn(async move {
+                    Self::handle(&params, dao.as_ref(), sessions.as_ref(), request).await
+                });
+            }
+        })
+    }
+
+    /// Handles a single request and sends back its reply.
+    async fn handle<D: AsyncUserDao<T, S>>(
+        params: &GroupParams<T>, dao: &D, sessions: &(dyn SessionStore + Send + Sync),
+        request: LocalRequest<C>,
+    ) {
+        // A prover that stopped waiting for its reply is not an error for the verifier.
+        match request {
+            LocalRequest::Commit {
+                user,
+                commitment,
+                reply,
+            } => {
+                reply
+                    .send(Self::commit(params, dao, user, commitment).await)
+                    .ok();
+            }
+            LocalRequest::Answer { auth_id, s, reply } => {
+                reply
+                    .send(Self::answer(params, dao, sessions, &auth_id, &s).await)
+                    .ok();
+            }
+        }
+    }
+
+    /// Stores a commitment, registering the user on first contact, and issues a challenge.
+    async fn commit<D: AsyncUserDao<T, S>>(
+        params: &GroupParams<T>, dao: &D, username: String, commitment: (T, T, T, T),
+    ) -> Result<(String, S), LocalAuthError> {
+        let (y1, y2, r1, r2) = commitment;
+
+        // Does nothing if the user is already registered.
+        dao.create(User {
+            username: username.clone(),
+            y1,
+            y2,
+            metadata: HashMap::new(),
+            namespace: String::new(),
+            params_version: 0,
+        })
+        .await;
+
+        let c = C::challenge(params);
+        let auth_id = dao
+            .create_auth_challenge(&username, &r1, &r2, &c, &[], None)
+            .await;
+        Ok((auth_id, c))
+    }
+
+    /// Verifies the answer to a challenge and issues a session ID.
+    async fn answer<D: AsyncUserDao<T, S>>(
+        params: &GroupParams<T>, dao: &D, sessions: &(dyn SessionStore + Send + Sync),
+        auth_id: &str, s: &S,
+    ) -> Result<String, LocalAuthError> {
+        let challenge = dao
+            .take_authentication_challenge(auth_id)
+            .await
+            .ok_or(LocalAuthError::ChallengeNotFound)?;
+
+        let user = dao
+            .read(&challenge.user)
+            .await
+            .ok_or(LocalAuthError::UserNotFound)?;
+        let commitment = (user.y1, user.y2, challenge.r1, challenge.r2);
+        if !C::verify(params, s, &challenge.c, &commitment) {
+            return Err(LocalAuthError::VerificationFailed);
+        }
+
+        let session_id = Uuid::new_v4().to_string();
+        sessions.upsert(&session_id, &challenge.user, DEFAULT_SESSION_TTL);
+        Ok(session_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chaum_pedersen::constants::EC25519_GROUP_PARAMS;
+    use crate::chaum_pedersen::curve25519::Curve25519ChaumPedersen;
+    use crate::rand::RandomGenerator;
+    use crate::repository::daoimpl::AsyncInMemoryUserDao;
+    use curve25519_dalek::{RistrettoPoint, Scalar};
+
+    /// Starts a Curve25519 verifier backed by an in-memory store.
+    fn start_verifier() -> LocalProver<Curve25519ChaumPedersen> {
+        let (verifier, prover) = LocalVerifier::new(128);
+        verifier.run(
+            EC25519_GROUP_PARAMS.to_owned(),
+            AsyncInMemoryUserDao::<RistrettoPoint, Scalar>::new(),
+        );
+        prover
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_concurrent_local_authentication() {
+        let prover = start_verifier();
+        let flows: Vec<_> = (0..100)
+            .map(|i| {
+                let prover = prover.clone();
+                tokio::spawn(async move {
+                    let params = EC25519_GROUP_PARAMS.to_owned();
+                    let x = Scalar::generate_random().unwrap();
+                    prover.prove(&params, &format!("user{}", i), &x).await
+                })
+            })
+            .collect();
+
+        let mut session_ids = Vec::new();
+        for flow in flows {
+            session_ids.push(flow.await.unwrap().unwrap());
+        }
+        session_ids.sort();
+        session_ids.dedup();
+        assert_eq!(session_ids.len(), 100);
+    }
+
+    #[tokio::test]
+    async fn test_wrong_secret_rejected() {
+        let prover = start_verifier();
+        let params = EC25519_GROUP_PARAMS.to_owned();
+        let x = Scalar::generate_random().unwrap();
+        prover.prove(&params, "alice", &x).await.unwrap();
+        prover.prove(&params, "alice", &x).await.unwrap();
+
+        let wrong = Scalar::generate_random().unwrap();
+        assert_eq!(
+            prover.prove(&params, "alice", &wrong).await.unwrap_err(),
+            LocalAuthError::VerificationFailed
+        );
+    }
+
+    #[tokio::test]
+    async fn test_closed_verifier() {
+        let (verifier, prover) = LocalVerifier::<Curve25519ChaumPedersen>::new(1);
+        drop(verifier);
+        let x = Scalar::generate_random().unwrap();
+        assert_eq!(
+            prover
+                .prove(&EC25519_GROUP_PARAMS, "alice", &x)
+                .await
+                .unwrap_err(),
+            LocalAuthError::VerifierClosed
+        );
+    }
+}
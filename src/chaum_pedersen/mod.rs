@@ -1,3 +1,6 @@
+/// This module provides functionality related to the Brainpool P-256r1 elliptic curve of RFC 5639, for deployments that must avoid the NIST curves.
+pub mod brainpool;
+
 /// This module defines various constants used throughout the application. These may include cryptographic constants, default values, or other static data that are integral to the operation of the system.
 pub mod constants;
 
@@ -7,20 +10,66 @@ pub mod curve25519;
 /// This module focuses on the discrete logarithm problem and related cryptographic operations. It is particularly relevant for the discrete log implementation of the Chaum-Pedersen protocol.
 pub mod discretelog;
 
+/// This module provides functionality related to the Jubjub elliptic curve, whose prime-order subgroup is friendly to zcash-style BLS12-381 circuits.
+pub mod jubjub;
+
+/// This module derives keypairs deterministically from a seed using HKDF-SHA256, for reproducible cross-language test vectors.
+pub mod keygen;
+
 /// A module dedicated to testing various components of the application. It includes test cases, utility functions for testing, and other resources needed to ensure the correctness and reliability of the system.
 pub mod test;
 
+/// This module runs the protocol between a prover and a verifier in the same process, exchanging messages over `tokio` channels instead of gRPC.
+#[cfg(feature = "std")]
+pub mod local;
+
+/// This module drives the prover's side of the protocol over any transport, such as gRPC or an in-process verifier.
+#[cfg(feature = "std")]
+pub mod runner;
+
+/// This module provides a Curve25519 prover drawing its commitment randomness from a hardware security module over HTTP, demonstrating the `AsyncChaumPedersen` trait.
+#[cfg(feature = "std")]
+pub mod hsm;
+
+/// This module provides standalone, additively homomorphic Pedersen commitments built on the discrete log and Curve25519 backends.
+pub mod pedersen;
+
+/// This module provides the Pedersen commitment variant of the protocol, proving knowledge of the opening `(x, r)` of a commitment `g^x * h^r`, and re-randomizes such commitments.
+pub mod pedersen_variant;
+
+/// This module proves that a Pallas and a Vesta commitment share the same secret, as a building block for recursive proofs over the Pallas-Vesta cycle.
+pub mod nova_pair;
+
 /// This module provides functionality related to the Pallas elliptic curve, often used in cryptographic operations, particularly in the elliptic curve implementation of the Chaum-Pedersen protocol.
 pub mod pallas;
 
+/// This module splits the protocol into `Prover` and `Verifier` roles, so that verify-only code has no access to prover operations or secrets.
+pub mod roles;
+
+/// This module reports the encoded size of proof components and how long each protocol step takes.
+pub mod stats;
+
+/// This module derives protocol challenges from a Merlin transcript, providing a Fiat-Shamir prover and verifier for non-interactive proofs.
+pub mod transcript;
+
+/// This module derives discrete log challenges from a verifiable random function, so that a client can check a challenge was generated fairly.
+pub mod vrf;
+
 /// This module provides functionality related to the Vesta elliptic curve, often used in cryptographic operations, particularly in the elliptic curve implementation of the Chaum-Pedersen protocol.
 pub mod vesta;
 
+use crate::chaum_pedersen::transcript::{commitment_transcript, TranscriptChallenge};
+use crate::codec::{push_field, read_field};
+use crate::conversion::{ByteConvertible, ConversionError};
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+
 /// A struct representing group parameters in cryptographic protocols.
 ///
 /// This struct is generic over a type `T`, allowing flexibility in the types of the parameters.
 /// It's typically used in cryptographic protocols like Chaum-Pedersen where specific group parameters are required.
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
 pub struct GroupParams<T> {
     /// The generator `g` of the group.
     pub g: T,
@@ -32,6 +81,311 @@ pub struct GroupParams<T> {
     pub q: T,
 }
 
+impl<T> GroupParams<T> {
+    /// Returns a builder that sets each parameter by name, avoiding accidentally swapped
+    /// positional arguments.
+    ///
+    /// ```
+    /// use num_bigint::BigUint;
+    /// use zk_pass::chaum_pedersen::GroupParams;
+    ///
+    /// let params = GroupParams::builder()
+    ///     .g(BigUint::from(4u32))
+    ///     .h(BigUint::from(9u32))
+    ///     .p(BigUint::from(23u32))
+    ///     .q(BigUint::from(11u32))
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(params.h, BigUint::from(9u32));
+    /// ```
+    pub fn builder() -> GroupParamsBuilder<T> {
+        GroupParamsBuilder::default()
+    }
+}
+
+/// A builder for `GroupParams`, created with [`GroupParams::builder`].
+#[derive(Clone, Debug)]
+pub struct GroupParamsBuilder<T> {
+    g: Option<T>,
+    h: Option<T>,
+    p: Option<T>,
+    q: Option<T>,
+}
+
+impl<T> Default for GroupParamsBuilder<T> {
+    fn default() -> Self {
+        Self {
+            g: None,
+            h: None,
+            p: None,
+            q: None,
+        }
+    }
+}
+
+impl<T> GroupParamsBuilder<T> {
+    /// Sets the generator `g` of the group.
+    pub fn g(mut self, g: T) -> Self {
+        self.g = Some(g);
+        self
+    }
+
+    /// Sets the additional generator `h` of the group.
+    pub fn h(mut self, h: T) -> Self {
+        self.h = Some(h);
+        self
+    }
+
+    /// Sets the prime modulus `p` of the group.
+    pub fn p(mut self, p: T) -> Self {
+        self.p = Some(p);
+        self
+    }
+
+    /// Sets the order `q` of the subgroup generated by `g` and `h`.
+    pub fn q(mut self, q: T) -> Self {
+        self.q = Some(q);
+        self
+    }
+
+    /// Builds the group parameters.
+    ///
+    /// The parameters are not validated; see [`GroupParams::validate`].
+    ///
+    /// # Returns
+    /// The group parameters, or the first parameter, in `g`, `h`, `p`, `q` order, left unset.
+    pub fn build(self) -> Result<GroupParams<T>, MissingGroupParam> {
+        Ok(GroupParams {
+            g: self.g.ok_or(MissingGroupParam("g"))?,
+            h: self.h.ok_or(MissingGroupParam("h"))?,
+            p: self.p.ok_or(MissingGroupParam("p"))?,
+            q: self.q.ok_or(MissingGroupParam("q"))?,
+        })
+    }
+}
+
+impl<T: ValidateGroupParams + Clone> GroupParamsBuilder<T> {
+    /// Builds the group parameters like [`build`](Self::build), but lets `p` and `q` be left
+    /// unset for curve groups, which have no use for them. Unset, they are set to `g` as a
+    /// placeholder.
+    ///
+    /// # Returns
+    /// The group parameters, or the first required parameter left unset.
+    pub fn build_group(self) -> Result<GroupParams<T>, MissingGroupParam> {
+        if T::HAS_MODULUS {
+            return self.build();
+        }
+        let g = self.g.ok_or(MissingGroupParam("g"))?;
+        let h = self.h.ok_or(MissingGroupParam("h"))?;
+        Ok(GroupParams {
+            p: self.p.unwrap_or_else(|| g.clone()),
+            q: self.q.unwrap_or_else(|| g.clone()),
+            g,
+            h,
+        })
+    }
+}
+
+/// An error raised when building `GroupParams` with a parameter left unset, holding its name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MissingGroupParam(pub &'static str);
+
+impl fmt::Display for MissingGroupParam {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Group parameter `{}` is not set", self.0)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for MissingGroupParam {}
+
+/// The approximate symmetric-equivalent security level offered by a set of group parameters.
+///
+/// The levels follow NIST SP 800-57 Part 1, which maps finite field group sizes to the
+/// strength of an equivalent symmetric key.
+///
+/// # Variants
+/// - `Bits80`: Broken by modern standards, offered by groups smaller than 2048 bits.
+/// - `Bits112`: Offered by 2048-bit groups.
+/// - `Bits128`: Offered by 3072-bit groups.
+/// - `Bits192`: Offered by 7680-bit groups.
+/// - `Bits256`: Offered by 15360-bit groups.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SecurityLevel {
+    Bits80,
+    Bits112,
+    Bits128,
+    Bits192,
+    Bits256,
+}
+
+impl SecurityLevel {
+    /// Returns the number of bits of security this level represents.
+    pub fn bits(&self) -> u32 {
+        match self {
+            SecurityLevel::Bits80 => 80,
+            SecurityLevel::Bits112 => 112,
+            SecurityLevel::Bits128 => 128,
+            SecurityLevel::Bits192 => 192,
+            SecurityLevel::Bits256 => 256,
+        }
+    }
+}
+
+/// Asserts that a security level is acceptable, panicking if it is below 112 bits.
+///
+/// Being a `const fn`, it can be evaluated at compile time to reject weak configurations:
+///
+/// ```
+/// use zk_pass::chaum_pedersen::assert_security_level;
+///
+/// const _: () = assert_security_level(128);
+/// ```
+///
+/// # Arguments
+/// * `bits` - The number of bits of security offered by the selected parameters.
+///
+/// # Panics
+/// Panics if `bits` is below 112.
+pub const fn assert_security_level(bits: u32) {
+    if bits < 112 {
+        panic!("Group parameters must offer at least 112 bits of security");
+    }
+}
+
+/// An error raised when a set of group parameters is unsafe to use.
+///
+/// # Variants
+/// - `TrivialGenerator`: `g` or `h` is the identity or otherwise generates a trivial group.
+/// - `DependentGenerators`: `g` and `h` are equal, so `h` is not independent from `g`.
+/// - `InvalidOrder`: `q` is not the order of the subgroup generated by `g` and `h`.
+/// - `NotOnCurve`: `g` or `h` does not satisfy the curve equation.
+/// - `CompositeModulus`: The modulus `p` is not prime.
+/// - `CompositeOrder`: The subgroup order `q` is not prime.
+/// - `OrderDoesNotDivide`: `q` does not divide `p - 1`, so there is no subgroup of order `q`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InvalidGroupParams {
+    TrivialGenerator,
+    DependentGenerators,
+    InvalidOrder,
+    NotOnCurve,
+    CompositeModulus,
+    CompositeOrder,
+    OrderDoesNotDivide,
+}
+
+impl fmt::Display for InvalidGroupParams {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InvalidGroupParams::TrivialGenerator => write!(f, "Trivial group generator"),
+            InvalidGroupParams::DependentGenerators => write!(f, "Generators are not independent"),
+            InvalidGroupParams::InvalidOrder => write!(f, "Invalid subgroup order"),
+            InvalidGroupParams::NotOnCurve => write!(f, "Generator is not on the curve"),
+            InvalidGroupParams::CompositeModulus => write!(f, "Modulus p is not prime"),
+            InvalidGroupParams::CompositeOrder => write!(f, "Subgroup order q is not prime"),
+            InvalidGroupParams::OrderDoesNotDivide => {
+                write!(f, "Subgroup order q does not divide p - 1")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for InvalidGroupParams {}
+
+/// A trait for group element types whose parameters can be checked before use.
+pub trait ValidateGroupParams: Sized {
+    /// Whether `p` and `q` hold the modulus and subgroup order of the group.
+    ///
+    /// Curve groups are fixed by their curve, and their scalars reduce modulo the curve order
+    /// on their own, so their `p` and `q` are mere placeholders, e.g. the base point or the
+    /// identity, that neither the protocol nor validation reads.
+    const HAS_MODULUS: bool = false;
+
+    /// Checks that the parameters describe a usable group for the Chaum-Pedersen protocol.
+    ///
+    /// Groups without a modulus must not check `p` and `q`, which are placeholders for them.
+    ///
+    /// # Arguments
+    /// * `params` - The group parameters to check.
+    ///
+    /// # Returns
+    /// `Ok(())` if the parameters are consistent, or the first problem found otherwise.
+    fn validate(params: &GroupParams<Self>) -> Result<(), InvalidGroupParams>;
+
+    /// Checks that an element received from a client, e.g. a commitment, lies in the group the
+    /// parameters describe and is not its identity.
+    ///
+    /// # Arguments
+    /// * `params` - The group parameters the element should belong to.
+    /// * `element` - The element to check.
+    ///
+    /// # Returns
+    /// `true` if the element is a non-trivial element of the group, `false` otherwise.
+    fn is_valid_group_element(params: &GroupParams<Self>, element: &Self) -> bool;
+
+    /// Checks the primality of the parameters, which is too costly to check in `validate`.
+    ///
+    /// Curve groups are fixed by their curve, so only groups with user-supplied moduli need to
+    /// override the default, which accepts any parameters.
+    ///
+    /// # Arguments
+    /// * `params` - The group parameters to check.
+    /// * `rounds` - The number of probabilistic primality test rounds per number.
+    ///
+    /// # Returns
+    /// `Ok(())` if the parameters pass the checks, or the first problem found otherwise.
+    fn check_primality(
+        params: &GroupParams<Self>, rounds: usize,
+    ) -> Result<(), InvalidGroupParams> {
+        let _ = (params, rounds);
+        Ok(())
+    }
+}
+
+/// A trait for curve points that expose their affine `(x, y)` coordinates, e.g. to cross-check
+/// the `g` and `h` constants against other libraries.
+///
+/// Ristretto points do not implement it: a Ristretto point stands for a class of Edwards points
+/// and is opaque by design, so it has no canonical coordinates to export.
+pub trait AffineCoordinates {
+    /// Returns the affine coordinates of the point, each encoded as the little-endian bytes of a
+    /// base field element. The identity, which has no affine coordinates, yields zeros for both.
+    fn to_affine_xy(&self) -> (Vec<u8>, Vec<u8>);
+}
+
+impl<T: ValidateGroupParams> GroupParams<T> {
+    /// Checks that the parameters describe a usable group for the Chaum-Pedersen protocol.
+    ///
+    /// # Returns
+    /// `Ok(())` if the parameters are consistent, or the first problem found otherwise.
+    pub fn validate(&self) -> Result<(), InvalidGroupParams> {
+        T::validate(self)
+    }
+
+    /// Returns the prime modulus `p`, or `None` for curve groups, whose `p` is a placeholder.
+    pub fn modulus(&self) -> Option<&T> {
+        T::HAS_MODULUS.then_some(&self.p)
+    }
+
+    /// Returns the subgroup order `q`, or `None` for curve groups, whose `q` is a placeholder.
+    pub fn order(&self) -> Option<&T> {
+        T::HAS_MODULUS.then_some(&self.q)
+    }
+
+    /// Checks the primality of the parameters, e.g. that `p` and `q` of a custom MODP group are
+    /// prime. See [`ValidateGroupParams::check_primality`].
+    ///
+    /// # Arguments
+    /// * `rounds` - The number of probabilistic primality test rounds per number.
+    ///
+    /// # Returns
+    /// `Ok(())` if the parameters pass the checks, or the first problem found otherwise.
+    pub fn check_primality(&self, rounds: usize) -> Result<(), InvalidGroupParams> {
+        T::check_primality(self, rounds)
+    }
+}
+
 /// A trait defining the interface for the Chaum-Pedersen zero-knowledge protocol.
 ///
 /// This trait provides the necessary methods for implementing the Chaum-Pedersen protocol,
@@ -92,6 +446,23 @@ pub trait ChaumPedersen {
     where
         Self: Sized;
 
+    /// Verifies the response in the Chaum-Pedersen protocol, reporting which check failed.
+    ///
+    /// # Arguments
+    /// * `params` - Group parameters used in the protocol.
+    /// * `s` - The response value to be verified.
+    /// * `c` - The challenge value used in the protocol.
+    /// * `cp` - The commitment parameters used in the protocol.
+    ///
+    /// # Returns
+    /// `VerifyOutcome::Ok` if both verification equations hold, or the first one that fails.
+    fn verify_detailed(
+        params: &Self::GroupParameters, s: &Self::Response, c: &Self::Challenge,
+        cp: &Self::CommitParameters,
+    ) -> VerifyOutcome
+    where
+        Self: Sized;
+
     /// Verifies the response in the Chaum-Pedersen protocol.
     ///
     /// # Arguments
@@ -107,5 +478,909 @@ pub trait ChaumPedersen {
         cp: &Self::CommitParameters,
     ) -> bool
     where
-        Self: Sized;
+        Self: Sized,
+    {
+        Self::verify_detailed(params, s, c, cp) == VerifyOutcome::Ok
+    }
+
+    /// Calculates the commitment like [`commitment`](Self::commitment), recording it in a
+    /// transcript.
+    ///
+    /// # Arguments
+    /// * `params` - Group parameters used in the protocol.
+    /// * `x` - The secret value for which the commitment is calculated.
+    /// * `transcript` - The transcript recording `y1`, `y2`, `r1` and `r2`, if any.
+    ///
+    /// # Returns
+    /// A tuple containing the commitment parameters and the commitment randomness.
+    fn commitment_traced<T: ByteConvertible<T>>(
+        params: &Self::GroupParameters, x: &Self::Secret,
+        transcript: Option<&mut ProtocolTranscript>,
+    ) -> (Self::CommitParameters, Self::CommitmentRandom)
+    where
+        Self: ChaumPedersen<CommitParameters = (T, T, T, T)> + Sized,
+    {
+        let (cp, k) = Self::commitment(params, x);
+        if let Some(transcript) = transcript {
+            transcript.record_commitment(&cp);
+        }
+        (cp, k)
+    }
+
+    /// Generates a challenge like [`challenge`](Self::challenge), recording it in a transcript.
+    ///
+    /// # Arguments
+    /// * `params` - Group parameters used in the protocol.
+    /// * `transcript` - The transcript recording `c`, if any.
+    ///
+    /// # Returns
+    /// The challenge value used in the protocol.
+    fn challenge_traced(
+        params: &Self::GroupParameters, transcript: Option<&mut ProtocolTranscript>,
+    ) -> Self::Challenge
+    where
+        Self: Sized,
+        Self::Challenge: ByteConvertible<Self::Challenge>,
+    {
+        let c = Self::challenge(params);
+        if let Some(transcript) = transcript {
+            transcript.c = Self::Challenge::convert_to(&c);
+        }
+        c
+    }
+
+    /// Calculates the challenge response like [`challenge_response`](Self::challenge_response),
+    /// recording it in a transcript.
+    ///
+    /// # Arguments
+    /// * `params` - Group parameters used in the protocol.
+    /// * `k` - The commitment randomness used in the protocol.
+    /// * `c` - The challenge value used in the protocol.
+    /// * `x` - The secret value for which the response is calculated.
+    /// * `transcript` - The transcript recording `c` and `s`, if any.
+    ///
+    /// # Returns
+    /// The response value in the protocol.
+    fn challenge_response_traced(
+        params: &Self::GroupParameters, k: &Self::CommitmentRandom, c: &Self::Challenge,
+        x: &Self::Secret, transcript: Option<&mut ProtocolTranscript>,
+    ) -> Self::Response
+    where
+        Self: Sized,
+        Self::Challenge: ByteConvertible<Self::Challenge>,
+        Self::Response: ByteConvertible<Self::Response>,
+    {
+        let s = Self::challenge_response(params, k, c, x);
+        if let Some(transcript) = transcript {
+            transcript.c = Self::Challenge::convert_to(c);
+            transcript.s = Self::Response::convert_to(&s);
+        }
+        s
+    }
+
+    /// Verifies the response like [`verify`](Self::verify), recording every protocol message and
+    /// the outcome in a transcript.
+    ///
+    /// # Arguments
+    /// * `params` - Group parameters used in the protocol.
+    /// * `s` - The response value to be verified.
+    /// * `c` - The challenge value used in the protocol.
+    /// * `cp` - The commitment parameters used in the protocol.
+    /// * `transcript` - The transcript recording the commitment, `c`, `s` and the outcome, if any.
+    ///
+    /// # Returns
+    /// A boolean indicating whether the verification was successful.
+    fn verify_traced<T: ByteConvertible<T>>(
+        params: &Self::GroupParameters, s: &Self::Response, c: &Self::Challenge,
+        cp: &Self::CommitParameters, transcript: Option<&mut ProtocolTranscript>,
+    ) -> bool
+    where
+        Self: ChaumPedersen<CommitParameters = (T, T, T, T)> + Sized,
+        Self::Challenge: ByteConvertible<Self::Challenge>,
+        Self::Response: ByteConvertible<Self::Response>,
+    {
+        let verified = Self::verify(params, s, c, cp);
+        if let Some(transcript) = transcript {
+            transcript.record_commitment(cp);
+            transcript.c = Self::Challenge::convert_to(c);
+            transcript.s = Self::Response::convert_to(s);
+            transcript.verified = verified;
+        }
+        verified
+    }
+}
+
+/// An asynchronous version of [`ChaumPedersen`], for protocols whose steps perform I/O, e.g. to
+/// draw the commitment randomness from an HSM or a randomness beacon.
+///
+/// Unlike [`ChaumPedersen`], its methods take `&self`, so that an implementation can hold a
+/// connection or endpoint, and the steps that may need I/O can fail. Every [`ChaumPedersen`]
+/// implementation is also an `AsyncChaumPedersen` whose steps never fail.
+#[cfg(feature = "std")]
+#[async_trait::async_trait]
+pub trait AsyncChaumPedersen: Send + Sync {
+    /// The type representing the secret to be proven.
+    type Secret: Sync;
+    /// The type representing the response in the protocol.
+    type Response: Send + Sync;
+    /// The type representing the challenge in the protocol.
+    type Challenge: Send + Sync;
+    /// The type representing the group parameters used in the protocol.
+    type GroupParameters: Sync;
+    /// The type representing the commitment parameters in the protocol.
+    type CommitParameters: Send + Sync;
+    /// The type representing the commitment randomness in the protocol.
+    type CommitmentRandom: Send + Sync;
+    /// The type of the error raised when a step fails.
+    type Error;
+
+    /// Calculates the commitment, see [`ChaumPedersen::commitment`].
+    ///
+    /// # Returns
+    /// A result containing the commitment parameters and the commitment randomness, or an
+    /// error if the randomness cannot be obtained.
+    async fn commitment(
+        &self, params: &Self::GroupParameters, x: &Self::Secret,
+    ) -> Result<(Self::CommitParameters, Self::CommitmentRandom), Self::Error>;
+
+    /// Generates a challenge, see [`ChaumPedersen::challenge`].
+    ///
+    /// # Returns
+    /// A result containing the challenge, or an error if it cannot be obtained.
+    async fn challenge(
+        &self, params: &Self::GroupParameters,
+    ) -> Result<Self::Challenge, Self::Error>;
+
+    /// Calculates the challenge response, see [`ChaumPedersen::challenge_response`].
+    async fn challenge_response(
+        &self, params: &Self::GroupParameters, k: &Self::CommitmentRandom, c: &Self::Challenge,
+        x: &Self::Secret,
+    ) -> Self::Response;
+
+    /// Verifies the response, see [`ChaumPedersen::verify`].
+    async fn verify(
+        &self, params: &Self::GroupParameters, s: &Self::Response, c: &Self::Challenge,
+        cp: &Self::CommitParameters,
+    ) -> bool;
+}
+
+#[cfg(feature = "std")]
+#[async_trait::async_trait]
+impl<C> AsyncChaumPedersen for C
+where
+    C: ChaumPedersen + Send + Sync,
+    C::Secret: Sync,
+    C::Response: Send + Sync,
+    C::Challenge: Send + Sync,
+    C::GroupParameters: Sync,
+    C::CommitParameters: Send + Sync,
+    C::CommitmentRandom: Send + Sync,
+{
+    type Secret = C::Secret;
+    type Response = C::Response;
+    type Challenge = C::Challenge;
+    type GroupParameters = C::GroupParameters;
+    type CommitParameters = C::CommitParameters;
+    type CommitmentRandom = C::CommitmentRandom;
+    type Error = core::convert::Infallible;
+
+    async fn commitment(
+        &self, params: &C::GroupParameters, x: &C::Secret,
+    ) -> Result<(C::CommitParameters, C::CommitmentRandom), Self::Error> {
+        Ok(C::commitment(params, x))
+    }
+
+    async fn challenge(&self, params: &C::GroupParameters) -> Result<C::Challenge, Self::Error> {
+        Ok(C::challenge(params))
+    }
+
+    async fn challenge_response(
+        &self, params: &C::GroupParameters, k: &C::CommitmentRandom, c: &C::Challenge,
+        x: &C::Secret,
+    ) -> C::Response {
+        C::challenge_response(params, k, c, x)
+    }
+
+    async fn verify(
+        &self, params: &C::GroupParameters, s: &C::Response, c: &C::Challenge,
+        cp: &C::CommitParameters,
+    ) -> bool {
+        C::verify(params, s, c, cp)
+    }
+}
+
+/// The outcome of verifying a response, identifying which verification equation failed.
+///
+/// Verification checks `g^s = r1 * y1^c` (the first, `g`-based equation) and `h^s = r2 * y2^c`
+/// (the second, `h`-based equation), written additively for the curve backends. Knowing which
+/// one fails helps debug interop mismatches, e.g. a swapped or mis-encoded `h`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum VerifyOutcome {
+    /// Both verification equations hold.
+    Ok,
+    /// The `g`-based equation, involving `y1` and `r1`, does not hold.
+    FirstEquationFailed,
+    /// The `g`-based equation holds, but the `h`-based one, involving `y2` and `r2`, does not.
+    SecondEquationFailed,
+}
+
+impl VerifyOutcome {
+    /// Combines the results of the two verification equations into an outcome.
+    pub(crate) fn from_equations(first: bool, second: bool) -> Self {
+        match (first, second) {
+            (false, _) => VerifyOutcome::FirstEquationFailed,
+            (true, false) => VerifyOutcome::SecondEquationFailed,
+            (true, true) => VerifyOutcome::Ok,
+        }
+    }
+}
+
+/// A record of the messages exchanged in one run of the protocol, e.g. for an audit trail of
+/// authentication attempts.
+///
+/// Every value is kept in its byte encoding, so transcripts of every backend share one type. The
+/// `*_traced` methods of [`ChaumPedersen`] fill a transcript in as the protocol runs.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ProtocolTranscript {
+    /// The encoded public commitment `y1`.
+    pub y1: Vec<u8>,
+    /// The encoded public commitment `y2`.
+    pub y2: Vec<u8>,
+    /// The encoded commitment `r1`.
+    pub r1: Vec<u8>,
+    /// The encoded commitment `r2`.
+    pub r2: Vec<u8>,
+    /// The encoded challenge `c`.
+    pub c: Vec<u8>,
+    /// The encoded response `s`.
+    pub s: Vec<u8>,
+    /// Whether the response was verified successfully.
+    pub verified: bool,
+    /// The time of the protocol run, in seconds since the Unix epoch.
+    pub timestamp: u64,
+}
+
+impl ProtocolTranscript {
+    /// Creates an empty transcript of a protocol run.
+    ///
+    /// # Arguments
+    /// * `timestamp` - The time of the protocol run, in seconds since the Unix epoch.
+    pub fn new(timestamp: u64) -> Self {
+        Self {
+            timestamp,
+            ..Self::default()
+        }
+    }
+
+    /// Records the commitment `(y1, y2, r1, r2)`.
+    fn record_commitment<T: ByteConvertible<T>>(&mut self, (y1, y2, r1, r2): &(T, T, T, T)) {
+        self.y1 = T::convert_to(y1);
+        self.y2 = T::convert_to(y2);
+        self.r1 = T::convert_to(r1);
+        self.r2 = T::convert_to(r2);
+    }
+}
+
+/// A self-contained, non-interactive proof of knowledge of a registered secret.
+///
+/// A proof bundle lets a client prove its identity without any live interaction with the server,
+/// e.g. by sending the bundle via email, a QR code or a file. The challenge is derived with the
+/// Fiat-Shamir transform over the group parameters, the commitment, the user name and the
+/// timestamp, so a bundle cannot be re-targeted to another user or re-dated.
+///
+/// # Type Parameters
+/// * `C` - The Chaum-Pedersen protocol the proof was created with.
+pub struct ProofBundle<C: ChaumPedersen> {
+    /// The name of the user the proof is for.
+    pub user: String,
+    /// The time the proof was created, in seconds since the Unix epoch.
+    pub timestamp: u64,
+    /// The commitment parameters (`y1`, `y2`, `r1`, `r2`) of the proof.
+    pub commitment: C::CommitParameters,
+    /// The challenge derived from the transcript.
+    pub c: C::Challenge,
+    /// The response to the challenge.
+    pub s: C::Response,
+}
+
+impl<C, T, S> ProofBundle<C>
+where
+    C: ChaumPedersen<
+        GroupParameters = GroupParams<T>,
+        CommitParameters = (T, T, T, T),
+        Challenge = S,
+        Response = S,
+    >,
+    T: ByteConvertible<T>,
+    S: ByteConvertible<S> + TranscriptChallenge<GroupParams<T>> + PartialEq,
+{
+    /// The domain separator binding challenges to proof bundles.
+    const DOMAIN_SEPARATOR: &'static [u8] = b"zk_pass.proof_bundle";
+
+    /// Creates a proof bundle for a user, proving knowledge of `x`.
+    ///
+    /// # Arguments
+    /// * `params` - The group parameters used in the protocol.
+    /// * `user` - The name of the user the proof is for.
+    /// * `timestamp` - The creation time, in seconds since the Unix epoch.
+    /// * `x` - The user's secret.
+    ///
+    /// # Returns
+    /// A proof bundle that can be verified offline against the user's registered commitment.
+    pub fn prove(params: &GroupParams<T>, user: String, timestamp: u64, x: &C::Secret) -> Self {
+        let (commitment, k) = C::commitment(params, x);
+        let c = Self::derive_challenge(params, &user, timestamp, &commitment);
+        let s = C::challenge_response(params, &k, &c, x);
+        Self {
+            user,
+            timestamp,
+            commitment,
+            c,
+            s,
+        }
+    }
+
+    /// Derives the challenge for a bundle from its commitment, user name and timestamp.
+    fn derive_challenge(
+        params: &GroupParams<T>, user: &str, timestamp: u64, cp: &(T, T, T, T),
+    ) -> S {
+        let mut transcript = commitment_transcript(Self::DOMAIN_SEPARATOR, params, cp);
+        transcript.append_message(b"user", user.as_bytes());
+        transcript.append_u64(b"timestamp", timestamp);
+        S::from_transcript(&mut transcript, params)
+    }
+
+    /// Verifies the bundle against a user's registered commitment.
+    ///
+    /// The registered `y1` and `y2` take the place of the values carried in the bundle, so a
+    /// bundle only verifies for the secret the user actually registered.
+    ///
+    /// # Arguments
+    /// * `params` - The group parameters used in the protocol.
+    /// * `y1` - The user's registered `y1`.
+    /// * `y2` - The user's registered `y2`.
+    ///
+    /// # Returns
+    /// `true` if the challenge matches the transcript and the response verifies, `false` otherwise.
+    pub fn verify(&self, params: &GroupParams<T>, y1: &T, y2: &T) -> bool
+    where
+        T: Clone,
+    {
+        let (_, _, r1, r2) = &self.commitment;
+        let cp = (y1.clone(), y2.clone(), r1.clone(), r2.clone());
+        let c = Self::derive_challenge(params, &self.user, self.timestamp, &cp);
+        c == self.c && C::verify(params, &self.s, &c, &cp)
+    }
+
+    /// Encodes the bundle in a compact binary layout.
+    ///
+    /// The layout is the user name, the big-endian timestamp, then `y1`, `y2`, `r1`, `r2`, `c`
+    /// and `s`, where the user name and every value are prefixed with their big-endian `u16`
+    /// length.
+    ///
+    /// # Returns
+    /// The encoded bundle, or `ConversionError::InvalidLength` if the user name or a value is
+    /// longer than `u16::MAX` bytes.
+    pub fn to_compact_bytes(&self) -> Result<Vec<u8>, ConversionError> {
+        let (y1, y2, r1, r2) = &self.commitment;
+        let mut bytes = Vec::new();
+        push_field(&mut bytes, self.user.as_bytes())?;
+        bytes.extend_from_slice(&self.timestamp.to_be_bytes());
+        for point in [y1, y2, r1, r2] {
+            push_field(&mut bytes, &T::convert_to(point))?;
+        }
+        push_field(&mut bytes, &S::convert_to(&self.c))?;
+        push_field(&mut bytes, &S::convert_to(&self.s))?;
+        Ok(bytes)
+    }
+
+    /// Decodes a bundle from the layout produced by [`ProofBundle::to_compact_bytes`].
+    ///
+    /// # Arguments
+    /// * `bytes` - The encoded bundle.
+    ///
+    /// # Returns
+    /// The decoded bundle, or a `ConversionError` if the bytes are truncated, have trailing data
+    /// or contain an invalid value.
+    pub fn from_compact_bytes(mut bytes: &[u8]) -> Result<Self, ConversionError> {
+        let user = String::from_utf8(read_field(&mut bytes)?.to_vec())
+            .map_err(|_| ConversionError::InvalidEncoding)?;
+        let timestamp = bytes
+            .get(..8)
+            .ok_or(ConversionError::InvalidLength)?
+            .try_into()
+            .map(u64::from_be_bytes)
+            .map_err(|_| ConversionError::InvalidLength)?;
+        bytes = &bytes[8..];
+        let y1 = T::convert_from(read_field(&mut bytes)?)?;
+        let y2 = T::convert_from(read_field(&mut bytes)?)?;
+        let r1 = T::convert_from(read_field(&mut bytes)?)?;
+        let r2 = T::convert_from(read_field(&mut bytes)?)?;
+        let c = S::convert_from(read_field(&mut bytes)?)?;
+        let s = S::convert_from(read_field(&mut bytes)?)?;
+        if !bytes.is_empty() {
+            return Err(ConversionError::InvalidLength);
+        }
+        Ok(Self {
+            user,
+            timestamp,
+            commitment: (y1, y2, r1, r2),
+            c,
+            s,
+        })
+    }
+}
+
+/// The serialized form of a `ProofBundle`, with every value encoded as `V`.
+///
+/// Human-readable formats such as JSON carry the values as base64 strings, while binary formats
+/// such as MessagePack carry them as raw bytes.
+#[cfg(feature = "std")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct EncodedProofBundle<V> {
+    user: String,
+    timestamp: u64,
+    y1: V,
+    y2: V,
+    r1: V,
+    r2: V,
+    c: V,
+    s: V,
+}
+
+#[cfg(feature = "std")]
+impl<C, T, S> serde::Serialize for ProofBundle<C>
+where
+    C: ChaumPedersen<CommitParameters = (T, T, T, T), Challenge = S, Response = S>,
+    T: ByteConvertible<T>,
+    S: ByteConvertible<S>,
+{
+    fn serialize<Ser: serde::Serializer>(&self, serializer: Ser) -> Result<Ser::Ok, Ser::Error> {
+        let (y1, y2, r1, r2) = &self.commitment;
+        let encoded = EncodedProofBundle {
+            user: self.user.clone(),
+            timestamp: self.timestamp,
+            y1: T::convert_to(y1),
+            y2: T::convert_to(y2),
+            r1: T::convert_to(r1),
+            r2: T::convert_to(r2),
+            c: S::convert_to(&self.c),
+            s: S::convert_to(&self.s),
+        };
+        if serializer.is_human_readable() {
+            use base64::{engine::general_purpose::STANDARD, Engine};
+            encoded
+                .map(|value| STANDARD.encode(value))
+                .serialize(serializer)
+        } else {
+            encoded
+                .map(serde_bytes::ByteBuf::from)
+                .serialize(serializer)
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'de, C, T, S> serde::Deserialize<'de> for ProofBundle<C>
+where
+    C: ChaumPedersen<CommitParameters = (T, T, T, T), Challenge = S, Response = S>,
+    T: ByteConvertible<T>,
+    S: ByteConvertible<S>,
+{
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error;
+        let encoded = if deserializer.is_human_readable() {
+            use base64::{engine::general_purpose::STANDARD, Engine};
+            let encoded = EncodedProofBundle::<String>::deserialize(deserializer)?;
+            encoded.try_map(|value| STANDARD.decode(value).map_err(D::Error::custom))?
+        } else {
+            let encoded = EncodedProofBundle::<serde_bytes::ByteBuf>::deserialize(deserializer)?;
+            encoded.map(serde_bytes::ByteBuf::into_vec)
+        };
+        Ok(Self {
+            user: encoded.user,
+            timestamp: encoded.timestamp,
+            commitment: (
+                T::convert_from(&encoded.y1).map_err(D::Error::custom)?,
+                T::convert_from(&encoded.y2).map_err(D::Error::custom)?,
+                T::convert_from(&encoded.r1).map_err(D::Error::custom)?,
+                T::convert_from(&encoded.r2).map_err(D::Error::custom)?,
+            ),
+            c: S::convert_from(&encoded.c).map_err(D::Error::custom)?,
+            s: S::convert_from(&encoded.s).map_err(D::Error::custom)?,
+        })
+    }
+}
+
+#[cfg(feature = "std")]
+impl<C, T, S> ProofBundle<C>
+where
+    C: ChaumPedersen<CommitParameters = (T, T, T, T), Challenge = S, Response = S>,
+    T: ByteConvertible<T>,
+    S: ByteConvertible<S>,
+{
+    /// Encodes the bundle as MessagePack, with every value as raw bytes.
+    ///
+    /// # Returns
+    /// The encoded bundle, or `ZkPassError::Unspecified` if it cannot be encoded.
+    pub fn to_msgpack(&self) -> Result<Vec<u8>, crate::client::ZkPassError> {
+        rmp_serde::to_vec(self).map_err(|_| crate::client::ZkPassError::Unspecified)
+    }
+
+    /// Decodes a bundle from the encoding produced by [`ProofBundle::to_msgpack`].
+    ///
+    /// # Arguments
+    /// * `bytes` - The encoded bundle.
+    ///
+    /// # Returns
+    /// The decoded bundle, or `ZkPassError::InvalidParameter` if the bytes are not valid
+    /// MessagePack or contain an invalid value.
+    pub fn from_msgpack(bytes: &[u8]) -> Result<Self, crate::client::ZkPassError> {
+        rmp_serde::from_slice(bytes).map_err(|_| crate::client::ZkPassError::InvalidParameter)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<V> EncodedProofBundle<V> {
+    /// Re-encodes every value with `f`.
+    fn map<W>(self, mut f: impl FnMut(V) -> W) -> EncodedProofBundle<W> {
+        EncodedProofBundle {
+            user: self.user,
+            timestamp: self.timestamp,
+            y1: f(self.y1),
+            y2: f(self.y2),
+            r1: f(self.r1),
+            r2: f(self.r2),
+            c: f(self.c),
+            s: f(self.s),
+        }
+    }
+
+    /// Re-encodes every value with `f`, failing on the first value `f` rejects.
+    fn try_map<W, E>(
+        self, mut f: impl FnMut(V) -> Result<W, E>,
+    ) -> Result<EncodedProofBundle<W>, E> {
+        Ok(EncodedProofBundle {
+            user: self.user,
+            timestamp: self.timestamp,
+            y1: f(self.y1)?,
+            y2: f(self.y2)?,
+            r1: f(self.r1)?,
+            r2: f(self.r2)?,
+            c: f(self.c)?,
+            s: f(self.s)?,
+        })
+    }
+}
+
+/// The serialized form of a `ProtocolTranscript`, with every value base64-encoded.
+#[cfg(feature = "std")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct EncodedProtocolTranscript {
+    y1: String,
+    y2: String,
+    r1: String,
+    r2: String,
+    c: String,
+    s: String,
+    verified: bool,
+    timestamp: u64,
+}
+
+#[cfg(feature = "std")]
+impl serde::Serialize for ProtocolTranscript {
+    fn serialize<Ser: serde::Serializer>(&self, serializer: Ser) -> Result<Ser::Ok, Ser::Error> {
+        use base64::{engine::general_purpose::STANDARD, Engine};
+        EncodedProtocolTranscript {
+            y1: STANDARD.encode(&self.y1),
+            y2: STANDARD.encode(&self.y2),
+            r1: STANDARD.encode(&self.r1),
+            r2: STANDARD.encode(&self.r2),
+            c: STANDARD.encode(&self.c),
+            s: STANDARD.encode(&self.s),
+            verified: self.verified,
+            timestamp: self.timestamp,
+        }
+        .serialize(serializer)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'de> serde::Deserialize<'de> for ProtocolTranscript {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use base64::{engine::general_purpose::STANDARD, Engine};
+        use serde::de::Error;
+        let encoded = EncodedProtocolTranscript::deserialize(deserializer)?;
+        let decode = |value: &str| STANDARD.decode(value).map_err(D::Error::custom);
+        Ok(Self {
+            y1: decode(&encoded.y1)?,
+            y2: decode(&encoded.y2)?,
+            r1: decode(&encoded.r1)?,
+            r2: decode(&encoded.r2)?,
+            c: decode(&encoded.c)?,
+            s: decode(&encoded.s)?,
+            verified: encoded.verified,
+            timestamp: encoded.timestamp,
+        })
+    }
+}
+
+/// The serialized form of `GroupParams`, with every value encoded as `V`.
+///
+/// Human-readable formats such as JSON carry the values as base64 strings, while binary formats
+/// such as MessagePack carry them as raw bytes.
+#[cfg(feature = "std")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct EncodedGroupParams<V> {
+    g: V,
+    h: V,
+    p: V,
+    q: V,
+}
+
+#[cfg(feature = "std")]
+impl<V> EncodedGroupParams<V> {
+    /// Re-encodes every value with `f`.
+    fn map<W>(self, mut f: impl FnMut(V) -> W) -> EncodedGroupParams<W> {
+        EncodedGroupParams {
+            g: f(self.g),
+            h: f(self.h),
+            p: f(self.p),
+            q: f(self.q),
+        }
+    }
+
+    /// Re-encodes every value with `f`, failing on the first value `f` rejects.
+    fn try_map<W, E>(
+        self, mut f: impl FnMut(V) -> Result<W, E>,
+    ) -> Result<EncodedGroupParams<W>, E> {
+        Ok(EncodedGroupParams {
+            g: f(self.g)?,
+            h: f(self.h)?,
+            p: f(self.p)?,
+            q: f(self.q)?,
+        })
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: ByteConvertible<T>> serde::Serialize for GroupParams<T> {
+    fn serialize<Ser: serde::Serializer>(&self, serializer: Ser) -> Result<Ser::Ok, Ser::Error> {
+        let encoded = EncodedGroupParams {
+            g: T::convert_to(&self.g),
+            h: T::convert_to(&self.h),
+            p: T::convert_to(&self.p),
+            q: T::convert_to(&self.q),
+        };
+        if serializer.is_human_readable() {
+            use base64::{engine::general_purpose::STANDARD, Engine};
+            encoded
+                .map(|value| STANDARD.encode(value))
+                .serialize(serializer)
+        } else {
+            encoded
+                .map(serde_bytes::ByteBuf::from)
+                .serialize(serializer)
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'de, T: ByteConvertible<T>> serde::Deserialize<'de> for GroupParams<T> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error;
+        let encoded = if deserializer.is_human_readable() {
+            use base64::{engine::general_purpose::STANDARD, Engine};
+            let encoded = EncodedGroupParams::<String>::deserialize(deserializer)?;
+            encoded.try_map(|value| STANDARD.decode(value).map_err(D::Error::custom))?
+        } else {
+            let encoded = EncodedGroupParams::<serde_bytes::ByteBuf>::deserialize(deserializer)?;
+            encoded.map(serde_bytes::ByteBuf::into_vec)
+        };
+        let encoded = encoded.try_map(|value| T::convert_from(&value).map_err(D::Error::custom))?;
+        Ok(Self {
+            g: encoded.g,
+            h: encoded.h,
+            p: encoded.p,
+            q: encoded.q,
+        })
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: ByteConvertible<T>> GroupParams<T> {
+    /// Encodes the group parameters as MessagePack, with every value as raw bytes.
+    ///
+    /// The encoding is about a quarter smaller than the JSON encoding, which carries the values
+    /// as base64 strings.
+    ///
+    /// # Returns
+    /// The encoded parameters, or `ZkPassError::Unspecified` if they cannot be encoded.
+    pub fn to_msgpack(&self) -> Result<Vec<u8>, crate::client::ZkPassError> {
+        rmp_serde::to_vec(self).map_err(|_| crate::client::ZkPassError::Unspecified)
+    }
+
+    /// Decodes group parameters from the encoding produced by [`GroupParams::to_msgpack`].
+    ///
+    /// # Arguments
+    /// * `bytes` - The encoded parameters.
+    ///
+    /// # Returns
+    /// The decoded parameters, or `ZkPassError::InvalidParameter` if the bytes are not valid
+    /// MessagePack or contain an invalid value.
+    pub fn from_msgpack(bytes: &[u8]) -> Result<Self, crate::client::ZkPassError> {
+        rmp_serde::from_slice(bytes).map_err(|_| crate::client::ZkPassError::InvalidParameter)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use curve25519_dalek::RistrettoPoint;
+
+    #[test]
+    fn test_builder_matches_positional_construction() {
+        let [g, h, p, q] = [1u32, 2, 3, 4].map(|n| RistrettoPoint::mul_base(&n.into()));
+        let built = GroupParams::builder().q(q).p(p).h(h).g(g).build().unwrap();
+        assert_eq!(built, GroupParams { g, h, p, q });
+    }
+
+    /// Checks that `C` still tells the two verification equations apart when it compares them
+    /// in constant time.
+    #[cfg(feature = "constant_time")]
+    fn assert_equations_told_apart<C, T>(params: GroupParams<T>)
+    where
+        C: ChaumPedersen<GroupParameters = GroupParams<T>, CommitParameters = (T, T, T, T)>,
+        C::Secret: crate::rand::RandomGenerator<C::Secret>,
+        T: Copy + core::ops::Add<Output = T>,
+    {
+        use crate::rand::RandomGenerator;
+
+        let x = C::Secret::generate_random().unwrap();
+        let ((y1, y2, r1, r2), k) = C::commitment(&params, &x);
+        let c = C::challenge(&params);
+        let s = C::challenge_response(&params, &k, &c, &x);
+
+        let outcome = |cp| C::verify_detailed(&params, &s, &c, &cp);
+        assert_eq!(outcome((y1, y2, r1 + params.g, r2)), VerifyOutcome::FirstEquationFailed);
+        assert_eq!(outcome((y1, y2, r1, r2 + params.h)), VerifyOutcome::SecondEquationFailed);
+        assert_eq!(outcome((y1, y2, r1, r2)), VerifyOutcome::Ok);
+    }
+
+    #[cfg(feature = "constant_time")]
+    #[test]
+    fn test_constant_time_verify_detailed() {
+        use crate::chaum_pedersen::brainpool::BrainpoolChaumPedersen;
+        use crate::chaum_pedersen::constants::{
+            BRAINPOOL_P256_GROUP_PARAMS, EC25519_GROUP_PARAMS, JUBJUB_GROUP_PARAMS,
+            PALLAS_GROUP_PARAMS, VESTA_GROUP_PARAMS,
+        };
+        use crate::chaum_pedersen::curve25519::Curve25519ChaumPedersen;
+        use crate::chaum_pedersen::jubjub::JubjubChaumPedersen;
+        use crate::chaum_pedersen::pallas::PallasCurveChaumPedersen;
+        use crate::chaum_pedersen::vesta::VestaCurveChaumPedersen;
+
+        assert_equations_told_apart::<Curve25519ChaumPedersen, _>(EC25519_GROUP_PARAMS.to_owned());
+        assert_equations_told_apart::<PallasCurveChaumPedersen, _>(PALLAS_GROUP_PARAMS.to_owned());
+        assert_equations_told_apart::<VestaCurveChaumPedersen, _>(VESTA_GROUP_PARAMS.to_owned());
+        assert_equations_told_apart::<JubjubChaumPedersen, _>(JUBJUB_GROUP_PARAMS.to_owned());
+        assert_equations_told_apart::<BrainpoolChaumPedersen, _>(
+            BRAINPOOL_P256_GROUP_PARAMS.to_owned(),
+        );
+    }
+
+    #[test]
+    fn test_traced_protocol_records_every_message() {
+        use crate::chaum_pedersen::constants::EC25519_GROUP_PARAMS;
+        use crate::chaum_pedersen::curve25519::Curve25519ChaumPedersen;
+        use crate::rand::RandomGenerator;
+        use curve25519_dalek::Scalar;
+
+        let params = EC25519_GROUP_PARAMS.to_owned();
+        let x = Scalar::generate_random().unwrap();
+        let mut prover = ProtocolTranscript::new(1);
+        let (cp, k) = Curve25519ChaumPedersen::commitment_traced(&params, &x, Some(&mut prover));
+        let c = Curve25519ChaumPedersen::challenge_traced(&params, None);
+        let s = Curve25519ChaumPedersen::challenge_response_traced(
+            &params,
+            &k,
+            &c,
+            &x,
+            Some(&mut prover),
+        );
+
+        let mut verifier = ProtocolTranscript::new(1);
+        let verified =
+            Curve25519ChaumPedersen::verify_traced(&params, &s, &c, &cp, Some(&mut verifier));
+        assert!(verified);
+        assert!(verifier.verified);
+        assert!(!prover.verified);
+        prover.verified = true;
+        assert_eq!(prover, verifier);
+
+        let mut failed = ProtocolTranscript::new(2);
+        let wrong_c = c + Scalar::ONE;
+        let verified =
+            Curve25519ChaumPedersen::verify_traced(&params, &s, &wrong_c, &cp, Some(&mut failed));
+        assert!(!verified);
+        assert!(!failed.verified);
+        assert_eq!(failed.c, Scalar::convert_to(&wrong_c));
+    }
+
+    #[test]
+    fn test_builder_rejects_missing_parameter() {
+        assert_eq!(GroupParams::<u32>::builder().build(), Err(MissingGroupParam("g")));
+        let missing_h = GroupParams::builder().g(1u32).p(3).q(4).build();
+        assert_eq!(missing_h, Err(MissingGroupParam("h")));
+        let missing_q = GroupParams::builder().g(1u32).h(2).p(3).build();
+        assert_eq!(missing_q.unwrap_err().to_string(), "Group parameter `q` is not set");
+    }
+
+    #[test]
+    fn test_curve_params_validate_with_any_placeholder() {
+        use crate::chaum_pedersen::constants::{
+            EC25519_GROUP_PARAMS, PALLAS_GROUP_PARAMS, RFC5114_MODP_2048_256_BIT_PARAMS,
+        };
+        use crate::rand::RandomGenerator;
+        use curve25519_dalek::traits::Identity;
+
+        let random = RistrettoPoint::generate_random().unwrap();
+        for placeholder in [RistrettoPoint::identity(), random] {
+            let params = GroupParams {
+                p: placeholder,
+                q: placeholder,
+                ..EC25519_GROUP_PARAMS.to_owned()
+            };
+            assert_eq!(params.validate(), Ok(()));
+            assert_eq!(params.check_primality(1), Ok(()));
+            assert_eq!((params.modulus(), params.order()), (None, None));
+        }
+
+        let pallas = GroupParams::builder()
+            .g(PALLAS_GROUP_PARAMS.g)
+            .h(PALLAS_GROUP_PARAMS.h)
+            .build_group()
+            .unwrap();
+        assert_eq!(pallas.validate(), Ok(()));
+        assert_eq!(pallas.order(), None);
+
+        // Modular groups still require `p` and `q`.
+        let modp = RFC5114_MODP_2048_256_BIT_PARAMS.to_owned();
+        assert_eq!((modp.modulus(), modp.order()), (Some(&modp.p), Some(&modp.q)));
+        let missing_p = GroupParams::builder()
+            .g(modp.g.clone())
+            .h(modp.h.clone())
+            .q(modp.q.clone())
+            .build_group();
+        assert_eq!(missing_p, Err(MissingGroupParam("p")));
+    }
+
+    #[test]
+    fn test_msgpack_is_smaller_than_json() {
+        use crate::chaum_pedersen::constants::{
+            EC25519_GROUP_PARAMS, RFC5114_MODP_2048_256_BIT_PARAMS,
+        };
+        use crate::chaum_pedersen::curve25519::Curve25519ChaumPedersen;
+        use crate::rand::RandomGenerator;
+        use curve25519_dalek::Scalar;
+        use num_bigint::BigUint;
+
+        let params = RFC5114_MODP_2048_256_BIT_PARAMS.to_owned();
+        let msgpack = params.to_msgpack().unwrap();
+        assert_eq!(GroupParams::<BigUint>::from_msgpack(&msgpack).unwrap(), params);
+        let json = serde_json::to_vec(&params).unwrap();
+        assert!(msgpack.len() * 4 < json.len() * 3);
+        // JSON keeps carrying the values as base64 strings.
+        assert_eq!(serde_json::from_slice::<GroupParams<BigUint>>(&json).unwrap(), params);
+
+        let params = EC25519_GROUP_PARAMS.to_owned();
+        let x = Scalar::generate_random().unwrap();
+        let bundle = ProofBundle::<Curve25519ChaumPedersen>::prove(&params, "alice".into(), 1, &x);
+        let msgpack = bundle.to_msgpack().unwrap();
+        let decoded = ProofBundle::<Curve25519ChaumPedersen>::from_msgpack(&msgpack).unwrap();
+        assert_eq!(decoded.to_compact_bytes().unwrap(), bundle.to_compact_bytes().unwrap());
+        assert!(msgpack.len() < serde_json::to_vec(&bundle).unwrap().len());
+
+        assert!(GroupParams::<BigUint>::from_msgpack(&msgpack[1..]).is_err());
+    }
 }
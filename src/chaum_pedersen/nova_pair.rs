@@ -0,0 +1,225 @@
+//! # Pallas-Vesta Cross-Curve Proofs
+//!
+//! The Pallas and Vesta curves form a cycle: the scalar field of each is the base field of the
+//! other, so that statements about one curve can be proven in circuits over the other, as
+//! Nova-style recursive proofs do. This module proves the building block of such proofs: that a
+//! Pallas commitment `(g_p^x, h_p^x)` and a Vesta commitment `(g_v^x, h_v^x)` share the same
+//! secret `x`, represented as `x mod |Pallas|` on Pallas and `x mod |Vesta|` on Vesta.
+//!
+//! The proof is the AND-composition of the Chaum-Pedersen instances on both curves, answered
+//! with one challenge `c` and one response `s`. As the group orders differ, `s = k + c * x` is
+//! computed over the integers rather than modulo either order, and each curve reduces it modulo
+//! its own order. The commitment randomness `k` is drawn [`STATISTICAL_BITS`] larger than
+//! `c * x`, so that `s` statistically hides `x`, and verifiers reject responses larger than
+//! any honest one, which bounds the `x` a prover can have used.
+
+use crate::chaum_pedersen::GroupParams;
+use crate::rand::ProtocolRng;
+use alloc::vec::Vec;
+use merlin::Transcript;
+use num_bigint::BigUint;
+use pasta_curves::group::ff::{FromUniformBytes, PrimeField};
+use pasta_curves::group::GroupEncoding;
+use pasta_curves::{pallas, vesta};
+use rand_core::RngCore;
+
+/// The number of bits of the challenge `c`.
+pub const CHALLENGE_BITS: u64 = 128;
+
+/// The number of bits by which the commitment randomness `k` exceeds `c * x`.
+pub const STATISTICAL_BITS: u64 = 128;
+
+/// The number of bits of a secret, enough for a scalar of either curve.
+const SECRET_BITS: u64 = 255;
+
+/// The number of bits of the commitment randomness `k`.
+const RANDOMNESS_BITS: u64 = SECRET_BITS + CHALLENGE_BITS + STATISTICAL_BITS;
+
+/// A proof that a Pallas and a Vesta commitment share the same secret.
+///
+/// # Fields
+/// - `pallas`: The Pallas commitment `(y1, y2) = (g_p^x, h_p^x)`.
+/// - `vesta`: The Vesta commitment `(y1, y2) = (g_v^x, h_v^x)`.
+/// - `pallas_r`: The Pallas commitment to the randomness, `(g_p^k, h_p^k)`.
+/// - `vesta_r`: The Vesta commitment to the randomness, `(g_v^k, h_v^k)`.
+/// - `s`: The response `k + c * x`, over the integers.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CrossCurveProof {
+    pub pallas: (pallas::Point, pallas::Point),
+    pub vesta: (vesta::Point, vesta::Point),
+    pub pallas_r: (pallas::Point, pallas::Point),
+    pub vesta_r: (vesta::Point, vesta::Point),
+    pub s: BigUint,
+}
+
+/// Returns the Vesta representation `x mod |Vesta|` of a Pallas secret `x`.
+///
+/// # Arguments
+/// * `x` - The secret, reduced modulo the order of Pallas.
+pub fn to_vesta_scalar(x: &pallas::Scalar) -> vesta::Scalar {
+    vesta_scalar(&BigUint::from_bytes_le(x.to_repr().as_ref()))
+}
+
+/// Proves that the commitments to `x_pallas` on Pallas and to `x_vesta` on Vesta share the
+/// same secret.
+///
+/// The response is computed from `x_pallas`, so the proof only verifies if `x_vesta` is its
+/// Vesta representation, see [`to_vesta_scalar`].
+///
+/// # Arguments
+/// * `pallas_params` - The Pallas group parameters.
+/// * `vesta_params` - The Vesta group parameters.
+/// * `x_pallas` - The secret, reduced modulo the order of Pallas.
+/// * `x_vesta` - The secret, reduced modulo the order of Vesta.
+///
+/// # Returns
+/// The proof, including both commitments.
+pub fn prove_cross_curve(
+    pallas_params: &GroupParams<pallas::Point>, vesta_params: &GroupParams<vesta::Point>,
+    x_pallas: &pallas::Scalar, x_vesta: &vesta::Scalar,
+) -> CrossCurveProof {
+    let mut bytes = [0u8; (RANDOMNESS_BITS as usize).div_ceil(8)];
+    ProtocolRng.fill_bytes(&mut bytes);
+    let k = BigUint::from_bytes_le(&bytes) >> (bytes.len() as u64 * 8 - RANDOMNESS_BITS);
+
+    let (k_pallas, k_vesta) = (pallas_scalar(&k), vesta_scalar(&k));
+    let mut proof = CrossCurveProof {
+        pallas: (pallas_params.g * x_pallas, pallas_params.h * x_pallas),
+        vesta: (vesta_params.g * x_vesta, vesta_params.h * x_vesta),
+        pallas_r: (pallas_params.g * k_pallas, pallas_params.h * k_pallas),
+        vesta_r: (vesta_params.g * k_vesta, vesta_params.h * k_vesta),
+        s: BigUint::default(),
+    };
+    let c = challenge(pallas_params, vesta_params, &proof);
+    proof.s = k + c * BigUint::from_bytes_le(x_pallas.to_repr().as_ref());
+    proof
+}
+
+/// Verifies a proof that a Pallas and a Vesta commitment share the same secret.
+///
+/// # Arguments
+/// * `pallas_params` - The Pallas group parameters.
+/// * `vesta_params` - The Vesta group parameters.
+/// * `proof` - The proof, including both commitments.
+///
+/// # Returns
+/// `true` if the response is in range and the verification equations hold on both curves.
+pub fn verify_cross_curve(
+    pallas_params: &GroupParams<pallas::Point>, vesta_params: &GroupParams<vesta::Point>,
+    proof: &CrossCurveProof,
+) -> bool {
+    if proof.s.bits() > RANDOMNESS_BITS + 1 {
+        return false;
+    }
+    let c = challenge(pallas_params, vesta_params, proof);
+    let (c_pallas, s_pallas) = (pallas_scalar(&c), pallas_scalar(&proof.s));
+    let (c_vesta, s_vesta) = (vesta_scalar(&c), vesta_scalar(&proof.s));
+
+    pallas_params.g * s_pallas == proof.pallas_r.0 + proof.pallas.0 * c_pallas
+        && pallas_params.h * s_pallas == proof.pallas_r.1 + proof.pallas.1 * c_pallas
+        && vesta_params.g * s_vesta == proof.vesta_r.0 + proof.vesta.0 * c_vesta
+        && vesta_params.h * s_vesta == proof.vesta_r.1 + proof.vesta.1 * c_vesta
+}
+
+/// Derives the challenge from the parameters, the commitments and the commitments to the
+/// randomness of both curves.
+fn challenge(
+    pallas_params: &GroupParams<pallas::Point>, vesta_params: &GroupParams<vesta::Point>,
+    proof: &CrossCurveProof,
+) -> BigUint {
+    let mut transcript = Transcript::new(b"zk_pass.cross_curve");
+    for point in [
+        &pallas_params.g,
+        &pallas_params.h,
+        &proof.pallas.0,
+        &proof.pallas.1,
+        &proof.pallas_r.0,
+        &proof.pallas_r.1,
+    ] {
+        transcript.append_message(b"pallas", point.to_bytes().as_ref());
+    }
+    for point in [
+        &vesta_params.g,
+        &vesta_params.h,
+        &proof.vesta.0,
+        &proof.vesta.1,
+        &proof.vesta_r.0,
+        &proof.vesta_r.1,
+    ] {
+        transcript.append_message(b"vesta", point.to_bytes().as_ref());
+    }
+    let mut bytes = [0u8; CHALLENGE_BITS as usize / 8];
+    transcript.challenge_bytes(b"c", &mut bytes);
+    BigUint::from_bytes_le(&bytes)
+}
+
+/// Reduces a non-negative integer of at most 512 bits modulo the order of Pallas.
+fn pallas_scalar(n: &BigUint) -> pallas::Scalar {
+    pallas::Scalar::from_uniform_bytes(&uniform_bytes(n))
+}
+
+/// Reduces a non-negative integer of at most 512 bits modulo the order of Vesta.
+fn vesta_scalar(n: &BigUint) -> vesta::Scalar {
+    vesta::Scalar::from_uniform_bytes(&uniform_bytes(n))
+}
+
+/// Encodes a non-negative integer of at most 512 bits as 64 little-endian bytes.
+fn uniform_bytes(n: &BigUint) -> [u8; 64] {
+    let le: Vec<u8> = n.to_bytes_le();
+    let mut bytes = [0u8; 64];
+    bytes[..le.len()].copy_from_slice(&le);
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chaum_pedersen::constants::{PALLAS_GROUP_PARAMS, VESTA_GROUP_PARAMS};
+    use crate::rand::RandomGenerator;
+
+    #[test]
+    fn test_cross_curve_proof_verifies() {
+        for _ in 0..10 {
+            let x = pallas::Scalar::generate_random().unwrap();
+            let proof = prove_cross_curve(
+                &PALLAS_GROUP_PARAMS,
+                &VESTA_GROUP_PARAMS,
+                &x,
+                &to_vesta_scalar(&x),
+            );
+            assert!(verify_cross_curve(&PALLAS_GROUP_PARAMS, &VESTA_GROUP_PARAMS, &proof));
+        }
+    }
+
+    #[test]
+    fn test_cross_curve_proof_rejects_mismatched_scalars() {
+        let x = pallas::Scalar::generate_random().unwrap();
+        let other = vesta::Scalar::generate_random().unwrap();
+        let proof = prove_cross_curve(&PALLAS_GROUP_PARAMS, &VESTA_GROUP_PARAMS, &x, &other);
+        assert!(!verify_cross_curve(&PALLAS_GROUP_PARAMS, &VESTA_GROUP_PARAMS, &proof));
+    }
+
+    #[test]
+    fn test_cross_curve_proof_rejects_tampering() {
+        let x = pallas::Scalar::generate_random().unwrap();
+        let proof =
+            prove_cross_curve(&PALLAS_GROUP_PARAMS, &VESTA_GROUP_PARAMS, &x, &to_vesta_scalar(&x));
+
+        let mut tampered = proof.clone();
+        tampered.s += 1u32;
+        assert!(!verify_cross_curve(&PALLAS_GROUP_PARAMS, &VESTA_GROUP_PARAMS, &tampered));
+
+        // Commitments to another secret on one curve do not pass for the proven ones.
+        let y = vesta::Scalar::generate_random().unwrap();
+        let mut tampered = proof.clone();
+        tampered.vesta = (VESTA_GROUP_PARAMS.g * y, VESTA_GROUP_PARAMS.h * y);
+        assert!(!verify_cross_curve(&PALLAS_GROUP_PARAMS, &VESTA_GROUP_PARAMS, &tampered));
+
+        // A response that could not have been computed honestly is out of range, even if it
+        // reduces to the right scalars on both curves.
+        let mut tampered = proof;
+        let order = |modulus: &str| BigUint::parse_bytes(&modulus.as_bytes()[2..], 16).unwrap();
+        tampered.s += (order(pallas::Scalar::MODULUS) * order(vesta::Scalar::MODULUS)) << 8;
+        assert!(!verify_cross_curve(&PALLAS_GROUP_PARAMS, &VESTA_GROUP_PARAMS, &tampered));
+    }
+}
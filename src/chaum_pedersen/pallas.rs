@@ -4,17 +4,23 @@
 //! The protocol includes methods for generating commitments, creating challenges,
 //! responding to challenges, and verifying the correctness of the response.
 
-use crate::chaum_pedersen::{ChaumPedersen, GroupParams};
-use crate::conversion::ByteConvertible;
-use crate::rand::RandomGenerator;
+use crate::chaum_pedersen::transcript::TranscriptChallenge;
+use crate::chaum_pedersen::{
+    AffineCoordinates, ChaumPedersen, GroupParams, InvalidGroupParams, ValidateGroupParams,
+    VerifyOutcome,
+};
+use crate::conversion::{ByteConvertible, ConversionError};
+use crate::rand::{ProtocolRng, RandomGenerator};
+use alloc::vec::Vec;
+use merlin::Transcript;
+use pasta_curves::arithmetic::{CurveAffine, CurveExt};
 use pasta_curves::group::ff::{Field, FromUniformBytes, PrimeField};
+use pasta_curves::group::Curve;
 use pasta_curves::group::Group;
 use pasta_curves::group::GroupEncoding;
 use pasta_curves::pallas::{Point, Scalar};
 use pasta_curves::Eq;
 use pasta_curves::Fq;
-use rand_core::OsRng;
-use std::error::Error;
 
 /// The PallasCurveChaumPedersen struct defines the specific types used in the Chaum-Pedersen protocol for the Pallas curve.
 pub struct PallasCurveChaumPedersen {}
@@ -43,9 +49,9 @@ impl ChaumPedersen for PallasCurveChaumPedersen {
     where
         Self: Sized,
     {
-        let y1 = params.g * Scalar::from(x.clone());
-        let y2 = params.h * Scalar::from(x.clone());
-        let mut rng = OsRng;
+        let y1 = params.g * x;
+        let y2 = params.h * x;
+        let mut rng = ProtocolRng;
         let k = Scalar::random(&mut rng);
         let r1 = params.g * k;
         let r2 = params.h * k;
@@ -62,7 +68,7 @@ impl ChaumPedersen for PallasCurveChaumPedersen {
     ///
     /// Returns a random scalar value to be used as a challenge.
     fn challenge(_: &GroupParams<Point>) -> Self::Challenge {
-        let mut rng = OsRng;
+        let mut rng = ProtocolRng;
         Scalar::random(&mut rng)
     }
 
@@ -99,13 +105,53 @@ impl ChaumPedersen for PallasCurveChaumPedersen {
     ///
     /// # Returns
     ///
-    /// Returns `true` if the verification is successful, `false` otherwise.
-    fn verify(
+    /// Returns `VerifyOutcome::Ok` if both equations hold, or the first equation that fails.
+    fn verify_detailed(
         params: &Self::GroupParameters, s: &Self::Response, c: &Self::Challenge,
         cp: &Self::CommitParameters,
-    ) -> bool {
+    ) -> VerifyOutcome {
         let (y1, y2, r1, r2) = cp;
-        (params.g * s == r1 + (y1 * c)) && (params.h * s == r2 + (y2 * c))
+        #[cfg(feature = "constant_time")]
+        {
+            use subtle::ConstantTimeEq;
+            VerifyOutcome::from_equations(
+                (params.g * s).ct_eq(&(r1 + (y1 * c))).into(),
+                (params.h * s).ct_eq(&(r2 + (y2 * c))).into(),
+            )
+        }
+        #[cfg(not(feature = "constant_time"))]
+        VerifyOutcome::from_equations(params.g * s == r1 + (y1 * c), params.h * s == r2 + (y2 * c))
+    }
+}
+
+impl ValidateGroupParams for Point {
+    /// Checks that `g` and `h` are distinct points on the curve other than the identity. The
+    /// Pallas curve has prime order, so every such point generates the whole group.
+    fn validate(params: &GroupParams<Point>) -> Result<(), InvalidGroupParams> {
+        if bool::from(params.g.is_identity()) || bool::from(params.h.is_identity()) {
+            return Err(InvalidGroupParams::TrivialGenerator);
+        }
+        if !bool::from(params.g.is_on_curve()) || !bool::from(params.h.is_on_curve()) {
+            return Err(InvalidGroupParams::NotOnCurve);
+        }
+        if params.g == params.h {
+            return Err(InvalidGroupParams::DependentGenerators);
+        }
+        Ok(())
+    }
+
+    /// Checks that the element is a point on the curve other than the identity.
+    fn is_valid_group_element(_params: &GroupParams<Point>, element: &Point) -> bool {
+        !bool::from(element.is_identity()) && bool::from(element.is_on_curve())
+    }
+}
+
+impl AffineCoordinates for Point {
+    fn to_affine_xy(&self) -> (Vec<u8>, Vec<u8>) {
+        let coordinates = self.to_affine().coordinates();
+        let x = coordinates.map(|c| *c.x()).unwrap_or(Field::ZERO);
+        let y = coordinates.map(|c| *c.y()).unwrap_or(Field::ZERO);
+        (x.to_repr().to_vec(), y.to_repr().to_vec())
     }
 }
 
@@ -114,15 +160,12 @@ impl ByteConvertible<Point> for Point {
         t.to_bytes().to_vec()
     }
 
-    fn convert_from(bytes: &[u8]) -> Result<Point, Box<dyn Error>> {
-        let array: [u8; 32] = bytes.try_into().map_err(|_| {
-            Box::new(std::io::Error::new(
-                std::io::ErrorKind::InvalidInput,
-                "Invalid bytes length for Scalar",
-            ))
-        })?;
+    fn convert_from(bytes: &[u8]) -> Result<Point, ConversionError> {
+        let array: [u8; 32] = bytes
+            .try_into()
+            .map_err(|_| ConversionError::InvalidLength)?;
 
-        Ok(Point::from_bytes(&array).unwrap())
+        Option::from(Point::from_bytes(&array)).ok_or(ConversionError::InvalidEncoding)
     }
 }
 
@@ -131,7 +174,7 @@ impl ByteConvertible<Scalar> for Scalar {
         t.to_repr().as_slice().to_vec()
     }
 
-    fn convert_from(bytes: &[u8]) -> Result<Scalar, Box<dyn Error>> {
+    fn convert_from(bytes: &[u8]) -> Result<Scalar, ConversionError> {
         // pad the array with zeros
         let array = |input: &[u8]| -> [u8; 64] {
             let mut output = [0u8; 64];
@@ -151,9 +194,9 @@ impl RandomGenerator<Fq> for Fq {
     /// A `Result` containing the random `Fq`, or an error if the generation fails.
     ///
     /// # Errors
-    /// Returns an error if the conversion from bytes to `Fq` fails.
-    fn generate_random() -> Result<Fq, Box<dyn std::error::Error>> {
-        Ok(Fq::random(&mut OsRng))
+    /// Returns an error if the random number generator fails.
+    fn generate_random() -> Result<Fq, rand_core::Error> {
+        Ok(Fq::random(&mut ProtocolRng))
     }
 }
 
@@ -164,9 +207,18 @@ impl RandomGenerator<Eq> for Eq {
     /// A `Result` containing the random `Fq`, or an error if the generation fails.
     ///
     /// # Errors
-    /// Returns an error if the conversion from bytes to `Fq` fails.
-    fn generate_random() -> Result<Eq, Box<dyn std::error::Error>> {
-        Ok(Eq::random(&mut OsRng))
+    /// Returns an error if the random number generator fails.
+    fn generate_random() -> Result<Eq, rand_core::Error> {
+        Ok(Eq::random(&mut ProtocolRng))
+    }
+}
+
+impl TranscriptChallenge<GroupParams<Point>> for Scalar {
+    /// Squeezes 64 bytes out of the transcript and reduces them to a `Scalar`.
+    fn from_transcript(transcript: &mut Transcript, _: &GroupParams<Point>) -> Scalar {
+        let mut bytes = [0u8; 64];
+        transcript.challenge_bytes(b"c", &mut bytes);
+        Scalar::from_uniform_bytes(&bytes)
     }
 }
 
@@ -182,6 +234,9 @@ mod test {
     use crate::chaum_pedersen::test::test_execute_protocol;
     use pasta_curves::group::GroupEncoding;
     use pasta_curves::pallas;
+    use proptest::collection::vec;
+    use proptest::prelude::*;
+    use rand_core::OsRng;
 
     #[test]
     fn pallas_point_conversion_round_trip() {
@@ -191,6 +246,18 @@ mod test {
         assert_eq!(original, recovered);
     }
 
+    #[test]
+    fn pallas_point_invalid_bytes() {
+        assert_eq!(
+            pallas::Point::convert_from(&[0u8; 31]).unwrap_err(),
+            ConversionError::InvalidLength
+        );
+        assert_eq!(
+            pallas::Point::convert_from(&[0xffu8; 32]).unwrap_err(),
+            ConversionError::InvalidEncoding
+        );
+    }
+
     #[test]
     fn pallas_scalar_conversion_round_trip() {
         let original = pallas::Scalar::generate_random().unwrap();
@@ -199,6 +266,28 @@ mod test {
         assert_eq!(original, recovered);
     }
 
+    #[test]
+    fn pallas_params_validate() {
+        let mut params = PALLAS_GROUP_PARAMS.to_owned();
+        assert_eq!(params.validate(), Ok(()));
+        params.h = params.g;
+        assert_eq!(params.validate(), Err(InvalidGroupParams::DependentGenerators));
+    }
+
+    #[test]
+    fn pallas_affine_coordinates_round_trip() {
+        let params = PALLAS_GROUP_PARAMS.to_owned();
+        for point in [params.g, params.h, Point::generate_random().unwrap()] {
+            let (x, y) = point.to_affine_xy();
+            let base = |bytes: Vec<u8>| {
+                <Point as CurveExt>::Base::from_repr(bytes.try_into().unwrap()).unwrap()
+            };
+            let affine = pasta_curves::pallas::Affine::from_xy(base(x), base(y)).unwrap();
+            assert_eq!(Point::from(affine), point);
+        }
+        assert_eq!(Point::identity().to_affine_xy(), (vec![0u8; 32], vec![0u8; 32]));
+    }
+
     /// Test verification using standard protocol execution.
     #[test]
     fn test_elliptic_curve_standard_verification() {
@@ -239,4 +328,29 @@ mod test {
         let verified = PallasCurveChaumPedersen::verify(&params, &fake_response, &c, &cp);
         assert!(!verified);
     }
+
+    proptest! {
+        #[test]
+        fn prop_conversions_round_trip(seed in vec(any::<u8>(), 64)) {
+            let seed: [u8; 64] = seed.try_into().unwrap();
+            let x = Scalar::from_uniform_bytes(&seed);
+            prop_assert_eq!(Scalar::convert_from(&Scalar::convert_to(&x)), Ok(x));
+            let point = Point::generator() * x;
+            prop_assert_eq!(Point::convert_from(&Point::convert_to(&point)), Ok(point));
+        }
+
+        #[test]
+        fn prop_wrong_length_rejected(
+            bytes in vec(any::<u8>(), 0..=96).prop_filter("not 32 bytes", |b| b.len() != 32),
+        ) {
+            prop_assert_eq!(Point::convert_from(&bytes), Err(ConversionError::InvalidLength));
+            // Scalars of any length are reduced into the field, so only panics are ruled out.
+            let _ = Scalar::convert_from(&bytes);
+        }
+
+        #[test]
+        fn prop_arbitrary_point_bytes_do_not_panic(bytes in vec(any::<u8>(), 32)) {
+            let _ = Point::convert_from(&bytes);
+        }
+    }
 }
@@ -0,0 +1,178 @@
+//! # Pedersen Commitments
+//!
+//! This module provides standalone Pedersen commitments on top of the group parameters used by
+//! the Chaum-Pedersen backends. A commitment to a message `m` with blinding factor `r` is the
+//! single group element `g^m * h^r`, which hides `m` perfectly and binds the committer to it as
+//! long as the discrete log of `h` with respect to `g` is unknown.
+//!
+//! Commitments are additively homomorphic: combining the commitments to `(m1, r1)` and
+//! `(m2, r2)` yields the commitment to `(m1 + m2, r1 + r2)`.
+
+use crate::chaum_pedersen::curve25519::Curve25519ChaumPedersen;
+use crate::chaum_pedersen::discretelog::DiscreteLogChaumPedersen;
+use crate::chaum_pedersen::{ChaumPedersen, GroupParams};
+use curve25519_dalek::RistrettoPoint;
+use num_bigint::BigUint;
+
+/// A trait for Chaum-Pedersen backends that support Pedersen commitments.
+///
+/// The message is committed as a `Secret` and blinded with a `CommitmentRandom`, so any value
+/// usable as a protocol secret can also be committed to.
+pub trait PedersenCommitment: ChaumPedersen {
+    /// The type of the group element produced by a commitment.
+    type Commitment: PartialEq;
+
+    /// Computes the commitment `g^m * h^r`.
+    ///
+    /// # Arguments
+    /// * `params` - Group parameters used for the commitment.
+    /// * `m` - The message being committed to.
+    /// * `r` - The blinding factor hiding the message.
+    ///
+    /// # Returns
+    /// The commitment to `m`.
+    fn commit(
+        params: &Self::GroupParameters, m: &Self::Secret, r: &Self::CommitmentRandom,
+    ) -> Self::Commitment;
+
+    /// Combines two commitments into a commitment to the sum of their messages.
+    ///
+    /// # Arguments
+    /// * `params` - Group parameters the commitments were made with.
+    /// * `c1` - The first commitment.
+    /// * `c2` - The second commitment.
+    ///
+    /// # Returns
+    /// The commitment to `m1 + m2` blinded by `r1 + r2`.
+    fn add_commitments(
+        params: &Self::GroupParameters, c1: &Self::Commitment, c2: &Self::Commitment,
+    ) -> Self::Commitment;
+}
+
+/// Computes the Pedersen commitment `g^m * h^r`.
+///
+/// # Arguments
+/// * `params` - Group parameters used for the commitment.
+/// * `m` - The message being committed to.
+/// * `r` - The blinding factor hiding the message.
+///
+/// # Returns
+/// The commitment to `m`.
+pub fn commit<C: PedersenCommitment>(
+    params: &C::GroupParameters, m: &C::Secret, r: &C::CommitmentRandom,
+) -> C::Commitment {
+    C::commit(params, m, r)
+}
+
+/// Opens a Pedersen commitment, checking that it was made to `m` with blinding factor `r`.
+///
+/// # Arguments
+/// * `params` - Group parameters the commitment was made with.
+/// * `commitment` - The commitment to open.
+/// * `m` - The claimed message.
+/// * `r` - The claimed blinding factor.
+///
+/// # Returns
+/// `true` if the commitment matches `m` and `r`, `false` otherwise.
+pub fn open<C: PedersenCommitment>(
+    params: &C::GroupParameters, commitment: &C::Commitment, m: &C::Secret, r: &C::CommitmentRandom,
+) -> bool {
+    C::commit(params, m, r) == *commitment
+}
+
+/// Combines two Pedersen commitments into a commitment to the sum of their messages.
+///
+/// # Arguments
+/// * `params` - Group parameters the commitments were made with.
+/// * `c1` - The first commitment.
+/// * `c2` - The second commitment.
+///
+/// # Returns
+/// The commitment to `m1 + m2` blinded by `r1 + r2`.
+pub fn add_commitments<C: PedersenCommitment>(
+    params: &C::GroupParameters, c1: &C::Commitment, c2: &C::Commitment,
+) -> C::Commitment {
+    C::add_commitments(params, c1, c2)
+}
+
+impl PedersenCommitment for DiscreteLogChaumPedersen {
+    type Commitment = BigUint;
+
+    fn commit(params: &GroupParams<BigUint>, m: &BigUint, r: &BigUint) -> BigUint {
+        (params.g.modpow(m, &params.p) * params.h.modpow(r, &params.p)) % &params.p
+    }
+
+    fn add_commitments(params: &GroupParams<BigUint>, c1: &BigUint, c2: &BigUint) -> BigUint {
+        // The group operation is multiplication modulo p.
+        (c1 * c2) % &params.p
+    }
+}
+
+impl PedersenCommitment for Curve25519ChaumPedersen {
+    type Commitment = RistrettoPoint;
+
+    fn commit(
+        params: &GroupParams<RistrettoPoint>, m: &Self::Secret, r: &Self::CommitmentRandom,
+    ) -> RistrettoPoint {
+        params.g * m + params.h * r
+    }
+
+    fn add_commitments(
+        _: &GroupParams<RistrettoPoint>, c1: &RistrettoPoint, c2: &RistrettoPoint,
+    ) -> RistrettoPoint {
+        // The group operation is point addition.
+        c1 + c2
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chaum_pedersen::constants::{
+        EC25519_GROUP_PARAMS, RFC5114_MODP_2048_256_BIT_PARAMS,
+    };
+    use crate::rand::RandomGenerator;
+    use curve25519_dalek::Scalar;
+
+    #[test]
+    fn test_discrete_log_commit_and_open() {
+        let params = RFC5114_MODP_2048_256_BIT_PARAMS.to_owned();
+        let m = BigUint::generate_random().unwrap();
+        let r = BigUint::generate_random().unwrap();
+        let c = commit::<DiscreteLogChaumPedersen>(&params, &m, &r);
+        assert!(open::<DiscreteLogChaumPedersen>(&params, &c, &m, &r));
+        assert!(!open::<DiscreteLogChaumPedersen>(&params, &c, &(m + 1u32), &r));
+    }
+
+    #[test]
+    fn test_discrete_log_additive_homomorphism() {
+        let params = RFC5114_MODP_2048_256_BIT_PARAMS.to_owned();
+        let (m1, r1) = (BigUint::generate_random().unwrap(), BigUint::generate_random().unwrap());
+        let (m2, r2) = (BigUint::generate_random().unwrap(), BigUint::generate_random().unwrap());
+        let c1 = commit::<DiscreteLogChaumPedersen>(&params, &m1, &r1);
+        let c2 = commit::<DiscreteLogChaumPedersen>(&params, &m2, &r2);
+        let sum = commit::<DiscreteLogChaumPedersen>(&params, &(m1 + m2), &(r1 + r2));
+        assert_eq!(sum, add_commitments::<DiscreteLogChaumPedersen>(&params, &c1, &c2));
+    }
+
+    #[test]
+    fn test_curve25519_commit_and_open() {
+        let params = EC25519_GROUP_PARAMS.to_owned();
+        let m = Scalar::generate_random().unwrap();
+        let r = Scalar::generate_random().unwrap();
+        let c = commit::<Curve25519ChaumPedersen>(&params, &m, &r);
+        assert!(open::<Curve25519ChaumPedersen>(&params, &c, &m, &r));
+        assert!(!open::<Curve25519ChaumPedersen>(&params, &c, &m, &(r + Scalar::ONE)));
+    }
+
+    #[test]
+    fn test_curve25519_additive_homomorphism() {
+        let params = EC25519_GROUP_PARAMS.to_owned();
+        let (m1, r1) = (Scalar::generate_random().unwrap(), Scalar::generate_random().unwrap());
+        let (m2, r2) = (Scalar::generate_random().unwrap(), Scalar::generate_random().unwrap());
+        let c1 = commit::<Curve25519ChaumPedersen>(&params, &m1, &r1);
+        let c2 = commit::<Curve25519ChaumPedersen>(&params, &m2, &r2);
+        let sum = commit::<Curve25519ChaumPedersen>(&params, &(m1 + m2), &(r1 + r2));
+        assert_eq!(sum, add_commitments::<Curve25519ChaumPedersen>(&params, &c1, &c2));
+    }
+}
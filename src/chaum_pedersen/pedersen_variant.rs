@@ -0,0 +1,399 @@
+//! # Pedersen Commitment Variant of the Chaum-Pedersen Protocol
+//!
+//! In the standard protocol the prover commits to `(y1 = g^x, y2 = h^x)`, so the secret `x` is
+//! the only value hidden behind the commitment. This module provides the Pedersen variant, in
+//! which the prover commits to `y = g^x * h^r` for an independent blinding factor `r`. The
+//! commitment `y` then hides `x` perfectly, as in ElGamal-style e-voting schemes, and the prover
+//! shows knowledge of both `x` and `r`:
+//!
+//! 1. The prover picks random `k` and `r_k`, and sends `y = g^x * h^r` and `r1 = g^k * h^r_k`.
+//! 2. The verifier sends a random challenge `c`.
+//! 3. The prover answers with `s_x = k + c * x` and `s_r = r_k + c * r`, modulo `q`.
+//! 4. The verifier checks that `g^s_x * h^s_r == r1 * y^c`.
+//!
+//! Commitments are computed with the [`PedersenCommitment`] implementation of a backend, so the
+//! variant is available for every backend implementing [`PedersenVariantBackend`].
+//!
+//! A commitment can also be re-randomized to `y' = y * h^r'` for a fresh blinding factor `r'`,
+//! yielding a commitment to the same `x` that cannot be linked to `y`, as blinded credential
+//! protocols require. The holder of `r'` proves that `y'` re-randomizes `y` without revealing
+//! it, see [`prove_rerandomization`].
+
+use crate::chaum_pedersen::curve25519::Curve25519ChaumPedersen;
+use crate::chaum_pedersen::discretelog::DiscreteLogChaumPedersen;
+use crate::chaum_pedersen::pedersen::PedersenCommitment;
+use crate::chaum_pedersen::transcript::TranscriptChallenge;
+use crate::chaum_pedersen::{ChaumPedersen, GroupParams, VerifyOutcome};
+use crate::conversion::ByteConvertible;
+use crate::rand::ProtocolRng;
+use core::marker::PhantomData;
+use curve25519_dalek::{RistrettoPoint, Scalar};
+use merlin::Transcript;
+use num_bigint::{BigUint, RandBigInt};
+
+/// A trait for backends providing the exponent arithmetic needed by the Pedersen variant.
+///
+/// Messages and blinding factors are both exponents, so backends must use the same type for
+/// their `Secret` and `CommitmentRandom`.
+pub trait PedersenVariantBackend:
+    PedersenCommitment<CommitmentRandom = <Self as ChaumPedersen>::Secret>
+{
+    /// Generates a random exponent below the group order `q`.
+    ///
+    /// # Arguments
+    /// * `params` - Group parameters bounding the exponent.
+    fn random_exponent(params: &Self::GroupParameters) -> Self::Secret;
+
+    /// Computes the response `k + c * x` modulo the group order `q`.
+    ///
+    /// # Arguments
+    /// * `params` - Group parameters whose order `q` reduces the response.
+    /// * `k` - The commitment randomness.
+    /// * `c` - The challenge.
+    /// * `x` - The committed value.
+    fn linear_response(
+        params: &Self::GroupParameters, k: &Self::Secret, c: &Self::Challenge, x: &Self::Secret,
+    ) -> Self::Secret;
+
+    /// Raises a commitment to the power of a challenge, computing `y^c`.
+    ///
+    /// # Arguments
+    /// * `params` - Group parameters the commitment was made with.
+    /// * `y` - The commitment.
+    /// * `c` - The challenge.
+    fn scale_commitment(
+        params: &Self::GroupParameters, y: &Self::Commitment, c: &Self::Challenge,
+    ) -> Self::Commitment;
+
+    /// Computes the blinding term `h^r` of a commitment.
+    ///
+    /// # Arguments
+    /// * `params` - Group parameters providing the generator `h`.
+    /// * `r` - The blinding factor.
+    fn blinding(params: &Self::GroupParameters, r: &Self::Secret) -> Self::Commitment;
+}
+
+/// The Chaum-Pedersen protocol over a Pedersen commitment `y = g^x * h^r`.
+///
+/// The secret is the pair `(x, r)`, the commitment randomness the pair `(k, r_k)`, and the
+/// response the pair `(s_x, s_r)`. The commitment parameters are `(y, r1)`.
+///
+/// # Type Parameters
+/// * `B` - The backend providing the group, e.g. [`Curve25519ChaumPedersen`].
+pub struct PedersenCommitChaumPedersen<B> {
+    _backend: PhantomData<B>,
+}
+
+impl<B: PedersenVariantBackend> ChaumPedersen for PedersenCommitChaumPedersen<B> {
+    type Secret = (B::Secret, B::Secret);
+    type CommitmentRandom = (B::Secret, B::Secret);
+    type Response = (B::Secret, B::Secret);
+    type Challenge = B::Challenge;
+    type GroupParameters = B::GroupParameters;
+    type CommitParameters = (B::Commitment, B::Commitment);
+
+    /// Commits to `x` blinded by `r`, computing `y = g^x * h^r` and `r1 = g^k * h^r_k` for
+    /// fresh random `k` and `r_k`.
+    fn commitment(
+        params: &Self::GroupParameters, x: &Self::Secret,
+    ) -> (Self::CommitParameters, Self::CommitmentRandom) {
+        let (x, r) = x;
+        let k = B::random_exponent(params);
+        let r_k = B::random_exponent(params);
+        let y = B::commit(params, x, r);
+        let r1 = B::commit(params, &k, &r_k);
+        ((y, r1), (k, r_k))
+    }
+
+    /// Generates a challenge with the backend.
+    fn challenge(params: &Self::GroupParameters) -> Self::Challenge {
+        B::challenge(params)
+    }
+
+    /// Computes the response `(s_x, s_r) = (k + c * x, r_k + c * r)`, modulo `q`.
+    fn challenge_response(
+        params: &Self::GroupParameters, k: &Self::CommitmentRandom, c: &Self::Challenge,
+        x: &Self::Secret,
+    ) -> Self::Response {
+        let ((k, r_k), (x, r)) = (k, x);
+        (B::linear_response(params, k, c, x), B::linear_response(params, r_k, c, r))
+    }
+
+    /// Checks that `g^s_x * h^s_r == r1 * y^c`.
+    ///
+    /// The variant has a single verification equation, so a failure is always reported as
+    /// `VerifyOutcome::FirstEquationFailed`.
+    fn verify_detailed(
+        params: &Self::GroupParameters, s: &Self::Response, c: &Self::Challenge,
+        cp: &Self::CommitParameters,
+    ) -> VerifyOutcome {
+        let ((s_x, s_r), (y, r1)) = (s, cp);
+        let lhs = B::commit(params, s_x, s_r);
+        let rhs = B::add_commitments(params, r1, &B::scale_commitment(params, y, c));
+        VerifyOutcome::from_equations(lhs == rhs, true)
+    }
+}
+
+/// A non-interactive proof that a commitment re-randomizes another, i.e. that the prover knows
+/// the `r'` with `fresh = original * h^r'`.
+///
+/// # Fields
+/// - `t`: The commitment to the proof randomness, `h^k`.
+/// - `s`: The response `k + c * r'`, modulo `q`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RerandomizationProof<T, S> {
+    pub t: T,
+    pub s: S,
+}
+
+/// Re-randomizes a commitment `y = g^x * h^r` to `y' = y * h^r'` for a fresh random `r'`.
+///
+/// The result commits to the same `x` with the blinding factor `r + r'`, but cannot be linked
+/// to `y` without knowing `r'`.
+///
+/// # Arguments
+/// * `params` - Group parameters the commitment was made with.
+/// * `commitment` - The commitment to re-randomize.
+///
+/// # Returns
+/// A tuple containing the fresh commitment and the blinding factor `r'` added to it.
+pub fn rerandomize<B: PedersenVariantBackend>(
+    params: &B::GroupParameters, commitment: &B::Commitment,
+) -> (B::Commitment, B::CommitmentRandom) {
+    let delta_r = B::random_exponent(params);
+    let fresh = B::add_commitments(params, commitment, &B::blinding(params, &delta_r));
+    (fresh, delta_r)
+}
+
+/// Proves that `fresh` re-randomizes `original` with the blinding factor `delta_r`, without
+/// revealing `delta_r`.
+///
+/// The proof is a Schnorr proof of knowledge of the discrete log of `fresh / original` to the
+/// base `h`, made non-interactive with the Fiat-Shamir transform.
+///
+/// # Arguments
+/// * `params` - Group parameters the commitments were made with.
+/// * `original` - The re-randomized commitment.
+/// * `fresh` - The commitment returned by [`rerandomize`].
+/// * `delta_r` - The blinding factor returned by [`rerandomize`].
+///
+/// # Returns
+/// The proof, which only verifies if `fresh = original * h^delta_r`.
+pub fn prove_rerandomization<B, T>(
+    params: &GroupParams<T>, original: &T, fresh: &T, delta_r: &B::Secret,
+) -> RerandomizationProof<T, B::Secret>
+where
+    B: PedersenVariantBackend<GroupParameters = GroupParams<T>, Commitment = T>,
+    B::Challenge: TranscriptChallenge<GroupParams<T>>,
+    T: ByteConvertible<T>,
+{
+    let k = B::random_exponent(params);
+    let t = B::blinding(params, &k);
+    let c: B::Challenge = rerandomization_challenge(params, original, fresh, &t);
+    let s = B::linear_response(params, &k, &c, delta_r);
+    RerandomizationProof { t, s }
+}
+
+/// Verifies a proof created by [`prove_rerandomization`].
+///
+/// # Arguments
+/// * `params` - Group parameters the commitments were made with.
+/// * `original` - The re-randomized commitment.
+/// * `fresh` - The claimed re-randomization of `original`.
+/// * `proof` - The proof.
+///
+/// # Returns
+/// `true` if `h^s * original^c == t * fresh^c`, i.e. the prover knows `r'` with
+/// `fresh = original * h^r'`.
+pub fn verify_rerandomization<B, T>(
+    params: &GroupParams<T>, original: &T, fresh: &T, proof: &RerandomizationProof<T, B::Secret>,
+) -> bool
+where
+    B: PedersenVariantBackend<GroupParameters = GroupParams<T>, Commitment = T>,
+    B::Challenge: TranscriptChallenge<GroupParams<T>>,
+    T: ByteConvertible<T> + PartialEq,
+{
+    let c: B::Challenge = rerandomization_challenge(params, original, fresh, &proof.t);
+    let lhs = B::add_commitments(
+        params,
+        &B::blinding(params, &proof.s),
+        &B::scale_commitment(params, original, &c),
+    );
+    let rhs = B::add_commitments(params, &proof.t, &B::scale_commitment(params, fresh, &c));
+    lhs == rhs
+}
+
+/// Derives the challenge of a re-randomization proof from the parameters, both commitments and
+/// the commitment to the proof randomness.
+fn rerandomization_challenge<T, S>(params: &GroupParams<T>, original: &T, fresh: &T, t: &T) -> S
+where
+    T: ByteConvertible<T>,
+    S: TranscriptChallenge<GroupParams<T>>,
+{
+    let mut transcript = Transcript::new(b"zk_pass.chaum_pedersen");
+    transcript.append_message(b"dom-sep", b"zk_pass.rerandomization");
+    transcript.append_message(b"g", &T::convert_to(&params.g));
+    transcript.append_message(b"h", &T::convert_to(&params.h));
+    transcript.append_message(b"p", &T::convert_to(&params.p));
+    transcript.append_message(b"q", &T::convert_to(&params.q));
+    transcript.append_message(b"original", &T::convert_to(original));
+    transcript.append_message(b"fresh", &T::convert_to(fresh));
+    transcript.append_message(b"t", &T::convert_to(t));
+    S::from_transcript(&mut transcript, params)
+}
+
+impl PedersenVariantBackend for DiscreteLogChaumPedersen {
+    fn random_exponent(params: &GroupParams<BigUint>) -> BigUint {
+        ProtocolRng.gen_biguint_below(&params.q)
+    }
+
+    fn linear_response(
+        params: &GroupParams<BigUint>, k: &BigUint, c: &BigUint, x: &BigUint,
+    ) -> BigUint {
+        (k + c * x) % &params.q
+    }
+
+    fn scale_commitment(params: &GroupParams<BigUint>, y: &BigUint, c: &BigUint) -> BigUint {
+        y.modpow(c, &params.p)
+    }
+
+    fn blinding(params: &GroupParams<BigUint>, r: &BigUint) -> BigUint {
+        params.h.modpow(r, &params.p)
+    }
+}
+
+impl PedersenVariantBackend for Curve25519ChaumPedersen {
+    fn random_exponent(_: &GroupParams<RistrettoPoint>) -> Scalar {
+        Scalar::random(&mut ProtocolRng)
+    }
+
+    fn linear_response(
+        _: &GroupParams<RistrettoPoint>, k: &Scalar, c: &Scalar, x: &Scalar,
+    ) -> Scalar {
+        // Scalar arithmetic is already reduced modulo the group order.
+        k + c * x
+    }
+
+    fn scale_commitment(
+        _: &GroupParams<RistrettoPoint>, y: &RistrettoPoint, c: &Scalar,
+    ) -> RistrettoPoint {
+        y * c
+    }
+
+    fn blinding(params: &GroupParams<RistrettoPoint>, r: &Scalar) -> RistrettoPoint {
+        params.h * r
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chaum_pedersen::constants::{
+        EC25519_GROUP_PARAMS, RFC5114_MODP_2048_256_BIT_PARAMS,
+    };
+    use crate::chaum_pedersen::pedersen::{commit, open};
+    use crate::chaum_pedersen::test::test_execute_protocol;
+    use crate::rand::RandomGenerator;
+
+    type DiscreteLogVariant = PedersenCommitChaumPedersen<DiscreteLogChaumPedersen>;
+    type Curve25519Variant = PedersenCommitChaumPedersen<Curve25519ChaumPedersen>;
+
+    #[test]
+    fn test_discrete_log_pedersen_variant() {
+        let params = RFC5114_MODP_2048_256_BIT_PARAMS.to_owned();
+        let x = DiscreteLogChaumPedersen::random_exponent(&params);
+        let r = DiscreteLogChaumPedersen::random_exponent(&params);
+        assert!(test_execute_protocol::<DiscreteLogVariant>(&params, &(x.clone(), r.clone())));
+
+        let ((y, r1), k) = DiscreteLogVariant::commitment(&params, &(x.clone(), r.clone()));
+        assert_eq!(y, commit::<DiscreteLogChaumPedersen>(&params, &x, &r));
+        let c = DiscreteLogVariant::challenge(&params);
+        let wrong_blinding = (x, DiscreteLogChaumPedersen::random_exponent(&params));
+        let s = DiscreteLogVariant::challenge_response(&params, &k, &c, &wrong_blinding);
+        assert_eq!(
+            DiscreteLogVariant::verify_detailed(&params, &s, &c, &(y, r1)),
+            VerifyOutcome::FirstEquationFailed
+        );
+    }
+
+    #[test]
+    fn test_curve25519_pedersen_variant() {
+        let params = EC25519_GROUP_PARAMS.to_owned();
+        let x = Scalar::generate_random().unwrap();
+        let r = Scalar::generate_random().unwrap();
+        assert!(test_execute_protocol::<Curve25519Variant>(&params, &(x, r)));
+
+        let ((y, r1), k) = Curve25519Variant::commitment(&params, &(x, r));
+        assert_eq!(y, commit::<Curve25519ChaumPedersen>(&params, &x, &r));
+        let c = Curve25519Variant::challenge(&params);
+        let wrong_secret = (Scalar::generate_random().unwrap(), r);
+        let s = Curve25519Variant::challenge_response(&params, &k, &c, &wrong_secret);
+        assert!(!Curve25519Variant::verify(&params, &s, &c, &(y, r1)));
+    }
+
+    #[test]
+    fn test_discrete_log_rerandomization_preserves_secret() {
+        let params = RFC5114_MODP_2048_256_BIT_PARAMS.to_owned();
+        let x = DiscreteLogChaumPedersen::random_exponent(&params);
+        let r = DiscreteLogChaumPedersen::random_exponent(&params);
+        let y = commit::<DiscreteLogChaumPedersen>(&params, &x, &r);
+
+        let (fresh, delta_r) = rerandomize::<DiscreteLogChaumPedersen>(&params, &y);
+        assert_ne!(fresh, y);
+        let fresh_r = (&r + &delta_r) % &params.q;
+        assert!(open::<DiscreteLogChaumPedersen>(&params, &fresh, &x, &fresh_r));
+        assert!(test_execute_protocol::<DiscreteLogVariant>(&params, &(x, fresh_r)));
+
+        let proof =
+            prove_rerandomization::<DiscreteLogChaumPedersen, _>(&params, &y, &fresh, &delta_r);
+        assert!(verify_rerandomization::<DiscreteLogChaumPedersen, _>(
+            &params, &y, &fresh, &proof
+        ));
+    }
+
+    #[test]
+    fn test_curve25519_rerandomization_preserves_secret() {
+        let params = EC25519_GROUP_PARAMS.to_owned();
+        let (x, r) = (Scalar::generate_random().unwrap(), Scalar::generate_random().unwrap());
+        let y = commit::<Curve25519ChaumPedersen>(&params, &x, &r);
+
+        let (fresh, delta_r) = rerandomize::<Curve25519ChaumPedersen>(&params, &y);
+        assert_ne!(fresh, y);
+        assert!(open::<Curve25519ChaumPedersen>(&params, &fresh, &x, &(r + delta_r)));
+        assert!(test_execute_protocol::<Curve25519Variant>(&params, &(x, r + delta_r)));
+
+        let proof =
+            prove_rerandomization::<Curve25519ChaumPedersen, _>(&params, &y, &fresh, &delta_r);
+        assert!(verify_rerandomization::<Curve25519ChaumPedersen, _>(
+            &params, &y, &fresh, &proof
+        ));
+    }
+
+    #[test]
+    fn test_rerandomization_proof_rejects_other_commitments() {
+        let params = EC25519_GROUP_PARAMS.to_owned();
+        let (x, r) = (Scalar::generate_random().unwrap(), Scalar::generate_random().unwrap());
+        let y = commit::<Curve25519ChaumPedersen>(&params, &x, &r);
+        let (fresh, delta_r) = rerandomize::<Curve25519ChaumPedersen>(&params, &y);
+        let proof =
+            prove_rerandomization::<Curve25519ChaumPedersen, _>(&params, &y, &fresh, &delta_r);
+
+        // A commitment to another secret is no re-randomization of `y`.
+        let other =
+            commit::<Curve25519ChaumPedersen>(&params, &Scalar::generate_random().unwrap(), &r);
+        assert!(!verify_rerandomization::<Curve25519ChaumPedersen, _>(
+            &params, &other, &fresh, &proof
+        ));
+        assert!(!verify_rerandomization::<Curve25519ChaumPedersen, _>(
+            &params, &y, &other, &proof
+        ));
+
+        // Nor is a proof made with the wrong blinding factor accepted.
+        let wrong = Scalar::generate_random().unwrap();
+        let proof =
+            prove_rerandomization::<Curve25519ChaumPedersen, _>(&params, &y, &fresh, &wrong);
+        assert!(!verify_rerandomization::<Curve25519ChaumPedersen, _>(
+            &params, &y, &fresh, &proof
+        ));
+    }
+}
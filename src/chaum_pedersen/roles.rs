@@ -0,0 +1,149 @@
+//! # Prover and Verifier Roles Module
+//!
+//! This module splits the [`ChaumPedersen`] trait into the two roles of the protocol. A
+//! [`Prover`] computes commitments and responses from a secret, while a [`Verifier`] only issues
+//! challenges and checks responses. Deployments that only verify, such as an authentication
+//! server, can hold a `Verifier` so that prover operations, and the secrets they need, are not
+//! reachable from that code at all.
+
+use crate::chaum_pedersen::{ChaumPedersen, VerifyOutcome};
+
+/// The verifying side of a Chaum-Pedersen protocol.
+///
+/// A verifier never handles a secret: it has no access to the prover's operations.
+///
+/// ```
+/// use zk_pass::chaum_pedersen::constants::EC25519_GROUP_PARAMS;
+/// use zk_pass::chaum_pedersen::curve25519::Curve25519ChaumPedersen;
+/// use zk_pass::chaum_pedersen::roles::Verifier;
+///
+/// let verifier = Verifier::<Curve25519ChaumPedersen>::new(EC25519_GROUP_PARAMS.to_owned());
+/// let _c = verifier.challenge();
+/// ```
+///
+/// ```compile_fail,E0599
+/// use curve25519_dalek::Scalar;
+/// use zk_pass::chaum_pedersen::constants::EC25519_GROUP_PARAMS;
+/// use zk_pass::chaum_pedersen::curve25519::Curve25519ChaumPedersen;
+/// use zk_pass::chaum_pedersen::roles::Verifier;
+///
+/// let verifier = Verifier::<Curve25519ChaumPedersen>::new(EC25519_GROUP_PARAMS.to_owned());
+/// let _commitment = verifier.commitment(&Scalar::ONE);
+/// ```
+pub struct Verifier<C: ChaumPedersen> {
+    params: C::GroupParameters,
+}
+
+impl<C: ChaumPedersen> Verifier<C> {
+    /// Creates a verifier for the given group parameters.
+    ///
+    /// # Arguments
+    /// * `params` - The group parameters used in the protocol.
+    pub fn new(params: C::GroupParameters) -> Self {
+        Self { params }
+    }
+
+    /// Returns the group parameters used in the protocol.
+    pub fn params(&self) -> &C::GroupParameters {
+        &self.params
+    }
+
+    /// Generates a challenge for the prover, see [`ChaumPedersen::challenge`].
+    pub fn challenge(&self) -> C::Challenge {
+        C::challenge(&self.params)
+    }
+
+    /// Verifies the prover's response, see [`ChaumPedersen::verify`].
+    ///
+    /// # Arguments
+    /// * `s` - The prover's response to the challenge.
+    /// * `c` - The challenge issued to the prover.
+    /// * `cp` - The prover's commitment parameters.
+    ///
+    /// # Returns
+    /// `true` if the response proves knowledge of the secret, `false` otherwise.
+    pub fn verify(&self, s: &C::Response, c: &C::Challenge, cp: &C::CommitParameters) -> bool {
+        C::verify(&self.params, s, c, cp)
+    }
+
+    /// Verifies the prover's response, reporting which equation failed, see
+    /// [`ChaumPedersen::verify_detailed`].
+    ///
+    /// # Arguments
+    /// * `s` - The prover's response to the challenge.
+    /// * `c` - The challenge issued to the prover.
+    /// * `cp` - The prover's commitment parameters.
+    pub fn verify_detailed(
+        &self, s: &C::Response, c: &C::Challenge, cp: &C::CommitParameters,
+    ) -> VerifyOutcome {
+        C::verify_detailed(&self.params, s, c, cp)
+    }
+}
+
+/// The proving side of a Chaum-Pedersen protocol.
+pub struct Prover<C: ChaumPedersen> {
+    params: C::GroupParameters,
+}
+
+impl<C: ChaumPedersen> Prover<C> {
+    /// Creates a prover for the given group parameters.
+    ///
+    /// # Arguments
+    /// * `params` - The group parameters used in the protocol.
+    pub fn new(params: C::GroupParameters) -> Self {
+        Self { params }
+    }
+
+    /// Returns the group parameters used in the protocol.
+    pub fn params(&self) -> &C::GroupParameters {
+        &self.params
+    }
+
+    /// Commits to the secret, see [`ChaumPedersen::commitment`].
+    ///
+    /// # Arguments
+    /// * `x` - The prover's secret.
+    ///
+    /// # Returns
+    /// The commitment parameters to send to the verifier, and the commitment randomness to keep
+    /// for the response.
+    pub fn commitment(&self, x: &C::Secret) -> (C::CommitParameters, C::CommitmentRandom) {
+        C::commitment(&self.params, x)
+    }
+
+    /// Answers the verifier's challenge, see [`ChaumPedersen::challenge_response`].
+    ///
+    /// # Arguments
+    /// * `k` - The commitment randomness returned by [`Prover::commitment`].
+    /// * `c` - The verifier's challenge.
+    /// * `x` - The prover's secret.
+    pub fn challenge_response(
+        &self, k: &C::CommitmentRandom, c: &C::Challenge, x: &C::Secret,
+    ) -> C::Response {
+        C::challenge_response(&self.params, k, c, x)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chaum_pedersen::constants::EC25519_GROUP_PARAMS;
+    use crate::chaum_pedersen::curve25519::Curve25519ChaumPedersen;
+    use crate::rand::RandomGenerator;
+    use curve25519_dalek::Scalar;
+
+    #[test]
+    fn test_prover_and_verifier_roles() {
+        let prover = Prover::<Curve25519ChaumPedersen>::new(EC25519_GROUP_PARAMS.to_owned());
+        let verifier = Verifier::<Curve25519ChaumPedersen>::new(EC25519_GROUP_PARAMS.to_owned());
+        let x = Scalar::generate_random().unwrap();
+
+        let (cp, k) = prover.commitment(&x);
+        let c = verifier.challenge();
+        let s = prover.challenge_response(&k, &c, &x);
+        assert!(verifier.verify(&s, &c, &cp));
+
+        let wrong = prover.challenge_response(&k, &c, &Scalar::generate_random().unwrap());
+        assert!(!verifier.verify(&wrong, &c, &cp));
+    }
+}
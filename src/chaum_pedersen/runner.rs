@@ -0,0 +1,289 @@
+//! # Protocol Runner Module
+//!
+//! This module drives the prover's side of the interactive Chaum-Pedersen protocol — commitment,
+//! challenge, response and verification — independently of how messages reach the verifier.
+//!
+//! A [`ProtocolRunner`] computes the prover's values and exchanges them through a [`Transport`],
+//! so the same choreography runs over gRPC (see
+//! [`GrpcTransport`](crate::client::GrpcTransport)), against an [`InMemoryTransport`] or against
+//! a mock.
+
+use crate::chaum_pedersen::local::LocalAuthError;
+use crate::chaum_pedersen::ChaumPedersen;
+use crate::conversion::ProofData;
+use async_trait::async_trait;
+use uuid::Uuid;
+
+/// The channel a prover exchanges protocol messages with a verifier through.
+///
+/// A runner calls the methods once each, in order: `send_commitment`, `recv_challenge`,
+/// `send_response` and `recv_result`.
+///
+/// # Type Parameters
+/// - `T`: The type of the values in the commitment `(y1, y2, r1, r2)`.
+/// - `S`: The type of the challenge and the response.
+#[async_trait]
+pub trait Transport<T: Send + Sync, S: Send + Sync>: Send {
+    /// What the verifier grants a prover it accepts, e.g. a session ID.
+    type Session;
+    /// The error raised when an exchange fails or the verifier rejects the prover.
+    type Error;
+
+    /// Sends the prover's commitment to the verifier.
+    ///
+    /// # Arguments
+    /// * `user` - The name of the user to authenticate as.
+    /// * `commitment` - The commitment `(y1, y2, r1, r2)`.
+    async fn send_commitment(
+        &mut self, user: &str, commitment: &(T, T, T, T),
+    ) -> Result<(), Self::Error>;
+
+    /// Receives the challenge the prover must answer.
+    ///
+    /// # Returns
+    /// The challenge, as the prover must use it in its response.
+    async fn recv_challenge(&mut self) -> Result<S, Self::Error>;
+
+    /// Sends the prover's response to the challenge.
+    ///
+    /// # Arguments
+    /// * `s` - The response to the challenge.
+    async fn send_response(&mut self, s: &S) -> Result<(), Self::Error>;
+
+    /// Receives the verifier's verdict on the response.
+    ///
+    /// # Returns
+    /// The session granted to the prover, or an error if the verifier rejected it.
+    async fn recv_result(&mut self) -> Result<Self::Session, Self::Error>;
+}
+
+/// The outcome of a protocol run the verifier accepted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProtocolOutcome<Session, T, S> {
+    /// The session granted by the verifier.
+    pub session: Session,
+    /// Whether the prover's own check of its response against its commitment succeeded.
+    pub verified: bool,
+    /// The transcript `(y1, y2, r1, r2, c, s)` of the run.
+    pub proof: ProofData<T, S>,
+}
+
+/// Runs the prover's side of the protocol over any [`Transport`].
+///
+/// # Type Parameters
+/// * `C` - The Chaum-Pedersen protocol to run.
+pub struct ProtocolRunner<'a, C: ChaumPedersen> {
+    params: &'a C::GroupParameters,
+}
+
+impl<'a, C, T, S> ProtocolRunner<'a, C>
+where
+    C: ChaumPedersen<CommitParameters = (T, T, T, T), Challenge = S, Response = S>,
+    T: Clone + Send + Sync,
+    S: Send + Sync,
+{
+    /// Creates a runner for the given group parameters.
+    ///
+    /// # Arguments
+    /// * `params` - Group parameters used in the protocol.
+    pub fn new(params: &'a C::GroupParameters) -> Self {
+        Self { params }
+    }
+
+    /// Proves knowledge of `x` to the verifier at the other end of the transport.
+    ///
+    /// # Arguments
+    /// * `transport` - The channel to the verifier.
+    /// * `user` - The name of the user to authenticate as.
+    /// * `x` - The user's secret.
+    ///
+    /// # Returns
+    /// The session granted by the verifier, or the transport's error if an exchange fails or the
+    /// verifier rejects the proof.
+    pub async fn run<Tr: Transport<T, S>>(
+        &self, transport: &mut Tr, user: &str, x: &C::Secret,
+    ) -> Result<ProtocolOutcome<Tr::Session, T, S>, Tr::Error> {
+        // Commits to fresh randomness and sends the commitment.
+        let (cp, k) = C::commitment(self.params, x);
+        transport.send_commitment(user, &cp).await?;
+
+        // Answers the verifier's challenge.
+        let c = transport.recv_challenge().await?;
+        let s = C::challenge_response(self.params, &k, &c, x);
+        transport.send_response(&s).await?;
+        let session = transport.recv_result().await?;
+
+        // Checks the response locally against the commitment, as the verifier did.
+        let verified = C::verify(self.params, &s, &c, &cp);
+        let (y1, y2, r1, r2) = cp;
+        Ok(ProtocolOutcome {
+            session,
+            verified,
+            proof: ProofData {
+                y1,
+                y2,
+                r1,
+                r2,
+                c,
+                s,
+            },
+        })
+    }
+}
+
+/// A [`Transport`] to a verifier simulated in the same process, e.g. for tests.
+///
+/// The verifier accepts the user's first commitment as its registration, issues a fresh random
+/// challenge and grants a random session ID if the response verifies.
+pub struct InMemoryTransport<C: ChaumPedersen> {
+    params: C::GroupParameters,
+    commitment: Option<C::CommitParameters>,
+    challenge: Option<C::Challenge>,
+    verified: Option<bool>,
+}
+
+impl<C: ChaumPedersen> InMemoryTransport<C> {
+    /// Creates a transport to a verifier using the given group parameters.
+    ///
+    /// # Arguments
+    /// * `params` - Group parameters used in the protocol.
+    pub fn new(params: C::GroupParameters) -> Self {
+        Self {
+            params,
+            commitment: None,
+            challenge: None,
+            verified: None,
+        }
+    }
+}
+
+#[async_trait]
+impl<C, T, S> Transport<T, S> for InMemoryTransport<C>
+where
+    C: ChaumPedersen<CommitParameters = (T, T, T, T), Challenge = S, Response = S>,
+    C::GroupParameters: Send + Sync,
+    T: Clone + Send + Sync,
+    S: Clone + Send + Sync,
+{
+    type Session = String;
+    type Error = LocalAuthError;
+
+    async fn send_commitment(
+        &mut self, _user: &str, commitment: &(T, T, T, T),
+    ) -> Result<(), LocalAuthError> {
+        self.commitment = Some(commitment.clone());
+        Ok(())
+    }
+
+    async fn recv_challenge(&mut self) -> Result<S, LocalAuthError> {
+        let c = C::challenge(&self.params);
+        self.challenge = Some(c.clone());
+        Ok(c)
+    }
+
+    async fn send_response(&mut self, s: &S) -> Result<(), LocalAuthError> {
+        let (Some(cp), Some(c)) = (&self.commitment, self.challenge.take()) else {
+            return Err(LocalAuthError::ChallengeNotFound);
+        };
+        self.verified = Some(C::verify(&self.params, s, &c, cp));
+        Ok(())
+    }
+
+    async fn recv_result(&mut self) -> Result<String, LocalAuthError> {
+        match self.verified.take() {
+            Some(true) => Ok(Uuid::new_v4().to_string()),
+            Some(false) => Err(LocalAuthError::VerificationFailed),
+            None => Err(LocalAuthError::ChallengeNotFound),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chaum_pedersen::constants::{
+        EC25519_GROUP_PARAMS, RFC5114_MODP_2048_256_BIT_PARAMS,
+    };
+    use crate::chaum_pedersen::curve25519::Curve25519ChaumPedersen;
+    use crate::chaum_pedersen::discretelog::DiscreteLogChaumPedersen;
+    use crate::rand::RandomGenerator;
+    use curve25519_dalek::{RistrettoPoint, Scalar};
+    use num_bigint::RandBigInt;
+    use rand::rngs::OsRng;
+
+    /// A transport that corrupts the prover's response on its way to the verifier.
+    struct TamperingTransport(InMemoryTransport<Curve25519ChaumPedersen>);
+
+    #[async_trait]
+    impl Transport<RistrettoPoint, Scalar> for TamperingTransport {
+        type Session = String;
+        type Error = LocalAuthError;
+
+        async fn send_commitment(
+            &mut self, user: &str,
+            commitment: &(RistrettoPoint, RistrettoPoint, RistrettoPoint, RistrettoPoint),
+        ) -> Result<(), LocalAuthError> {
+            self.0.send_commitment(user, commitment).await
+        }
+
+        async fn recv_challenge(&mut self) -> Result<Scalar, LocalAuthError> {
+            self.0.recv_challenge().await
+        }
+
+        async fn send_response(&mut self, s: &Scalar) -> Result<(), LocalAuthError> {
+            self.0.send_response(&(s + Scalar::ONE)).await
+        }
+
+        async fn recv_result(&mut self) -> Result<String, LocalAuthError> {
+            self.0.recv_result().await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_run_discrete_log() {
+        let params = RFC5114_MODP_2048_256_BIT_PARAMS.to_owned();
+        let x = OsRng.gen_biguint_below(&params.q);
+        let mut transport = InMemoryTransport::<DiscreteLogChaumPedersen>::new(params.clone());
+        let outcome = ProtocolRunner::<DiscreteLogChaumPedersen>::new(&params)
+            .run(&mut transport, "alice", &x)
+            .await
+            .unwrap();
+        assert!(outcome.verified);
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_run_curve25519() {
+        let params = EC25519_GROUP_PARAMS.to_owned();
+        let x = Scalar::generate_random().unwrap();
+        let mut transport = InMemoryTransport::<Curve25519ChaumPedersen>::new(params);
+        let outcome = ProtocolRunner::<Curve25519ChaumPedersen>::new(&params)
+            .run(&mut transport, "alice", &x)
+            .await
+            .unwrap();
+        assert!(outcome.verified);
+        assert!(!outcome.session.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_tampered_response_rejected() {
+        let params = EC25519_GROUP_PARAMS.to_owned();
+        let x = Scalar::generate_random().unwrap();
+        let mut transport = TamperingTransport(InMemoryTransport::new(params));
+        let error = ProtocolRunner::<Curve25519ChaumPedersen>::new(&params)
+            .run(&mut transport, "alice", &x)
+            .await
+            .unwrap_err();
+        assert_eq!(error, LocalAuthError::VerificationFailed);
+    }
+
+    #[tokio::test]
+    async fn test_response_without_challenge_rejected() {
+        let mut transport =
+            InMemoryTransport::<Curve25519ChaumPedersen>::new(EC25519_GROUP_PARAMS.to_owned());
+        let s = Scalar::generate_random().unwrap();
+        assert_eq!(
+            Transport::<RistrettoPoint, Scalar>::send_response(&mut transport, &s).await,
+            Err(LocalAuthError::ChallengeNotFound)
+        );
+    }
+}
@@ -0,0 +1,157 @@
+//! # Proof Statistics Module
+//!
+//! This module reports how large the serialized components of a Chaum-Pedersen proof are and,
+//! with the `std` feature, how long each step of the protocol takes. Both are computed by running
+//! the protocol once with the existing [`ChaumPedersen`] methods, which makes them useful for
+//! documentation and capacity planning across backends.
+
+use crate::chaum_pedersen::ChaumPedersen;
+use crate::conversion::ByteConvertible;
+#[cfg(feature = "std")]
+use std::time::{Duration, Instant};
+
+/// The encoded sizes, in bytes, of the components of a proof.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ProofStats {
+    /// The sizes of the commitment parameters `y1`, `y2`, `r1` and `r2`.
+    pub commitment: [usize; 4],
+    /// The size of the challenge.
+    pub challenge: usize,
+    /// The size of the response.
+    pub response: usize,
+}
+
+impl ProofStats {
+    /// Runs the protocol once and measures the encoded size of each proof component.
+    ///
+    /// Discrete log values are encoded minimally, so their sizes may fall short of the
+    /// modulus width when a value has leading zero bytes.
+    ///
+    /// # Arguments
+    /// * `params` - The group parameters used in the protocol.
+    /// * `x` - The secret to prove knowledge of.
+    ///
+    /// # Returns
+    /// The sizes of the commitment, challenge and response as produced by `ByteConvertible`.
+    pub fn measure<C, T, S>(params: &C::GroupParameters, x: &C::Secret) -> Self
+    where
+        C: ChaumPedersen<CommitParameters = (T, T, T, T), Challenge = S, Response = S>,
+        T: ByteConvertible<T>,
+        S: ByteConvertible<S>,
+    {
+        let ((y1, y2, r1, r2), k) = C::commitment(params, x);
+        let c = C::challenge(params);
+        let s = C::challenge_response(params, &k, &c, x);
+        Self {
+            commitment: [y1, y2, r1, r2].map(|v| T::convert_to(&v).len()),
+            challenge: S::convert_to(&c).len(),
+            response: S::convert_to(&s).len(),
+        }
+    }
+
+    /// Returns the total size of the proof, in bytes.
+    pub fn total(&self) -> usize {
+        self.commitment.iter().sum::<usize>() + self.challenge + self.response
+    }
+}
+
+/// The time taken by each step of one protocol run.
+#[cfg(feature = "std")]
+#[derive(Copy, Clone, Debug)]
+pub struct Timings {
+    /// The time taken to compute the commitment.
+    pub commitment: Duration,
+    /// The time taken to generate the challenge.
+    pub challenge: Duration,
+    /// The time taken to compute the response.
+    pub response: Duration,
+    /// The time taken to verify the response.
+    pub verification: Duration,
+}
+
+/// Runs the protocol once, timing each step.
+///
+/// # Arguments
+/// * `params` - The group parameters used in the protocol.
+/// * `x` - The secret to prove knowledge of.
+///
+/// # Returns
+/// The time taken by the commitment, challenge, response and verification steps.
+///
+/// # Panics
+/// Panics if the proof fails to verify, since the timings of a broken run are meaningless.
+#[cfg(feature = "std")]
+pub fn measure_protocol<T: ChaumPedersen>(params: &T::GroupParameters, x: &T::Secret) -> Timings {
+    let start = Instant::now();
+    let (cp, k) = T::commitment(params, x);
+    let commitment = start.elapsed();
+
+    let start = Instant::now();
+    let c = T::challenge(params);
+    let challenge = start.elapsed();
+
+    let start = Instant::now();
+    let s = T::challenge_response(params, &k, &c, x);
+    let response = start.elapsed();
+
+    let start = Instant::now();
+    assert!(T::verify(params, &s, &c, &cp), "proof failed to verify");
+    let verification = start.elapsed();
+
+    Timings {
+        commitment,
+        challenge,
+        response,
+        verification,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chaum_pedersen::constants::{
+        EC25519_GROUP_PARAMS, RFC5114_MODP_2048_256_BIT_PARAMS,
+    };
+    use crate::chaum_pedersen::curve25519::Curve25519ChaumPedersen;
+    use crate::chaum_pedersen::discretelog::DiscreteLogChaumPedersen;
+    use crate::rand::RandomGenerator;
+    use curve25519_dalek::Scalar;
+    use num_bigint::RandBigInt;
+    use rand_core::OsRng;
+
+    #[test]
+    fn test_ristretto_components_are_32_bytes() {
+        let params = EC25519_GROUP_PARAMS.to_owned();
+        let x = Scalar::generate_random().unwrap();
+        let stats = ProofStats::measure::<Curve25519ChaumPedersen, _, _>(&params, &x);
+        assert_eq!(stats.commitment, [32; 4]);
+        assert_eq!(stats.challenge, 32);
+        assert_eq!(stats.response, 32);
+        assert_eq!(stats.total(), 6 * 32);
+    }
+
+    #[test]
+    fn test_discrete_log_components_match_modulus_width() {
+        let params = RFC5114_MODP_2048_256_BIT_PARAMS.to_owned();
+        let x = OsRng.gen_biguint_below(&params.q);
+        let stats = ProofStats::measure::<DiscreteLogChaumPedersen, _, _>(&params, &x);
+
+        // Minimal encodings drop leading zero bytes, so allow a little slack below the width.
+        let p_len = params.p.to_bytes_be().len();
+        let q_len = params.q.to_bytes_be().len();
+        assert_eq!((p_len, q_len), (256, 32));
+        for len in stats.commitment {
+            assert!(len <= p_len && len + 2 >= p_len, "commitment of {} bytes", len);
+        }
+        assert!(stats.challenge <= q_len && stats.challenge + 2 >= q_len);
+        assert!(stats.response <= q_len && stats.response + 2 >= q_len);
+    }
+
+    #[test]
+    fn test_measure_protocol() {
+        let params = EC25519_GROUP_PARAMS.to_owned();
+        let x = Scalar::generate_random().unwrap();
+        let timings = measure_protocol::<Curve25519ChaumPedersen>(&params, &x);
+        assert!(timings.verification > Duration::ZERO);
+    }
+}
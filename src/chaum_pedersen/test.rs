@@ -55,7 +55,7 @@ where
 
     // The client calculates the response based on the commitment random, challenge,
     // and their secret.
-    let s = T::challenge_response(params, &k, &c, &x);
+    let s = T::challenge_response(params, &k, &c, x);
 
     // The server (simulated here) verifies the response against the challenge and
     // commitment parameters.
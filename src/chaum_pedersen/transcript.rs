@@ -0,0 +1,368 @@
+//! # Transcript-Based Challenge Derivation
+//!
+//! This module derives Chaum-Pedersen challenges from a Merlin transcript, turning the
+//! interactive protocol into a non-interactive one via the Fiat-Shamir transform.
+//! The transcript absorbs a domain separator, the group parameters and every commitment
+//! point before the challenge is squeezed out, so a proof made for one context can never
+//! be replayed in another.
+
+use crate::chaum_pedersen::{ChaumPedersen, GroupParams};
+use crate::conversion::ByteConvertible;
+use merlin::Transcript;
+
+/// A challenge type that can be squeezed out of a Merlin transcript.
+///
+/// # Type Parameters
+/// * `P` - The group parameters the challenge is derived for.
+pub trait TranscriptChallenge<P> {
+    /// Squeezes a challenge out of the transcript.
+    ///
+    /// # Arguments
+    /// * `transcript` - The transcript that has absorbed all protocol messages so far.
+    /// * `params` - The group parameters, used to reduce the challenge into range.
+    ///
+    /// # Returns
+    /// The challenge value bound to everything appended to the transcript.
+    fn from_transcript(transcript: &mut Transcript, params: &P) -> Self;
+
+    /// Reduces the challenge modulo `2^challenge_bits`, for services that issue short
+    /// challenges.
+    ///
+    /// # Arguments
+    /// * `challenge_bits` - The bit length of the shortened challenge.
+    ///
+    /// # Returns
+    /// The shortened challenge, or `None` if challenges of this type cannot be shortened, as
+    /// only discrete log challenges can.
+    fn shorten(&self, _challenge_bits: u32) -> Option<Self>
+    where
+        Self: Sized,
+    {
+        None
+    }
+}
+
+/// Builds a transcript that has absorbed the domain separator, group parameters and commitment.
+///
+/// # Arguments
+/// * `domain_separator` - A label identifying the application context of the proof.
+/// * `params` - The group parameters used in the protocol.
+/// * `cp` - The commitment parameters (`y1`, `y2`, `r1`, `r2`).
+///
+/// # Returns
+/// A transcript ready for further messages to be appended or a challenge to be squeezed out.
+pub(crate) fn commitment_transcript<T>(
+    domain_separator: &'static [u8], params: &GroupParams<T>, cp: &(T, T, T, T),
+) -> Transcript
+where
+    T: ByteConvertible<T>,
+{
+    let (y1, y2, r1, r2) = cp;
+    let mut transcript = Transcript::new(b"zk_pass.chaum_pedersen");
+    transcript.append_message(b"dom-sep", domain_separator);
+    transcript.append_message(b"g", &T::convert_to(&params.g));
+    transcript.append_message(b"h", &T::convert_to(&params.h));
+    transcript.append_message(b"p", &T::convert_to(&params.p));
+    transcript.append_message(b"q", &T::convert_to(&params.q));
+    transcript.append_message(b"y1", &T::convert_to(y1));
+    transcript.append_message(b"y2", &T::convert_to(y2));
+    transcript.append_message(b"r1", &T::convert_to(r1));
+    transcript.append_message(b"r2", &T::convert_to(r2));
+    transcript
+}
+
+/// Derives the challenge for a commitment from a Merlin transcript.
+///
+/// # Arguments
+/// * `domain_separator` - A label identifying the application context of the proof.
+/// * `params` - The group parameters used in the protocol.
+/// * `cp` - The commitment parameters (`y1`, `y2`, `r1`, `r2`).
+///
+/// # Returns
+/// The challenge bound to the domain separator, group parameters and commitment.
+pub fn derive_challenge<T, S>(
+    domain_separator: &'static [u8], params: &GroupParams<T>, cp: &(T, T, T, T),
+) -> S
+where
+    T: ByteConvertible<T>,
+    S: TranscriptChallenge<GroupParams<T>>,
+{
+    let mut transcript = commitment_transcript(domain_separator, params, cp);
+    S::from_transcript(&mut transcript, params)
+}
+
+/// Derives the challenge for a commitment, additionally bound to caller-supplied context.
+///
+/// Binding a verifier-chosen nonce into the challenge makes the resulting proof fresh, so it
+/// cannot be replayed to a verifier that picked a different nonce.
+///
+/// # Arguments
+/// * `domain_separator` - A label identifying the application context of the proof.
+/// * `params` - The group parameters used in the protocol.
+/// * `cp` - The commitment parameters (`y1`, `y2`, `r1`, `r2`).
+/// * `context` - Additional bytes the challenge is bound to, such as a nonce.
+///
+/// # Returns
+/// The challenge bound to the domain separator, group parameters, commitment and context.
+pub fn derive_bound_challenge<T, S>(
+    domain_separator: &'static [u8], params: &GroupParams<T>, cp: &(T, T, T, T), context: &[u8],
+) -> S
+where
+    T: ByteConvertible<T>,
+    S: TranscriptChallenge<GroupParams<T>>,
+{
+    let mut transcript = commitment_transcript(domain_separator, params, cp);
+    transcript.append_message(b"context", context);
+    S::from_transcript(&mut transcript, params)
+}
+
+/// Derives the effective challenge of an interactive authentication from the server's nonce
+/// and its random challenge.
+///
+/// Hashing a fresh nonce into the challenge binds a response to the authentication attempt it
+/// was issued for, so a `(c, s)` pair from one session is useless in another even if `c` repeats.
+///
+/// # Arguments
+/// * `params` - The group parameters used in the protocol.
+/// * `nonce` - The nonce the server issued with the challenge.
+/// * `c` - The random challenge the server issued.
+///
+/// # Returns
+/// The challenge the prover must answer and the verifier must check the response against.
+pub fn derive_nonce_challenge<T, S>(params: &GroupParams<T>, nonce: &[u8], c: &S) -> S
+where
+    S: ByteConvertible<S> + TranscriptChallenge<GroupParams<T>>,
+{
+    let mut transcript = Transcript::new(b"zk_pass.chaum_pedersen");
+    transcript.append_message(b"dom-sep", b"zk_pass.challenge_nonce");
+    transcript.append_message(b"n", nonce);
+    transcript.append_message(b"c", &S::convert_to(c));
+    S::from_transcript(&mut transcript, params)
+}
+
+/// Derives the effective challenge of an interactive authentication like
+/// [`derive_nonce_challenge`], shortened if the server issues short challenges.
+///
+/// # Arguments
+/// * `params` - The group parameters used in the protocol.
+/// * `nonce` - The nonce the server issued with the challenge.
+/// * `c` - The random challenge the server issued.
+/// * `challenge_bits` - The bit length of the effective challenge the server reported, or 0
+///   for the full group order.
+///
+/// # Returns
+/// The challenge the prover must answer and the verifier must check the response against, or
+/// `None` if the server shortens challenges of a type that cannot be shortened.
+pub fn derive_sized_nonce_challenge<T, S>(
+    params: &GroupParams<T>, nonce: &[u8], c: &S, challenge_bits: u32,
+) -> Option<S>
+where
+    S: ByteConvertible<S> + TranscriptChallenge<GroupParams<T>>,
+{
+    let challenge = derive_nonce_challenge(params, nonce, c);
+    match challenge_bits {
+        0 => Some(challenge),
+        bits => challenge.shorten(bits),
+    }
+}
+
+/// Derives a random challenge from local randomness mixed with the output of a public
+/// randomness beacon, so that the challenge has entropy from both the server and the beacon.
+///
+/// # Arguments
+/// * `params` - The group parameters used in the protocol.
+/// * `local` - Randomness generated by the server.
+/// * `beacon` - Randomness fetched from the beacon, e.g. a drand round.
+///
+/// # Returns
+/// The random challenge, uniform as long as either input is.
+pub fn derive_beacon_challenge<T, S>(
+    params: &GroupParams<T>, local: &[u8; 32], beacon: &[u8; 32],
+) -> S
+where
+    S: TranscriptChallenge<GroupParams<T>>,
+{
+    let mut seed = *local;
+    seed.iter_mut().zip(beacon).for_each(|(l, b)| *l ^= b);
+    let mut transcript = Transcript::new(b"zk_pass.chaum_pedersen");
+    transcript.append_message(b"dom-sep", b"zk_pass.beacon_challenge");
+    transcript.append_message(b"seed", &seed);
+    S::from_transcript(&mut transcript, params)
+}
+
+/// Creates a non-interactive proof of knowledge of `x` using the Fiat-Shamir transform.
+///
+/// # Arguments
+/// * `domain_separator` - A label identifying the application context of the proof.
+/// * `params` - The group parameters used in the protocol.
+/// * `x` - The secret value to prove knowledge of.
+///
+/// # Returns
+/// A tuple containing the commitment parameters and the response to the derived challenge.
+pub fn prove<C, T, S>(
+    domain_separator: &'static [u8], params: &GroupParams<T>, x: &C::Secret,
+) -> ((T, T, T, T), S)
+where
+    C: ChaumPedersen<
+        GroupParameters = GroupParams<T>,
+        CommitParameters = (T, T, T, T),
+        Challenge = S,
+        Response = S,
+    >,
+    T: ByteConvertible<T>,
+    S: TranscriptChallenge<GroupParams<T>>,
+{
+    let (cp, k) = C::commitment(params, x);
+    let c = derive_challenge(domain_separator, params, &cp);
+    let s = C::challenge_response(params, &k, &c, x);
+    (cp, s)
+}
+
+/// Verifies a non-interactive proof created by [`prove`].
+///
+/// # Arguments
+/// * `domain_separator` - The label the proof must have been created for.
+/// * `params` - The group parameters used in the protocol.
+/// * `cp` - The commitment parameters (`y1`, `y2`, `r1`, `r2`).
+/// * `s` - The response to the derived challenge.
+///
+/// # Returns
+/// `true` if the proof is valid for the given domain separator, `false` otherwise.
+pub fn verify<C, T, S>(
+    domain_separator: &'static [u8], params: &GroupParams<T>, cp: &(T, T, T, T), s: &S,
+) -> bool
+where
+    C: ChaumPedersen<
+        GroupParameters = GroupParams<T>,
+        CommitParameters = (T, T, T, T),
+        Challenge = S,
+        Response = S,
+    >,
+    T: ByteConvertible<T>,
+    S: TranscriptChallenge<GroupParams<T>>,
+{
+    let c = derive_challenge(domain_separator, params, cp);
+    C::verify(params, s, &c, cp)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chaum_pedersen::constants::{
+        EC25519_GROUP_PARAMS, PALLAS_GROUP_PARAMS, RFC5114_MODP_2048_256_BIT_PARAMS,
+        VESTA_GROUP_PARAMS,
+    };
+    use crate::chaum_pedersen::curve25519::Curve25519ChaumPedersen;
+    use crate::chaum_pedersen::discretelog::DiscreteLogChaumPedersen;
+    use crate::chaum_pedersen::pallas::PallasCurveChaumPedersen;
+    use crate::chaum_pedersen::vesta::VestaCurveChaumPedersen;
+    use crate::rand::RandomGenerator;
+    use curve25519_dalek::Scalar;
+    use num_bigint::BigUint;
+
+    #[test]
+    fn test_beacon_challenge_mixes_both_inputs() {
+        let params = RFC5114_MODP_2048_256_BIT_PARAMS.to_owned();
+        let c: BigUint = derive_beacon_challenge(&params, &[1; 32], &[2; 32]);
+        assert!(c < params.q);
+        assert_eq!(c, derive_beacon_challenge(&params, &[1; 32], &[2; 32]));
+        assert_ne!(c, derive_beacon_challenge(&params, &[3; 32], &[2; 32]));
+        assert_ne!(c, derive_beacon_challenge(&params, &[1; 32], &[3; 32]));
+    }
+
+    #[test]
+    fn test_domain_separators_produce_different_challenges() {
+        let params = EC25519_GROUP_PARAMS.to_owned();
+        let x = Scalar::generate_random().unwrap();
+        let (cp, _) = Curve25519ChaumPedersen::commitment(&params, &x);
+
+        let c1: Scalar = derive_challenge(b"login", &params, &cp);
+        let c2: Scalar = derive_challenge(b"signup", &params, &cp);
+        let c3: Scalar = derive_challenge(b"login", &params, &cp);
+        assert_ne!(c1, c2);
+        assert_eq!(c1, c3);
+    }
+
+    #[test]
+    fn test_bound_challenges_depend_on_context() {
+        let params = EC25519_GROUP_PARAMS.to_owned();
+        let x = Scalar::generate_random().unwrap();
+        let (cp, _) = Curve25519ChaumPedersen::commitment(&params, &x);
+
+        let c1: Scalar = derive_bound_challenge(b"login", &params, &cp, b"nonce one");
+        let c2: Scalar = derive_bound_challenge(b"login", &params, &cp, b"nonce two");
+        let c3: Scalar = derive_challenge(b"login", &params, &cp);
+        assert_ne!(c1, c2);
+        assert_ne!(c1, c3);
+    }
+
+    #[test]
+    fn test_nonce_challenges_depend_on_nonce() {
+        let params = EC25519_GROUP_PARAMS.to_owned();
+        let c = Scalar::generate_random().unwrap();
+
+        let c1: Scalar = derive_nonce_challenge(&params, b"nonce one", &c);
+        let c2: Scalar = derive_nonce_challenge(&params, b"nonce two", &c);
+        let c3: Scalar = derive_nonce_challenge(&params, b"nonce one", &c);
+        assert_ne!(c1, c2);
+        assert_ne!(c1, c);
+        assert_eq!(c1, c3);
+    }
+
+    #[test]
+    fn test_sized_nonce_challenges() {
+        let params = RFC5114_MODP_2048_256_BIT_PARAMS.to_owned();
+        let c = BigUint::generate_random().unwrap();
+        let full: BigUint = derive_nonce_challenge(&params, b"nonce", &c);
+        let short = derive_sized_nonce_challenge(&params, b"nonce", &c, 128).unwrap();
+        assert_eq!(short, &full % (BigUint::from(1u8) << 128));
+        assert_eq!(derive_sized_nonce_challenge(&params, b"nonce", &c, 0), Some(full));
+
+        let params = EC25519_GROUP_PARAMS.to_owned();
+        let c = Scalar::generate_random().unwrap();
+        assert!(derive_sized_nonce_challenge(&params, b"nonce", &c, 128).is_none());
+    }
+
+    #[test]
+    fn test_discrete_log_domain_separators_produce_different_challenges() {
+        let params = RFC5114_MODP_2048_256_BIT_PARAMS.to_owned();
+        let x = BigUint::generate_random().unwrap();
+        let (cp, _) = DiscreteLogChaumPedersen::commitment(&params, &x);
+
+        let c1: BigUint = derive_challenge(b"login", &params, &cp);
+        let c2: BigUint = derive_challenge(b"signup", &params, &cp);
+        assert_ne!(c1, c2);
+        assert!(c1 < params.q && c2 < params.q);
+    }
+
+    #[test]
+    fn test_non_interactive_proofs() {
+        let params = RFC5114_MODP_2048_256_BIT_PARAMS.to_owned();
+        let x = BigUint::generate_random().unwrap();
+        let (cp, s) = prove::<DiscreteLogChaumPedersen, _, _>(b"test", &params, &x);
+        assert!(verify::<DiscreteLogChaumPedersen, _, _>(b"test", &params, &cp, &s));
+
+        let params = EC25519_GROUP_PARAMS.to_owned();
+        let x = Scalar::generate_random().unwrap();
+        let (cp, s) = prove::<Curve25519ChaumPedersen, _, _>(b"test", &params, &x);
+        assert!(verify::<Curve25519ChaumPedersen, _, _>(b"test", &params, &cp, &s));
+
+        let params = PALLAS_GROUP_PARAMS.to_owned();
+        let x = pasta_curves::pallas::Scalar::generate_random().unwrap();
+        let (cp, s) = prove::<PallasCurveChaumPedersen, _, _>(b"test", &params, &x);
+        assert!(verify::<PallasCurveChaumPedersen, _, _>(b"test", &params, &cp, &s));
+
+        let params = VESTA_GROUP_PARAMS.to_owned();
+        let x = pasta_curves::vesta::Scalar::generate_random().unwrap();
+        let (cp, s) = prove::<VestaCurveChaumPedersen, _, _>(b"test", &params, &x);
+        assert!(verify::<VestaCurveChaumPedersen, _, _>(b"test", &params, &cp, &s));
+    }
+
+    #[test]
+    fn test_proof_rejected_under_other_domain_separator() {
+        let params = EC25519_GROUP_PARAMS.to_owned();
+        let x = Scalar::generate_random().unwrap();
+        let (cp, s) = prove::<Curve25519ChaumPedersen, _, _>(b"login", &params, &x);
+        assert!(!verify::<Curve25519ChaumPedersen, _, _>(b"signup", &params, &cp, &s));
+    }
+}
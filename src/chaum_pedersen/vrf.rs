@@ -0,0 +1,249 @@
+//! # Verifiable Random Function Challenges
+//!
+//! This module derives challenges for the discrete log backend from a verifiable random
+//! function (VRF), following the construction of the IETF ECVRF draft over the order-`q`
+//! subgroup modulo `p`. The server hashes the input into the subgroup as `H`, raises it to its
+//! secret `x` and publishes `gamma = H^x` along with a Chaum-Pedersen proof that
+//! `log_g(y) = log_H(gamma)` for its public key `y = g^x`. The challenge is a hash of `gamma`.
+//!
+//! Given the server's public key, anyone can check that a challenge is the unique VRF output for
+//! an input, so a client can later prove that its challenge was generated fairly rather than
+//! picked by the server.
+
+use crate::chaum_pedersen::discretelog::DiscreteLogChaumPedersen;
+use crate::chaum_pedersen::transcript::{derive_bound_challenge, TranscriptChallenge};
+use crate::chaum_pedersen::{ChaumPedersen, GroupParams, ValidateGroupParams};
+use crate::conversion::ByteConvertible;
+use alloc::vec::Vec;
+use merlin::Transcript;
+use num_bigint::BigUint;
+use num_traits::One;
+
+/// The domain separator of the proof that `gamma` was computed with the server's secret.
+const PROOF_DOMAIN_SEPARATOR: &[u8] = b"zk_pass.vrf";
+
+/// A proof that a VRF output was computed with the secret behind a public key.
+///
+/// # Fields
+/// - `gamma`: The input hashed into the group, raised to the secret.
+/// - `c`: The challenge of the Chaum-Pedersen proof.
+/// - `s`: The response of the Chaum-Pedersen proof.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VrfProof {
+    pub gamma: BigUint,
+    pub c: BigUint,
+    pub s: BigUint,
+}
+
+/// Builds the input a challenge is derived from for an authentication.
+///
+/// Each part is prefixed with its length, so that different parts never concatenate to the same
+/// input.
+///
+/// # Arguments
+/// * `user` - The name of the user authenticating.
+/// * `r1` - The user's first commitment.
+/// * `r2` - The user's second commitment.
+/// * `nonce` - A nonce chosen by the server.
+///
+/// # Returns
+/// The VRF input binding the challenge to the user, its commitment and the nonce.
+pub fn vrf_input(user: &str, r1: &BigUint, r2: &BigUint, nonce: &[u8]) -> Vec<u8> {
+    let mut input = Vec::new();
+    for part in [user.as_bytes(), &r1.to_bytes_be(), &r2.to_bytes_be(), nonce] {
+        input.extend_from_slice(&(part.len() as u64).to_be_bytes());
+        input.extend_from_slice(part);
+    }
+    input
+}
+
+/// Generates a challenge as the VRF output for an input, along with the proof that it is.
+///
+/// The proof is deterministic: the same secret and input always yield the same challenge and
+/// proof.
+///
+/// # Arguments
+/// * `params` - The group parameters of the discrete log backend.
+/// * `server_secret` - The server's VRF secret `x`, below `q`.
+/// * `input` - The VRF input, see [`vrf_input`].
+///
+/// # Returns
+/// The challenge, below `q`, and the proof for it.
+pub fn vrf_challenge(
+    params: &GroupParams<BigUint>, server_secret: &BigUint, input: &[u8],
+) -> (BigUint, VrfProof) {
+    let vrf_params = hashed_params(params, input);
+    let y = params.g.modpow(server_secret, &params.p);
+    let gamma = vrf_params.h.modpow(server_secret, &params.p);
+
+    let k = nonce(&vrf_params, server_secret);
+    let r1 = params.g.modpow(&k, &params.p);
+    let r2 = vrf_params.h.modpow(&k, &params.p);
+    let c: BigUint = derive_bound_challenge(
+        PROOF_DOMAIN_SEPARATOR,
+        &vrf_params,
+        &(y, gamma.clone(), r1, r2),
+        input,
+    );
+    let s = DiscreteLogChaumPedersen::challenge_response(&vrf_params, &k, &c, server_secret);
+
+    (output(params, &gamma), VrfProof { gamma, c, s })
+}
+
+/// Verifies that a challenge is the VRF output for an input under the server's public key.
+///
+/// # Arguments
+/// * `params` - The group parameters of the discrete log backend.
+/// * `server_public` - The server's VRF public key `g^x`.
+/// * `input` - The VRF input, see [`vrf_input`].
+/// * `output` - The challenge to verify.
+/// * `proof` - The proof returned along with the challenge.
+///
+/// # Returns
+/// `true` if `output` is the VRF output for `input`, `false` otherwise.
+pub fn vrf_verify(
+    params: &GroupParams<BigUint>, server_public: &BigUint, input: &[u8], output: &BigUint,
+    proof: &VrfProof,
+) -> bool {
+    if !BigUint::is_valid_group_element(params, server_public)
+        || !BigUint::is_valid_group_element(params, &proof.gamma)
+        || proof.c >= params.q
+        || proof.s >= params.q
+    {
+        return false;
+    }
+
+    // Recomputes the commitment from the response, as in `g^s * y^c = g^k`.
+    let vrf_params = hashed_params(params, input);
+    let p = &params.p;
+    let r1 = params.g.modpow(&proof.s, p) * server_public.modpow(&proof.c, p) % p;
+    let r2 = vrf_params.h.modpow(&proof.s, p) * proof.gamma.modpow(&proof.c, p) % p;
+    let cp = (server_public.clone(), proof.gamma.clone(), r1, r2);
+    let c: BigUint = derive_bound_challenge(PROOF_DOMAIN_SEPARATOR, &vrf_params, &cp, input);
+
+    c == proof.c
+        && DiscreteLogChaumPedersen::verify(&vrf_params, &proof.s, &c, &cp)
+        && *output == self::output(params, &proof.gamma)
+}
+
+/// Replaces `h` in the group parameters with the input hashed into the order-`q` subgroup.
+///
+/// Hashes to `p` plus 128 bits, reduced modulo `p` and raised to the cofactor `(p - 1) / q`. A
+/// counter is hashed in and incremented in the negligible case of landing on the identity.
+fn hashed_params(params: &GroupParams<BigUint>, input: &[u8]) -> GroupParams<BigUint> {
+    let cofactor = (&params.p - BigUint::one()) / &params.q;
+    let mut bytes = alloc::vec![0u8; (params.p.bits() as usize).div_ceil(8) + 16];
+    let mut counter = 0u32;
+    loop {
+        let mut transcript = Transcript::new(b"zk_pass.vrf_hash_to_group");
+        transcript.append_message(b"p", &BigUint::convert_to(&params.p));
+        transcript.append_message(b"q", &BigUint::convert_to(&params.q));
+        transcript.append_message(b"input", input);
+        transcript.append_u64(b"counter", counter.into());
+        transcript.challenge_bytes(b"h", &mut bytes);
+        let h = (BigUint::from_bytes_be(&bytes) % &params.p).modpow(&cofactor, &params.p);
+        if BigUint::is_valid_group_element(params, &h) {
+            return GroupParams {
+                g: params.g.clone(),
+                h,
+                p: params.p.clone(),
+                q: params.q.clone(),
+            };
+        }
+        counter += 1;
+    }
+}
+
+/// Derives the proof's nonce `k` from the secret and the hashed input, so that proving needs no
+/// randomness and never reuses a nonce for different inputs.
+fn nonce(vrf_params: &GroupParams<BigUint>, server_secret: &BigUint) -> BigUint {
+    let mut transcript = Transcript::new(b"zk_pass.vrf_nonce");
+    transcript.append_message(b"x", &BigUint::convert_to(server_secret));
+    transcript.append_message(b"h", &BigUint::convert_to(&vrf_params.h));
+    BigUint::from_transcript(&mut transcript, vrf_params)
+}
+
+/// Hashes `gamma` into the VRF output, below `q`.
+fn output(params: &GroupParams<BigUint>, gamma: &BigUint) -> BigUint {
+    let mut transcript = Transcript::new(b"zk_pass.vrf_output");
+    transcript.append_message(b"gamma", &BigUint::convert_to(gamma));
+    BigUint::from_transcript(&mut transcript, params)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chaum_pedersen::constants::RFC5114_MODP_2048_256_BIT_PARAMS;
+    use crate::chaum_pedersen::discretelog::generate_random_below;
+
+    /// The VRF output for the test vector, hex-encoded.
+    const VECTOR_OUTPUT: &str = "105ad596031515859fe05058c42e57fe72e6b08bf0c6db9d639f14ee1b2ee2";
+    /// The challenge of the proof for the test vector, hex-encoded.
+    const VECTOR_PROOF_C: &str = "8a39e3de2a44248a78e51d4fec24a7b933f676c9910d4a108cb27414127f12ad";
+    /// The response of the proof for the test vector, hex-encoded.
+    const VECTOR_PROOF_S: &str = "3cfb5b4cce6ed2a6c0b7cd98b1729c3d81bfab05fa91ccb053e7d72c49153789";
+
+    /// The test vector's secret and commitment, with the input built from them.
+    fn vector_input() -> (BigUint, Vec<u8>) {
+        let params = RFC5114_MODP_2048_256_BIT_PARAMS.to_owned();
+        let x = BigUint::from(0x5eed_u32);
+        let (r1, r2) = (params.g.modpow(&BigUint::from(7u32), &params.p), BigUint::from(3u32));
+        (x, vrf_input("alice", &r1, &r2, b"nonce"))
+    }
+
+    #[test]
+    fn test_vrf_input_is_length_prefixed() {
+        let (r1, r2) = (BigUint::from(1u32), BigUint::from(2u32));
+        assert_ne!(vrf_input("ab", &r1, &r2, b"c"), vrf_input("a", &r1, &r2, b"bc"));
+        assert_eq!(
+            hex::encode(vrf_input("a", &r1, &r2, &[9])),
+            "000000000000000161000000000000000101000000000000000102000000000000000109"
+        );
+    }
+
+    #[test]
+    fn test_vrf_challenge_round_trip() {
+        let params = RFC5114_MODP_2048_256_BIT_PARAMS.to_owned();
+        let x = generate_random_below(&params.q).unwrap();
+        let y = params.g.modpow(&x, &params.p);
+        let input = vrf_input("alice", &params.g, &params.h, b"nonce");
+
+        let (c, proof) = vrf_challenge(&params, &x, &input);
+        assert!(c < params.q);
+        assert!(vrf_verify(&params, &y, &input, &c, &proof));
+        assert_eq!(vrf_challenge(&params, &x, &input), (c.clone(), proof.clone()));
+
+        // Any other output, input, key or proof is rejected.
+        assert!(!vrf_verify(&params, &y, &input, &(&c + 1u8), &proof));
+        let other_input = vrf_input("bob", &params.g, &params.h, b"nonce");
+        assert!(!vrf_verify(&params, &y, &other_input, &c, &proof));
+        assert!(!vrf_verify(&params, &(&y * &params.g % &params.p), &input, &c, &proof));
+        let forged_gamma = VrfProof {
+            gamma: &proof.gamma * &params.g % &params.p,
+            ..proof.clone()
+        };
+        assert!(!vrf_verify(&params, &y, &input, &c, &forged_gamma));
+        let forged_s = VrfProof {
+            s: (&proof.s + 1u8) % &params.q,
+            ..proof.clone()
+        };
+        assert!(!vrf_verify(&params, &y, &input, &c, &forged_s));
+        let unreduced = VrfProof {
+            c: &proof.c + &params.q,
+            ..proof
+        };
+        assert!(!vrf_verify(&params, &y, &input, &c, &unreduced));
+    }
+
+    #[test]
+    fn test_vrf_test_vector() {
+        let params = RFC5114_MODP_2048_256_BIT_PARAMS.to_owned();
+        let (x, input) = vector_input();
+        let (c, proof) = vrf_challenge(&params, &x, &input);
+        assert_eq!(hex::encode(c.to_bytes_be()), VECTOR_OUTPUT);
+        assert_eq!(hex::encode(proof.c.to_bytes_be()), VECTOR_PROOF_C);
+        assert_eq!(hex::encode(proof.s.to_bytes_be()), VECTOR_PROOF_S);
+        let y = params.g.modpow(&x, &params.p);
+        assert!(vrf_verify(&params, &y, &input, &c, &proof));
+    }
+}
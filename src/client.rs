@@ -1,11 +1,29 @@
+// `tonic::Status` is the error type for every call in this module.
+#![allow(clippy::result_large_err)]
+
 use tonic::codegen::StdError;
+use tonic::metadata::{MetadataMap, MetadataValue};
 use tonic::transport::Channel;
 
+use crate::chaum_pedersen::runner::{ProtocolRunner, Transport};
+use crate::chaum_pedersen::transcript::{
+    derive_bound_challenge, derive_sized_nonce_challenge, TranscriptChallenge,
+};
 use crate::chaum_pedersen::ChaumPedersen;
-use crate::chaum_pedersen::GroupParams;
-use crate::conversion::ByteConvertible;
+use crate::chaum_pedersen::{GroupParams, ValidateGroupParams};
+use crate::conversion::{encode_proof, ByteConvertible};
 use crate::rand::RandomGenerator;
+use crate::service::{
+    session_token_message, API_KEY_HEADER, ERROR_CODE_HEADER, SERVER_IDENTITY_DOMAIN,
+};
+use async_trait::async_trait;
+use ed25519_dalek::{Signature, VerifyingKey};
+use rand_core::{OsRng, RngCore};
 use std::error::Error;
+use std::fmt;
+use std::sync::atomic::{AtomicU32, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 /// A module that contains the auto-generated gRPC code for the Zero-Knowledge Proof (ZKP) authentication service.
 pub mod zkp_auth {
@@ -15,16 +33,270 @@ pub mod zkp_auth {
 // Importing specific structures from the `zkp_auth` module.
 use zkp_auth::{
     auth_client::AuthClient, AuthenticationAnswerRequest, AuthenticationChallengeRequest,
-    RegisterRequest,
+    GetGroupParamsRequest, GetGroupParamsResponse, GetUserMetadataRequest, HealthRequest,
+    HealthResponse, Metadata, MutualAuthenticationRequest, NegotiateRequest, ProofRequest,
+    RegisterBatchRequest, RegisterRequest, RegisterResponse, RegisterResult, ResumeRequest,
+    ServerIdentityRequest, SessionToken, StatelessAnswerRequest, UpdateCredentialsRequest,
+    ValidateSessionRequest, ZkPassErrorCode,
 };
 
+/// The state of a [`CircuitBreaker`].
+///
+/// # Variants
+/// - `Closed`: Calls go through to the server.
+/// - `Open`: Calls are rejected without contacting the server, until the reset timeout has
+///   elapsed since the contained instant.
+/// - `HalfOpen`: A single probe call is in flight; its outcome closes or reopens the breaker.
+///   A probe that is dropped before its outcome is recorded reopens the breaker.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    Closed,
+    Open(Instant),
+    HalfOpen,
+}
+
+/// A circuit breaker that stops a client from hammering an unavailable server.
+///
+/// After `failure_threshold` consecutive failed calls the breaker opens and further calls fail
+/// immediately with an `Unavailable` status carrying [`ZkPassError::CircuitOpen`]. Once
+/// `reset_timeout` has elapsed, a single probe call is let through: if it succeeds the breaker
+/// closes again, otherwise it reopens.
+///
+/// Only errors that indicate the server is unhealthy (`Unavailable`, `DeadlineExceeded`,
+/// `Internal` and `Unknown`) count as failures; rejected proofs or unknown users do not.
+/// Clones share the same state.
+#[derive(Debug, Clone)]
+pub struct CircuitBreaker {
+    /// The current state of the breaker.
+    state: Arc<Mutex<CircuitState>>,
+    /// The number of consecutive failures seen while closed.
+    failures: Arc<AtomicU32>,
+    /// The number of consecutive failures that opens the breaker.
+    failure_threshold: u32,
+    /// How long the breaker stays open before allowing a probe call.
+    reset_timeout: Duration,
+}
+
+impl CircuitBreaker {
+    /// Creates a closed circuit breaker.
+    ///
+    /// # Arguments
+    /// * `failure_threshold` - The number of consecutive failures that opens the breaker.
+    /// * `reset_timeout` - How long the breaker stays open before allowing a probe call.
+    pub fn new(failure_threshold: u32, reset_timeout: Duration) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(CircuitState::Closed)),
+            failures: Arc::new(AtomicU32::new(0)),
+            failure_threshold,
+            reset_timeout,
+        }
+    }
+
+    /// Returns the current state of the breaker.
+    pub fn state(&self) -> CircuitState {
+        *self.state.lock().unwrap()
+    }
+
+    /// Checks whether a call may be issued, moving an expired open breaker to half-open.
+    ///
+    /// # Returns
+    /// A permit to pass to [`CircuitBreaker::record`] with the outcome of the call, or an
+    /// `Unavailable` status carrying [`ZkPassError::CircuitOpen`] if the breaker is open or a
+    /// probe call is already in flight.
+    pub fn acquire(&self) -> Result<CircuitPermit, tonic::Status> {
+        let mut state = self.state.lock().unwrap();
+        let probe = match *state {
+            CircuitState::Closed => false,
+            CircuitState::Open(since) if since.elapsed() >= self.reset_timeout => {
+                *state = CircuitState::HalfOpen;
+                true
+            }
+            CircuitState::Open(_) | CircuitState::HalfOpen => {
+                let mut metadata = MetadataMap::new();
+                metadata.insert_bin(
+                    ERROR_CODE_HEADER,
+                    MetadataValue::from_bytes(&(ZkPassErrorCode::CircuitOpen as i32).to_be_bytes()),
+                );
+                return Err(tonic::Status::with_metadata(
+                    tonic::Code::Unavailable,
+                    "Circuit breaker is open",
+                    metadata,
+                ));
+            }
+        };
+        Ok(CircuitPermit {
+            breaker: self.clone(),
+            probe,
+        })
+    }
+
+    /// Records the outcome of a call issued after [`CircuitBreaker::acquire`].
+    ///
+    /// # Arguments
+    /// * `permit` - The permit the call was issued with.
+    /// * `result` - The result of the call.
+    ///
+    /// # Returns
+    /// The `result`, unchanged.
+    pub fn record<R>(
+        &self, mut permit: CircuitPermit, result: Result<R, tonic::Status>,
+    ) -> Result<R, tonic::Status> {
+        permit.probe = false;
+        let failed = matches!(
+            &result,
+            Err(status) if matches!(
+                status.code(),
+                tonic::Code::Unavailable
+                    | tonic::Code::DeadlineExceeded
+                    | tonic::Code::Internal
+                    | tonic::Code::Unknown
+            )
+        );
+        let mut state = self.state.lock().unwrap();
+        if !failed {
+            self.failures.store(0, Ordering::SeqCst);
+            *state = CircuitState::Closed;
+        } else if *state == CircuitState::HalfOpen
+            || self.failures.fetch_add(1, Ordering::SeqCst) + 1 >= self.failure_threshold
+        {
+            self.failures.store(0, Ordering::SeqCst);
+            *state = CircuitState::Open(Instant::now());
+        }
+        result
+    }
+}
+
+/// Permission to issue one call through a [`CircuitBreaker`], handed out by
+/// [`CircuitBreaker::acquire`].
+///
+/// A probe permit dropped without its outcome being recorded, e.g. because the future issuing
+/// the call was cancelled, reopens the breaker so that it does not stay half-open forever.
+#[must_use]
+#[derive(Debug)]
+pub struct CircuitPermit {
+    /// The breaker the permit was acquired from.
+    breaker: CircuitBreaker,
+    /// Whether the call is the probe of a half-open breaker, with its outcome not yet recorded.
+    probe: bool,
+}
+
+impl Drop for CircuitPermit {
+    fn drop(&mut self) {
+        if self.probe {
+            *self.breaker.state.lock().unwrap() = CircuitState::Open(Instant::now());
+        }
+    }
+}
+
+/// A machine-readable error reported by the server.
+///
+/// The server sends the error code of every error status in the [`ERROR_CODE_HEADER`]
+/// metadata entry, so clients can handle errors without parsing status messages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ZkPassError {
+    /// The error has no more specific code; see the status code and message.
+    Unspecified,
+    /// No user with the given name is registered.
+    UserNotFound,
+    /// The authentication challenge does not exist or has already been answered.
+    ChallengeNotFound,
+    /// The response or proof does not verify.
+    VerificationFailed,
+    /// A request field is invalid, e.g. a value that cannot be decoded.
+    InvalidParameter,
+    /// The server refuses requests from the client for a while.
+    RateLimited,
+    /// The challenge or proof is too old to be accepted.
+    ChallengeExpired,
+    /// The client's circuit breaker rejected the call without contacting the server.
+    CircuitOpen,
+    /// The session is unknown or has expired, or its token does not verify.
+    InvalidSession,
+    /// The randomness beacon mixed into challenges cannot be reached.
+    BeaconUnavailable,
+    /// The third party supplying challenges cannot be reached.
+    ChallengeSourceUnavailable,
+    /// The commitment was already sent for another challenge.
+    CommitmentReused,
+}
+
+impl ZkPassError {
+    /// Extracts the machine-readable error from a status returned by [`AuthClientLib`].
+    ///
+    /// # Arguments
+    /// * `status` - The error status.
+    ///
+    /// # Returns
+    /// The error, or `None` if the status carries no known error code, e.g. because it was
+    /// raised by the transport or the client itself.
+    pub fn from_status(status: &tonic::Status) -> Option<ZkPassError> {
+        let bytes = status
+            .metadata()
+            .get_bin(ERROR_CODE_HEADER)?
+            .to_bytes()
+            .ok()?;
+        let code = i32::from_be_bytes(bytes.as_ref().try_into().ok()?);
+        Some(match ZkPassErrorCode::try_from(code).ok()? {
+            ZkPassErrorCode::Unspecified => ZkPassError::Unspecified,
+            ZkPassErrorCode::UserNotFound => ZkPassError::UserNotFound,
+            ZkPassErrorCode::ChallengeNotFound => ZkPassError::ChallengeNotFound,
+            ZkPassErrorCode::VerificationFailed => ZkPassError::VerificationFailed,
+            ZkPassErrorCode::InvalidParameter => ZkPassError::InvalidParameter,
+            ZkPassErrorCode::RateLimited => ZkPassError::RateLimited,
+            ZkPassErrorCode::ChallengeExpired => ZkPassError::ChallengeExpired,
+            ZkPassErrorCode::CircuitOpen => ZkPassError::CircuitOpen,
+            ZkPassErrorCode::InvalidSession => ZkPassError::InvalidSession,
+            ZkPassErrorCode::BeaconUnavailable => ZkPassError::BeaconUnavailable,
+            ZkPassErrorCode::ChallengeSourceUnavailable => ZkPassError::ChallengeSourceUnavailable,
+            ZkPassErrorCode::CommitmentReused => ZkPassError::CommitmentReused,
+        })
+    }
+}
+
+impl fmt::Display for ZkPassError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ZkPassError::Unspecified => write!(f, "Unspecified error"),
+            ZkPassError::UserNotFound => write!(f, "User not found"),
+            ZkPassError::ChallengeNotFound => write!(f, "Challenge not found"),
+            ZkPassError::VerificationFailed => write!(f, "Verification failed"),
+            ZkPassError::InvalidParameter => write!(f, "Invalid parameter"),
+            ZkPassError::RateLimited => write!(f, "Rate limited"),
+            ZkPassError::ChallengeExpired => write!(f, "Challenge expired"),
+            ZkPassError::CircuitOpen => write!(f, "Circuit breaker is open"),
+            ZkPassError::InvalidSession => write!(f, "Invalid session"),
+            ZkPassError::BeaconUnavailable => write!(f, "Randomness beacon unavailable"),
+            ZkPassError::ChallengeSourceUnavailable => write!(f, "Challenge source unavailable"),
+            ZkPassError::CommitmentReused => write!(f, "Commitment reused"),
+        }
+    }
+}
+
+impl Error for ZkPassError {}
+
 /// A client library for interacting with the ZKP authentication service.
 ///
 /// This struct encapsulates the functionality for connecting to the service and performing
-/// operations like user registration and authentication.
+/// operations like user registration and authentication. Errors reported by the server can be
+/// told apart with [`ZkPassError::from_status`].
+#[derive(Clone)]
 pub struct AuthClientLib {
     /// The gRPC client for the ZKP authentication service.
     client: AuthClient<Channel>,
+    /// The server's expected public values (`y1`, `y2`), if its identity must be verified.
+    server_verification: Option<(Vec<u8>, Vec<u8>)>,
+    /// The circuit breaker guarding every call, if enabled.
+    breaker: Option<CircuitBreaker>,
+    /// The namespace users are registered and authenticated in; empty for the default namespace.
+    namespace: String,
+    /// The version of the server's group parameters credentials are computed with.
+    params_version: u32,
+    /// The API key attached to registrations, if the server gates registration.
+    api_key: Option<String>,
+    /// The group configuration requests are routed to; empty for the server's default group.
+    group: String,
+    /// The server's group parameters, once fetched.
+    group_params: Option<GetGroupParamsResponse>,
 }
 
 impl AuthClientLib {
@@ -42,7 +314,247 @@ impl AuthClientLib {
         D::Error: Into<StdError>,
     {
         let client = AuthClient::connect(dst).await?;
-        Ok(Self { client })
+        Ok(Self::from_client(client))
+    }
+
+    /// Creates a client issuing its calls over an established channel, e.g. one with a custom
+    /// transport.
+    ///
+    /// # Arguments
+    /// * `channel` - The channel to the ZKP authentication service.
+    pub fn from_channel(channel: Channel) -> Self {
+        Self::from_client(AuthClient::new(channel))
+    }
+
+    /// Wraps a gRPC client with the default settings.
+    fn from_client(client: AuthClient<Channel>) -> Self {
+        Self {
+            client,
+            server_verification: None,
+            breaker: None,
+            namespace: String::new(),
+            params_version: 0,
+            api_key: None,
+            group: String::new(),
+            group_params: None,
+        }
+    }
+
+    /// Guards every call with a circuit breaker, so that an unavailable server is not hammered
+    /// with requests that are bound to fail.
+    ///
+    /// # Arguments
+    /// * `failure_threshold` - The number of consecutive failures that opens the breaker.
+    /// * `reset_timeout` - How long the breaker stays open before allowing a probe call.
+    pub fn with_circuit_breaker(mut self, failure_threshold: u32, reset_timeout: Duration) -> Self {
+        self.breaker = Some(CircuitBreaker::new(failure_threshold, reset_timeout));
+        self
+    }
+
+    /// Registers and authenticates users within a namespace, isolating them from the users of
+    /// other tenants on the same server.
+    ///
+    /// # Arguments
+    /// * `namespace` - The tenant the client's users belong to.
+    pub fn with_namespace(mut self, namespace: impl Into<String>) -> Self {
+        self.namespace = namespace.into();
+        self
+    }
+
+    /// Sets the version of the server's group parameters that registered and updated
+    /// credentials are computed with. Defaults to 0, the server's initial parameters, and is
+    /// set by [`AuthClientLib::negotiate`].
+    ///
+    /// # Arguments
+    /// * `params_version` - The version of the group parameters.
+    pub fn with_params_version(mut self, params_version: u32) -> Self {
+        self.params_version = params_version;
+        self
+    }
+
+    /// Routes every request to a group configuration, on servers serving several groups with
+    /// [`MultiGroupAuth`](crate::service::MultiGroupAuth). Set by [`AuthClientLib::negotiate`].
+    ///
+    /// # Arguments
+    /// * `group` - The identifier of the group, such as `ec25519`.
+    pub fn with_group(mut self, group: impl Into<String>) -> Self {
+        self.group = group.into();
+        self
+    }
+
+    /// Attaches an API key to registrations, for servers only accepting registrations that
+    /// carry one in the [`API_KEY_HEADER`] header.
+    ///
+    /// # Arguments
+    /// * `api_key` - The API key configured on the server.
+    pub fn with_api_key(mut self, api_key: impl Into<String>) -> Self {
+        self.api_key = Some(api_key.into());
+        self
+    }
+
+    /// Returns the circuit breaker guarding this client, if enabled.
+    pub fn circuit_breaker(&self) -> Option<&CircuitBreaker> {
+        self.breaker.as_ref()
+    }
+
+    /// Checks the circuit breaker, if enabled, before issuing a call.
+    ///
+    /// # Returns
+    /// The permit to pass to [`AuthClientLib::after_call`], if the breaker is enabled.
+    fn before_call(&self) -> Result<Option<CircuitPermit>, tonic::Status> {
+        self.breaker
+            .as_ref()
+            .map(CircuitBreaker::acquire)
+            .transpose()
+    }
+
+    /// Wraps a registration message in a request carrying the API key, if set.
+    fn registration_request<M>(&self, message: M) -> Result<tonic::Request<M>, tonic::Status> {
+        let mut request = tonic::Request::new(message);
+        if let Some(api_key) = &self.api_key {
+            let value = api_key
+                .parse()
+                .map_err(|_| tonic::Status::invalid_argument("Invalid API key"))?;
+            request.metadata_mut().insert(API_KEY_HEADER, value);
+        }
+        Ok(request)
+    }
+
+    /// Records the outcome of a call with the circuit breaker, if enabled.
+    fn after_call<R>(
+        &self, permit: Option<CircuitPermit>, result: Result<R, tonic::Status>,
+    ) -> Result<R, tonic::Status> {
+        match (&self.breaker, permit) {
+            (Some(breaker), Some(permit)) => breaker.record(permit, result),
+            _ => result,
+        }
+    }
+
+    /// Requires the server to prove its identity before the client registers a commitment.
+    ///
+    /// The expected values must come from a trusted source, such as the output of a server
+    /// started with `--server-secret`. [`execute_protocol`] then calls
+    /// [`AuthClientLib::verify_server_identity`] before registering.
+    ///
+    /// # Arguments
+    /// * `expected_y1` - The encoded `y1` of the server's secret.
+    /// * `expected_y2` - The encoded `y2` of the server's secret.
+    pub fn with_server_verification(mut self, expected_y1: Vec<u8>, expected_y2: Vec<u8>) -> Self {
+        self.server_verification = Some((expected_y1, expected_y2));
+        self
+    }
+
+    /// Asks the server to prove knowledge of the secret behind the expected public values.
+    ///
+    /// The server's proof is bound to a fresh random nonce, so a proof recorded from an earlier
+    /// exchange cannot be replayed by an impostor.
+    ///
+    /// # Type Parameters
+    /// * `T`: The type of Chaum-Pedersen protocol.
+    /// * `P`: The type of the group elements.
+    /// * `S`: The type of the response and challenge.
+    ///
+    /// # Arguments
+    /// * `params` - Group parameters for the cryptographic operations.
+    ///
+    /// # Returns
+    /// `Ok(())` if the server proved its identity, or an error if server verification is not
+    /// configured, the call fails, or the proof does not match the expected values.
+    pub async fn verify_server_identity<T, P, S>(
+        &mut self, params: &GroupParams<P>,
+    ) -> Result<(), Box<dyn Error>>
+    where
+        T: ChaumPedersen<
+            GroupParameters = GroupParams<P>,
+            CommitParameters = (P, P, P, P),
+            Response = S,
+            Challenge = S,
+        >,
+        P: ByteConvertible<P>,
+        S: ByteConvertible<S> + TranscriptChallenge<GroupParams<P>>,
+    {
+        let (expected_y1, expected_y2) = self
+            .server_verification
+            .clone()
+            .ok_or("Server verification is not configured")?;
+
+        let mut nonce = vec![0u8; 32];
+        OsRng.fill_bytes(&mut nonce);
+        let permit = self.before_call()?;
+        let response = self
+            .client
+            .prove_server_identity(ServerIdentityRequest {
+                nonce: nonce.clone(),
+                group: self.group.clone(),
+            })
+            .await;
+        let proof = self.after_call(permit, response)?.into_inner();
+        if proof.sy1 != expected_y1 || proof.sy2 != expected_y2 {
+            return Err("Server failed to prove its identity".into());
+        }
+
+        let cp = (
+            P::convert_from(&expected_y1)?,
+            P::convert_from(&expected_y2)?,
+            P::convert_from(&proof.sr1)?,
+            P::convert_from(&proof.sr2)?,
+        );
+        let s = S::convert_from(&proof.ss)?;
+        let c: S = derive_bound_challenge(SERVER_IDENTITY_DOMAIN, params, &cp, &nonce);
+        if !T::verify(params, &s, &c, &cp) {
+            return Err("Server failed to prove its identity".into());
+        }
+        Ok(())
+    }
+
+    /// Agrees on a protocol type with the server before registration.
+    ///
+    /// # Arguments
+    /// * `supported_types` - The protocol types the client supports, in order of preference,
+    ///   such as `ec25519` or `rfc5114_modp_2048_256`.
+    ///
+    /// Later registrations and credential updates use the version of the returned group
+    /// parameters, and later requests are routed to the group of the selected type.
+    ///
+    /// # Returns
+    /// A result containing the selected protocol type and the server's JSON-encoded group
+    /// parameters, which can be decoded with [`decode_group_params`], or an error if there is
+    /// no mutually supported type.
+    pub async fn negotiate(
+        &mut self, supported_types: Vec<String>,
+    ) -> Result<(String, Vec<u8>), tonic::Status> {
+        let request = NegotiateRequest { supported_types };
+        let permit = self.before_call()?;
+        let response = self.client.negotiate_protocol(request).await;
+        let inner = self.after_call(permit, response)?.into_inner();
+        self.params_version = inner.params_version;
+        self.group = inner.selected_type.clone();
+        Ok((inner.selected_type, inner.group_params_encoded))
+    }
+
+    /// Fetches the server's protocol type and group parameters, so that the client can run the
+    /// protocol without configuring the group locally.
+    ///
+    /// The response is cached for the lifetime of the client, so only the first call contacts
+    /// the server. Later registrations and credential updates use the version of the returned
+    /// group parameters.
+    ///
+    /// # Returns
+    /// A result containing the protocol type and the hex-encoded group parameters, which can be
+    /// decoded with [`decode_hex_group_params`], or an error if the call fails.
+    pub async fn fetch_group_params(&mut self) -> Result<GetGroupParamsResponse, tonic::Status> {
+        if let Some(cached) = &self.group_params {
+            return Ok(cached.clone());
+        }
+        let request = GetGroupParamsRequest {
+            group: self.group.clone(),
+        };
+        let permit = self.before_call()?;
+        let response = self.client.get_group_params(request).await;
+        let inner = self.after_call(permit, response)?.into_inner();
+        self.params_version = inner.params_version;
+        self.group_params = Some(inner.clone());
+        Ok(inner)
     }
 
     /// Registers a new user with the ZKP authentication service.
@@ -53,13 +565,83 @@ impl AuthClientLib {
     /// * `y2` - The second part of the user's cryptographic credential.
     ///
     /// # Returns
-    /// A result indicating success or an error if the registration fails.
+    /// A result containing the server's reply, which carries the user's ID and whether the user
+    /// was created, or an error if the registration fails.
     pub async fn register(
         &mut self, user: String, y1: Vec<u8>, y2: Vec<u8>,
-    ) -> Result<(), tonic::Status> {
-        let request = RegisterRequest { user, y1, y2 };
-        self.client.register(request).await?;
-        Ok(())
+    ) -> Result<RegisterResponse, tonic::Status> {
+        self.register_with_metadata(user, y1, y2, vec![]).await
+    }
+
+    /// Registers a new user along with auxiliary metadata, such as an email address or roles.
+    ///
+    /// # Arguments
+    /// * `user` - The username of the user to be registered.
+    /// * `y1` - The first part of the user's cryptographic credential.
+    /// * `y2` - The second part of the user's cryptographic credential.
+    /// * `metadata` - Key/value pairs to store alongside the user's credential.
+    ///
+    /// # Returns
+    /// A result containing the server's reply, or an error if the registration fails. The reply's
+    /// `created` is false if the user was already registered with the same `y1`/`y2`.
+    pub async fn register_with_metadata(
+        &mut self, user: String, y1: Vec<u8>, y2: Vec<u8>, metadata: Vec<Metadata>,
+    ) -> Result<RegisterResponse, tonic::Status> {
+        let request = RegisterRequest {
+            user,
+            y1,
+            y2,
+            metadata,
+            namespace: self.namespace.clone(),
+            params_version: self.params_version,
+            group: self.group.clone(),
+        };
+        let request = self.registration_request(request)?;
+        let permit = self.before_call()?;
+        let response = self.client.register(request).await;
+        Ok(self.after_call(permit, response)?.into_inner())
+    }
+
+    /// Retrieves the auxiliary metadata stored for a registered user.
+    ///
+    /// # Arguments
+    /// * `user` - The username of the user whose metadata is requested.
+    ///
+    /// # Returns
+    /// A result containing the user's metadata, or an error if the user is not registered.
+    pub async fn get_user_metadata(
+        &mut self, user: String,
+    ) -> Result<Vec<Metadata>, tonic::Status> {
+        let request = GetUserMetadataRequest {
+            user,
+            namespace: self.namespace.clone(),
+            group: self.group.clone(),
+        };
+        let permit = self.before_call()?;
+        let response = self.client.get_user_metadata(request).await;
+        let response = self.after_call(permit, response)?;
+        Ok(response.into_inner().metadata)
+    }
+
+    /// Registers a batch of users with the ZKP authentication service in a single call.
+    ///
+    /// # Arguments
+    /// * `users` - The registration requests for each user to be registered.
+    ///
+    /// # Returns
+    /// A result containing one `RegisterResult` per requested user, in request order,
+    /// or an error if the call itself fails.
+    pub async fn register_batch(
+        &mut self, users: Vec<RegisterRequest>,
+    ) -> Result<Vec<RegisterResult>, tonic::Status> {
+        let request = self.registration_request(RegisterBatchRequest {
+            users,
+            group: self.group.clone(),
+        })?;
+        let permit = self.before_call()?;
+        let response = self.client.register_batch(request).await;
+        let response = self.after_call(permit, response)?;
+        Ok(response.into_inner().results)
     }
 
     /// Creates an authentication challenge for a user.
@@ -70,15 +652,152 @@ impl AuthClientLib {
     /// * `r2` - The second part of the randomness used in the challenge.
     ///
     /// # Returns
-    /// A result containing the challenge and an authentication ID if successful,
-    /// or an error if the operation fails.
+    /// A result containing the challenge, an authentication ID, the server's nonce and the bit
+    /// length of the effective challenge if successful, or an error if the operation fails. The
+    /// response must answer the challenge derived from the nonce, `c` and the bit length with
+    /// [`derive_sized_nonce_challenge`].
     pub async fn create_authentication_challenge(
         &mut self, user: String, r1: Vec<u8>, r2: Vec<u8>,
-    ) -> Result<(Vec<u8>, String), tonic::Status> {
-        let request = AuthenticationChallengeRequest { user, r1, r2 };
-        let response = self.client.create_authentication_challenge(request).await?;
+    ) -> Result<(Vec<u8>, String, Vec<u8>, u32), tonic::Status> {
+        let request = AuthenticationChallengeRequest {
+            user,
+            r1,
+            r2,
+            namespace: self.namespace.clone(),
+            group: self.group.clone(),
+        };
+        let permit = self.before_call()?;
+        let response = self.client.create_authentication_challenge(request).await;
+        let response = self.after_call(permit, response)?;
+        let inner = response.into_inner();
+        Ok((inner.c, inner.auth_id, inner.nonce, inner.challenge_bits))
+    }
+
+    /// Creates an authentication challenge for a user, along with the resumption token handed out
+    /// by servers that enable resumption.
+    ///
+    /// # Arguments
+    /// * `user` - The username of the user for whom the challenge is being created.
+    /// * `r1` - The first part of the randomness used in the challenge.
+    /// * `r2` - The second part of the randomness used in the challenge.
+    ///
+    /// # Returns
+    /// A result containing the challenge, an authentication ID, the server's nonce, the bit
+    /// length of the effective challenge and the resumption token if successful, or an error if
+    /// the operation fails or the server does not enable resumption.
+    #[allow(clippy::type_complexity)]
+    pub async fn create_resumable_authentication_challenge(
+        &mut self, user: String, r1: Vec<u8>, r2: Vec<u8>,
+    ) -> Result<(Vec<u8>, String, Vec<u8>, u32, Vec<u8>), tonic::Status> {
+        let request = AuthenticationChallengeRequest {
+            user,
+            r1,
+            r2,
+            namespace: self.namespace.clone(),
+            group: self.group.clone(),
+        };
+        let permit = self.before_call()?;
+        let response = self.client.create_authentication_challenge(request).await;
+        let response = self.after_call(permit, response)?;
+        let inner = response.into_inner();
+        if inner.resume_token.is_empty() {
+            return Err(tonic::Status::failed_precondition("Server does not enable resumption"));
+        }
+        Ok((inner.c, inner.auth_id, inner.nonce, inner.challenge_bits, inner.resume_token))
+    }
+
+    /// Asks for the challenge of an interrupted authentication again.
+    ///
+    /// # Arguments
+    /// * `resume_token` - The resumption token handed out with the challenge.
+    ///
+    /// # Returns
+    /// The same challenge, authentication ID, nonce and challenge bit length as
+    /// [`AuthClientLib::create_authentication_challenge`] returned, or an error if the token is
+    /// invalid or expired or the challenge was already answered.
+    pub async fn resume_authentication(
+        &mut self, resume_token: Vec<u8>,
+    ) -> Result<(Vec<u8>, String, Vec<u8>, u32), tonic::Status> {
+        let permit = self.before_call()?;
+        let response = self
+            .client
+            .resume_authentication(ResumeRequest {
+                resume_token,
+                group: self.group.clone(),
+            })
+            .await;
+        let response = self.after_call(permit, response)?;
+        let inner = response.into_inner();
+        Ok((inner.c, inner.auth_id, inner.nonce, inner.challenge_bits))
+    }
+
+    /// Creates an authentication challenge for a user, along with the server's commitment
+    /// for mutual authentication.
+    ///
+    /// # Arguments
+    /// * `user` - The username of the user for whom the challenge is being created.
+    /// * `r1` - The first part of the randomness used in the challenge.
+    /// * `r2` - The second part of the randomness used in the challenge.
+    ///
+    /// # Returns
+    /// A result containing the challenge, an authentication ID, the server's nonce, the bit
+    /// length of the effective challenge and the server's commitment (`r1`, `r2`) if successful,
+    /// or an error if the operation fails or the server does not support mutual authentication.
+    #[allow(clippy::type_complexity)]
+    pub async fn create_mutual_authentication_challenge(
+        &mut self, user: String, r1: Vec<u8>, r2: Vec<u8>,
+    ) -> Result<(Vec<u8>, String, Vec<u8>, u32, (Vec<u8>, Vec<u8>)), tonic::Status> {
+        let request = AuthenticationChallengeRequest {
+            user,
+            r1,
+            r2,
+            namespace: self.namespace.clone(),
+            group: self.group.clone(),
+        };
+        let permit = self.before_call()?;
+        let response = self.client.create_authentication_challenge(request).await;
+        let response = self.after_call(permit, response)?;
+        let inner = response.into_inner();
+        if inner.server_r1.is_empty() || inner.server_r2.is_empty() {
+            return Err(tonic::Status::failed_precondition(
+                "Server does not support mutual authentication",
+            ));
+        }
+        Ok((
+            inner.c,
+            inner.auth_id,
+            inner.nonce,
+            inner.challenge_bits,
+            (inner.server_r1, inner.server_r2),
+        ))
+    }
+
+    /// Answers an authentication challenge and challenges the server in return.
+    ///
+    /// # Arguments
+    /// * `auth_id` - The authentication ID associated with the challenge.
+    /// * `s` - The user's response to the challenge.
+    /// * `nonce` - The nonce issued with the challenge.
+    /// * `server_c` - The user's challenge to the server.
+    ///
+    /// # Returns
+    /// A result containing a session ID and the server's response to `server_c` if the user's
+    /// answer verifies, or an error otherwise.
+    pub async fn mutual_authenticate(
+        &mut self, auth_id: String, s: Vec<u8>, nonce: Vec<u8>, server_c: Vec<u8>,
+    ) -> Result<(String, Vec<u8>), tonic::Status> {
+        let request = MutualAuthenticationRequest {
+            auth_id,
+            s,
+            server_c,
+            nonce,
+            group: self.group.clone(),
+        };
+        let permit = self.before_call()?;
+        let response = self.client.mutual_authenticate(request).await;
+        let response = self.after_call(permit, response)?;
         let inner = response.into_inner();
-        Ok((inner.c, inner.auth_id))
+        Ok((inner.session_id, inner.server_s))
     }
 
     /// Verifies an authentication challenge for a user.
@@ -86,24 +805,437 @@ impl AuthClientLib {
     /// # Arguments
     /// * `auth_id` - The authentication ID associated with the challenge.
     /// * `s` - The user's response to the challenge.
+    /// * `nonce` - The nonce issued with the challenge.
     ///
     /// # Returns
     /// A result containing a session ID if the verification is successful,
     /// or an error if the verification fails.
     pub async fn verify_authentication(
-        &mut self, auth_id: String, s: Vec<u8>,
+        &mut self, auth_id: String, s: Vec<u8>, nonce: Vec<u8>,
     ) -> Result<String, tonic::Status> {
-        let request = AuthenticationAnswerRequest { auth_id, s };
-        let response = self.client.verify_authentication(request).await?;
+        let (session_id, _) = self
+            .verify_authentication_with_token(auth_id, s, nonce)
+            .await?;
+        Ok(session_id)
+    }
+
+    /// Verifies an authentication challenge for a user, also returning the signed session
+    /// token issued by servers with a signing key.
+    ///
+    /// # Arguments
+    /// * `auth_id` - The authentication ID associated with the challenge.
+    /// * `s` - The user's response to the challenge.
+    /// * `nonce` - The nonce issued with the challenge.
+    ///
+    /// # Returns
+    /// A result containing a session ID and, if the server signs sessions, the session token,
+    /// or an error if the verification fails.
+    pub async fn verify_authentication_with_token(
+        &mut self, auth_id: String, s: Vec<u8>, nonce: Vec<u8>,
+    ) -> Result<(String, Option<SessionToken>), tonic::Status> {
+        let request = AuthenticationAnswerRequest {
+            auth_id,
+            s,
+            nonce,
+            group: self.group.clone(),
+        };
+        let permit = self.before_call()?;
+        let response = self.client.verify_authentication(request).await;
+        let response = self.after_call(permit, response)?;
+        let inner = response.into_inner();
+        Ok((inner.session_id, inner.session_token))
+    }
+
+    /// Verifies an answer to an authentication challenge that carries the full commitment and
+    /// the challenge, so the server need not look up the challenge.
+    ///
+    /// # Arguments
+    /// * `answer` - The answer, along with `y1`, `y2`, `r1`, `r2`, the challenge `c` and nonce
+    ///   issued, and the resumption token handed out with the challenge. An empty `group` is
+    ///   set to the client's group.
+    ///
+    /// # Returns
+    /// A result containing a session ID if the verification is successful,
+    /// or an error if the verification fails.
+    pub async fn verify_authentication_stateless(
+        &mut self, mut answer: StatelessAnswerRequest,
+    ) -> Result<String, tonic::Status> {
+        if answer.group.is_empty() {
+            answer.group = self.group.clone();
+        }
+        let permit = self.before_call()?;
+        let response = self.client.verify_authentication_stateless(answer).await;
+        let response = self.after_call(permit, response)?;
         Ok(response.into_inner().session_id)
     }
+
+    /// Submits a non-interactive proof built offline, authenticating in a single request.
+    ///
+    /// # Arguments
+    /// * `user` - The username the proof is for.
+    /// * `proof_bytes` - The proof bundle, encoded with `ProofBundle::to_compact_bytes`.
+    ///
+    /// # Returns
+    /// A result containing whether the proof verifies and, if it does, the session ID, or an
+    /// error if the server rejects the proof.
+    pub async fn verify_proof(
+        &mut self, user: String, proof_bytes: Vec<u8>,
+    ) -> Result<(bool, String), tonic::Status> {
+        let request = ProofRequest {
+            user,
+            proof_bytes,
+            group: self.group.clone(),
+        };
+        let permit = self.before_call()?;
+        let response = self.client.verify_proof(request).await;
+        let response = self.after_call(permit, response)?;
+        let inner = response.into_inner();
+        Ok((inner.verified, inner.session_id))
+    }
+
+    /// Checks whether a session issued by the server is still valid.
+    ///
+    /// # Arguments
+    /// * `token` - The session ID returned on authentication.
+    ///
+    /// # Returns
+    /// A result containing the user the session belongs to and its expiry, which is zero for
+    /// UUID sessions, or an error if the session is invalid.
+    pub async fn validate_session(
+        &mut self, token: String,
+    ) -> Result<(String, u64), tonic::Status> {
+        let request = ValidateSessionRequest {
+            token,
+            group: self.group.clone(),
+        };
+        let permit = self.before_call()?;
+        let response = self.client.validate_session(request).await;
+        let response = self.after_call(permit, response)?;
+        let inner = response.into_inner();
+        Ok((inner.user, inner.expiry))
+    }
+
+    /// Asks the server whether it is serving and which protocol it runs.
+    ///
+    /// # Returns
+    /// A result containing the server's serving status, protocol type and version of its
+    /// current group parameters, or an error if the server cannot be reached.
+    pub async fn health(&mut self) -> Result<HealthResponse, tonic::Status> {
+        let permit = self.before_call()?;
+        let response = self
+            .client
+            .health(HealthRequest {
+                group: self.group.clone(),
+            })
+            .await;
+        Ok(self.after_call(permit, response)?.into_inner())
+    }
+
+    /// Rotates a user's public commitment.
+    ///
+    /// The caller must first open a challenge with `create_authentication_challenge` and
+    /// answer it with the current secret; the new commitment only replaces the old one if
+    /// that answer verifies.
+    ///
+    /// # Arguments
+    /// * `auth_id` - The authentication ID associated with the challenge.
+    /// * `s` - The response to the challenge, computed with the current secret.
+    /// * `nonce` - The nonce issued with the challenge.
+    /// * `y1` - The first part of the new cryptographic credential.
+    /// * `y2` - The second part of the new cryptographic credential.
+    ///
+    /// # Returns
+    /// A result indicating success or an error if the proof or the update fails.
+    pub async fn update_credentials(
+        &mut self, auth_id: String, s: Vec<u8>, nonce: Vec<u8>, y1: Vec<u8>, y2: Vec<u8>,
+    ) -> Result<(), tonic::Status> {
+        let request = UpdateCredentialsRequest {
+            auth_id,
+            s,
+            y1,
+            y2,
+            nonce,
+            params_version: self.params_version,
+            group: self.group.clone(),
+            old_y1: Vec::new(),
+            old_y2: Vec::new(),
+        };
+        let permit = self.before_call()?;
+        let response = self.client.update_credentials(request).await;
+        self.after_call(permit, response)?;
+        Ok(())
+    }
+}
+
+/// A pool of clients for high-throughput interaction with the ZKP authentication service.
+///
+/// Each pooled client holds its own gRPC channel, so concurrent requests are spread across
+/// several connections instead of being serialized through a single one. Clients are selected
+/// in round-robin order.
+pub struct AuthClientPool {
+    /// The pooled clients, each with a dedicated channel.
+    pool: Vec<AuthClientLib>,
+    /// The index of the next client to hand out, wrapping around the pool size.
+    next: AtomicUsize,
+}
+
+impl AuthClientPool {
+    /// Connects a pool of clients to the ZKP authentication service.
+    ///
+    /// # Arguments
+    /// * `dst` - The destination where the ZKP authentication service is hosted.
+    /// * `size` - The number of channels to open. A size of zero is treated as one.
+    ///
+    /// # Returns
+    /// A result containing the `AuthClientPool` instance if every connection is successful,
+    /// or an error if any connection fails.
+    pub async fn connect_pooled<D>(dst: D, size: usize) -> Result<Self, tonic::transport::Error>
+    where
+        D: std::convert::TryInto<tonic::transport::Endpoint>,
+        D::Error: Into<StdError>,
+    {
+        let endpoint = tonic::transport::Endpoint::new(dst)?;
+        let mut pool = Vec::with_capacity(size.max(1));
+        for _ in 0..size.max(1) {
+            pool.push(AuthClientLib::connect(endpoint.clone()).await?);
+        }
+        Ok(Self {
+            pool,
+            next: AtomicUsize::new(0),
+        })
+    }
+
+    /// Returns the number of clients in the pool.
+    pub fn size(&self) -> usize {
+        self.pool.len()
+    }
+
+    /// Selects the next client in round-robin order.
+    ///
+    /// Clients share their underlying channel with their clones, so the returned client can be
+    /// used independently while still sending over the pooled connection.
+    fn next_client(&self) -> AuthClientLib {
+        let index = self.next.fetch_add(1, Ordering::Relaxed) % self.pool.len();
+        self.pool[index].clone()
+    }
+
+    /// Registers a new user using the next pooled client.
+    ///
+    /// See [`AuthClientLib::register`].
+    pub async fn register(
+        &self, user: String, y1: Vec<u8>, y2: Vec<u8>,
+    ) -> Result<RegisterResponse, tonic::Status> {
+        self.next_client().register(user, y1, y2).await
+    }
+
+    /// Creates an authentication challenge for a user using the next pooled client.
+    ///
+    /// See [`AuthClientLib::create_authentication_challenge`].
+    pub async fn create_authentication_challenge(
+        &self, user: String, r1: Vec<u8>, r2: Vec<u8>,
+    ) -> Result<(Vec<u8>, String, Vec<u8>, u32), tonic::Status> {
+        self.next_client()
+            .create_authentication_challenge(user, r1, r2)
+            .await
+    }
+
+    /// Verifies an authentication challenge for a user using the next pooled client.
+    ///
+    /// See [`AuthClientLib::verify_authentication`].
+    pub async fn verify_authentication(
+        &self, auth_id: String, s: Vec<u8>, nonce: Vec<u8>,
+    ) -> Result<String, tonic::Status> {
+        self.next_client()
+            .verify_authentication(auth_id, s, nonce)
+            .await
+    }
+}
+
+/// Decodes and validates the group parameters returned by [`AuthClientLib::negotiate`].
+///
+/// # Type Parameters
+/// * `P`: The type of the group elements of the negotiated protocol.
+///
+/// # Arguments
+/// * `encoded` - The JSON-encoded group parameters.
+///
+/// # Returns
+/// The decoded group parameters, or an error if they cannot be decoded or are not valid
+/// parameters for the group.
+pub fn decode_group_params<P>(encoded: &[u8]) -> Result<GroupParams<P>, Box<dyn Error>>
+where
+    P: ByteConvertible<P> + ValidateGroupParams,
+{
+    let params: GroupParams<P> = serde_json::from_slice(encoded)?;
+    params.validate()?;
+    Ok(params)
+}
+
+/// Decodes and validates the group parameters returned by
+/// [`AuthClientLib::fetch_group_params`].
+///
+/// # Type Parameters
+/// * `P`: The type of the group elements of the server's protocol.
+///
+/// # Arguments
+/// * `response` - The response carrying the hex-encoded group parameters.
+///
+/// # Returns
+/// The decoded group parameters, or an error if they cannot be decoded or are not valid
+/// parameters for the group.
+pub fn decode_hex_group_params<P>(
+    response: &GetGroupParamsResponse,
+) -> Result<GroupParams<P>, Box<dyn Error>>
+where
+    P: ByteConvertible<P> + ValidateGroupParams,
+{
+    let params = GroupParams {
+        g: P::from_hex(&response.g)?,
+        h: P::from_hex(&response.h)?,
+        p: P::from_hex(&response.p)?,
+        q: P::from_hex(&response.q)?,
+    };
+    params.validate()?;
+    Ok(params)
+}
+
+/// Verifies a session token issued by a server with a signing key.
+///
+/// The token's signature is checked against the server's public key, so services holding that
+/// key can accept the session without calling back to the server.
+///
+/// # Arguments
+/// * `token` - The session token returned by the server.
+/// * `pubkey` - The public half of the server's signing key.
+///
+/// # Returns
+/// `true` if the token is signed by `pubkey` and has not expired, `false` otherwise.
+pub fn verify_session_token(token: &SessionToken, pubkey: &VerifyingKey) -> bool {
+    let Ok(signature) = Signature::from_slice(&token.signature) else {
+        return false;
+    };
+    let Ok(message) = session_token_message(&token.user, &token.session_id, token.expiry) else {
+        return false;
+    };
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |now| now.as_secs());
+    pubkey.verify_strict(&message, &signature).is_ok() && now <= token.expiry
+}
+
+/// A [`Transport`] to a ZKPass server over gRPC.
+///
+/// Sending the commitment registers the user with `y1` and `y2`, then requests an
+/// authentication challenge for `r1` and `r2`. The challenge the prover answers is derived from
+/// the server's random challenge and nonce.
+///
+/// # Type Parameters
+/// * `P`: The type of the group elements.
+pub struct GrpcTransport<'a, P> {
+    client: &'a mut AuthClientLib,
+    params: &'a GroupParams<P>,
+    challenge: Option<(Vec<u8>, String, Vec<u8>, u32)>,
+    pending: Option<(String, Vec<u8>)>,
+    response: Option<Vec<u8>>,
+}
+
+impl<'a, P> GrpcTransport<'a, P> {
+    /// Creates a transport sending its messages through a client.
+    ///
+    /// # Arguments
+    /// * `client` - The client object for communication with the ZKPass server.
+    /// * `params` - Group parameters used in the protocol.
+    pub fn new(client: &'a mut AuthClientLib, params: &'a GroupParams<P>) -> Self {
+        Self {
+            client,
+            params,
+            challenge: None,
+            pending: None,
+            response: None,
+        }
+    }
+
+    /// Returns the error raised when the protocol messages are exchanged out of order.
+    fn out_of_order() -> tonic::Status {
+        tonic::Status::failed_precondition("No pending authentication challenge")
+    }
+}
+
+#[async_trait]
+impl<'a, P, S> Transport<P, S> for GrpcTransport<'a, P>
+where
+    P: ByteConvertible<P> + Send + Sync,
+    S: ByteConvertible<S> + TranscriptChallenge<GroupParams<P>> + Send + Sync,
+{
+    type Session = (String, Option<SessionToken>);
+    type Error = tonic::Status;
+
+    async fn send_commitment(
+        &mut self, user: &str, (y1, y2, r1, r2): &(P, P, P, P),
+    ) -> Result<(), tonic::Status> {
+        self.client
+            .register(user.to_string(), P::convert_to(y1), P::convert_to(y2))
+            .await?;
+        self.challenge = Some(
+            self.client
+                .create_authentication_challenge(
+                    user.to_string(),
+                    P::convert_to(r1),
+                    P::convert_to(r2),
+                )
+                .await?,
+        );
+        Ok(())
+    }
+
+    async fn recv_challenge(&mut self) -> Result<S, tonic::Status> {
+        let (c, auth_id, nonce, challenge_bits) =
+            self.challenge.take().ok_or_else(Self::out_of_order)?;
+        let c = S::convert_from(&c)
+            .map_err(|_| tonic::Status::invalid_argument("Invalid challenge"))?;
+        let challenge = derive_sized_nonce_challenge(self.params, &nonce, &c, challenge_bits)
+            .ok_or_else(|| tonic::Status::invalid_argument("Invalid challenge size"))?;
+        self.pending = Some((auth_id, nonce));
+        Ok(challenge)
+    }
+
+    async fn send_response(&mut self, s: &S) -> Result<(), tonic::Status> {
+        if self.pending.is_none() {
+            return Err(Self::out_of_order());
+        }
+        self.response = Some(S::convert_to(s));
+        Ok(())
+    }
+
+    async fn recv_result(&mut self) -> Result<(String, Option<SessionToken>), tonic::Status> {
+        let (Some((auth_id, nonce)), Some(s)) = (self.pending.take(), self.response.take()) else {
+            return Err(Self::out_of_order());
+        };
+        self.client
+            .verify_authentication_with_token(auth_id, s, nonce)
+            .await
+    }
+}
+
+/// The outcome of a successful run of [`execute_protocol`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuthResult {
+    /// The session ID issued by the server.
+    pub session_id: String,
+    /// The signed session token, if the server has a signing key.
+    pub session_token: Option<SessionToken>,
+    /// Whether the client's own check of its response against its commitment succeeded.
+    pub verified: bool,
+    /// The transcript `(y1, y2, r1, r2, c, s)` of the run, encoded with [`encode_proof`].
+    pub proof: Vec<u8>,
 }
 
 /// Executes the Chaum-Pedersen protocol for client authentication.
 ///
 /// This function handles the client side of the Chaum-Pedersen protocol, including
 /// registering the commitment, creating an authentication challenge, and verifying
-/// the authentication response.
+/// the authentication response. If the client was configured with
+/// [`AuthClientLib::with_server_verification`], the server must prove its identity first.
 ///
 /// # Type Parameters
 /// * `T`: The type of Chaum-Pedersen protocol (either Discrete Log or Elliptic Curve).
@@ -117,11 +1249,11 @@ impl AuthClientLib {
 /// * `client` - The client object for communication with the ZKPass server.
 ///
 /// # Returns
-/// Returns a `Result` which is `Ok(())` on successful execution or an error
+/// Returns a `Result` containing the `AuthResult` on successful execution or an error
 /// if any part of the process fails.
 pub async fn execute_protocol<T, P, S>(
-    params: &GroupParams<P>, x: &T::Secret, user: &String, client: &mut AuthClientLib,
-) -> Result<(), Box<dyn Error>>
+    params: &GroupParams<P>, x: &T::Secret, user: &str, client: &mut AuthClientLib,
+) -> Result<AuthResult, Box<dyn Error>>
 where
     T: ChaumPedersen<
         GroupParameters = GroupParams<P>,
@@ -129,39 +1261,95 @@ where
         Response = S,
         Challenge = S,
     >,
-    P: ByteConvertible<P> + RandomGenerator<P>,
-    S: ByteConvertible<S> + RandomGenerator<S>,
+    P: ByteConvertible<P> + RandomGenerator<P> + Clone + Send + Sync,
+    S: ByteConvertible<S> + RandomGenerator<S> + TranscriptChallenge<GroupParams<P>> + Send + Sync,
 {
-    // Client calculates the commitment.
-    let ((y1, y2, r1, r2), k) = T::commitment(params, x);
-
-    // Registers the commitment with the server.
-    client
-        .register(user.clone(), P::convert_to(&y1), P::convert_to(&y2))
-        .await?;
+    // Checks the server's identity before revealing anything to it, if configured.
+    if client.server_verification.is_some() {
+        client.verify_server_identity::<T, P, S>(params).await?;
+    }
 
-    // Creates an authentication challenge.
-    let (c, auth_id) = client
-        .create_authentication_challenge(user.clone(), P::convert_to(&r1), P::convert_to(&r2))
+    // Registers the commitment, answers the server's challenge and receives a session ID.
+    let mut transport = GrpcTransport::new(client, params);
+    let outcome = ProtocolRunner::<T>::new(params)
+        .run(&mut transport, user, x)
         .await?;
+    let (session_id, session_token) = outcome.session;
+    let p = &outcome.proof;
 
-    // Converts the challenge from bytes to the appropriate type.
-    let challenge = S::convert_from(&c)?;
+    Ok(AuthResult {
+        session_id,
+        session_token,
+        verified: outcome.verified,
+        proof: encode_proof(&p.y1, &p.y2, &p.r1, &p.r2, &p.c, &p.s)?,
+    })
+}
 
-    // Calculates the response to the challenge.
-    let s = T::challenge_response(&params, &k, &challenge, &x);
+/// Executes the Chaum-Pedersen protocol in both directions for mutual authentication.
+///
+/// The client proves knowledge of `x` to the server, and the server proves knowledge of the
+/// secret behind `server_y`, which the client must have obtained from a trusted source. The
+/// user must already be registered.
+///
+/// # Type Parameters
+/// * `T`: The type of Chaum-Pedersen protocol (either Discrete Log or Elliptic Curve).
+/// * `P`: The type of the group parameters (either `BigUint` for Discrete Log or `RistrettoPoint` for Elliptic Curve).
+/// * `S`: The type of the response and challenge (usually `BigUint`).
+///
+/// # Arguments
+/// * `params` - Group parameters for the cryptographic operations.
+/// * `x` - The secret value used in the protocol.
+/// * `user` - The username for authentication.
+/// * `server_y` - The server's public values (`y1`, `y2`).
+/// * `client` - The client object for communication with the ZKPass server.
+///
+/// # Returns
+/// Returns a `Result` containing the session ID if both sides authenticated, or an error if
+/// any part of the process fails or the server's proof does not verify.
+pub async fn execute_mutual_protocol<T, P, S>(
+    params: &GroupParams<P>, x: &T::Secret, user: &str, server_y: &(P, P),
+    client: &mut AuthClientLib,
+) -> Result<String, Box<dyn Error>>
+where
+    T: ChaumPedersen<
+        GroupParameters = GroupParams<P>,
+        CommitParameters = (P, P, P, P),
+        Response = S,
+        Challenge = S,
+    >,
+    P: ByteConvertible<P> + Clone,
+    S: ByteConvertible<S> + TranscriptChallenge<GroupParams<P>>,
+{
+    // Client calculates its commitment.
+    let ((_, _, r1, r2), k) = T::commitment(params, x);
 
-    // Sends the response to the server and receives a session ID.
-    let session_id = client
-        .verify_authentication(auth_id, S::convert_to(&s))
+    // Receives the client's challenge along with the server's commitment.
+    let (c, auth_id, nonce, challenge_bits, (server_r1, server_r2)) = client
+        .create_mutual_authentication_challenge(
+            user.to_string(),
+            P::convert_to(&r1),
+            P::convert_to(&r2),
+        )
         .await?;
+    let server_r1 = P::convert_from(&server_r1)?;
+    let server_r2 = P::convert_from(&server_r2)?;
 
-    // Displays the session ID.
-    println!("🔑 Authentication successful! 🔑");
-    println!("Session ID: {}", session_id);
+    // Answers the challenge and challenges the server in return.
+    let challenge =
+        derive_sized_nonce_challenge(params, &nonce, &S::convert_from(&c)?, challenge_bits)
+            .ok_or("Invalid challenge size")?;
+    let s = T::challenge_response(params, &k, &challenge, x);
+    let server_c = T::challenge(params);
+    let (session_id, server_s) = client
+        .mutual_authenticate(auth_id, S::convert_to(&s), nonce, S::convert_to(&server_c))
+        .await?;
 
-    // The server verifies the authentication attempt.
-    T::verify(&params, &s, &challenge, &(y1, y2, r1, r2));
+    // Verifies the server's proof against its trusted public values.
+    let (server_y1, server_y2) = server_y.clone();
+    let server_s = S::convert_from(&server_s)?;
+    if !T::verify(params, &server_s, &server_c, &(server_y1, server_y2, server_r1, server_r2)) {
+        return Err("Server failed to prove its identity".into());
+    }
 
-    Ok(())
+    Ok(session_id)
 }
@@ -1,3 +1,26 @@
+use crate::chaum_pedersen::constants::{
+    self, BRAINPOOL_P256_GROUP_PARAMS, EC25519_GROUP_PARAMS, JUBJUB_GROUP_PARAMS,
+    PALLAS_GROUP_PARAMS, RFC5114_MODP_2048_224_BIT_PARAMS, RFC5114_MODP_2048_256_BIT_PARAMS,
+    VESTA_GROUP_PARAMS,
+};
+use crate::chaum_pedersen::keygen::DeterministicKeygen;
+use crate::chaum_pedersen::GroupParams;
+use crate::rand::RandomGenerator;
+use crate::repository::session::{
+    log_session_cleanups, InMemorySessionStore, RedisSessionStore, SessionStore,
+};
+use blake2::Blake2b512;
+use bp256::r1::ProjectivePoint as BrainpoolPoint;
+use curve25519_dalek::RistrettoPoint;
+use jubjub::SubgroupPoint as JubjubPoint;
+use num_bigint::BigUint;
+use pasta_curves::pallas::Point as PallasPoint;
+use pasta_curves::vesta::Point as VestaPoint;
+use sha2::{Digest, Sha256, Sha512};
+use std::str::FromStr;
+use strum::VariantNames;
+use tracing_subscriber::filter::LevelFilter;
+
 /// An enumeration representing the types of RFC MODP groups.
 ///
 /// These types correspond to the different MODP groups defined in RFC 5114. They are used
@@ -8,7 +31,9 @@
 /// - `Rfc5114Modp_1024_160`: Represents the 1024-bit MODP group with a 160-bit prime order subgroup.
 /// - `Rfc5114Modp_2048_224`: Represents the 2048-bit MODP group with a 224-bit prime order subgroup.
 /// - `Rfc5114Modp_2048_256`: Represents the 2048-bit MODP group with a 256-bit prime order subgroup.
-#[derive(PartialEq, Debug, strum::EnumString, strum::EnumVariantNames, strum::Display)]
+#[derive(
+    PartialEq, Debug, strum::EnumString, strum::EnumVariantNames, strum::EnumIter, strum::Display,
+)]
 #[strum(serialize_all = "snake_case")]
 #[allow(non_camel_case_types)]
 pub enum RfcModpType {
@@ -26,7 +51,9 @@ pub enum RfcModpType {
 /// # Variants
 /// - `DiscreteLog`: Indicates that the protocol is based on discrete logarithms.
 /// - `EllipticCurve`: Indicates that the protocol is based on elliptic curves.
-#[derive(PartialEq, Debug, strum::EnumString, strum::EnumVariantNames, strum::Display)]
+#[derive(
+    PartialEq, Debug, strum::EnumString, strum::EnumVariantNames, strum::EnumIter, strum::Display,
+)]
 #[strum(serialize_all = "snake_case")]
 pub enum ChaumPedersenType {
     DiscreteLog,
@@ -41,10 +68,391 @@ pub enum ChaumPedersenType {
 /// # Variants
 /// - `Ec25519`: Represents the Curve25519 elliptic curve, commonly used in cryptographic
 ///   protocols for key exchange and digital signatures.
-#[derive(PartialEq, Debug, strum::EnumString, strum::EnumVariantNames, strum::Display)]
+/// - `Pallas`: Represents the Pallas curve of the Pasta cycle.
+/// - `Vesta`: Represents the Vesta curve of the Pasta cycle, whose scalar field is the base
+///   field of Pallas.
+/// - `Jubjub`: Represents the Jubjub curve, embedded in the scalar field of BLS12-381.
+/// - `BrainpoolP256`: Represents the brainpoolP256r1 curve of RFC 5639.
+#[derive(
+    PartialEq, Debug, strum::EnumString, strum::EnumVariantNames, strum::EnumIter, strum::Display,
+)]
 #[strum(serialize_all = "snake_case")]
 pub enum EllipticCurveType {
     Ec25519,
     Pallas,
     Vesta,
+    Jubjub,
+    BrainpoolP256,
+}
+
+/// An enumeration representing where the server keeps the sessions of authenticated users.
+///
+/// # Variants
+/// - `Memory`: Keeps sessions in process memory, which is lost on restart.
+/// - `Redis`: Keeps sessions in Redis, so that several servers can share them.
+#[derive(PartialEq, Debug, strum::EnumString, strum::EnumVariantNames, strum::Display)]
+#[strum(serialize_all = "snake_case")]
+pub enum SessionBackend {
+    Memory,
+    Redis,
+}
+
+/// An enumeration representing the form of the session IDs the server issues.
+///
+/// # Variants
+/// - `Uuid`: Random UUIDs, which only the server's session store can resolve.
+/// - `Jwt`: JWTs signed with the server's Ed25519 signing key.
+/// - `Paseto`: PASETO v4.local tokens, encrypted with a symmetric key.
+#[derive(
+    Clone, Copy, PartialEq, Debug, strum::EnumString, strum::EnumVariantNames, strum::Display,
+)]
+#[strum(serialize_all = "snake_case")]
+pub enum SessionTokenType {
+    Uuid,
+    Jwt,
+    Paseto,
+}
+
+/// An enumeration representing the formats the server can write its logs in.
+///
+/// # Variants
+/// - `Text`: Human-readable lines.
+/// - `Json`: One JSON object per line, for log aggregators such as Loki or Datadog.
+#[derive(PartialEq, Debug, strum::EnumString, strum::EnumVariantNames, strum::Display)]
+#[strum(serialize_all = "snake_case")]
+pub enum LogFormat {
+    Text,
+    Json,
+}
+
+/// An enumeration representing the minimum severity of the log events the server writes.
+#[derive(PartialEq, Debug, strum::EnumString, strum::EnumVariantNames, strum::Display)]
+#[strum(serialize_all = "snake_case")]
+pub enum LogLevel {
+    Trace,
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+/// An enumeration representing the hash functions secrets can be derived from passcodes with.
+///
+/// Clients must use the same algorithm when registering and authenticating, as each algorithm
+/// derives a different secret from the same passcode.
+///
+/// # Variants
+/// - `Sha256`: SHA-256, with a 32-byte digest.
+/// - `Sha512`: SHA-512, with a 64-byte digest.
+/// - `Blake2b`: BLAKE2b-512, with a 64-byte digest.
+#[derive(
+    PartialEq, Debug, Clone, Copy, strum::EnumString, strum::EnumVariantNames, strum::Display,
+)]
+#[strum(serialize_all = "snake_case")]
+pub enum HashAlgorithm {
+    Sha256,
+    Sha512,
+    Blake2b,
+}
+
+impl HashAlgorithm {
+    /// Hashes the concatenation of the given byte strings.
+    ///
+    /// # Arguments
+    /// * `parts` - The byte strings to hash, in order.
+    ///
+    /// # Returns
+    /// The digest, zero-extended to 64 bytes so that it can be reduced into any secret type
+    /// regardless of the digest width.
+    pub fn wide_digest(&self, parts: &[&[u8]]) -> [u8; 64] {
+        fn digest<D: Digest>(parts: &[&[u8]]) -> Vec<u8> {
+            let mut hasher = D::new();
+            for part in parts {
+                hasher.update(part);
+            }
+            hasher.finalize().to_vec()
+        }
+
+        let digest = match self {
+            HashAlgorithm::Sha256 => digest::<Sha256>(parts),
+            HashAlgorithm::Sha512 => digest::<Sha512>(parts),
+            HashAlgorithm::Blake2b => digest::<Blake2b512>(parts),
+        };
+        let mut wide = [0u8; 64];
+        wide[..digest.len()].copy_from_slice(&digest);
+        wide
+    }
+}
+
+/// The domain separation tag prefixed to the input of domain-separated secret hashes.
+pub const SECRET_HASH_DST: &[u8] = b"zk_pass.secret.v1";
+
+/// Hashes the provided secret string or generates a random value.
+///
+/// This function takes an optional secret string and performs one of two actions:
+/// - If a secret string is provided, it hashes the string with the given algorithm and then
+///   reduces the digest into the protocol's secret type, see
+///   [`DeterministicKeygen::secret_from_okm`]. Unless no domain is given, the hash input is
+///   prefixed with a domain separation tag and the length-prefixed domain, so the same string
+///   yields unrelated secrets in different domains.
+/// - If no secret is provided (i.e., `None`), it generates a random secret.
+///
+/// # Type Parameters
+/// * `C`: The Chaum-Pedersen protocol the secret is derived for.
+///
+/// # Parameters
+/// * `params`: The group parameters the secret is reduced for.
+/// * `secret`: An `Option<&String>` representing the secret string to hash.
+///   - `Some(&String)`: The string to hash.
+///   - `None`: Indicates that a random value should be generated instead of hashing.
+/// * `domain`: The domain the hash is scoped to, or `None` for the legacy, unseparated hash.
+/// * `algorithm`: The hash function applied to the secret string.
+///
+/// # Returns
+/// Returns a secret. The secret is either:
+/// - The reduced hash of the provided secret string, or
+/// - A randomly generated secret, if no secret string was provided.
+///
+/// # Panics
+/// This function panics if random value generation fails.
+///
+/// # Examples
+/// ```
+/// # use zk_pass::chaum_pedersen::discretelog::DiscreteLogChaumPedersen;
+/// # use zk_pass::chaum_pedersen::GroupParams;
+/// # use zk_pass::cmdutil::{hash_or_randomize_secret, HashAlgorithm, RfcModpType};
+/// let params = GroupParams::for_modp(RfcModpType::Rfc5114Modp_2048_256);
+/// let secret = Some(String::from("my_secret"));
+/// let hashed_secret = hash_or_randomize_secret::<DiscreteLogChaumPedersen>(
+///     &params, secret.as_ref(), Some("rfc5114_modp_2048_256"), HashAlgorithm::Sha512,
+/// );
+/// // hashed_secret is now the SHA-512 hash of "my_secret" in its domain, reduced modulo `q`.
+///
+/// let random_secret = hash_or_randomize_secret::<DiscreteLogChaumPedersen>(
+///     &params, None, None, HashAlgorithm::Sha512,
+/// );
+/// // random_secret is now a randomly generated secret.
+/// ```
+pub fn hash_or_randomize_secret<C>(
+    params: &C::GroupParameters, secret: Option<&String>, domain: Option<&str>,
+    algorithm: HashAlgorithm,
+) -> C::Secret
+where
+    C: DeterministicKeygen,
+    C::Secret: RandomGenerator<C::Secret>,
+{
+    match secret {
+        Some(s) => {
+            let digest = match domain {
+                Some(domain) => algorithm.wide_digest(&[
+                    SECRET_HASH_DST,
+                    &(domain.len() as u64).to_be_bytes(),
+                    domain.as_bytes(),
+                    s.as_bytes(),
+                ]),
+                None => algorithm.wide_digest(&[s.as_bytes()]),
+            };
+            C::secret_from_okm(params, &digest)
+        }
+        None => C::Secret::generate_random().expect("Failed to generate random value"),
+    }
+}
+
+impl From<LogLevel> for LevelFilter {
+    fn from(level: LogLevel) -> Self {
+        match level {
+            LogLevel::Trace => LevelFilter::TRACE,
+            LogLevel::Debug => LevelFilter::DEBUG,
+            LogLevel::Info => LevelFilter::INFO,
+            LogLevel::Warn => LevelFilter::WARN,
+            LogLevel::Error => LevelFilter::ERROR,
+        }
+    }
+}
+
+impl SessionBackend {
+    /// Creates the session store of this backend.
+    ///
+    /// When called within a Tokio runtime, the cleanups of an in-memory store are logged and
+    /// counted in [`SESSIONS_PURGED_TOTAL`](crate::repository::session::SESSIONS_PURGED_TOTAL).
+    ///
+    /// # Arguments
+    /// * `redis_url` - The Redis connection URL, only used by the `Redis` backend.
+    ///
+    /// # Returns
+    /// The session store, or an error message if the Redis URL is invalid.
+    pub fn session_store(
+        &self, redis_url: &str,
+    ) -> Result<Box<dyn SessionStore + Send + Sync>, String> {
+        match self {
+            SessionBackend::Memory => {
+                let store = InMemorySessionStore::new();
+                if let Ok(runtime) = tokio::runtime::Handle::try_current() {
+                    runtime.spawn(log_session_cleanups(store.cleanup_events()));
+                }
+                Ok(Box::new(store))
+            }
+            SessionBackend::Redis => RedisSessionStore::new(redis_url)
+                .map(|store| Box::new(store) as Box<dyn SessionStore + Send + Sync>)
+                .map_err(|e| format!("Invalid Redis URL: {}", e)),
+        }
+    }
+}
+
+impl GroupParams<BigUint> {
+    /// Returns the group parameters of the given RFC 5114 MODP group.
+    ///
+    /// # Arguments
+    /// * `modp` - The MODP group selected on the command line.
+    ///
+    /// # Returns
+    /// The parameters of the selected group.
+    #[allow(deprecated)]
+    pub fn for_modp(modp: RfcModpType) -> Self {
+        match modp {
+            RfcModpType::Rfc5114Modp_1024_160 => {
+                constants::RFC5114_MODP_1024_160_BIT_PARAMS.to_owned()
+            }
+            RfcModpType::Rfc5114Modp_2048_224 => RFC5114_MODP_2048_224_BIT_PARAMS.to_owned(),
+            RfcModpType::Rfc5114Modp_2048_256 => RFC5114_MODP_2048_256_BIT_PARAMS.to_owned(),
+        }
+    }
+}
+
+/// The group parameters of an elliptic curve, typed by the curve's point representation.
+///
+/// Each curve uses a different point type, so the parameters for a curve selected at runtime
+/// are returned as a variant of this enumeration rather than a single `GroupParams<T>`.
+///
+/// # Variants
+/// - `Ec25519`: The parameters of the Ristretto group over Curve25519.
+/// - `Pallas`: The parameters of the Pallas curve.
+/// - `Vesta`: The parameters of the Vesta curve.
+/// - `Jubjub`: The parameters of the prime-order subgroup of the Jubjub curve.
+/// - `BrainpoolP256`: The parameters of the brainpoolP256r1 curve.
+#[derive(Debug, Clone)]
+pub enum CurveParams {
+    Ec25519(GroupParams<RistrettoPoint>),
+    Pallas(GroupParams<PallasPoint>),
+    Vesta(GroupParams<VestaPoint>),
+    Jubjub(GroupParams<JubjubPoint>),
+    BrainpoolP256(GroupParams<BrainpoolPoint>),
+}
+
+impl CurveParams {
+    /// Returns the group parameters of the given elliptic curve.
+    ///
+    /// # Arguments
+    /// * `curve` - The elliptic curve selected on the command line.
+    ///
+    /// # Returns
+    /// The parameters of the selected curve, wrapped in the matching variant.
+    pub fn for_curve(curve: EllipticCurveType) -> Self {
+        match curve {
+            EllipticCurveType::Ec25519 => CurveParams::Ec25519(EC25519_GROUP_PARAMS.to_owned()),
+            EllipticCurveType::Pallas => CurveParams::Pallas(PALLAS_GROUP_PARAMS.to_owned()),
+            EllipticCurveType::Vesta => CurveParams::Vesta(VESTA_GROUP_PARAMS.to_owned()),
+            EllipticCurveType::Jubjub => CurveParams::Jubjub(JUBJUB_GROUP_PARAMS.to_owned()),
+            EllipticCurveType::BrainpoolP256 => {
+                CurveParams::BrainpoolP256(BRAINPOOL_P256_GROUP_PARAMS.to_owned())
+            }
+        }
+    }
+}
+
+/// Parses a command-line value into an enumeration, listing the accepted values on failure.
+///
+/// # Arguments
+/// * `s` - The value passed on the command line.
+///
+/// # Returns
+/// The parsed value, or an error message naming the rejected value and every accepted one.
+pub fn parse_enum_or_help<T: FromStr + VariantNames>(s: &str) -> Result<T, String> {
+    T::from_str(s).map_err(|_| {
+        format!("Invalid value '{}'; accepted values are: {}", s, T::VARIANTS.join(", "))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use strum::IntoEnumIterator;
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_for_modp_returns_matching_group() {
+        for (modp, expected) in [
+            (RfcModpType::Rfc5114Modp_1024_160, &*constants::RFC5114_MODP_1024_160_BIT_PARAMS),
+            (RfcModpType::Rfc5114Modp_2048_224, &*RFC5114_MODP_2048_224_BIT_PARAMS),
+            (RfcModpType::Rfc5114Modp_2048_256, &*RFC5114_MODP_2048_256_BIT_PARAMS),
+        ] {
+            let params = GroupParams::for_modp(modp);
+            assert_eq!(
+                (params.g, params.h, params.p, params.q),
+                (expected.g.clone(), expected.h.clone(), expected.p.clone(), expected.q.clone())
+            );
+        }
+    }
+
+    #[test]
+    fn test_for_curve_returns_matching_group() {
+        match CurveParams::for_curve(EllipticCurveType::Ec25519) {
+            CurveParams::Ec25519(params) => {
+                assert_eq!((params.g, params.h), (EC25519_GROUP_PARAMS.g, EC25519_GROUP_PARAMS.h))
+            }
+            other => panic!("unexpected parameters {:?}", other),
+        }
+        match CurveParams::for_curve(EllipticCurveType::Pallas) {
+            CurveParams::Pallas(params) => {
+                assert_eq!((params.g, params.h), (PALLAS_GROUP_PARAMS.g, PALLAS_GROUP_PARAMS.h))
+            }
+            other => panic!("unexpected parameters {:?}", other),
+        }
+        match CurveParams::for_curve(EllipticCurveType::Vesta) {
+            CurveParams::Vesta(params) => {
+                assert_eq!((params.g, params.h), (VESTA_GROUP_PARAMS.g, VESTA_GROUP_PARAMS.h))
+            }
+            other => panic!("unexpected parameters {:?}", other),
+        }
+        match CurveParams::for_curve(EllipticCurveType::Jubjub) {
+            CurveParams::Jubjub(params) => {
+                assert_eq!((params.g, params.h), (JUBJUB_GROUP_PARAMS.g, JUBJUB_GROUP_PARAMS.h))
+            }
+            other => panic!("unexpected parameters {:?}", other),
+        }
+        match CurveParams::for_curve(EllipticCurveType::BrainpoolP256) {
+            CurveParams::BrainpoolP256(params) => assert_eq!(
+                (params.g, params.h),
+                (BRAINPOOL_P256_GROUP_PARAMS.g, BRAINPOOL_P256_GROUP_PARAMS.h)
+            ),
+            other => panic!("unexpected parameters {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_enum_or_help() {
+        assert_eq!(
+            parse_enum_or_help::<EllipticCurveType>("pallas"),
+            Ok(EllipticCurveType::Pallas)
+        );
+        assert_eq!(
+            parse_enum_or_help::<EllipticCurveType>("ed25519").unwrap_err(),
+            "Invalid value 'ed25519'; accepted values are: ec25519, pallas, vesta, jubjub, brainpool_p256"
+        );
+    }
+
+    #[test]
+    fn test_iterated_variants_round_trip() {
+        let curves: Vec<String> = EllipticCurveType::iter().map(|t| t.to_string()).collect();
+        assert_eq!(curves, EllipticCurveType::VARIANTS);
+        assert!(curves.contains(&"vesta".to_string()));
+        for curve in EllipticCurveType::iter() {
+            assert_eq!(EllipticCurveType::from_str(&curve.to_string()), Ok(curve));
+        }
+
+        let modps: Vec<String> = RfcModpType::iter().map(|t| t.to_string()).collect();
+        assert_eq!(modps, RfcModpType::VARIANTS);
+        let types: Vec<String> = ChaumPedersenType::iter().map(|t| t.to_string()).collect();
+        assert_eq!(types, ChaumPedersenType::VARIANTS);
+    }
 }
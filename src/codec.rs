@@ -0,0 +1,130 @@
+//! # Wire Codec Module
+//!
+//! This module provides a canonical, length-prefixed encoding for sequences of values, such as
+//! the commitment tuple `(y1, y2, r1, r2)`. Every value is serialized with [`ByteConvertible`]
+//! and prefixed with its big-endian `u16` length, so a sequence of values can be stored or sent
+//! as a single byte string and split back apart unambiguously. Values longer than `u16::MAX`
+//! bytes cannot be encoded.
+
+use crate::conversion::{ByteConvertible, ConversionError};
+use alloc::vec::Vec;
+
+/// Encodes a sequence of values into a single length-prefixed byte string.
+///
+/// # Arguments
+/// * `points` - The values to encode, in order.
+///
+/// # Returns
+/// The concatenation of each value's encoding, each prefixed with its big-endian `u16` length,
+/// or `ConversionError::InvalidLength` if a value encodes to more than `u16::MAX` bytes.
+pub fn encode_points<T: ByteConvertible<T>>(points: &[&T]) -> Result<Vec<u8>, ConversionError> {
+    let mut bytes = Vec::new();
+    for point in points {
+        push_field(&mut bytes, &T::convert_to(point))?;
+    }
+    Ok(bytes)
+}
+
+/// Decodes exactly `count` values from the layout produced by [`encode_points`].
+///
+/// # Arguments
+/// * `bytes` - The encoded values.
+/// * `count` - The number of values expected in `bytes`.
+///
+/// # Returns
+/// The decoded values, or a `ConversionError` if the bytes are truncated, have trailing data
+/// or contain an invalid value.
+pub fn decode_points<T: ByteConvertible<T>>(
+    mut bytes: &[u8], count: usize,
+) -> Result<Vec<T>, ConversionError> {
+    let mut points = Vec::with_capacity(count.min(bytes.len() / 2));
+    for _ in 0..count {
+        points.push(T::convert_from(read_field(&mut bytes)?)?);
+    }
+    if !bytes.is_empty() {
+        return Err(ConversionError::InvalidLength);
+    }
+    Ok(points)
+}
+
+/// Appends a field to a compact encoding, prefixed with its big-endian `u16` length.
+///
+/// # Returns
+/// `Ok(())`, or `ConversionError::InvalidLength` if the field is longer than `u16::MAX` bytes,
+/// whose length the prefix cannot hold.
+pub(crate) fn push_field(bytes: &mut Vec<u8>, field: &[u8]) -> Result<(), ConversionError> {
+    let len = u16::try_from(field.len()).map_err(|_| ConversionError::InvalidLength)?;
+    bytes.extend_from_slice(&len.to_be_bytes());
+    bytes.extend_from_slice(field);
+    Ok(())
+}
+
+/// Reads a length-prefixed field from the front of a compact encoding, advancing past it.
+pub(crate) fn read_field<'a>(bytes: &mut &'a [u8]) -> Result<&'a [u8], ConversionError> {
+    let len = bytes.get(..2).ok_or(ConversionError::InvalidLength)?;
+    let len = u16::from_be_bytes([len[0], len[1]]) as usize;
+    let field = bytes
+        .get(2..2 + len)
+        .ok_or(ConversionError::InvalidLength)?;
+    *bytes = &bytes[2 + len..];
+    Ok(field)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rand::RandomGenerator;
+    use curve25519_dalek::RistrettoPoint;
+
+    #[test]
+    fn test_commitment_tuple_round_trip() {
+        let points: Vec<RistrettoPoint> = (0..4)
+            .map(|_| RistrettoPoint::generate_random().unwrap())
+            .collect();
+        let refs: Vec<&RistrettoPoint> = points.iter().collect();
+        let bytes = encode_points(&refs).unwrap();
+        assert_eq!(bytes.len(), 4 * (2 + 32));
+        assert_eq!(decode_points::<RistrettoPoint>(&bytes, 4).unwrap(), points);
+    }
+
+    #[test]
+    fn test_truncated_input_rejected() {
+        let y1 = RistrettoPoint::generate_random().unwrap();
+        let y2 = RistrettoPoint::generate_random().unwrap();
+        let bytes = encode_points(&[&y1, &y2]).unwrap();
+        for len in 0..bytes.len() {
+            assert_eq!(
+                decode_points::<RistrettoPoint>(&bytes[..len], 2).unwrap_err(),
+                ConversionError::InvalidLength
+            );
+        }
+    }
+
+    #[test]
+    fn test_trailing_and_invalid_data_rejected() {
+        let y1 = RistrettoPoint::generate_random().unwrap();
+        let bytes = encode_points(&[&y1, &y1]).unwrap();
+        assert_eq!(
+            decode_points::<RistrettoPoint>(&bytes, 1).unwrap_err(),
+            ConversionError::InvalidLength
+        );
+
+        let mut corrupt = bytes.clone();
+        corrupt[2..34].copy_from_slice(&[0xff; 32]);
+        assert_eq!(
+            decode_points::<RistrettoPoint>(&corrupt, 2).unwrap_err(),
+            ConversionError::InvalidEncoding
+        );
+    }
+
+    #[test]
+    fn test_oversized_field_rejected() {
+        let mut bytes = Vec::new();
+        push_field(&mut bytes, &[0; u16::MAX as usize]).unwrap();
+        assert_eq!(
+            push_field(&mut bytes, &[0; u16::MAX as usize + 1]).unwrap_err(),
+            ConversionError::InvalidLength
+        );
+        assert_eq!(bytes.len(), 2 + u16::MAX as usize);
+    }
+}
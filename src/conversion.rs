@@ -1,4 +1,31 @@
-use std::error::Error;
+use crate::codec::{push_field, read_field};
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+
+/// An error raised when a byte array cannot be converted back into an object.
+///
+/// # Variants
+/// - `InvalidLength`: The byte array does not have the length required by the target type.
+/// - `InvalidEncoding`: The byte array has the right length but does not encode a valid value,
+///   for example a point that is not on the curve.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConversionError {
+    InvalidLength,
+    InvalidEncoding,
+}
+
+impl fmt::Display for ConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConversionError::InvalidLength => write!(f, "Invalid bytes length"),
+            ConversionError::InvalidEncoding => write!(f, "Invalid bytes encoding"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ConversionError {}
 
 /// A trait for converting types to and from byte representations.
 ///
@@ -23,8 +50,398 @@ pub trait ByteConvertible<T> {
     ///
     /// # Returns
     /// A `Result` which is `Ok` containing the constructed object if successful,
-    /// or an `Err` containing an error if the conversion failed.
-    fn convert_from(bytes: &[u8]) -> Result<T, Box<dyn Error>>
+    /// or an `Err` containing a `ConversionError` if the conversion failed.
+    fn convert_from(bytes: &[u8]) -> Result<T, ConversionError>
     where
         Self: Sized;
+
+    /// Converts the provided object to a lowercase hex string.
+    ///
+    /// # Arguments
+    /// - `t`: A reference to the object to be converted.
+    ///
+    /// # Returns
+    /// A `String` containing the hex encoding of the object's byte array.
+    fn to_hex(t: &T) -> String {
+        hex::encode(Self::convert_to(t))
+    }
+
+    /// Constructs an object from a hex string.
+    ///
+    /// # Arguments
+    /// - `s`: The hex encoding of the object's byte array.
+    ///
+    /// # Returns
+    /// A `Result` which is `Ok` containing the constructed object if successful,
+    /// or an `Err` containing a `ConversionError` if the string is not valid hex
+    /// or the decoded bytes cannot be converted.
+    fn from_hex(s: &str) -> Result<T, ConversionError>
+    where
+        Self: Sized,
+    {
+        let bytes = hex::decode(s).map_err(|_| ConversionError::InvalidEncoding)?;
+        Self::convert_from(&bytes)
+    }
+}
+
+/// Implementation of `ByteConvertible` for raw byte strings, which convert to themselves, e.g.
+/// to store opaque digests in a user data store.
+impl ByteConvertible<Vec<u8>> for Vec<u8> {
+    fn convert_to(t: &Vec<u8>) -> Vec<u8> {
+        t.clone()
+    }
+
+    fn convert_from(bytes: &[u8]) -> Result<Vec<u8>, ConversionError> {
+        Ok(bytes.to_vec())
+    }
+}
+
+/// Encodes a value as a MessagePack binary string of its byte representation.
+///
+/// Unlike JSON, which carries bytes as base64 strings, MessagePack adds only a few bytes of
+/// framing to the value.
+///
+/// # Arguments
+/// - `value`: The value to encode.
+///
+/// # Returns
+/// A `Result` which is `Ok` containing the encoded value, or an `Err` containing
+/// `ZkPassError::Unspecified` if the value cannot be encoded.
+#[cfg(feature = "std")]
+pub fn encode_msgpack<T: ByteConvertible<T>>(
+    value: &T,
+) -> Result<Vec<u8>, crate::client::ZkPassError> {
+    rmp_serde::to_vec(serde_bytes::Bytes::new(&T::convert_to(value)))
+        .map_err(|_| crate::client::ZkPassError::Unspecified)
+}
+
+/// Decodes a value from the encoding produced by [`encode_msgpack`].
+///
+/// # Arguments
+/// - `bytes`: The encoded value.
+///
+/// # Returns
+/// A `Result` which is `Ok` containing the decoded value, or an `Err` containing
+/// `ZkPassError::InvalidParameter` if the bytes are not a MessagePack binary string or do not
+/// encode a valid value.
+#[cfg(feature = "std")]
+pub fn decode_msgpack<T: ByteConvertible<T>>(
+    bytes: &[u8],
+) -> Result<T, crate::client::ZkPassError> {
+    let value: serde_bytes::ByteBuf =
+        rmp_serde::from_slice(bytes).map_err(|_| crate::client::ZkPassError::InvalidParameter)?;
+    T::convert_from(&value).map_err(|_| crate::client::ZkPassError::InvalidParameter)
+}
+
+/// The version byte written at the start of every encoding produced by [`encode_proof`].
+pub const PROOF_FORMAT_VERSION: u8 = 1;
+
+/// The decoded contents of a proof produced by [`encode_proof`].
+///
+/// # Fields
+/// - `y1`, `y2`: The prover's public values.
+/// - `r1`, `r2`: The commitment values.
+/// - `c`: The challenge.
+/// - `s`: The response to the challenge.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProofData<T, S> {
+    pub y1: T,
+    pub y2: T,
+    pub r1: T,
+    pub r2: T,
+    pub c: S,
+    pub s: S,
+}
+
+impl<T: ByteConvertible<T>, S: ByteConvertible<S>> fmt::Display for ProofData<T, S> {
+    /// Formats the proof as `y1=<hex> y2=<hex> r1=<hex> r2=<hex> c=<hex> s=<hex>`, with each
+    /// component hex-encoded as by [`ByteConvertible::to_hex`].
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_hex_fields(
+            f,
+            &[
+                ("y1", T::to_hex(&self.y1)),
+                ("y2", T::to_hex(&self.y2)),
+                ("r1", T::to_hex(&self.r1)),
+                ("r2", T::to_hex(&self.r2)),
+                ("c", S::to_hex(&self.c)),
+                ("s", S::to_hex(&self.s)),
+            ],
+        )
+    }
+}
+
+/// The prover's public values and commitment values, as returned by
+/// [`ChaumPedersen::commitment`](crate::chaum_pedersen::ChaumPedersen::commitment).
+///
+/// Wraps the `(y1, y2, r1, r2)` commitment tuple so it can be displayed.
+///
+/// # Fields
+/// - `y1`, `y2`: The prover's public values.
+/// - `r1`, `r2`: The commitment values.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Commitment<T> {
+    pub y1: T,
+    pub y2: T,
+    pub r1: T,
+    pub r2: T,
+}
+
+impl<T> From<(T, T, T, T)> for Commitment<T> {
+    fn from((y1, y2, r1, r2): (T, T, T, T)) -> Self {
+        Commitment { y1, y2, r1, r2 }
+    }
+}
+
+impl<T: ByteConvertible<T>> fmt::Display for Commitment<T> {
+    /// Formats the commitment as `y1=<hex> y2=<hex> r1=<hex> r2=<hex>`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_hex_fields(
+            f,
+            &[
+                ("y1", T::to_hex(&self.y1)),
+                ("y2", T::to_hex(&self.y2)),
+                ("r1", T::to_hex(&self.r1)),
+                ("r2", T::to_hex(&self.r2)),
+            ],
+        )
+    }
+}
+
+/// Writes hex-encoded fields as space-separated `name=value` pairs.
+fn write_hex_fields(f: &mut fmt::Formatter<'_>, fields: &[(&str, String)]) -> fmt::Result {
+    for (i, (name, value)) in fields.iter().enumerate() {
+        if i > 0 {
+            f.write_str(" ")?;
+        }
+        write!(f, "{}={}", name, value)?;
+    }
+    Ok(())
+}
+
+/// Encodes a commitment and its challenge and response into a versioned binary format.
+///
+/// The encoding starts with [`PROOF_FORMAT_VERSION`], followed by `y1`, `y2`, `r1`, `r2`, `c`
+/// and `s` in the length-prefixed layout of the [`crate::codec`] module.
+///
+/// # Arguments
+/// - `y1`, `y2`: The prover's public values.
+/// - `r1`, `r2`: The commitment values.
+/// - `c`: The challenge.
+/// - `s`: The response to the challenge.
+///
+/// # Returns
+/// A `Result` which is `Ok` containing the encoded proof, or an `Err` containing
+/// `ConversionError::InvalidLength` if a value encodes to more than `u16::MAX` bytes.
+pub fn encode_proof<T: ByteConvertible<T>, S: ByteConvertible<S>>(
+    y1: &T, y2: &T, r1: &T, r2: &T, c: &S, s: &S,
+) -> Result<Vec<u8>, ConversionError> {
+    let mut bytes = Vec::from([PROOF_FORMAT_VERSION]);
+    for field in [
+        T::convert_to(y1),
+        T::convert_to(y2),
+        T::convert_to(r1),
+        T::convert_to(r2),
+        S::convert_to(c),
+        S::convert_to(s),
+    ] {
+        push_field(&mut bytes, &field)?;
+    }
+    Ok(bytes)
+}
+
+/// Decodes a proof from the layout produced by [`encode_proof`].
+///
+/// # Arguments
+/// - `bytes`: The encoded proof.
+///
+/// # Returns
+/// A `Result` which is `Ok` containing the decoded proof, or an `Err` containing a
+/// `ConversionError` if the version is unknown, the bytes are truncated or have trailing data,
+/// or a field does not encode a valid value.
+pub fn decode_proof<T: ByteConvertible<T>, S: ByteConvertible<S>>(
+    bytes: &[u8],
+) -> Result<ProofData<T, S>, ConversionError> {
+    let (version, mut bytes) = bytes.split_first().ok_or(ConversionError::InvalidLength)?;
+    if *version != PROOF_FORMAT_VERSION {
+        return Err(ConversionError::InvalidEncoding);
+    }
+    let proof = ProofData {
+        y1: T::convert_from(read_field(&mut bytes)?)?,
+        y2: T::convert_from(read_field(&mut bytes)?)?,
+        r1: T::convert_from(read_field(&mut bytes)?)?,
+        r2: T::convert_from(read_field(&mut bytes)?)?,
+        c: S::convert_from(read_field(&mut bytes)?)?,
+        s: S::convert_from(read_field(&mut bytes)?)?,
+    };
+    if !bytes.is_empty() {
+        return Err(ConversionError::InvalidLength);
+    }
+    Ok(proof)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chaum_pedersen::brainpool::BrainpoolChaumPedersen;
+    use crate::chaum_pedersen::constants::{
+        BRAINPOOL_P256_GROUP_PARAMS, EC25519_GROUP_PARAMS, JUBJUB_GROUP_PARAMS,
+        PALLAS_GROUP_PARAMS, RFC5114_MODP_2048_256_BIT_PARAMS, VESTA_GROUP_PARAMS,
+    };
+    use crate::chaum_pedersen::curve25519::Curve25519ChaumPedersen;
+    use crate::chaum_pedersen::discretelog::DiscreteLogChaumPedersen;
+    use crate::chaum_pedersen::jubjub::JubjubChaumPedersen;
+    use crate::chaum_pedersen::pallas::PallasCurveChaumPedersen;
+    use crate::chaum_pedersen::vesta::VestaCurveChaumPedersen;
+    use crate::chaum_pedersen::{ChaumPedersen, GroupParams};
+    use crate::rand::RandomGenerator;
+    use core::fmt::Debug;
+    use curve25519_dalek::{RistrettoPoint, Scalar};
+    use rand_core::{OsRng, RngCore};
+
+    /// Runs the protocol and checks that the resulting proof survives an encode/decode round trip.
+    fn assert_round_trip<C, T, S>(params: &GroupParams<T>)
+    where
+        C: ChaumPedersen<
+            Secret = S,
+            Response = S,
+            Challenge = S,
+            GroupParameters = GroupParams<T>,
+            CommitParameters = (T, T, T, T),
+        >,
+        T: ByteConvertible<T> + PartialEq + Debug,
+        S: ByteConvertible<S> + RandomGenerator<S> + PartialEq + Debug,
+    {
+        let x = S::generate_random().unwrap();
+        let ((y1, y2, r1, r2), k) = C::commitment(params, &x);
+        let c = C::challenge(params);
+        let s = C::challenge_response(params, &k, &c, &x);
+
+        let bytes = encode_proof(&y1, &y2, &r1, &r2, &c, &s).unwrap();
+        assert_eq!(bytes[0], PROOF_FORMAT_VERSION);
+        let proof = decode_proof::<T, S>(&bytes).unwrap();
+        assert_eq!(
+            proof,
+            ProofData {
+                y1,
+                y2,
+                r1,
+                r2,
+                c,
+                s
+            }
+        );
+    }
+
+    #[test]
+    fn test_proof_round_trip_all_groups() {
+        assert_round_trip::<DiscreteLogChaumPedersen, _, _>(&RFC5114_MODP_2048_256_BIT_PARAMS);
+        assert_round_trip::<Curve25519ChaumPedersen, _, _>(&EC25519_GROUP_PARAMS);
+        assert_round_trip::<PallasCurveChaumPedersen, _, _>(&PALLAS_GROUP_PARAMS);
+        assert_round_trip::<VestaCurveChaumPedersen, _, _>(&VESTA_GROUP_PARAMS);
+        assert_round_trip::<JubjubChaumPedersen, _, _>(&JUBJUB_GROUP_PARAMS);
+        assert_round_trip::<BrainpoolChaumPedersen, _, _>(&BRAINPOOL_P256_GROUP_PARAMS);
+    }
+
+    #[test]
+    fn test_decode_proof_rejects_bad_version_and_truncation() {
+        let y = RistrettoPoint::generate_random().unwrap();
+        let c = Scalar::generate_random().unwrap();
+        let mut bytes = encode_proof(&y, &y, &y, &y, &c, &c).unwrap();
+        for len in 0..bytes.len() {
+            assert!(decode_proof::<RistrettoPoint, Scalar>(&bytes[..len]).is_err());
+        }
+        assert_eq!(
+            decode_proof::<RistrettoPoint, Scalar>(&[bytes.as_slice(), &[0]].concat()).unwrap_err(),
+            ConversionError::InvalidLength
+        );
+        bytes[0] = PROOF_FORMAT_VERSION + 1;
+        assert_eq!(
+            decode_proof::<RistrettoPoint, Scalar>(&bytes).unwrap_err(),
+            ConversionError::InvalidEncoding
+        );
+    }
+
+    /// Parses `name=<hex>` pairs back into the values they display.
+    fn parse_hex_fields<T: ByteConvertible<T>>(display: &str, names: &[&str]) -> Vec<T> {
+        let fields: Vec<_> = display.split(' ').collect();
+        assert_eq!(fields.len(), names.len());
+        fields
+            .iter()
+            .zip(names)
+            .map(|(field, name)| {
+                let hex = field.strip_prefix(&format!("{}=", name)).unwrap();
+                T::from_hex(hex).unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_display_parses_back_from_hex() {
+        let params = EC25519_GROUP_PARAMS.to_owned();
+        let x = Scalar::generate_random().unwrap();
+        let (commitment, k) = Curve25519ChaumPedersen::commitment(&params, &x);
+        let commitment = Commitment::from(commitment);
+        let c = Curve25519ChaumPedersen::challenge(&params);
+        let s = Curve25519ChaumPedersen::challenge_response(&params, &k, &c, &x);
+
+        let points: Vec<RistrettoPoint> =
+            parse_hex_fields(&commitment.to_string(), &["y1", "y2", "r1", "r2"]);
+        assert_eq!(points, [commitment.y1, commitment.y2, commitment.r1, commitment.r2]);
+
+        let proof = ProofData {
+            y1: commitment.y1,
+            y2: commitment.y2,
+            r1: commitment.r1,
+            r2: commitment.r2,
+            c,
+            s,
+        };
+        let display = proof.to_string();
+        assert!(display.starts_with(&commitment.to_string()));
+        let (points, scalars) = display.split_at(display.find(" c=").unwrap());
+        let points: Vec<RistrettoPoint> = parse_hex_fields(points, &["y1", "y2", "r1", "r2"]);
+        let scalars: Vec<Scalar> = parse_hex_fields(&scalars[1..], &["c", "s"]);
+        assert_eq!(points, [proof.y1, proof.y2, proof.r1, proof.r2]);
+        assert_eq!(scalars, [c, s]);
+    }
+
+    #[test]
+    fn test_decode_proof_never_panics_on_arbitrary_input() {
+        let mut rng = OsRng;
+        for i in 0..2000 {
+            let mut bytes = vec![0u8; (rng.next_u32() % 256) as usize];
+            rng.fill_bytes(&mut bytes);
+            // Keeps the version byte valid half the time so the field parser is exercised.
+            if i % 2 == 0 && !bytes.is_empty() {
+                bytes[0] = PROOF_FORMAT_VERSION;
+            }
+            let _ = decode_proof::<RistrettoPoint, Scalar>(&bytes);
+            let _ = decode_proof::<num_bigint::BigUint, num_bigint::BigUint>(&bytes);
+            let _ =
+                decode_proof::<pasta_curves::pallas::Point, pasta_curves::pallas::Scalar>(&bytes);
+            let _ = decode_proof::<jubjub::SubgroupPoint, jubjub::Scalar>(&bytes);
+            let _ = decode_proof::<bp256::r1::ProjectivePoint, bp256::Scalar>(&bytes);
+        }
+    }
+    #[test]
+    fn test_msgpack_round_trip() {
+        use crate::client::ZkPassError;
+
+        let point = RistrettoPoint::generate_random().unwrap();
+        let bytes = encode_msgpack(&point).unwrap();
+        // A binary string with a one-byte length prefix.
+        assert_eq!(bytes.len(), RistrettoPoint::convert_to(&point).len() + 2);
+        assert_eq!(decode_msgpack::<RistrettoPoint>(&bytes).unwrap(), point);
+        let scalar = Scalar::generate_random().unwrap();
+        assert_eq!(decode_msgpack::<Scalar>(&encode_msgpack(&scalar).unwrap()).unwrap(), scalar);
+
+        assert_eq!(
+            decode_msgpack::<RistrettoPoint>(&bytes[..10]),
+            Err(ZkPassError::InvalidParameter)
+        );
+        let short = rmp_serde::to_vec(serde_bytes::Bytes::new(&[0xff; 31])).unwrap();
+        assert_eq!(decode_msgpack::<Scalar>(&short), Err(ZkPassError::InvalidParameter));
+        assert_eq!(decode_msgpack::<Scalar>(&[0x01]), Err(ZkPassError::InvalidParameter));
+    }
 }
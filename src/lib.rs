@@ -31,6 +31,9 @@
 //! - `cmdutil`: Utility module that aids in command-line operations and parameter handling.
 //!   It simplifies the process of parsing command-line arguments and configuring the client or server.
 //!
+//! - `codec`: Provides a canonical length-prefixed encoding for sequences of points and scalars,
+//!   such as commitment tuples.
+//!
 //! - `conversion`: Offers functionality to convert between different data types and formats.
 //!   This module is essential for handling cryptographic operations and data serialization/deserialization.
 //!
@@ -41,6 +44,18 @@
 //!
 //! - `repository`: An internal module that may contain data storage and retrieval logic, potentially for managing user data or cryptographic keys.
 //!
+//! ## `no_std` Support
+//!
+//! The `chaum_pedersen`, `codec`, `conversion` and `rand` modules build without the standard library.
+//! Disable the default `std` feature and enable `alloc` to use the prover and verifier on
+//! embedded targets:
+//!
+//! ```bash
+//! cargo build --lib --no-default-features --features alloc
+//! ```
+//!
+//! The `client`, `cmdutil`, `service` and `repository` modules require the `std` feature.
+//!
 //! ## Usage
 //!
 //! This crate can be integrated into applications that require secure authentication mechanisms.
@@ -59,23 +74,50 @@
 //!
 //! This project is licensed under the [MIT License](LICENSE).
 
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
 /// Implements the Chaum-Pedersen zero-knowledge proof protocol.
 pub mod chaum_pedersen;
 
 /// Handles client-side operations and interactions.
+#[cfg(feature = "std")]
 pub mod client;
 
 /// Utilities for command line argument parsing and handling.
+#[cfg(feature = "std")]
 pub mod cmdutil;
 
+/// Length-prefixed encoding of value sequences.
+pub mod codec;
+
 /// Functions for type conversions and data formatting.
 pub mod conversion;
 
 /// Cryptographically secure random number generation utilities.
 pub mod rand;
 
+/// Pluggable sources of the challenges a service issues.
+#[cfg(feature = "std")]
+pub mod challenge;
+
 /// Core services and business logic implementation.
+#[cfg(feature = "std")]
 pub mod service;
 
 /// Data storage and retrieval mechanisms.
-mod repository;
+#[cfg(feature = "std")]
+pub mod repository;
+
+/// Self-contained session tokens, as JWTs or PASETO tokens.
+#[cfg(feature = "std")]
+pub mod token;
+
+/// HMAC-sealed tokens for resuming interrupted authentications.
+#[cfg(feature = "std")]
+pub mod resume;
+
+/// The gRPC service over encrypted, mutually authenticated Noise channels.
+#[cfg(feature = "std")]
+pub mod noise;
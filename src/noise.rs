@@ -0,0 +1,466 @@
+//! # Noise Module
+//!
+//! This module runs the ZKPass gRPC service over a [Noise](https://noiseprotocol.org) channel,
+//! as used by WireGuard and Signal.
+//!
+//! Before any gRPC message is exchanged, client and server perform a `Noise_XX` handshake with
+//! their static X25519 keys. The client checks the server's static key against the one it
+//! expects, which prevents man-in-the-middle attacks without certificates, and both sides get
+//! forward secrecy from their ephemeral keys. Every gRPC message then travels encrypted in Noise
+//! transport messages.
+//!
+//! Servers accept Noise connections with [`noise_incoming`], and clients connect with
+//! [`NoiseAuthClient::connect`].
+
+use crate::chaum_pedersen::transcript::TranscriptChallenge;
+use crate::chaum_pedersen::{ChaumPedersen, GroupParams};
+use crate::client::{execute_protocol, AuthClientLib, AuthResult};
+use crate::conversion::ByteConvertible;
+use crate::rand::RandomGenerator;
+use log::{debug, error};
+use snow::{Builder, Keypair, StatelessTransportState};
+use std::fmt;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use tokio::io::{
+    AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, DuplexStream, ReadBuf, ReadHalf, WriteHalf,
+};
+use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::codegen::http::Uri;
+use tonic::transport::server::{Connected, TcpConnectInfo};
+use tonic::transport::Endpoint;
+
+/// The Noise protocol run by client and server: the `XX` handshake pattern, which transmits
+/// both static keys encrypted, over X25519, ChaCha20-Poly1305 and BLAKE2s.
+pub const NOISE_PATTERN: &str = "Noise_XX_25519_ChaChaPoly_BLAKE2s";
+
+/// The maximum length of a Noise message, set by the Noise specification.
+const MAX_MESSAGE_LEN: usize = 65535;
+
+/// The length of the authentication tag of every encrypted Noise message.
+const TAG_LEN: usize = 16;
+
+/// The maximum number of plaintext bytes carried by one transport message.
+const MAX_PAYLOAD_LEN: usize = MAX_MESSAGE_LEN - TAG_LEN;
+
+/// The number of completed handshakes waiting for the server to accept them.
+const ACCEPT_BACKLOG: usize = 128;
+
+/// The reasons a Noise channel cannot be established.
+#[derive(Debug)]
+pub enum NoiseError {
+    /// The connection failed or was closed during the handshake.
+    Io(std::io::Error),
+    /// The handshake failed, e.g. because a message did not decrypt.
+    Handshake(snow::Error),
+    /// The server proved a different static key than the one the client expects.
+    UnexpectedServerKey,
+    /// The gRPC channel over the Noise connection could not be established.
+    Transport(tonic::transport::Error),
+}
+
+impl fmt::Display for NoiseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NoiseError::Io(e) => write!(f, "Noise connection failed: {}", e),
+            NoiseError::Handshake(e) => write!(f, "Noise handshake failed: {}", e),
+            NoiseError::UnexpectedServerKey => {
+                write!(f, "Server presented an unexpected static key")
+            }
+            NoiseError::Transport(e) => write!(f, "Noise transport failed: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for NoiseError {}
+
+impl From<std::io::Error> for NoiseError {
+    fn from(e: std::io::Error) -> Self {
+        NoiseError::Io(e)
+    }
+}
+
+impl From<tonic::transport::Error> for NoiseError {
+    fn from(e: tonic::transport::Error) -> Self {
+        NoiseError::Transport(e)
+    }
+}
+
+impl From<snow::Error> for NoiseError {
+    fn from(e: snow::Error) -> Self {
+        NoiseError::Handshake(e)
+    }
+}
+
+/// Generates a static X25519 key pair for the [`NOISE_PATTERN`].
+///
+/// # Returns
+/// The key pair, whose public key the server hands out to its clients.
+pub fn generate_keypair() -> Result<Keypair, NoiseError> {
+    Ok(Builder::new(NOISE_PATTERN.parse()?).generate_keypair()?)
+}
+
+/// Writes a Noise message prefixed with its big-endian `u16` length.
+async fn write_frame<W: AsyncWrite + Unpin>(writer: &mut W, message: &[u8]) -> std::io::Result<()> {
+    writer
+        .write_all(&(message.len() as u16).to_be_bytes())
+        .await?;
+    writer.write_all(message).await?;
+    writer.flush().await
+}
+
+/// Reads a Noise message written by [`write_frame`] into `buf`.
+///
+/// # Returns
+/// The length of the message, or `None` if the connection was closed between messages.
+async fn read_frame<R: AsyncRead + Unpin>(
+    reader: &mut R, buf: &mut [u8],
+) -> std::io::Result<Option<usize>> {
+    let mut len = [0u8; 2];
+    match reader.read_exact(&mut len).await {
+        Ok(_) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e),
+    }
+    let len = u16::from_be_bytes(len) as usize;
+    reader.read_exact(&mut buf[..len]).await?;
+    Ok(Some(len))
+}
+
+/// Reads the next handshake message, failing if the connection is closed.
+async fn read_handshake_frame(stream: &mut TcpStream, buf: &mut [u8]) -> Result<usize, NoiseError> {
+    read_frame(stream, buf).await?.ok_or_else(|| {
+        NoiseError::Io(std::io::Error::new(
+            std::io::ErrorKind::UnexpectedEof,
+            "Connection closed during the Noise handshake",
+        ))
+    })
+}
+
+/// Performs the initiator side of the handshake, checking the responder's static key before
+/// revealing the initiator's.
+///
+/// # Arguments
+/// * `stream` - The connection to the responder.
+/// * `private_key` - The initiator's static private key.
+/// * `expected_remote` - The static public key the responder must prove.
+async fn initiate(
+    stream: &mut TcpStream, private_key: &[u8], expected_remote: &[u8],
+) -> Result<StatelessTransportState, NoiseError> {
+    let mut handshake = Builder::new(NOISE_PATTERN.parse()?)
+        .local_private_key(private_key)
+        .build_initiator()?;
+    let mut message = vec![0u8; MAX_MESSAGE_LEN];
+    let mut payload = vec![0u8; MAX_MESSAGE_LEN];
+
+    // -> e
+    let len = handshake.write_message(&[], &mut message)?;
+    write_frame(stream, &message[..len]).await?;
+    // <- e, ee, s, es
+    let len = read_handshake_frame(stream, &mut message).await?;
+    handshake.read_message(&message[..len], &mut payload)?;
+    if handshake.get_remote_static() != Some(expected_remote) {
+        return Err(NoiseError::UnexpectedServerKey);
+    }
+    // -> s, se
+    let len = handshake.write_message(&[], &mut message)?;
+    write_frame(stream, &message[..len]).await?;
+
+    Ok(handshake.into_stateless_transport_mode()?)
+}
+
+/// Performs the responder side of the handshake.
+///
+/// # Arguments
+/// * `stream` - The connection to the initiator.
+/// * `private_key` - The responder's static private key.
+async fn respond(
+    stream: &mut TcpStream, private_key: &[u8],
+) -> Result<StatelessTransportState, NoiseError> {
+    let mut handshake = Builder::new(NOISE_PATTERN.parse()?)
+        .local_private_key(private_key)
+        .build_responder()?;
+    let mut message = vec![0u8; MAX_MESSAGE_LEN];
+    let mut payload = vec![0u8; MAX_MESSAGE_LEN];
+
+    // -> e
+    let len = read_handshake_frame(stream, &mut message).await?;
+    handshake.read_message(&message[..len], &mut payload)?;
+    // <- e, ee, s, es
+    let len = handshake.write_message(&[], &mut message)?;
+    write_frame(stream, &message[..len]).await?;
+    // -> s, se
+    let len = read_handshake_frame(stream, &mut message).await?;
+    handshake.read_message(&message[..len], &mut payload)?;
+
+    Ok(handshake.into_stateless_transport_mode()?)
+}
+
+/// An established Noise channel, carrying plaintext bytes that travel encrypted over the
+/// underlying connection.
+///
+/// The encryption runs in two background tasks, one per direction, which end when the
+/// connection or the stream is closed.
+pub struct NoiseStream {
+    io: DuplexStream,
+    local_addr: Option<SocketAddr>,
+    remote_addr: Option<SocketAddr>,
+    remote_static: Vec<u8>,
+}
+
+impl NoiseStream {
+    /// Starts encrypting traffic over a connection whose handshake has completed.
+    ///
+    /// # Arguments
+    /// * `stream` - The connection the handshake was performed on.
+    /// * `transport` - The transport state resulting from the handshake.
+    fn new(stream: TcpStream, transport: StatelessTransportState) -> Self {
+        let local_addr = stream.local_addr().ok();
+        let remote_addr = stream.peer_addr().ok();
+        let remote_static = transport
+            .get_remote_static()
+            .map(<[u8]>::to_vec)
+            .unwrap_or_default();
+        let transport = Arc::new(transport);
+
+        let (io, plaintext) = tokio::io::duplex(MAX_MESSAGE_LEN);
+        let (plaintext_reader, plaintext_writer) = tokio::io::split(plaintext);
+        let (connection_reader, connection_writer) = stream.into_split();
+        tokio::spawn(encrypt(plaintext_reader, connection_writer, transport.clone()));
+        tokio::spawn(decrypt(connection_reader, plaintext_writer, transport));
+
+        Self {
+            io,
+            local_addr,
+            remote_addr,
+            remote_static,
+        }
+    }
+
+    /// Returns the static public key the peer proved during the handshake.
+    pub fn remote_static(&self) -> &[u8] {
+        &self.remote_static
+    }
+}
+
+/// Encrypts the plaintext written to a [`NoiseStream`] into transport messages.
+async fn encrypt(
+    mut plaintext: ReadHalf<DuplexStream>, mut connection: OwnedWriteHalf,
+    transport: Arc<StatelessTransportState>,
+) {
+    let mut payload = vec![0u8; MAX_PAYLOAD_LEN];
+    let mut message = vec![0u8; MAX_MESSAGE_LEN];
+    for nonce in 0u64.. {
+        let len = match plaintext.read(&mut payload).await {
+            Ok(0) | Err(_) => break,
+            Ok(len) => len,
+        };
+        let written = match transport.write_message(nonce, &payload[..len], &mut message) {
+            Ok(written) => written,
+            Err(e) => {
+                error!("Failed to encrypt Noise message: {}", e);
+                break;
+            }
+        };
+        if write_frame(&mut connection, &message[..written])
+            .await
+            .is_err()
+        {
+            break;
+        }
+    }
+    connection.shutdown().await.ok();
+}
+
+/// Decrypts the transport messages received on a connection into the plaintext read from a
+/// [`NoiseStream`].
+async fn decrypt(
+    mut connection: OwnedReadHalf, mut plaintext: WriteHalf<DuplexStream>,
+    transport: Arc<StatelessTransportState>,
+) {
+    let mut message = vec![0u8; MAX_MESSAGE_LEN];
+    let mut payload = vec![0u8; MAX_MESSAGE_LEN];
+    for nonce in 0u64.. {
+        let len = match read_frame(&mut connection, &mut message).await {
+            Ok(Some(len)) => len,
+            Ok(None) | Err(_) => break,
+        };
+        let read = match transport.read_message(nonce, &message[..len], &mut payload) {
+            Ok(read) => read,
+            Err(e) => {
+                error!("Failed to decrypt Noise message: {}", e);
+                break;
+            }
+        };
+        if plaintext.write_all(&payload[..read]).await.is_err() {
+            break;
+        }
+    }
+    plaintext.shutdown().await.ok();
+}
+
+impl AsyncRead for NoiseStream {
+    fn poll_read(
+        mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.io).poll_read(cx, buf)
+    }
+}
+
+impl AsyncWrite for NoiseStream {
+    fn poll_write(
+        mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        Pin::new(&mut self.io).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.io).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.io).poll_shutdown(cx)
+    }
+}
+
+impl Connected for NoiseStream {
+    type ConnectInfo = TcpConnectInfo;
+
+    fn connect_info(&self) -> Self::ConnectInfo {
+        TcpConnectInfo {
+            local_addr: self.local_addr,
+            remote_addr: self.remote_addr,
+        }
+    }
+}
+
+/// Accepts Noise connections on a listener, for `tonic`'s `Server::serve_with_incoming`.
+///
+/// Every connection performs its handshake in its own task, so a slow or malicious client does
+/// not hold up the others. Connections failing the handshake are logged and dropped.
+///
+/// # Arguments
+/// * `listener` - The listener accepting connections.
+/// * `keypair` - The server's static key pair.
+///
+/// # Returns
+/// A stream of established Noise channels.
+pub fn noise_incoming(
+    listener: TcpListener, keypair: Keypair,
+) -> ReceiverStream<Result<NoiseStream, std::io::Error>> {
+    let (sender, receiver) = mpsc::channel(ACCEPT_BACKLOG);
+    let private_key: Arc<[u8]> = keypair.private.into();
+    tokio::spawn(async move {
+        loop {
+            let (mut stream, addr) = match listener.accept().await {
+                Ok(accepted) => accepted,
+                Err(e) => {
+                    error!("Failed to accept connection: {}", e);
+                    continue;
+                }
+            };
+            let sender = sender.clone();
+            let private_key = private_key.clone();
+            tokio::spawn(async move {
+                match respond(&mut stream, &private_key).await {
+                    Ok(transport) => {
+                        debug!("Noise handshake with {} completed", addr);
+                        sender
+                            .send(Ok(NoiseStream::new(stream, transport)))
+                            .await
+                            .ok();
+                    }
+                    Err(e) => error!("Noise handshake with {} failed: {}", addr, e),
+                }
+            });
+        }
+    });
+    ReceiverStream::new(receiver)
+}
+
+/// A client of the ZKP authentication service whose every call travels over a Noise channel to
+/// a server with a known static key.
+pub struct NoiseAuthClient {
+    client: AuthClientLib,
+}
+
+impl NoiseAuthClient {
+    /// Connects to the ZKP authentication service over a Noise channel.
+    ///
+    /// The handshake only completes if the server proves `server_static_public_key`, so the
+    /// client never talks to an impostor. Reconnections perform a fresh handshake.
+    ///
+    /// # Arguments
+    /// * `dst` - The destination where the ZKP authentication service is hosted, e.g.
+    ///   `http://[::1]:50051`.
+    /// * `server_static_public_key` - The static public key of the server.
+    /// * `client_keypair` - The client's static key pair.
+    ///
+    /// # Returns
+    /// The connected client, or an error if the server cannot be reached or fails the
+    /// handshake.
+    pub async fn connect<D>(
+        dst: D, server_static_public_key: &[u8], client_keypair: &Keypair,
+    ) -> Result<Self, NoiseError>
+    where
+        D: TryInto<Endpoint>,
+        D::Error: Into<NoiseError>,
+    {
+        let endpoint = dst.try_into().map_err(Into::into)?;
+        let private_key: Arc<[u8]> = client_keypair.private.as_slice().into();
+        let server_key: Arc<[u8]> = server_static_public_key.into();
+        let connector = tower::service_fn(move |uri: Uri| {
+            let private_key = private_key.clone();
+            let server_key = server_key.clone();
+            async move {
+                let authority = uri.authority().map(|a| a.as_str()).unwrap_or_default();
+                let mut stream = TcpStream::connect(authority).await?;
+                let transport = initiate(&mut stream, &private_key, &server_key).await?;
+                Ok::<_, NoiseError>(NoiseStream::new(stream, transport))
+            }
+        });
+        let channel = endpoint.connect_with_connector(connector).await?;
+        Ok(Self {
+            client: AuthClientLib::from_channel(channel),
+        })
+    }
+
+    /// Returns the client to issue calls over the Noise channel with.
+    pub fn client(&mut self) -> &mut AuthClientLib {
+        &mut self.client
+    }
+
+    /// Registers and authenticates a user over the Noise channel, see [`execute_protocol`].
+    ///
+    /// # Arguments
+    /// * `params` - Group parameters for the cryptographic operations.
+    /// * `x` - The user's secret.
+    /// * `user` - The username for authentication.
+    ///
+    /// # Returns
+    /// The outcome of the authentication, or an error if any step fails.
+    pub async fn authenticate<T, P, S>(
+        &mut self, params: &GroupParams<P>, x: &T::Secret, user: &str,
+    ) -> Result<AuthResult, Box<dyn std::error::Error>>
+    where
+        T: ChaumPedersen<
+            GroupParameters = GroupParams<P>,
+            CommitParameters = (P, P, P, P),
+            Response = S,
+            Challenge = S,
+        >,
+        P: ByteConvertible<P> + RandomGenerator<P> + Clone + Send + Sync,
+        S: ByteConvertible<S>
+            + RandomGenerator<S>
+            + TranscriptChallenge<GroupParams<P>>
+            + Send
+            + Sync,
+    {
+        execute_protocol::<T, P, S>(params, x, user, &mut self.client).await
+    }
+}
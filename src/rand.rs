@@ -1,5 +1,3 @@
-use std::error::Error;
-
 /// Defines a trait for generating random values of a given type.
 ///
 /// This trait is intended to abstract the generation of random values
@@ -12,6 +10,243 @@ pub trait RandomGenerator<T> {
     /// if the generation fails.
     ///
     /// # Errors
-    /// Returns an error if the random value generation fails.
-    fn generate_random() -> Result<T, Box<dyn Error>>;
+    /// Returns a `rand_core::Error` if the underlying random number generator fails.
+    fn generate_random() -> Result<T, rand_core::Error>;
+}
+
+/// The source of the randomness of the protocol backends, e.g. of secrets, commitment randomness
+/// and challenges: the operating system's random number generator.
+#[cfg(not(feature = "deterministic-tests"))]
+pub use rand_core::OsRng as ProtocolRng;
+
+#[cfg(feature = "deterministic-tests")]
+pub use deterministic::{set_test_seed, ProtocolRng};
+
+/// A thread-local, seedable replacement for `OsRng`, so that protocol runs can be replayed in
+/// tests.
+#[cfg(feature = "deterministic-tests")]
+mod deterministic {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use rand_core::{CryptoRng, RngCore};
+    use std::cell::RefCell;
+
+    std::thread_local! {
+        static RNG: RefCell<StdRng> = RefCell::new(StdRng::from_entropy());
+    }
+
+    /// The source of the randomness of the protocol backends, e.g. of secrets, commitment
+    /// randomness and challenges: a generator local to the current thread, seeded from the
+    /// operating system until [`set_test_seed`] is called.
+    ///
+    /// Its output is predictable once seeded, so it must only be used in tests.
+    #[derive(Clone, Copy, Debug, Default)]
+    pub struct ProtocolRng;
+
+    impl RngCore for ProtocolRng {
+        fn next_u32(&mut self) -> u32 {
+            RNG.with(|rng| rng.borrow_mut().next_u32())
+        }
+
+        fn next_u64(&mut self) -> u64 {
+            RNG.with(|rng| rng.borrow_mut().next_u64())
+        }
+
+        fn fill_bytes(&mut self, dest: &mut [u8]) {
+            RNG.with(|rng| rng.borrow_mut().fill_bytes(dest))
+        }
+
+        fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+            RNG.with(|rng| rng.borrow_mut().try_fill_bytes(dest))
+        }
+    }
+
+    impl CryptoRng for ProtocolRng {}
+
+    /// Reseeds the protocol randomness of the current thread, so that the protocol steps that
+    /// follow draw the same values on every run.
+    ///
+    /// # Arguments
+    /// * `seed` - The seed.
+    pub fn set_test_seed(seed: u64) {
+        RNG.with(|rng| *rng.borrow_mut() = StdRng::seed_from_u64(seed));
+    }
+}
+
+/// A round of a drand randomness beacon, as served by its HTTP API.
+#[cfg(feature = "std")]
+#[derive(serde::Deserialize)]
+struct DrandRound {
+    round: u64,
+    randomness: String,
+    signature: String,
+}
+
+/// Fetches the latest round of a drand randomness beacon, e.g. to mix public randomness into
+/// challenges so that they do not depend on the server's randomness alone.
+///
+/// The randomness is checked to be the SHA-256 hash of the round's signature, as drand derives
+/// it. The signature itself is not verified against the beacon's public key.
+///
+/// # Arguments
+/// * `url` - The base URL of the beacon's HTTP API, e.g. `https://api.drand.sh`; the round is
+///   fetched from `<url>/public/latest`.
+///
+/// # Returns
+/// The randomness of the latest round, or `ZkPassError::BeaconUnavailable` if it cannot be
+/// fetched or is malformed.
+#[cfg(feature = "std")]
+pub async fn fetch_drand_randomness(url: &str) -> Result<[u8; 32], crate::client::ZkPassError> {
+    use crate::client::ZkPassError;
+    use log::warn;
+    use sha2::{Digest, Sha256};
+
+    let url = format!("{}/public/latest", url.trim_end_matches('/'));
+    let round: DrandRound = reqwest::get(&url)
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| {
+            warn!("Failed to fetch the beacon round from {}: {}", url, e);
+            ZkPassError::BeaconUnavailable
+        })?
+        .json()
+        .await
+        .map_err(|e| {
+            warn!("Malformed beacon round from {}: {}", url, e);
+            ZkPassError::BeaconUnavailable
+        })?;
+
+    let signature = hex::decode(&round.signature).map_err(|_| ZkPassError::BeaconUnavailable)?;
+    let randomness: [u8; 32] = hex::decode(&round.randomness)
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or(ZkPassError::BeaconUnavailable)?;
+    if randomness[..] != Sha256::digest(&signature)[..] {
+        warn!("Beacon round {} from {} does not match its signature", round.round, url);
+        return Err(ZkPassError::BeaconUnavailable);
+    }
+    Ok(randomness)
+}
+
+#[cfg(all(test, feature = "deterministic-tests"))]
+mod deterministic_tests {
+    use super::*;
+    use crate::chaum_pedersen::constants::{
+        EC25519_GROUP_PARAMS, RFC5114_MODP_2048_256_BIT_PARAMS,
+    };
+    use crate::chaum_pedersen::curve25519::Curve25519ChaumPedersen;
+    use crate::chaum_pedersen::discretelog::{generate_random_below, DiscreteLogChaumPedersen};
+    use crate::chaum_pedersen::ChaumPedersen;
+    use crate::conversion::ByteConvertible;
+    use curve25519_dalek::{RistrettoPoint, Scalar};
+    use num_bigint::BigUint;
+
+    /// Runs the prover's steps of a discrete log protocol run from a seed.
+    ///
+    /// # Returns
+    /// The encoded `y1`, `r1`, `k` and `c`.
+    fn discrete_log_run(seed: u64) -> [Vec<u8>; 4] {
+        let params = RFC5114_MODP_2048_256_BIT_PARAMS.to_owned();
+        set_test_seed(seed);
+        let x = generate_random_below(&params.q).unwrap();
+        let ((y1, _, r1, _), k) = DiscreteLogChaumPedersen::commitment(&params, &x);
+        let c = DiscreteLogChaumPedersen::challenge(&params);
+        [y1, r1, k, c].map(|n| BigUint::convert_to(&n))
+    }
+
+    /// Runs the prover's steps of a Ristretto protocol run from a seed.
+    ///
+    /// # Returns
+    /// The encoded `y1`, `r1`, `k` and `c`.
+    fn ristretto_run(seed: u64) -> [Vec<u8>; 4] {
+        let params = EC25519_GROUP_PARAMS.to_owned();
+        set_test_seed(seed);
+        let x = Scalar::generate_random().unwrap();
+        let ((y1, _, r1, _), k) = Curve25519ChaumPedersen::commitment(&params, &x);
+        let c = Curve25519ChaumPedersen::challenge(&params);
+        [
+            RistrettoPoint::convert_to(&y1),
+            RistrettoPoint::convert_to(&r1),
+            Scalar::convert_to(&k),
+            Scalar::convert_to(&c),
+        ]
+    }
+
+    #[test]
+    fn test_seed_replays_discrete_log_run() {
+        assert_eq!(discrete_log_run(7), discrete_log_run(7));
+        assert_ne!(discrete_log_run(7), discrete_log_run(8));
+    }
+
+    #[test]
+    fn test_seed_replays_ristretto_run() {
+        assert_eq!(ristretto_run(7), ristretto_run(7));
+        assert_ne!(ristretto_run(7), ristretto_run(8));
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+pub(crate) mod tests {
+    use super::*;
+    use crate::client::ZkPassError;
+    use sha2::{Digest, Sha256};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// Serves every HTTP request on a free local port with the given status and body, e.g. to
+    /// stand in for a drand endpoint.
+    ///
+    /// # Returns
+    /// The base URL of the endpoint.
+    pub(crate) async fn mock_endpoint(status: u16, body: String) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut request = [0u8; 4096];
+                let _ = stream.read(&mut request).await;
+                let response = format!(
+                    "HTTP/1.1 {} Mock\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                );
+                let _ = stream.write_all(response.as_bytes()).await;
+            }
+        });
+        url
+    }
+
+    /// Encodes a beacon round whose randomness is derived from `signature`, as drand does.
+    pub(crate) fn drand_round(signature: &[u8]) -> String {
+        format!(
+            r#"{{"round":42,"randomness":"{}","signature":"{}"}}"#,
+            hex::encode(Sha256::digest(signature)),
+            hex::encode(signature)
+        )
+    }
+
+    #[tokio::test]
+    async fn test_fetch_drand_randomness() {
+        let url = mock_endpoint(200, drand_round(&[7; 96])).await;
+        let randomness = fetch_drand_randomness(&format!("{}/", url)).await.unwrap();
+        assert_eq!(randomness[..], Sha256::digest([7; 96])[..]);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_drand_randomness_rejects_bad_rounds() {
+        let mismatched = format!(
+            r#"{{"round":42,"randomness":"{}","signature":"{}"}}"#,
+            hex::encode([1; 32]),
+            hex::encode([7; 96])
+        );
+        for (status, body) in [
+            (200, mismatched),
+            (200, "not json".to_string()),
+            (500, drand_round(&[7; 96])),
+        ] {
+            let url = mock_endpoint(status, body).await;
+            assert_eq!(fetch_drand_randomness(&url).await, Err(ZkPassError::BeaconUnavailable));
+        }
+    }
 }
@@ -1,12 +1,21 @@
+use crate::client::ZkPassError;
 // Importing necessary structs from the repository's models module.
 use crate::repository::models::AuthChallenge;
 use crate::repository::models::User;
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::Arc;
+use tokio::sync::Mutex;
 
 /// Trait defining the operations for User Data Access Object (DAO).
 ///
 /// This trait abstracts the CRUD (Create, Read, Update, Delete) operations
 /// and authentication challenge related operations for user data.
 ///
+/// Users are stored under their key, see [`User::key`]; methods taking a username expect
+/// that key, which is the plain username for users in the default namespace.
+///
 /// # Type Parameters
 /// - `T`: Type parameter for User related data.
 /// - `S`: Type parameter for Authentication Challenge related data.
@@ -15,7 +24,22 @@ pub trait UserDao<T, S> {
     ///
     /// # Arguments
     /// * `user` - User object to be created.
-    fn create(&mut self, user: User<T>);
+    ///
+    /// # Returns
+    /// An `Option` containing `()` if the user was created, or `None` if a user with the same
+    /// name already exists.
+    fn create(&mut self, user: User<T>) -> Option<()>;
+
+    /// Creates a user, overwriting any existing user with the same name.
+    ///
+    /// Intended for administrative overrides; regular registrations should use `create`.
+    ///
+    /// # Arguments
+    /// * `user` - User object to be stored.
+    ///
+    /// # Returns
+    /// `true` if an existing user was replaced, `false` if the user is new.
+    fn force_create(&mut self, user: User<T>) -> bool;
 
     /// Reads user data based on the provided username.
     ///
@@ -26,6 +50,17 @@ pub trait UserDao<T, S> {
     /// An `Option` containing the `User` if found, or `None` if not.
     fn read(&mut self, username: &str) -> Option<User<T>>;
 
+    /// Retrieves the auxiliary metadata stored for a user.
+    ///
+    /// # Arguments
+    /// * `username` - The username for which metadata is to be retrieved.
+    ///
+    /// # Returns
+    /// The user's metadata, `ZkPassError::UserNotFound` if the user does not exist, or another
+    /// error if the metadata cannot be loaded.
+    fn get_user_metadata(&mut self, username: &str)
+        -> Result<HashMap<String, String>, ZkPassError>;
+
     /// Updates the user data.
     ///
     /// # Arguments
@@ -34,7 +69,19 @@ pub trait UserDao<T, S> {
     ///
     /// # Returns
     /// An `Option` containing `()` if the operation was successful, or `None` if not.
-    fn update(&mut self, name: &String, user: User<T>) -> Option<()>;
+    fn update(&mut self, name: &str, user: User<T>) -> Option<()>;
+
+    /// Replaces the public commitment (`y1`, `y2`) of an existing user.
+    ///
+    /// # Arguments
+    /// * `name` - The name of the user whose credentials are rotated.
+    /// * `y1` - The new first commitment value.
+    /// * `y2` - The new second commitment value.
+    /// * `params_version` - The version of the group parameters the new values were computed with.
+    ///
+    /// # Returns
+    /// An `Option` containing `()` if the operation was successful, or `None` if the user does not exist.
+    fn update_credentials(&mut self, name: &str, y1: T, y2: T, params_version: u32) -> Option<()>;
 
     /// Deletes a user based on the provided name.
     ///
@@ -43,23 +90,40 @@ pub trait UserDao<T, S> {
     ///
     /// # Returns
     /// An `Option` containing the deleted `User` if successful, or `None` if not.
-    fn delete(&mut self, name: &String) -> Option<User<T>>;
+    fn delete(&mut self, name: &str) -> Option<User<T>>;
+
+    /// Lists the names of registered users in lexicographic order, for paging through them.
+    ///
+    /// # Arguments
+    /// * `after` - The name to list after, typically the last name of the previous page, or
+    ///   `None` to start from the beginning.
+    /// * `limit` - The maximum number of names to return.
+    ///
+    /// # Returns
+    /// Up to `limit` names following `after`.
+    fn list(&mut self, after: Option<&str>, limit: usize) -> Vec<String>;
 
     /// Creates an authentication challenge for a user.
     ///
     /// # Arguments
     /// * `user` - The user for whom the authentication challenge is being created.
+    /// * `r1` - The prover's first commitment the challenge is issued for.
+    /// * `r2` - The prover's second commitment the challenge is issued for.
     /// * `c` - The challenge data.
+    /// * `nonce` - The server nonce issued with the challenge.
+    /// * `ip` - The IP address of the client to bind the challenge to, if any.
     ///
     /// # Returns
     /// A `String` representing the created authentication challenge.
-    fn create_auth_challenge(&mut self, user: &String, c: &S) -> String;
+    fn create_auth_challenge(
+        &mut self, user: &str, r1: &T, r2: &T, c: &S, nonce: &[u8], ip: Option<IpAddr>,
+    ) -> String;
 
     /// Deletes an authentication challenge based on its ID.
     ///
     /// # Arguments
     /// * `id` - The ID of the authentication challenge to be deleted.
-    fn delete_auth_challenge(&mut self, id: &String);
+    fn delete_auth_challenge(&mut self, id: &str);
 
     /// Retrieves an authentication challenge based on its ID.
     ///
@@ -68,5 +132,316 @@ pub trait UserDao<T, S> {
     ///
     /// # Returns
     /// An `Option` containing the `AuthChallenge` if found, or `None` if not.
-    fn get_authentication_challenge(&mut self, id: &String) -> Option<AuthChallenge<S>>;
+    fn get_authentication_challenge(&mut self, id: &str) -> Option<AuthChallenge<T, S>>;
+
+    /// Removes an authentication challenge based on its ID and returns it, so that it can be
+    /// answered only once.
+    ///
+    /// # Arguments
+    /// * `id` - The ID of the authentication challenge to be taken.
+    ///
+    /// # Returns
+    /// An `Option` containing the removed `AuthChallenge` if found, or `None` if not.
+    fn take_authentication_challenge(&mut self, id: &str) -> Option<AuthChallenge<T, S>>;
+}
+
+/// A list of revoked users, who may not authenticate even with a valid secret, e.g. because
+/// it was compromised.
+///
+/// Revocation leaves the user's registration untouched, so unrevoking a user lets them
+/// authenticate again without registering anew. Usernames are user keys, see [`User::key`].
+pub trait RevocationList {
+    /// Revokes a user's credentials. Revoking a revoked user has no effect.
+    ///
+    /// # Arguments
+    /// * `username` - The key of the user to revoke.
+    fn revoke(&self, username: &str);
+
+    /// Checks whether a user's credentials are revoked.
+    ///
+    /// # Arguments
+    /// * `username` - The key of the user to check.
+    fn is_revoked(&self, username: &str) -> bool;
+
+    /// Lifts the revocation of a user's credentials. Unrevoking a user who is not revoked has
+    /// no effect.
+    ///
+    /// # Arguments
+    /// * `username` - The key of the user to unrevoke.
+    fn unrevoke(&self, username: &str);
+}
+
+/// Asynchronous variant of [`UserDao`] for use from async handlers.
+///
+/// Methods take `&self`, leaving synchronization to the implementation, so callers do not
+/// need to serialize every access behind a single lock and never block the async runtime
+/// while waiting for one.
+///
+/// # Type Parameters
+/// - `T`: Type parameter for User related data.
+/// - `S`: Type parameter for Authentication Challenge related data.
+#[async_trait]
+pub trait AsyncUserDao<T, S>: Send + Sync {
+    /// Creates a new user.
+    ///
+    /// # Arguments
+    /// * `user` - User object to be created.
+    ///
+    /// # Returns
+    /// An `Option` containing `()` if the user was created, or `None` if a user with the same
+    /// name already exists.
+    async fn create(&self, user: User<T>) -> Option<()>;
+
+    /// Creates a user, overwriting any existing user with the same name.
+    ///
+    /// Intended for administrative overrides; regular registrations should use `create`.
+    ///
+    /// # Arguments
+    /// * `user` - User object to be stored.
+    ///
+    /// # Returns
+    /// `true` if an existing user was replaced, `false` if the user is new.
+    async fn force_create(&self, user: User<T>) -> bool;
+
+    /// Reads user data based on the provided username.
+    ///
+    /// # Arguments
+    /// * `username` - The username for which user data is to be retrieved.
+    ///
+    /// # Returns
+    /// An `Option` containing the `User` if found, or `None` if not.
+    async fn read(&self, username: &str) -> Option<User<T>>;
+
+    /// Retrieves the auxiliary metadata stored for a user.
+    ///
+    /// # Arguments
+    /// * `username` - The username for which metadata is to be retrieved.
+    ///
+    /// # Returns
+    /// The user's metadata, `ZkPassError::UserNotFound` if the user does not exist, or another
+    /// error if the metadata cannot be loaded.
+    async fn get_user_metadata(
+        &self, username: &str,
+    ) -> Result<HashMap<String, String>, ZkPassError>;
+
+    /// Updates the user data.
+    ///
+    /// # Arguments
+    /// * `name` - The name of the user to be updated.
+    /// * `user` - The new user data to update.
+    ///
+    /// # Returns
+    /// An `Option` containing `()` if the operation was successful, or `None` if not.
+    async fn update(&self, name: &str, user: User<T>) -> Option<()>;
+
+    /// Replaces the public commitment (`y1`, `y2`) of an existing user.
+    ///
+    /// # Arguments
+    /// * `name` - The name of the user whose credentials are rotated.
+    /// * `y1` - The new first commitment value.
+    /// * `y2` - The new second commitment value.
+    /// * `params_version` - The version of the group parameters the new values were computed with.
+    ///
+    /// # Returns
+    /// An `Option` containing `()` if the operation was successful, or `None` if the user does not exist.
+    async fn update_credentials(&self, name: &str, y1: T, y2: T, params_version: u32)
+        -> Option<()>;
+
+    /// Deletes a user based on the provided name.
+    ///
+    /// # Arguments
+    /// * `name` - The name of the user to be deleted.
+    ///
+    /// # Returns
+    /// An `Option` containing the deleted `User` if successful, or `None` if not.
+    async fn delete(&self, name: &str) -> Option<User<T>>;
+
+    /// Lists the names of registered users in lexicographic order, for paging through them.
+    ///
+    /// # Arguments
+    /// * `after` - The name to list after, typically the last name of the previous page, or
+    ///   `None` to start from the beginning.
+    /// * `limit` - The maximum number of names to return.
+    ///
+    /// # Returns
+    /// Up to `limit` names following `after`.
+    async fn list(&self, after: Option<&str>, limit: usize) -> Vec<String>;
+
+    /// Creates an authentication challenge for a user.
+    ///
+    /// # Arguments
+    /// * `user` - The user for whom the authentication challenge is being created.
+    /// * `r1` - The prover's first commitment the challenge is issued for.
+    /// * `r2` - The prover's second commitment the challenge is issued for.
+    /// * `c` - The challenge data.
+    /// * `nonce` - The server nonce issued with the challenge.
+    /// * `ip` - The IP address of the client to bind the challenge to, if any.
+    ///
+    /// # Returns
+    /// A `String` representing the created authentication challenge.
+    async fn create_auth_challenge(
+        &self, user: &str, r1: &T, r2: &T, c: &S, nonce: &[u8], ip: Option<IpAddr>,
+    ) -> String;
+
+    /// Deletes an authentication challenge based on its ID.
+    ///
+    /// # Arguments
+    /// * `id` - The ID of the authentication challenge to be deleted.
+    async fn delete_auth_challenge(&self, id: &str);
+
+    /// Retrieves an authentication challenge based on its ID.
+    ///
+    /// # Arguments
+    /// * `id` - The ID of the authentication challenge to be retrieved.
+    ///
+    /// # Returns
+    /// An `Option` containing the `AuthChallenge` if found, or `None` if not.
+    async fn get_authentication_challenge(&self, id: &str) -> Option<AuthChallenge<T, S>>;
+
+    /// Removes an authentication challenge based on its ID and returns it, so that it can be
+    /// answered only once.
+    ///
+    /// Taking a challenge must be atomic: of concurrent calls for the same ID, at most one
+    /// returns the challenge.
+    ///
+    /// # Arguments
+    /// * `id` - The ID of the authentication challenge to be taken.
+    ///
+    /// # Returns
+    /// An `Option` containing the removed `AuthChallenge` if found, or `None` if not.
+    async fn take_authentication_challenge(&self, id: &str) -> Option<AuthChallenge<T, S>>;
+}
+
+/// Adapts any synchronous [`UserDao`] to [`AsyncUserDao`] by serializing access behind a `Mutex`.
+#[async_trait]
+impl<T, S, D> AsyncUserDao<T, S> for Mutex<D>
+where
+    T: Send + Sync + 'static,
+    S: Send + Sync + 'static,
+    D: UserDao<T, S> + Send,
+{
+    async fn create(&self, user: User<T>) -> Option<()> {
+        self.lock().await.create(user)
+    }
+
+    async fn force_create(&self, user: User<T>) -> bool {
+        self.lock().await.force_create(user)
+    }
+
+    async fn read(&self, username: &str) -> Option<User<T>> {
+        self.lock().await.read(username)
+    }
+
+    async fn get_user_metadata(
+        &self, username: &str,
+    ) -> Result<HashMap<String, String>, ZkPassError> {
+        self.lock().await.get_user_metadata(username)
+    }
+
+    async fn update(&self, name: &str, user: User<T>) -> Option<()> {
+        self.lock().await.update(name, user)
+    }
+
+    async fn update_credentials(
+        &self, name: &str, y1: T, y2: T, params_version: u32,
+    ) -> Option<()> {
+        self.lock()
+            .await
+            .update_credentials(name, y1, y2, params_version)
+    }
+
+    async fn delete(&self, name: &str) -> Option<User<T>> {
+        self.lock().await.delete(name)
+    }
+
+    async fn list(&self, after: Option<&str>, limit: usize) -> Vec<String> {
+        self.lock().await.list(after, limit)
+    }
+
+    async fn create_auth_challenge(
+        &self, user: &str, r1: &T, r2: &T, c: &S, nonce: &[u8], ip: Option<IpAddr>,
+    ) -> String {
+        self.lock()
+            .await
+            .create_auth_challenge(user, r1, r2, c, nonce, ip)
+    }
+
+    async fn delete_auth_challenge(&self, id: &str) {
+        self.lock().await.delete_auth_challenge(id)
+    }
+
+    async fn get_authentication_challenge(&self, id: &str) -> Option<AuthChallenge<T, S>> {
+        self.lock().await.get_authentication_challenge(id)
+    }
+
+    async fn take_authentication_challenge(&self, id: &str) -> Option<AuthChallenge<T, S>> {
+        self.lock().await.take_authentication_challenge(id)
+    }
+}
+
+/// Shares an [`AsyncUserDao`] between owners, e.g. the service and an administrative task.
+#[async_trait]
+impl<T, S, D> AsyncUserDao<T, S> for Arc<D>
+where
+    T: Send + Sync + 'static,
+    S: Send + Sync + 'static,
+    D: AsyncUserDao<T, S> + ?Sized,
+{
+    async fn create(&self, user: User<T>) -> Option<()> {
+        (**self).create(user).await
+    }
+
+    async fn force_create(&self, user: User<T>) -> bool {
+        (**self).force_create(user).await
+    }
+
+    async fn read(&self, username: &str) -> Option<User<T>> {
+        (**self).read(username).await
+    }
+
+    async fn get_user_metadata(
+        &self, username: &str,
+    ) -> Result<HashMap<String, String>, ZkPassError> {
+        (**self).get_user_metadata(username).await
+    }
+
+    async fn update(&self, name: &str, user: User<T>) -> Option<()> {
+        (**self).update(name, user).await
+    }
+
+    async fn update_credentials(
+        &self, name: &str, y1: T, y2: T, params_version: u32,
+    ) -> Option<()> {
+        (**self)
+            .update_credentials(name, y1, y2, params_version)
+            .await
+    }
+
+    async fn delete(&self, name: &str) -> Option<User<T>> {
+        (**self).delete(name).await
+    }
+
+    async fn list(&self, after: Option<&str>, limit: usize) -> Vec<String> {
+        (**self).list(after, limit).await
+    }
+
+    async fn create_auth_challenge(
+        &self, user: &str, r1: &T, r2: &T, c: &S, nonce: &[u8], ip: Option<IpAddr>,
+    ) -> String {
+        (**self)
+            .create_auth_challenge(user, r1, r2, c, nonce, ip)
+            .await
+    }
+
+    async fn delete_auth_challenge(&self, id: &str) {
+        (**self).delete_auth_challenge(id).await
+    }
+
+    async fn get_authentication_challenge(&self, id: &str) -> Option<AuthChallenge<T, S>> {
+        (**self).get_authentication_challenge(id).await
+    }
+
+    async fn take_authentication_challenge(&self, id: &str) -> Option<AuthChallenge<T, S>> {
+        (**self).take_authentication_challenge(id).await
+    }
 }
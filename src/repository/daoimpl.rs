@@ -1,8 +1,19 @@
 // Importing necessary traits, structs, and modules.
+use crate::client::ZkPassError;
 use crate::conversion::ByteConvertible;
-use crate::repository::dao::UserDao;
+use crate::repository::dao::{AsyncUserDao, RevocationList, UserDao};
 use crate::repository::models::User;
-use std::collections::HashMap;
+use argon2::password_hash::{PasswordHasher, SaltString};
+use argon2::Argon2;
+use async_trait::async_trait;
+use lru::LruCache;
+use rand_core::OsRng;
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+use std::marker::PhantomData;
+use std::net::IpAddr;
+use std::num::NonZeroUsize;
+use tokio::sync::RwLock;
 use uuid::Uuid;
 
 use super::models::AuthChallenge;
@@ -10,26 +21,54 @@ use super::models::AuthChallenge;
 /// A struct representing an in-memory User Data Access Object (DAO).
 ///
 /// This struct provides an in-memory implementation of the `UserDao` trait,
-/// storing user data and authentication challenges in hash maps.
+/// storing user data in a map and authentication challenges in an LRU cache. Both are unbounded
+/// unless a capacity is set with [`InMemoryUserDao::with_capacity`].
 ///
 /// # Type Parameters
 /// - `T`: Type parameter for User related data.
 /// - `S`: Type parameter for Authentication Challenge related data.
 pub struct InMemoryUserDao<T, S> {
     users: HashMap<String, User<T>>,
-    auth_challenges: HashMap<String, AuthChallenge<S>>,
+    max_users: Option<usize>,
+    auth_challenges: LruCache<String, AuthChallenge<T, S>>,
 }
 
 impl<T, S> InMemoryUserDao<T, S> {
     /// Constructs a new instance of `InMemoryUserDao`.
     ///
-    /// Initializes the internal hash maps for users and authentication challenges.
+    /// Initializes unbounded storage for users and authentication challenges.
     pub fn new() -> Self {
         InMemoryUserDao {
             users: HashMap::new(),
-            auth_challenges: HashMap::new(),
+            max_users: None,
+            auth_challenges: LruCache::unbounded(),
         }
     }
+
+    /// Constructs an `InMemoryUserDao` that holds at most `capacity` users and `capacity`
+    /// authentication challenges, keeping memory bounded on a public server.
+    ///
+    /// Once the challenge cache is full, issuing a challenge evicts the least recently used one;
+    /// reads count as uses. Registered users are never evicted, as that would free their names
+    /// for anyone to claim: once full, `create` refuses new users instead.
+    ///
+    /// # Arguments
+    /// * `capacity` - The maximum number of users, and of challenges, held. A capacity of zero is
+    ///   treated as one.
+    pub fn with_capacity(capacity: usize) -> Self {
+        let capacity = NonZeroUsize::new(capacity).unwrap_or(NonZeroUsize::MIN);
+        InMemoryUserDao {
+            users: HashMap::new(),
+            max_users: Some(capacity.get()),
+            auth_challenges: LruCache::new(capacity),
+        }
+    }
+}
+
+impl<T, S> Default for InMemoryUserDao<T, S> {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl<T, S> UserDao<T, S> for InMemoryUserDao<T, S>
@@ -39,22 +78,52 @@ where
 {
     /// Implements the `create` method for user data.
     ///
-    /// Inserts the provided user into the internal users hash map.
-    fn create(&mut self, user: User<T>) {
-        self.users.insert(user.username.clone(), user);
+    /// Inserts the provided user into the internal users map, unless the username is taken or
+    /// the map holds as many users as its capacity allows.
+    fn create(&mut self, user: User<T>) -> Option<()> {
+        if self.max_users.is_some_and(|max| self.users.len() >= max) {
+            return None;
+        }
+        match self.users.entry(user.key()) {
+            Entry::Occupied(_) => None,
+            Entry::Vacant(entry) => {
+                entry.insert(user);
+                Some(())
+            }
+        }
+    }
+
+    /// Implements the `force_create` method for user data.
+    ///
+    /// Inserts the provided user into the internal users map, replacing any existing user.
+    /// Administrative overrides are not bound by the capacity.
+    fn force_create(&mut self, user: User<T>) -> bool {
+        self.users.insert(user.key(), user).is_some()
     }
 
     /// Implements the `read` method for user data.
     ///
-    /// Retrieves the user based on the provided username from the internal users hash map.
+    /// Retrieves the user based on the provided username from the internal users map.
     fn read(&mut self, username: &str) -> Option<User<T>> {
         self.users.get(username).cloned()
     }
 
+    /// Implements the `get_user_metadata` method for user data.
+    ///
+    /// Retrieves the metadata of the user with the provided username from the internal users map.
+    fn get_user_metadata(
+        &mut self, username: &str,
+    ) -> Result<HashMap<String, String>, ZkPassError> {
+        self.users
+            .get(username)
+            .map(|user| user.metadata.clone())
+            .ok_or(ZkPassError::UserNotFound)
+    }
+
     /// Implements the `update` method for user data.
     ///
     /// Updates the user data based on the provided name.
-    fn update(&mut self, name: &String, new_user: User<T>) -> Option<()> {
+    fn update(&mut self, name: &str, new_user: User<T>) -> Option<()> {
         if let Some(user) = self.users.get_mut(name) {
             *user = new_user;
             Some(())
@@ -63,38 +132,563 @@ where
         }
     }
 
+    /// Implements the `update_credentials` method for user data.
+    ///
+    /// Replaces the commitment of the user with the provided name, along with the version of the
+    /// group parameters it was computed with.
+    fn update_credentials(&mut self, name: &str, y1: T, y2: T, params_version: u32) -> Option<()> {
+        let user = self.users.get_mut(name)?;
+        user.y1 = y1;
+        user.y2 = y2;
+        user.params_version = params_version;
+        Some(())
+    }
+
     /// Implements the `delete` method for user data.
     ///
-    /// Deletes the user based on the provided name from the internal users hash map.
-    fn delete(&mut self, name: &String) -> Option<User<T>> {
+    /// Deletes the user based on the provided name from the internal users map.
+    fn delete(&mut self, name: &str) -> Option<User<T>> {
         self.users.remove(name)
     }
 
+    /// Implements the `list` method for user data.
+    ///
+    /// Collects and sorts the names in the internal users map, then slices from the cursor.
+    fn list(&mut self, after: Option<&str>, limit: usize) -> Vec<String> {
+        list_after(self.users.keys(), after, limit)
+    }
+
     /// Implements the `create_auth_challenge` method.
     ///
     /// Creates and stores an authentication challenge for a user.
-    fn create_auth_challenge(&mut self, user: &String, c: &S) -> String {
+    fn create_auth_challenge(
+        &mut self, user: &str, r1: &T, r2: &T, c: &S, nonce: &[u8], ip: Option<IpAddr>,
+    ) -> String {
         let uid = Uuid::new_v4().to_string();
         let auth_challenge = AuthChallenge {
             id: uid.clone(),
-            user: user.clone(),
+            user: user.to_string(),
+            r1: r1.clone(),
+            r2: r2.clone(),
             c: c.clone(),
+            nonce: nonce.to_vec(),
+            ip,
         };
-        self.auth_challenges.insert(uid.clone(), auth_challenge);
+        self.auth_challenges.put(uid.clone(), auth_challenge);
         uid
     }
 
     /// Implements the `delete_auth_challenge` method.
     ///
     /// Deletes an authentication challenge based on its ID.
-    fn delete_auth_challenge(&mut self, id: &String) {
-        self.auth_challenges.remove(id);
+    fn delete_auth_challenge(&mut self, id: &str) {
+        self.auth_challenges.pop(id);
     }
 
     /// Implements the `get_authentication_challenge` method.
     ///
     /// Retrieves an authentication challenge based on its ID.
-    fn get_authentication_challenge(&mut self, id: &String) -> Option<AuthChallenge<S>> {
+    fn get_authentication_challenge(&mut self, id: &str) -> Option<AuthChallenge<T, S>> {
         self.auth_challenges.get(id).cloned()
     }
+
+    /// Implements the `take_authentication_challenge` method.
+    ///
+    /// Removes an authentication challenge based on its ID and returns it.
+    fn take_authentication_challenge(&mut self, id: &str) -> Option<AuthChallenge<T, S>> {
+        self.auth_challenges.pop(id)
+    }
+}
+
+/// Sorts user names and returns up to `limit` of those following `after`.
+fn list_after<'a>(
+    names: impl Iterator<Item = &'a String>, after: Option<&str>, limit: usize,
+) -> Vec<String> {
+    let mut names: Vec<&String> = names
+        .filter(|name| after.is_none_or(|after| name.as_str() > after))
+        .collect();
+    names.sort_unstable();
+    names.into_iter().take(limit).cloned().collect()
+}
+
+/// A struct representing an in-memory, asynchronous User Data Access Object (DAO).
+///
+/// This struct provides an in-memory implementation of the `AsyncUserDao` trait. Users and
+/// authentication challenges live in separate `RwLock`-guarded hash maps, so concurrent reads
+/// proceed in parallel and challenge traffic does not contend with user lookups.
+///
+/// # Type Parameters
+/// - `T`: Type parameter for User related data.
+/// - `S`: Type parameter for Authentication Challenge related data.
+pub struct AsyncInMemoryUserDao<T, S> {
+    users: RwLock<HashMap<String, User<T>>>,
+    auth_challenges: RwLock<HashMap<String, AuthChallenge<T, S>>>,
+}
+
+impl<T, S> AsyncInMemoryUserDao<T, S> {
+    /// Constructs a new instance of `AsyncInMemoryUserDao`.
+    ///
+    /// Initializes the internal hash maps for users and authentication challenges.
+    pub fn new() -> Self {
+        AsyncInMemoryUserDao {
+            users: RwLock::new(HashMap::new()),
+            auth_challenges: RwLock::new(HashMap::new()),
+        }
+    }
+}
+
+impl<T, S> Default for AsyncInMemoryUserDao<T, S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl<T, S> AsyncUserDao<T, S> for AsyncInMemoryUserDao<T, S>
+where
+    T: Send + Sync + 'static + Clone,
+    S: Send + Sync + 'static + Clone,
+{
+    /// Implements the `create` method for user data.
+    ///
+    /// Inserts the provided user into the internal users hash map, unless the username is taken.
+    async fn create(&self, user: User<T>) -> Option<()> {
+        match self.users.write().await.entry(user.key()) {
+            Entry::Occupied(_) => None,
+            Entry::Vacant(entry) => {
+                entry.insert(user);
+                Some(())
+            }
+        }
+    }
+
+    /// Implements the `force_create` method for user data.
+    ///
+    /// Inserts the provided user into the internal users hash map, replacing any existing user.
+    async fn force_create(&self, user: User<T>) -> bool {
+        self.users.write().await.insert(user.key(), user).is_some()
+    }
+
+    /// Implements the `read` method for user data.
+    ///
+    /// Retrieves the user based on the provided username from the internal users hash map.
+    async fn read(&self, username: &str) -> Option<User<T>> {
+        self.users.read().await.get(username).cloned()
+    }
+
+    /// Implements the `get_user_metadata` method for user data.
+    ///
+    /// Retrieves the metadata of the user with the provided username from the internal users hash map.
+    async fn get_user_metadata(
+        &self, username: &str,
+    ) -> Result<HashMap<String, String>, ZkPassError> {
+        self.users
+            .read()
+            .await
+            .get(username)
+            .map(|user| user.metadata.clone())
+            .ok_or(ZkPassError::UserNotFound)
+    }
+
+    /// Implements the `update` method for user data.
+    ///
+    /// Updates the user data based on the provided name.
+    async fn update(&self, name: &str, new_user: User<T>) -> Option<()> {
+        let mut users = self.users.write().await;
+        let user = users.get_mut(name)?;
+        *user = new_user;
+        Some(())
+    }
+
+    /// Implements the `update_credentials` method for user data.
+    ///
+    /// Replaces the commitment of the user with the provided name, along with the version of the
+    /// group parameters it was computed with.
+    async fn update_credentials(
+        &self, name: &str, y1: T, y2: T, params_version: u32,
+    ) -> Option<()> {
+        let mut users = self.users.write().await;
+        let user = users.get_mut(name)?;
+        user.y1 = y1;
+        user.y2 = y2;
+        user.params_version = params_version;
+        Some(())
+    }
+
+    /// Implements the `delete` method for user data.
+    ///
+    /// Deletes the user based on the provided name from the internal users hash map.
+    async fn delete(&self, name: &str) -> Option<User<T>> {
+        self.users.write().await.remove(name)
+    }
+
+    /// Implements the `list` method for user data.
+    ///
+    /// Collects and sorts the names in the internal users hash map, then slices from the cursor.
+    async fn list(&self, after: Option<&str>, limit: usize) -> Vec<String> {
+        list_after(self.users.read().await.keys(), after, limit)
+    }
+
+    /// Implements the `create_auth_challenge` method.
+    ///
+    /// Creates and stores an authentication challenge for a user.
+    async fn create_auth_challenge(
+        &self, user: &str, r1: &T, r2: &T, c: &S, nonce: &[u8], ip: Option<IpAddr>,
+    ) -> String {
+        let uid = Uuid::new_v4().to_string();
+        let auth_challenge = AuthChallenge {
+            id: uid.clone(),
+            user: user.to_string(),
+            r1: r1.clone(),
+            r2: r2.clone(),
+            c: c.clone(),
+            nonce: nonce.to_vec(),
+            ip,
+        };
+        self.auth_challenges
+            .write()
+            .await
+            .insert(uid.clone(), auth_challenge);
+        uid
+    }
+
+    /// Implements the `delete_auth_challenge` method.
+    ///
+    /// Deletes an authentication challenge based on its ID.
+    async fn delete_auth_challenge(&self, id: &str) {
+        self.auth_challenges.write().await.remove(id);
+    }
+
+    /// Implements the `get_authentication_challenge` method.
+    ///
+    /// Retrieves an authentication challenge based on its ID.
+    async fn get_authentication_challenge(&self, id: &str) -> Option<AuthChallenge<T, S>> {
+        self.auth_challenges.read().await.get(id).cloned()
+    }
+
+    /// Implements the `take_authentication_challenge` method.
+    ///
+    /// Removes an authentication challenge based on its ID and returns it, under the write lock.
+    async fn take_authentication_challenge(&self, id: &str) -> Option<AuthChallenge<T, S>> {
+        self.auth_challenges.write().await.remove(id)
+    }
+}
+
+/// A [`RevocationList`] keeping revoked usernames in process memory.
+pub struct InMemoryRevocationList {
+    revoked: std::sync::RwLock<HashSet<String>>,
+}
+
+impl InMemoryRevocationList {
+    /// Constructs a new, empty `InMemoryRevocationList`.
+    pub fn new() -> Self {
+        InMemoryRevocationList {
+            revoked: std::sync::RwLock::new(HashSet::new()),
+        }
+    }
+}
+
+impl Default for InMemoryRevocationList {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RevocationList for InMemoryRevocationList {
+    fn revoke(&self, username: &str) {
+        self.revoked.write().unwrap().insert(username.to_string());
+    }
+
+    fn is_revoked(&self, username: &str) -> bool {
+        self.revoked.read().unwrap().contains(username)
+    }
+
+    fn unrevoke(&self, username: &str) {
+        self.revoked.write().unwrap().remove(username);
+    }
+}
+
+/// A [`UserDao`] that keeps users' public commitments out of the underlying store, so that a
+/// dump of the store does not allow offline dictionary attacks on secrets derived from
+/// passwords.
+///
+/// The inner store only holds the Argon2id hashes of the encoded `y1` and `y2`, as PHC strings
+/// with a random salt per value. The commitments themselves are kept in memory, where reads take
+/// them from after checking that the user is still in the inner store. The hashes cannot be
+/// reversed, so users whose commitments are not in memory, e.g. after a restart, must register
+/// again. Authentication challenges are passed through with their commitments encoded.
+///
+/// # Type Parameters
+/// - `T`: Type parameter for User related data.
+/// - `S`: Type parameter for Authentication Challenge related data.
+/// - `D`: The underlying store, holding the hashes as byte strings.
+pub struct HardenedUserDao<T, S, D> {
+    inner: D,
+    credentials: HashMap<String, (T, T)>,
+    _challenge: PhantomData<S>,
+}
+
+impl<T, S, D> HardenedUserDao<T, S, D> {
+    /// Wraps a user data store.
+    ///
+    /// # Arguments
+    /// * `inner` - The store to keep the hashed commitments in.
+    pub fn new(inner: D) -> Self {
+        HardenedUserDao {
+            inner,
+            credentials: HashMap::new(),
+            _challenge: PhantomData,
+        }
+    }
+
+    /// Returns the underlying store, e.g. to inspect what a dump of it would reveal.
+    pub fn inner(&mut self) -> &mut D {
+        &mut self.inner
+    }
+}
+
+impl<T, S, D> HardenedUserDao<T, S, D>
+where
+    T: Clone + ByteConvertible<T>,
+    D: UserDao<Vec<u8>, S>,
+{
+    /// Hashes a commitment value with Argon2id under a fresh random salt.
+    ///
+    /// # Returns
+    /// The hash as a PHC string.
+    fn harden(value: &T) -> Vec<u8> {
+        let salt = SaltString::generate(&mut OsRng);
+        Argon2::default()
+            .hash_password(&T::convert_to(value), &salt)
+            .expect("Argon2id with default parameters accepts any input")
+            .to_string()
+            .into_bytes()
+    }
+
+    /// Replaces the commitments of a user by their hashes, remembering the commitments.
+    ///
+    /// # Returns
+    /// The user as stored in the inner store.
+    fn split(&mut self, user: User<T>) -> (User<Vec<u8>>, (T, T)) {
+        let hardened = User {
+            y1: Self::harden(&user.y1),
+            y2: Self::harden(&user.y2),
+            username: user.username,
+            metadata: user.metadata,
+            namespace: user.namespace,
+            params_version: user.params_version,
+        };
+        (hardened, (user.y1, user.y2))
+    }
+
+    /// Decodes the commitments of a challenge passed through the inner store.
+    ///
+    /// # Returns
+    /// The challenge, or `None` if a commitment does not decode.
+    fn decode_challenge(challenge: AuthChallenge<Vec<u8>, S>) -> Option<AuthChallenge<T, S>> {
+        Some(AuthChallenge {
+            id: challenge.id,
+            user: challenge.user,
+            r1: T::convert_from(&challenge.r1).ok()?,
+            r2: T::convert_from(&challenge.r2).ok()?,
+            c: challenge.c,
+            nonce: challenge.nonce,
+            ip: challenge.ip,
+        })
+    }
+}
+
+impl<T, S, D> UserDao<T, S> for HardenedUserDao<T, S, D>
+where
+    T: Clone + ByteConvertible<T>,
+    D: UserDao<Vec<u8>, S>,
+{
+    fn create(&mut self, user: User<T>) -> Option<()> {
+        let key = user.key();
+        let (hardened, credentials) = self.split(user);
+        self.inner.create(hardened)?;
+        self.credentials.insert(key, credentials);
+        Some(())
+    }
+
+    fn force_create(&mut self, user: User<T>) -> bool {
+        let key = user.key();
+        let (hardened, credentials) = self.split(user);
+        let replaced = self.inner.force_create(hardened);
+        self.credentials.insert(key, credentials);
+        replaced
+    }
+
+    /// Implements the `read` method for user data.
+    ///
+    /// Returns `None` for users whose commitments are not in memory, as well as for users no
+    /// longer in the inner store.
+    fn read(&mut self, username: &str) -> Option<User<T>> {
+        let user = self.inner.read(username)?;
+        let (y1, y2) = self.credentials.get(username)?.clone();
+        Some(User {
+            username: user.username,
+            y1,
+            y2,
+            metadata: user.metadata,
+            namespace: user.namespace,
+            params_version: user.params_version,
+        })
+    }
+
+    fn get_user_metadata(
+        &mut self, username: &str,
+    ) -> Result<HashMap<String, String>, ZkPassError> {
+        self.inner.get_user_metadata(username)
+    }
+
+    fn update(&mut self, name: &str, user: User<T>) -> Option<()> {
+        let (hardened, credentials) = self.split(user);
+        self.inner.update(name, hardened)?;
+        self.credentials.insert(name.to_string(), credentials);
+        Some(())
+    }
+
+    fn update_credentials(&mut self, name: &str, y1: T, y2: T, params_version: u32) -> Option<()> {
+        let (h1, h2) = (Self::harden(&y1), Self::harden(&y2));
+        self.inner
+            .update_credentials(name, h1, h2, params_version)?;
+        self.credentials.insert(name.to_string(), (y1, y2));
+        Some(())
+    }
+
+    fn delete(&mut self, name: &str) -> Option<User<T>> {
+        let user = self.inner.delete(name)?;
+        let (y1, y2) = self.credentials.remove(name)?;
+        Some(User {
+            username: user.username,
+            y1,
+            y2,
+            metadata: user.metadata,
+            namespace: user.namespace,
+            params_version: user.params_version,
+        })
+    }
+
+    fn list(&mut self, after: Option<&str>, limit: usize) -> Vec<String> {
+        self.inner.list(after, limit)
+    }
+
+    fn create_auth_challenge(
+        &mut self, user: &str, r1: &T, r2: &T, c: &S, nonce: &[u8], ip: Option<IpAddr>,
+    ) -> String {
+        let (r1, r2) = (T::convert_to(r1), T::convert_to(r2));
+        self.inner
+            .create_auth_challenge(user, &r1, &r2, c, nonce, ip)
+    }
+
+    fn delete_auth_challenge(&mut self, id: &str) {
+        self.inner.delete_auth_challenge(id)
+    }
+
+    fn get_authentication_challenge(&mut self, id: &str) -> Option<AuthChallenge<T, S>> {
+        Self::decode_challenge(self.inner.get_authentication_challenge(id)?)
+    }
+
+    fn take_authentication_challenge(&mut self, id: &str) -> Option<AuthChallenge<T, S>> {
+        Self::decode_challenge(self.inner.take_authentication_challenge(id)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rand::RandomGenerator;
+    use curve25519_dalek::{RistrettoPoint, Scalar};
+
+    fn user(username: &str) -> User<RistrettoPoint> {
+        User {
+            username: username.to_string(),
+            y1: RistrettoPoint::generate_random().unwrap(),
+            y2: RistrettoPoint::generate_random().unwrap(),
+            metadata: HashMap::new(),
+            namespace: String::new(),
+            params_version: 0,
+        }
+    }
+
+    #[test]
+    fn test_hardened_dao_stores_only_hashes() {
+        let mut dao = HardenedUserDao::new(InMemoryUserDao::<Vec<u8>, Scalar>::new());
+        let alice = user("alice");
+        dao.create(alice.clone()).unwrap();
+        assert!(dao.create(user("alice")).is_none());
+
+        let stored = dao.inner().read("alice").unwrap();
+        assert_ne!(stored.y1, RistrettoPoint::convert_to(&alice.y1));
+        assert_ne!(stored.y2, RistrettoPoint::convert_to(&alice.y2));
+        let read = dao.read("alice").unwrap();
+        assert_eq!((read.y1, read.y2), (alice.y1, alice.y2));
+
+        // Updated credentials are hashed anew.
+        let y = RistrettoPoint::generate_random().unwrap();
+        dao.update_credentials("alice", y, y, 1).unwrap();
+        let updated = dao.inner().read("alice").unwrap();
+        assert_ne!(updated.y1, stored.y1);
+        // Each value is hashed under its own salt.
+        assert_ne!(updated.y1, updated.y2);
+        assert_eq!(dao.read("alice").unwrap().y1, y);
+
+        // Users whose commitments are not in memory cannot be read.
+        let mut restarted = HardenedUserDao::<RistrettoPoint, Scalar, _>::new(dao.inner);
+        assert!(restarted.read("alice").is_none());
+    }
+
+    #[test]
+    fn test_capacity_evicts_least_recently_used() {
+        let mut dao = InMemoryUserDao::<RistrettoPoint, Scalar>::with_capacity(2);
+        let c = Scalar::generate_random().unwrap();
+        let r = RistrettoPoint::generate_random().unwrap();
+        let first = dao.create_auth_challenge("bob", &r, &r, &c, &[], None);
+        let second = dao.create_auth_challenge("bob", &r, &r, &c, &[], None);
+        let third = dao.create_auth_challenge("bob", &r, &r, &c, &[], None);
+        assert!(dao.get_authentication_challenge(&first).is_none());
+        assert!(dao.get_authentication_challenge(&second).is_some());
+        assert!(dao.get_authentication_challenge(&third).is_some());
+    }
+
+    #[test]
+    fn test_read_refreshes_recency() {
+        let mut dao = InMemoryUserDao::<RistrettoPoint, Scalar>::with_capacity(2);
+        let c = Scalar::generate_random().unwrap();
+        let r = RistrettoPoint::generate_random().unwrap();
+        let first = dao.create_auth_challenge("bob", &r, &r, &c, &[], None);
+        let second = dao.create_auth_challenge("bob", &r, &r, &c, &[], None);
+        assert!(dao.get_authentication_challenge(&first).is_some());
+        dao.create_auth_challenge("bob", &r, &r, &c, &[], None);
+        assert!(dao.get_authentication_challenge(&first).is_some());
+        assert!(dao.get_authentication_challenge(&second).is_none());
+    }
+
+    #[test]
+    fn test_capacity_refuses_new_users() {
+        let mut dao = InMemoryUserDao::<RistrettoPoint, Scalar>::with_capacity(2);
+        dao.create(user("alice")).unwrap();
+        dao.create(user("bob")).unwrap();
+        assert!(dao.create(user("carol")).is_none());
+        assert!(dao.read("alice").is_some());
+        assert!(dao.read("bob").is_some());
+        assert!(dao.read("carol").is_none());
+
+        // Deleting a user makes room for another.
+        dao.delete("bob").unwrap();
+        dao.create(user("carol")).unwrap();
+    }
+
+    #[test]
+    fn test_revocation_list() {
+        let revocations = InMemoryRevocationList::new();
+        assert!(!revocations.is_revoked("alice"));
+        revocations.revoke("alice");
+        revocations.revoke("alice");
+        assert!(revocations.is_revoked("alice"));
+        assert!(!revocations.is_revoked("bob"));
+        revocations.unrevoke("alice");
+        assert!(!revocations.is_revoked("alice"));
+    }
 }
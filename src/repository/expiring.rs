@@ -0,0 +1,121 @@
+use std::collections::{HashMap, VecDeque};
+use std::hash::Hash;
+use std::time::Duration;
+
+/// An in-memory map whose entries expire a fixed time after they were inserted.
+///
+/// Expired entries are dropped in insertion order whenever an entry is inserted, so keeping the
+/// map bounded costs amortized constant time per insertion instead of a scan of every entry.
+///
+/// Times are Unix times in seconds, passed in by the caller.
+pub struct ExpiringMap<K, V> {
+    ttl: u64,
+    entries: HashMap<K, (V, u64)>,
+    expiries: VecDeque<(u64, K)>,
+}
+
+impl<K: Eq + Hash + Clone, V> ExpiringMap<K, V> {
+    /// Creates an empty map.
+    ///
+    /// # Arguments
+    /// * `ttl` - How long an entry is kept after it was inserted.
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl: ttl.as_secs(),
+            entries: HashMap::new(),
+            expiries: VecDeque::new(),
+        }
+    }
+
+    /// Inserts an entry, dropping the entries that expired by `now` first.
+    ///
+    /// # Arguments
+    /// * `key` - The key of the entry.
+    /// * `value` - The value of the entry.
+    /// * `now` - The current Unix time in seconds.
+    ///
+    /// # Returns
+    /// The value the key held, unless it had expired.
+    pub fn insert(&mut self, key: K, value: V, now: u64) -> Option<V> {
+        self.expire(now);
+        let expiry = now.saturating_add(self.ttl);
+        self.expiries.push_back((expiry, key.clone()));
+        self.entries
+            .insert(key, (value, expiry))
+            .map(|(value, _)| value)
+    }
+
+    /// Returns the value of an entry, unless it expired by `now`.
+    pub fn get(&self, key: &K, now: u64) -> Option<&V> {
+        self.entries
+            .get(key)
+            .filter(|(_, expiry)| *expiry >= now)
+            .map(|(value, _)| value)
+    }
+
+    /// Removes an entry, returning its value unless it expired by `now`.
+    pub fn remove(&mut self, key: &K, now: u64) -> Option<V> {
+        self.entries
+            .remove(key)
+            .filter(|(_, expiry)| *expiry >= now)
+            .map(|(value, _)| value)
+    }
+
+    /// Returns the number of entries, including expired ones not dropped yet.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns whether the map holds no entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Drops the entries that expired by `now`.
+    fn expire(&mut self, now: u64) {
+        while let Some((expiry, _)) = self.expiries.front() {
+            if *expiry >= now {
+                break;
+            }
+            let (expiry, key) = self.expiries.pop_front().expect("the front entry exists");
+            // The key may have been removed, or inserted again with a later expiry.
+            if self.entries.get(&key).is_some_and(|(_, e)| *e == expiry) {
+                self.entries.remove(&key);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_entries_expire_after_ttl() {
+        let mut map = ExpiringMap::new(Duration::from_secs(10));
+        assert_eq!(map.insert("a", 1, 100), None);
+        assert_eq!(map.insert("a", 2, 105), Some(1));
+        assert_eq!(map.get(&"a", 115), Some(&2));
+        assert_eq!(map.get(&"a", 116), None);
+
+        // Re-inserting "a" moved its expiry, so the first queue entry does not drop it.
+        map.insert("b", 3, 112);
+        assert_eq!(map.len(), 2);
+        map.insert("c", 4, 120);
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.get(&"a", 120), None);
+        assert_eq!(map.remove(&"b", 120), Some(3));
+        assert_eq!(map.remove(&"c", 131), None);
+        assert!(map.is_empty());
+    }
+
+    #[test]
+    fn test_expired_entries_are_dropped_on_insert() {
+        let mut map = ExpiringMap::new(Duration::from_secs(1));
+        for now in 0..10_000 {
+            map.insert(now, (), now);
+        }
+        assert!(map.len() <= 2);
+        assert!(map.expiries.len() <= 2);
+    }
+}
@@ -4,8 +4,20 @@ pub mod dao;
 /// Implementation of the Data Access Object (DAO) for specific data sources.
 pub mod daoimpl;
 
+/// In-memory maps whose entries expire after a fixed time.
+pub mod expiring;
+
 /// Data models and structures representing entities in the application.
 pub mod models;
 
 /// Session management and user authentication logic.
 pub mod session;
+
+/// Loading of known users from a seed file at startup.
+pub mod seed;
+
+/// Implementation of the Data Access Object (DAO) persisting data in SQLite.
+pub mod sqlite;
+
+/// Audit logs recording the transcript of every authentication attempt.
+pub mod transcript;
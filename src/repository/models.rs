@@ -1,3 +1,11 @@
+use crate::conversion::ByteConvertible;
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::net::IpAddr;
+use uuid::{Builder, Uuid};
+
 /// Represents a user within the system.
 ///
 /// This struct is generic over `T`, allowing for flexibility in the type of data associated with a user.
@@ -6,28 +14,240 @@
 /// - `username`: A `String` representing the username of the user.
 /// - `y1`: A generic field of type `T`.
 /// - `y2`: Another generic field of type `T`.
-/// - `r1`: An `Option<T>` representing an optional field of type `T`.
-/// - `r2`: Another `Option<T>` representing an optional field of type `T`.
+/// - `metadata`: Auxiliary key/value information about the user, such as an email address or roles.
+/// - `namespace`: The tenant the user belongs to; empty for the default namespace.
+/// - `params_version`: The version of the server's group parameters `y1` and `y2` were computed with.
+///
+/// Users serialize with their group elements hex-encoded, e.g. to back up a user data store.
 #[derive(Debug, Clone)]
 pub struct User<T> {
     pub username: String,
     pub y1: T,
     pub y2: T,
-    pub r1: Option<T>,
-    pub r2: Option<T>,
+    pub metadata: HashMap<String, String>,
+    pub namespace: String,
+    pub params_version: u32,
+}
+
+impl<T> User<T> {
+    /// Returns the key the user is stored under, see [`user_key`].
+    pub fn key(&self) -> String {
+        user_key(&self.namespace, &self.username)
+    }
+
+    /// Returns the user's ID, see [`user_id`].
+    pub fn id(&self) -> Uuid {
+        user_id(&self.key())
+    }
+}
+
+/// Separates the namespace from the username in a user's key.
+pub const NAMESPACE_SEPARATOR: &str = "::";
+
+/// Builds the key a user is stored under in a user data store.
+///
+/// # Arguments
+/// * `namespace` - The tenant the user belongs to; empty for the default namespace.
+/// * `username` - The name of the user.
+///
+/// # Returns
+/// `namespace::username`, or just the username in the default namespace.
+pub fn user_key(namespace: &str, username: &str) -> String {
+    if namespace.is_empty() {
+        username.to_string()
+    } else {
+        format!("{}{}{}", namespace, NAMESPACE_SEPARATOR, username)
+    }
+}
+
+/// Derives the stable ID of the user stored under a key.
+///
+/// The ID is a UUID built from a SHA-256 hash of the key, so it is the same across servers and
+/// re-registrations without being stored.
+///
+/// # Arguments
+/// * `key` - The key the user is stored under, see [`user_key`].
+pub fn user_id(key: &str) -> Uuid {
+    let digest = Sha256::new()
+        .chain_update(b"zk_pass.user_id")
+        .chain_update(key.as_bytes())
+        .finalize();
+    let mut bytes = [0u8; 16];
+    bytes.copy_from_slice(&digest[..16]);
+    Builder::from_custom_bytes(bytes).into_uuid()
 }
 
 /// Represents an authentication challenge for a user.
 ///
-/// This struct is generic over `S`, allowing different types of challenges to be used.
+/// This struct is generic over `T` and `S`, allowing different types of commitments and
+/// challenges to be used.
+///
+/// A challenge carries the commitment it was issued for, so that concurrent authentications of
+/// the same user each verify against their own commitment.
 ///
 /// # Fields
 /// - `id`: A `String` representing the unique identifier of the challenge.
 /// - `user`: A `String` representing the username of the user this challenge is associated with.
+/// - `r1`: The prover's first commitment the challenge was issued for.
+/// - `r2`: The prover's second commitment the challenge was issued for.
 /// - `c`: A generic field of type `S` representing the challenge data.
+/// - `nonce`: The server nonce issued with the challenge, which the effective challenge is derived from.
+/// - `ip`: The IP address of the client the challenge was issued to, if the challenge is bound to it.
 #[derive(Debug, Clone)]
-pub struct AuthChallenge<S> {
+pub struct AuthChallenge<T, S> {
     pub id: String,
     pub user: String,
+    pub r1: T,
+    pub r2: T,
     pub c: S,
+    pub nonce: Vec<u8>,
+    pub ip: Option<IpAddr>,
+}
+
+/// The serialized form of a `User`, with every group element hex-encoded.
+#[derive(Serialize, Deserialize)]
+struct EncodedUser {
+    username: String,
+    y1: String,
+    y2: String,
+    metadata: HashMap<String, String>,
+    namespace: String,
+    params_version: u32,
+}
+
+impl<T: ByteConvertible<T>> Serialize for User<T> {
+    fn serialize<Ser: Serializer>(&self, serializer: Ser) -> Result<Ser::Ok, Ser::Error> {
+        EncodedUser {
+            username: self.username.clone(),
+            y1: T::to_hex(&self.y1),
+            y2: T::to_hex(&self.y2),
+            metadata: self.metadata.clone(),
+            namespace: self.namespace.clone(),
+            params_version: self.params_version,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de, T: ByteConvertible<T>> Deserialize<'de> for User<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let encoded = EncodedUser::deserialize(deserializer)?;
+        let decode = |value: &str| T::from_hex(value).map_err(D::Error::custom);
+        Ok(Self {
+            username: encoded.username,
+            y1: decode(&encoded.y1)?,
+            y2: decode(&encoded.y2)?,
+            metadata: encoded.metadata,
+            namespace: encoded.namespace,
+            params_version: encoded.params_version,
+        })
+    }
+}
+
+/// The serialized form of an `AuthChallenge`, with the commitment, challenge and nonce
+/// hex-encoded.
+#[derive(Serialize, Deserialize)]
+struct EncodedAuthChallenge {
+    id: String,
+    user: String,
+    r1: String,
+    r2: String,
+    c: String,
+    nonce: String,
+    ip: Option<IpAddr>,
+}
+
+impl<T: ByteConvertible<T>, S: ByteConvertible<S>> Serialize for AuthChallenge<T, S> {
+    fn serialize<Ser: Serializer>(&self, serializer: Ser) -> Result<Ser::Ok, Ser::Error> {
+        EncodedAuthChallenge {
+            id: self.id.clone(),
+            user: self.user.clone(),
+            r1: T::to_hex(&self.r1),
+            r2: T::to_hex(&self.r2),
+            c: S::to_hex(&self.c),
+            nonce: hex::encode(&self.nonce),
+            ip: self.ip,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de, T: ByteConvertible<T>, S: ByteConvertible<S>> Deserialize<'de> for AuthChallenge<T, S> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let encoded = EncodedAuthChallenge::deserialize(deserializer)?;
+        Ok(Self {
+            id: encoded.id,
+            user: encoded.user,
+            r1: T::from_hex(&encoded.r1).map_err(D::Error::custom)?,
+            r2: T::from_hex(&encoded.r2).map_err(D::Error::custom)?,
+            c: S::from_hex(&encoded.c).map_err(D::Error::custom)?,
+            nonce: hex::decode(&encoded.nonce).map_err(D::Error::custom)?,
+            ip: encoded.ip,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rand::RandomGenerator;
+    use curve25519_dalek::{RistrettoPoint, Scalar};
+
+    fn assert_round_trip(user: &User<RistrettoPoint>) {
+        let json = serde_json::to_string(user).unwrap();
+        let restored: User<RistrettoPoint> = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.username, user.username);
+        assert_eq!((restored.y1, restored.y2), (user.y1, user.y2));
+        assert_eq!(restored.metadata, user.metadata);
+        assert_eq!(restored.namespace, user.namespace);
+        assert_eq!(restored.params_version, user.params_version);
+    }
+
+    #[test]
+    fn test_user_serde_round_trip() {
+        let point = || RistrettoPoint::generate_random().unwrap();
+        let user = User {
+            username: "alice".to_string(),
+            y1: point(),
+            y2: point(),
+            metadata: HashMap::from([("email".to_string(), "alice@example.com".to_string())]),
+            namespace: "acme".to_string(),
+            params_version: 2,
+        };
+        assert_round_trip(&user);
+
+        let json = serde_json::to_value(&user).unwrap();
+        assert_eq!(json["y1"], RistrettoPoint::to_hex(&user.y1));
+        let mut invalid = json.clone();
+        invalid["y2"] = "zz".into();
+        assert!(serde_json::from_value::<User<RistrettoPoint>>(invalid).is_err());
+    }
+
+    #[test]
+    fn test_user_id_is_stable_per_key() {
+        assert_eq!(user_id("acme::alice"), user_id(&user_key("acme", "alice")));
+        assert_ne!(user_id("acme::alice"), user_id("alice"));
+        assert_eq!(user_id("alice").get_version(), Some(uuid::Version::Custom));
+    }
+
+    #[test]
+    fn test_auth_challenge_serde_round_trip() {
+        let challenge = AuthChallenge {
+            id: "id".to_string(),
+            user: "alice".to_string(),
+            r1: RistrettoPoint::generate_random().unwrap(),
+            r2: RistrettoPoint::generate_random().unwrap(),
+            c: Scalar::generate_random().unwrap(),
+            nonce: vec![1, 2, 3],
+            ip: Some("127.0.0.1".parse().unwrap()),
+        };
+        let json = serde_json::to_string(&challenge).unwrap();
+        let restored: AuthChallenge<RistrettoPoint, Scalar> = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.id, challenge.id);
+        assert_eq!(restored.user, challenge.user);
+        assert_eq!((restored.r1, restored.r2), (challenge.r1, challenge.r2));
+        assert_eq!(restored.c, challenge.c);
+        assert_eq!(restored.nonce, challenge.nonce);
+        assert_eq!(restored.ip, challenge.ip);
+    }
 }
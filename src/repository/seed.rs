@@ -0,0 +1,114 @@
+//! # Seed Users Module
+//!
+//! This module loads known users from a JSON seed file so that read-only demo servers can be
+//! preloaded at startup. A seed file holds a list of entries of the form
+//! `{"username": ..., "y1_hex": ..., "y2_hex": ...}`, where `y1` and `y2` are the user's
+//! hex-encoded public values.
+
+use crate::conversion::ByteConvertible;
+use crate::repository::models::User;
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+/// A user entry in a seed file.
+#[derive(Debug, Deserialize)]
+pub struct SeedUser {
+    /// The name of the user.
+    pub username: String,
+    /// The user's hex-encoded `y1`.
+    pub y1_hex: String,
+    /// The user's hex-encoded `y2`.
+    pub y2_hex: String,
+}
+
+impl SeedUser {
+    /// Decodes the entry into a registered user, validating both public values.
+    fn into_user<T: ByteConvertible<T>>(self) -> Result<User<T>, String> {
+        let y1 = T::from_hex(&self.y1_hex)
+            .map_err(|e| format!("Invalid y1 for seed user '{}': {}", self.username, e))?;
+        let y2 = T::from_hex(&self.y2_hex)
+            .map_err(|e| format!("Invalid y2 for seed user '{}': {}", self.username, e))?;
+        Ok(User {
+            username: self.username,
+            y1,
+            y2,
+            metadata: HashMap::new(),
+            namespace: String::new(),
+            params_version: 0,
+        })
+    }
+}
+
+/// Loads the users listed in a JSON seed file.
+///
+/// # Arguments
+/// * `path` - The path to the seed file.
+///
+/// # Returns
+/// The seeded users, or an error message if the file cannot be read or holds invalid data.
+pub fn load_seed_users<T: ByteConvertible<T>>(path: &Path) -> Result<Vec<User<T>>, String> {
+    let json = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read seed file {}: {}", path.display(), e))?;
+    parse_seed_users(&json)
+}
+
+/// Parses the users listed in a JSON seed document.
+///
+/// # Arguments
+/// * `json` - The seed document.
+///
+/// # Returns
+/// The seeded users, or an error message if the document is malformed, a public value does not
+/// decode to a valid group element, or a username appears more than once.
+pub fn parse_seed_users<T: ByteConvertible<T>>(json: &str) -> Result<Vec<User<T>>, String> {
+    let entries: Vec<SeedUser> =
+        serde_json::from_str(json).map_err(|e| format!("Invalid seed file: {}", e))?;
+    let mut usernames = HashSet::new();
+    entries
+        .into_iter()
+        .map(|entry| {
+            if !usernames.insert(entry.username.clone()) {
+                return Err(format!("Duplicate seed user '{}'", entry.username));
+            }
+            entry.into_user()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use curve25519_dalek::RistrettoPoint;
+
+    #[test]
+    fn test_parse_seed_users() {
+        let y = RistrettoPoint::to_hex(&curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT);
+        let json = format!(r#"[{{"username": "alice", "y1_hex": "{y}", "y2_hex": "{y}"}}]"#);
+        let users = parse_seed_users::<RistrettoPoint>(&json).unwrap();
+        assert_eq!(users.len(), 1);
+        assert_eq!(users[0].username, "alice");
+    }
+
+    #[test]
+    fn test_invalid_seed_data_rejected() {
+        let y = RistrettoPoint::to_hex(&curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT);
+        let bad = "ff".repeat(32);
+        let json = format!(r#"[{{"username": "alice", "y1_hex": "{y}", "y2_hex": "{bad}"}}]"#);
+        assert_eq!(
+            parse_seed_users::<RistrettoPoint>(&json).unwrap_err(),
+            "Invalid y2 for seed user 'alice': Invalid bytes encoding"
+        );
+
+        let entry = format!(r#"{{"username": "alice", "y1_hex": "{y}", "y2_hex": "{y}"}}"#);
+        let json = format!("[{entry}, {entry}]");
+        assert_eq!(
+            parse_seed_users::<RistrettoPoint>(&json).unwrap_err(),
+            "Duplicate seed user 'alice'"
+        );
+
+        assert!(parse_seed_users::<RistrettoPoint>("{}")
+            .unwrap_err()
+            .starts_with("Invalid seed file"));
+    }
+}
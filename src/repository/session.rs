@@ -1,55 +1,157 @@
 // Importing necessary modules and traits.
 use cron::Schedule;
 use lazy_static::lazy_static;
+use log::{debug, error, info, warn};
+use prometheus::{register_int_counter, IntCounter};
+use redis::Commands;
+use sha2::{Digest, Sha256};
 use std::collections::HashMap;
 use std::str::FromStr;
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Mutex, Weak};
 use std::thread;
 use std::time::{Duration, Instant};
+use tokio::sync::broadcast;
+use tokio_stream::wrappers::errors::BroadcastStreamRecvError;
+use tokio_stream::wrappers::BroadcastStream;
+use tokio_stream::StreamExt;
+
+/// How long a session stays valid after it was last refreshed, unless configured otherwise.
+pub const DEFAULT_SESSION_TTL: Duration = Duration::from_secs(30 * 60);
+
+/// How many challenge IDs a [`ChallengeBloomFilter`] epoch holds, unless configured otherwise.
+pub const DEFAULT_CHALLENGE_FILTER_CAPACITY: usize = 10_000_000;
+
+/// The false positive rate of a full [`ChallengeBloomFilter`] epoch, unless configured otherwise.
+pub const DEFAULT_CHALLENGE_FILTER_FALSE_POSITIVE_RATE: f64 = 0.01;
+
+/// How many cleanup events an observer may fall behind by before it misses some.
+const CLEANUP_EVENT_CAPACITY: usize = 16;
 
-// Using lazy_static to initialize global static variables.
 lazy_static! {
-    // A static reference to a SessionMap which holds all active sessions.
-    static ref SESSIONS: SessionMap = Arc::new(Mutex::new(HashMap::new()));
-
-    // Initializing and starting the cron scheduler.
-    static ref CRON_SCHEDULER: CronScheduler = {
-        let scheduler = CronScheduler::new(Arc::clone(&SESSIONS));
-        scheduler.start();
-        scheduler
-    };
+    /// The number of expired sessions removed by the cleanups that [`log_session_cleanups`]
+    /// observes.
+    pub static ref SESSIONS_PURGED_TOTAL: IntCounter = register_int_counter!(
+        "zkpass_sessions_purged_total",
+        "The number of expired sessions removed from memory"
+    )
+    .expect("the metric is registered once");
+}
+
+/// A store of authenticated sessions, mapping session IDs to the users they belong to.
+///
+/// Sessions expire once their TTL elapses without being refreshed. Implementations decide where
+/// sessions live, e.g. in process memory or in a store shared between several servers.
+pub trait SessionStore {
+    /// Creates a session for a user, or refreshes it if it already exists.
+    ///
+    /// # Arguments
+    /// - `session_id`: The unique ID of the session.
+    /// - `user`: The username of the user the session belongs to.
+    /// - `ttl`: How long the session stays valid.
+    fn upsert(&self, session_id: &str, user: &str, ttl: Duration);
+
+    /// Invalidates a session, e.g. when the user logs out.
+    ///
+    /// # Arguments
+    /// - `session_id`: The unique ID of the session.
+    ///
+    /// # Returns
+    /// `true` if a valid session was invalidated, `false` if there was none.
+    fn invalidate(&self, session_id: &str) -> bool;
+
+    /// Checks whether a session exists and has not expired.
+    ///
+    /// # Arguments
+    /// - `session_id`: The unique ID of the session.
+    fn is_valid(&self, session_id: &str) -> bool {
+        self.get_user(session_id).is_some()
+    }
+
+    /// Looks up the user a session belongs to.
+    ///
+    /// # Arguments
+    /// - `session_id`: The unique ID of the session.
+    ///
+    /// # Returns
+    /// The username of the session's user, or `None` if the session is unknown or has expired.
+    fn get_user(&self, session_id: &str) -> Option<String>;
 }
 
 /// Represents a user session.
 ///
 /// # Fields
 /// - `user`: A `String` representing the username of the user.
-/// - `last_activity`: An `Instant` representing the last activity time of the session.
+/// - `expires_at`: An `Instant` after which the session is no longer valid.
 #[derive(Debug, Clone)]
 struct Session {
-    #[allow(dead_code)]
     user: String,
-    last_activity: Instant,
+    expires_at: Instant,
 }
 
 // Type alias for a thread-safe, reference-counted session map.
 type SessionMap = Arc<Mutex<HashMap<String, Session>>>;
 
-/// Cleans up expired sessions.
+/// A report of a run of the session cleanup, sent to the observers of an
+/// [`InMemorySessionStore`].
 ///
-/// Retains only those sessions which have had activity in the last 30 minutes.
+/// # Fields
+/// - `purged`: The number of expired sessions removed.
+/// - `remaining`: The number of sessions left in the store.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SessionCleanup {
+    pub purged: usize,
+    pub remaining: usize,
+}
+
+/// Cleans up expired sessions, reporting the run to any observers.
 ///
 /// # Arguments
 /// - `sessions`: Reference to the session map to clean up.
-fn cleanup_sessions(sessions: &SessionMap) {
+/// - `events`: The channel to report the run on.
+///
+/// # Returns
+/// The number of expired sessions removed.
+fn cleanup_sessions(sessions: &SessionMap, events: &broadcast::Sender<SessionCleanup>) -> usize {
     let mut sessions = sessions.lock().unwrap();
-    sessions.retain(|_, session| session.last_activity.elapsed() < Duration::from_secs(30 * 60));
-    println!("Session cleanup performed");
+    let now = Instant::now();
+    let before = sessions.len();
+    sessions.retain(|_, session| session.expires_at > now);
+    let cleanup = SessionCleanup {
+        purged: before - sessions.len(),
+        remaining: sessions.len(),
+    };
+    debug!("Session cleanup removed {} expired sessions", cleanup.purged);
+    // Sending only fails when nobody observes the cleanups.
+    let _ = events.send(cleanup);
+    cleanup.purged
+}
+
+/// Logs the reports of session cleanups and counts the removed sessions in
+/// [`SESSIONS_PURGED_TOTAL`], until the cleanups stop.
+///
+/// # Arguments
+/// - `events`: The reports, from [`InMemorySessionStore::cleanup_events`].
+pub async fn log_session_cleanups(mut events: BroadcastStream<SessionCleanup>) {
+    while let Some(event) = events.next().await {
+        match event {
+            Ok(cleanup) => {
+                info!(
+                    "Session cleanup removed {} expired sessions, {} remaining",
+                    cleanup.purged, cleanup.remaining
+                );
+                SESSIONS_PURGED_TOTAL.inc_by(cleanup.purged as u64);
+            }
+            Err(BroadcastStreamRecvError::Lagged(missed)) => {
+                warn!("Missed the reports of {} session cleanups", missed);
+            }
+        }
+    }
 }
 
 /// A struct for managing a cron scheduler to perform periodic tasks.
 struct CronScheduler {
-    sessions: SessionMap,
+    sessions: Weak<Mutex<HashMap<String, Session>>>,
+    events: broadcast::Sender<SessionCleanup>,
 }
 
 impl CronScheduler {
@@ -57,15 +159,21 @@ impl CronScheduler {
     ///
     /// # Arguments
     /// - `sessions`: SessionMap that the scheduler will use to perform cleanups.
-    fn new(sessions: SessionMap) -> Self {
-        Self { sessions }
+    /// - `events`: The channel to report cleanups on.
+    fn new(sessions: &SessionMap, events: &broadcast::Sender<SessionCleanup>) -> Self {
+        Self {
+            sessions: Arc::downgrade(sessions),
+            events: events.clone(),
+        }
     }
 
     /// Starts the cron scheduler.
     ///
-    /// Initializes a thread to perform session cleanups every 30 minutes.
+    /// Initializes a thread to perform session cleanups every 30 minutes, which exits once the
+    /// session map has been dropped.
     fn start(&self) {
-        let sessions_clone = Arc::clone(&self.sessions);
+        let sessions_weak = Weak::clone(&self.sessions);
+        let events = self.events.clone();
 
         thread::spawn(move || {
             let expression = "0 0/30 * * * * *"; // Every 30 minutes
@@ -76,25 +184,358 @@ impl CronScheduler {
                     let dur = datetime - now;
                     let std_duration = dur.to_std().unwrap();
                     thread::sleep(std_duration);
-                    cleanup_sessions(&sessions_clone); // Call your cleanup function
+                    let Some(sessions) = sessions_weak.upgrade() else {
+                        return;
+                    };
+                    cleanup_sessions(&sessions, &events);
                 }
             }
         });
     }
 }
 
-/// Updates or creates a session for a user.
+/// A [`SessionStore`] keeping sessions in process memory.
 ///
-/// # Arguments
-/// - `user`: A `String` representing the username of the user.
-/// - `session_id`: A `String` representing the unique ID of the session.
-pub fn update_session(user: String, session_id: String) {
-    let mut sessions = SESSIONS.lock().unwrap();
-    sessions.insert(
-        session_id,
-        Session {
-            user,
-            last_activity: Instant::now(),
-        },
-    );
+/// Expired sessions are never returned, and are swept every 30 minutes so that abandoned ones
+/// do not accumulate. Each sweep is reported as a [`SessionCleanup`] to the observers from
+/// [`subscribe`](Self::subscribe) or [`cleanup_events`](Self::cleanup_events).
+pub struct InMemorySessionStore {
+    sessions: SessionMap,
+    events: broadcast::Sender<SessionCleanup>,
+}
+
+impl InMemorySessionStore {
+    /// Constructs a new, empty `InMemorySessionStore` and starts its cleanup schedule.
+    pub fn new() -> Self {
+        let sessions: SessionMap = Arc::new(Mutex::new(HashMap::new()));
+        let (events, _) = broadcast::channel(CLEANUP_EVENT_CAPACITY);
+        CronScheduler::new(&sessions, &events).start();
+        Self { sessions, events }
+    }
+
+    /// Removes the expired sessions now, without waiting for the next scheduled sweep, and
+    /// reports the run to the observers.
+    ///
+    /// # Returns
+    /// The number of expired sessions removed.
+    pub fn cleanup(&self) -> usize {
+        cleanup_sessions(&self.sessions, &self.events)
+    }
+
+    /// Subscribes to the reports of the cleanups run from now on.
+    ///
+    /// # Returns
+    /// A receiver of the reports. A receiver that falls behind misses the oldest reports.
+    pub fn subscribe(&self) -> broadcast::Receiver<SessionCleanup> {
+        self.events.subscribe()
+    }
+
+    /// Subscribes to the reports of the cleanups run from now on, as a stream, e.g. to log them
+    /// or export them as metrics.
+    ///
+    /// # Returns
+    /// A stream of the reports, yielding an error in place of the reports it fell behind on.
+    pub fn cleanup_events(&self) -> BroadcastStream<SessionCleanup> {
+        BroadcastStream::new(self.subscribe())
+    }
+}
+
+impl Default for InMemorySessionStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SessionStore for InMemorySessionStore {
+    fn upsert(&self, session_id: &str, user: &str, ttl: Duration) {
+        let mut sessions = self.sessions.lock().unwrap();
+        sessions.insert(
+            session_id.to_string(),
+            Session {
+                user: user.to_string(),
+                expires_at: Instant::now() + ttl,
+            },
+        );
+    }
+
+    fn invalidate(&self, session_id: &str) -> bool {
+        let mut sessions = self.sessions.lock().unwrap();
+        sessions
+            .remove(session_id)
+            .is_some_and(|session| session.expires_at > Instant::now())
+    }
+
+    fn get_user(&self, session_id: &str) -> Option<String> {
+        let sessions = self.sessions.lock().unwrap();
+        sessions
+            .get(session_id)
+            .filter(|session| session.expires_at > Instant::now())
+            .map(|session| session.user.clone())
+    }
+}
+
+/// A [`SessionStore`] keeping sessions in Redis, so that several servers can share them.
+///
+/// Expiry is left to Redis, which only supports whole seconds; TTLs are rounded up. Each
+/// operation opens a connection and blocks until Redis replies. Redis errors are logged, and
+/// treated as a missing session.
+pub struct RedisSessionStore {
+    client: redis::Client,
+}
+
+impl RedisSessionStore {
+    /// The prefix of the keys sessions are stored under.
+    const KEY_PREFIX: &'static str = "zk_pass:session:";
+
+    /// Constructs a `RedisSessionStore` for the Redis server at `url`.
+    ///
+    /// The URL is only validated here; connections are opened on use.
+    ///
+    /// # Arguments
+    /// - `url`: The Redis connection URL, e.g. `redis://127.0.0.1/`.
+    ///
+    /// # Returns
+    /// The store, or an error if the URL is invalid.
+    pub fn new(url: &str) -> redis::RedisResult<Self> {
+        Ok(Self {
+            client: redis::Client::open(url)?,
+        })
+    }
+
+    /// Returns the key a session is stored under.
+    fn key(session_id: &str) -> String {
+        format!("{}{}", Self::KEY_PREFIX, session_id)
+    }
+
+    /// Opens a connection to Redis, logging any failure.
+    fn connection(&self) -> Option<redis::Connection> {
+        self.client
+            .get_connection()
+            .map_err(|e| error!("Failed to connect to Redis: {}", e))
+            .ok()
+    }
+}
+
+impl SessionStore for RedisSessionStore {
+    fn upsert(&self, session_id: &str, user: &str, ttl: Duration) {
+        let Some(mut conn) = self.connection() else {
+            return;
+        };
+        let seconds = ttl.as_secs() + u64::from(ttl.subsec_nanos() > 0);
+        let result: redis::RedisResult<()> =
+            conn.set_ex(Self::key(session_id), user, seconds.max(1) as usize);
+        if let Err(e) = result {
+            error!("Failed to store session: {}", e);
+        }
+    }
+
+    fn invalidate(&self, session_id: &str) -> bool {
+        let Some(mut conn) = self.connection() else {
+            return false;
+        };
+        match conn.del::<_, usize>(Self::key(session_id)) {
+            Ok(removed) => removed > 0,
+            Err(e) => {
+                error!("Failed to invalidate session: {}", e);
+                false
+            }
+        }
+    }
+
+    fn get_user(&self, session_id: &str) -> Option<String> {
+        let mut conn = self.connection()?;
+        conn.get(Self::key(session_id))
+            .map_err(|e| error!("Failed to read session: {}", e))
+            .ok()
+            .flatten()
+    }
+}
+
+/// A Bloom filter of the IDs of used authentication challenges, so that a challenge is never
+/// issued under an ID whose answer may have been captured.
+///
+/// The filter records the SHA-256 hash of each ID, and never forgets an ID too early: lookups
+/// have no false negatives, but may have false positives. To bound its size, IDs are recorded in
+/// the current of two epochs, each sized for `capacity` IDs at the configured false positive rate.
+/// Once the current epoch is full, it becomes the previous epoch, replacing the oldest, and a new
+/// one is started. An ID is thus remembered for at least `capacity` further insertions. With the
+/// defaults of 10 million IDs at 1%, each epoch takes about 12 MB.
+pub struct ChallengeBloomFilter {
+    current: Vec<u64>,
+    previous: Vec<u64>,
+    inserted: usize,
+    capacity: usize,
+    bits: u64,
+    hashes: u32,
+}
+
+impl ChallengeBloomFilter {
+    /// Constructs an empty filter.
+    ///
+    /// # Arguments
+    /// * `capacity` - The number of IDs an epoch holds before the epochs are rotated.
+    /// * `false_positive_rate` - The rate of false positives of a full epoch, in `(0, 1)`.
+    pub fn new(capacity: usize, false_positive_rate: f64) -> Self {
+        let capacity = capacity.max(1);
+        let ln2 = std::f64::consts::LN_2;
+        // The optimal number of bits is `-n ln(p) / ln(2)^2`, with `m / n ln(2)` hash functions.
+        let bits = (-(capacity as f64) * false_positive_rate.ln() / (ln2 * ln2)).ceil();
+        let bits = (bits as u64).max(64).next_multiple_of(64);
+        let hashes = ((bits as f64 / capacity as f64) * ln2).round().max(1.0) as u32;
+        let words = (bits / 64) as usize;
+        Self {
+            current: vec![0; words],
+            previous: vec![0; words],
+            inserted: 0,
+            capacity,
+            bits,
+            hashes,
+        }
+    }
+
+    /// Records a challenge ID as used, rotating the epochs if the current one is full.
+    ///
+    /// # Arguments
+    /// * `auth_id` - The ID of the challenge.
+    pub fn insert(&mut self, auth_id: &str) {
+        if self.inserted == self.capacity {
+            self.rotate();
+        }
+        for (word, mask) in self.positions(auth_id) {
+            self.current[word] |= mask;
+        }
+        self.inserted += 1;
+    }
+
+    /// Checks whether a challenge ID may have been used.
+    ///
+    /// # Arguments
+    /// * `auth_id` - The ID of the challenge.
+    ///
+    /// # Returns
+    /// `true` if the ID was recorded in either epoch, or in rare cases if it was not.
+    pub fn contains(&self, auth_id: &str) -> bool {
+        let positions: Vec<_> = self.positions(auth_id).collect();
+        [&self.current, &self.previous].iter().any(|epoch| {
+            positions
+                .iter()
+                .all(|&(word, mask)| epoch[word] & mask != 0)
+        })
+    }
+
+    /// Starts a new epoch, forgetting the IDs of the previous one.
+    pub fn rotate(&mut self) {
+        self.previous = std::mem::replace(&mut self.current, vec![0; self.previous.len()]);
+        self.inserted = 0;
+    }
+
+    /// Maps an ID to the bits it sets, as word indices and masks, deriving the hash functions
+    /// from two halves of its SHA-256 hash.
+    fn positions(&self, auth_id: &str) -> impl Iterator<Item = (usize, u64)> {
+        let digest = Sha256::digest(auth_id.as_bytes());
+        let h1 = u64::from_be_bytes(digest[..8].try_into().unwrap());
+        let h2 = u64::from_be_bytes(digest[8..16].try_into().unwrap()) | 1;
+        let bits = self.bits;
+        (0..u64::from(self.hashes)).map(move |i| {
+            let bit = h1.wrapping_add(i.wrapping_mul(h2)) % bits;
+            ((bit / 64) as usize, 1 << (bit % 64))
+        })
+    }
+}
+
+impl Default for ChallengeBloomFilter {
+    fn default() -> Self {
+        Self::new(DEFAULT_CHALLENGE_FILTER_CAPACITY, DEFAULT_CHALLENGE_FILTER_FALSE_POSITIVE_RATE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Checks user lookup, invalidation and TTL expiry against a session store.
+    fn check_session_store(store: &dyn SessionStore, ttl: Duration) {
+        store.upsert("session-1", "alice", ttl);
+        store.upsert("session-2", "bob", ttl * 10);
+        assert!(store.is_valid("session-1"));
+        assert_eq!(store.get_user("session-1").as_deref(), Some("alice"));
+        assert_eq!(store.get_user("session-2").as_deref(), Some("bob"));
+        assert_eq!(store.get_user("unknown"), None);
+
+        assert!(store.invalidate("session-2"));
+        assert!(!store.invalidate("session-2"));
+        assert!(!store.is_valid("session-2"));
+
+        thread::sleep(ttl * 2);
+        assert!(!store.is_valid("session-1"));
+        assert_eq!(store.get_user("session-1"), None);
+    }
+
+    #[test]
+    fn test_in_memory_session_store() {
+        check_session_store(&InMemorySessionStore::new(), Duration::from_millis(50));
+    }
+
+    #[test]
+    fn test_cleanup_reports_purged_sessions() {
+        let store = InMemorySessionStore::new();
+        let mut events = store.subscribe();
+        store.upsert("session-1", "alice", Duration::from_millis(10));
+        store.upsert("session-2", "bob", Duration::from_millis(10));
+        store.upsert("session-3", "carol", Duration::from_secs(60));
+        thread::sleep(Duration::from_millis(20));
+
+        assert_eq!(store.cleanup(), 2);
+        let cleanup = events.try_recv().unwrap();
+        assert_eq!(
+            cleanup,
+            SessionCleanup {
+                purged: 2,
+                remaining: 1
+            }
+        );
+        assert!(store.is_valid("session-3"));
+
+        assert_eq!(store.cleanup(), 0);
+        assert_eq!(events.try_recv().unwrap().purged, 0);
+        assert!(events.try_recv().is_err());
+    }
+
+    #[test]
+    #[ignore = "requires a Redis server at REDIS_URL"]
+    fn test_redis_session_store() {
+        let url = std::env::var("REDIS_URL").unwrap_or_else(|_| "redis://127.0.0.1/".into());
+        let store = RedisSessionStore::new(&url).unwrap();
+        check_session_store(&store, Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_challenge_filter_has_no_false_negatives() {
+        let mut filter = ChallengeBloomFilter::new(100_000, 0.01);
+        let ids: Vec<String> = (0..100_000).map(|i| format!("challenge-{}", i)).collect();
+        for id in &ids {
+            filter.insert(id);
+        }
+        assert!(ids.iter().all(|id| filter.contains(id)));
+
+        let false_positives = (0..100_000)
+            .filter(|i| filter.contains(&format!("other-{}", i)))
+            .count();
+        assert!(false_positives < 2_000, "{} false positives", false_positives);
+    }
+
+    #[test]
+    fn test_challenge_filter_rotates_epochs() {
+        let mut filter = ChallengeBloomFilter::new(2, 0.01);
+        filter.insert("first");
+        filter.insert("second");
+        // The third ID starts a new epoch, still remembering the previous one.
+        filter.insert("third");
+        assert!(filter.contains("first") && filter.contains("second") && filter.contains("third"));
+
+        filter.rotate();
+        assert!(filter.contains("third"));
+        filter.rotate();
+        assert!(!filter.contains("third"));
+    }
 }
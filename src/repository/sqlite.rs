@@ -0,0 +1,429 @@
+// Importing necessary traits, structs, and modules.
+use crate::client::ZkPassError;
+use crate::conversion::ByteConvertible;
+use crate::repository::dao::UserDao;
+use crate::repository::models::{AuthChallenge, User};
+use core::marker::PhantomData;
+use rusqlite::{params, Connection, Params, Row};
+use std::collections::HashMap;
+use std::net::IpAddr;
+use uuid::Uuid;
+
+/// The schema of the tables holding users and authentication challenges.
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS users (
+        key TEXT PRIMARY KEY,
+        username TEXT NOT NULL,
+        namespace TEXT NOT NULL,
+        y1 BLOB NOT NULL,
+        y2 BLOB NOT NULL,
+        metadata TEXT NOT NULL,
+        params_version INTEGER NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS auth_challenges (
+        id TEXT PRIMARY KEY,
+        user TEXT NOT NULL,
+        r1 BLOB NOT NULL,
+        r2 BLOB NOT NULL,
+        c BLOB NOT NULL,
+        nonce BLOB NOT NULL,
+        ip TEXT
+    );";
+
+/// The columns of the `users` table a [`User`] is decoded from, in decoding order.
+const USER_COLUMNS: &str = "username, namespace, y1, y2, metadata, params_version";
+
+/// A User Data Access Object (DAO) persisting users and authentication challenges in SQLite.
+///
+/// Group elements and challenges are stored in their [`ByteConvertible`] encoding, and user
+/// metadata as a JSON object. Like the other DAOs, database errors are reported as missing
+/// entries, and rows that no longer decode are treated as absent. Metadata lookups are the
+/// exception: they report both as `ZkPassError::Unspecified`, so callers can tell them apart
+/// from unknown users.
+///
+/// # Type Parameters
+/// - `T`: Type parameter for User related data.
+/// - `S`: Type parameter for Authentication Challenge related data.
+pub struct SqliteUserDao<T, S> {
+    connection: Connection,
+    _types: PhantomData<(T, S)>,
+}
+
+impl<T, S> SqliteUserDao<T, S> {
+    /// Opens the SQLite database at the given path, creating it and its tables if needed.
+    ///
+    /// # Arguments
+    /// * `path` - The path of the database file, or `:memory:` for a private in-memory database.
+    ///
+    /// # Returns
+    /// The DAO, or an error if the database cannot be opened or initialized.
+    pub fn open(path: &str) -> rusqlite::Result<Self> {
+        Self::with_connection(Connection::open(path)?)
+    }
+
+    /// Opens a private in-memory SQLite database, which is dropped along with the DAO.
+    pub fn open_in_memory() -> rusqlite::Result<Self> {
+        Self::with_connection(Connection::open_in_memory()?)
+    }
+
+    /// Creates the tables on an open connection, unless they already exist.
+    fn with_connection(connection: Connection) -> rusqlite::Result<Self> {
+        connection.execute_batch(SCHEMA)?;
+        Ok(Self {
+            connection,
+            _types: PhantomData,
+        })
+    }
+
+    /// Executes a statement, which is only prepared on its first execution.
+    ///
+    /// # Returns
+    /// The number of changed rows, or `None` if the statement fails.
+    fn execute(&self, sql: &str, params: impl Params) -> Option<usize> {
+        self.connection
+            .prepare_cached(sql)
+            .and_then(|mut statement| statement.execute(params))
+            .ok()
+    }
+
+    /// Maps the first row returned by a query, which is only prepared on its first execution.
+    ///
+    /// # Returns
+    /// The mapped row, or `None` if there is no row or the query fails.
+    fn query_row<R>(
+        &self, sql: &str, params: impl Params, f: impl FnOnce(&Row) -> rusqlite::Result<R>,
+    ) -> Option<R> {
+        self.connection
+            .prepare_cached(sql)
+            .and_then(|mut statement| statement.query_row(params, f))
+            .ok()
+    }
+}
+
+impl<T, S> SqliteUserDao<T, S>
+where
+    T: ByteConvertible<T>,
+{
+    /// Stores a user under a key, either ignoring the user or replacing the existing row on a
+    /// key conflict.
+    ///
+    /// # Returns
+    /// The number of inserted rows, or `None` if the statement fails.
+    fn insert(&self, key: &str, user: &User<T>, conflict: &str) -> Option<usize> {
+        let metadata = serde_json::to_string(&user.metadata).ok()?;
+        let sql = format!(
+            "INSERT OR {} INTO users \
+             (key, username, namespace, y1, y2, metadata, params_version) \
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            conflict
+        );
+        self.execute(
+            &sql,
+            params![
+                key,
+                user.username,
+                user.namespace,
+                T::convert_to(&user.y1),
+                T::convert_to(&user.y2),
+                metadata,
+                user.params_version,
+            ],
+        )
+    }
+
+    /// Decodes a row of the `users` table, selected with [`USER_COLUMNS`].
+    ///
+    /// # Returns
+    /// The user, or `None` if the row does not decode.
+    fn user_from_row(row: &Row) -> rusqlite::Result<Option<User<T>>> {
+        let (username, namespace): (String, String) = (row.get(0)?, row.get(1)?);
+        let (y1, y2): (Vec<u8>, Vec<u8>) = (row.get(2)?, row.get(3)?);
+        let (metadata, params_version): (String, u32) = (row.get(4)?, row.get(5)?);
+
+        let decode = || {
+            Some(User {
+                username,
+                namespace,
+                y1: T::convert_from(&y1).ok()?,
+                y2: T::convert_from(&y2).ok()?,
+                metadata: serde_json::from_str(&metadata).ok()?,
+                params_version,
+            })
+        };
+        Ok(decode())
+    }
+}
+
+impl<T, S> SqliteUserDao<T, S>
+where
+    T: ByteConvertible<T>,
+    S: ByteConvertible<S>,
+{
+    /// Runs a query returning the `user`, `r1`, `r2`, `c`, `nonce` and `ip` columns of an
+    /// authentication challenge, and decodes the row.
+    ///
+    /// # Returns
+    /// The challenge, or `None` if there is no row or it does not decode.
+    fn query_challenge(&self, sql: &str, id: &str) -> Option<AuthChallenge<T, S>> {
+        self.query_row(sql, [id], |row| {
+            let user: String = row.get(0)?;
+            let (r1, r2): (Vec<u8>, Vec<u8>) = (row.get(1)?, row.get(2)?);
+            let (c, nonce): (Vec<u8>, Vec<u8>) = (row.get(3)?, row.get(4)?);
+            let ip: Option<String> = row.get(5)?;
+
+            let decode = || {
+                Some(AuthChallenge {
+                    id: id.to_string(),
+                    user,
+                    r1: T::convert_from(&r1).ok()?,
+                    r2: T::convert_from(&r2).ok()?,
+                    c: S::convert_from(&c).ok()?,
+                    nonce,
+                    ip: match ip {
+                        Some(ip) => Some(ip.parse().ok()?),
+                        None => None,
+                    },
+                })
+            };
+            Ok(decode())
+        })
+        .flatten()
+    }
+}
+
+impl<T, S> UserDao<T, S> for SqliteUserDao<T, S>
+where
+    T: ByteConvertible<T>,
+    S: ByteConvertible<S>,
+{
+    /// Implements the `create` method for user data.
+    ///
+    /// Inserts a row for the user, unless its key is taken.
+    fn create(&mut self, user: User<T>) -> Option<()> {
+        let inserted = self.insert(&user.key(), &user, "IGNORE")?;
+        (inserted > 0).then_some(())
+    }
+
+    /// Implements the `force_create` method for user data.
+    ///
+    /// Inserts a row for the user, replacing any existing row with the same key.
+    fn force_create(&mut self, user: User<T>) -> bool {
+        let key = user.key();
+        let replaced = self.read(&key).is_some();
+        self.insert(&key, &user, "REPLACE");
+        replaced
+    }
+
+    /// Implements the `read` method for user data.
+    ///
+    /// Selects the row stored under the provided key.
+    fn read(&mut self, username: &str) -> Option<User<T>> {
+        self.query_row(
+            &format!("SELECT {} FROM users WHERE key = ?1", USER_COLUMNS),
+            [username],
+            Self::user_from_row,
+        )
+        .flatten()
+    }
+
+    /// Implements the `get_user_metadata` method for user data.
+    ///
+    /// Selects and decodes only the metadata column of the user's row.
+    fn get_user_metadata(
+        &mut self, username: &str,
+    ) -> Result<HashMap<String, String>, ZkPassError> {
+        let metadata: String = self
+            .connection
+            .prepare_cached("SELECT metadata FROM users WHERE key = ?1")
+            .and_then(|mut statement| statement.query_row([username], |row| row.get(0)))
+            .map_err(|e| match e {
+                rusqlite::Error::QueryReturnedNoRows => ZkPassError::UserNotFound,
+                _ => ZkPassError::Unspecified,
+            })?;
+        serde_json::from_str(&metadata).map_err(|_| ZkPassError::Unspecified)
+    }
+
+    /// Implements the `update` method for user data.
+    ///
+    /// Replaces the row stored under the provided name, if there is one.
+    fn update(&mut self, name: &str, user: User<T>) -> Option<()> {
+        self.read(name)?;
+        self.insert(name, &user, "REPLACE").map(|_| ())
+    }
+
+    /// Implements the `update_credentials` method for user data.
+    ///
+    /// Replaces the commitment and parameters version of the user's row.
+    fn update_credentials(&mut self, name: &str, y1: T, y2: T, params_version: u32) -> Option<()> {
+        let updated = self.execute(
+            "UPDATE users SET y1 = ?1, y2 = ?2, params_version = ?3 WHERE key = ?4",
+            params![T::convert_to(&y1), T::convert_to(&y2), params_version, name],
+        )?;
+        (updated > 0).then_some(())
+    }
+
+    /// Implements the `delete` method for user data.
+    ///
+    /// Reads the user's row, then deletes it.
+    fn delete(&mut self, name: &str) -> Option<User<T>> {
+        let user = self.read(name)?;
+        self.execute("DELETE FROM users WHERE key = ?1", [name])?;
+        Some(user)
+    }
+
+    /// Implements the `list` method for user data.
+    ///
+    /// Selects the keys following the cursor, in the database's binary collation order.
+    fn list(&mut self, after: Option<&str>, limit: usize) -> Vec<String> {
+        let Ok(mut statement) = self.connection.prepare_cached(
+            "SELECT key FROM users WHERE ?1 IS NULL OR key > ?1 ORDER BY key LIMIT ?2",
+        ) else {
+            return Vec::new();
+        };
+        let limit = i64::try_from(limit).unwrap_or(i64::MAX);
+        statement
+            .query_map(params![after, limit], |row| row.get(0))
+            .map(|rows| rows.filter_map(Result::ok).collect())
+            .unwrap_or_default()
+    }
+
+    /// Implements the `create_auth_challenge` method.
+    ///
+    /// Inserts a row for a new authentication challenge under a random ID.
+    fn create_auth_challenge(
+        &mut self, user: &str, r1: &T, r2: &T, c: &S, nonce: &[u8], ip: Option<IpAddr>,
+    ) -> String {
+        let uid = Uuid::new_v4().to_string();
+        // The trait has no way to report a failed insert; the challenge is then simply not found.
+        self.execute(
+            "INSERT INTO auth_challenges (id, user, r1, r2, c, nonce, ip) \
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                uid,
+                user,
+                T::convert_to(r1),
+                T::convert_to(r2),
+                S::convert_to(c),
+                nonce,
+                ip.map(|ip| ip.to_string())
+            ],
+        );
+        uid
+    }
+
+    /// Implements the `delete_auth_challenge` method.
+    ///
+    /// Deletes the row of an authentication challenge based on its ID.
+    fn delete_auth_challenge(&mut self, id: &str) {
+        self.execute("DELETE FROM auth_challenges WHERE id = ?1", [id]);
+    }
+
+    /// Implements the `get_authentication_challenge` method.
+    ///
+    /// Selects and decodes the row of an authentication challenge based on its ID.
+    fn get_authentication_challenge(&mut self, id: &str) -> Option<AuthChallenge<T, S>> {
+        self.query_challenge(
+            "SELECT user, r1, r2, c, nonce, ip FROM auth_challenges WHERE id = ?1",
+            id,
+        )
+    }
+
+    /// Implements the `take_authentication_challenge` method.
+    ///
+    /// Deletes the row of an authentication challenge based on its ID and decodes the returned
+    /// row, in a single statement.
+    fn take_authentication_challenge(&mut self, id: &str) -> Option<AuthChallenge<T, S>> {
+        self.query_challenge(
+            "DELETE FROM auth_challenges WHERE id = ?1 RETURNING user, r1, r2, c, nonce, ip",
+            id,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rand::RandomGenerator;
+    use curve25519_dalek::{RistrettoPoint, Scalar};
+
+    type Dao = SqliteUserDao<RistrettoPoint, Scalar>;
+
+    fn user(username: &str) -> User<RistrettoPoint> {
+        User {
+            username: username.to_string(),
+            y1: RistrettoPoint::generate_random().unwrap(),
+            y2: RistrettoPoint::generate_random().unwrap(),
+            metadata: HashMap::from([("email".to_string(), "alice@example.com".to_string())]),
+            namespace: String::new(),
+            params_version: 2,
+        }
+    }
+
+    #[test]
+    fn test_sqlite_user_crud() {
+        let mut dao = Dao::open_in_memory().unwrap();
+        let alice = user("alice");
+        assert_eq!(dao.create(alice.clone()), Some(()));
+        assert_eq!(dao.create(user("alice")), None);
+
+        let read = dao.read("alice").unwrap();
+        assert_eq!((read.y1, read.y2), (alice.y1, alice.y2));
+        assert_eq!(read.params_version, 2);
+        assert_eq!(dao.get_user_metadata("alice"), Ok(alice.metadata.clone()));
+        assert_eq!(dao.get_user_metadata("bob"), Err(ZkPassError::UserNotFound));
+
+        let (y1, y2) = (RistrettoPoint::generate_random().unwrap(), alice.y2);
+        assert_eq!(dao.update_credentials("alice", y1, y2, 3), Some(()));
+        let read = dao.read("alice").unwrap();
+        assert_eq!((read.y1, read.params_version), (y1, 3));
+        assert_eq!(dao.update_credentials("bob", y1, y2, 3), None);
+        assert_eq!(dao.update("bob", user("bob")), None);
+
+        assert!(!dao.force_create(user("bob")));
+        assert!(dao.force_create(user("bob")));
+        assert_eq!(dao.list(None, 10), vec!["alice", "bob"]);
+        assert_eq!(dao.list(Some("alice"), 10), vec!["bob"]);
+        assert_eq!(dao.delete("alice").unwrap().username, "alice");
+        assert!(dao.read("alice").is_none());
+    }
+
+    #[test]
+    fn test_sqlite_metadata_errors_are_not_missing_users() {
+        let mut dao = Dao::open_in_memory().unwrap();
+        dao.create(user("alice")).unwrap();
+        dao.execute("UPDATE users SET metadata = 'not json' WHERE key = ?1", ["alice"])
+            .unwrap();
+        assert_eq!(dao.get_user_metadata("alice"), Err(ZkPassError::Unspecified));
+
+        dao.execute("DROP TABLE users", []).unwrap();
+        assert_eq!(dao.get_user_metadata("alice"), Err(ZkPassError::Unspecified));
+    }
+
+    #[test]
+    fn test_sqlite_auth_challenges() {
+        let mut dao = Dao::open_in_memory().unwrap();
+        let c = Scalar::generate_random().unwrap();
+        let (r1, r2) = (
+            RistrettoPoint::generate_random().unwrap(),
+            RistrettoPoint::generate_random().unwrap(),
+        );
+        let ip: IpAddr = "192.0.2.1".parse().unwrap();
+        let id = dao.create_auth_challenge("alice", &r1, &r2, &c, &[1, 2, 3], Some(ip));
+
+        let challenge = dao.get_authentication_challenge(&id).unwrap();
+        assert_eq!(challenge.user, "alice");
+        assert_eq!((challenge.r1, challenge.r2), (r1, r2));
+        assert_eq!(challenge.c, c);
+        assert_eq!(challenge.nonce, vec![1, 2, 3]);
+        assert_eq!(challenge.ip, Some(ip));
+
+        let taken = dao.take_authentication_challenge(&id).unwrap();
+        assert_eq!(taken.nonce, vec![1, 2, 3]);
+        assert!(dao.get_authentication_challenge(&id).is_none());
+        assert!(dao.take_authentication_challenge(&id).is_none());
+
+        let id = dao.create_auth_challenge("alice", &r1, &r2, &c, &[], None);
+        dao.delete_auth_challenge(&id);
+        assert!(dao.get_authentication_challenge(&id).is_none());
+    }
+}
@@ -0,0 +1,166 @@
+// Importing necessary modules and traits.
+use crate::chaum_pedersen::ProtocolTranscript;
+use log::error;
+use serde::{Deserialize, Serialize};
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex, RwLock};
+
+/// The transcript of an authentication attempt, together with the user who made it.
+///
+/// # Fields
+/// - `user`: The key of the user who attempted to authenticate.
+/// - `transcript`: The protocol messages of the attempt and its outcome.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TranscriptRecord {
+    pub user: String,
+    pub transcript: ProtocolTranscript,
+}
+
+/// An append-only log of authentication attempts, e.g. as an audit trail.
+///
+/// Failed attempts are logged as well as successful ones. Implementations decide where the
+/// records are kept.
+pub trait TranscriptLog {
+    /// Appends the transcript of an authentication attempt.
+    ///
+    /// # Arguments
+    /// - `user`: The key of the user who attempted to authenticate.
+    /// - `transcript`: The protocol messages of the attempt and its outcome.
+    fn append(&self, user: &str, transcript: &ProtocolTranscript);
+}
+
+/// Lets a log be shared, e.g. to read an [`InMemoryTranscriptLog`] while a service appends to it.
+impl<L: TranscriptLog + ?Sized> TranscriptLog for Arc<L> {
+    fn append(&self, user: &str, transcript: &ProtocolTranscript) {
+        (**self).append(user, transcript)
+    }
+}
+
+/// A [`TranscriptLog`] keeping records in process memory.
+pub struct InMemoryTranscriptLog {
+    records: RwLock<Vec<TranscriptRecord>>,
+}
+
+impl InMemoryTranscriptLog {
+    /// Constructs a new, empty `InMemoryTranscriptLog`.
+    pub fn new() -> Self {
+        Self {
+            records: RwLock::new(Vec::new()),
+        }
+    }
+
+    /// Returns every record, oldest first.
+    pub fn records(&self) -> Vec<TranscriptRecord> {
+        self.records.read().unwrap().clone()
+    }
+}
+
+impl Default for InMemoryTranscriptLog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TranscriptLog for InMemoryTranscriptLog {
+    fn append(&self, user: &str, transcript: &ProtocolTranscript) {
+        self.records.write().unwrap().push(TranscriptRecord {
+            user: user.to_string(),
+            transcript: transcript.clone(),
+        });
+    }
+}
+
+/// A [`TranscriptLog`] appending records to a file, one JSON-encoded [`TranscriptRecord`] per
+/// line.
+///
+/// Each record is flushed as it is appended. Write errors are logged, and the record is lost.
+pub struct JsonlTranscriptLog {
+    file: Mutex<File>,
+}
+
+impl JsonlTranscriptLog {
+    /// Opens the log at `path`, creating the file if it does not exist and appending to it
+    /// otherwise.
+    ///
+    /// # Arguments
+    /// - `path`: The path of the log file.
+    ///
+    /// # Returns
+    /// The log, or an error if the file cannot be opened for appending.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self {
+            file: Mutex::new(file),
+        })
+    }
+}
+
+impl TranscriptLog for JsonlTranscriptLog {
+    fn append(&self, user: &str, transcript: &ProtocolTranscript) {
+        let record = TranscriptRecord {
+            user: user.to_string(),
+            transcript: transcript.clone(),
+        };
+        let mut line = serde_json::to_vec(&record).expect("transcript records are serializable");
+        line.push(b'\n');
+        let mut file = self.file.lock().unwrap();
+        if let Err(e) = file.write_all(&line).and_then(|_| file.flush()) {
+            error!("Failed to append the transcript of user: {}: {}", user, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader};
+
+    fn transcript(verified: bool) -> ProtocolTranscript {
+        ProtocolTranscript {
+            y1: vec![1],
+            y2: vec![2],
+            r1: vec![3],
+            r2: vec![4],
+            c: vec![5; 32],
+            s: vec![6; 32],
+            verified,
+            timestamp: 1_700_000_000,
+        }
+    }
+
+    #[test]
+    fn test_jsonl_log_appends_one_record_per_line() {
+        let path =
+            std::env::temp_dir().join(format!("zk_pass_transcripts_{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        JsonlTranscriptLog::open(&path)
+            .unwrap()
+            .append("alice", &transcript(true));
+        // Reopening appends instead of truncating.
+        JsonlTranscriptLog::open(&path)
+            .unwrap()
+            .append("mallory", &transcript(false));
+
+        let records: Vec<TranscriptRecord> = BufReader::new(File::open(&path).unwrap())
+            .lines()
+            .map(|line| serde_json::from_str(&line.unwrap()).unwrap())
+            .collect();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(
+            records,
+            vec![
+                TranscriptRecord {
+                    user: "alice".to_string(),
+                    transcript: transcript(true),
+                },
+                TranscriptRecord {
+                    user: "mallory".to_string(),
+                    transcript: transcript(false),
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,288 @@
+//! # Resumption Token Module
+//!
+//! This module seals and opens the resumption tokens the server hands out with authentication
+//! challenges. A client whose connection drops before it receives the challenge for its
+//! commitment can present the token to the `ResumeAuthentication` method and is issued the
+//! same challenge again, instead of starting over. The token also lets the server verify an
+//! answer to the challenge without looking it up, see the `VerifyAuthenticationStateless` method.
+//!
+//! A token carries its [`ResumeClaims`] followed by an HMAC-SHA256 tag over them, so the server
+//! can trust the claims without storing the tokens it issued.
+
+use crate::codec::{push_field, read_field};
+use crate::conversion::ConversionError;
+use hmac::{Hmac, Mac};
+use rand_core::{OsRng, RngCore};
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::time::Duration;
+
+/// How long a resumption token remains valid after it was issued, unless configured otherwise.
+pub const DEFAULT_RESUME_TOKEN_TTL: Duration = Duration::from_secs(300);
+
+/// The length of the HMAC-SHA256 tag at the end of a token.
+const TAG_LEN: usize = 32;
+
+/// The claims of a resumption token.
+///
+/// # Fields
+/// - `user`: The key of the user the challenge was issued to.
+/// - `auth_id`: The ID of the challenge.
+/// - `r1_hash`: The SHA-256 hash of the encoded commitment `r1` the challenge was issued for.
+/// - `r2_hash`: The SHA-256 hash of the encoded commitment `r2` the challenge was issued for.
+/// - `c_hash`: The SHA-256 hash of the encoded challenge `c`.
+/// - `issued_at`: The Unix time in seconds at which the token was issued.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResumeClaims {
+    pub user: String,
+    pub auth_id: String,
+    pub r1_hash: [u8; 32],
+    pub r2_hash: [u8; 32],
+    pub c_hash: [u8; 32],
+    pub issued_at: u64,
+}
+
+impl ResumeClaims {
+    /// Creates the claims for a challenge issued for a commitment.
+    ///
+    /// # Arguments
+    /// * `user` - The key of the user the challenge is issued to.
+    /// * `auth_id` - The ID of the challenge.
+    /// * `r1` - The encoded commitment `r1`.
+    /// * `r2` - The encoded commitment `r2`.
+    /// * `c` - The encoded challenge `c`.
+    /// * `issued_at` - The Unix time in seconds at which the token is issued.
+    pub fn new(user: &str, auth_id: &str, r1: &[u8], r2: &[u8], c: &[u8], issued_at: u64) -> Self {
+        Self {
+            user: user.to_string(),
+            auth_id: auth_id.to_string(),
+            r1_hash: Sha256::digest(r1).into(),
+            r2_hash: Sha256::digest(r2).into(),
+            c_hash: Sha256::digest(c).into(),
+            issued_at,
+        }
+    }
+
+    /// Checks whether the claims were made for the given commitment.
+    ///
+    /// # Arguments
+    /// * `r1` - The encoded commitment `r1`.
+    /// * `r2` - The encoded commitment `r2`.
+    pub fn matches_commitment(&self, r1: &[u8], r2: &[u8]) -> bool {
+        self.r1_hash[..] == Sha256::digest(r1)[..] && self.r2_hash[..] == Sha256::digest(r2)[..]
+    }
+
+    /// Checks whether the claims were made for the given challenge.
+    ///
+    /// # Arguments
+    /// * `c` - The encoded challenge `c`.
+    pub fn matches_challenge(&self, c: &[u8]) -> bool {
+        self.c_hash[..] == Sha256::digest(c)[..]
+    }
+
+    /// Encodes the claims as length-prefixed fields followed by the big-endian issue time.
+    fn encode(&self) -> Result<Vec<u8>, ConversionError> {
+        let mut bytes = Vec::new();
+        push_field(&mut bytes, self.user.as_bytes())?;
+        push_field(&mut bytes, self.auth_id.as_bytes())?;
+        bytes.extend_from_slice(&self.r1_hash);
+        bytes.extend_from_slice(&self.r2_hash);
+        bytes.extend_from_slice(&self.c_hash);
+        bytes.extend_from_slice(&self.issued_at.to_be_bytes());
+        Ok(bytes)
+    }
+
+    /// Decodes claims from the layout produced by [`ResumeClaims::encode`].
+    fn decode(mut bytes: &[u8]) -> Option<Self> {
+        let user = String::from_utf8(read_field(&mut bytes).ok()?.to_vec()).ok()?;
+        let auth_id = String::from_utf8(read_field(&mut bytes).ok()?.to_vec()).ok()?;
+        if bytes.len() != 32 + 32 + 32 + 8 {
+            return None;
+        }
+        let (r1_hash, rest) = bytes.split_at(32);
+        let (r2_hash, rest) = rest.split_at(32);
+        let (c_hash, issued_at) = rest.split_at(32);
+        Some(Self {
+            user,
+            auth_id,
+            r1_hash: r1_hash.try_into().ok()?,
+            r2_hash: r2_hash.try_into().ok()?,
+            c_hash: c_hash.try_into().ok()?,
+            issued_at: u64::from_be_bytes(issued_at.try_into().ok()?),
+        })
+    }
+}
+
+/// The reasons a resumption token is rejected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResumeTokenError {
+    /// The token is not a well-formed resumption token.
+    Malformed,
+    /// The token's authentication tag does not verify.
+    InvalidTag,
+    /// The token has expired.
+    Expired,
+}
+
+impl fmt::Display for ResumeTokenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ResumeTokenError::Malformed => write!(f, "Malformed resume token"),
+            ResumeTokenError::InvalidTag => write!(f, "Resume token failed verification"),
+            ResumeTokenError::Expired => write!(f, "Resume token expired"),
+        }
+    }
+}
+
+impl std::error::Error for ResumeTokenError {}
+
+/// Seals and opens resumption tokens with an HMAC-SHA256 key.
+#[derive(Clone)]
+pub struct ResumeTokenSealer {
+    key: [u8; 32],
+    ttl: Duration,
+}
+
+impl ResumeTokenSealer {
+    /// Creates a sealer from a 32-byte key.
+    ///
+    /// # Arguments
+    /// * `key` - The HMAC key, which must be kept secret.
+    /// * `ttl` - How long a token remains valid after it was issued.
+    pub fn new(key: [u8; 32], ttl: Duration) -> Self {
+        Self { key, ttl }
+    }
+
+    /// Creates a sealer with a random key, so that tokens are only valid until the server
+    /// restarts.
+    ///
+    /// # Arguments
+    /// * `ttl` - How long a token remains valid after it was issued.
+    pub fn generate(ttl: Duration) -> Self {
+        let mut key = [0u8; 32];
+        OsRng.fill_bytes(&mut key);
+        Self::new(key, ttl)
+    }
+
+    /// Returns how long a token remains valid after it was issued.
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    /// Seals claims into a token.
+    ///
+    /// # Returns
+    /// The encoded claims followed by their HMAC-SHA256 tag, or
+    /// `ConversionError::InvalidLength` if the user or challenge ID is longer than `u16::MAX`
+    /// bytes.
+    pub fn seal(&self, claims: &ResumeClaims) -> Result<Vec<u8>, ConversionError> {
+        let mut token = claims.encode()?;
+        let tag = self.mac(b"token", &token).finalize().into_bytes();
+        token.extend_from_slice(&tag);
+        Ok(token)
+    }
+
+    /// Opens a token, checking its tag and that it has not expired.
+    ///
+    /// # Arguments
+    /// * `token` - The token to open.
+    /// * `now` - The current Unix time in seconds.
+    ///
+    /// # Returns
+    /// The claims of the token, or the reason it is rejected.
+    pub fn open(&self, token: &[u8], now: u64) -> Result<ResumeClaims, ResumeTokenError> {
+        let split = token
+            .len()
+            .checked_sub(TAG_LEN)
+            .ok_or(ResumeTokenError::Malformed)?;
+        let (claims, tag) = token.split_at(split);
+        self.mac(b"token", claims)
+            .verify_slice(tag)
+            .map_err(|_| ResumeTokenError::InvalidTag)?;
+        let claims = ResumeClaims::decode(claims).ok_or(ResumeTokenError::Malformed)?;
+        if claims.issued_at.saturating_add(self.ttl.as_secs()) < now {
+            return Err(ResumeTokenError::Expired);
+        }
+        Ok(claims)
+    }
+
+    /// Derives the challenge nonce for a commitment from the claims of its token, so that a
+    /// resumed authentication is issued the same nonce, and thus the same effective challenge.
+    ///
+    /// The nonce is derived from everything but the challenge ID, which is only assigned once
+    /// the challenge is stored with its nonce, and the challenge, which the nonce is mixed into.
+    ///
+    /// # Arguments
+    /// * `claims` - The claims of the token.
+    /// * `len` - The length of the nonce, at most 32 bytes.
+    ///
+    /// # Returns
+    /// The nonce, or `ConversionError::InvalidLength` if the user is longer than `u16::MAX`
+    /// bytes.
+    pub fn derive_nonce(
+        &self, claims: &ResumeClaims, len: usize,
+    ) -> Result<Vec<u8>, ConversionError> {
+        let mut message = Vec::new();
+        push_field(&mut message, claims.user.as_bytes())?;
+        message.extend_from_slice(&claims.r1_hash);
+        message.extend_from_slice(&claims.r2_hash);
+        message.extend_from_slice(&claims.issued_at.to_be_bytes());
+        Ok(self.mac(b"nonce", &message).finalize().into_bytes()[..len].to_vec())
+    }
+
+    /// Starts an HMAC over a message, separating the purposes the key is used for.
+    fn mac(&self, domain: &[u8], message: &[u8]) -> Hmac<Sha256> {
+        let mut mac =
+            Hmac::<Sha256>::new_from_slice(&self.key).expect("HMAC accepts keys of any length");
+        mac.update(domain);
+        mac.update(message);
+        mac
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn claims() -> ResumeClaims {
+        ResumeClaims::new("alice", "auth-id", b"r1", b"r2", b"c", 1_000)
+    }
+
+    #[test]
+    fn test_seal_open_round_trip() {
+        let sealer = ResumeTokenSealer::generate(Duration::from_secs(60));
+        let token = sealer.seal(&claims()).unwrap();
+        assert_eq!(sealer.open(&token, 1_060), Ok(claims()));
+        assert!(claims().matches_commitment(b"r1", b"r2"));
+        assert!(!claims().matches_commitment(b"r2", b"r1"));
+        assert!(claims().matches_challenge(b"c"));
+        assert!(!claims().matches_challenge(b"r1"));
+    }
+
+    #[test]
+    fn test_open_rejects_bad_tokens() {
+        let sealer = ResumeTokenSealer::generate(Duration::from_secs(60));
+        let token = sealer.seal(&claims()).unwrap();
+        assert_eq!(sealer.open(&token, 1_061), Err(ResumeTokenError::Expired));
+
+        let mut tampered = token.clone();
+        tampered[3] ^= 1;
+        assert_eq!(sealer.open(&tampered, 1_000), Err(ResumeTokenError::InvalidTag));
+        let other = ResumeTokenSealer::generate(Duration::from_secs(60));
+        assert_eq!(other.open(&token, 1_000), Err(ResumeTokenError::InvalidTag));
+        assert_eq!(sealer.open(&token[..TAG_LEN - 1], 1_000), Err(ResumeTokenError::Malformed));
+    }
+
+    #[test]
+    fn test_nonce_is_deterministic() {
+        let sealer = ResumeTokenSealer::generate(Duration::from_secs(60));
+        let nonce = sealer.derive_nonce(&claims(), 16).unwrap();
+        assert_eq!(nonce.len(), 16);
+        assert_eq!(sealer.derive_nonce(&claims(), 16).unwrap(), nonce);
+        let later = ResumeClaims {
+            issued_at: 1_001,
+            ..claims()
+        };
+        assert_ne!(sealer.derive_nonce(&later, 16).unwrap(), nonce);
+    }
+}
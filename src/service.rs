@@ -1,13 +1,55 @@
-use crate::conversion::ByteConvertible;
-use crate::repository::daoimpl::InMemoryUserDao;
-use log::{debug, error, info, trace};
-use tokio::sync::Mutex;
-use tonic::{Request, Response, Status};
+// `tonic::Status` is the error type for every handler in this module.
+#![allow(clippy::result_large_err)]
+
+use crate::codec::push_field;
+use crate::conversion::{ByteConvertible, ConversionError};
+use crate::repository::daoimpl::{AsyncInMemoryUserDao, InMemoryRevocationList};
+use ed25519_dalek::{Signer, SigningKey};
+use ipnet::IpNet;
+use lazy_static::lazy_static;
+use log::{debug, error, info, trace, warn};
+use num_bigint::BigUint;
+use prometheus::{register_int_counter, register_int_gauge, IntCounter, IntGauge};
+use rand_core::{OsRng, RngCore};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::net::{IpAddr, SocketAddr};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use subtle::ConstantTimeEq;
+use tokio::sync::{Mutex, Semaphore};
+use tokio::task::JoinSet;
+use tonic::body::BoxBody;
+use tonic::codegen::{http, BoxFuture, Context, Poll, Service};
+use tonic::metadata::{MetadataMap, MetadataValue};
+use tonic::server::NamedService;
+use tonic::service::Interceptor;
+use tonic::{Code, Request, Response, Status};
+use tower::layer::util::{Identity, Stack};
+use tower::timeout::{error::Elapsed, TimeoutLayer};
+use tower::util::MapErrLayer;
+use tower::{BoxError, ServiceBuilder};
 use uuid::Uuid;
 
 use crate::{
-    chaum_pedersen::{ChaumPedersen, GroupParams},
-    repository::{dao::UserDao, models::User, session::update_session},
+    challenge::{ChallengeSource, DrandChallengeSource, FnChallengeSource, RandomChallengeSource},
+    chaum_pedersen::{
+        discretelog::{
+            DiscreteLogChaumPedersen, DiscreteLogChaumPedersenConfig, InvalidChallengeBits,
+        },
+        transcript::{derive_bound_challenge, derive_sized_nonce_challenge, TranscriptChallenge},
+        ChaumPedersen, GroupParams, ProofBundle, ProtocolTranscript, ValidateGroupParams,
+    },
+    client::ZkPassError,
+    repository::{
+        dao::{AsyncUserDao, RevocationList},
+        expiring::ExpiringMap,
+        models::{user_key, User, NAMESPACE_SEPARATOR},
+        session::{ChallengeBloomFilter, InMemorySessionStore, SessionStore, DEFAULT_SESSION_TTL},
+        transcript::TranscriptLog,
+    },
+    resume::{ResumeClaims, ResumeTokenError, ResumeTokenSealer},
+    token::{SessionClaims, SessionTokenFormat},
 };
 
 // Protobuf generated module
@@ -17,11 +59,446 @@ pub mod zkp_auth {
 
 // Protobuf imports
 use zkp_auth::{
-    auth_server::Auth, AuthenticationAnswerRequest, AuthenticationAnswerResponse,
-    AuthenticationChallengeRequest, AuthenticationChallengeResponse, RegisterRequest,
-    RegisterResponse,
+    admin_server::Admin, auth_server::Auth, health_response::ServingStatus,
+    AuthenticationAnswerRequest, AuthenticationAnswerResponse, AuthenticationChallengeRequest,
+    AuthenticationChallengeResponse, BatchVerifyRequest, BatchVerifyResponse, BatchVerifyResult,
+    GetGroupParamsRequest, GetGroupParamsResponse, GetUserMetadataRequest, HealthRequest,
+    HealthResponse, ListUsersRequest, ListUsersResponse, Metadata, MetadataResponse,
+    MutualAuthenticationRequest, MutualAuthenticationResponse, NegotiateRequest, NegotiateResponse,
+    ProofRequest, RegisterBatchRequest, RegisterBatchResponse, RegisterRequest, RegisterResponse,
+    RegisterResult, ResumeRequest, RevokeUserRequest, RevokeUserResponse, RotateParamsRequest,
+    RotateParamsResponse, ServerIdentityRequest, ServerIdentityResponse, SessionToken,
+    StatelessAnswerRequest, UnrevokeUserRequest, UnrevokeUserResponse, UpdateCredentialsRequest,
+    UpdateCredentialsResponse, ValidateSessionRequest, ValidateSessionResponse, VerifyResponse,
+    ZkPassErrorCode,
 };
 
+/// How long the server's commitment for a challenge is kept in mutual mode, bounding the time
+/// a client has to answer the challenge.
+pub const SERVER_COMMITMENT_TTL: Duration = Duration::from_secs(5 * 60);
+
+/// The domain separator binding the server's identity proofs to this protocol.
+pub const SERVER_IDENTITY_DOMAIN: &[u8] = b"zk_pass.server_identity";
+
+/// The domain separator binding session token signatures to this protocol.
+pub const SESSION_TOKEN_DOMAIN: &[u8] = b"zk_pass.session_token";
+
+/// The length in bytes of the nonce issued with every authentication challenge.
+pub const CHALLENGE_NONCE_LEN: usize = 16;
+
+/// The number of Miller-Rabin rounds checking the primality of rotated group parameters.
+const PRIMALITY_TEST_ROUNDS: usize = 32;
+
+/// The metadata header carrying the token that authorizes requests to the `Admin` service.
+pub const ADMIN_TOKEN_HEADER: &str = "x-admin-token";
+
+/// The metadata header carrying the API key that authorizes registrations.
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+/// The paths of the `Auth` methods gated by [`RegisterApiKey`].
+const REGISTRATION_PATHS: [&str; 2] = ["/zkp_auth.Auth/Register", "/zkp_auth.Auth/RegisterBatch"];
+
+/// The metadata entry carrying the [`ZkPassErrorCode`] of every error status, as a big-endian
+/// `i32`. gRPC requires the `-bin` suffix for binary metadata.
+pub const ERROR_CODE_HEADER: &str = "x-zkpass-error-code-bin";
+
+/// The number of usernames returned by `list_users` when a request does not set a page size.
+pub const DEFAULT_LIST_USERS_PAGE_SIZE: u32 = 100;
+
+/// The maximum number of usernames returned by `list_users` in a single page.
+pub const MAX_LIST_USERS_PAGE_SIZE: u32 = 1000;
+
+/// Builds an error status carrying a machine-readable error code.
+///
+/// # Arguments
+/// * `code` - The gRPC status code.
+/// * `error_code` - The machine-readable code, sent in the [`ERROR_CODE_HEADER`] metadata entry.
+/// * `message` - The human-readable error message.
+fn error_status(code: Code, error_code: ZkPassErrorCode, message: impl Into<String>) -> Status {
+    let mut metadata = MetadataMap::new();
+    metadata.insert_bin(
+        ERROR_CODE_HEADER,
+        MetadataValue::from_bytes(&(error_code as i32).to_be_bytes()),
+    );
+    Status::with_metadata(code, message, metadata)
+}
+
+/// Builds the `Status::not_found` error for requests naming an unregistered user.
+fn user_not_found() -> Status {
+    error_status(Code::NotFound, ZkPassErrorCode::UserNotFound, "User not found")
+}
+
+/// Builds the `Status::resource_exhausted` error for registrations refused because the user
+/// store holds as many users as it can.
+fn user_capacity_reached() -> Status {
+    error_status(Code::ResourceExhausted, ZkPassErrorCode::Unspecified, "User capacity reached")
+}
+
+/// Builds a `Status::invalid_argument` error for a request field that cannot be decoded.
+fn invalid_parameter(message: impl Into<String>) -> Status {
+    error_status(Code::InvalidArgument, ZkPassErrorCode::InvalidParameter, message)
+}
+
+/// Builds the error for a resumption token that cannot be opened.
+///
+/// # Arguments
+/// * `error` - The reason the token is rejected.
+fn resume_token_status(error: ResumeTokenError) -> Status {
+    match error {
+        ResumeTokenError::Expired => error_status(
+            Code::PermissionDenied,
+            ZkPassErrorCode::ChallengeExpired,
+            error.to_string(),
+        ),
+        _ => invalid_parameter(error.to_string()),
+    }
+}
+
+/// Decodes a group element sent by a client, such as `y1` or `r1`.
+///
+/// # Arguments
+/// * `params` - The group parameters the element must belong to.
+/// * `bytes` - The encoded element.
+/// * `name` - The name of the element, for the error message.
+///
+/// # Returns
+/// The element, or a `Status::invalid_argument` error if it cannot be decoded or is not a
+/// non-trivial element of the group.
+fn decode_group_element<T: ByteConvertible<T> + ValidateGroupParams>(
+    params: &GroupParams<T>, bytes: &[u8], name: &str,
+) -> Result<T, Status> {
+    T::convert_from(bytes)
+        .ok()
+        .filter(|element| T::is_valid_group_element(params, element))
+        .ok_or_else(|| invalid_parameter(format!("Invalid {}", name)))
+}
+
+/// Builds an interceptor guarding the `Admin` service, which only admits requests carrying the
+/// given token in the [`ADMIN_TOKEN_HEADER`] header. Tokens are compared in constant time, so
+/// response times do not leak how much of a guess matches.
+///
+/// # Arguments
+/// * `token` - The token administrators must present.
+///
+/// # Returns
+/// An interceptor rejecting requests without the token with `Status::unauthenticated`.
+pub fn admin_token_interceptor(token: String) -> impl Interceptor + Clone {
+    move |request: Request<()>| match request.metadata().get(ADMIN_TOKEN_HEADER) {
+        Some(value) if bool::from(value.as_bytes().ct_eq(token.as_bytes())) => Ok(request),
+        Some(_) => Err(error_status(
+            Code::Unauthenticated,
+            ZkPassErrorCode::Unspecified,
+            "Invalid admin token",
+        )),
+        None => Err(error_status(
+            Code::Unauthenticated,
+            ZkPassErrorCode::Unspecified,
+            "Missing admin token",
+        )),
+    }
+}
+
+/// Wraps the `Auth` service, only admitting registrations that carry the given API key in the
+/// [`API_KEY_HEADER`] header, while authentication stays open to anyone.
+///
+/// Interceptors only see a request's metadata, not the method it calls, so the key is checked
+/// against the HTTP request before it reaches the gRPC service. Like admin tokens, keys are
+/// compared in constant time.
+#[derive(Clone)]
+pub struct RegisterApiKey<S> {
+    inner: S,
+    key: Arc<str>,
+}
+
+impl<S> RegisterApiKey<S> {
+    /// Guards the registration methods of a service with an API key.
+    ///
+    /// # Arguments
+    /// * `inner` - The `Auth` service to guard.
+    /// * `key` - The API key clients must present to register users.
+    pub fn new(inner: S, key: String) -> Self {
+        Self {
+            inner,
+            key: key.into(),
+        }
+    }
+}
+
+impl<S: NamedService> NamedService for RegisterApiKey<S> {
+    const NAME: &'static str = S::NAME;
+}
+
+impl<S, B> Service<http::Request<B>> for RegisterApiKey<S>
+where
+    S: Service<http::Request<B>, Response = http::Response<BoxBody>>,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: http::Request<B>) -> Self::Future {
+        if REGISTRATION_PATHS.contains(&request.uri().path()) {
+            let message = match request.headers().get(API_KEY_HEADER) {
+                Some(value) if bool::from(value.as_bytes().ct_eq(self.key.as_bytes())) => None,
+                Some(_) => Some("Invalid API key"),
+                None => Some("Missing API key"),
+            };
+            if let Some(message) = message {
+                let status =
+                    error_status(Code::Unauthenticated, ZkPassErrorCode::Unspecified, message);
+                return Box::pin(async move { Ok(status.to_http()) });
+            }
+        }
+        Box::pin(self.inner.call(request))
+    }
+}
+
+/// The layer built by [`rpc_timeout_layer`].
+pub type RpcTimeoutLayer =
+    Stack<TimeoutLayer, Stack<MapErrLayer<fn(BoxError) -> BoxError>, Identity>>;
+
+/// Builds a layer failing every request that does not complete within `timeout` with
+/// `Status::deadline_exceeded`.
+///
+/// The handler of a timed out request is dropped, which cancels it at its next `.await`, so a
+/// client stalling mid-protocol does not keep the server busy.
+///
+/// # Arguments
+/// * `timeout` - The time every request is given to complete.
+pub fn rpc_timeout_layer(timeout: Duration) -> RpcTimeoutLayer {
+    ServiceBuilder::new()
+        .map_err(deadline_exceeded as fn(BoxError) -> BoxError)
+        .timeout(timeout)
+        .into_inner()
+}
+
+/// Turns the error of a timed out request into a `Status::deadline_exceeded` error, which tonic
+/// sends as the response, and passes any other error through.
+fn deadline_exceeded(error: BoxError) -> BoxError {
+    if error.is::<Elapsed>() {
+        Box::new(error_status(
+            Code::DeadlineExceeded,
+            ZkPassErrorCode::Unspecified,
+            "Request timed out",
+        ))
+    } else {
+        error
+    }
+}
+
+/// Verifies a response, recording the protocol messages and the outcome in a transcript if
+/// asked to.
+///
+/// # Arguments
+/// * `params` - The group parameters the user is registered with.
+/// * `s` - The response.
+/// * `c` - The effective challenge.
+/// * `cp` - The user's commitments `(y1, y2, r1, r2)`.
+/// * `transcript` - The transcript to fill in.
+/// * `record` - Whether to fill the transcript in.
+///
+/// # Returns
+/// Whether the response verifies, along with the transcript.
+fn verify_recorded<C, T, S>(
+    params: &GroupParams<T>, s: &S, c: &S, cp: &(T, T, T, T), mut transcript: ProtocolTranscript,
+    record: bool,
+) -> (bool, ProtocolTranscript)
+where
+    C: ChaumPedersen<
+        Response = S,
+        Challenge = S,
+        GroupParameters = GroupParams<T>,
+        CommitParameters = (T, T, T, T),
+    >,
+    T: ByteConvertible<T>,
+    S: ByteConvertible<S>,
+{
+    let verified = C::verify_traced(params, s, c, cp, record.then_some(&mut transcript));
+    (verified, transcript)
+}
+
+/// Logs the cancellation of an authentication that is dropped before it completes, e.g.
+/// because the client went away or the request timed out.
+struct CancellationLog<'a> {
+    auth_id: &'a str,
+    completed: bool,
+}
+
+impl<'a> CancellationLog<'a> {
+    /// Starts watching the authentication answering a challenge.
+    fn new(auth_id: &'a str) -> Self {
+        Self {
+            auth_id,
+            completed: false,
+        }
+    }
+
+    /// Marks the authentication as completed, whatever its outcome.
+    fn complete(mut self) {
+        self.completed = true;
+    }
+}
+
+impl Drop for CancellationLog<'_> {
+    fn drop(&mut self) {
+        if !self.completed {
+            warn!("✂️ Authentication for challenge: {} cancelled", self.auth_id);
+        }
+    }
+}
+
+lazy_static! {
+    /// The number of requests a [`LoadShedder`] is currently admitting.
+    pub static ref IN_FLIGHT_REQUESTS: IntGauge = register_int_gauge!(
+        "zkpass_in_flight_requests",
+        "The number of requests being handled"
+    )
+    .expect("the metric is registered once");
+
+    /// The number of requests a [`LoadShedder`] has rejected.
+    pub static ref REQUESTS_SHED_TOTAL: IntCounter = register_int_counter!(
+        "zkpass_requests_shed_total",
+        "The number of requests rejected because too many were being handled"
+    )
+    .expect("the metric is registered once");
+}
+
+/// Sheds load under overload by rejecting requests beyond a maximum number in flight, before
+/// they queue up behind the user data store.
+///
+/// The number of admitted requests is exported in the [`IN_FLIGHT_REQUESTS`] gauge, and the
+/// rejected requests are counted in [`REQUESTS_SHED_TOTAL`].
+pub struct LoadShedder {
+    max_queue_depth: usize,
+    current: AtomicUsize,
+}
+
+impl LoadShedder {
+    /// Creates a load shedder admitting up to `max_queue_depth` requests at a time.
+    ///
+    /// # Arguments
+    /// * `max_queue_depth` - The maximum number of requests in flight.
+    pub fn new(max_queue_depth: usize) -> Self {
+        Self {
+            max_queue_depth,
+            current: AtomicUsize::new(0),
+        }
+    }
+
+    /// Admits a request if fewer than the maximum number are in flight.
+    ///
+    /// # Returns
+    /// A guard counting the request as in flight until it is dropped, or a
+    /// `Status::resource_exhausted` error if the request is shed.
+    fn admit(&self) -> Result<InFlightRequest<'_>, Status> {
+        if self.current.fetch_add(1, Ordering::SeqCst) >= self.max_queue_depth {
+            self.current.fetch_sub(1, Ordering::SeqCst);
+            REQUESTS_SHED_TOTAL.inc();
+            return Err(error_status(
+                Code::ResourceExhausted,
+                ZkPassErrorCode::Unspecified,
+                "Server is overloaded",
+            ));
+        }
+        IN_FLIGHT_REQUESTS.inc();
+        Ok(InFlightRequest(self))
+    }
+}
+
+/// A request admitted by a [`LoadShedder`], in flight until dropped.
+struct InFlightRequest<'a>(&'a LoadShedder);
+
+impl Drop for InFlightRequest<'_> {
+    fn drop(&mut self) {
+        self.0.current.fetch_sub(1, Ordering::SeqCst);
+        IN_FLIGHT_REQUESTS.dec();
+    }
+}
+
+/// Builds the message signed in a session token.
+///
+/// # Arguments
+/// * `user` - The key of the authenticated user.
+/// * `session_id` - The ID of the session.
+/// * `expiry` - The Unix time in seconds after which the session is no longer valid.
+///
+/// # Returns
+/// The [`SESSION_TOKEN_DOMAIN`], followed by the length-prefixed user and session ID and the
+/// big-endian expiry, or `ConversionError::InvalidLength` if the user or session ID is longer
+/// than `u16::MAX` bytes.
+pub fn session_token_message(
+    user: &str, session_id: &str, expiry: u64,
+) -> Result<Vec<u8>, ConversionError> {
+    let mut message = SESSION_TOKEN_DOMAIN.to_vec();
+    push_field(&mut message, user.as_bytes())?;
+    push_field(&mut message, session_id.as_bytes())?;
+    message.extend_from_slice(&expiry.to_be_bytes());
+    Ok(message)
+}
+
+/// Returns the current Unix time in seconds.
+fn unix_time() -> Result<u64, Status> {
+    Ok(SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|_| {
+            error_status(
+                Code::Internal,
+                ZkPassErrorCode::Unspecified,
+                "System clock is before the Unix epoch",
+            )
+        })?
+        .as_secs())
+}
+
+/// The versions of the group parameters a service holds.
+///
+/// Rotating the parameters adds a version, which becomes the current one. Users keep
+/// authenticating with the version they registered with, while registrations with older
+/// versions are only accepted until `effective_after`.
+struct ParamsVersions<T> {
+    /// The group parameters by version; the highest version is the current one.
+    versions: BTreeMap<u32, GroupParams<T>>,
+    /// The Unix time, in seconds, after which only the current version accepts registrations.
+    effective_after: u64,
+}
+
+impl<T> ParamsVersions<T> {
+    /// Holds the initial group parameters as version 0.
+    fn new(params: GroupParams<T>) -> Self {
+        Self {
+            versions: BTreeMap::from([(0, params)]),
+            effective_after: 0,
+        }
+    }
+
+    /// Returns the current version and its group parameters.
+    fn current(&self) -> (u32, &GroupParams<T>) {
+        let (version, params) = self
+            .versions
+            .last_key_value()
+            .expect("the current version is never removed");
+        (*version, params)
+    }
+
+    /// Checks whether registrations computed with a version are accepted at time `now`.
+    fn accepts_registrations(&self, version: u32, now: u64) -> bool {
+        version == self.current().0
+            || (self.versions.contains_key(&version) && now <= self.effective_after)
+    }
+}
+
+/// The Unix time, in seconds, at which each commitment `r1` was first seen, by user key.
+type SeenCommitments = HashMap<(String, Vec<u8>), u64>;
+
+/// The server's commitment random `k` for a challenge in mutual mode, along with its encoded
+/// commitment `(r1, r2)`.
+type ServerCommitment<S> = (S, Vec<u8>, Vec<u8>);
+
 /// A struct representing the zero-knowledge authentication service.
 /// It supports different types of Chaum-Pedersen protocols.
 ///
@@ -30,187 +507,4690 @@ use zkp_auth::{
 /// * `C`: Represents the type of Chaum-Pedersen protocol.
 /// * `T`: The type used for group elements.
 /// * `S`: The type used for scalar values.
-pub struct ZkAuth<C, T, S> {
-    params: GroupParams<T>,
-    dao: Mutex<Box<dyn UserDao<T, S> + Send + Sync>>,
+/// * `D`: The user data store. Defaults to an in-memory store.
+///
+/// Clones share the user data store, the sessions, the revoked users, the versions of the group parameters and any
+/// pending challenge state, so several services, e.g. listening on different ports, can be
+/// backed by the same store.
+pub struct ZkAuth<C, T, S, D = AsyncInMemoryUserDao<T, S>> {
+    params: Arc<RwLock<ParamsVersions<T>>>,
+    dao: Arc<D>,
+    bind_challenge_to_ip: bool,
+    allowed_cidrs: Vec<IpNet>,
+    denied_cidrs: Vec<IpNet>,
+    proof_bundle_ttl: Duration,
+    used_proof_bundles: Arc<Mutex<HashMap<Vec<u8>, u64>>>,
+    commitment_reuse_ttl: Option<Duration>,
+    seen_commitments: Arc<Mutex<SeenCommitments>>,
+    server_secret: Option<S>,
+    server_commitments: Arc<Mutex<ExpiringMap<String, ServerCommitment<S>>>>,
+    resume_tokens: Option<ResumeTokenSealer>,
+    answered_challenges: Arc<Mutex<HashMap<String, u64>>>,
+    challenge_filter: Option<Arc<Mutex<ChallengeBloomFilter>>>,
+    credential_updates: Arc<Mutex<()>>,
+    signing_key: Option<SigningKey>,
+    session_token_format: SessionTokenFormat,
+    protocol_type: Option<String>,
+    namespace: String,
+    sessions: Arc<dyn SessionStore + Send + Sync>,
+    revocations: Arc<dyn RevocationList + Send + Sync>,
+    challenge_source: Option<Arc<dyn ChallengeSource<T, S>>>,
+    challenge_bits: u32,
+    transcript_log: Option<Arc<dyn TranscriptLog + Send + Sync>>,
+    load_shedder: Option<Arc<LoadShedder>>,
+    verify_timeout: Option<(Duration, Arc<Semaphore>)>,
     _type_phantom: std::marker::PhantomData<C>,
     _scalar_phantom: std::marker::PhantomData<S>,
 }
 
+impl<C, T: Clone, S: Clone, D> Clone for ZkAuth<C, T, S, D> {
+    fn clone(&self) -> Self {
+        Self {
+            params: self.params.clone(),
+            dao: self.dao.clone(),
+            bind_challenge_to_ip: self.bind_challenge_to_ip,
+            allowed_cidrs: self.allowed_cidrs.clone(),
+            denied_cidrs: self.denied_cidrs.clone(),
+            proof_bundle_ttl: self.proof_bundle_ttl,
+            used_proof_bundles: self.used_proof_bundles.clone(),
+            commitment_reuse_ttl: self.commitment_reuse_ttl,
+            seen_commitments: self.seen_commitments.clone(),
+            server_secret: self.server_secret.clone(),
+            server_commitments: self.server_commitments.clone(),
+            resume_tokens: self.resume_tokens.clone(),
+            answered_challenges: self.answered_challenges.clone(),
+            challenge_filter: self.challenge_filter.clone(),
+            credential_updates: self.credential_updates.clone(),
+            signing_key: self.signing_key.clone(),
+            session_token_format: self.session_token_format.clone(),
+            protocol_type: self.protocol_type.clone(),
+            namespace: self.namespace.clone(),
+            sessions: self.sessions.clone(),
+            revocations: self.revocations.clone(),
+            challenge_source: self.challenge_source.clone(),
+            challenge_bits: self.challenge_bits,
+            transcript_log: self.transcript_log.clone(),
+            load_shedder: self.load_shedder.clone(),
+            verify_timeout: self.verify_timeout.clone(),
+            _type_phantom: std::marker::PhantomData,
+            _scalar_phantom: std::marker::PhantomData,
+        }
+    }
+}
+
 impl<
         C,
         T: std::marker::Send + std::marker::Sync + std::clone::Clone + ByteConvertible<T> + 'static,
         S: std::marker::Send + std::marker::Sync + std::clone::Clone + ByteConvertible<S> + 'static,
     > ZkAuth<C, T, S>
 {
+    /// Creates the service with an in-memory user data store.
+    ///
+    /// # Arguments
+    /// * `params` - The group parameters used in the protocol.
     pub fn new(params: GroupParams<T>) -> Self {
-        let dao = Mutex::new(
-            Box::new(InMemoryUserDao::<T, S>::new()) as Box<dyn UserDao<T, S> + Send + Sync>
-        );
+        Self::with_dao(params, AsyncInMemoryUserDao::new())
+    }
+}
+
+impl<
+        C,
+        T: std::marker::Send + std::marker::Sync + std::clone::Clone + ByteConvertible<T> + 'static,
+        S: std::marker::Send + std::marker::Sync + std::clone::Clone + ByteConvertible<S> + 'static,
+        D: AsyncUserDao<T, S>,
+    > ZkAuth<C, T, S, D>
+{
+    /// Creates the service backed by the given user data store.
+    ///
+    /// # Arguments
+    /// * `params` - The group parameters used in the protocol, as version 0.
+    /// * `dao` - The store holding registered users and pending challenges.
+    pub fn with_dao(params: GroupParams<T>, dao: D) -> Self {
         Self {
-            params,
-            dao,
+            params: Arc::new(RwLock::new(ParamsVersions::new(params))),
+            dao: Arc::new(dao),
+            bind_challenge_to_ip: false,
+            allowed_cidrs: Vec::new(),
+            denied_cidrs: Vec::new(),
+            proof_bundle_ttl: Duration::from_secs(300),
+            used_proof_bundles: Arc::new(Mutex::new(HashMap::new())),
+            commitment_reuse_ttl: None,
+            seen_commitments: Arc::new(Mutex::new(HashMap::new())),
+            server_secret: None,
+            server_commitments: Arc::new(Mutex::new(ExpiringMap::new(SERVER_COMMITMENT_TTL))),
+            resume_tokens: None,
+            answered_challenges: Arc::new(Mutex::new(HashMap::new())),
+            challenge_filter: None,
+            credential_updates: Arc::new(Mutex::new(())),
+            signing_key: None,
+            session_token_format: SessionTokenFormat::Uuid,
+            protocol_type: None,
+            namespace: String::new(),
+            sessions: Arc::new(InMemorySessionStore::new()),
+            revocations: Arc::new(InMemoryRevocationList::new()),
+            challenge_source: None,
+            challenge_bits: 0,
+            transcript_log: None,
+            load_shedder: None,
+            verify_timeout: None,
             _type_phantom: std::marker::PhantomData,
             _scalar_phantom: std::marker::PhantomData,
         }
     }
-}
 
-/// Implementation of the `Auth` trait for `ZkAuth`.
-///
-/// This implementation provides the necessary methods for user registration,
-/// creating authentication challenges, and verifying authentication answers.
-/// It uses generic parameters `C`, `T`, and `S` to work with different cryptographic protocols and data types.
-///
-/// `C` represents a specific Chaum-Pedersen protocol implementation.
-/// `T` is the type for group parameters and public information.
-/// `S` is the scalar type used for cryptographic operations.
-#[tonic::async_trait]
-impl<C, T, S> Auth for ZkAuth<C, T, S>
-where
-    T: Send + Sync + 'static + Clone + ByteConvertible<T>,
-    S: Send + Sync + 'static + Clone + ByteConvertible<S>,
-    C: ChaumPedersen<
-            Response = S,
-            CommitmentRandom = S,
-            Challenge = S,
-            Secret = S,
-            GroupParameters = GroupParams<T>,
-            CommitParameters = (T, T, T, T),
-        >
-        + 'static
-        + std::marker::Sync
-        + std::marker::Send,
-{
-    // Register a user with provided credentials.
-    // This method accepts a `RegisterRequest` and returns a `RegisterResponse`.
-    //
-    // # Arguments
-    // * `request` - A `Request<RegisterRequest>` containing the user's registration information.
-    //
-    // # Returns
-    // A `Result` containing a `Response<RegisterResponse>` on success, or a `Status` error on failure.
-    async fn register(
-        &self, request: Request<RegisterRequest>,
-    ) -> Result<Response<RegisterResponse>, Status> {
-        trace!("register: {:?}", request);
-        let req = request.into_inner();
+    /// Returns the current group parameters, which new registrations use.
+    pub fn params(&self) -> GroupParams<T> {
+        self.params.read().unwrap().current().1.clone()
+    }
 
-        let y1 =
-            T::convert_from(&req.y1).or_else(|_| Err(Status::invalid_argument("Invalid y1")))?;
-        let y2 =
-            T::convert_from(&req.y2).or_else(|_| Err(Status::invalid_argument("Invalid y2")))?;
+    /// Returns the version of the current group parameters.
+    pub fn params_version(&self) -> u32 {
+        self.params.read().unwrap().current().0
+    }
 
-        let user = User {
-            username: req.user.clone(),
-            y1,
-            y2,
-            r1: None,
-            r2: None,
+    /// Returns the group parameters of a version.
+    ///
+    /// # Arguments
+    /// * `version` - The version of the group parameters, e.g. the one a user registered with.
+    ///
+    /// # Returns
+    /// The group parameters, or a `Status::failed_precondition` error if the version is unknown.
+    fn params_for(&self, version: u32) -> Result<GroupParams<T>, Status> {
+        self.params
+            .read()
+            .unwrap()
+            .versions
+            .get(&version)
+            .cloned()
+            .ok_or_else(|| {
+                error_status(
+                    Code::FailedPrecondition,
+                    ZkPassErrorCode::InvalidParameter,
+                    "Unknown group parameters version",
+                )
+            })
+    }
+
+    /// Rotates to new group parameters without disrupting registered users.
+    ///
+    /// The new parameters become the current version, which new registrations should use. Users
+    /// registered with an older version keep authenticating against it, and may move to the new
+    /// version by updating their credentials. Registrations and credential updates computed with
+    /// an older version are accepted until `effective_after`; after that, older versions are
+    /// removed as soon as no registered user relies on them.
+    ///
+    /// # Arguments
+    /// * `params` - The new group parameters.
+    /// * `effective_after` - The Unix time, in seconds, after which registrations with older
+    ///   versions are refused.
+    ///
+    /// # Returns
+    /// The version assigned to the new parameters.
+    pub async fn rotate_group_params(&self, params: GroupParams<T>, effective_after: u64) -> u32 {
+        let version = {
+            let mut params_versions = self.params.write().unwrap();
+            let version = params_versions.current().0 + 1;
+            params_versions.versions.insert(version, params);
+            params_versions.effective_after = effective_after;
+            version
         };
+        self.prune_params_versions().await;
+        version
+    }
 
-        let mut dao = self.dao.lock().await;
-        dao.create(user);
+    /// Removes the older versions of the group parameters that no longer accept registrations
+    /// and that no registered user relies on anymore.
+    async fn prune_params_versions(&self) {
+        let Ok(now) = unix_time() else {
+            return;
+        };
+        if now <= self.params.read().unwrap().effective_after {
+            return;
+        }
 
-        let reply = RegisterResponse {};
-        trace!("register reply: {:?}", reply);
-        Ok(Response::new(reply))
-    }
+        let mut in_use = BTreeSet::new();
+        let page_size = MAX_LIST_USERS_PAGE_SIZE as usize;
+        let mut after = None;
+        loop {
+            let keys = self.dao.list(after.as_deref(), page_size).await;
+            for key in &keys {
+                if let Some(user) = self.dao.read(key).await {
+                    in_use.insert(user.params_version);
+                }
+            }
+            match keys.last() {
+                Some(last) if keys.len() == page_size => after = Some(last.clone()),
+                _ => break,
+            }
+        }
 
-    // Create an authentication challenge for a user.
-    // This method accepts an `AuthenticationChallengeRequest` and returns an `AuthenticationChallengeResponse`.
-    //
-    // # Arguments
-    // * `request` - A `Request<AuthenticationChallengeRequest>` containing the user's information.
-    //
-    // # Returns
-    // A `Result` containing a `Response<AuthenticationChallengeResponse>` on success, or a `Status` error on failure.
-    async fn create_authentication_challenge(
-        &self, request: Request<AuthenticationChallengeRequest>,
-    ) -> Result<Response<AuthenticationChallengeResponse>, Status> {
-        trace!("create_authentication_challenge request: {:?}", request);
-        let req = request.into_inner();
-        let challenge = C::challenge(&self.params);
+        // Registrations with older versions may have been reopened by a rotation in the meantime.
+        let mut params_versions = self.params.write().unwrap();
+        if now <= params_versions.effective_after {
+            return;
+        }
+        let current = params_versions.current().0;
+        params_versions
+            .versions
+            .retain(|version, _| *version == current || in_use.contains(version));
+    }
 
-        let user = {
-            let mut dao = self.dao.lock().await;
-            let mut user = dao
-                .read(&req.user)
-                .ok_or_else(|| Status::not_found("User not found"))?;
-            user.r1 = Some(
-                T::convert_from(&req.r1)
-                    .or_else(|_| Err(Status::invalid_argument("Invalid r1")))?,
+    /// Checks that registrations computed with a version of the group parameters are accepted.
+    ///
+    /// # Arguments
+    /// * `version` - The version named by the request.
+    ///
+    /// # Returns
+    /// `Ok(())` if the version is current, or an older version still accepting registrations, or
+    /// a `Status::failed_precondition` error otherwise.
+    fn check_registration_params_version(&self, version: u32) -> Result<(), Status> {
+        if !self
+            .params
+            .read()
+            .unwrap()
+            .accepts_registrations(version, unix_time()?)
+        {
+            let message = format!(
+                "Group parameters version {} is not accepted for registration; the current version is {}",
+                version,
+                self.params_version()
             );
-            user.r2 = Some(
-                T::convert_from(&req.r2)
-                    .or_else(|_| Err(Status::invalid_argument("Invalid r2")))?,
-            );
-            user.clone()
-        };
+            return Err(error_status(
+                Code::FailedPrecondition,
+                ZkPassErrorCode::InvalidParameter,
+                message,
+            ));
+        }
+        Ok(())
+    }
 
-        let auth_id = {
-            let mut dao = self.dao.lock().await;
-            dao.update(&user.username, user.clone());
-            dao.create_auth_challenge(&req.user, &challenge)
-        };
+    /// Returns the store holding the sessions of authenticated users.
+    pub fn sessions(&self) -> &(dyn SessionStore + Send + Sync) {
+        self.sessions.as_ref()
+    }
 
-        let reply = AuthenticationChallengeResponse {
-            auth_id,
-            c: S::convert_to(&challenge),
-        };
-        trace!("create_authentication_challenge reply: {:?}", reply);
-        Ok(Response::new(reply))
+    /// Registers known users before the service starts serving, e.g. from a seed file.
+    ///
+    /// # Arguments
+    /// * `users` - The users to register.
+    ///
+    /// # Returns
+    /// `Ok(())` if every user was registered, or an error message naming the first user that
+    /// already exists or does not fit in the user store.
+    pub async fn seed_users(&self, users: Vec<User<T>>) -> Result<(), String> {
+        for mut user in users {
+            user.namespace = self.namespace.clone();
+            let (username, key) = (user.username.clone(), user.key());
+            if self.dao.create(user).await.is_none() {
+                return Err(match self.dao.read(&key).await {
+                    Some(_) => format!("Seed user '{}' already exists", username),
+                    None => format!("Seed user '{}' does not fit in the user store", username),
+                });
+            }
+        }
+        Ok(())
     }
 
-    // Verify an authentication challenge answer from a user.
-    // This method accepts an `AuthenticationAnswerRequest` and returns an `AuthenticationAnswerResponse`.
-    //
-    // # Arguments
-    // * `request` - A `Request<AuthenticationAnswerRequest>` containing the user's authentication answer.
-    //
-    // # Returns
-    // A `Result` containing a `Response<AuthenticationAnswerResponse>` on success, or a `Status` error on failure.
-    async fn verify_authentication(
-        &self, request: Request<AuthenticationAnswerRequest>,
-    ) -> Result<Response<AuthenticationAnswerResponse>, Status> {
-        trace!("verify_authentication: {:?}", request);
-        let req = request.into_inner();
+    /// Enables or disables binding authentication challenges to the client's IP address.
+    ///
+    /// When enabled, a challenge can only be answered from the IP address it was issued to,
+    /// which prevents a relay from forwarding a challenge to another client.
+    ///
+    /// # Arguments
+    /// * `enabled` - Whether challenges should be bound to the client's IP address.
+    pub fn with_challenge_ip_binding(mut self, enabled: bool) -> Self {
+        self.bind_challenge_to_ip = enabled;
+        self
+    }
 
-        let challenge = {
-            let mut dao = self.dao.lock().await;
-            dao.get_authentication_challenge(&req.auth_id)
-                .ok_or_else(|| Status::not_found("Challenge not found"))?
-        };
+    /// Restricts the service to clients whose address falls within one of the given networks.
+    ///
+    /// An empty list allows every address, which is the default.
+    ///
+    /// # Arguments
+    /// * `cidrs` - The networks clients are allowed to connect from.
+    pub fn with_allowed_cidrs(mut self, cidrs: Vec<IpNet>) -> Self {
+        self.allowed_cidrs = cidrs;
+        self
+    }
 
-        let user = {
-            let mut dao = self.dao.lock().await;
-            dao.read(&challenge.user)
-                .ok_or_else(|| Status::not_found("User not found"))?
-        };
+    /// Rejects clients whose address falls within one of the given networks.
+    ///
+    /// The denylist takes precedence over the allowlist.
+    ///
+    /// # Arguments
+    /// * `cidrs` - The networks clients are not allowed to connect from.
+    pub fn with_denied_cidrs(mut self, cidrs: Vec<IpNet>) -> Self {
+        self.denied_cidrs = cidrs;
+        self
+    }
 
-        let s = S::convert_from(&req.s).or_else(|_| Err(Status::invalid_argument("Invalid s")))?;
-        let params = self.params.clone();
-        let verified = C::verify(
-            &params,
-            &s,
-            &challenge.c,
-            &(user.y1, user.y2, user.r1.unwrap(), user.r2.unwrap()),
+    /// Enables mutual authentication, with the server proving knowledge of its own secret.
+    ///
+    /// Every challenge then carries a server commitment, which the server answers in
+    /// `mutual_authenticate` so clients holding the server's public values can verify it. The
+    /// commitment is dropped after [`SERVER_COMMITMENT_TTL`], so challenges must be answered
+    /// within it.
+    ///
+    /// # Arguments
+    /// * `x` - The server's secret.
+    pub fn with_server_secret(mut self, x: S) -> Self {
+        self.server_secret = Some(x);
+        self
+    }
+
+    /// Signs the session issued on every successful authentication with an Ed25519 key.
+    ///
+    /// Authentication responses then carry a [`SessionToken`], which other services holding
+    /// the key's public half can verify offline, e.g. with
+    /// [`verify_session_token`](crate::client::verify_session_token).
+    ///
+    /// # Arguments
+    /// * `key` - The server's signing key.
+    pub fn with_signing_key(mut self, key: SigningKey) -> Self {
+        self.signing_key = Some(key);
+        self
+    }
+
+    /// Sets the form of the session IDs issued on successful authentication. Defaults to
+    /// [`SessionTokenFormat::Uuid`].
+    ///
+    /// JWT and PASETO session IDs are self-contained tokens, which `validate_session` checks
+    /// without consulting the session store.
+    ///
+    /// # Arguments
+    /// * `format` - The form of the session IDs, holding the key tokens are protected with.
+    pub fn with_session_token_format(mut self, format: SessionTokenFormat) -> Self {
+        self.session_token_format = format;
+        self
+    }
+
+    /// Sets the name of the protocol type this service runs, such as `ec25519` or
+    /// `rfc5114_modp_2048_256`, enabling protocol negotiation.
+    ///
+    /// # Arguments
+    /// * `protocol_type` - The name clients must list to select this service's protocol.
+    pub fn with_protocol_type(mut self, protocol_type: impl Into<String>) -> Self {
+        self.protocol_type = Some(protocol_type.into());
+        self
+    }
+
+    /// Confines the service to a namespace, isolating its users from those of other tenants
+    /// sharing the same user data store.
+    ///
+    /// Requests may then only name this namespace or leave it empty. Without a namespace, which
+    /// is the default, each request selects its own.
+    ///
+    /// # Arguments
+    /// * `namespace` - The tenant whose users this service serves.
+    ///
+    /// # Panics
+    /// Panics if `namespace` contains the namespace separator `::`.
+    pub fn with_namespace(mut self, namespace: &str) -> Self {
+        assert!(
+            !namespace.contains(NAMESPACE_SEPARATOR),
+            "namespace must not contain '{}'",
+            NAMESPACE_SEPARATOR
         );
+        self.namespace = namespace.to_string();
+        self
+    }
 
-        debug!("User: {} verified", user.username);
-        if !verified {
-            error!("Invalid authentication for user: {}", user.username);
-            return Err(Status::invalid_argument("Invalid authentication"));
-        }
-        let session_id = Uuid::new_v4().to_string();
-        update_session(user.username.clone(), session_id.clone()); // Clone session_id before moving it
-        let reply = AuthenticationAnswerResponse { session_id };
+    /// Sets the store holding the sessions of authenticated users. Defaults to an
+    /// [`InMemorySessionStore`].
+    ///
+    /// # Arguments
+    /// * `sessions` - The session store, e.g. a Redis store shared between several servers.
+    pub fn with_session_store(mut self, sessions: Box<dyn SessionStore + Send + Sync>) -> Self {
+        self.sessions = Arc::from(sessions);
+        self
+    }
 
-        let mut dao = self.dao.lock().await;
-        dao.delete_auth_challenge(&req.auth_id);
+    /// Sets the list of users whose credentials are revoked. Defaults to an
+    /// [`InMemoryRevocationList`].
+    ///
+    /// # Arguments
+    /// * `revocations` - The revocation list, e.g. one shared between several servers.
+    pub fn with_revocation_list(
+        mut self, revocations: Box<dyn RevocationList + Send + Sync>,
+    ) -> Self {
+        self.revocations = Arc::from(revocations);
+        self
+    }
 
-        info!("🔑 User: {} authenticated, session id: {}", user.username, req.auth_id);
-        trace!("verify_authentication reply: {:?}", reply);
-        Ok(Response::new(reply))
+    /// Returns the list of users whose credentials are revoked.
+    pub fn revocations(&self) -> &(dyn RevocationList + Send + Sync) {
+        self.revocations.as_ref()
+    }
+
+    /// Takes the challenges issued to users from the given source instead of drawing them with
+    /// [`RandomChallengeSource`], e.g. so that a trusted third party supplies them.
+    ///
+    /// # Arguments
+    /// * `source` - The source of the challenges.
+    pub fn with_challenge_source(mut self, source: impl ChallengeSource<T, S> + 'static) -> Self {
+        self.challenge_source = Some(Arc::new(source));
+        self
+    }
+
+    /// Generates the challenges issued to users with the given function instead of the
+    /// protocol's default.
+    ///
+    /// The generated challenge is hashed with the nonce issued alongside it into the effective
+    /// challenge users answer, see [`derive_sized_nonce_challenge`], which spans the full group
+    /// order. A generator therefore cannot shorten the challenges users answer; use
+    /// [`ZkAuth::with_challenge_config`] for that.
+    ///
+    /// # Arguments
+    /// * `generator` - The function generating a challenge for a user's group parameters.
+    pub fn with_challenge_generator(
+        self, generator: impl Fn(&GroupParams<T>) -> S + Send + Sync + 'static,
+    ) -> Self {
+        self.with_challenge_source(FnChallengeSource(generator))
+    }
+
+    /// Mixes the latest round of a drand randomness beacon into every challenge with a
+    /// [`DrandChallengeSource`], as defence in depth against weak local randomness. The server
+    /// still chooses the challenges, which are refused while the beacon cannot be reached.
+    ///
+    /// # Arguments
+    /// * `url` - The base URL of the beacon's HTTP API, e.g. `https://api.drand.sh`.
+    pub fn with_drand_url(self, url: impl Into<String>) -> Self
+    where
+        S: TranscriptChallenge<GroupParams<T>>,
+    {
+        self.with_challenge_source(DrandChallengeSource::new(url))
+    }
+
+    /// Records the transcript of every verified authentication answer, successful or not, in a log.
+    ///
+    /// # Arguments
+    /// * `log` - The transcript log, e.g. a file kept as an audit trail.
+    pub fn with_transcript_log(mut self, log: Box<dyn TranscriptLog + Send + Sync>) -> Self {
+        self.transcript_log = Some(Arc::from(log));
+        self
+    }
+
+    /// Sets how long a proof bundle remains valid after its timestamp. Defaults to 5 minutes.
+    ///
+    /// # Arguments
+    /// * `ttl` - The maximum age of an accepted proof bundle.
+    pub fn with_proof_bundle_ttl(mut self, ttl: Duration) -> Self {
+        self.proof_bundle_ttl = ttl;
+        self
+    }
+
+    /// Rejects challenge requests that reuse an `r1` the same user sent within the given window.
+    /// Answering two challenges for the same commitment reveals the secret, so this protects
+    /// naive clients that do not draw a fresh commitment for every attempt.
+    ///
+    /// # Arguments
+    /// * `ttl` - How long a commitment is remembered after it was first seen.
+    pub fn with_commitment_reuse_guard(mut self, ttl: Duration) -> Self {
+        self.commitment_reuse_ttl = Some(ttl);
+        self
+    }
+
+    /// Hands out a resumption token with every challenge, which a client that lost the reply
+    /// can present to `ResumeAuthentication` to be issued the same challenge again. The token
+    /// also enables `VerifyAuthenticationStateless`, unless challenges are bound to IP addresses.
+    ///
+    /// # Arguments
+    /// * `sealer` - Seals and opens the tokens.
+    pub fn with_resumption(mut self, sealer: ResumeTokenSealer) -> Self {
+        self.resume_tokens = Some(sealer);
+        self
+    }
+
+    /// Records the IDs of used challenges in a Bloom filter, and refuses to issue a challenge
+    /// under a recorded ID with `Status::aborted`, so that a captured answer can never be
+    /// replayed against a recycled ID. Clients may retry, as false positives are possible.
+    ///
+    /// # Arguments
+    /// * `filter` - The filter, e.g. `ChallengeBloomFilter::default()`.
+    pub fn with_challenge_filter(mut self, filter: ChallengeBloomFilter) -> Self {
+        self.challenge_filter = Some(Arc::new(Mutex::new(filter)));
+        self
+    }
+
+    /// Rejects requests with `Status::resource_exhausted` while the given number of requests is
+    /// already being handled, so that the service stays responsive under overload instead of
+    /// queueing every request behind the user data store. Health checks are always answered.
+    ///
+    /// # Arguments
+    /// * `max_concurrent_requests` - The maximum number of requests handled at a time.
+    pub fn with_load_shedding(mut self, max_concurrent_requests: usize) -> Self {
+        self.load_shedder = Some(Arc::new(LoadShedder::new(max_concurrent_requests)));
+        self
+    }
+
+    /// Fails verifications that take longer than the given time with
+    /// `Status::deadline_exceeded`. Verification then runs on a blocking thread, so that slow
+    /// modular exponentiations, e.g. with 2048-bit discrete log parameters, do not stall other
+    /// requests.
+    ///
+    /// The deadline only stops the request from waiting: a verification that timed out cannot
+    /// be cancelled and keeps its thread until it completes. At most
+    /// `max_blocking_verifications` verifications therefore run at a time, timed out or not, and
+    /// further answers are rejected with `Status::resource_exhausted` until one completes.
+    ///
+    /// # Arguments
+    /// * `timeout` - The time every verification is given to complete.
+    /// * `max_blocking_verifications` - The maximum number of verifications running at a time.
+    pub fn with_verify_timeout(
+        mut self, timeout: Duration, max_blocking_verifications: usize,
+    ) -> Self {
+        let slots = Arc::new(Semaphore::new(max_blocking_verifications));
+        self.verify_timeout = Some((timeout, slots));
+        self
+    }
+
+    /// Admits a request through the load shedder, if load shedding is enabled.
+    ///
+    /// # Returns
+    /// A guard to hold while the request is handled, or a `Status::resource_exhausted` error
+    /// if the request is shed.
+    fn admit(&self) -> Result<Option<InFlightRequest<'_>>, Status> {
+        self.load_shedder
+            .as_deref()
+            .map(LoadShedder::admit)
+            .transpose()
+    }
+
+    /// Remembers a user's commitment, if the commitment reuse guard is enabled.
+    ///
+    /// # Arguments
+    /// * `key` - The key of the user sending the commitment.
+    /// * `r1` - The encoded commitment `r1`.
+    ///
+    /// # Returns
+    /// `Ok(())` if the commitment was not seen within the window, or a
+    /// `Status::failed_precondition` error otherwise.
+    async fn check_commitment_not_reused(&self, key: &str, r1: &[u8]) -> Result<(), Status> {
+        let Some(ttl) = self.commitment_reuse_ttl else {
+            return Ok(());
+        };
+        let now = unix_time()?;
+        let mut seen = self.seen_commitments.lock().await;
+        seen.retain(|_, timestamp| timestamp.saturating_add(ttl.as_secs()) >= now);
+        if seen.insert((key.to_string(), r1.to_vec()), now).is_some() {
+            error!("Reused commitment for user: {}", key);
+            return Err(error_status(
+                Code::FailedPrecondition,
+                ZkPassErrorCode::CommitmentReused,
+                "Commitment already used",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Starts a session for an authenticated user.
+    ///
+    /// # Arguments
+    /// * `user` - The key of the authenticated user.
+    ///
+    /// # Returns
+    /// The session ID, in the service's session token format, and, when the service has a
+    /// signing key, a session token signed with it.
+    fn start_session(&self, user: &str) -> Result<(String, Option<SessionToken>), Status> {
+        let iat = unix_time()?;
+        let expiry = iat + DEFAULT_SESSION_TTL.as_secs();
+        let claims = SessionClaims {
+            sub: user.to_string(),
+            exp: expiry,
+            iat,
+            jti: Uuid::new_v4().to_string(),
+        };
+        let session_id = self.session_token_format.issue(&claims).map_err(|e| {
+            error_status(Code::Internal, ZkPassErrorCode::Unspecified, e.to_string())
+        })?;
+        self.sessions.upsert(&session_id, user, DEFAULT_SESSION_TTL);
+        let session_token = match &self.signing_key {
+            Some(key) => {
+                let message = session_token_message(user, &session_id, expiry)
+                    .map_err(|_| invalid_parameter("User name is too long"))?;
+                Some(SessionToken {
+                    user: user.to_string(),
+                    session_id: session_id.clone(),
+                    expiry,
+                    signature: key.sign(&message).to_bytes().to_vec(),
+                })
+            }
+            None => None,
+        };
+        Ok((session_id, session_token))
+    }
+
+    /// Verifies an offline proof bundle against the user's registered commitment.
+    ///
+    /// A bundle is only accepted once, and only while its timestamp is within the proof bundle
+    /// TTL of the current time; the challenges of accepted bundles are remembered until they
+    /// expire so a captured bundle cannot be replayed.
+    ///
+    /// # Arguments
+    /// * `bundle` - The proof bundle to verify.
+    ///
+    /// # Returns
+    /// `Ok(true)` if the proof is valid, `Ok(false)` if it does not verify, or a `Status` error
+    /// if the user is not registered or revoked, or the bundle is expired or has already been
+    /// used.
+    pub async fn verify_proof_bundle(&self, bundle: &ProofBundle<C>) -> Result<bool, Status>
+    where
+        C: ChaumPedersen<
+            Response = S,
+            Challenge = S,
+            GroupParameters = GroupParams<T>,
+            CommitParameters = (T, T, T, T),
+        >,
+        S: TranscriptChallenge<GroupParams<T>> + PartialEq,
+    {
+        let now = unix_time()?;
+        let ttl = self.proof_bundle_ttl.as_secs();
+        if bundle.timestamp.saturating_add(ttl) < now || bundle.timestamp > now.saturating_add(ttl)
+        {
+            error!("Expired proof bundle for user: {}", bundle.user);
+            return Err(error_status(
+                Code::PermissionDenied,
+                ZkPassErrorCode::ChallengeExpired,
+                "Proof bundle expired",
+            ));
+        }
+
+        let key = self.resolve_user_key("", &bundle.user)?;
+        self.check_not_revoked(&key)?;
+        let user = self.dao.read(&key).await.ok_or_else(user_not_found)?;
+        let params = self.params_for(user.params_version)?;
+        if !bundle.verify(&params, &user.y1, &user.y2) {
+            error!("Invalid proof bundle for user: {}", bundle.user);
+            return Ok(false);
+        }
+
+        let mut used = self.used_proof_bundles.lock().await;
+        used.retain(|_, timestamp| timestamp.saturating_add(ttl) >= now);
+        if used
+            .insert(S::convert_to(&bundle.c), bundle.timestamp)
+            .is_some()
+        {
+            error!("Replayed proof bundle for user: {}", bundle.user);
+            return Err(error_status(
+                Code::PermissionDenied,
+                ZkPassErrorCode::VerificationFailed,
+                "Proof bundle already used",
+            ));
+        }
+
+        info!("🔑 User: {} verified proof bundle", bundle.user);
+        Ok(true)
+    }
+
+    /// Reads the client's address from a request and checks it against the allowlist and denylist.
+    ///
+    /// # Arguments
+    /// * `request` - The incoming request.
+    ///
+    /// # Returns
+    /// The client's address, if known, or a `Status::permission_denied` error if the address is
+    /// denied, or is unknown or outside the allowlist while an allowlist is configured.
+    fn check_peer<R>(&self, request: &Request<R>) -> Result<Option<SocketAddr>, Status> {
+        let addr = request.remote_addr();
+        debug!("Request from peer: {:?}", addr);
+
+        let ip = addr.map(|addr| addr.ip());
+        let denied = ip.is_some_and(|ip| self.denied_cidrs.iter().any(|net| net.contains(&ip)));
+        let allowed = self.allowed_cidrs.is_empty()
+            || ip.is_some_and(|ip| self.allowed_cidrs.iter().any(|net| net.contains(&ip)));
+        if denied || !allowed {
+            error!("Rejected request from peer: {:?}", addr);
+            return Err(error_status(
+                Code::PermissionDenied,
+                ZkPassErrorCode::Unspecified,
+                "Client address not allowed",
+            ));
+        }
+        Ok(addr)
+    }
+
+    /// Registers a user, overwriting any existing registration with the same name.
+    ///
+    /// Unlike the `register` RPC, which refuses to replace an existing user, this is not exposed
+    /// to clients and is intended for administrative overrides such as resetting a user's
+    /// credentials.
+    ///
+    /// # Arguments
+    /// * `req` - The registration request containing the username and `y1`/`y2` commitments.
+    ///
+    /// # Returns
+    /// `Ok(true)` if an existing user was overwritten, `Ok(false)` if the user is new, or a
+    /// `Status::invalid_argument` error if `y1` or `y2` cannot be decoded or lie outside the group.
+    pub async fn force_register(&self, req: &RegisterRequest) -> Result<bool, Status>
+    where
+        T: ValidateGroupParams,
+    {
+        let user = self.user_from_request(req)?;
+        Ok(self.dao.force_create(user).await)
+    }
+
+    /// Resolves the namespace a request operates in.
+    ///
+    /// # Arguments
+    /// * `requested` - The namespace named by the request, or empty if it names none.
+    ///
+    /// # Returns
+    /// The service's namespace if it has one, otherwise the requested namespace. Fails with
+    /// `Status::permission_denied` if the request names a different namespace than the service's,
+    /// or with `Status::invalid_argument` if it contains the namespace separator.
+    fn resolve_namespace(&self, requested: &str) -> Result<String, Status> {
+        if requested.contains(NAMESPACE_SEPARATOR) {
+            return Err(invalid_parameter("Invalid namespace"));
+        }
+        if self.namespace.is_empty() {
+            Ok(requested.to_string())
+        } else if requested.is_empty() || requested == self.namespace {
+            Ok(self.namespace.clone())
+        } else {
+            Err(error_status(
+                Code::PermissionDenied,
+                ZkPassErrorCode::Unspecified,
+                "Namespace not served",
+            ))
+        }
+    }
+
+    /// Resolves the key a user is stored under, see [`user_key`].
+    ///
+    /// # Arguments
+    /// * `namespace` - The namespace named by the request, or empty if it names none.
+    /// * `username` - The name of the user.
+    ///
+    /// # Returns
+    /// The user's key, or an error if the namespace is not served or the username contains the
+    /// namespace separator.
+    fn resolve_user_key(&self, namespace: &str, username: &str) -> Result<String, Status> {
+        if username.contains(NAMESPACE_SEPARATOR) {
+            return Err(invalid_parameter("Invalid username"));
+        }
+        Ok(user_key(&self.resolve_namespace(namespace)?, username))
+    }
+
+    /// Rejects users whose credentials are revoked.
+    ///
+    /// # Arguments
+    /// * `key` - The user's key.
+    ///
+    /// # Returns
+    /// `Ok(())` if the user may authenticate, or a `Status::permission_denied` error otherwise.
+    fn check_not_revoked(&self, key: &str) -> Result<(), Status> {
+        if self.revocations.is_revoked(key) {
+            return Err(error_status(
+                Code::PermissionDenied,
+                ZkPassErrorCode::Unspecified,
+                "User credentials are revoked",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Builds a `User` from a registration request, validating the commitment values.
+    ///
+    /// # Arguments
+    /// * `req` - The registration request containing the username and `y1`/`y2` commitments.
+    ///
+    /// # Returns
+    /// The decoded `User`, or a `Status::invalid_argument` error if `y1` or `y2` cannot be decoded
+    /// or lie outside the group, or the username contains the namespace separator. Fails with
+    /// `Status::failed_precondition` if registrations with the requested version of the group
+    /// parameters are not accepted.
+    fn user_from_request(&self, req: &RegisterRequest) -> Result<User<T>, Status>
+    where
+        T: ValidateGroupParams,
+    {
+        if req.user.contains(NAMESPACE_SEPARATOR) {
+            return Err(invalid_parameter("Invalid username"));
+        }
+        let namespace = self.resolve_namespace(&req.namespace)?;
+        self.check_registration_params_version(req.params_version)?;
+        let params = self.params_for(req.params_version)?;
+        let y1 = decode_group_element(&params, &req.y1, "y1")?;
+        let y2 = decode_group_element(&params, &req.y2, "y2")?;
+
+        Ok(User {
+            username: req.user.clone(),
+            y1,
+            y2,
+            metadata: req
+                .metadata
+                .iter()
+                .map(|m| (m.key.clone(), m.value.clone()))
+                .collect(),
+            namespace,
+            params_version: req.params_version,
+        })
+    }
+
+    /// Verifies a user's answer to a pending authentication challenge, against the group
+    /// parameters the user registered with.
+    ///
+    /// The challenge is taken out of the store before the response is checked, so that of
+    /// concurrent answers to it only one is verified, and it is recorded as answered, so that it
+    /// cannot be answered statelessly either.
+    ///
+    /// # Arguments
+    /// * `auth_id` - The ID of the authentication challenge being answered.
+    /// * `s` - The encoded response to the challenge.
+    /// * `nonce` - The nonce the client received with the challenge.
+    /// * `ip` - The IP address of the client answering the challenge, if known.
+    ///
+    /// # Returns
+    /// The `User` the challenge was issued to if the response is valid, or a `Status` error if
+    /// the challenge or user cannot be found, the user's credentials are revoked, the challenge
+    /// is bound to a different IP address, the nonce does not match, the response does not
+    /// verify against the challenge derived from the nonce, or the challenge was already
+    /// answered.
+    async fn verify_answer(
+        &self, auth_id: &str, s: &[u8], nonce: &[u8], ip: Option<IpAddr>,
+    ) -> Result<User<T>, Status>
+    where
+        C: ChaumPedersen<
+                Response = S,
+                Challenge = S,
+                GroupParameters = GroupParams<T>,
+                CommitParameters = (T, T, T, T),
+            > + 'static,
+        S: TranscriptChallenge<GroupParams<T>>,
+    {
+        let not_found = || {
+            error_status(Code::NotFound, ZkPassErrorCode::ChallengeNotFound, "Challenge not found")
+        };
+        let challenge = self
+            .dao
+            .get_authentication_challenge(auth_id)
+            .await
+            .ok_or_else(not_found)?;
+        self.check_not_revoked(&challenge.user)?;
+
+        if challenge.ip.is_some() && challenge.ip != ip {
+            error!("Challenge for user: {} answered from a different address", challenge.user);
+            return Err(error_status(
+                Code::PermissionDenied,
+                ZkPassErrorCode::VerificationFailed,
+                "Challenge was issued to a different address",
+            ));
+        }
+
+        if challenge.nonce != nonce {
+            error!("Challenge for user: {} answered with the wrong nonce", challenge.user);
+            return Err(error_status(
+                Code::PermissionDenied,
+                ZkPassErrorCode::VerificationFailed,
+                "Invalid challenge nonce",
+            ));
+        }
+
+        // Answers from elsewhere or with the wrong nonce are rejected above without using up the
+        // challenge, which is only taken once the answer is about to be verified.
+        let challenge = self
+            .dao
+            .take_authentication_challenge(auth_id)
+            .await
+            .ok_or_else(not_found)?;
+        let user = self
+            .dao
+            .read(&challenge.user)
+            .await
+            .ok_or_else(user_not_found)?;
+        let params = self.params_for(user.params_version)?;
+        let c = self.effective_challenge(&params, &challenge.nonce, &challenge.c);
+
+        self.check_response(&user, &params, s, &c, challenge.r1, challenge.r2)
+            .await?;
+
+        // The token was issued before now, so it expires before the record is dropped.
+        let now = unix_time()?;
+        if !self.record_answered(auth_id, now, now).await {
+            error!("Challenge: {} was already answered statelessly", auth_id);
+            return Err(error_status(
+                Code::PermissionDenied,
+                ZkPassErrorCode::VerificationFailed,
+                "Challenge already answered",
+            ));
+        }
+        Ok(user)
+    }
+
+    /// Checks a user's response to a challenge for a commitment, logging the transcript.
+    ///
+    /// # Arguments
+    /// * `user` - The user answering the challenge.
+    /// * `params` - The group parameters the user is registered with.
+    /// * `s` - The encoded response.
+    /// * `c` - The effective challenge, with the nonce mixed in.
+    /// * `r1` - The user's commitment `r1`.
+    /// * `r2` - The user's commitment `r2`.
+    ///
+    /// # Returns
+    /// `Ok(())` if the response verifies, or a `Status::invalid_argument` error otherwise.
+    async fn check_response(
+        &self, user: &User<T>, params: &GroupParams<T>, s: &[u8], c: &S, r1: T, r2: T,
+    ) -> Result<(), Status>
+    where
+        C: ChaumPedersen<
+                Response = S,
+                Challenge = S,
+                GroupParameters = GroupParams<T>,
+                CommitParameters = (T, T, T, T),
+            > + 'static,
+    {
+        let s = S::convert_from(s).map_err(|_| invalid_parameter("Invalid s"))?;
+
+        // Verification runs to completion once started, so yield first: a request that was
+        // cancelled while the store was queried is dropped here instead of burning CPU.
+        tokio::task::yield_now().await;
+        let transcript = ProtocolTranscript::new(unix_time()?);
+        let cp = (user.y1.clone(), user.y2.clone(), r1, r2);
+        let record = self.transcript_log.is_some();
+        let (verified, transcript) = match &self.verify_timeout {
+            None => verify_recorded::<C, T, S>(params, &s, c, &cp, transcript, record),
+            Some((timeout, slots)) => {
+                // The slot moves into the blocking task, so that it is only released once the
+                // verification completes, even if the request timed out long before.
+                let slot = slots.clone().try_acquire_owned().map_err(|_| {
+                    warn!("Verification for user: {} rejected, all slots busy", user.username);
+                    error_status(
+                        Code::ResourceExhausted,
+                        ZkPassErrorCode::Unspecified,
+                        "Too many verifications in progress",
+                    )
+                })?;
+                let (params, c) = (params.clone(), c.clone());
+                let verification = tokio::task::spawn_blocking(move || {
+                    let _slot = slot;
+                    verify_recorded::<C, T, S>(&params, &s, &c, &cp, transcript, record)
+                });
+                match tokio::time::timeout(*timeout, verification).await {
+                    Ok(result) => result.map_err(|e| {
+                        error!("Verification for user: {} failed to run: {}", user.username, e);
+                        Status::internal("Verification failed to run")
+                    })?,
+                    Err(_) => {
+                        error!("Verification for user: {} timed out", user.username);
+                        return Err(error_status(
+                            Code::DeadlineExceeded,
+                            ZkPassErrorCode::Unspecified,
+                            "Verification timed out",
+                        ));
+                    }
+                }
+            }
+        };
+        if let Some(log) = &self.transcript_log {
+            log.append(&user.key(), &transcript);
+        }
+
+        debug!("User: {} verified", user.username);
+        if !verified {
+            error!("Invalid authentication for user: {}", user.username);
+            return Err(error_status(
+                Code::InvalidArgument,
+                ZkPassErrorCode::VerificationFailed,
+                "Invalid authentication",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Records that a challenge was answered for as long as its resumption token is valid, so
+    /// that the answer cannot be replayed through `VerifyAuthenticationStateless`.
+    ///
+    /// # Arguments
+    /// * `auth_id` - The ID of the challenge.
+    /// * `issued_at` - The Unix time in seconds at which the challenge's token was issued.
+    /// * `now` - The current Unix time in seconds.
+    ///
+    /// # Returns
+    /// `true` if the challenge had not been answered before, `false` otherwise.
+    async fn record_answered(&self, auth_id: &str, issued_at: u64, now: u64) -> bool {
+        let ttl = match &self.resume_tokens {
+            Some(sealer) => sealer.ttl().as_secs(),
+            None => return true,
+        };
+        let mut answered = self.answered_challenges.lock().await;
+        answered.retain(|_, issued_at| issued_at.saturating_add(ttl) >= now);
+        answered.insert(auth_id.to_string(), issued_at).is_none()
+    }
+
+    /// Checks that the commitment stored for the user a challenge was issued to is the one a
+    /// credential update expects to replace, before the answer to the challenge is verified
+    /// against it.
+    ///
+    /// # Arguments
+    /// * `auth_id` - The ID of the challenge answered by the update.
+    /// * `old_y1` - The encoded `y1` the update expects, or empty to accept any.
+    /// * `old_y2` - The encoded `y2` the update expects, or empty to accept any.
+    ///
+    /// # Returns
+    /// `Ok(())` if no commitment is expected, the stored one is expected, or the challenge or
+    /// user is unknown, which verifying the answer reports. A `Status::aborted` error otherwise.
+    async fn check_expected_credentials(
+        &self, auth_id: &str, old_y1: &[u8], old_y2: &[u8],
+    ) -> Result<(), Status> {
+        if old_y1.is_empty() && old_y2.is_empty() {
+            return Ok(());
+        }
+        let Some(challenge) = self.dao.get_authentication_challenge(auth_id).await else {
+            return Ok(());
+        };
+        let Some(user) = self.dao.read(&challenge.user).await else {
+            return Ok(());
+        };
+        if T::convert_to(&user.y1) != old_y1 || T::convert_to(&user.y2) != old_y2 {
+            error!("Credential update for user: {} expected another commitment", user.username);
+            return Err(error_status(
+                Code::Aborted,
+                ZkPassErrorCode::Unspecified,
+                "Stored credentials do not match old_y1 and old_y2",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Derives the effective challenge users answer from the nonce and challenge issued to them,
+    /// shortened to the configured challenge size, if any.
+    ///
+    /// # Arguments
+    /// * `params` - The group parameters the user is registered with.
+    /// * `nonce` - The nonce issued with the challenge.
+    /// * `c` - The issued challenge.
+    ///
+    /// # Returns
+    /// The effective challenge.
+    fn effective_challenge(&self, params: &GroupParams<T>, nonce: &[u8], c: &S) -> S
+    where
+        S: TranscriptChallenge<GroupParams<T>>,
+    {
+        derive_sized_nonce_challenge(params, nonce, c, self.challenge_bits)
+            .expect("only discrete log services shorten challenges, whose type supports it")
+    }
+
+    /// Returns the nonce issued with a challenge.
+    ///
+    /// Public coins from the challenge source are issued without a nonce, so the server has no
+    /// say in the effective challenge. Otherwise, with resumption, the nonce is derived from the
+    /// claims of the challenge's token, so that a resumed authentication is issued the same
+    /// effective challenge, and without it the nonce is random.
+    ///
+    /// # Arguments
+    /// * `resume` - The sealer of resumption tokens and the claims of the challenge's token, if
+    ///   resumption is enabled.
+    ///
+    /// # Returns
+    /// The nonce, or `ConversionError::InvalidLength` if the claims cannot be encoded.
+    fn challenge_nonce(
+        &self, resume: Option<(&ResumeTokenSealer, &ResumeClaims)>,
+    ) -> Result<Vec<u8>, ConversionError> {
+        if self
+            .challenge_source
+            .as_ref()
+            .is_some_and(|source| source.is_public_coin())
+        {
+            return Ok(Vec::new());
+        }
+        match resume {
+            Some((sealer, claims)) => sealer.derive_nonce(claims, CHALLENGE_NONCE_LEN),
+            None => {
+                let mut nonce = vec![0u8; CHALLENGE_NONCE_LEN];
+                OsRng.fill_bytes(&mut nonce);
+                Ok(nonce)
+            }
+        }
+    }
+
+    /// Records a challenge ID as used in the challenge filter, if any.
+    ///
+    /// # Arguments
+    /// * `auth_id` - The ID of the answered challenge.
+    async fn record_used_challenge(&self, auth_id: &str) {
+        if let Some(filter) = &self.challenge_filter {
+            filter.lock().await.insert(auth_id);
+        }
+    }
+
+    /// Checks that a newly stored challenge was not issued under a used ID, discarding it if it
+    /// was.
+    ///
+    /// # Arguments
+    /// * `auth_id` - The ID of the new challenge.
+    ///
+    /// # Returns
+    /// `Ok(())` if no challenge filter is set or the ID is not in it, or a `Status::aborted`
+    /// error otherwise.
+    async fn check_challenge_id_unused(&self, auth_id: &str) -> Result<(), Status> {
+        let Some(filter) = &self.challenge_filter else {
+            return Ok(());
+        };
+        if !filter.lock().await.contains(auth_id) {
+            return Ok(());
+        }
+        warn!("Challenge ID: {} may have been used before", auth_id);
+        self.dao.delete_auth_challenge(auth_id).await;
+        Err(error_status(
+            Code::Aborted,
+            ZkPassErrorCode::Unspecified,
+            "Challenge ID was used before, please retry",
+        ))
+    }
+
+    /// Verifies a user's answer to a pending authentication challenge and starts a session.
+    ///
+    /// # Arguments
+    /// * `req` - The answer to the challenge.
+    /// * `ip` - The IP address of the client answering the challenge, if known.
+    ///
+    /// # Returns
+    /// The session started for the user, or a `Status` error if the answer does not verify, see
+    /// [`verify_answer`](Self::verify_answer).
+    async fn authenticate(
+        &self, req: &AuthenticationAnswerRequest, ip: Option<IpAddr>,
+    ) -> Result<AuthenticationAnswerResponse, Status>
+    where
+        C: ChaumPedersen<
+                Response = S,
+                Challenge = S,
+                GroupParameters = GroupParams<T>,
+                CommitParameters = (T, T, T, T),
+            > + 'static,
+        S: TranscriptChallenge<GroupParams<T>>,
+    {
+        let cancellation_log = CancellationLog::new(&req.auth_id);
+        let user = self
+            .verify_answer(&req.auth_id, &req.s, &req.nonce, ip)
+            .await;
+        cancellation_log.complete();
+        let user = user?;
+
+        let (session_id, session_token) = self.start_session(&user.key())?;
+
+        self.server_commitments
+            .lock()
+            .await
+            .remove(&req.auth_id, unix_time()?);
+        self.record_used_challenge(&req.auth_id).await;
+
+        info!("🔑 User: {} authenticated, session id: {}", user.username, req.auth_id);
+        Ok(AuthenticationAnswerResponse {
+            session_id,
+            session_token,
+        })
+    }
+
+    /// Verifies an answer carrying its challenge and commitments, and starts a session.
+    ///
+    /// The challenge is not looked up: the resumption token issued with it vouches for the
+    /// challenge, the commitment and the nonce, so only the user's registration is read, to check
+    /// that `y1` and `y2` match it. The challenge is then discarded, so it cannot be answered
+    /// again either way.
+    ///
+    /// # Arguments
+    /// * `req` - The answer, along with the commitments, the challenge and its resumption token.
+    ///
+    /// # Returns
+    /// The session started for the user, or a `Status` error if stateless verification is not
+    /// enabled, the token does not vouch for the answer, the challenge was already answered or
+    /// the answer does not verify.
+    async fn authenticate_stateless(
+        &self, req: &StatelessAnswerRequest,
+    ) -> Result<AuthenticationAnswerResponse, Status>
+    where
+        T: ValidateGroupParams,
+        C: ChaumPedersen<
+                Response = S,
+                Challenge = S,
+                GroupParameters = GroupParams<T>,
+                CommitParameters = (T, T, T, T),
+            > + 'static,
+        S: TranscriptChallenge<GroupParams<T>>,
+    {
+        let sealer = match &self.resume_tokens {
+            Some(sealer) if !self.bind_challenge_to_ip => sealer,
+            _ => {
+                return Err(error_status(
+                    Code::FailedPrecondition,
+                    ZkPassErrorCode::Unspecified,
+                    "Stateless verification is not enabled",
+                ))
+            }
+        };
+        let now = unix_time()?;
+        let claims = sealer
+            .open(&req.resume_token, now)
+            .map_err(resume_token_status)?;
+        self.check_not_revoked(&claims.user)?;
+        if !claims.matches_commitment(&req.r1, &req.r2)
+            || !claims.matches_challenge(&req.c)
+            || self.challenge_nonce(Some((sealer, &claims))).ok().as_ref() != Some(&req.nonce)
+        {
+            error!("Resume token does not match answer to challenge: {}", claims.auth_id);
+            return Err(invalid_parameter("Invalid resume token"));
+        }
+
+        let user = self
+            .dao
+            .read(&claims.user)
+            .await
+            .ok_or_else(user_not_found)?;
+        if T::convert_to(&user.y1) != req.y1 || T::convert_to(&user.y2) != req.y2 {
+            error!("Stateless answer for user: {} with another registration", user.username);
+            return Err(error_status(
+                Code::PermissionDenied,
+                ZkPassErrorCode::VerificationFailed,
+                "Commitment does not match the registered user",
+            ));
+        }
+        let params = self.params_for(user.params_version)?;
+        let r1 = decode_group_element(&params, &req.r1, "r1")?;
+        let r2 = decode_group_element(&params, &req.r2, "r2")?;
+        let c = S::convert_from(&req.c).map_err(|_| invalid_parameter("Invalid c"))?;
+        let c = self.effective_challenge(&params, &req.nonce, &c);
+        self.check_response(&user, &params, &req.s, &c, r1, r2)
+            .await?;
+
+        if !self
+            .record_answered(&claims.auth_id, claims.issued_at, now)
+            .await
+        {
+            error!("Replayed stateless answer to challenge: {}", claims.auth_id);
+            return Err(error_status(
+                Code::PermissionDenied,
+                ZkPassErrorCode::VerificationFailed,
+                "Challenge already answered",
+            ));
+        }
+        let (session_id, session_token) = self.start_session(&user.key())?;
+        self.dao.delete_auth_challenge(&claims.auth_id).await;
+        self.server_commitments
+            .lock()
+            .await
+            .remove(&claims.auth_id, unix_time()?);
+        self.record_used_challenge(&claims.auth_id).await;
+
+        info!(
+            "🔑 User: {} authenticated statelessly, session id: {}",
+            user.username, claims.auth_id
+        );
+        Ok(AuthenticationAnswerResponse {
+            session_id,
+            session_token,
+        })
+    }
+}
+
+impl<D: AsyncUserDao<BigUint, BigUint>> ZkAuth<DiscreteLogChaumPedersen, BigUint, BigUint, D> {
+    /// Shortens the effective challenges users answer to the configured number of bits, trading
+    /// soundness for cheaper verification, see
+    /// [`DiscreteLogChaumPedersen::challenge_with_bits`]. Clients learn the size from the
+    /// `challenge_bits` field of every challenge.
+    ///
+    /// # Arguments
+    /// * `config` - The configuration holding the challenge size.
+    ///
+    /// # Returns
+    /// The service, or the reason the challenge size does not suit the current group parameters.
+    pub fn with_challenge_config(
+        mut self, config: DiscreteLogChaumPedersenConfig,
+    ) -> Result<Self, InvalidChallengeBits> {
+        config.validate(&self.params())?;
+        self.challenge_bits = config.challenge_bits;
+        Ok(self)
+    }
+}
+
+/// Implementation of the `Auth` trait for `ZkAuth`.
+///
+/// This implementation provides the necessary methods for user registration,
+/// creating authentication challenges, and verifying authentication answers.
+/// It uses generic parameters `C`, `T`, and `S` to work with different cryptographic protocols and data types.
+///
+/// `C` represents a specific Chaum-Pedersen protocol implementation.
+/// `T` is the type for group parameters and public information.
+/// `S` is the scalar type used for cryptographic operations.
+/// `D` is the user data store.
+#[tonic::async_trait]
+impl<C, T, S, D> Auth for ZkAuth<C, T, S, D>
+where
+    T: Send + Sync + 'static + Clone + ByteConvertible<T> + ValidateGroupParams,
+    S: Send
+        + Sync
+        + 'static
+        + Clone
+        + PartialEq
+        + ByteConvertible<S>
+        + TranscriptChallenge<GroupParams<T>>,
+    D: AsyncUserDao<T, S> + 'static,
+    C: ChaumPedersen<
+            Response = S,
+            CommitmentRandom = S,
+            Challenge = S,
+            Secret = S,
+            GroupParameters = GroupParams<T>,
+            CommitParameters = (T, T, T, T),
+        >
+        + 'static
+        + std::marker::Sync
+        + std::marker::Send,
+{
+    // Negotiate the protocol type before registration.
+    // The server selects the first of the client's supported types that it runs and returns its
+    // group parameters, so a misconfigured client fails with a diagnostic instead of a
+    // cryptographic error.
+    //
+    // # Arguments
+    // * `request` - A `Request<NegotiateRequest>` listing the client's supported protocol types.
+    //
+    // # Returns
+    // A `Result` containing a `Response<NegotiateResponse>` with the selected type and the
+    // JSON-encoded current group parameters along with their version, or a
+    // `Status::failed_precondition` error if negotiation is not enabled or there is no mutually
+    // supported type.
+    async fn negotiate_protocol(
+        &self, request: Request<NegotiateRequest>,
+    ) -> Result<Response<NegotiateResponse>, Status> {
+        let _in_flight = self.admit()?;
+        trace!("negotiate_protocol: {:?}", request);
+        self.check_peer(&request)?;
+        let req = request.into_inner();
+
+        let protocol_type = self.protocol_type.as_ref().ok_or_else(|| {
+            error_status(
+                Code::FailedPrecondition,
+                ZkPassErrorCode::Unspecified,
+                "Protocol negotiation is not enabled",
+            )
+        })?;
+        let selected_type = req
+            .supported_types
+            .into_iter()
+            .find(|supported| supported == protocol_type)
+            .ok_or_else(|| {
+                error_status(
+                    Code::FailedPrecondition,
+                    ZkPassErrorCode::Unspecified,
+                    format!(
+                        "No mutually supported protocol type; the server supports {}",
+                        protocol_type
+                    ),
+                )
+            })?;
+        let (params_version, group_params_encoded) = {
+            let params_versions = self.params.read().unwrap();
+            let (version, params) = params_versions.current();
+            let encoded = serde_json::to_vec(params).map_err(|e| {
+                error_status(Code::Internal, ZkPassErrorCode::Unspecified, e.to_string())
+            })?;
+            (version, encoded)
+        };
+
+        let reply = NegotiateResponse {
+            selected_type,
+            group_params_encoded,
+            params_version,
+        };
+        trace!("negotiate_protocol reply: {:?}", reply);
+        Ok(Response::new(reply))
+    }
+
+    // Fetch the server's current group parameters.
+    // Lets a client run the protocol without configuring the group locally, unlike negotiation,
+    // which only confirms a type the client already supports.
+    //
+    // # Arguments
+    // * `request` - A `Request<GetGroupParamsRequest>`.
+    //
+    // # Returns
+    // A `Result` containing a `Response<GetGroupParamsResponse>` with the protocol type and the
+    // hex-encoded current group parameters along with their version.
+    async fn get_group_params(
+        &self, request: Request<GetGroupParamsRequest>,
+    ) -> Result<Response<GetGroupParamsResponse>, Status> {
+        let _in_flight = self.admit()?;
+        trace!("get_group_params: {:?}", request);
+        self.check_peer(&request)?;
+
+        let reply = {
+            let params_versions = self.params.read().unwrap();
+            let (params_version, params) = params_versions.current();
+            GetGroupParamsResponse {
+                protocol_type: self.protocol_type.clone().unwrap_or_default(),
+                g: T::to_hex(&params.g),
+                h: T::to_hex(&params.h),
+                p: T::to_hex(&params.p),
+                q: T::to_hex(&params.q),
+                params_version,
+            }
+        };
+        trace!("get_group_params reply: {:?}", reply);
+        Ok(Response::new(reply))
+    }
+
+    // Register a user with provided credentials.
+    // This method accepts a `RegisterRequest` and returns a `RegisterResponse`.
+    //
+    // # Arguments
+    // * `request` - A `Request<RegisterRequest>` containing the user's registration information.
+    //
+    // # Returns
+    // A `Result` containing a `Response<RegisterResponse>` on success, or a `Status` error on failure.
+    // The reply carries the user's stable ID and whether the user was created. Registering a
+    // username again with the same `y1`/`y2` succeeds with `created` false, so clients can retry
+    // a registration whose reply was lost. Registering a username that is already taken with
+    // other values fails with `Status::already_exists`, registering while the user store is full
+    // fails with `Status::resource_exhausted`, and registering with a version of the group
+    // parameters that is no longer accepted fails with `Status::failed_precondition`.
+    async fn register(
+        &self, request: Request<RegisterRequest>,
+    ) -> Result<Response<RegisterResponse>, Status> {
+        let _in_flight = self.admit()?;
+        trace!("register: {:?}", request);
+        self.check_peer(&request)?;
+        let req = request.into_inner();
+        let user = self.user_from_request(&req)?;
+        let params = self.params_for(user.params_version)?;
+        let (key, user_id) = (user.key(), user.id());
+        let credentials = (T::convert_to(&user.y1), T::convert_to(&user.y2));
+
+        let created = self.dao.create(user).await.is_some();
+        if created {
+            info!("📝 User: {} registered", req.user);
+        } else {
+            let Some(existing) = self.dao.read(&key).await else {
+                error!("User store is full, refusing: {}", req.user);
+                return Err(user_capacity_reached());
+            };
+            if (T::convert_to(&existing.y1), T::convert_to(&existing.y2)) != credentials {
+                error!("User already exists: {}", req.user);
+                return Err(error_status(
+                    Code::AlreadyExists,
+                    ZkPassErrorCode::Unspecified,
+                    "User already exists",
+                ));
+            }
+            info!("📝 User: {} already registered", req.user);
+        }
+
+        // In mutual mode, the server's public values are returned so clients can pin them.
+        let (sy1, sy2) = match &self.server_secret {
+            Some(x) => {
+                let ((y1, y2, _, _), _) = C::commitment(&params, x);
+                (T::convert_to(&y1), T::convert_to(&y2))
+            }
+            None => (Vec::new(), Vec::new()),
+        };
+
+        let reply = RegisterResponse {
+            sy1,
+            sy2,
+            user_id: user_id.to_string(),
+            created,
+        };
+        trace!("register reply: {:?}", reply);
+        Ok(Response::new(reply))
+    }
+
+    // Prove the server's identity to a client.
+    // The server proves knowledge of its secret non-interactively, with the challenge bound to
+    // the client's nonce so the proof cannot be replayed to another client. Clients use this to
+    // check the server before sending their own commitment.
+    //
+    // # Arguments
+    // * `request` - A `Request<ServerIdentityRequest>` containing the client's nonce.
+    //
+    // # Returns
+    // A `Result` containing a `Response<ServerIdentityResponse>` with the server's public values
+    // and proof, or a `Status` error if mutual authentication is not enabled.
+    async fn prove_server_identity(
+        &self, request: Request<ServerIdentityRequest>,
+    ) -> Result<Response<ServerIdentityResponse>, Status> {
+        let _in_flight = self.admit()?;
+        trace!("prove_server_identity: {:?}", request);
+        self.check_peer(&request)?;
+        let req = request.into_inner();
+
+        let x = self.server_secret.as_ref().ok_or_else(|| {
+            error_status(
+                Code::FailedPrecondition,
+                ZkPassErrorCode::Unspecified,
+                "Mutual authentication is not enabled",
+            )
+        })?;
+        let params = self.params();
+        let (cp, k) = C::commitment(&params, x);
+        let c: S = derive_bound_challenge(SERVER_IDENTITY_DOMAIN, &params, &cp, &req.nonce);
+        let s = C::challenge_response(&params, &k, &c, x);
+
+        let (y1, y2, r1, r2) = cp;
+        let reply = ServerIdentityResponse {
+            sy1: T::convert_to(&y1),
+            sy2: T::convert_to(&y2),
+            sr1: T::convert_to(&r1),
+            sr2: T::convert_to(&r2),
+            ss: S::convert_to(&s),
+        };
+        trace!("prove_server_identity reply: {:?}", reply);
+        Ok(Response::new(reply))
+    }
+
+    // Register a batch of users in a single call.
+    // Users that fail validation are reported individually without aborting the rest
+    // of the batch.
+    //
+    // # Arguments
+    // * `request` - A `Request<RegisterBatchRequest>` containing the registration requests.
+    //
+    // # Returns
+    // A `Result` containing a `Response<RegisterBatchResponse>` with one `RegisterResult` per
+    // requested user, in request order.
+    async fn register_batch(
+        &self, request: Request<RegisterBatchRequest>,
+    ) -> Result<Response<RegisterBatchResponse>, Status> {
+        let _in_flight = self.admit()?;
+        trace!("register_batch: {} users", request.get_ref().users.len());
+        self.check_peer(&request)?;
+        let req = request.into_inner();
+
+        let mut results = Vec::with_capacity(req.users.len());
+        for user_req in req.users {
+            let created = match self.user_from_request(&user_req) {
+                Ok(user) => {
+                    let key = user.key();
+                    match self.dao.create(user).await {
+                        Some(()) => Ok(()),
+                        None if self.dao.read(&key).await.is_none() => Err(user_capacity_reached()),
+                        None => Err(error_status(
+                            Code::AlreadyExists,
+                            ZkPassErrorCode::Unspecified,
+                            "User already exists",
+                        )),
+                    }
+                }
+                Err(status) => Err(status),
+            };
+            let result = match created {
+                Ok(()) => RegisterResult {
+                    user: user_req.user,
+                    success: true,
+                    error: String::new(),
+                },
+                Err(status) => {
+                    error!("Failed to register user: {}: {}", user_req.user, status.message());
+                    RegisterResult {
+                        user: user_req.user,
+                        success: false,
+                        error: status.message().to_string(),
+                    }
+                }
+            };
+            results.push(result);
+        }
+
+        let reply = RegisterBatchResponse { results };
+        trace!("register_batch reply: {:?}", reply);
+        Ok(Response::new(reply))
+    }
+
+    // Retrieve the auxiliary metadata stored for a registered user.
+    // This method accepts a `GetUserMetadataRequest` and returns a `MetadataResponse`.
+    //
+    // # Arguments
+    // * `request` - A `Request<GetUserMetadataRequest>` containing the username.
+    //
+    // # Returns
+    // A `Result` containing a `Response<MetadataResponse>` on success, a `Status::not_found`
+    // error if the user is not registered, or a `Status::internal` error if the metadata cannot
+    // be loaded.
+    async fn get_user_metadata(
+        &self, request: Request<GetUserMetadataRequest>,
+    ) -> Result<Response<MetadataResponse>, Status> {
+        let _in_flight = self.admit()?;
+        trace!("get_user_metadata: {:?}", request);
+        self.check_peer(&request)?;
+        let req = request.into_inner();
+        let key = self.resolve_user_key(&req.namespace, &req.user)?;
+
+        let metadata = self
+            .dao
+            .get_user_metadata(&key)
+            .await
+            .map_err(|e| match e {
+                ZkPassError::UserNotFound => user_not_found(),
+                e => {
+                    error!("Failed to load metadata of user {}: {}", req.user, e);
+                    error_status(
+                        Code::Internal,
+                        ZkPassErrorCode::Unspecified,
+                        "Failed to load metadata",
+                    )
+                }
+            })?;
+
+        let reply = MetadataResponse {
+            metadata: metadata
+                .into_iter()
+                .map(|(key, value)| Metadata { key, value })
+                .collect(),
+        };
+        trace!("get_user_metadata reply: {:?}", reply);
+        Ok(Response::new(reply))
+    }
+
+    // Create an authentication challenge for a user.
+    // This method accepts an `AuthenticationChallengeRequest` and returns an `AuthenticationChallengeResponse`.
+    // Every challenge carries a fresh nonce; the client answers the challenge derived from the
+    // nonce and `c`, which binds its response to this authentication attempt.
+    //
+    // # Arguments
+    // * `request` - A `Request<AuthenticationChallengeRequest>` containing the user's information.
+    //
+    // # Returns
+    // A `Result` containing a `Response<AuthenticationChallengeResponse>` on success, or a `Status` error on failure.
+    async fn create_authentication_challenge(
+        &self, request: Request<AuthenticationChallengeRequest>,
+    ) -> Result<Response<AuthenticationChallengeResponse>, Status> {
+        let _in_flight = self.admit()?;
+        trace!("create_authentication_challenge request: {:?}", request);
+        let addr = self.check_peer(&request)?;
+        let ip = if self.bind_challenge_to_ip {
+            let addr = addr.ok_or_else(|| {
+                error_status(
+                    Code::PermissionDenied,
+                    ZkPassErrorCode::Unspecified,
+                    "Client address unavailable",
+                )
+            })?;
+            Some(addr.ip())
+        } else {
+            None
+        };
+        let req = request.into_inner();
+        let key = self.resolve_user_key(&req.namespace, &req.user)?;
+        self.check_not_revoked(&key)?;
+
+        let user = self.dao.read(&key).await.ok_or_else(user_not_found)?;
+        let params = self.params_for(user.params_version)?;
+        let r1 = decode_group_element(&params, &req.r1, "r1")?;
+        let r2 = decode_group_element(&params, &req.r2, "r2")?;
+        self.check_commitment_not_reused(&key, &req.r1).await?;
+        let challenge = match &self.challenge_source {
+            Some(source) => source.challenge(&params).await,
+            None => RandomChallengeSource::<C>::new().challenge(&params).await,
+        }
+        .map_err(|e| {
+            let code = match e {
+                ZkPassError::ChallengeSourceUnavailable => {
+                    ZkPassErrorCode::ChallengeSourceUnavailable
+                }
+                _ => ZkPassErrorCode::BeaconUnavailable,
+            };
+            error_status(Code::Unavailable, code, e.to_string())
+        })?;
+        let c = S::convert_to(&challenge);
+        let resume = match &self.resume_tokens {
+            Some(sealer) => {
+                Some((sealer, ResumeClaims::new(&key, "", &req.r1, &req.r2, &c, unix_time()?)))
+            }
+            None => None,
+        };
+        let nonce = self
+            .challenge_nonce(resume.as_ref().map(|(sealer, claims)| (*sealer, claims)))
+            .map_err(|_| invalid_parameter("User name is too long"))?;
+
+        let auth_id = self
+            .dao
+            .create_auth_challenge(&key, &r1, &r2, &challenge, &nonce, ip)
+            .await;
+        self.check_challenge_id_unused(&auth_id).await?;
+
+        // In mutual mode, the server commits before it can see the client's challenge.
+        let (server_r1, server_r2) = match &self.server_secret {
+            Some(x) => {
+                let ((_, _, r1, r2), k) = C::commitment(&params, x);
+                let (r1, r2) = (T::convert_to(&r1), T::convert_to(&r2));
+                self.server_commitments.lock().await.insert(
+                    auth_id.clone(),
+                    (k, r1.clone(), r2.clone()),
+                    unix_time()?,
+                );
+                (r1, r2)
+            }
+            None => (Vec::new(), Vec::new()),
+        };
+
+        let resume_token = match resume {
+            Some((sealer, claims)) => sealer
+                .seal(&ResumeClaims {
+                    auth_id: auth_id.clone(),
+                    ..claims
+                })
+                .map_err(|_| invalid_parameter("User name is too long"))?,
+            None => Vec::new(),
+        };
+        let reply = AuthenticationChallengeResponse {
+            auth_id,
+            c,
+            server_r1,
+            server_r2,
+            nonce,
+            resume_token,
+            challenge_bits: self.challenge_bits,
+        };
+        trace!("create_authentication_challenge reply: {:?}", reply);
+        Ok(Response::new(reply))
+    }
+
+    // Issue the challenge of an interrupted authentication again.
+    // This method accepts a `ResumeRequest` carrying the resumption token handed out with the
+    // challenge, and returns the same `AuthenticationChallengeResponse` as the original request,
+    // as long as the token is valid and the challenge has not been answered.
+    //
+    // # Arguments
+    // * `request` - A `Request<ResumeRequest>` containing the resumption token.
+    //
+    // # Returns
+    // A `Result` containing a `Response<AuthenticationChallengeResponse>` on success, or a `Status` error on failure.
+    async fn resume_authentication(
+        &self, request: Request<ResumeRequest>,
+    ) -> Result<Response<AuthenticationChallengeResponse>, Status> {
+        let _in_flight = self.admit()?;
+        trace!("resume_authentication request: {:?}", request);
+        self.check_peer(&request)?;
+        let req = request.into_inner();
+
+        let sealer = self.resume_tokens.as_ref().ok_or_else(|| {
+            error_status(
+                Code::FailedPrecondition,
+                ZkPassErrorCode::Unspecified,
+                "Resumption is not enabled",
+            )
+        })?;
+        let claims = sealer
+            .open(&req.resume_token, unix_time()?)
+            .map_err(resume_token_status)?;
+        self.check_not_revoked(&claims.user)?;
+
+        let challenge = self
+            .dao
+            .get_authentication_challenge(&claims.auth_id)
+            .await
+            .ok_or_else(|| {
+                error_status(
+                    Code::NotFound,
+                    ZkPassErrorCode::ChallengeNotFound,
+                    "Challenge not found",
+                )
+            })?;
+        let nonce = self.challenge_nonce(Some((sealer, &claims))).ok();
+        if challenge.user != claims.user
+            || nonce.as_ref() != Some(&challenge.nonce)
+            || !claims.matches_challenge(&S::convert_to(&challenge.c))
+            || !claims
+                .matches_commitment(&T::convert_to(&challenge.r1), &T::convert_to(&challenge.r2))
+        {
+            error!("Resume token does not match challenge: {}", claims.auth_id);
+            return Err(invalid_parameter("Invalid resume token"));
+        }
+
+        let (server_r1, server_r2) = self
+            .server_commitments
+            .lock()
+            .await
+            .get(&claims.auth_id, unix_time()?)
+            .map(|(_, r1, r2)| (r1.clone(), r2.clone()))
+            .unwrap_or_default();
+
+        info!("⏯️ Authentication for challenge: {} resumed", claims.auth_id);
+        let reply = AuthenticationChallengeResponse {
+            auth_id: claims.auth_id,
+            c: S::convert_to(&challenge.c),
+            server_r1,
+            server_r2,
+            nonce: challenge.nonce,
+            resume_token: req.resume_token,
+            challenge_bits: self.challenge_bits,
+        };
+        trace!("resume_authentication reply: {:?}", reply);
+        Ok(Response::new(reply))
+    }
+
+    // Verify an authentication challenge answer from a user.
+    // This method accepts an `AuthenticationAnswerRequest` and returns an `AuthenticationAnswerResponse`.
+    // The answer must carry the nonce issued with the challenge and respond to the challenge
+    // derived from it.
+    //
+    // # Arguments
+    // * `request` - A `Request<AuthenticationAnswerRequest>` containing the user's authentication answer.
+    //
+    // # Returns
+    // A `Result` containing a `Response<AuthenticationAnswerResponse>` on success, or a `Status` error on failure.
+    async fn verify_authentication(
+        &self, request: Request<AuthenticationAnswerRequest>,
+    ) -> Result<Response<AuthenticationAnswerResponse>, Status> {
+        let _in_flight = self.admit()?;
+        trace!("verify_authentication: {:?}", request);
+        let ip = self.check_peer(&request)?.map(|addr| addr.ip());
+        let req = request.into_inner();
+
+        let reply = self.authenticate(&req, ip).await?;
+        trace!("verify_authentication reply: {:?}", reply);
+        Ok(Response::new(reply))
+    }
+
+    // Verify an authentication challenge answer without looking up the challenge.
+    // This method accepts a `StatelessAnswerRequest`, which resubmits the user's full commitment
+    // and the challenge along with the response, vouched for by the challenge's resumption token.
+    //
+    // # Arguments
+    // * `request` - A `Request<StatelessAnswerRequest>` containing the user's authentication answer.
+    //
+    // # Returns
+    // A `Result` containing a `Response<AuthenticationAnswerResponse>` on success, or a `Status` error on failure.
+    async fn verify_authentication_stateless(
+        &self, request: Request<StatelessAnswerRequest>,
+    ) -> Result<Response<AuthenticationAnswerResponse>, Status> {
+        let _in_flight = self.admit()?;
+        trace!("verify_authentication_stateless: {:?}", request);
+        self.check_peer(&request)?;
+        let req = request.into_inner();
+
+        let reply = self.authenticate_stateless(&req).await?;
+        trace!("verify_authentication_stateless reply: {:?}", reply);
+        Ok(Response::new(reply))
+    }
+
+    // Verify many authentication answers in one call, e.g. for fleets of devices.
+    // The answers are verified concurrently, each as in `verify_authentication`, and a failing
+    // answer does not affect the others. Repeated answers to a challenge are rejected without
+    // being verified, so only its first answer can start a session.
+    //
+    // # Arguments
+    // * `request` - A `Request<BatchVerifyRequest>` containing the answers.
+    //
+    // # Returns
+    // A `Result` containing a `Response<BatchVerifyResponse>` with one result per answer, in the
+    // order of the request.
+    async fn batch_verify(
+        &self, request: Request<BatchVerifyRequest>,
+    ) -> Result<Response<BatchVerifyResponse>, Status> {
+        let _in_flight = self.admit()?;
+        trace!("batch_verify: {} answers", request.get_ref().answers.len());
+        let ip = self.check_peer(&request)?.map(|addr| addr.ip());
+        let req = request.into_inner();
+
+        let mut tasks = JoinSet::new();
+        let mut results = BTreeMap::new();
+        let mut auth_ids = HashSet::new();
+        for (index, answer) in req.answers.into_iter().enumerate() {
+            if !auth_ids.insert(answer.auth_id.clone()) {
+                error!("Challenge: {} answered twice in a batch", answer.auth_id);
+                let result = BatchVerifyResult {
+                    auth_id: answer.auth_id,
+                    session_id: String::new(),
+                    error: "Duplicate auth_id".to_string(),
+                };
+                results.insert(index, result);
+                continue;
+            }
+            let auth = self.clone();
+            tasks.spawn(async move {
+                let result = match auth.authenticate(&answer, ip).await {
+                    Ok(reply) => BatchVerifyResult {
+                        auth_id: answer.auth_id,
+                        session_id: reply.session_id,
+                        error: String::new(),
+                    },
+                    Err(status) => BatchVerifyResult {
+                        auth_id: answer.auth_id,
+                        session_id: String::new(),
+                        error: status.message().to_string(),
+                    },
+                };
+                (index, result)
+            });
+        }
+
+        while let Some(joined) = tasks.join_next().await {
+            let (index, result) = joined.map_err(|e| {
+                error_status(Code::Internal, ZkPassErrorCode::Unspecified, e.to_string())
+            })?;
+            results.insert(index, result);
+        }
+
+        let reply = BatchVerifyResponse {
+            results: results.into_values().collect(),
+        };
+        trace!("batch_verify reply: {:?}", reply);
+        Ok(Response::new(reply))
+    }
+
+    // Verify a non-interactive proof built offline by the client, in a single request.
+    // The proof is a `ProofBundle` for the requested user, checked against the user's registered
+    // commitment as in `verify_proof_bundle`, and starts a session when it verifies.
+    //
+    // # Arguments
+    // * `request` - A `Request<ProofRequest>` containing the user and the encoded proof bundle.
+    //
+    // # Returns
+    // A `Result` containing a `Response<VerifyResponse>` telling whether the proof verifies, with
+    // a session ID if it does, or a `Status` error if the proof cannot be decoded, is for another
+    // user, or is rejected by `verify_proof_bundle`.
+    async fn verify_proof(
+        &self, request: Request<ProofRequest>,
+    ) -> Result<Response<VerifyResponse>, Status> {
+        let _in_flight = self.admit()?;
+        trace!("verify_proof: {:?}", request);
+        self.check_peer(&request)?;
+        let req = request.into_inner();
+
+        let bundle = ProofBundle::<C>::from_compact_bytes(&req.proof_bytes)
+            .map_err(|_| invalid_parameter("Invalid proof"))?;
+        if bundle.user != req.user {
+            return Err(invalid_parameter("Proof is for another user"));
+        }
+
+        let reply = if self.verify_proof_bundle(&bundle).await? {
+            let key = self.resolve_user_key("", &req.user)?;
+            let (session_id, _) = self.start_session(&key)?;
+            VerifyResponse {
+                verified: true,
+                session_id,
+            }
+        } else {
+            VerifyResponse {
+                verified: false,
+                session_id: String::new(),
+            }
+        };
+        trace!("verify_proof reply: {:?}", reply);
+        Ok(Response::new(reply))
+    }
+
+    // Mutually authenticate a user and the server.
+    // The user's answer is verified as in `verify_authentication`; the server then answers the
+    // user's challenge against the commitment it sent with the authentication challenge, so the
+    // user can check that the server knows its secret.
+    //
+    // # Arguments
+    // * `request` - A `Request<MutualAuthenticationRequest>` containing the user's answer and challenge.
+    //
+    // # Returns
+    // A `Result` containing a `Response<MutualAuthenticationResponse>` on success, or a `Status` error on failure.
+    async fn mutual_authenticate(
+        &self, request: Request<MutualAuthenticationRequest>,
+    ) -> Result<Response<MutualAuthenticationResponse>, Status> {
+        let _in_flight = self.admit()?;
+        trace!("mutual_authenticate: {:?}", request);
+        let ip = self.check_peer(&request)?.map(|addr| addr.ip());
+        let req = request.into_inner();
+
+        let x = self.server_secret.as_ref().ok_or_else(|| {
+            error_status(
+                Code::FailedPrecondition,
+                ZkPassErrorCode::Unspecified,
+                "Mutual authentication is not enabled",
+            )
+        })?;
+        let server_c =
+            S::convert_from(&req.server_c).map_err(|_| invalid_parameter("Invalid server_c"))?;
+        let user = self
+            .verify_answer(&req.auth_id, &req.s, &req.nonce, ip)
+            .await?;
+        let k = self
+            .server_commitments
+            .lock()
+            .await
+            .remove(&req.auth_id, unix_time()?)
+            .map(|(k, _, _)| k)
+            .ok_or_else(|| {
+                error_status(
+                    Code::NotFound,
+                    ZkPassErrorCode::ChallengeNotFound,
+                    "Server commitment not found",
+                )
+            })?;
+        let params = self.params_for(user.params_version)?;
+        let server_s = C::challenge_response(&params, &k, &server_c, x);
+
+        let (session_id, session_token) = self.start_session(&user.key())?;
+        let reply = MutualAuthenticationResponse {
+            session_id,
+            server_s: S::convert_to(&server_s),
+            session_token,
+        };
+
+        self.record_used_challenge(&req.auth_id).await;
+
+        info!("🤝 User: {} mutually authenticated, session id: {}", user.username, req.auth_id);
+        trace!("mutual_authenticate reply: {:?}", reply);
+        Ok(Response::new(reply))
+    }
+
+    // Rotate a user's public commitment.
+    // The caller must answer a pending authentication challenge against the current
+    // commitment before the new `y1`/`y2` values replace it, so only the secret holder can rotate.
+    // The new values may be computed with a newer version of the group parameters, moving the
+    // user to it. Updates are serialized, so the commitment the answer is checked against, and
+    // compared to `old_y1`/`old_y2` if given, is the one that gets replaced.
+    //
+    // # Arguments
+    // * `request` - A `Request<UpdateCredentialsRequest>` containing the challenge answer and the new commitment.
+    //
+    // # Returns
+    // A `Result` containing a `Response<UpdateCredentialsResponse>` on success, or a `Status` error on failure.
+    async fn update_credentials(
+        &self, request: Request<UpdateCredentialsRequest>,
+    ) -> Result<Response<UpdateCredentialsResponse>, Status> {
+        let _in_flight = self.admit()?;
+        trace!("update_credentials: {:?}", request);
+        let ip = self.check_peer(&request)?.map(|addr| addr.ip());
+        let req = request.into_inner();
+
+        self.check_registration_params_version(req.params_version)?;
+        let params = self.params_for(req.params_version)?;
+        let y1 = decode_group_element(&params, &req.y1, "y1")?;
+        let y2 = decode_group_element(&params, &req.y2, "y2")?;
+        let _update = self.credential_updates.lock().await;
+        self.check_expected_credentials(&req.auth_id, &req.old_y1, &req.old_y2)
+            .await?;
+        let user = self
+            .verify_answer(&req.auth_id, &req.s, &req.nonce, ip)
+            .await?;
+
+        self.dao
+            .update_credentials(&user.key(), y1, y2, req.params_version)
+            .await
+            .ok_or_else(user_not_found)?;
+        self.server_commitments
+            .lock()
+            .await
+            .remove(&req.auth_id, unix_time()?);
+        self.record_used_challenge(&req.auth_id).await;
+        if user.params_version != req.params_version {
+            self.prune_params_versions().await;
+        }
+
+        info!("🔄 User: {} rotated credentials", user.username);
+        let reply = UpdateCredentialsResponse {};
+        trace!("update_credentials reply: {:?}", reply);
+        Ok(Response::new(reply))
+    }
+
+    // Validate a session issued by this service, e.g. on behalf of another service the user
+    // presents it to.
+    // UUID sessions are looked up in the session store. JWT and PASETO sessions are validated
+    // from the token alone, by checking its signature or decrypting it and checking its expiry.
+    //
+    // # Arguments
+    // * `request` - A `Request<ValidateSessionRequest>` containing the session ID.
+    //
+    // # Returns
+    // A `Result` containing a `Response<ValidateSessionResponse>` naming the session's user, or a
+    // `Status` error if the session is invalid.
+    async fn validate_session(
+        &self, request: Request<ValidateSessionRequest>,
+    ) -> Result<Response<ValidateSessionResponse>, Status> {
+        let _in_flight = self.admit()?;
+        self.check_peer(&request)?;
+        let req = request.into_inner();
+
+        let reply = match &self.session_token_format {
+            SessionTokenFormat::Uuid => ValidateSessionResponse {
+                user: self.sessions.get_user(&req.token).ok_or_else(|| {
+                    error_status(
+                        Code::Unauthenticated,
+                        ZkPassErrorCode::InvalidSession,
+                        "Session not found",
+                    )
+                })?,
+                expiry: 0,
+            },
+            format => {
+                let claims = format.validate(&req.token).map_err(|e| {
+                    error_status(
+                        Code::Unauthenticated,
+                        ZkPassErrorCode::InvalidSession,
+                        e.to_string(),
+                    )
+                })?;
+                ValidateSessionResponse {
+                    user: claims.sub,
+                    expiry: claims.exp,
+                }
+            }
+        };
+        trace!("validate_session reply: {:?}", reply);
+        Ok(Response::new(reply))
+    }
+
+    // Report whether the service is ready, e.g. to a load balancer's readiness probe, along with
+    // the protocol it runs.
+    // The service is only constructed around an initialized user data store, so it is serving
+    // whenever it answers. The standard gRPC health service additionally reports the server as
+    // not serving while it drains on shutdown.
+    //
+    // # Arguments
+    // * `request` - A `Request<HealthRequest>`.
+    //
+    // # Returns
+    // A `Result` containing a `Response<HealthResponse>` with the serving status, the protocol
+    // type and the version of the current group parameters.
+    async fn health(
+        &self, request: Request<HealthRequest>,
+    ) -> Result<Response<HealthResponse>, Status> {
+        trace!("health: {:?}", request);
+        let reply = HealthResponse {
+            status: ServingStatus::Serving.into(),
+            protocol_type: self.protocol_type.clone().unwrap_or_default(),
+            params_version: self.params_version(),
+        };
+        trace!("health reply: {:?}", reply);
+        Ok(Response::new(reply))
+    }
+}
+
+/// Implementation of the `Admin` service for `ZkAuth`, sharing the user data store of the
+/// authentication service. Serve it behind [`admin_token_interceptor`].
+#[tonic::async_trait]
+impl<C, T, S, D> Admin for ZkAuth<C, T, S, D>
+where
+    T: Send + Sync + 'static + Clone + ByteConvertible<T> + ValidateGroupParams,
+    S: Send + Sync + 'static + Clone + ByteConvertible<S>,
+    D: AsyncUserDao<T, S> + 'static,
+    C: 'static + std::marker::Sync + std::marker::Send,
+{
+    // List the registered usernames for auditing, one page at a time.
+    // Usernames are listed in lexicographic order, with the last username of a page serving as
+    // the cursor for the next. Users in a namespace are listed as `namespace::username`.
+    //
+    // # Arguments
+    // * `request` - A `Request<ListUsersRequest>` containing the page token and page size.
+    //
+    // # Returns
+    // A `Result` containing a `Response<ListUsersResponse>` with the page of usernames and the
+    // token for the next page, which is empty on the last page.
+    async fn list_users(
+        &self, request: Request<ListUsersRequest>,
+    ) -> Result<Response<ListUsersResponse>, Status> {
+        trace!("list_users: {:?}", request);
+        self.check_peer(&request)?;
+        let req = request.into_inner();
+
+        let page_size = match req.page_size {
+            0 => DEFAULT_LIST_USERS_PAGE_SIZE,
+            size => size.min(MAX_LIST_USERS_PAGE_SIZE),
+        } as usize;
+        let after = (!req.page_token.is_empty()).then_some(req.page_token.as_str());
+        let usernames = self.dao.list(after, page_size).await;
+        let next_page_token = match usernames.last() {
+            Some(last) if usernames.len() == page_size => last.clone(),
+            _ => String::new(),
+        };
+
+        let reply = ListUsersResponse {
+            usernames,
+            next_page_token,
+        };
+        trace!("list_users reply: {:?}", reply);
+        Ok(Response::new(reply))
+    }
+
+    // Rotate the server's group parameters, e.g. to a stronger group.
+    // Registered users keep authenticating with the parameters they registered with, while
+    // registrations with older parameters are refused after `effective_after`. See
+    // `ZkAuth::rotate_group_params`.
+    //
+    // # Arguments
+    // * `request` - A `Request<RotateParamsRequest>` containing the JSON-encoded new group
+    //   parameters and the time after which older parameters stop accepting registrations.
+    //
+    // # Returns
+    // A `Result` containing a `Response<RotateParamsResponse>` with the version assigned to the
+    // new parameters, or a `Status::invalid_argument` error if they cannot be decoded or are
+    // unsafe to use.
+    async fn rotate_params(
+        &self, request: Request<RotateParamsRequest>,
+    ) -> Result<Response<RotateParamsResponse>, Status> {
+        trace!("rotate_params: {:?}", request);
+        self.check_peer(&request)?;
+        let req = request.into_inner();
+
+        let params: GroupParams<T> = serde_json::from_slice(&req.new_params_encoded)
+            .map_err(|e| invalid_parameter(format!("Invalid group parameters: {}", e)))?;
+        params
+            .validate()
+            .and_then(|_| params.check_primality(PRIMALITY_TEST_ROUNDS))
+            .map_err(|e| invalid_parameter(format!("Invalid group parameters: {}", e)))?;
+        let params_version = self.rotate_group_params(params, req.effective_after).await;
+        info!("🔁 Group parameters rotated to version: {}", params_version);
+
+        let reply = RotateParamsResponse { params_version };
+        trace!("rotate_params reply: {:?}", reply);
+        Ok(Response::new(reply))
+    }
+
+    // Revoke a user's credentials, e.g. because their secret was compromised.
+    // The user can no longer obtain authentication challenges, but stays registered so the
+    // revocation can be lifted with `unrevoke_user`.
+    //
+    // # Arguments
+    // * `request` - A `Request<RevokeUserRequest>` naming the user to revoke.
+    //
+    // # Returns
+    // A `Result` containing an empty `Response<RevokeUserResponse>` on success, or a `Status`
+    // error if the namespace is not served or the user is not registered.
+    async fn revoke_user(
+        &self, request: Request<RevokeUserRequest>,
+    ) -> Result<Response<RevokeUserResponse>, Status> {
+        trace!("revoke_user: {:?}", request);
+        self.check_peer(&request)?;
+        let req = request.into_inner();
+
+        let key = self.resolve_user_key(&req.namespace, &req.user)?;
+        if self.dao.read(&key).await.is_none() {
+            return Err(user_not_found());
+        }
+        self.revocations.revoke(&key);
+        info!("⛔ User: {} revoked", key);
+
+        Ok(Response::new(RevokeUserResponse {}))
+    }
+
+    // Lift the revocation of a user's credentials.
+    // The user can authenticate again with their registered secret, without registering anew.
+    //
+    // # Arguments
+    // * `request` - A `Request<UnrevokeUserRequest>` naming the user to unrevoke.
+    //
+    // # Returns
+    // A `Result` containing an empty `Response<UnrevokeUserResponse>` on success, or a `Status`
+    // error if the namespace is not served.
+    async fn unrevoke_user(
+        &self, request: Request<UnrevokeUserRequest>,
+    ) -> Result<Response<UnrevokeUserResponse>, Status> {
+        trace!("unrevoke_user: {:?}", request);
+        self.check_peer(&request)?;
+        let req = request.into_inner();
+
+        let key = self.resolve_user_key(&req.namespace, &req.user)?;
+        self.revocations.unrevoke(&key);
+        info!("✅ User: {} unrevoked", key);
+
+        Ok(Response::new(UnrevokeUserResponse {}))
+    }
+}
+
+/// Serves several group configurations on one server, e.g. discrete log and Curve25519
+/// clients side by side.
+///
+/// Each request is routed to the service of the group named in its `group` field, and requests
+/// with an empty `group` go to the first group added. Groups should be named after the protocol
+/// type their service negotiates, e.g. `ec25519` or `rfc5114_modp_2048_256`, so that clients
+/// select them with [`AuthClientLib::negotiate`](crate::client::AuthClientLib::negotiate).
+#[derive(Clone, Default)]
+pub struct MultiGroupAuth {
+    groups: HashMap<String, Arc<dyn Auth>>,
+    default_group: Option<String>,
+}
+
+impl MultiGroupAuth {
+    /// Constructs a router without any groups.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Serves a group configuration, replacing any group of the same name.
+    ///
+    /// # Arguments
+    /// * `group` - The identifier clients select the group with.
+    /// * `auth` - The service of the group, e.g. a `ZkAuth` with the group's parameters.
+    pub fn with_group(mut self, group: impl Into<String>, auth: impl Auth) -> Self {
+        let group = group.into();
+        self.default_group.get_or_insert_with(|| group.clone());
+        self.groups.insert(group, Arc::new(auth));
+        self
+    }
+
+    /// Looks up the service of a group.
+    ///
+    /// # Arguments
+    /// * `group` - The identifier of the group, or an empty string for the default group.
+    ///
+    /// # Returns
+    /// The group's service, or a `Status::invalid_argument` error if no such group is served.
+    fn route(&self, group: &str) -> Result<&Arc<dyn Auth>, Status> {
+        let group = match (group, &self.default_group) {
+            ("", Some(default_group)) => default_group.as_str(),
+            _ => group,
+        };
+        self.groups
+            .get(group)
+            .ok_or_else(|| invalid_parameter(format!("Unknown group: {}", group)))
+    }
+}
+
+#[tonic::async_trait]
+impl Auth for MultiGroupAuth {
+    // Negotiate the protocol type, selecting the first of the client's supported types that names
+    // a group, and let that group's service answer.
+    async fn negotiate_protocol(
+        &self, request: Request<NegotiateRequest>,
+    ) -> Result<Response<NegotiateResponse>, Status> {
+        let selected = request
+            .get_ref()
+            .supported_types
+            .iter()
+            .find_map(|supported| self.groups.get(supported))
+            .ok_or_else(|| {
+                let groups: Vec<&str> = self.groups.keys().map(String::as_str).collect();
+                error_status(
+                    Code::FailedPrecondition,
+                    ZkPassErrorCode::Unspecified,
+                    format!(
+                        "No mutually supported protocol type; the server supports {}",
+                        groups.join(", ")
+                    ),
+                )
+            })?;
+        selected.negotiate_protocol(request).await
+    }
+
+    async fn get_group_params(
+        &self, request: Request<GetGroupParamsRequest>,
+    ) -> Result<Response<GetGroupParamsResponse>, Status> {
+        self.route(&request.get_ref().group)?
+            .get_group_params(request)
+            .await
+    }
+
+    async fn register(
+        &self, request: Request<RegisterRequest>,
+    ) -> Result<Response<RegisterResponse>, Status> {
+        self.route(&request.get_ref().group)?
+            .register(request)
+            .await
+    }
+
+    async fn prove_server_identity(
+        &self, request: Request<ServerIdentityRequest>,
+    ) -> Result<Response<ServerIdentityResponse>, Status> {
+        self.route(&request.get_ref().group)?
+            .prove_server_identity(request)
+            .await
+    }
+
+    async fn register_batch(
+        &self, request: Request<RegisterBatchRequest>,
+    ) -> Result<Response<RegisterBatchResponse>, Status> {
+        self.route(&request.get_ref().group)?
+            .register_batch(request)
+            .await
+    }
+
+    async fn get_user_metadata(
+        &self, request: Request<GetUserMetadataRequest>,
+    ) -> Result<Response<MetadataResponse>, Status> {
+        self.route(&request.get_ref().group)?
+            .get_user_metadata(request)
+            .await
+    }
+
+    async fn create_authentication_challenge(
+        &self, request: Request<AuthenticationChallengeRequest>,
+    ) -> Result<Response<AuthenticationChallengeResponse>, Status> {
+        self.route(&request.get_ref().group)?
+            .create_authentication_challenge(request)
+            .await
+    }
+
+    async fn resume_authentication(
+        &self, request: Request<ResumeRequest>,
+    ) -> Result<Response<AuthenticationChallengeResponse>, Status> {
+        self.route(&request.get_ref().group)?
+            .resume_authentication(request)
+            .await
+    }
+
+    async fn verify_authentication(
+        &self, request: Request<AuthenticationAnswerRequest>,
+    ) -> Result<Response<AuthenticationAnswerResponse>, Status> {
+        self.route(&request.get_ref().group)?
+            .verify_authentication(request)
+            .await
+    }
+
+    async fn verify_authentication_stateless(
+        &self, request: Request<StatelessAnswerRequest>,
+    ) -> Result<Response<AuthenticationAnswerResponse>, Status> {
+        self.route(&request.get_ref().group)?
+            .verify_authentication_stateless(request)
+            .await
+    }
+
+    async fn batch_verify(
+        &self, request: Request<BatchVerifyRequest>,
+    ) -> Result<Response<BatchVerifyResponse>, Status> {
+        self.route(&request.get_ref().group)?
+            .batch_verify(request)
+            .await
+    }
+
+    async fn verify_proof(
+        &self, request: Request<ProofRequest>,
+    ) -> Result<Response<VerifyResponse>, Status> {
+        self.route(&request.get_ref().group)?
+            .verify_proof(request)
+            .await
+    }
+
+    async fn mutual_authenticate(
+        &self, request: Request<MutualAuthenticationRequest>,
+    ) -> Result<Response<MutualAuthenticationResponse>, Status> {
+        self.route(&request.get_ref().group)?
+            .mutual_authenticate(request)
+            .await
+    }
+
+    async fn update_credentials(
+        &self, request: Request<UpdateCredentialsRequest>,
+    ) -> Result<Response<UpdateCredentialsResponse>, Status> {
+        self.route(&request.get_ref().group)?
+            .update_credentials(request)
+            .await
+    }
+
+    async fn validate_session(
+        &self, request: Request<ValidateSessionRequest>,
+    ) -> Result<Response<ValidateSessionResponse>, Status> {
+        self.route(&request.get_ref().group)?
+            .validate_session(request)
+            .await
+    }
+
+    async fn health(
+        &self, request: Request<HealthRequest>,
+    ) -> Result<Response<HealthResponse>, Status> {
+        self.route(&request.get_ref().group)?.health(request).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chaum_pedersen::constants::{
+        EC25519_GROUP_PARAMS, RFC5114_MODP_2048_256_BIT_PARAMS,
+    };
+    use crate::chaum_pedersen::curve25519::Curve25519ChaumPedersen;
+    use crate::chaum_pedersen::discretelog::DiscreteLogChaumPedersen;
+    use crate::chaum_pedersen::transcript::derive_nonce_challenge;
+    use crate::chaum_pedersen::VerifyOutcome;
+    use crate::client::ZkPassError;
+    use crate::rand::RandomGenerator;
+    use crate::repository::dao::UserDao;
+    use crate::repository::daoimpl::{HardenedUserDao, InMemoryUserDao};
+    use crate::repository::models::user_id;
+    use crate::repository::sqlite::SqliteUserDao;
+    use crate::repository::transcript::InMemoryTranscriptLog;
+    use curve25519_dalek::{RistrettoPoint, Scalar};
+    use std::sync::Arc;
+
+    type Ec25519Auth<D = AsyncInMemoryUserDao<RistrettoPoint, Scalar>> =
+        ZkAuth<Curve25519ChaumPedersen, RistrettoPoint, Scalar, D>;
+
+    /// How long [`SlowChaumPedersen`] takes to verify a response.
+    const SLOW_VERIFY_DELAY: Duration = Duration::from_millis(500);
+
+    /// The Curve25519 protocol with a verification that blocks its thread for
+    /// [`SLOW_VERIFY_DELAY`], standing in for verification with large parameters.
+    struct SlowChaumPedersen;
+
+    impl ChaumPedersen for SlowChaumPedersen {
+        type Secret = Scalar;
+        type CommitmentRandom = Scalar;
+        type Response = Scalar;
+        type Challenge = Scalar;
+        type GroupParameters = GroupParams<RistrettoPoint>;
+        type CommitParameters = (RistrettoPoint, RistrettoPoint, RistrettoPoint, RistrettoPoint);
+
+        fn commitment(
+            params: &Self::GroupParameters, x: &Scalar,
+        ) -> (Self::CommitParameters, Scalar) {
+            Curve25519ChaumPedersen::commitment(params, x)
+        }
+
+        fn challenge(params: &Self::GroupParameters) -> Scalar {
+            Curve25519ChaumPedersen::challenge(params)
+        }
+
+        fn challenge_response(
+            params: &Self::GroupParameters, k: &Scalar, c: &Scalar, x: &Scalar,
+        ) -> Scalar {
+            Curve25519ChaumPedersen::challenge_response(params, k, c, x)
+        }
+
+        fn verify_detailed(
+            params: &Self::GroupParameters, s: &Scalar, c: &Scalar, cp: &Self::CommitParameters,
+        ) -> VerifyOutcome {
+            std::thread::sleep(SLOW_VERIFY_DELAY);
+            Curve25519ChaumPedersen::verify_detailed(params, s, c, cp)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_register_batch_reports_invalid_users() {
+        let auth = Ec25519Auth::new(EC25519_GROUP_PARAMS.to_owned());
+
+        let users = (0..100)
+            .map(|i| RegisterRequest {
+                user: format!("user{}", i),
+                y1: if i == 42 {
+                    vec![0xff; 32]
+                } else {
+                    RistrettoPoint::convert_to(&RistrettoPoint::generate_random().unwrap())
+                },
+                y2: RistrettoPoint::convert_to(&RistrettoPoint::generate_random().unwrap()),
+                metadata: vec![],
+                namespace: String::new(),
+                params_version: 0,
+                group: String::new(),
+            })
+            .collect();
+
+        let reply = auth
+            .register_batch(Request::new(RegisterBatchRequest {
+                users,
+                group: String::new(),
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+
+        assert_eq!(reply.results.len(), 100);
+        for (i, result) in reply.results.iter().enumerate() {
+            assert_eq!(result.user, format!("user{}", i));
+            assert_eq!(result.success, i != 42);
+        }
+        assert_eq!(reply.results[42].error, "Invalid y1");
+
+        assert!(auth.dao.read("user0").await.is_some());
+        assert!(auth.dao.read("user99").await.is_some());
+        assert!(auth.dao.read("user42").await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_registration_rejected() {
+        let auth = Ec25519Auth::new(EC25519_GROUP_PARAMS.to_owned());
+        let victim = Scalar::generate_random().unwrap();
+        register_secret(&auth, "alice", &victim).await;
+
+        let status = auth
+            .register(Request::new(register_request("alice")))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::AlreadyExists);
+
+        let reply = auth
+            .register_batch(Request::new(RegisterBatchRequest {
+                users: vec![register_request("alice"), register_request("bob")],
+                group: String::new(),
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert!(!reply.results[0].success);
+        assert_eq!(reply.results[0].error, "User already exists");
+        assert!(reply.results[1].success);
+
+        // The original registration still authenticates.
+        let (auth_id, s, nonce) = answer_challenge(&auth, "alice", &victim).await;
+        assert!(auth
+            .verify_authentication(Request::new(AuthenticationAnswerRequest {
+                auth_id,
+                s,
+                nonce,
+                group: String::new()
+            }))
+            .await
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_repeated_registration_is_not_created() {
+        let auth = Ec25519Auth::new(EC25519_GROUP_PARAMS.to_owned());
+        let secret = Scalar::generate_random().unwrap();
+        let params = EC25519_GROUP_PARAMS.to_owned();
+
+        let first = register_secret_with(&auth, &params, 0, "alice", &secret)
+            .await
+            .unwrap()
+            .into_inner();
+        assert!(first.created);
+        assert_eq!(first.user_id, user_id("alice").to_string());
+
+        let second = register_secret_with(&auth, &params, 0, "alice", &secret)
+            .await
+            .unwrap()
+            .into_inner();
+        assert!(!second.created);
+        assert_eq!(second.user_id, first.user_id);
+
+        let bob = register_secret_with(&auth, &params, 0, "bob", &secret)
+            .await
+            .unwrap()
+            .into_inner();
+        assert!(bob.created);
+        assert_ne!(bob.user_id, first.user_id);
+    }
+
+    #[tokio::test]
+    async fn test_register_returns_server_public_values() {
+        let params = EC25519_GROUP_PARAMS.to_owned();
+        let server_secret = Scalar::generate_random().unwrap();
+        let auth = Ec25519Auth::new(params).with_server_secret(server_secret);
+
+        let reply = auth
+            .register(Request::new(register_request("alice")))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(reply.sy1, RistrettoPoint::convert_to(&(params.g * server_secret)));
+        assert_eq!(reply.sy2, RistrettoPoint::convert_to(&(params.h * server_secret)));
+
+        let plain = Ec25519Auth::new(params);
+        let reply = plain
+            .register(Request::new(register_request("alice")))
+            .await
+            .unwrap()
+            .into_inner();
+        assert!(reply.sy1.is_empty() && reply.sy2.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_force_register_overwrites_user() {
+        let auth = Ec25519Auth::new(EC25519_GROUP_PARAMS.to_owned());
+        let params = EC25519_GROUP_PARAMS.to_owned();
+        let old_secret = Scalar::generate_random().unwrap();
+        let new_secret = Scalar::generate_random().unwrap();
+        register_secret(&auth, "alice", &old_secret).await;
+
+        let ((y1, y2, _, _), _) = Curve25519ChaumPedersen::commitment(&params, &new_secret);
+        let request = RegisterRequest {
+            user: "alice".to_string(),
+            y1: RistrettoPoint::convert_to(&y1),
+            y2: RistrettoPoint::convert_to(&y2),
+            metadata: vec![],
+            namespace: String::new(),
+            params_version: 0,
+            group: String::new(),
+        };
+        assert!(auth.force_register(&request).await.unwrap());
+        assert!(!auth
+            .force_register(&RegisterRequest {
+                user: "bob".to_string(),
+                ..request
+            })
+            .await
+            .unwrap());
+
+        let (auth_id, s, nonce) = answer_challenge(&auth, "alice", &new_secret).await;
+        assert!(auth
+            .verify_authentication(Request::new(AuthenticationAnswerRequest {
+                auth_id,
+                s,
+                nonce,
+                group: String::new()
+            }))
+            .await
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_user_metadata_round_trip() {
+        let auth = Ec25519Auth::new(EC25519_GROUP_PARAMS.to_owned());
+
+        let metadata = vec![
+            Metadata {
+                key: "email".to_string(),
+                value: "alice@example.com".to_string(),
+            },
+            Metadata {
+                key: "roles".to_string(),
+                value: "admin,auditor".to_string(),
+            },
+        ];
+        let request = RegisterRequest {
+            user: "alice".to_string(),
+            y1: RistrettoPoint::convert_to(&RistrettoPoint::generate_random().unwrap()),
+            y2: RistrettoPoint::convert_to(&RistrettoPoint::generate_random().unwrap()),
+            metadata: metadata.clone(),
+            namespace: String::new(),
+            params_version: 0,
+            group: String::new(),
+        };
+        auth.register(Request::new(request)).await.unwrap();
+
+        let reply = auth
+            .get_user_metadata(Request::new(GetUserMetadataRequest {
+                user: "alice".to_string(),
+                namespace: String::new(),
+                group: String::new(),
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+
+        let mut received = reply.metadata;
+        received.sort_by(|a, b| a.key.cmp(&b.key));
+        assert_eq!(received, metadata);
+    }
+
+    #[tokio::test]
+    async fn test_user_metadata_unknown_user() {
+        let auth = Ec25519Auth::new(EC25519_GROUP_PARAMS.to_owned());
+
+        let status = auth
+            .get_user_metadata(Request::new(GetUserMetadataRequest {
+                user: "nobody".to_string(),
+                namespace: String::new(),
+                group: String::new(),
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::NotFound);
+    }
+
+    #[tokio::test]
+    async fn test_user_metadata_storage_error() {
+        let path = std::env::temp_dir()
+            .join(format!("zk_pass_metadata_{}.sqlite", std::process::id()))
+            .to_string_lossy()
+            .into_owned();
+        let dao = SqliteUserDao::<RistrettoPoint, Scalar>::open(&path).unwrap();
+        let auth = Ec25519Auth::with_dao(EC25519_GROUP_PARAMS.to_owned(), Mutex::new(dao));
+        register_secret(&auth, "alice", &Scalar::generate_random().unwrap()).await;
+        rusqlite::Connection::open(&path)
+            .unwrap()
+            .execute("UPDATE users SET metadata = 'not json'", [])
+            .unwrap();
+
+        // Metadata that cannot be loaded is not reported as an unknown user.
+        let status = auth
+            .get_user_metadata(Request::new(GetUserMetadataRequest {
+                user: "alice".to_string(),
+                namespace: String::new(),
+                group: String::new(),
+            }))
+            .await
+            .unwrap_err();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(status.code(), tonic::Code::Internal);
+    }
+
+    /// Opens a challenge for `user` with a commitment for `x`, returning the challenge ID, the
+    /// response to it computed with `x` and the challenge nonce.
+    async fn answer_challenge<D: AsyncUserDao<RistrettoPoint, Scalar> + 'static>(
+        auth: &Ec25519Auth<D>, user: &str, x: &Scalar,
+    ) -> (String, Vec<u8>, Vec<u8>) {
+        answer_challenge_with(auth, &EC25519_GROUP_PARAMS, user, x).await
+    }
+
+    /// Like [`answer_challenge`], for a user registered with the given group parameters.
+    async fn answer_challenge_with(
+        auth: &impl Auth, params: &GroupParams<RistrettoPoint>, user: &str, x: &Scalar,
+    ) -> (String, Vec<u8>, Vec<u8>) {
+        let ((_, _, r1, r2), k) = Curve25519ChaumPedersen::commitment(params, x);
+        let reply = auth
+            .create_authentication_challenge(Request::new(AuthenticationChallengeRequest {
+                user: user.to_string(),
+                r1: RistrettoPoint::convert_to(&r1),
+                r2: RistrettoPoint::convert_to(&r2),
+                namespace: String::new(),
+                group: String::new(),
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        let c = Scalar::convert_from(&reply.c).unwrap();
+        let c = derive_nonce_challenge(params, &reply.nonce, &c);
+        let s = Curve25519ChaumPedersen::challenge_response(params, &k, &c, x);
+        (reply.auth_id, Scalar::convert_to(&s), reply.nonce)
+    }
+
+    /// Registers `user` with the commitment for secret `x`.
+    async fn register_secret<D: AsyncUserDao<RistrettoPoint, Scalar> + 'static>(
+        auth: &Ec25519Auth<D>, user: &str, x: &Scalar,
+    ) {
+        register_secret_with(auth, &EC25519_GROUP_PARAMS, 0, user, x)
+            .await
+            .unwrap();
+    }
+
+    /// Registers `user` with the commitment for secret `x` under the given version of the group
+    /// parameters.
+    async fn register_secret_with(
+        auth: &impl Auth, params: &GroupParams<RistrettoPoint>, params_version: u32, user: &str,
+        x: &Scalar,
+    ) -> Result<Response<RegisterResponse>, Status> {
+        let ((y1, y2, _, _), _) = Curve25519ChaumPedersen::commitment(params, x);
+        auth.register(Request::new(RegisterRequest {
+            user: user.to_string(),
+            y1: RistrettoPoint::convert_to(&y1),
+            y2: RistrettoPoint::convert_to(&y2),
+            metadata: vec![],
+            namespace: String::new(),
+            params_version,
+            group: String::new(),
+        }))
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_transcript_log_records_every_answer() {
+        let log = Arc::new(InMemoryTranscriptLog::new());
+        let auth = Ec25519Auth::new(EC25519_GROUP_PARAMS.to_owned())
+            .with_transcript_log(Box::new(log.clone()));
+        let secret = Scalar::generate_random().unwrap();
+        register_secret(&auth, "alice", &secret).await;
+
+        let (auth_id, s, nonce) = answer_challenge(&auth, "alice", &secret).await;
+        auth.verify_authentication(Request::new(AuthenticationAnswerRequest {
+            auth_id,
+            s,
+            nonce,
+            group: String::new(),
+        }))
+        .await
+        .unwrap();
+        let wrong_secret = Scalar::generate_random().unwrap();
+        let (auth_id, s, nonce) = answer_challenge(&auth, "alice", &wrong_secret).await;
+        auth.verify_authentication(Request::new(AuthenticationAnswerRequest {
+            auth_id,
+            s: s.clone(),
+            nonce,
+            group: String::new(),
+        }))
+        .await
+        .unwrap_err();
+
+        let records = log.records();
+        assert_eq!(records.len(), 2);
+        assert!(records.iter().all(|record| record.user == "alice"));
+        assert!(records[0].transcript.verified);
+        assert!(!records[1].transcript.verified);
+        assert_eq!(records[1].transcript.s, s);
+        assert_ne!(records[0].transcript.c, records[1].transcript.c);
+        assert_eq!(records[0].transcript.y1, records[1].transcript.y1);
+    }
+
+    #[tokio::test]
+    async fn test_update_credentials_with_valid_proof() {
+        let auth = Ec25519Auth::new(EC25519_GROUP_PARAMS.to_owned());
+        let params = EC25519_GROUP_PARAMS.to_owned();
+        let old_secret = Scalar::generate_random().unwrap();
+        let new_secret = Scalar::generate_random().unwrap();
+        register_secret(&auth, "alice", &old_secret).await;
+
+        let (auth_id, s, nonce) = answer_challenge(&auth, "alice", &old_secret).await;
+        let ((y1, y2, _, _), _) = Curve25519ChaumPedersen::commitment(&params, &new_secret);
+        auth.update_credentials(Request::new(UpdateCredentialsRequest {
+            auth_id,
+            s,
+            y1: RistrettoPoint::convert_to(&y1),
+            y2: RistrettoPoint::convert_to(&y2),
+            nonce,
+            params_version: 0,
+            group: String::new(),
+            old_y1: Vec::new(),
+            old_y2: Vec::new(),
+        }))
+        .await
+        .unwrap();
+
+        // The new secret authenticates, the old one no longer does.
+        let (auth_id, s, nonce) = answer_challenge(&auth, "alice", &new_secret).await;
+        assert!(auth
+            .verify_authentication(Request::new(AuthenticationAnswerRequest {
+                auth_id,
+                s,
+                nonce,
+                group: String::new()
+            }))
+            .await
+            .is_ok());
+        let (auth_id, s, nonce) = answer_challenge(&auth, "alice", &old_secret).await;
+        assert!(auth
+            .verify_authentication(Request::new(AuthenticationAnswerRequest {
+                auth_id,
+                s,
+                nonce,
+                group: String::new()
+            }))
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_update_credentials_without_valid_proof() {
+        let auth = Ec25519Auth::new(EC25519_GROUP_PARAMS.to_owned());
+        let params = EC25519_GROUP_PARAMS.to_owned();
+        let secret = Scalar::generate_random().unwrap();
+        let attacker_secret = Scalar::generate_random().unwrap();
+        register_secret(&auth, "alice", &secret).await;
+
+        let (auth_id, _, nonce) = answer_challenge(&auth, "alice", &secret).await;
+        let ((y1, y2, _, _), _) = Curve25519ChaumPedersen::commitment(&params, &attacker_secret);
+        let status = auth
+            .update_credentials(Request::new(UpdateCredentialsRequest {
+                auth_id,
+                s: Scalar::convert_to(&Scalar::generate_random().unwrap()),
+                y1: RistrettoPoint::convert_to(&y1),
+                y2: RistrettoPoint::convert_to(&y2),
+                nonce,
+                params_version: 0,
+                group: String::new(),
+                old_y1: Vec::new(),
+                old_y2: Vec::new(),
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+
+        // The original secret still authenticates.
+        let (auth_id, s, nonce) = answer_challenge(&auth, "alice", &secret).await;
+        assert!(auth
+            .verify_authentication(Request::new(AuthenticationAnswerRequest {
+                auth_id,
+                s,
+                nonce,
+                group: String::new()
+            }))
+            .await
+            .is_ok());
+    }
+
+    /// Builds a request replacing the commitment of the user a challenge was answered for with
+    /// the one for `new_secret`, if the stored one is the one for `old_secret`.
+    fn update_request(
+        (auth_id, s, nonce): (String, Vec<u8>, Vec<u8>), old_secret: &Scalar, new_secret: &Scalar,
+    ) -> Request<UpdateCredentialsRequest> {
+        let params = EC25519_GROUP_PARAMS.to_owned();
+        let ((old_y1, old_y2, _, _), _) = Curve25519ChaumPedersen::commitment(&params, old_secret);
+        let ((y1, y2, _, _), _) = Curve25519ChaumPedersen::commitment(&params, new_secret);
+        Request::new(UpdateCredentialsRequest {
+            auth_id,
+            s,
+            y1: RistrettoPoint::convert_to(&y1),
+            y2: RistrettoPoint::convert_to(&y2),
+            nonce,
+            params_version: 0,
+            group: String::new(),
+            old_y1: RistrettoPoint::convert_to(&old_y1),
+            old_y2: RistrettoPoint::convert_to(&old_y2),
+        })
+    }
+
+    #[tokio::test]
+    async fn test_update_credentials_compares_old_credentials() {
+        let auth = Ec25519Auth::new(EC25519_GROUP_PARAMS.to_owned());
+        let old_secret = Scalar::generate_random().unwrap();
+        let new_secret = Scalar::generate_random().unwrap();
+        register_secret(&auth, "alice", &old_secret).await;
+
+        // A valid answer does not help if another commitment is expected.
+        let answer = answer_challenge(&auth, "alice", &old_secret).await;
+        let wrong_secret = Scalar::generate_random().unwrap();
+        let status = auth
+            .update_credentials(update_request(answer.clone(), &wrong_secret, &new_secret))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::Aborted);
+
+        auth.update_credentials(update_request(answer, &old_secret, &new_secret))
+            .await
+            .unwrap();
+        let (auth_id, s, nonce) = answer_challenge(&auth, "alice", &new_secret).await;
+        assert!(auth
+            .verify_authentication(Request::new(AuthenticationAnswerRequest {
+                auth_id,
+                s,
+                nonce,
+                group: String::new(),
+            }))
+            .await
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_credential_updates_conflict() {
+        let auth = Ec25519Auth::new(EC25519_GROUP_PARAMS.to_owned());
+        let secret = Scalar::generate_random().unwrap();
+        register_secret(&auth, "alice", &secret).await;
+
+        // Two devices holding the secret rotate it at the same time; only one of them wins.
+        let first = answer_challenge(&auth, "alice", &secret).await;
+        let second = answer_challenge(&auth, "alice", &secret).await;
+        let (first_secret, second_secret) =
+            (Scalar::generate_random().unwrap(), Scalar::generate_random().unwrap());
+        let (first, second) = tokio::join!(
+            auth.update_credentials(update_request(first, &secret, &first_secret)),
+            auth.update_credentials(update_request(second, &secret, &second_secret)),
+        );
+        let (winner, loser) = match (first, second) {
+            (Ok(_), Err(status)) => (first_secret, status),
+            (Err(status), Ok(_)) => (second_secret, status),
+            results => panic!("Expected exactly one update to succeed: {:?}", results),
+        };
+        assert_eq!(loser.code(), Code::Aborted);
+        register_secret(&auth, "bob", &winner).await;
+        let stored = auth.dao.read("alice").await.unwrap();
+        assert_eq!(stored.y1, auth.dao.read("bob").await.unwrap().y1);
+    }
+
+    #[tokio::test]
+    async fn test_update_credentials_of_deleted_user() {
+        let auth = Ec25519Auth::new(EC25519_GROUP_PARAMS.to_owned());
+        let secret = Scalar::generate_random().unwrap();
+        register_secret(&auth, "alice", &secret).await;
+
+        let answer = answer_challenge(&auth, "alice", &secret).await;
+        auth.dao.delete("alice").await.unwrap();
+        let status = auth
+            .update_credentials(update_request(answer, &secret, &secret))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::NotFound);
+    }
+
+    /// Wraps `message` in a request that appears to come from `addr`.
+    fn request_from<R>(message: R, addr: &str) -> Request<R> {
+        let mut request = Request::new(message);
+        request
+            .extensions_mut()
+            .insert(tonic::transport::server::TcpConnectInfo {
+                local_addr: None,
+                remote_addr: Some(addr.parse().unwrap()),
+            });
+        request
+    }
+
+    #[tokio::test]
+    async fn test_challenge_bound_to_ip() {
+        let auth =
+            Ec25519Auth::new(EC25519_GROUP_PARAMS.to_owned()).with_challenge_ip_binding(true);
+        let params = EC25519_GROUP_PARAMS.to_owned();
+        let secret = Scalar::generate_random().unwrap();
+        register_secret(&auth, "alice", &secret).await;
+
+        let ((_, _, r1, r2), k) = Curve25519ChaumPedersen::commitment(&params, &secret);
+        let reply = auth
+            .create_authentication_challenge(request_from(
+                AuthenticationChallengeRequest {
+                    user: "alice".to_string(),
+                    r1: RistrettoPoint::convert_to(&r1),
+                    r2: RistrettoPoint::convert_to(&r2),
+                    namespace: String::new(),
+                    group: String::new(),
+                },
+                "10.0.0.1:5000",
+            ))
+            .await
+            .unwrap()
+            .into_inner();
+        let c = Scalar::convert_from(&reply.c).unwrap();
+        let c = derive_nonce_challenge(&params, &reply.nonce, &c);
+        let s = Scalar::convert_to(&Curve25519ChaumPedersen::challenge_response(
+            &params, &k, &c, &secret,
+        ));
+
+        // A valid answer forwarded from another address is rejected.
+        let status = auth
+            .verify_authentication(request_from(
+                AuthenticationAnswerRequest {
+                    auth_id: reply.auth_id.clone(),
+                    s: s.clone(),
+                    nonce: reply.nonce.clone(),
+                    group: String::new(),
+                },
+                "10.0.0.2:5000",
+            ))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::PermissionDenied);
+
+        // The same answer from the original address, on a different port, is accepted.
+        assert!(auth
+            .verify_authentication(request_from(
+                AuthenticationAnswerRequest {
+                    auth_id: reply.auth_id,
+                    s,
+                    nonce: reply.nonce,
+                    group: String::new(),
+                },
+                "10.0.0.1:6000",
+            ))
+            .await
+            .is_ok());
+    }
+
+    /// Builds a registration request for a fresh random secret.
+    fn register_request(user: &str) -> RegisterRequest {
+        RegisterRequest {
+            user: user.to_string(),
+            y1: RistrettoPoint::convert_to(&RistrettoPoint::generate_random().unwrap()),
+            y2: RistrettoPoint::convert_to(&RistrettoPoint::generate_random().unwrap()),
+            metadata: vec![],
+            namespace: String::new(),
+            params_version: 0,
+            group: String::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_allowlist_restricts_peers() {
+        let auth = Ec25519Auth::new(EC25519_GROUP_PARAMS.to_owned())
+            .with_allowed_cidrs(vec!["10.0.0.0/8".parse().unwrap()]);
+
+        assert!(auth
+            .register(request_from(register_request("alice"), "10.1.2.3:5000"))
+            .await
+            .is_ok());
+
+        let status = auth
+            .register(request_from(register_request("bob"), "192.168.1.1:5000"))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::PermissionDenied);
+
+        // Without a known peer address, an allowlist cannot be satisfied.
+        let status = auth
+            .register(Request::new(register_request("carol")))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::PermissionDenied);
+    }
+
+    #[tokio::test]
+    async fn test_denylist_rejects_peers() {
+        let auth = Ec25519Auth::new(EC25519_GROUP_PARAMS.to_owned())
+            .with_allowed_cidrs(vec!["10.0.0.0/8".parse().unwrap()])
+            .with_denied_cidrs(vec!["10.0.0.0/24".parse().unwrap()]);
+
+        let status = auth
+            .register(request_from(register_request("alice"), "10.0.0.7:5000"))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::PermissionDenied);
+
+        assert!(auth
+            .register(request_from(register_request("bob"), "10.0.1.7:5000"))
+            .await
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_validate_session_rejects_denied_peers() {
+        let auth = Ec25519Auth::new(EC25519_GROUP_PARAMS.to_owned())
+            .with_denied_cidrs(vec!["10.0.0.0/24".parse().unwrap()]);
+        let validate = |addr: &str| {
+            request_from(
+                ValidateSessionRequest {
+                    token: "unknown".to_string(),
+                    group: String::new(),
+                },
+                addr,
+            )
+        };
+
+        let status = auth
+            .validate_session(validate("10.0.0.7:5000"))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::PermissionDenied);
+
+        // Other peers get past the check and are told the session does not exist.
+        let status = auth
+            .validate_session(validate("10.0.1.7:5000"))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::Unauthenticated);
+    }
+
+    /// Returns the current time in seconds since the Unix epoch.
+    fn now() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+    }
+
+    #[tokio::test]
+    async fn test_offline_proof_bundle_accepted_once() {
+        let auth = Ec25519Auth::new(EC25519_GROUP_PARAMS.to_owned());
+        let params = EC25519_GROUP_PARAMS.to_owned();
+        let secret = Scalar::generate_random().unwrap();
+        register_secret(&auth, "alice", &secret).await;
+
+        // The bundle travels as JSON, e.g. in a file or QR code.
+        let bundle = ProofBundle::<Curve25519ChaumPedersen>::prove(
+            &params,
+            "alice".to_string(),
+            now(),
+            &secret,
+        );
+        let json = serde_json::to_string(&bundle).unwrap();
+        let bundle: ProofBundle<Curve25519ChaumPedersen> = serde_json::from_str(&json).unwrap();
+        assert!(auth.verify_proof_bundle(&bundle).await.unwrap());
+
+        // Replaying the same bundle is rejected.
+        let status = auth.verify_proof_bundle(&bundle).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::PermissionDenied);
+    }
+
+    #[tokio::test]
+    async fn test_proof_bundle_rejections() {
+        let auth = Ec25519Auth::new(EC25519_GROUP_PARAMS.to_owned())
+            .with_proof_bundle_ttl(Duration::from_secs(60));
+        let params = EC25519_GROUP_PARAMS.to_owned();
+        let secret = Scalar::generate_random().unwrap();
+        register_secret(&auth, "alice", &secret).await;
+
+        // A bundle older than the TTL is rejected.
+        let stale = ProofBundle::<Curve25519ChaumPedersen>::prove(
+            &params,
+            "alice".to_string(),
+            now() - 120,
+            &secret,
+        );
+        let status = auth.verify_proof_bundle(&stale).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::PermissionDenied);
+        assert_eq!(ZkPassError::from_status(&status), Some(ZkPassError::ChallengeExpired));
+
+        // A bundle made with another secret does not verify.
+        let forged = ProofBundle::<Curve25519ChaumPedersen>::prove(
+            &params,
+            "alice".to_string(),
+            now(),
+            &Scalar::generate_random().unwrap(),
+        );
+        assert!(!auth.verify_proof_bundle(&forged).await.unwrap());
+
+        // Re-dating a valid bundle invalidates its challenge.
+        let mut redated = ProofBundle::<Curve25519ChaumPedersen>::from_compact_bytes(
+            &ProofBundle::<Curve25519ChaumPedersen>::prove(
+                &params,
+                "alice".to_string(),
+                now(),
+                &secret,
+            )
+            .to_compact_bytes()
+            .unwrap(),
+        )
+        .unwrap();
+        redated.timestamp -= 1;
+        assert!(!auth.verify_proof_bundle(&redated).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_verify_proof_starts_session() {
+        let auth = Ec25519Auth::new(EC25519_GROUP_PARAMS.to_owned());
+        let params = EC25519_GROUP_PARAMS.to_owned();
+        let secret = Scalar::generate_random().unwrap();
+        register_secret(&auth, "alice", &secret).await;
+        let verify = |proof_bytes| {
+            auth.verify_proof(Request::new(ProofRequest {
+                user: "alice".to_string(),
+                proof_bytes,
+                group: String::new(),
+            }))
+        };
+
+        // A valid proof starts a session.
+        let bundle = ProofBundle::<Curve25519ChaumPedersen>::prove(
+            &params,
+            "alice".to_string(),
+            now(),
+            &secret,
+        );
+        let reply = verify(bundle.to_compact_bytes().unwrap())
+            .await
+            .unwrap()
+            .into_inner();
+        assert!(reply.verified);
+        let session = auth
+            .validate_session(Request::new(ValidateSessionRequest {
+                token: reply.session_id,
+                group: String::new(),
+            }))
+            .await
+            .unwrap();
+        assert_eq!(session.get_ref().user, "alice");
+
+        // A proof of another secret does not.
+        let forged = ProofBundle::<Curve25519ChaumPedersen>::prove(
+            &params,
+            "alice".to_string(),
+            now(),
+            &Scalar::generate_random().unwrap(),
+        );
+        let reply = verify(forged.to_compact_bytes().unwrap())
+            .await
+            .unwrap()
+            .into_inner();
+        assert!(!reply.verified);
+        assert!(reply.session_id.is_empty());
+
+        // Undecodable proofs are rejected.
+        let status = verify(vec![1, 2, 3]).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+    }
+
+    #[tokio::test]
+    async fn test_answer_requires_challenge_nonce() {
+        let auth = Ec25519Auth::new(EC25519_GROUP_PARAMS.to_owned());
+        let params = EC25519_GROUP_PARAMS.to_owned();
+        let secret = Scalar::generate_random().unwrap();
+        register_secret(&auth, "alice", &secret).await;
+
+        // A correct response sent with a different nonce is rejected.
+        let (auth_id, s, _) = answer_challenge(&auth, "alice", &secret).await;
+        let status = auth
+            .verify_authentication(Request::new(AuthenticationAnswerRequest {
+                auth_id,
+                s,
+                nonce: vec![0; CHALLENGE_NONCE_LEN],
+                group: String::new(),
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::PermissionDenied);
+
+        // A response to the bare challenge `c`, ignoring the nonce, does not verify.
+        let ((_, _, r1, r2), k) = Curve25519ChaumPedersen::commitment(&params, &secret);
+        let reply = auth
+            .create_authentication_challenge(Request::new(AuthenticationChallengeRequest {
+                user: "alice".to_string(),
+                r1: RistrettoPoint::convert_to(&r1),
+                r2: RistrettoPoint::convert_to(&r2),
+                namespace: String::new(),
+                group: String::new(),
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(reply.nonce.len(), CHALLENGE_NONCE_LEN);
+        let c = Scalar::convert_from(&reply.c).unwrap();
+        let s = Curve25519ChaumPedersen::challenge_response(&params, &k, &c, &secret);
+        let status = auth
+            .verify_authentication(Request::new(AuthenticationAnswerRequest {
+                auth_id: reply.auth_id,
+                s: Scalar::convert_to(&s),
+                nonce: reply.nonce,
+                group: String::new(),
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+    }
+
+    #[tokio::test]
+    async fn test_full_dao_refuses_registration() {
+        let dao = Mutex::new(InMemoryUserDao::<RistrettoPoint, Scalar>::with_capacity(1));
+        let auth = Ec25519Auth::with_dao(EC25519_GROUP_PARAMS.to_owned(), dao);
+        let secret = Scalar::generate_random().unwrap();
+        register_secret(&auth, "alice", &secret).await;
+
+        let status = auth
+            .register(Request::new(RegisterRequest {
+                user: "mallory".to_string(),
+                y1: RistrettoPoint::convert_to(&RistrettoPoint::generate_random().unwrap()),
+                y2: RistrettoPoint::convert_to(&RistrettoPoint::generate_random().unwrap()),
+                metadata: vec![],
+                namespace: String::new(),
+                params_version: 0,
+                group: String::new(),
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::ResourceExhausted);
+
+        // The registered user is kept and can still authenticate.
+        let (auth_id, s, nonce) = answer_challenge(&auth, "alice", &secret).await;
+        assert!(auth
+            .verify_authentication(Request::new(AuthenticationAnswerRequest {
+                auth_id,
+                s,
+                nonce,
+                group: String::new()
+            }))
+            .await
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_mutex_wrapped_sync_dao() {
+        let dao = Mutex::new(InMemoryUserDao::<RistrettoPoint, Scalar>::new());
+        let auth = Ec25519Auth::with_dao(EC25519_GROUP_PARAMS.to_owned(), dao);
+        let secret = Scalar::generate_random().unwrap();
+        register_secret(&auth, "alice", &secret).await;
+
+        let (auth_id, s, nonce) = answer_challenge(&auth, "alice", &secret).await;
+        assert!(auth
+            .verify_authentication(Request::new(AuthenticationAnswerRequest {
+                auth_id,
+                s,
+                nonce,
+                group: String::new()
+            }))
+            .await
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_verify_timeout_fails_slow_verification() {
+        let auth = ZkAuth::<SlowChaumPedersen, RistrettoPoint, Scalar>::new(
+            EC25519_GROUP_PARAMS.to_owned(),
+        )
+        .with_verify_timeout(Duration::from_millis(50), 1);
+        let x = Scalar::generate_random().unwrap();
+        register_secret_with(&auth, &EC25519_GROUP_PARAMS, 0, "alice", &x)
+            .await
+            .unwrap();
+        let (auth_id, s, nonce) =
+            answer_challenge_with(&auth, &EC25519_GROUP_PARAMS, "alice", &x).await;
+        let second = answer_challenge_with(&auth, &EC25519_GROUP_PARAMS, "alice", &x).await;
+
+        // The test runs on a single-threaded runtime, so the timeout can only fire before
+        // verification completes if verification does not block the runtime's thread.
+        let started = std::time::Instant::now();
+        let status = auth
+            .verify_authentication(Request::new(AuthenticationAnswerRequest {
+                auth_id,
+                s,
+                nonce,
+                group: String::new(),
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::DeadlineExceeded);
+        assert!(started.elapsed() < SLOW_VERIFY_DELAY);
+
+        // The timed-out verification still holds the only slot, so the next answer fails fast.
+        let (auth_id, s, nonce) = second;
+        let status = auth
+            .verify_authentication(Request::new(AuthenticationAnswerRequest {
+                auth_id,
+                s,
+                nonce,
+                group: String::new(),
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::ResourceExhausted);
+        assert!(started.elapsed() < SLOW_VERIFY_DELAY);
+    }
+
+    #[tokio::test]
+    async fn test_verify_timeout_passes_fast_verification() {
+        let auth = Ec25519Auth::new(EC25519_GROUP_PARAMS.to_owned())
+            .with_verify_timeout(Duration::from_secs(5), 1);
+        let x = Scalar::generate_random().unwrap();
+        register_secret(&auth, "alice", &x).await;
+        let (auth_id, s, nonce) = answer_challenge(&auth, "alice", &x).await;
+        assert!(auth
+            .verify_authentication(Request::new(AuthenticationAnswerRequest {
+                auth_id,
+                s,
+                nonce,
+                group: String::new(),
+            }))
+            .await
+            .is_ok());
+
+        // A wrong answer still fails verification, rather than the deadline.
+        let (auth_id, _, nonce) = answer_challenge(&auth, "alice", &x).await;
+        let status = auth
+            .verify_authentication(Request::new(AuthenticationAnswerRequest {
+                auth_id,
+                s: Scalar::convert_to(&Scalar::generate_random().unwrap()),
+                nonce,
+                group: String::new(),
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::InvalidArgument);
+    }
+
+    #[tokio::test]
+    async fn test_hardened_dao_authenticates_without_storing_commitments() {
+        let dao =
+            Arc::new(Mutex::new(HardenedUserDao::new(InMemoryUserDao::<Vec<u8>, Scalar>::new())));
+        let auth = Ec25519Auth::with_dao(EC25519_GROUP_PARAMS.to_owned(), dao.clone());
+        let secret = Scalar::generate_random().unwrap();
+        register_secret(&auth, "alice", &secret).await;
+
+        // The underlying store only holds the Argon2id hashes of the commitment.
+        let ((y1, y2, _, _), _) =
+            Curve25519ChaumPedersen::commitment(&EC25519_GROUP_PARAMS, &secret);
+        let stored = dao.lock().await.inner().read("alice").unwrap();
+        for (hash, y) in [(&stored.y1, &y1), (&stored.y2, &y2)] {
+            assert!(hash.starts_with(b"$argon2id$"));
+            assert_ne!(*hash, RistrettoPoint::convert_to(y));
+        }
+
+        let (auth_id, s, nonce) = answer_challenge(&auth, "alice", &secret).await;
+        assert!(auth
+            .verify_authentication(Request::new(AuthenticationAnswerRequest {
+                auth_id,
+                s,
+                nonce,
+                group: String::new()
+            }))
+            .await
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_resume_authentication() {
+        let params = EC25519_GROUP_PARAMS.to_owned();
+        let sealer = ResumeTokenSealer::new([7; 32], Duration::from_secs(60));
+        let auth = Ec25519Auth::new(params).with_resumption(sealer.clone());
+        let secret = Scalar::generate_random().unwrap();
+        register_secret(&auth, "alice", &secret).await;
+        let resume = |resume_token: Vec<u8>| {
+            auth.resume_authentication(Request::new(ResumeRequest {
+                resume_token,
+                group: String::new(),
+            }))
+        };
+
+        let ((_, _, r1, r2), k) = Curve25519ChaumPedersen::commitment(&params, &secret);
+        let (r1, r2) = (RistrettoPoint::convert_to(&r1), RistrettoPoint::convert_to(&r2));
+        let reply = auth
+            .create_authentication_challenge(Request::new(AuthenticationChallengeRequest {
+                user: "alice".to_string(),
+                r1: r1.clone(),
+                r2: r2.clone(),
+                namespace: String::new(),
+                group: String::new(),
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert!(!reply.resume_token.is_empty());
+
+        // A valid token re-issues the same challenge, which can then be answered.
+        let resumed = resume(reply.resume_token.clone())
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(resumed, reply);
+
+        // Tampered and expired tokens are rejected.
+        let mut tampered = reply.resume_token.clone();
+        tampered[4] ^= 1;
+        let status = resume(tampered).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+        let issued_at = unix_time().unwrap() - 120;
+        let expired = sealer
+            .seal(&ResumeClaims::new("alice", &reply.auth_id, &r1, &r2, &reply.c, issued_at))
+            .unwrap();
+        let status = resume(expired).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::PermissionDenied);
+        assert_eq!(ZkPassError::from_status(&status), Some(ZkPassError::ChallengeExpired));
+
+        let c = Scalar::convert_from(&resumed.c).unwrap();
+        let c = derive_nonce_challenge(&params, &resumed.nonce, &c);
+        let s = Curve25519ChaumPedersen::challenge_response(&params, &k, &c, &secret);
+        auth.verify_authentication(Request::new(AuthenticationAnswerRequest {
+            auth_id: resumed.auth_id,
+            s: Scalar::convert_to(&s),
+            nonce: resumed.nonce,
+            group: String::new(),
+        }))
+        .await
+        .unwrap();
+
+        // An answered challenge cannot be resumed.
+        let status = resume(reply.resume_token).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::NotFound);
+    }
+
+    #[tokio::test]
+    async fn test_resume_requires_resumption() {
+        let auth = Ec25519Auth::new(EC25519_GROUP_PARAMS.to_owned());
+        let secret = Scalar::generate_random().unwrap();
+        register_secret(&auth, "alice", &secret).await;
+        let (auth_id, _, _) = answer_challenge(&auth, "alice", &secret).await;
+        assert!(!auth_id.is_empty());
+
+        let status = auth
+            .resume_authentication(Request::new(ResumeRequest {
+                resume_token: vec![0; 64],
+                group: String::new(),
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::FailedPrecondition);
+    }
+
+    #[tokio::test]
+    async fn test_challenge_filter_refuses_used_ids() {
+        let auth = Ec25519Auth::new(EC25519_GROUP_PARAMS.to_owned())
+            .with_challenge_filter(ChallengeBloomFilter::new(1_000, 0.01));
+        let secret = Scalar::generate_random().unwrap();
+        register_secret(&auth, "alice", &secret).await;
+        let (auth_id, s, nonce) = answer_challenge(&auth, "alice", &secret).await;
+        assert!(auth.check_challenge_id_unused(&auth_id).await.is_ok());
+        auth.verify_authentication(Request::new(AuthenticationAnswerRequest {
+            auth_id: auth_id.clone(),
+            s,
+            nonce,
+            group: String::new(),
+        }))
+        .await
+        .unwrap();
+
+        // Once answered, the ID is refused for new challenges.
+        let status = auth.check_challenge_id_unused(&auth_id).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::Aborted);
+    }
+
+    #[tokio::test]
+    async fn test_stateless_verification_matches_stateful() {
+        let params = EC25519_GROUP_PARAMS.to_owned();
+        let sealer = ResumeTokenSealer::new([7; 32], Duration::from_secs(60));
+        let auth = Ec25519Auth::new(params).with_resumption(sealer);
+        let secret = Scalar::generate_random().unwrap();
+        register_secret(&auth, "alice", &secret).await;
+        let ((y1, y2, _, _), _) = Curve25519ChaumPedersen::commitment(&params, &secret);
+        let (y1, y2) = (RistrettoPoint::convert_to(&y1), RistrettoPoint::convert_to(&y2));
+
+        // Issues a challenge and answers it with `x`, as a stateful and a stateless answer.
+        let answer = |x: Scalar| {
+            let auth = auth.clone();
+            let (y1, y2) = (y1.clone(), y2.clone());
+            async move {
+                let ((_, _, r1, r2), k) = Curve25519ChaumPedersen::commitment(&params, &x);
+                let (r1, r2) = (RistrettoPoint::convert_to(&r1), RistrettoPoint::convert_to(&r2));
+                let reply = auth
+                    .create_authentication_challenge(Request::new(AuthenticationChallengeRequest {
+                        user: "alice".to_string(),
+                        r1: r1.clone(),
+                        r2: r2.clone(),
+                        namespace: String::new(),
+                        group: String::new(),
+                    }))
+                    .await
+                    .unwrap()
+                    .into_inner();
+                let c = Scalar::convert_from(&reply.c).unwrap();
+                let c = derive_nonce_challenge(&params, &reply.nonce, &c);
+                let s = Curve25519ChaumPedersen::challenge_response(&params, &k, &c, &x);
+                let s = Scalar::convert_to(&s);
+                let stateful = AuthenticationAnswerRequest {
+                    auth_id: reply.auth_id,
+                    s: s.clone(),
+                    nonce: reply.nonce.clone(),
+                    group: String::new(),
+                };
+                let stateless = StatelessAnswerRequest {
+                    y1,
+                    y2,
+                    r1,
+                    r2,
+                    c: reply.c,
+                    s,
+                    nonce: reply.nonce,
+                    resume_token: reply.resume_token,
+                    group: String::new(),
+                };
+                (stateful, stateless)
+            }
+        };
+        let verify_stateful = |req| auth.verify_authentication(Request::new(req));
+        let verify_stateless = |req| auth.verify_authentication_stateless(Request::new(req));
+
+        // Both paths accept the right secret and reject a wrong one alike.
+        for (x, verifies) in [(secret, true), (Scalar::generate_random().unwrap(), false)] {
+            let (stateful, _) = answer(x).await;
+            let (_, stateless) = answer(x).await;
+            let stateful = verify_stateful(stateful).await;
+            let stateless = verify_stateless(stateless).await;
+            assert_eq!(stateful.is_ok(), verifies);
+            assert_eq!(stateless.is_ok(), verifies);
+            if let (Err(stateful), Err(stateless)) = (stateful, stateless) {
+                assert_eq!(stateful.code(), stateless.code());
+                assert_eq!(
+                    ZkPassError::from_status(&stateful),
+                    ZkPassError::from_status(&stateless)
+                );
+            }
+        }
+
+        // A challenge can only be answered once, through either path.
+        let (stateful, stateless) = answer(secret).await;
+        verify_stateless(stateless.clone()).await.unwrap();
+        let status = verify_stateless(stateless).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::PermissionDenied);
+        let status = verify_stateful(stateful).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::NotFound);
+        let (stateful, stateless) = answer(secret).await;
+        verify_stateful(stateful).await.unwrap();
+        let status = verify_stateless(stateless).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::PermissionDenied);
+
+        // The resubmitted commitment must be the one the challenge was issued for, and the
+        // registered one.
+        let (_, stateless) = answer(secret).await;
+        let other = StatelessAnswerRequest {
+            r1: stateless.r2.clone(),
+            ..stateless.clone()
+        };
+        let status = verify_stateless(other).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+        let other = StatelessAnswerRequest {
+            y1: stateless.y2.clone(),
+            ..stateless
+        };
+        let status = verify_stateless(other).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::PermissionDenied);
+    }
+
+    #[tokio::test]
+    async fn test_interleaved_authentications_of_one_user() {
+        let auth = Ec25519Auth::new(EC25519_GROUP_PARAMS.to_owned());
+        let secret = Scalar::generate_random().unwrap();
+        register_secret(&auth, "alice", &secret).await;
+
+        // The second challenge is issued before the first is answered, and each answer verifies
+        // against the commitment its own challenge was issued for.
+        let first = answer_challenge(&auth, "alice", &secret).await;
+        let second = answer_challenge(&auth, "alice", &secret).await;
+        for (auth_id, s, nonce) in [first, second] {
+            assert!(auth
+                .verify_authentication(Request::new(AuthenticationAnswerRequest {
+                    auth_id,
+                    s,
+                    nonce,
+                    group: String::new()
+                }))
+                .await
+                .is_ok());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_namespaces_isolate_users() {
+        let dao = Arc::new(AsyncInMemoryUserDao::<RistrettoPoint, Scalar>::new());
+        let params = EC25519_GROUP_PARAMS.to_owned();
+        let tenant_a = Ec25519Auth::with_dao(params, dao.clone()).with_namespace("a");
+        let tenant_b = Ec25519Auth::with_dao(params, dao.clone()).with_namespace("b");
+        let secret_a = Scalar::generate_random().unwrap();
+        let secret_b = Scalar::generate_random().unwrap();
+        register_secret(&tenant_a, "alice", &secret_a).await;
+        register_secret(&tenant_b, "alice", &secret_b).await;
+        assert!(dao.read("a::alice").await.is_some());
+        assert!(dao.read("b::alice").await.is_some());
+
+        let (auth_id, s, nonce) = answer_challenge(&tenant_a, "alice", &secret_a).await;
+        assert!(tenant_a
+            .verify_authentication(Request::new(AuthenticationAnswerRequest {
+                auth_id,
+                s,
+                nonce,
+                group: String::new()
+            }))
+            .await
+            .is_ok());
+        let (auth_id, s, nonce) = answer_challenge(&tenant_b, "alice", &secret_a).await;
+        assert!(tenant_b
+            .verify_authentication(Request::new(AuthenticationAnswerRequest {
+                auth_id,
+                s,
+                nonce,
+                group: String::new()
+            }))
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_request_without_namespace_cannot_access_namespaced_users() {
+        let dao = Arc::new(AsyncInMemoryUserDao::<RistrettoPoint, Scalar>::new());
+        let params = EC25519_GROUP_PARAMS.to_owned();
+        let unscoped = Ec25519Auth::with_dao(params, dao.clone());
+        let tenant = Ec25519Auth::with_dao(params, dao.clone()).with_namespace("a");
+        register_secret(&tenant, "alice", &Scalar::generate_random().unwrap()).await;
+
+        let metadata_request = |user: &str, namespace: &str| {
+            Request::new(GetUserMetadataRequest {
+                user: user.to_string(),
+                namespace: namespace.to_string(),
+                group: String::new(),
+            })
+        };
+        let status = unscoped
+            .get_user_metadata(metadata_request("alice", ""))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::NotFound);
+        assert!(unscoped
+            .get_user_metadata(metadata_request("alice", "a"))
+            .await
+            .is_ok());
+
+        let status = tenant
+            .get_user_metadata(metadata_request("alice", "b"))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::PermissionDenied);
+        let status = unscoped
+            .get_user_metadata(metadata_request("a::alice", ""))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+    }
+
+    #[tokio::test]
+    async fn test_authentication_creates_session() {
+        let auth = Ec25519Auth::new(EC25519_GROUP_PARAMS.to_owned())
+            .with_session_store(Box::new(InMemorySessionStore::new()));
+        let secret = Scalar::generate_random().unwrap();
+        register_secret(&auth, "alice", &secret).await;
+
+        let (auth_id, s, nonce) = answer_challenge(&auth, "alice", &secret).await;
+        let session_id = auth
+            .verify_authentication(Request::new(AuthenticationAnswerRequest {
+                auth_id,
+                s,
+                nonce,
+                group: String::new(),
+            }))
+            .await
+            .unwrap()
+            .into_inner()
+            .session_id;
+        assert_eq!(auth.sessions().get_user(&session_id).as_deref(), Some("alice"));
+        assert!(auth.sessions().invalidate(&session_id));
+        assert!(!auth.sessions().is_valid(&session_id));
+    }
+
+    #[tokio::test]
+    async fn test_validate_session() {
+        let validate = |auth: &Ec25519Auth, token: &str| {
+            let auth = auth.clone();
+            let request = Request::new(ValidateSessionRequest {
+                token: token.to_string(),
+                group: String::new(),
+            });
+            async move {
+                auth.validate_session(request)
+                    .await
+                    .map(Response::into_inner)
+            }
+        };
+        let secret = Scalar::generate_random().unwrap();
+
+        // UUID sessions are looked up in the session store.
+        let auth = Ec25519Auth::new(EC25519_GROUP_PARAMS.to_owned());
+        register_secret(&auth, "alice", &secret).await;
+        let (auth_id, s, nonce) = answer_challenge(&auth, "alice", &secret).await;
+        let session_id = auth
+            .verify_authentication(Request::new(AuthenticationAnswerRequest {
+                auth_id,
+                s,
+                nonce,
+                group: String::new(),
+            }))
+            .await
+            .unwrap()
+            .into_inner()
+            .session_id;
+        let reply = validate(&auth, &session_id).await.unwrap();
+        assert_eq!((reply.user.as_str(), reply.expiry), ("alice", 0));
+        auth.sessions().invalidate(&session_id);
+        let status = validate(&auth, &session_id).await.unwrap_err();
+        assert_eq!(ZkPassError::from_status(&status), Some(ZkPassError::InvalidSession));
+
+        // PASETO sessions are validated from the token alone.
+        let auth = Ec25519Auth::new(EC25519_GROUP_PARAMS.to_owned())
+            .with_session_token_format(SessionTokenFormat::paseto(&[7; 32]).unwrap());
+        register_secret(&auth, "alice", &secret).await;
+        let (auth_id, s, nonce) = answer_challenge(&auth, "alice", &secret).await;
+        let token = auth
+            .verify_authentication(Request::new(AuthenticationAnswerRequest {
+                auth_id,
+                s,
+                nonce,
+                group: String::new(),
+            }))
+            .await
+            .unwrap()
+            .into_inner()
+            .session_id;
+        assert!(token.starts_with("v4.local."));
+        let reply = validate(&auth, &token).await.unwrap();
+        assert_eq!(reply.user, "alice");
+        assert!(reply.expiry > 0);
+
+        let mut tampered = token.into_bytes();
+        let last = tampered.len() - 10;
+        tampered[last] = if tampered[last] == b'A' { b'B' } else { b'A' };
+        let status = validate(&auth, &String::from_utf8(tampered).unwrap())
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::Unauthenticated);
+        assert_eq!(ZkPassError::from_status(&status), Some(ZkPassError::InvalidSession));
+    }
+
+    #[tokio::test]
+    async fn test_health() {
+        let auth = Ec25519Auth::new(EC25519_GROUP_PARAMS.to_owned()).with_protocol_type("ec25519");
+        let reply = auth
+            .health(Request::new(HealthRequest {
+                group: String::new(),
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(reply.status(), ServingStatus::Serving);
+        assert_eq!(reply.protocol_type, "ec25519");
+        assert_eq!(reply.params_version, auth.params_version());
+    }
+
+    #[tokio::test]
+    async fn test_challenge_generator() {
+        let auth = Ec25519Auth::new(EC25519_GROUP_PARAMS.to_owned())
+            .with_challenge_generator(|_| Scalar::from(7u64));
+        let secret = Scalar::generate_random().unwrap();
+        register_secret(&auth, "alice", &secret).await;
+
+        let reply = auth
+            .create_authentication_challenge(Request::new(AuthenticationChallengeRequest {
+                user: "alice".to_string(),
+                r1: RistrettoPoint::convert_to(&RistrettoPoint::generate_random().unwrap()),
+                r2: RistrettoPoint::convert_to(&RistrettoPoint::generate_random().unwrap()),
+                namespace: String::new(),
+                group: String::new(),
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(Scalar::convert_from(&reply.c).unwrap(), Scalar::from(7u64));
+
+        let (auth_id, s, nonce) = answer_challenge(&auth, "alice", &secret).await;
+        auth.verify_authentication(Request::new(AuthenticationAnswerRequest {
+            auth_id,
+            s,
+            nonce,
+            group: String::new(),
+        }))
+        .await
+        .unwrap();
+    }
+
+    /// A challenge source always supplying the same challenge.
+    struct FixedChallengeSource(Scalar);
+
+    #[tonic::async_trait]
+    impl ChallengeSource<RistrettoPoint, Scalar> for FixedChallengeSource {
+        async fn challenge(
+            &self, _params: &GroupParams<RistrettoPoint>,
+        ) -> Result<Scalar, ZkPassError> {
+            Ok(self.0)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_challenge_source() {
+        let auth = Ec25519Auth::new(EC25519_GROUP_PARAMS.to_owned())
+            .with_challenge_source(FixedChallengeSource(Scalar::from(42u64)));
+        let secret = Scalar::generate_random().unwrap();
+        register_secret(&auth, "alice", &secret).await;
+
+        for _ in 0..2 {
+            let reply = auth
+                .create_authentication_challenge(Request::new(AuthenticationChallengeRequest {
+                    user: "alice".to_string(),
+                    r1: RistrettoPoint::convert_to(&RistrettoPoint::generate_random().unwrap()),
+                    r2: RistrettoPoint::convert_to(&RistrettoPoint::generate_random().unwrap()),
+                    namespace: String::new(),
+                    group: String::new(),
+                }))
+                .await
+                .unwrap()
+                .into_inner();
+            assert_eq!(Scalar::convert_from(&reply.c).unwrap(), Scalar::from(42u64));
+        }
+
+        let (auth_id, s, nonce) = answer_challenge(&auth, "alice", &secret).await;
+        auth.verify_authentication(Request::new(AuthenticationAnswerRequest {
+            auth_id,
+            s,
+            nonce,
+            group: String::new(),
+        }))
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_batch_verify() {
+        let auth = Ec25519Auth::new(EC25519_GROUP_PARAMS.to_owned());
+        let mut answers = Vec::new();
+        for i in 0..100 {
+            let user = format!("user{}", i);
+            let secret = Scalar::generate_random().unwrap();
+            register_secret(&auth, &user, &secret).await;
+            let (auth_id, s, nonce) = answer_challenge(&auth, &user, &secret).await;
+            // Every odd answer is for a different secret than the registered one.
+            let s = match i % 2 {
+                0 => s,
+                _ => Scalar::convert_to(&Scalar::generate_random().unwrap()),
+            };
+            answers.push(AuthenticationAnswerRequest {
+                auth_id,
+                s,
+                nonce,
+                group: String::new(),
+            });
+        }
+        let auth_ids: Vec<String> = answers.iter().map(|a| a.auth_id.clone()).collect();
+
+        let results = auth
+            .batch_verify(Request::new(BatchVerifyRequest {
+                answers,
+                group: String::new(),
+            }))
+            .await
+            .unwrap()
+            .into_inner()
+            .results;
+        assert_eq!(results.len(), 100);
+        for (i, result) in results.iter().enumerate() {
+            assert_eq!(result.auth_id, auth_ids[i]);
+            if i % 2 == 0 {
+                assert!(!result.session_id.is_empty());
+                assert!(result.error.is_empty());
+            } else {
+                assert!(result.session_id.is_empty());
+                assert_eq!(result.error, "Invalid authentication");
+            }
+        }
+        assert_eq!(results.iter().filter(|r| r.error.is_empty()).count(), 50);
+    }
+
+    #[tokio::test]
+    async fn test_challenge_answered_once() {
+        let auth = Ec25519Auth::new(EC25519_GROUP_PARAMS.to_owned());
+        let secret = Scalar::generate_random().unwrap();
+        register_secret(&auth, "alice", &secret).await;
+
+        // The same answer twice in one batch starts a single session.
+        let (auth_id, s, nonce) = answer_challenge(&auth, "alice", &secret).await;
+        let answer = AuthenticationAnswerRequest {
+            auth_id,
+            s,
+            nonce,
+            group: String::new(),
+        };
+        let results = auth
+            .batch_verify(Request::new(BatchVerifyRequest {
+                answers: vec![answer.clone(), answer],
+                group: String::new(),
+            }))
+            .await
+            .unwrap()
+            .into_inner()
+            .results;
+        assert_eq!(results.iter().filter(|r| !r.session_id.is_empty()).count(), 1);
+        assert_eq!(results[1].error, "Duplicate auth_id");
+
+        // Concurrent answers race for the challenge, and only one of them is verified.
+        let (auth_id, s, nonce) = answer_challenge(&auth, "alice", &secret).await;
+        let answer = AuthenticationAnswerRequest {
+            auth_id,
+            s,
+            nonce,
+            group: String::new(),
+        };
+        let verify = || auth.verify_authentication(Request::new(answer.clone()));
+        let (first, second) = tokio::join!(verify(), verify());
+        assert_eq!(first.is_ok() as usize + second.is_ok() as usize, 1);
+        let status = first.and(second).unwrap_err();
+        assert_eq!(ZkPassError::from_status(&status), Some(ZkPassError::ChallengeNotFound));
+    }
+
+    #[tokio::test]
+    async fn test_commitment_reuse_guard() {
+        let auth = Ec25519Auth::new(EC25519_GROUP_PARAMS.to_owned())
+            .with_commitment_reuse_guard(Duration::from_secs(60));
+        let secret = Scalar::generate_random().unwrap();
+        register_secret(&auth, "alice", &secret).await;
+        register_secret(&auth, "bob", &secret).await;
+
+        let r1 = RistrettoPoint::convert_to(&RistrettoPoint::generate_random().unwrap());
+        let challenge = |user: &str, r1: Vec<u8>| {
+            auth.create_authentication_challenge(Request::new(AuthenticationChallengeRequest {
+                user: user.to_string(),
+                r1,
+                r2: RistrettoPoint::convert_to(&RistrettoPoint::generate_random().unwrap()),
+                namespace: String::new(),
+                group: String::new(),
+            }))
+        };
+        challenge("alice", r1.clone()).await.unwrap();
+
+        // The same r1 is rejected for the same user within the window.
+        let status = challenge("alice", r1.clone()).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::FailedPrecondition);
+        assert_eq!(ZkPassError::from_status(&status), Some(ZkPassError::CommitmentReused));
+
+        // Other users and fresh commitments are unaffected.
+        challenge("bob", r1).await.unwrap();
+        let (auth_id, s, nonce) = answer_challenge(&auth, "alice", &secret).await;
+        auth.verify_authentication(Request::new(AuthenticationAnswerRequest {
+            auth_id,
+            s,
+            nonce,
+            group: String::new(),
+        }))
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_drand_beacon_challenge() {
+        use crate::rand::tests::{drand_round, mock_endpoint};
+
+        let challenge = |auth: &Ec25519Auth| {
+            let auth = auth.clone();
+            let request = Request::new(AuthenticationChallengeRequest {
+                user: "alice".to_string(),
+                r1: RistrettoPoint::convert_to(&RistrettoPoint::generate_random().unwrap()),
+                r2: RistrettoPoint::convert_to(&RistrettoPoint::generate_random().unwrap()),
+                namespace: String::new(),
+                group: String::new(),
+            });
+            async move {
+                auth.create_authentication_challenge(request)
+                    .await
+                    .map(|reply| reply.into_inner().c)
+            }
+        };
+        let secret = Scalar::generate_random().unwrap();
+        let auth = Ec25519Auth::new(EC25519_GROUP_PARAMS.to_owned())
+            .with_drand_url(mock_endpoint(200, drand_round(&[7; 96])).await);
+        register_secret(&auth, "alice", &secret).await;
+
+        // Local randomness keeps challenges apart within one beacon round.
+        let c1 = challenge(&auth).await.unwrap();
+        let c2 = challenge(&auth).await.unwrap();
+        assert_ne!(c1, c2);
+        let (auth_id, s, nonce) = answer_challenge(&auth, "alice", &secret).await;
+        auth.verify_authentication(Request::new(AuthenticationAnswerRequest {
+            auth_id,
+            s,
+            nonce,
+            group: String::new(),
+        }))
+        .await
+        .unwrap();
+
+        // Challenges are refused while the beacon is unavailable.
+        let auth = Ec25519Auth::new(EC25519_GROUP_PARAMS.to_owned())
+            .with_drand_url(mock_endpoint(503, String::new()).await);
+        register_secret(&auth, "alice", &secret).await;
+        let status = challenge(&auth).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::Unavailable);
+        assert_eq!(ZkPassError::from_status(&status), Some(ZkPassError::BeaconUnavailable));
+    }
+
+    #[tokio::test]
+    async fn test_public_coin_challenge_depends_on_coin_alone() {
+        use crate::challenge::ExternalChallengeSource;
+        use crate::rand::tests::mock_endpoint;
+
+        let coin = |byte: u8| format!(r#"{{"randomness":"{}"}}"#, hex::encode([byte; 32]));
+        let params = EC25519_GROUP_PARAMS.to_owned();
+        let secret = Scalar::generate_random().unwrap();
+        // Servers with different resumption keys, so any nonce of their own would differ.
+        let effective_challenge = |byte: u8, key: [u8; 32]| async move {
+            let auth = Ec25519Auth::new(params)
+                .with_resumption(ResumeTokenSealer::new(key, Duration::from_secs(60)))
+                .with_challenge_source(ExternalChallengeSource::new(
+                    mock_endpoint(200, coin(byte)).await,
+                ));
+            register_secret(&auth, "alice", &secret).await;
+            let reply = auth
+                .create_authentication_challenge(Request::new(AuthenticationChallengeRequest {
+                    user: "alice".to_string(),
+                    r1: RistrettoPoint::convert_to(&RistrettoPoint::generate_random().unwrap()),
+                    r2: RistrettoPoint::convert_to(&RistrettoPoint::generate_random().unwrap()),
+                    namespace: String::new(),
+                    group: String::new(),
+                }))
+                .await
+                .unwrap()
+                .into_inner();
+            assert!(reply.nonce.is_empty());
+            let c = Scalar::convert_from(&reply.c).unwrap();
+            derive_nonce_challenge(&params, &reply.nonce, &c)
+        };
+        let c = effective_challenge(1, [1; 32]).await;
+        assert_eq!(effective_challenge(1, [2; 32]).await, c);
+        assert_ne!(effective_challenge(2, [1; 32]).await, c);
+
+        // Public coins are answered like any other challenge.
+        let auth = Ec25519Auth::new(params)
+            .with_challenge_source(ExternalChallengeSource::new(mock_endpoint(200, coin(1)).await));
+        register_secret(&auth, "alice", &secret).await;
+        let (auth_id, s, nonce) = answer_challenge(&auth, "alice", &secret).await;
+        auth.verify_authentication(Request::new(AuthenticationAnswerRequest {
+            auth_id,
+            s,
+            nonce,
+            group: String::new(),
+        }))
+        .await
+        .unwrap();
+
+        // The source being unreachable has its own error code.
+        let auth = Ec25519Auth::new(EC25519_GROUP_PARAMS.to_owned()).with_challenge_source(
+            ExternalChallengeSource::new(mock_endpoint(503, String::new()).await),
+        );
+        register_secret(&auth, "alice", &secret).await;
+        let status = auth
+            .create_authentication_challenge(Request::new(AuthenticationChallengeRequest {
+                user: "alice".to_string(),
+                r1: RistrettoPoint::convert_to(&RistrettoPoint::generate_random().unwrap()),
+                r2: RistrettoPoint::convert_to(&RistrettoPoint::generate_random().unwrap()),
+                namespace: String::new(),
+                group: String::new(),
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::Unavailable);
+        assert_eq!(
+            ZkPassError::from_status(&status),
+            Some(ZkPassError::ChallengeSourceUnavailable)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_clones_share_user_store() {
+        let auth = Ec25519Auth::new(EC25519_GROUP_PARAMS.to_owned());
+        let other = auth.clone();
+        let secret = Scalar::generate_random().unwrap();
+        register_secret(&auth, "alice", &secret).await;
+        register_secret(&other, "bob", &secret).await;
+        assert!(auth.dao.read("bob").await.is_some());
+        assert!(other.dao.read("alice").await.is_some());
+
+        // A challenge issued by one clone can be answered through the other.
+        let (auth_id, s, nonce) = answer_challenge(&auth, "alice", &secret).await;
+        let session_id = other
+            .verify_authentication(Request::new(AuthenticationAnswerRequest {
+                auth_id,
+                s,
+                nonce,
+                group: String::new(),
+            }))
+            .await
+            .unwrap()
+            .into_inner()
+            .session_id;
+        assert!(auth.sessions().is_valid(&session_id));
+    }
+
+    #[tokio::test]
+    async fn test_list_users_pagination() {
+        let auth = Ec25519Auth::new(EC25519_GROUP_PARAMS.to_owned());
+        for user in ["dave", "alice", "erin", "carol", "bob"] {
+            auth.register(Request::new(register_request(user)))
+                .await
+                .unwrap();
+        }
+
+        let mut pages = Vec::new();
+        let mut page_token = String::new();
+        loop {
+            let reply = auth
+                .list_users(Request::new(ListUsersRequest {
+                    page_token,
+                    page_size: 2,
+                }))
+                .await
+                .unwrap()
+                .into_inner();
+            pages.push(reply.usernames);
+            if reply.next_page_token.is_empty() {
+                break;
+            }
+            page_token = reply.next_page_token;
+        }
+        assert_eq!(pages, vec![vec!["alice", "bob"], vec!["carol", "dave"], vec!["erin"]]);
+    }
+
+    /// Builds Curve25519 group parameters with a fresh `h`, standing in for a stronger group.
+    fn rotated_params() -> GroupParams<RistrettoPoint> {
+        GroupParams::builder()
+            .g(EC25519_GROUP_PARAMS.g)
+            .h(RistrettoPoint::generate_random().unwrap())
+            .p(EC25519_GROUP_PARAMS.p)
+            .q(EC25519_GROUP_PARAMS.q)
+            .build()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_rotate_params_keeps_old_users_authenticating() {
+        let auth = Ec25519Auth::new(EC25519_GROUP_PARAMS.to_owned());
+        let alice = Scalar::generate_random().unwrap();
+        register_secret(&auth, "alice", &alice).await;
+
+        let new_params = rotated_params();
+        let reply = auth
+            .rotate_params(Request::new(RotateParamsRequest {
+                new_params_encoded: serde_json::to_vec(&new_params).unwrap(),
+                effective_after: 0,
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(reply.params_version, 1);
+        assert_eq!(auth.params(), new_params);
+
+        // Alice still authenticates with the parameters she registered with.
+        let (auth_id, s, nonce) = answer_challenge(&auth, "alice", &alice).await;
+        assert!(auth
+            .verify_authentication(Request::new(AuthenticationAnswerRequest {
+                auth_id,
+                s,
+                nonce,
+                group: String::new()
+            }))
+            .await
+            .is_ok());
+
+        // New registrations must use the new parameters.
+        let bob = Scalar::generate_random().unwrap();
+        let status = register_secret_with(&auth, &EC25519_GROUP_PARAMS, 0, "bob", &bob)
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::FailedPrecondition);
+        register_secret_with(&auth, &new_params, 1, "bob", &bob)
+            .await
+            .unwrap();
+        let (auth_id, s, nonce) = answer_challenge_with(&auth, &new_params, "bob", &bob).await;
+        assert!(auth
+            .verify_authentication(Request::new(AuthenticationAnswerRequest {
+                auth_id,
+                s,
+                nonce,
+                group: String::new()
+            }))
+            .await
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_old_params_removed_once_unused() {
+        let auth = Ec25519Auth::new(EC25519_GROUP_PARAMS.to_owned());
+        let secret = Scalar::generate_random().unwrap();
+        register_secret(&auth, "alice", &secret).await;
+        let new_params = rotated_params();
+        auth.rotate_group_params(new_params, 0).await;
+        assert!(auth.params_for(0).is_ok());
+
+        // Moving the last user to the new parameters retires the old ones.
+        let (auth_id, s, nonce) = answer_challenge(&auth, "alice", &secret).await;
+        let ((y1, y2, _, _), _) = Curve25519ChaumPedersen::commitment(&new_params, &secret);
+        auth.update_credentials(Request::new(UpdateCredentialsRequest {
+            auth_id,
+            s,
+            y1: RistrettoPoint::convert_to(&y1),
+            y2: RistrettoPoint::convert_to(&y2),
+            nonce,
+            params_version: 1,
+            group: String::new(),
+            old_y1: Vec::new(),
+            old_y2: Vec::new(),
+        }))
+        .await
+        .unwrap();
+        assert!(auth.params_for(0).is_err());
+
+        let (auth_id, s, nonce) = answer_challenge_with(&auth, &new_params, "alice", &secret).await;
+        assert!(auth
+            .verify_authentication(Request::new(AuthenticationAnswerRequest {
+                auth_id,
+                s,
+                nonce,
+                group: String::new()
+            }))
+            .await
+            .is_ok());
+    }
+
+    #[test]
+    fn test_old_params_accept_registrations_until_effective_after() {
+        let mut params_versions = ParamsVersions::new(EC25519_GROUP_PARAMS.to_owned());
+        params_versions.versions.insert(1, rotated_params());
+        params_versions.effective_after = 100;
+        assert!(params_versions.accepts_registrations(0, 100));
+        assert!(!params_versions.accepts_registrations(0, 101));
+        assert!(params_versions.accepts_registrations(1, 101));
+        assert!(!params_versions.accepts_registrations(2, 0));
+    }
+
+    #[tokio::test]
+    async fn test_rotate_params_rejects_invalid_params() {
+        let auth = Ec25519Auth::new(EC25519_GROUP_PARAMS.to_owned());
+        let mut params = rotated_params();
+        params.h = params.g;
+        let status = auth
+            .rotate_params(Request::new(RotateParamsRequest {
+                new_params_encoded: serde_json::to_vec(&params).unwrap(),
+                effective_after: 0,
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+        assert_eq!(auth.params_version(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_rotate_params_rejects_composite_order() {
+        let params = RFC5114_MODP_2048_256_BIT_PARAMS.to_owned();
+        let auth = ZkAuth::<DiscreteLogChaumPedersen, BigUint, BigUint>::new(params.clone());
+        // 2q still divides p - 1 and annihilates both generators, so only the primality check
+        // rejects it.
+        let mut composite = params.clone();
+        composite.q = &params.q * 2u32;
+        let status = auth
+            .rotate_params(Request::new(RotateParamsRequest {
+                new_params_encoded: serde_json::to_vec(&composite).unwrap(),
+                effective_after: 0,
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+        assert!(status.message().contains("order q is not prime"));
+        assert_eq!(auth.params_version(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_register_rejects_element_outside_subgroup() {
+        let params = RFC5114_MODP_2048_256_BIT_PARAMS.to_owned();
+        let auth = ZkAuth::<DiscreteLogChaumPedersen, BigUint, BigUint>::new(params.clone());
+        let status = auth
+            .register(Request::new(RegisterRequest {
+                user: "mallory".to_string(),
+                y1: BigUint::convert_to(&(&params.p - 1u32)),
+                y2: BigUint::convert_to(&params.h),
+                metadata: vec![],
+                namespace: String::new(),
+                params_version: 0,
+                group: String::new(),
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+        assert_eq!(status.message(), "Invalid y1");
+        assert!(auth.dao.read("mallory").await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_challenge_rejects_identity_commitment() {
+        let auth = Ec25519Auth::new(EC25519_GROUP_PARAMS.to_owned());
+        register_secret(&auth, "alice", &Scalar::generate_random().unwrap()).await;
+        let status = auth
+            .create_authentication_challenge(Request::new(AuthenticationChallengeRequest {
+                user: "alice".to_string(),
+                r1: RistrettoPoint::convert_to(&RistrettoPoint::generate_random().unwrap()),
+                // The default Ristretto point is the identity.
+                r2: RistrettoPoint::convert_to(&RistrettoPoint::default()),
+                namespace: String::new(),
+                group: String::new(),
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+        assert_eq!(status.message(), "Invalid r2");
+    }
+
+    #[tokio::test]
+    async fn test_revoked_user_cannot_authenticate() {
+        let auth = Ec25519Auth::new(EC25519_GROUP_PARAMS.to_owned());
+        let secret = Scalar::generate_random().unwrap();
+        register_secret(&auth, "alice", &secret).await;
+
+        auth.revoke_user(Request::new(RevokeUserRequest {
+            user: "alice".to_string(),
+            namespace: String::new(),
+        }))
+        .await
+        .unwrap();
+        assert!(auth.revocations().is_revoked("alice"));
+        let ((_, _, r1, r2), _) =
+            Curve25519ChaumPedersen::commitment(&EC25519_GROUP_PARAMS, &secret);
+        let status = auth
+            .create_authentication_challenge(Request::new(AuthenticationChallengeRequest {
+                user: "alice".to_string(),
+                r1: RistrettoPoint::convert_to(&r1),
+                r2: RistrettoPoint::convert_to(&r2),
+                namespace: String::new(),
+                group: String::new(),
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::PermissionDenied);
+
+        // Unrevoking restores access with the original registration.
+        auth.unrevoke_user(Request::new(UnrevokeUserRequest {
+            user: "alice".to_string(),
+            namespace: String::new(),
+        }))
+        .await
+        .unwrap();
+        let (auth_id, s, nonce) = answer_challenge(&auth, "alice", &secret).await;
+        auth.verify_authentication(Request::new(AuthenticationAnswerRequest {
+            auth_id,
+            s,
+            nonce,
+            group: String::new(),
+        }))
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_user_revoked_after_challenge() {
+        let auth = Ec25519Auth::new(EC25519_GROUP_PARAMS.to_owned());
+        let secret = Scalar::generate_random().unwrap();
+        register_secret(&auth, "alice", &secret).await;
+
+        // A challenge issued before the revocation cannot be answered after it.
+        let (auth_id, s, nonce) = answer_challenge(&auth, "alice", &secret).await;
+        auth.revoke_user(Request::new(RevokeUserRequest {
+            user: "alice".to_string(),
+            namespace: String::new(),
+        }))
+        .await
+        .unwrap();
+        let status = auth
+            .verify_authentication(Request::new(AuthenticationAnswerRequest {
+                auth_id,
+                s,
+                nonce,
+                group: String::new(),
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::PermissionDenied);
+        assert_eq!(status.message(), "User credentials are revoked");
+    }
+
+    #[tokio::test]
+    async fn test_revoke_unknown_user() {
+        let auth = Ec25519Auth::new(EC25519_GROUP_PARAMS.to_owned());
+        let status = auth
+            .revoke_user(Request::new(RevokeUserRequest {
+                user: "nobody".to_string(),
+                namespace: String::new(),
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::NotFound);
+    }
+
+    #[test]
+    fn test_admin_token_interceptor() {
+        let mut interceptor = admin_token_interceptor("secret".to_string());
+        let status = interceptor.call(Request::new(())).unwrap_err();
+        assert_eq!(status.code(), tonic::Code::Unauthenticated);
+
+        let mut request = Request::new(());
+        request
+            .metadata_mut()
+            .insert(ADMIN_TOKEN_HEADER, "wrong".parse().unwrap());
+        let status = interceptor.call(request).unwrap_err();
+        assert_eq!(status.code(), tonic::Code::Unauthenticated);
+
+        let mut request = Request::new(());
+        request
+            .metadata_mut()
+            .insert(ADMIN_TOKEN_HEADER, "secret".parse().unwrap());
+        assert!(interceptor.call(request).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_challenge_config_shortens_effective_challenges() {
+        let params = RFC5114_MODP_2048_256_BIT_PARAMS.to_owned();
+        let short = DiscreteLogChaumPedersenConfig {
+            challenge_bits: 128,
+        };
+        let too_short = DiscreteLogChaumPedersenConfig { challenge_bits: 80 };
+        assert_eq!(
+            ZkAuth::<DiscreteLogChaumPedersen, BigUint, BigUint>::new(params.clone())
+                .with_challenge_config(too_short)
+                .err(),
+            Some(InvalidChallengeBits::TooShort)
+        );
+        let auth = ZkAuth::<DiscreteLogChaumPedersen, BigUint, BigUint>::new(params.clone())
+            .with_challenge_config(short)
+            .unwrap();
+        let x = BigUint::generate_random().unwrap();
+        let ((y1, y2, _, _), _) = DiscreteLogChaumPedersen::commitment(&params, &x);
+        auth.register(Request::new(RegisterRequest {
+            user: "alice".to_string(),
+            y1: BigUint::convert_to(&y1),
+            y2: BigUint::convert_to(&y2),
+            metadata: vec![],
+            namespace: String::new(),
+            params_version: 0,
+            group: String::new(),
+        }))
+        .await
+        .unwrap();
+
+        // Answers to the shortened effective challenge verify, answers to the full one do not.
+        for shorten in [true, false] {
+            let ((_, _, r1, r2), k) = DiscreteLogChaumPedersen::commitment(&params, &x);
+            let reply = auth
+                .create_authentication_challenge(Request::new(AuthenticationChallengeRequest {
+                    user: "alice".to_string(),
+                    r1: BigUint::convert_to(&r1),
+                    r2: BigUint::convert_to(&r2),
+                    namespace: String::new(),
+                    group: String::new(),
+                }))
+                .await
+                .unwrap()
+                .into_inner();
+            assert_eq!(reply.challenge_bits, 128);
+            let c = BigUint::convert_from(&reply.c).unwrap();
+            let full: BigUint = derive_nonce_challenge(&params, &reply.nonce, &c);
+            let effective = if shorten {
+                &full % (BigUint::from(1u8) << 128)
+            } else {
+                full
+            };
+            let s = DiscreteLogChaumPedersen::challenge_response(&params, &k, &effective, &x);
+            let result = auth
+                .verify_authentication(Request::new(AuthenticationAnswerRequest {
+                    auth_id: reply.auth_id,
+                    s: BigUint::convert_to(&s),
+                    nonce: reply.nonce,
+                    group: String::new(),
+                }))
+                .await;
+            assert_eq!(result.is_ok(), shorten);
+        }
     }
 }
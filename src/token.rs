@@ -0,0 +1,314 @@
+//! # Session Token Module
+//!
+//! This module issues and validates self-contained session tokens, which services can hand to
+//! the ZKPass server's `ValidateSession` method instead of sharing its session store.
+//!
+//! A token carries the [`SessionClaims`] of a session, either as a JWT signed with the server's
+//! Ed25519 key or as a PASETO v4.local token encrypted with a symmetric key.
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{DateTime, SecondsFormat};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier};
+use pasetors::claims::{Claims, ClaimsValidationRules};
+use pasetors::errors::{ClaimValidationError, Error as PasetoError};
+use pasetors::keys::SymmetricKey;
+use pasetors::token::UntrustedToken;
+use pasetors::version4::V4;
+use pasetors::{local, Local};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// The header of every JWT issued by the server, as JSON encoded in the token.
+const JWT_HEADER: &str = r#"{"alg":"EdDSA","typ":"JWT"}"#;
+
+/// The claims describing a session.
+///
+/// # Fields
+/// - `sub`: The key of the authenticated user.
+/// - `exp`: The Unix time in seconds after which the session is no longer valid.
+/// - `iat`: The Unix time in seconds at which the session started.
+/// - `jti`: The ID of the session.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionClaims {
+    pub sub: String,
+    pub exp: u64,
+    pub iat: u64,
+    pub jti: String,
+}
+
+/// The reasons a session token is rejected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenError {
+    /// The token is not a well-formed token of the expected type.
+    Malformed,
+    /// The token's signature or, for PASETO, its authentication tag does not verify.
+    InvalidSignature,
+    /// The session has expired.
+    Expired,
+}
+
+impl fmt::Display for TokenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TokenError::Malformed => write!(f, "Malformed session token"),
+            TokenError::InvalidSignature => write!(f, "Session token failed verification"),
+            TokenError::Expired => write!(f, "Session token expired"),
+        }
+    }
+}
+
+impl std::error::Error for TokenError {}
+
+/// The form of the session IDs the server issues.
+#[derive(Clone, Default)]
+pub enum SessionTokenFormat {
+    /// Random UUIDs, which only the server's session store can resolve.
+    #[default]
+    Uuid,
+    /// JWTs signed with an Ed25519 key (`"alg": "EdDSA"`).
+    Jwt(SigningKey),
+    /// PASETO v4.local tokens, encrypted and authenticated with a 32-byte key.
+    Paseto(SymmetricKey<V4>),
+}
+
+impl SessionTokenFormat {
+    /// Creates a PASETO format from a 32-byte key.
+    ///
+    /// # Arguments
+    /// * `key` - The symmetric key.
+    ///
+    /// # Returns
+    /// The format, or `None` if the key is not 32 bytes long.
+    pub fn paseto(key: &[u8]) -> Option<Self> {
+        SymmetricKey::<V4>::from(key)
+            .ok()
+            .map(SessionTokenFormat::Paseto)
+    }
+
+    /// Issues a token carrying the given claims.
+    ///
+    /// # Arguments
+    /// * `claims` - The claims of the session.
+    ///
+    /// # Returns
+    /// The token; for [`SessionTokenFormat::Uuid`], the session ID `jti` itself.
+    pub fn issue(&self, claims: &SessionClaims) -> Result<String, TokenError> {
+        match self {
+            SessionTokenFormat::Uuid => Ok(claims.jti.clone()),
+            SessionTokenFormat::Jwt(key) => {
+                let payload = serde_json::to_vec(claims).map_err(|_| TokenError::Malformed)?;
+                let signing_input = format!(
+                    "{}.{}",
+                    URL_SAFE_NO_PAD.encode(JWT_HEADER),
+                    URL_SAFE_NO_PAD.encode(payload)
+                );
+                let signature = key.sign(signing_input.as_bytes());
+                Ok(format!("{}.{}", signing_input, URL_SAFE_NO_PAD.encode(signature.to_bytes())))
+            }
+            SessionTokenFormat::Paseto(key) => {
+                let mut paseto_claims = Claims::new().map_err(|_| TokenError::Malformed)?;
+                // Only the session's own claims are carried; `nbf` would repeat `iat`.
+                paseto_claims.remove_claim("nbf");
+                paseto_claims
+                    .subject(&claims.sub)
+                    .map_err(|_| TokenError::Malformed)?;
+                paseto_claims
+                    .token_identifier(&claims.jti)
+                    .map_err(|_| TokenError::Malformed)?;
+                paseto_claims
+                    .issued_at(&to_rfc3339(claims.iat)?)
+                    .map_err(|_| TokenError::Malformed)?;
+                paseto_claims
+                    .expiration(&to_rfc3339(claims.exp)?)
+                    .map_err(|_| TokenError::Malformed)?;
+                local::encrypt(key, &paseto_claims, None, None).map_err(|_| TokenError::Malformed)
+            }
+        }
+    }
+
+    /// Validates a token and extracts its claims.
+    ///
+    /// # Arguments
+    /// * `token` - The token, as issued by [`SessionTokenFormat::issue`].
+    ///
+    /// # Returns
+    /// The claims of the session, or the reason the token is rejected. UUIDs carry no claims, so
+    /// they are always rejected as [`TokenError::Malformed`]; look them up in the session store
+    /// instead.
+    pub fn validate(&self, token: &str) -> Result<SessionClaims, TokenError> {
+        let claims = match self {
+            SessionTokenFormat::Uuid => return Err(TokenError::Malformed),
+            SessionTokenFormat::Jwt(key) => {
+                let (signing_input, signature) =
+                    token.rsplit_once('.').ok_or(TokenError::Malformed)?;
+                let (header, payload) =
+                    signing_input.split_once('.').ok_or(TokenError::Malformed)?;
+                if decode_part(header)? != JWT_HEADER.as_bytes() {
+                    return Err(TokenError::Malformed);
+                }
+                let signature = Signature::from_slice(&decode_part(signature)?)
+                    .map_err(|_| TokenError::Malformed)?;
+                key.verifying_key()
+                    .verify(signing_input.as_bytes(), &signature)
+                    .map_err(|_| TokenError::InvalidSignature)?;
+                serde_json::from_slice(&decode_part(payload)?).map_err(|_| TokenError::Malformed)?
+            }
+            SessionTokenFormat::Paseto(key) => {
+                let untrusted = UntrustedToken::<Local, V4>::try_from(token)
+                    .map_err(|_| TokenError::Malformed)?;
+                let mut rules = ClaimsValidationRules::new();
+                rules.disable_valid_at();
+                let trusted =
+                    local::decrypt(key, &untrusted, &rules, None, None).map_err(|e| match e {
+                        PasetoError::ClaimValidation(ClaimValidationError::Exp) => {
+                            TokenError::Expired
+                        }
+                        PasetoError::TokenValidation => TokenError::InvalidSignature,
+                        _ => TokenError::Malformed,
+                    })?;
+                let paseto_claims = trusted.payload_claims().ok_or(TokenError::Malformed)?;
+                let claim = |name: &str| {
+                    paseto_claims
+                        .get_claim(name)
+                        .and_then(|value| value.as_str())
+                        .ok_or(TokenError::Malformed)
+                };
+                SessionClaims {
+                    sub: claim("sub")?.to_string(),
+                    exp: from_rfc3339(claim("exp")?)?,
+                    iat: from_rfc3339(claim("iat")?)?,
+                    jti: claim("jti")?.to_string(),
+                }
+            }
+        };
+        if claims.exp <= unix_time() {
+            return Err(TokenError::Expired);
+        }
+        Ok(claims)
+    }
+}
+
+impl fmt::Debug for SessionTokenFormat {
+    // Keeps the keys out of logs.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SessionTokenFormat::Uuid => write!(f, "Uuid"),
+            SessionTokenFormat::Jwt(_) => write!(f, "Jwt"),
+            SessionTokenFormat::Paseto(_) => write!(f, "Paseto"),
+        }
+    }
+}
+
+/// Decodes a base64url-encoded part of a JWT.
+fn decode_part(part: &str) -> Result<Vec<u8>, TokenError> {
+    URL_SAFE_NO_PAD
+        .decode(part)
+        .map_err(|_| TokenError::Malformed)
+}
+
+/// Formats a Unix time in seconds as an RFC 3339 date, as PASETO claims require.
+fn to_rfc3339(secs: u64) -> Result<String, TokenError> {
+    let secs = i64::try_from(secs).map_err(|_| TokenError::Malformed)?;
+    DateTime::from_timestamp(secs, 0)
+        .map(|time| time.to_rfc3339_opts(SecondsFormat::Secs, true))
+        .ok_or(TokenError::Malformed)
+}
+
+/// Parses an RFC 3339 date into a Unix time in seconds.
+fn from_rfc3339(date: &str) -> Result<u64, TokenError> {
+    let time = DateTime::parse_from_rfc3339(date).map_err(|_| TokenError::Malformed)?;
+    u64::try_from(time.timestamp()).map_err(|_| TokenError::Malformed)
+}
+
+/// Returns the current Unix time in seconds.
+fn unix_time() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PASETO_KEY: [u8; 32] = [7; 32];
+
+    fn claims(iat: u64, ttl: u64) -> SessionClaims {
+        SessionClaims {
+            sub: "alice".to_string(),
+            exp: iat + ttl,
+            iat,
+            jti: "8a1f6f4e-5d0c-4c3b-9b8e-2f1a6d7c9e10".to_string(),
+        }
+    }
+
+    /// Flips a bit of the character at the middle of the token's last part.
+    fn flip_byte(token: &str) -> String {
+        let mut bytes = token.as_bytes().to_vec();
+        let start = token.rfind('.').unwrap() + 1;
+        let i = start + (bytes.len() - start) / 2;
+        // 'A' and 'B' are both base64url characters, so the token still decodes.
+        bytes[i] = if bytes[i] == b'A' { b'B' } else { b'A' };
+        String::from_utf8(bytes).unwrap()
+    }
+
+    #[test]
+    fn test_paseto_token_decrypts_to_its_claims() {
+        let format = SessionTokenFormat::paseto(&PASETO_KEY).unwrap();
+        let claims = claims(unix_time(), 3600);
+        let token = format.issue(&claims).unwrap();
+        assert!(token.starts_with("v4.local."));
+        assert_eq!(format.validate(&token), Ok(claims));
+    }
+
+    #[test]
+    fn test_paseto_expired_token_rejected() {
+        let format = SessionTokenFormat::paseto(&PASETO_KEY).unwrap();
+        let token = format.issue(&claims(unix_time() - 7200, 3600)).unwrap();
+        assert_eq!(format.validate(&token), Err(TokenError::Expired));
+    }
+
+    #[test]
+    fn test_paseto_tampered_token_rejected() {
+        let format = SessionTokenFormat::paseto(&PASETO_KEY).unwrap();
+        let token = format.issue(&claims(unix_time(), 3600)).unwrap();
+        assert_eq!(format.validate(&flip_byte(&token)), Err(TokenError::InvalidSignature));
+
+        // A token encrypted under another key does not decrypt either.
+        let other = SessionTokenFormat::paseto(&[8; 32]).unwrap();
+        assert_eq!(other.validate(&token), Err(TokenError::InvalidSignature));
+        assert_eq!(format.validate("v4.local.not-a-token"), Err(TokenError::Malformed));
+    }
+
+    #[test]
+    fn test_paseto_rejects_short_key() {
+        assert!(SessionTokenFormat::paseto(&[7; 16]).is_none());
+    }
+
+    #[test]
+    fn test_jwt_token_round_trip_and_rejections() {
+        let format = SessionTokenFormat::Jwt(SigningKey::from_bytes(&[9; 32]));
+        let claims = claims(unix_time(), 3600);
+        let token = format.issue(&claims).unwrap();
+        assert_eq!(token.split('.').count(), 3);
+        assert_eq!(format.validate(&token), Ok(claims.clone()));
+
+        assert_eq!(format.validate(&flip_byte(&token)), Err(TokenError::InvalidSignature));
+        let expired = format
+            .issue(&self::claims(unix_time() - 7200, 3600))
+            .unwrap();
+        assert_eq!(format.validate(&expired), Err(TokenError::Expired));
+        let other = SessionTokenFormat::Jwt(SigningKey::from_bytes(&[10; 32]));
+        assert_eq!(other.validate(&token), Err(TokenError::InvalidSignature));
+    }
+
+    #[test]
+    fn test_uuid_format_issues_the_session_id() {
+        let claims = claims(unix_time(), 3600);
+        let format = SessionTokenFormat::Uuid;
+        assert_eq!(format.issue(&claims).unwrap(), claims.jti);
+        assert_eq!(format.validate(&claims.jti), Err(TokenError::Malformed));
+    }
+}
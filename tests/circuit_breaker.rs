@@ -0,0 +1,153 @@
+use curve25519_dalek::{RistrettoPoint, Scalar};
+use std::future::Future;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tonic::body::BoxBody;
+use tonic::codegen::http;
+use tonic::transport::Server;
+use tonic::Status;
+use tower::{Layer, Service};
+use zk_pass::chaum_pedersen::constants::EC25519_GROUP_PARAMS;
+use zk_pass::chaum_pedersen::curve25519::Curve25519ChaumPedersen;
+use zk_pass::client::{CircuitBreaker, CircuitState, ZkPassError};
+use zk_pass::conversion::ByteConvertible;
+use zk_pass::rand::RandomGenerator;
+use zk_pass::service::zkp_auth::auth_server::AuthServer;
+use zk_pass::service::ZkAuth;
+
+mod common;
+
+/// The gRPC path of the metadata lookups the [`FlakyLayer`] fails.
+const GET_USER_METADATA_PATH: &str = "/zkp_auth.Auth/GetUserMetadata";
+
+/// A tower layer failing the first `failures` metadata lookups as if the server were
+/// unavailable, passing every other request on to the wrapped service.
+#[derive(Clone)]
+struct FlakyLayer {
+    failures: usize,
+    lookups: Arc<AtomicUsize>,
+}
+
+impl<S> Layer<S> for FlakyLayer {
+    type Service = Flaky<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        Flaky {
+            inner,
+            layer: self.clone(),
+        }
+    }
+}
+
+/// The service wrapped by a [`FlakyLayer`].
+#[derive(Clone)]
+struct Flaky<S> {
+    inner: S,
+    layer: FlakyLayer,
+}
+
+impl<S, B> Service<http::Request<B>> for Flaky<S>
+where
+    S: Service<http::Request<B>, Response = http::Response<BoxBody>>,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: http::Request<B>) -> Self::Future {
+        if request.uri().path() == GET_USER_METADATA_PATH
+            && self.layer.lookups.fetch_add(1, Ordering::SeqCst) < self.layer.failures
+        {
+            return Box::pin(async { Ok(Status::unavailable("Server is down").to_http()) });
+        }
+        Box::pin(self.inner.call(request))
+    }
+}
+
+/// Starts an in-process flaky Curve25519 server on a free local port.
+async fn start_server(failures: usize, lookups: Arc<AtomicUsize>) -> SocketAddr {
+    let auth = ZkAuth::<Curve25519ChaumPedersen, RistrettoPoint, Scalar>::new(
+        EC25519_GROUP_PARAMS.to_owned(),
+    );
+    let (addr, incoming) = common::listen().await;
+    tokio::spawn(
+        Server::builder()
+            .layer(FlakyLayer { failures, lookups })
+            .add_service(AuthServer::new(auth))
+            .serve_with_incoming(incoming),
+    );
+    addr
+}
+
+#[tokio::test]
+async fn test_circuit_breaker_opens_and_resets() {
+    let lookups = Arc::new(AtomicUsize::new(0));
+    let addr = start_server(5, lookups.clone()).await;
+    let mut client = common::connect(addr)
+        .await
+        .with_circuit_breaker(5, Duration::from_millis(200));
+
+    let params = EC25519_GROUP_PARAMS.to_owned();
+    let x = Scalar::generate_random().unwrap();
+    client
+        .register(
+            "alice".to_string(),
+            RistrettoPoint::convert_to(&(params.g * x)),
+            RistrettoPoint::convert_to(&(params.h * x)),
+        )
+        .await
+        .unwrap();
+
+    // Each failure reaches the server until the threshold is hit.
+    for _ in 0..5 {
+        let status = client
+            .get_user_metadata("alice".to_string())
+            .await
+            .unwrap_err();
+        assert_eq!(status.message(), "Server is down");
+    }
+    let breaker = client.circuit_breaker().unwrap().clone();
+    assert!(matches!(breaker.state(), CircuitState::Open(_)));
+
+    // While open, calls fail fast without contacting the server.
+    let status = client
+        .get_user_metadata("alice".to_string())
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), tonic::Code::Unavailable);
+    assert_eq!(ZkPassError::from_status(&status), Some(ZkPassError::CircuitOpen));
+    assert_eq!(lookups.load(Ordering::SeqCst), 5);
+
+    // After the reset timeout a probe goes through and closes the breaker.
+    tokio::time::sleep(Duration::from_millis(250)).await;
+    client.get_user_metadata("alice".to_string()).await.unwrap();
+    assert_eq!(lookups.load(Ordering::SeqCst), 6);
+    assert_eq!(breaker.state(), CircuitState::Closed);
+}
+
+#[test]
+fn test_dropped_probe_reopens_breaker() {
+    let breaker = CircuitBreaker::new(1, Duration::ZERO);
+    let permit = breaker.acquire().unwrap();
+    let _ = breaker.record::<()>(permit, Err(Status::unavailable("Server is down")));
+    assert!(matches!(breaker.state(), CircuitState::Open(_)));
+
+    // A probe cancelled before its outcome is recorded does not leave the breaker half-open.
+    let probe = breaker.acquire().unwrap();
+    assert_eq!(breaker.state(), CircuitState::HalfOpen);
+    drop(probe);
+    assert!(matches!(breaker.state(), CircuitState::Open(_)));
+
+    let probe = breaker.acquire().unwrap();
+    breaker.record(probe, Ok(())).unwrap();
+    assert_eq!(breaker.state(), CircuitState::Closed);
+}
@@ -0,0 +1,237 @@
+//! Helpers shared by the integration tests and benchmarks.
+#![allow(dead_code)]
+
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::ops::{Deref, DerefMut};
+use std::process::{Child, Command};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tokio::sync::{watch, Notify};
+use tokio_stream::wrappers::TcpListenerStream;
+use tonic::transport::Server;
+use zk_pass::client::{AuthClientLib, ZkPassError};
+use zk_pass::repository::dao::AsyncUserDao;
+use zk_pass::repository::daoimpl::AsyncInMemoryUserDao;
+use zk_pass::repository::models::{AuthChallenge, User};
+use zk_pass::service::zkp_auth::auth_server::{Auth, AuthServer};
+
+/// How many times [`connect`] retries before giving up.
+const CONNECT_ATTEMPTS: usize = 100;
+
+/// Binds a listener on a free local port.
+///
+/// # Returns
+/// The bound address and the incoming connections, to pass to `serve_with_incoming`. The
+/// listener stays bound, so the port cannot be taken before the server starts accepting.
+pub async fn listen() -> (SocketAddr, TcpListenerStream) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    (addr, TcpListenerStream::new(listener))
+}
+
+/// Serves `auth` in-process on a free local port.
+pub async fn start_server<A: Auth>(auth: A) -> SocketAddr {
+    let (addr, incoming) = listen().await;
+    tokio::spawn(
+        Server::builder()
+            .add_service(AuthServer::new(auth))
+            .serve_with_incoming(incoming),
+    );
+    addr
+}
+
+/// Connects to the server at `addr`, retrying while it starts up.
+///
+/// # Panics
+/// If the server does not accept a connection within five seconds.
+pub async fn connect(addr: SocketAddr) -> AuthClientLib {
+    for _ in 0..CONNECT_ATTEMPTS {
+        if let Ok(client) = AuthClientLib::connect(format!("http://{}", addr)).await {
+            return client;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    panic!("server at {} did not accept connections", addr);
+}
+
+/// Returns a local port that was free when this was called, for a server binary to bind.
+pub fn free_port() -> u16 {
+    std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port()
+}
+
+/// A spawned server binary, killed when dropped so a failing test does not leak it.
+pub struct ServerProcess(Child);
+
+impl ServerProcess {
+    /// Spawns the server binary with the given arguments.
+    pub fn spawn(command: &mut Command) -> Self {
+        Self(command.spawn().expect("Failed to start server"))
+    }
+}
+
+impl Deref for ServerProcess {
+    type Target = Child;
+
+    fn deref(&self) -> &Child {
+        &self.0
+    }
+}
+
+impl DerefMut for ServerProcess {
+    fn deref_mut(&mut self) -> &mut Child {
+        &mut self.0
+    }
+}
+
+impl Drop for ServerProcess {
+    fn drop(&mut self) {
+        let _ = self.0.kill();
+        let _ = self.0.wait();
+    }
+}
+
+/// A gate holding the lookups of pending challenges in a [`GatedDao`], so a test can act while
+/// an authentication is in flight.
+#[derive(Clone)]
+pub struct Gate(Arc<GateState>);
+
+struct GateState {
+    entered: Notify,
+    open: watch::Sender<bool>,
+    cancelled: Notify,
+}
+
+impl Default for Gate {
+    fn default() -> Self {
+        Self(Arc::new(GateState {
+            entered: Notify::new(),
+            open: watch::Sender::new(false),
+            cancelled: Notify::new(),
+        }))
+    }
+}
+
+impl Gate {
+    /// Waits until a lookup reaches the gate.
+    pub async fn entered(&self) {
+        self.0.entered.notified().await
+    }
+
+    /// Lets the held lookups, and every later one, through.
+    pub fn open(&self) {
+        self.0.open.send_replace(true);
+    }
+
+    /// Waits until a lookup held at the gate is dropped, i.e. its request was cancelled.
+    pub async fn cancelled(&self) {
+        self.0.cancelled.notified().await
+    }
+
+    /// Holds a lookup until the gate opens.
+    async fn pass(&self) {
+        /// Reports the lookup as cancelled unless it passed the gate.
+        struct Held<'a>(Option<&'a GateState>);
+
+        impl Drop for Held<'_> {
+            fn drop(&mut self) {
+                if let Some(state) = self.0 {
+                    state.cancelled.notify_one();
+                }
+            }
+        }
+
+        let mut held = Held(Some(&self.0));
+        self.0.entered.notify_one();
+        let _ = self.0.open.subscribe().wait_for(|open| *open).await;
+        held.0 = None;
+    }
+}
+
+/// Wraps the in-memory store, holding the answers' lookups of pending challenges at a [`Gate`].
+pub struct GatedDao<T, S> {
+    inner: AsyncInMemoryUserDao<T, S>,
+    gate: Gate,
+}
+
+impl<T, S> GatedDao<T, S> {
+    /// Creates an empty store whose challenge lookups are held at `gate`.
+    pub fn new(gate: Gate) -> Self {
+        Self {
+            inner: AsyncInMemoryUserDao::default(),
+            gate,
+        }
+    }
+}
+
+#[async_trait]
+impl<T, S> AsyncUserDao<T, S> for GatedDao<T, S>
+where
+    T: Send + Sync + 'static + Clone,
+    S: Send + Sync + 'static + Clone,
+{
+    async fn create(&self, user: User<T>) -> Option<()> {
+        self.inner.create(user).await
+    }
+
+    async fn force_create(&self, user: User<T>) -> bool {
+        self.inner.force_create(user).await
+    }
+
+    async fn read(&self, username: &str) -> Option<User<T>> {
+        self.inner.read(username).await
+    }
+
+    async fn get_user_metadata(
+        &self, username: &str,
+    ) -> Result<HashMap<String, String>, ZkPassError> {
+        self.inner.get_user_metadata(username).await
+    }
+
+    async fn update(&self, name: &str, user: User<T>) -> Option<()> {
+        self.inner.update(name, user).await
+    }
+
+    async fn update_credentials(
+        &self, name: &str, y1: T, y2: T, params_version: u32,
+    ) -> Option<()> {
+        self.inner
+            .update_credentials(name, y1, y2, params_version)
+            .await
+    }
+
+    async fn delete(&self, name: &str) -> Option<User<T>> {
+        self.inner.delete(name).await
+    }
+
+    async fn list(&self, after: Option<&str>, limit: usize) -> Vec<String> {
+        self.inner.list(after, limit).await
+    }
+
+    async fn create_auth_challenge(
+        &self, user: &str, r1: &T, r2: &T, c: &S, nonce: &[u8], ip: Option<IpAddr>,
+    ) -> String {
+        self.inner
+            .create_auth_challenge(user, r1, r2, c, nonce, ip)
+            .await
+    }
+
+    async fn delete_auth_challenge(&self, id: &str) {
+        self.inner.delete_auth_challenge(id).await
+    }
+
+    async fn get_authentication_challenge(&self, id: &str) -> Option<AuthChallenge<T, S>> {
+        self.inner.get_authentication_challenge(id).await
+    }
+
+    async fn take_authentication_challenge(&self, id: &str) -> Option<AuthChallenge<T, S>> {
+        self.gate.pass().await;
+        self.inner.take_authentication_challenge(id).await
+    }
+}
@@ -0,0 +1,31 @@
+use common::{connect, free_port, ServerProcess};
+use pasta_curves::vesta::Point;
+use std::process::Command;
+use zk_pass::chaum_pedersen::constants::VESTA_GROUP_PARAMS;
+use zk_pass::client::decode_hex_group_params;
+use zk_pass::client::zkp_auth::health_response::ServingStatus;
+
+mod common;
+
+/// Spawns the server binary on the given port, serving the given elliptic curve.
+fn spawn_server(port: u16, curve: &str) -> ServerProcess {
+    ServerProcess::spawn(
+        Command::new(env!("CARGO_BIN_EXE_server"))
+            .args(["--host", "127.0.0.1", "--port", &port.to_string()])
+            .args(["--type", "elliptic_curve", "--curve", curve]),
+    )
+}
+
+#[tokio::test]
+async fn test_server_starts_with_vesta_curve() {
+    let port = free_port();
+    let _server = spawn_server(port, "vesta");
+    let mut client = connect(([127, 0, 0, 1], port).into()).await;
+
+    let health = client.health().await.unwrap();
+    let fetched = client.fetch_group_params().await.unwrap();
+
+    assert_eq!(health.status(), ServingStatus::Serving);
+    assert_eq!(health.protocol_type, "vesta");
+    assert_eq!(decode_hex_group_params::<Point>(&fetched).unwrap(), *VESTA_GROUP_PARAMS);
+}
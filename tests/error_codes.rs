@@ -0,0 +1,91 @@
+use common::connect;
+use curve25519_dalek::{RistrettoPoint, Scalar};
+use std::net::SocketAddr;
+use zk_pass::chaum_pedersen::constants::EC25519_GROUP_PARAMS;
+use zk_pass::chaum_pedersen::curve25519::Curve25519ChaumPedersen;
+use zk_pass::client::ZkPassError;
+use zk_pass::conversion::ByteConvertible;
+use zk_pass::rand::RandomGenerator;
+use zk_pass::service::ZkAuth;
+
+mod common;
+
+/// Starts an in-process Curve25519 server on a free local port.
+async fn start_server() -> SocketAddr {
+    let auth = ZkAuth::<Curve25519ChaumPedersen, RistrettoPoint, Scalar>::new(
+        EC25519_GROUP_PARAMS.to_owned(),
+    );
+    common::start_server(auth).await
+}
+
+/// Returns a random encoded point, valid as any of `y1`, `y2`, `r1` or `r2`.
+fn random_point() -> Vec<u8> {
+    RistrettoPoint::convert_to(&RistrettoPoint::generate_random().unwrap())
+}
+
+#[tokio::test]
+async fn test_user_not_found_code() {
+    let mut client = connect(start_server().await).await;
+    let status = client
+        .create_authentication_challenge("nobody".to_string(), random_point(), random_point())
+        .await
+        .unwrap_err();
+    assert_eq!(ZkPassError::from_status(&status), Some(ZkPassError::UserNotFound));
+}
+
+#[tokio::test]
+async fn test_challenge_not_found_code() {
+    let mut client = connect(start_server().await).await;
+    let s = Scalar::convert_to(&Scalar::generate_random().unwrap());
+    let status = client
+        .verify_authentication("no-such-challenge".to_string(), s, Vec::new())
+        .await
+        .unwrap_err();
+    assert_eq!(ZkPassError::from_status(&status), Some(ZkPassError::ChallengeNotFound));
+}
+
+#[tokio::test]
+async fn test_verification_failed_code() {
+    let mut client = connect(start_server().await).await;
+    client
+        .register("alice".to_string(), random_point(), random_point())
+        .await
+        .unwrap();
+    let (_, auth_id, nonce, _) = client
+        .create_authentication_challenge("alice".to_string(), random_point(), random_point())
+        .await
+        .unwrap();
+
+    let s = Scalar::convert_to(&Scalar::generate_random().unwrap());
+    let status = client
+        .verify_authentication(auth_id, s, nonce)
+        .await
+        .unwrap_err();
+    assert_eq!(ZkPassError::from_status(&status), Some(ZkPassError::VerificationFailed));
+}
+
+#[tokio::test]
+async fn test_invalid_parameter_code() {
+    let mut client = connect(start_server().await).await;
+    let status = client
+        .register("alice".to_string(), vec![1, 2, 3], random_point())
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), tonic::Code::InvalidArgument);
+    assert_eq!(ZkPassError::from_status(&status), Some(ZkPassError::InvalidParameter));
+}
+
+#[tokio::test]
+async fn test_error_without_specific_code() {
+    let mut client = connect(start_server().await).await;
+    client
+        .register("alice".to_string(), random_point(), random_point())
+        .await
+        .unwrap();
+    let status = client
+        .register("alice".to_string(), random_point(), random_point())
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), tonic::Code::AlreadyExists);
+    assert_eq!(ZkPassError::from_status(&status), Some(ZkPassError::Unspecified));
+}
@@ -0,0 +1,29 @@
+use curve25519_dalek::{RistrettoPoint, Scalar};
+use zk_pass::chaum_pedersen::constants::EC25519_GROUP_PARAMS;
+use zk_pass::chaum_pedersen::curve25519::Curve25519ChaumPedersen;
+use zk_pass::client::execute_protocol;
+use zk_pass::rand::RandomGenerator;
+use zk_pass::service::ZkAuth;
+
+mod common;
+
+#[tokio::test]
+async fn test_execute_protocol_returns_server_session() {
+    let auth = ZkAuth::<Curve25519ChaumPedersen, RistrettoPoint, Scalar>::new(
+        EC25519_GROUP_PARAMS.to_owned(),
+    );
+    let addr = common::start_server(auth).await;
+    let mut client = common::connect(addr).await;
+
+    let params = EC25519_GROUP_PARAMS.to_owned();
+    let x = Scalar::generate_random().unwrap();
+    let result =
+        execute_protocol::<Curve25519ChaumPedersen, _, _>(&params, &x, "alice", &mut client)
+            .await
+            .unwrap();
+
+    assert!(result.verified);
+    // The session ID is one the server issued to the user.
+    let (user, _) = client.validate_session(result.session_id).await.unwrap();
+    assert_eq!(user, "alice");
+}
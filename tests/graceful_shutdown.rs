@@ -0,0 +1,30 @@
+use common::{connect, free_port, ServerProcess};
+use std::process::Command;
+
+mod common;
+
+/// Spawns the server binary on the given port using the Curve25519 protocol.
+fn spawn_server(port: u16) -> ServerProcess {
+    ServerProcess::spawn(
+        Command::new(env!("CARGO_BIN_EXE_server"))
+            .args(["--host", "127.0.0.1", "--port", &port.to_string()])
+            .args(["--type", "elliptic_curve", "--curve", "ec25519"])
+            .args(["--shutdown-timeout-secs", "60"]),
+    )
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_sigterm_shuts_down_server() {
+    let port = free_port();
+    let mut server = spawn_server(port);
+    connect(([127, 0, 0, 1], port).into()).await;
+
+    let killed = Command::new("kill")
+        .args(["-TERM", &server.id().to_string()])
+        .status()
+        .unwrap();
+    assert!(killed.success());
+
+    let status = tokio::task::block_in_place(|| server.wait()).unwrap();
+    assert!(status.success());
+}
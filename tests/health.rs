@@ -0,0 +1,28 @@
+use common::connect;
+use curve25519_dalek::{RistrettoPoint, Scalar};
+use std::net::SocketAddr;
+use zk_pass::chaum_pedersen::constants::EC25519_GROUP_PARAMS;
+use zk_pass::chaum_pedersen::curve25519::Curve25519ChaumPedersen;
+use zk_pass::client::zkp_auth::health_response::ServingStatus;
+use zk_pass::service::ZkAuth;
+
+mod common;
+
+/// Starts an in-process Curve25519 server on a free local port.
+async fn start_server() -> SocketAddr {
+    let auth = ZkAuth::<Curve25519ChaumPedersen, RistrettoPoint, Scalar>::new(
+        EC25519_GROUP_PARAMS.to_owned(),
+    )
+    .with_protocol_type("ec25519");
+    common::start_server(auth).await
+}
+
+#[tokio::test]
+async fn test_health_serving_after_startup() {
+    let mut client = connect(start_server().await).await;
+    let health = client.health().await.unwrap();
+
+    assert_eq!(health.status(), ServingStatus::Serving);
+    assert_eq!(health.protocol_type, "ec25519");
+    assert_eq!(health.params_version, 0);
+}
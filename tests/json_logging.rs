@@ -0,0 +1,64 @@
+use common::{connect, free_port, ServerProcess};
+use curve25519_dalek::RistrettoPoint;
+use serde_json::Value;
+use std::io::Read;
+use std::process::{Command, Stdio};
+use std::time::Duration;
+use zk_pass::conversion::ByteConvertible;
+use zk_pass::rand::RandomGenerator;
+
+mod common;
+
+/// Spawns the server binary on the given port, writing JSON logs to a captured stdout.
+fn spawn_server(port: u16) -> ServerProcess {
+    ServerProcess::spawn(
+        Command::new(env!("CARGO_BIN_EXE_server"))
+            .args(["--host", "127.0.0.1", "--port", &port.to_string()])
+            .args(["--type", "elliptic_curve", "--curve", "ec25519"])
+            .args(["--log-format", "json", "--log-level", "info"])
+            .stdout(Stdio::piped()),
+    )
+}
+
+#[tokio::test]
+async fn test_registration_emits_json_log_line() {
+    let port = free_port();
+    let mut server = spawn_server(port);
+    let mut client = connect(([127, 0, 0, 1], port).into()).await;
+
+    let y = RistrettoPoint::convert_to(&RistrettoPoint::generate_random().unwrap());
+    client
+        .register("alice".to_string(), y.clone(), y)
+        .await
+        .unwrap();
+
+    // Give the server a moment to flush the log line before stopping it.
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    server.kill().unwrap();
+    server.wait().unwrap();
+    let mut stdout = String::new();
+    server
+        .stdout
+        .take()
+        .unwrap()
+        .read_to_string(&mut stdout)
+        .unwrap();
+
+    // The startup banner is plain text; every log line is a JSON object.
+    let events: Vec<Value> = stdout
+        .lines()
+        .filter(|line| line.starts_with('{'))
+        .map(|line| serde_json::from_str(line).expect("Log line is not valid JSON"))
+        .collect();
+    let registered = events
+        .iter()
+        .find(|event| {
+            event["message"]
+                .as_str()
+                .is_some_and(|message| message.contains("alice registered"))
+        })
+        .expect("No log line for the registration");
+    assert_eq!(registered["level"], "INFO");
+    assert_eq!(registered["target"], "zk_pass::service");
+    assert!(registered["timestamp"].is_string());
+}
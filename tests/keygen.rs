@@ -0,0 +1,55 @@
+use num_bigint::BigUint;
+use pasta_curves::pallas::Point as PallasPoint;
+use std::process::{Command, Output};
+use zk_pass::chaum_pedersen::GroupParams;
+
+/// Runs the keygen binary with the given arguments.
+fn keygen(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_keygen"))
+        .args(args)
+        .output()
+        .expect("Failed to run keygen")
+}
+
+#[test]
+fn test_keygen_discrete_log() {
+    let output = keygen(&["--type", "discrete_log", "--modulus-bits", "256", "--verify"]);
+    assert!(output.status.success());
+    let params: GroupParams<BigUint> = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(params.p.bits(), 256);
+    assert_eq!(params.p, &params.q * 2u8 + 1u8);
+    assert_eq!(params.validate(), Ok(()));
+    assert_eq!(params.check_primality(32), Ok(()));
+}
+
+#[test]
+fn test_keygen_elliptic_curve() {
+    let path = std::env::temp_dir().join(format!("keygen-{}.json", std::process::id()));
+    let output = keygen(&[
+        "--type",
+        "elliptic_curve",
+        "--security-bits",
+        "128",
+        "--verify",
+        "--output",
+        path.to_str().unwrap(),
+    ]);
+    assert!(output.status.success());
+    let json = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    let params: GroupParams<PallasPoint> = serde_json::from_str(&json).unwrap();
+    assert_eq!(params.validate(), Ok(()));
+
+    // `h` is derived from the domain, so another domain yields another `h`.
+    let output = keygen(&["--type", "elliptic_curve", "--domain", "other"]);
+    let other: GroupParams<PallasPoint> = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(other.g, params.g);
+    assert_ne!(other.h, params.h);
+}
+
+#[test]
+fn test_keygen_rejects_unsupported_curve_security() {
+    let output = keygen(&["--type", "elliptic_curve", "--security-bits", "256"]);
+    assert!(!output.status.success());
+    assert!(output.stdout.is_empty());
+}
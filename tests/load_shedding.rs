@@ -0,0 +1,79 @@
+use async_trait::async_trait;
+use curve25519_dalek::{RistrettoPoint, Scalar};
+use std::time::Duration;
+use tonic::Code;
+use zk_pass::challenge::ChallengeSource;
+use zk_pass::chaum_pedersen::constants::EC25519_GROUP_PARAMS;
+use zk_pass::chaum_pedersen::curve25519::Curve25519ChaumPedersen;
+use zk_pass::chaum_pedersen::GroupParams;
+use zk_pass::client::{AuthClientLib, ZkPassError};
+use zk_pass::conversion::ByteConvertible;
+use zk_pass::rand::RandomGenerator;
+use zk_pass::service::{ZkAuth, IN_FLIGHT_REQUESTS, REQUESTS_SHED_TOTAL};
+
+mod common;
+
+/// The number of challenge requests sent at once.
+const REQUESTS: usize = 500;
+
+/// A challenge source answering slowly, as if overloaded, so that requests pile up.
+struct SlowChallengeSource;
+
+#[async_trait]
+impl ChallengeSource<RistrettoPoint, Scalar> for SlowChallengeSource {
+    async fn challenge(
+        &self, _params: &GroupParams<RistrettoPoint>,
+    ) -> Result<Scalar, ZkPassError> {
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        Ok(Scalar::generate_random().unwrap())
+    }
+}
+
+/// Starts an in-process Curve25519 server handling at most 10 requests at a time on a free
+/// local port.
+async fn connect() -> AuthClientLib {
+    let auth = ZkAuth::<Curve25519ChaumPedersen, _, _>::new(EC25519_GROUP_PARAMS.to_owned())
+        .with_challenge_source(SlowChallengeSource)
+        .with_load_shedding(10);
+    common::connect(common::start_server(auth).await).await
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_overload_sheds_requests() {
+    let mut client = connect().await;
+    let y = RistrettoPoint::convert_to(&RistrettoPoint::generate_random().unwrap());
+    client
+        .register("alice".to_string(), y.clone(), y)
+        .await
+        .unwrap();
+
+    let shed_before = REQUESTS_SHED_TOTAL.get();
+    let requests: Vec<_> = (0..REQUESTS)
+        .map(|_| {
+            let mut client = client.clone();
+            tokio::spawn(async move {
+                let r = RistrettoPoint::convert_to(&RistrettoPoint::generate_random().unwrap());
+                client
+                    .create_authentication_challenge("alice".to_string(), r.clone(), r)
+                    .await
+            })
+        })
+        .collect();
+
+    let mut admitted = 0;
+    let mut shed = 0;
+    for request in requests {
+        match request.await.unwrap() {
+            Ok(_) => admitted += 1,
+            Err(status) => {
+                assert_eq!(status.code(), Code::ResourceExhausted, "{}", status);
+                shed += 1;
+            }
+        }
+    }
+
+    assert!(admitted >= 10, "only {} requests admitted", admitted);
+    assert!(shed > 0, "no request was shed");
+    assert_eq!(REQUESTS_SHED_TOTAL.get() - shed_before, shed);
+    assert_eq!(IN_FLIGHT_REQUESTS.get(), 0);
+}
@@ -0,0 +1,102 @@
+use common::connect;
+use curve25519_dalek::{RistrettoPoint, Scalar};
+use num_bigint::{BigUint, RandBigInt};
+use rand::rngs::OsRng;
+use std::net::SocketAddr;
+use zk_pass::chaum_pedersen::constants::{EC25519_GROUP_PARAMS, RFC5114_MODP_2048_256_BIT_PARAMS};
+use zk_pass::chaum_pedersen::curve25519::Curve25519ChaumPedersen;
+use zk_pass::chaum_pedersen::discretelog::DiscreteLogChaumPedersen;
+use zk_pass::client::{decode_group_params, execute_protocol};
+use zk_pass::rand::RandomGenerator;
+use zk_pass::service::{MultiGroupAuth, ZkAuth};
+
+mod common;
+
+const DL_GROUP: &str = "rfc5114_modp_2048_256";
+const EC_GROUP: &str = "ec25519";
+
+/// Starts an in-process server serving a discrete log and a Curve25519 group on a free local
+/// port.
+async fn start_server() -> SocketAddr {
+    let dl_auth = ZkAuth::<DiscreteLogChaumPedersen, BigUint, BigUint>::new(
+        RFC5114_MODP_2048_256_BIT_PARAMS.to_owned(),
+    )
+    .with_protocol_type(DL_GROUP);
+    let ec_auth = ZkAuth::<Curve25519ChaumPedersen, RistrettoPoint, Scalar>::new(
+        EC25519_GROUP_PARAMS.to_owned(),
+    )
+    .with_protocol_type(EC_GROUP);
+    let auth = MultiGroupAuth::new()
+        .with_group(DL_GROUP, dl_auth)
+        .with_group(EC_GROUP, ec_auth);
+    common::start_server(auth).await
+}
+
+#[tokio::test]
+async fn test_clients_on_different_groups_share_a_server() {
+    let addr = start_server().await;
+
+    // A discrete log client and a Curve25519 client, both registering as "alice".
+    let mut dl_client = connect(addr).await;
+    let (selected, encoded) = dl_client
+        .negotiate(vec![DL_GROUP.to_string()])
+        .await
+        .unwrap();
+    assert_eq!(selected, DL_GROUP);
+    let dl_params = decode_group_params::<BigUint>(&encoded).unwrap();
+    assert_eq!(dl_params, *RFC5114_MODP_2048_256_BIT_PARAMS);
+    let dl_x = OsRng.gen_biguint_below(&dl_params.q);
+
+    let mut ec_client = connect(addr).await;
+    let (selected, _) = ec_client
+        .negotiate(vec!["pallas".to_string(), EC_GROUP.to_string()])
+        .await
+        .unwrap();
+    assert_eq!(selected, EC_GROUP);
+    let ec_params = EC25519_GROUP_PARAMS.to_owned();
+    let ec_x = Scalar::generate_random().unwrap();
+
+    let (dl_result, ec_result) = tokio::join!(
+        execute_protocol::<DiscreteLogChaumPedersen, _, _>(
+            &dl_params,
+            &dl_x,
+            "alice",
+            &mut dl_client
+        ),
+        execute_protocol::<Curve25519ChaumPedersen, _, _>(
+            &ec_params,
+            &ec_x,
+            "alice",
+            &mut ec_client
+        ),
+    );
+    assert!(dl_result.unwrap().verified);
+    assert!(ec_result.unwrap().verified);
+}
+
+#[tokio::test]
+async fn test_requests_without_group_use_first_group() {
+    let addr = start_server().await;
+    let mut client = connect(addr).await;
+    let x = OsRng.gen_biguint_below(&RFC5114_MODP_2048_256_BIT_PARAMS.q);
+    execute_protocol::<DiscreteLogChaumPedersen, _, _>(
+        &RFC5114_MODP_2048_256_BIT_PARAMS,
+        &x,
+        "bob",
+        &mut client,
+    )
+    .await
+    .unwrap();
+
+    let mut client = connect(addr).await.with_group("jubjub");
+    let status = client
+        .register("bob".to_string(), vec![1], vec![2])
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), tonic::Code::InvalidArgument);
+    let status = client
+        .negotiate(vec!["jubjub".to_string()])
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), tonic::Code::FailedPrecondition);
+}
@@ -0,0 +1,119 @@
+use common::connect;
+use curve25519_dalek::{RistrettoPoint, Scalar};
+use std::net::SocketAddr;
+use zk_pass::chaum_pedersen::constants::EC25519_GROUP_PARAMS;
+use zk_pass::chaum_pedersen::curve25519::Curve25519ChaumPedersen;
+use zk_pass::client::{execute_mutual_protocol, execute_protocol, AuthClientLib};
+use zk_pass::conversion::ByteConvertible;
+use zk_pass::rand::RandomGenerator;
+use zk_pass::service::ZkAuth;
+
+mod common;
+
+/// Starts an in-process Curve25519 server holding `server_secret` on a free local port.
+async fn start_server(server_secret: Scalar) -> SocketAddr {
+    let auth = ZkAuth::<Curve25519ChaumPedersen, RistrettoPoint, Scalar>::new(
+        EC25519_GROUP_PARAMS.to_owned(),
+    )
+    .with_server_secret(server_secret);
+    common::start_server(auth).await
+}
+
+/// Connects to the server and registers `user` with the secret `x`.
+async fn connect_and_register(addr: SocketAddr, user: &str, x: &Scalar) -> AuthClientLib {
+    let params = EC25519_GROUP_PARAMS.to_owned();
+    let mut client = connect(addr).await;
+    client
+        .register(
+            user.to_string(),
+            RistrettoPoint::convert_to(&(params.g * x)),
+            RistrettoPoint::convert_to(&(params.h * x)),
+        )
+        .await
+        .unwrap();
+    client
+}
+
+#[tokio::test]
+async fn test_mutual_authentication() {
+    let params = EC25519_GROUP_PARAMS.to_owned();
+    let server_secret = Scalar::generate_random().unwrap();
+    let server_y = (params.g * server_secret, params.h * server_secret);
+    let addr = start_server(server_secret).await;
+
+    let x = Scalar::generate_random().unwrap();
+    let mut client = connect_and_register(addr, "alice", &x).await;
+    let session_id = execute_mutual_protocol::<Curve25519ChaumPedersen, _, _>(
+        &params,
+        &x,
+        "alice",
+        &server_y,
+        &mut client,
+    )
+    .await
+    .unwrap();
+    assert!(!session_id.is_empty());
+}
+
+#[tokio::test]
+async fn test_mutual_authentication_rejects_wrong_server_secret() {
+    let params = EC25519_GROUP_PARAMS.to_owned();
+    let trusted_secret = Scalar::generate_random().unwrap();
+    let server_y = (params.g * trusted_secret, params.h * trusted_secret);
+
+    // A rogue server that does not know the trusted secret.
+    let addr = start_server(Scalar::generate_random().unwrap()).await;
+
+    let x = Scalar::generate_random().unwrap();
+    let mut client = connect_and_register(addr, "alice", &x).await;
+    let result = execute_mutual_protocol::<Curve25519ChaumPedersen, _, _>(
+        &params,
+        &x,
+        "alice",
+        &server_y,
+        &mut client,
+    )
+    .await;
+    assert_eq!(result.unwrap_err().to_string(), "Server failed to prove its identity");
+}
+
+#[tokio::test]
+async fn test_server_identity_verified_before_registration() {
+    let params = EC25519_GROUP_PARAMS.to_owned();
+    let server_secret = Scalar::generate_random().unwrap();
+    let addr = start_server(server_secret).await;
+
+    let mut client = connect(addr).await.with_server_verification(
+        RistrettoPoint::convert_to(&(params.g * server_secret)),
+        RistrettoPoint::convert_to(&(params.h * server_secret)),
+    );
+    let x = Scalar::generate_random().unwrap();
+    execute_protocol::<Curve25519ChaumPedersen, _, _>(&params, &x, "alice", &mut client)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_impostor_server_rejected_before_registration() {
+    let params = EC25519_GROUP_PARAMS.to_owned();
+    let trusted_secret = Scalar::generate_random().unwrap();
+
+    // A rogue server that does not know the trusted secret.
+    let addr = start_server(Scalar::generate_random().unwrap()).await;
+
+    let mut client = connect(addr).await.with_server_verification(
+        RistrettoPoint::convert_to(&(params.g * trusted_secret)),
+        RistrettoPoint::convert_to(&(params.h * trusted_secret)),
+    );
+    let x = Scalar::generate_random().unwrap();
+    let result =
+        execute_protocol::<Curve25519ChaumPedersen, _, _>(&params, &x, "alice", &mut client).await;
+    assert_eq!(result.unwrap_err().to_string(), "Server failed to prove its identity");
+
+    // The client never sent its commitment to the impostor.
+    let status = client
+        .get_user_metadata("alice".to_string())
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), tonic::Code::NotFound);
+}
@@ -0,0 +1,48 @@
+use common::connect;
+use pasta_curves::pallas::{Point, Scalar};
+use std::net::SocketAddr;
+use zk_pass::chaum_pedersen::constants::PALLAS_GROUP_PARAMS;
+use zk_pass::chaum_pedersen::pallas::PallasCurveChaumPedersen;
+use zk_pass::client::{decode_group_params, decode_hex_group_params};
+use zk_pass::service::ZkAuth;
+
+mod common;
+
+/// Starts an in-process Pallas server on a free local port.
+async fn start_server() -> SocketAddr {
+    let auth =
+        ZkAuth::<PallasCurveChaumPedersen, Point, Scalar>::new(PALLAS_GROUP_PARAMS.to_owned())
+            .with_protocol_type("pallas");
+    common::start_server(auth).await
+}
+
+#[tokio::test]
+async fn test_negotiates_supported_type() {
+    let mut client = connect(start_server().await).await;
+    let supported = vec!["ec25519".to_string(), "pallas".to_string(), "vesta".to_string()];
+    let (selected, encoded) = client.negotiate(supported).await.unwrap();
+
+    assert_eq!(selected, "pallas");
+    assert_eq!(decode_group_params::<Point>(&encoded).unwrap(), *PALLAS_GROUP_PARAMS);
+}
+
+#[tokio::test]
+async fn test_negotiation_without_overlap_fails() {
+    let mut client = connect(start_server().await).await;
+    let status = client
+        .negotiate(vec!["jubjub".to_string()])
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), tonic::Code::FailedPrecondition);
+}
+
+#[tokio::test]
+async fn test_fetches_and_caches_group_params() {
+    let mut client = connect(start_server().await).await;
+    let fetched = client.fetch_group_params().await.unwrap();
+
+    assert_eq!(fetched.protocol_type, "pallas");
+    assert_eq!(fetched.params_version, 0);
+    assert_eq!(decode_hex_group_params::<Point>(&fetched).unwrap(), *PALLAS_GROUP_PARAMS);
+    assert_eq!(client.fetch_group_params().await.unwrap(), fetched);
+}
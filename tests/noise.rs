@@ -0,0 +1,70 @@
+use curve25519_dalek::{RistrettoPoint, Scalar};
+use std::net::SocketAddr;
+use tokio::net::TcpListener;
+use tonic::transport::Server;
+use zk_pass::chaum_pedersen::constants::EC25519_GROUP_PARAMS;
+use zk_pass::chaum_pedersen::curve25519::Curve25519ChaumPedersen;
+use zk_pass::noise::{generate_keypair, noise_incoming, NoiseAuthClient};
+use zk_pass::rand::RandomGenerator;
+use zk_pass::service::zkp_auth::auth_server::AuthServer;
+use zk_pass::service::ZkAuth;
+
+/// Starts an in-process Curve25519 server accepting Noise connections on a free local port.
+///
+/// # Returns
+/// The server's address and static public key.
+async fn start_server() -> (SocketAddr, Vec<u8>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let keypair = generate_keypair().unwrap();
+    let public_key = keypair.public.clone();
+    let auth = ZkAuth::<Curve25519ChaumPedersen, RistrettoPoint, Scalar>::new(
+        EC25519_GROUP_PARAMS.to_owned(),
+    );
+    tokio::spawn(
+        Server::builder()
+            .add_service(AuthServer::new(auth))
+            .serve_with_incoming(noise_incoming(listener, keypair)),
+    );
+    (addr, public_key)
+}
+
+#[tokio::test]
+async fn test_authentication_over_noise_channel() {
+    let (addr, server_key) = start_server().await;
+    let client_keypair = generate_keypair().unwrap();
+    let mut client =
+        NoiseAuthClient::connect(format!("http://{}", addr), &server_key, &client_keypair)
+            .await
+            .unwrap();
+
+    let params = EC25519_GROUP_PARAMS.to_owned();
+    let x = Scalar::generate_random().unwrap();
+    let result = client
+        .authenticate::<Curve25519ChaumPedersen, _, _>(&params, &x, "alice")
+        .await
+        .unwrap();
+    assert!(result.verified);
+    assert!(!result.session_id.is_empty());
+
+    // The session is valid when checked over the same channel.
+    let (user, _) = client
+        .client()
+        .validate_session(result.session_id)
+        .await
+        .unwrap();
+    assert_eq!(user, "alice");
+}
+
+#[tokio::test]
+async fn test_unexpected_server_key_rejected() {
+    let (addr, _) = start_server().await;
+    let impostor_key = generate_keypair().unwrap().public;
+    let client_keypair = generate_keypair().unwrap();
+    let result =
+        NoiseAuthClient::connect(format!("http://{}", addr), &impostor_key, &client_keypair).await;
+    let error = result
+        .err()
+        .expect("handshake with the wrong key succeeded");
+    assert!(format!("{:?}", error).contains("UnexpectedServerKey"), "{:?}", error);
+}
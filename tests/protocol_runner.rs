@@ -0,0 +1,49 @@
+use common::connect;
+use curve25519_dalek::{RistrettoPoint, Scalar};
+use std::net::SocketAddr;
+use zk_pass::chaum_pedersen::constants::EC25519_GROUP_PARAMS;
+use zk_pass::chaum_pedersen::curve25519::Curve25519ChaumPedersen;
+use zk_pass::chaum_pedersen::runner::{InMemoryTransport, ProtocolRunner, Transport};
+use zk_pass::client::GrpcTransport;
+use zk_pass::rand::RandomGenerator;
+use zk_pass::service::ZkAuth;
+
+mod common;
+
+/// Starts an in-process Curve25519 server on a free local port.
+async fn start_server() -> SocketAddr {
+    let auth = ZkAuth::<Curve25519ChaumPedersen, RistrettoPoint, Scalar>::new(
+        EC25519_GROUP_PARAMS.to_owned(),
+    );
+    common::start_server(auth).await
+}
+
+/// Authenticates `user` with a fresh secret over the transport, returning whether the verifier
+/// accepted the proof and the prover's own check passed.
+async fn authenticate<Tr: Transport<RistrettoPoint, Scalar>>(
+    transport: &mut Tr, user: &str,
+) -> bool {
+    let params = EC25519_GROUP_PARAMS.to_owned();
+    let x = Scalar::generate_random().unwrap();
+    ProtocolRunner::<Curve25519ChaumPedersen>::new(&params)
+        .run(transport, user, &x)
+        .await
+        .is_ok_and(|outcome| outcome.verified)
+}
+
+#[tokio::test]
+async fn test_runner_over_in_memory_transport() {
+    let mut transport =
+        InMemoryTransport::<Curve25519ChaumPedersen>::new(EC25519_GROUP_PARAMS.to_owned());
+    assert!(authenticate(&mut transport, "alice").await);
+}
+
+#[tokio::test]
+async fn test_runner_over_grpc_transport() {
+    let params = EC25519_GROUP_PARAMS.to_owned();
+    let mut client = connect(start_server().await).await;
+    assert!(authenticate(&mut GrpcTransport::new(&mut client, &params), "alice").await);
+
+    // Registering the same user again with a new secret is refused by the server.
+    assert!(!authenticate(&mut GrpcTransport::new(&mut client, &params), "alice").await);
+}
@@ -0,0 +1,105 @@
+use common::connect;
+use curve25519_dalek::{RistrettoPoint, Scalar};
+use std::net::SocketAddr;
+use tonic::transport::Server;
+use zk_pass::chaum_pedersen::constants::EC25519_GROUP_PARAMS;
+use zk_pass::chaum_pedersen::curve25519::Curve25519ChaumPedersen;
+use zk_pass::client::zkp_auth::RegisterRequest;
+use zk_pass::conversion::ByteConvertible;
+use zk_pass::rand::RandomGenerator;
+use zk_pass::service::zkp_auth::auth_server::AuthServer;
+use zk_pass::service::{RegisterApiKey, ZkAuth};
+
+mod common;
+
+const API_KEY: &str = "s3cr3t-api-key";
+
+/// Starts an in-process Curve25519 server on a free local port, gating registration with
+/// [`API_KEY`].
+async fn start_server() -> SocketAddr {
+    let (addr, incoming) = common::listen().await;
+    let auth = ZkAuth::<Curve25519ChaumPedersen, RistrettoPoint, Scalar>::new(
+        EC25519_GROUP_PARAMS.to_owned(),
+    );
+    tokio::spawn(
+        Server::builder()
+            .add_service(RegisterApiKey::new(AuthServer::new(auth), API_KEY.to_string()))
+            .serve_with_incoming(incoming),
+    );
+    addr
+}
+
+/// Returns a random encoded point, valid as either of `y1` and `y2`.
+fn random_point() -> Vec<u8> {
+    RistrettoPoint::convert_to(&RistrettoPoint::generate_random().unwrap())
+}
+
+#[tokio::test]
+async fn test_registration_with_api_key() {
+    let addr = start_server().await;
+    let mut client = connect(addr).await.with_api_key(API_KEY);
+    client
+        .register("alice".to_string(), random_point(), random_point())
+        .await
+        .unwrap();
+
+    let results = client
+        .register_batch(vec![RegisterRequest {
+            user: "bob".to_string(),
+            y1: random_point(),
+            y2: random_point(),
+            ..Default::default()
+        }])
+        .await
+        .unwrap();
+    assert_eq!(results.len(), 1);
+}
+
+#[tokio::test]
+async fn test_registration_without_api_key() {
+    let addr = start_server().await;
+    let mut client = connect(addr).await;
+    let status = client
+        .register("alice".to_string(), random_point(), random_point())
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), tonic::Code::Unauthenticated);
+
+    let status = client
+        .register_batch(vec![RegisterRequest {
+            user: "bob".to_string(),
+            y1: random_point(),
+            y2: random_point(),
+            ..Default::default()
+        }])
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), tonic::Code::Unauthenticated);
+}
+
+#[tokio::test]
+async fn test_registration_with_wrong_api_key() {
+    let addr = start_server().await;
+    let mut client = connect(addr).await.with_api_key("wrong-key");
+    let status = client
+        .register("alice".to_string(), random_point(), random_point())
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), tonic::Code::Unauthenticated);
+}
+
+#[tokio::test]
+async fn test_authentication_does_not_require_api_key() {
+    let addr = start_server().await;
+    let mut client = connect(addr).await.with_api_key(API_KEY);
+    client
+        .register("alice".to_string(), random_point(), random_point())
+        .await
+        .unwrap();
+
+    let mut client = connect(addr).await;
+    client
+        .create_authentication_challenge("alice".to_string(), random_point(), random_point())
+        .await
+        .unwrap();
+}
@@ -0,0 +1,71 @@
+use common::{Gate, GatedDao};
+use curve25519_dalek::Scalar;
+use std::time::Duration;
+use tokio::time::timeout;
+use tonic::transport::Server;
+use zk_pass::chaum_pedersen::constants::EC25519_GROUP_PARAMS;
+use zk_pass::chaum_pedersen::curve25519::Curve25519ChaumPedersen;
+use zk_pass::client::{execute_protocol, AuthClientLib};
+use zk_pass::rand::RandomGenerator;
+use zk_pass::service::zkp_auth::auth_server::AuthServer;
+use zk_pass::service::{rpc_timeout_layer, ZkAuth};
+
+mod common;
+
+/// How long a test waits for the server to cancel a held authentication before failing.
+const CANCEL_WAIT: Duration = Duration::from_secs(10);
+
+/// Starts an in-process Curve25519 server whose challenge lookups are held at `gate`, failing
+/// requests after `rpc_timeout` if set.
+async fn connect(gate: &Gate, rpc_timeout: Option<Duration>) -> AuthClientLib {
+    let auth = ZkAuth::<Curve25519ChaumPedersen, _, _, _>::with_dao(
+        EC25519_GROUP_PARAMS.to_owned(),
+        GatedDao::new(gate.clone()),
+    );
+    let (addr, incoming) = common::listen().await;
+    let server = Server::builder()
+        .layer(tower::util::option_layer(rpc_timeout.map(rpc_timeout_layer)))
+        .add_service(AuthServer::new(auth))
+        .serve_with_incoming(incoming);
+    tokio::spawn(server);
+    common::connect(addr).await
+}
+
+#[tokio::test]
+async fn test_timed_out_verification_is_cancelled() {
+    let params = EC25519_GROUP_PARAMS.to_owned();
+    let gate = Gate::default();
+    let mut client = connect(&gate, Some(Duration::from_millis(100))).await;
+
+    // The verification is held until the RPC timeout fails the request and drops it.
+    let x = Scalar::generate_random().unwrap();
+    let error =
+        execute_protocol::<Curve25519ChaumPedersen, _, _>(&params, &x, "alice", &mut client)
+            .await
+            .unwrap_err();
+    assert!(error.to_string().contains("Request timed out"), "{}", error);
+    timeout(CANCEL_WAIT, gate.cancelled())
+        .await
+        .expect("Server did not cancel the verification");
+}
+
+#[tokio::test]
+async fn test_abandoned_verification_is_cancelled() {
+    let params = EC25519_GROUP_PARAMS.to_owned();
+    let gate = Gate::default();
+    let mut client = connect(&gate, None).await;
+
+    // The client goes away while the verification is held.
+    let x = Scalar::generate_random().unwrap();
+    let protocol = tokio::spawn(async move {
+        execute_protocol::<Curve25519ChaumPedersen, _, _>(&params, &x, "bob", &mut client)
+            .await
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    });
+    gate.entered().await;
+    protocol.abort();
+    timeout(CANCEL_WAIT, gate.cancelled())
+        .await
+        .expect("Server did not cancel the verification");
+}
@@ -0,0 +1,60 @@
+use curve25519_dalek::{RistrettoPoint, Scalar};
+use std::net::SocketAddr;
+use zk_pass::chaum_pedersen::constants::EC25519_GROUP_PARAMS;
+use zk_pass::chaum_pedersen::curve25519::Curve25519ChaumPedersen;
+use zk_pass::chaum_pedersen::transcript::derive_nonce_challenge;
+use zk_pass::chaum_pedersen::ChaumPedersen;
+use zk_pass::conversion::ByteConvertible;
+use zk_pass::rand::RandomGenerator;
+use zk_pass::repository::seed::load_seed_users;
+use zk_pass::service::ZkAuth;
+
+mod common;
+
+/// Starts an in-process Curve25519 server preloaded from the seed file at `path`.
+async fn start_seeded_server(path: &std::path::Path) -> SocketAddr {
+    let auth = ZkAuth::<Curve25519ChaumPedersen, RistrettoPoint, Scalar>::new(
+        EC25519_GROUP_PARAMS.to_owned(),
+    );
+    auth.seed_users(load_seed_users(path).unwrap())
+        .await
+        .unwrap();
+    common::start_server(auth).await
+}
+
+#[tokio::test]
+async fn test_authenticate_as_seeded_user() {
+    let params = EC25519_GROUP_PARAMS.to_owned();
+    let x = Scalar::generate_random().unwrap();
+    let (y1, y2) = (params.g * x, params.h * x);
+
+    let path = std::env::temp_dir().join(format!("zk_pass_seed_{}.json", std::process::id()));
+    let json = format!(
+        r#"[{{"username": "alice", "y1_hex": "{}", "y2_hex": "{}"}}]"#,
+        RistrettoPoint::to_hex(&y1),
+        RistrettoPoint::to_hex(&y2)
+    );
+    std::fs::write(&path, json).unwrap();
+    let addr = start_seeded_server(&path).await;
+    std::fs::remove_file(&path).unwrap();
+
+    let mut client = common::connect(addr).await;
+
+    // Authenticates without registering first.
+    let ((_, _, r1, r2), k) = Curve25519ChaumPedersen::commitment(&params, &x);
+    let (c, auth_id, nonce, _) = client
+        .create_authentication_challenge(
+            "alice".to_string(),
+            RistrettoPoint::convert_to(&r1),
+            RistrettoPoint::convert_to(&r2),
+        )
+        .await
+        .unwrap();
+    let c = derive_nonce_challenge(&params, &nonce, &Scalar::convert_from(&c).unwrap());
+    let s = Curve25519ChaumPedersen::challenge_response(&params, &k, &c, &x);
+    let session_id = client
+        .verify_authentication(auth_id, Scalar::convert_to(&s), nonce)
+        .await
+        .unwrap();
+    assert!(!session_id.is_empty());
+}
@@ -0,0 +1,108 @@
+use common::connect;
+use curve25519_dalek::{RistrettoPoint, Scalar};
+use ed25519_dalek::SigningKey;
+use rand_core::OsRng;
+use std::net::SocketAddr;
+use zk_pass::chaum_pedersen::constants::EC25519_GROUP_PARAMS;
+use zk_pass::chaum_pedersen::curve25519::Curve25519ChaumPedersen;
+use zk_pass::client::zkp_auth::SessionToken;
+use zk_pass::client::{execute_protocol, verify_session_token, ZkPassError};
+use zk_pass::rand::RandomGenerator;
+use zk_pass::service::ZkAuth;
+use zk_pass::token::SessionTokenFormat;
+
+mod common;
+
+type Ec25519Auth = ZkAuth<Curve25519ChaumPedersen, RistrettoPoint, Scalar>;
+
+/// Starts an in-process Curve25519 server on a free local port, signing sessions with `key`.
+async fn start_server(key: Option<SigningKey>) -> SocketAddr {
+    let mut auth = Ec25519Auth::new(EC25519_GROUP_PARAMS.to_owned());
+    if let Some(key) = key {
+        auth = auth.with_signing_key(key);
+    }
+    common::start_server(auth).await
+}
+
+/// Authenticates `user` against the server, returning the session token it issued.
+async fn authenticate(addr: SocketAddr, user: &str) -> Option<SessionToken> {
+    let params = EC25519_GROUP_PARAMS.to_owned();
+    let x = Scalar::generate_random().unwrap();
+    let mut client = connect(addr).await;
+    let result = execute_protocol::<Curve25519ChaumPedersen, _, _>(&params, &x, user, &mut client)
+        .await
+        .unwrap();
+    if let Some(token) = &result.session_token {
+        assert_eq!(token.session_id, result.session_id);
+    }
+    result.session_token
+}
+
+#[tokio::test]
+async fn test_valid_session_token() {
+    let key = SigningKey::generate(&mut OsRng);
+    let addr = start_server(Some(key.clone())).await;
+
+    let token = authenticate(addr, "alice").await.unwrap();
+    assert_eq!(token.user, "alice");
+    assert!(verify_session_token(&token, &key.verifying_key()));
+}
+
+#[tokio::test]
+async fn test_tampered_session_token() {
+    let key = SigningKey::generate(&mut OsRng);
+    let addr = start_server(Some(key.clone())).await;
+    let token = authenticate(addr, "alice").await.unwrap();
+    let pubkey = key.verifying_key();
+
+    let mut other_user = token.clone();
+    other_user.user = "mallory".to_string();
+    assert!(!verify_session_token(&other_user, &pubkey));
+
+    let mut extended = token.clone();
+    extended.expiry += 3600;
+    assert!(!verify_session_token(&extended, &pubkey));
+
+    let mut truncated = token.clone();
+    truncated.signature.pop();
+    assert!(!verify_session_token(&truncated, &pubkey));
+
+    let other_key = SigningKey::generate(&mut OsRng);
+    assert!(!verify_session_token(&token, &other_key.verifying_key()));
+}
+
+#[tokio::test]
+async fn test_no_session_token_without_signing_key() {
+    let addr = start_server(None).await;
+    assert!(authenticate(addr, "alice").await.is_none());
+}
+
+#[tokio::test]
+async fn test_paseto_session_validated_by_server() {
+    let format = SessionTokenFormat::paseto(&[7; 32]).unwrap();
+    let addr = common::start_server(
+        Ec25519Auth::new(EC25519_GROUP_PARAMS.to_owned()).with_session_token_format(format),
+    )
+    .await;
+    let params = EC25519_GROUP_PARAMS.to_owned();
+    let x = Scalar::generate_random().unwrap();
+    let mut client = connect(addr).await;
+    let result =
+        execute_protocol::<Curve25519ChaumPedersen, _, _>(&params, &x, "alice", &mut client)
+            .await
+            .unwrap();
+    assert!(result.session_id.starts_with("v4.local."));
+
+    let (user, expiry) = client
+        .validate_session(result.session_id.clone())
+        .await
+        .unwrap();
+    assert_eq!(user, "alice");
+    assert!(expiry > 0);
+
+    let status = client
+        .validate_session(result.session_id.replace("v4.local.", "v4.local.x"))
+        .await
+        .unwrap_err();
+    assert_eq!(ZkPassError::from_status(&status), Some(ZkPassError::InvalidSession));
+}